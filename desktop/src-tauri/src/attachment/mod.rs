@@ -0,0 +1,11 @@
+//! Attachment management module for files attached through file fields.
+
+mod model;
+mod registry;
+mod service;
+pub mod storage;
+
+pub use model::*;
+pub use registry::AttachmentRegistry;
+pub use service::AttachmentService;
+pub use storage::AttachmentStorage;
@@ -0,0 +1,51 @@
+//! Attachment data transfer objects.
+
+use serde::{Deserialize, Serialize};
+
+/// Value stored in metadata for each file attached to a file field.
+///
+/// File fields hold an ordered list of these.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// SHA-256 hex digest of the file contents
+    pub sha256: String,
+    /// Path relative to the attachments directory (`ab/abcdef....pdf`)
+    pub path: String,
+    /// File name the attachment was added with
+    pub filename: String,
+    /// MIME type guessed from the file extension
+    pub mime_type: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
+impl Attachment {
+    /// Whether the path is the storage path of the digest, so a value can
+    /// only ever point at a file stored for its own content.
+    pub fn has_storage_path(&self) -> bool {
+        let is_digest = self.sha256.len() == 64
+            && self
+                .sha256
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        if !is_digest {
+            return false;
+        }
+
+        let prefix = format!("{}/{}.", &self.sha256[..2], self.sha256);
+        self.path
+            .strip_prefix(&prefix)
+            .is_some_and(|ext| !ext.is_empty() && ext.bytes().all(|b| b.is_ascii_alphanumeric()))
+    }
+}
+
+/// Result of writing a file into attachment storage.
+#[derive(Debug, Clone)]
+pub struct StoredAttachment {
+    /// SHA-256 hex digest of the file contents
+    pub hash: String,
+    /// Path relative to the attachments directory
+    pub relative_path: String,
+    /// File size in bytes
+    pub size: u64,
+}
@@ -0,0 +1,170 @@
+//! Reference counting for stored attachment files.
+//!
+//! Every stored file has a row in `attachment_blobs` counting how many file
+//! field values hold it. References are taken and dropped in the same
+//! transaction as the metadata holding them, and a file is removed from
+//! disk once its last reference is gone and that transaction has committed.
+
+use std::collections::HashMap;
+
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, EntityTrait, QueryFilter, QuerySelect, Statement,
+};
+use serde_json::{Map, Value};
+
+use crate::core::{AppResult, now_formatted};
+use crate::entities::attachment_blob::Entity as AttachmentBlob;
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::field::FieldType;
+
+use super::{Attachment, AttachmentStorage};
+
+/// Registry tracking references to stored attachment files.
+pub struct AttachmentRegistry;
+
+impl AttachmentRegistry {
+    /// Records a reference to an attachment's file, registering the file
+    /// the first time it is referenced.
+    pub async fn acquire<C: ConnectionTrait>(conn: &C, attachment: &Attachment) -> AppResult<()> {
+        conn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            r#"
+            INSERT INTO attachment_blobs (hash, path, size, ref_count, created_at)
+            VALUES (?, ?, ?, 1, ?)
+            ON CONFLICT(hash) DO UPDATE SET ref_count = ref_count + 1
+            "#,
+            [
+                attachment.sha256.clone().into(),
+                attachment.path.clone().into(),
+                (attachment.size as i64).into(),
+                now_formatted().into(),
+            ],
+        ))
+        .await?;
+
+        log::debug!("Acquired attachment reference: {}", attachment.path);
+        Ok(())
+    }
+
+    /// Drops a reference to an attachment's file.
+    ///
+    /// Returns whether it was the last one, in which case the file is to be
+    /// deleted once the change is committed. Unregistered files are left
+    /// alone.
+    pub async fn release<C: ConnectionTrait>(conn: &C, attachment: &Attachment) -> AppResult<bool> {
+        let Some(blob) = AttachmentBlob::find_by_id(attachment.sha256.clone())
+            .one(conn)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        if blob.ref_count > 1 {
+            conn.execute(Statement::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                "UPDATE attachment_blobs SET ref_count = ref_count - 1 WHERE hash = ?",
+                [blob.hash.into()],
+            ))
+            .await?;
+            return Ok(false);
+        }
+
+        AttachmentBlob::delete_by_id(blob.hash).exec(conn).await?;
+        Ok(true)
+    }
+
+    /// Takes and drops references as an entry's metadata changes from
+    /// `before` to `after`, which are `None` for an entry being created or
+    /// deleted. Only values of the vault's file fields count.
+    ///
+    /// Returns the paths of files left without references.
+    pub async fn sync_entry<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> AppResult<Vec<String>> {
+        let file_fields: Vec<i32> = FieldDefinition::find()
+            .select_only()
+            .column(field_definition::Column::Id)
+            .filter(field_definition::Column::VaultId.eq(vault_id))
+            .filter(field_definition::Column::FieldType.eq(FieldType::File.as_str()))
+            .into_tuple()
+            .all(conn)
+            .await?;
+        if file_fields.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Net change in references per file
+        let mut changes: HashMap<String, (Attachment, i32)> = HashMap::new();
+        for (metadata, delta) in [(before, -1), (after, 1)] {
+            for attachment in Self::attachments_in(metadata, &file_fields) {
+                changes
+                    .entry(attachment.sha256.clone())
+                    .or_insert((attachment, 0))
+                    .1 += delta;
+            }
+        }
+
+        let mut unreferenced = Vec::new();
+        for (attachment, delta) in changes.into_values() {
+            for _ in 0..delta {
+                Self::acquire(conn, &attachment).await?;
+            }
+            for _ in delta..0 {
+                if Self::release(conn, &attachment).await? {
+                    unreferenced.push(attachment.path.clone());
+                }
+            }
+        }
+
+        Ok(unreferenced)
+    }
+
+    /// Drops the references held by values of a file field, such as the
+    /// values of a deleted field.
+    ///
+    /// Returns the paths of files left without references.
+    pub async fn release_values<C: ConnectionTrait>(
+        conn: &C,
+        values: &[Value],
+    ) -> AppResult<Vec<String>> {
+        let mut unreferenced = Vec::new();
+        for value in values {
+            let attachments: Vec<Attachment> =
+                serde_json::from_value(value.clone()).unwrap_or_default();
+            for attachment in attachments {
+                if Self::release(conn, &attachment).await? {
+                    unreferenced.push(attachment.path);
+                }
+            }
+        }
+
+        Ok(unreferenced)
+    }
+
+    /// Deletes files left without references. Failures are logged, as the
+    /// change that dropped the references has already been committed.
+    pub fn delete_files(storage: &AttachmentStorage, paths: &[String]) {
+        for path in paths {
+            if let Err(e) = storage.delete_file(path) {
+                log::warn!("Failed to delete attachment {}: {}", path, e);
+            }
+        }
+    }
+
+    /// Attachments held by the file fields of entry metadata.
+    fn attachments_in(metadata: Option<&str>, file_fields: &[i32]) -> Vec<Attachment> {
+        let metadata: Map<String, Value> = metadata
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+
+        file_fields
+            .iter()
+            .filter_map(|id| metadata.get(&id.to_string()))
+            .filter_map(|value| serde_json::from_value::<Vec<Attachment>>(value.clone()).ok())
+            .flatten()
+            .collect()
+    }
+}
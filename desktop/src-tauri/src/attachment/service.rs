@@ -0,0 +1,516 @@
+//! Attachment service for files attached to entries through file fields.
+//!
+//! A file field holds a list of attachments in entry metadata. Stored files
+//! are shared by content, so a file is only removed from disk once the
+//! [`AttachmentRegistry`] holds no reference to it anymore.
+
+use std::path::{Path, PathBuf};
+
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Set, TransactionTrait,
+};
+use serde_json::{Map, Value};
+
+use crate::core::{AppError, AppResult, now_formatted};
+use crate::entities::entry::{self, ActiveModel as EntryActiveModel, Entity as Entry};
+use crate::entry::{EntryDto, EntryService};
+use crate::field::{FieldDefinitionDto, FieldService, FieldType};
+
+use super::{Attachment, AttachmentRegistry, AttachmentStorage};
+
+/// Service for adding, locating and removing attachments.
+pub struct AttachmentService;
+
+impl AttachmentService {
+    /// Copies a file into storage and appends it to an entry's file field.
+    ///
+    /// The file must satisfy the field's size limit and extension allow-list.
+    pub async fn add(
+        conn: &DatabaseConnection,
+        storage: &AttachmentStorage,
+        entry_id: i32,
+        field_id: i32,
+        source_path: &Path,
+    ) -> AppResult<EntryDto> {
+        let (entry, field, mut metadata) = Self::load(conn, entry_id, field_id).await?;
+        let options = field.options.clone().unwrap_or_default();
+
+        let filename = source_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::Validation("A file path is required".to_string()))?;
+        let extension = AttachmentStorage::extension_of(&filename);
+
+        if !options.accepts_extension(extension.as_deref()) {
+            return Err(AppError::Validation(format!(
+                "Field '{}' only accepts .{} files",
+                field.name,
+                options.allowed_extensions.unwrap_or_default().join(", .")
+            )));
+        }
+
+        let size = std::fs::metadata(source_path)
+            .map_err(|e| AppError::Validation(format!("Failed to read file: {}", e)))?
+            .len();
+        let limit = options.file_size_limit();
+        if size > limit {
+            return Err(AppError::Validation(format!(
+                "'{}' exceeds the {} limit of field '{}'",
+                filename,
+                format_size(limit),
+                field.name
+            )));
+        }
+
+        let mut attachments = Self::attachments_of(&metadata, field_id);
+        let stored = storage.store_file(source_path, extension.as_deref())?;
+        if let Some(existing) = attachments.iter().find(|a| a.sha256 == stored.hash) {
+            return Err(AppError::Validation(format!(
+                "'{}' is already attached as '{}'",
+                filename, existing.filename
+            )));
+        }
+
+        let attachment = Attachment {
+            sha256: stored.hash,
+            path: stored.relative_path,
+            filename,
+            mime_type: AttachmentStorage::mime_type_of(extension.as_deref()).to_string(),
+            size: stored.size,
+        };
+        attachments.push(attachment.clone());
+        metadata.insert(
+            field_id.to_string(),
+            serde_json::to_value(&attachments).unwrap_or_default(),
+        );
+
+        let txn = conn.begin().await?;
+        Self::save_metadata(&txn, entry, metadata).await?;
+        AttachmentRegistry::acquire(&txn, &attachment).await?;
+        txn.commit().await?;
+
+        log::info!("Attached file to entry {} (field {})", entry_id, field_id);
+        EntryService::get(conn, entry_id).await
+    }
+
+    /// Returns the absolute path of an attachment so it can be opened.
+    pub async fn file_path(
+        conn: &DatabaseConnection,
+        storage: &AttachmentStorage,
+        entry_id: i32,
+        field_id: i32,
+        sha256: &str,
+    ) -> AppResult<PathBuf> {
+        let (_, _, metadata) = Self::load(conn, entry_id, field_id).await?;
+        let attachment = Self::attachments_of(&metadata, field_id)
+            .into_iter()
+            .find(|a| a.sha256 == sha256)
+            .ok_or_else(|| AppError::Validation("Attachment not found".to_string()))?;
+
+        let path = storage.get_full_path(&attachment.path)?;
+        if !path.exists() {
+            return Err(AppError::Internal(format!(
+                "Attachment file '{}' is missing from storage",
+                attachment.filename
+            )));
+        }
+
+        Ok(path)
+    }
+
+    /// Removes an attachment from an entry's file field, deleting the file
+    /// once nothing references it.
+    pub async fn remove(
+        conn: &DatabaseConnection,
+        storage: &AttachmentStorage,
+        entry_id: i32,
+        field_id: i32,
+        sha256: &str,
+    ) -> AppResult<EntryDto> {
+        let (entry, _, mut metadata) = Self::load(conn, entry_id, field_id).await?;
+
+        let (removed, kept): (Vec<_>, Vec<_>) = Self::attachments_of(&metadata, field_id)
+            .into_iter()
+            .partition(|a| a.sha256 == sha256);
+        if removed.is_empty() {
+            return Err(AppError::Validation("Attachment not found".to_string()));
+        }
+
+        let key = field_id.to_string();
+        if kept.is_empty() {
+            metadata.remove(&key);
+        } else {
+            metadata.insert(key, serde_json::to_value(&kept).unwrap_or_default());
+        }
+        let txn = conn.begin().await?;
+        Self::save_metadata(&txn, entry, metadata).await?;
+        let mut unreferenced = Vec::new();
+        for attachment in &removed {
+            if AttachmentRegistry::release(&txn, attachment).await? {
+                unreferenced.push(attachment.path.clone());
+            }
+        }
+        txn.commit().await?;
+
+        AttachmentRegistry::delete_files(storage, &unreferenced);
+
+        log::info!(
+            "Removed attachment from entry {} (field {})",
+            entry_id,
+            field_id
+        );
+        EntryService::get(conn, entry_id).await
+    }
+
+    /// Loads an entry with its metadata and one of its vault's file fields.
+    async fn load(
+        conn: &DatabaseConnection,
+        entry_id: i32,
+        field_id: i32,
+    ) -> AppResult<(entry::Model, FieldDefinitionDto, Map<String, Value>)> {
+        let entry = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+        let field = FieldService::get(conn, field_id).await?;
+
+        if field.vault_id != entry.vault_id || field.field_type != FieldType::File {
+            return Err(AppError::Validation(format!(
+                "Field '{}' is not a file field of this entry's vault",
+                field.name
+            )));
+        }
+
+        let metadata = entry
+            .metadata
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+
+        Ok((entry, field, metadata))
+    }
+
+    /// Reads the attachments of a file field. Values of another shape read
+    /// as an empty list.
+    fn attachments_of(metadata: &Map<String, Value>, field_id: i32) -> Vec<Attachment> {
+        metadata
+            .get(&field_id.to_string())
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    async fn save_metadata<C: ConnectionTrait>(
+        conn: &C,
+        entry: entry::Model,
+        metadata: Map<String, Value>,
+    ) -> AppResult<()> {
+        let mut active_model: EntryActiveModel = entry.into();
+        active_model.metadata = Set(Some(Value::Object(metadata).to_string()));
+        active_model.updated_at = Set(now_formatted());
+        active_model.update(conn).await?;
+        Ok(())
+    }
+}
+
+/// Formats a byte count for messages, e.g. `10MB` or `512KB`.
+fn format_size(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB && bytes.is_multiple_of(MB) {
+        format!("{}MB", bytes / MB)
+    } else if bytes >= 1024 {
+        format!("{}KB", bytes.div_ceil(1024))
+    } else {
+        format!("{} bytes", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::UpdateEntryDto;
+    use sea_orm::Database;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            CREATE TABLE attachment_blobs (
+                hash TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                ref_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT INTO vaults (name) VALUES ('Movies');
+            INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'Subtitles', 'file', '{"allowedExtensions": ["srt", "vtt"], "maxFileSize": 16}'),
+                (1, 'Notes', 'text', NULL),
+                (1, 'Extra', 'json', NULL);
+            INSERT INTO entries (vault_id, title) VALUES (1, 'Heat'), (1, 'Ronin');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    /// Creates a scratch directory holding the given files.
+    fn scratch_dir(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            std::fs::write(dir.join(file), contents).unwrap();
+        }
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_add_enforces_field_options() {
+        let conn = setup_test_db().await;
+        let dir = scratch_dir(
+            "vaultrs_test_attachment_limits",
+            &[
+                ("heat.pdf", b"%PDF"),
+                ("heat.srt", b"1\n00:00:01,000 --> x\n"),
+            ],
+        );
+        let storage = AttachmentStorage::new(&dir);
+
+        let wrong_type = AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.pdf")).await;
+        assert!(matches!(wrong_type, Err(AppError::Validation(e)) if e.contains(".srt, .vtt")));
+
+        let too_big = AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.srt")).await;
+        assert!(matches!(too_big, Err(AppError::Validation(e)) if e.contains("16 bytes")));
+
+        let not_file = AttachmentService::add(&conn, &storage, 1, 2, &dir.join("heat.srt")).await;
+        assert!(matches!(not_file, Err(AppError::Validation(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_files_are_released_with_last_reference() {
+        let conn = setup_test_db().await;
+        let dir = scratch_dir(
+            "vaultrs_test_attachment_release",
+            &[("heat.srt", b"1\nhi\n")],
+        );
+        let storage = AttachmentStorage::new(&dir);
+        let source = dir.join("heat.srt");
+
+        let heat = AttachmentService::add(&conn, &storage, 1, 1, &source)
+            .await
+            .unwrap();
+        AttachmentService::add(&conn, &storage, 2, 1, &source)
+            .await
+            .unwrap();
+
+        let metadata: Map<String, Value> =
+            serde_json::from_str(heat.metadata.as_deref().unwrap()).unwrap();
+        let attachments = AttachmentService::attachments_of(&metadata, 1);
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].filename, "heat.srt");
+        assert_eq!(attachments[0].mime_type, "application/x-subrip");
+        assert_eq!(attachments[0].size, 5);
+        let sha256 = attachments[0].sha256.clone();
+
+        let duplicate = AttachmentService::add(&conn, &storage, 1, 1, &source).await;
+        assert!(
+            matches!(duplicate, Err(AppError::Validation(e)) if e.contains("already attached"))
+        );
+
+        // Ronin still uses the same stored file
+        let path = AttachmentService::file_path(&conn, &storage, 1, 1, &sha256)
+            .await
+            .unwrap();
+        AttachmentService::remove(&conn, &storage, 1, 1, &sha256)
+            .await
+            .unwrap();
+        assert!(path.exists());
+
+        EntryService::delete(&conn, 2, None, Some(&storage))
+            .await
+            .unwrap();
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_files_are_released_when_entry_drops_them() {
+        let conn = setup_test_db().await;
+        let dir = scratch_dir(
+            "vaultrs_test_attachment_update",
+            &[("heat.srt", b"1\nhi\n"), ("heat.vtt", b"WEBVTT\n")],
+        );
+        let storage = AttachmentStorage::new(&dir);
+
+        AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.srt"))
+            .await
+            .unwrap();
+        let heat = AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.vtt"))
+            .await
+            .unwrap();
+        let metadata: Map<String, Value> =
+            serde_json::from_str(heat.metadata.as_deref().unwrap()).unwrap();
+        let attachments = AttachmentService::attachments_of(&metadata, 1);
+        let srt = storage.get_full_path(&attachments[0].path).unwrap();
+        let vtt = storage.get_full_path(&attachments[1].path).unwrap();
+
+        // Removing one item of the list releases only that file
+        let kept = serde_json::json!({ "1": [attachments[1]] }).to_string();
+        let update = |metadata: String| UpdateEntryDto {
+            title: None,
+            description: None,
+            metadata: Some(metadata),
+        };
+        EntryService::update(&conn, 1, update(kept.clone()), Some(&storage))
+            .await
+            .unwrap();
+        assert!(!srt.exists());
+        assert!(vtt.exists());
+
+        EntryService::update(&conn, 1, update("{}".to_string()), Some(&storage))
+            .await
+            .unwrap();
+        assert!(!vtt.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_files_are_released_with_their_field() {
+        let conn = setup_test_db().await;
+        let dir = scratch_dir(
+            "vaultrs_test_attachment_field_delete",
+            &[("heat.srt", b"1\nhi\n")],
+        );
+        let storage = AttachmentStorage::new(&dir);
+
+        AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.srt"))
+            .await
+            .unwrap();
+        let ronin = AttachmentService::add(&conn, &storage, 2, 1, &dir.join("heat.srt"))
+            .await
+            .unwrap();
+        let metadata: Map<String, Value> =
+            serde_json::from_str(ronin.metadata.as_deref().unwrap()).unwrap();
+        let path = storage
+            .get_full_path(&AttachmentService::attachments_of(&metadata, 1)[0].path)
+            .unwrap();
+
+        FieldService::delete(&conn, 1, Some(&storage)).await.unwrap();
+
+        assert!(!path.exists());
+        let ronin = EntryService::get(&conn, 2).await.unwrap();
+        assert_eq!(ronin.metadata.as_deref(), Some("{}"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_other_fields_do_not_hold_files() {
+        let conn = setup_test_db().await;
+        let dir = scratch_dir(
+            "vaultrs_test_attachment_json_path",
+            &[("heat.srt", b"1\nhi\n")],
+        );
+        let storage = AttachmentStorage::new(&dir);
+
+        let heat = AttachmentService::add(&conn, &storage, 1, 1, &dir.join("heat.srt"))
+            .await
+            .unwrap();
+        let metadata: Map<String, Value> =
+            serde_json::from_str(heat.metadata.as_deref().unwrap()).unwrap();
+        let attachment = AttachmentService::attachments_of(&metadata, 1).remove(0);
+        let path = storage.get_full_path(&attachment.path).unwrap();
+
+        // A json value shaped like an attachment is not a reference
+        let extra = serde_json::json!({ "3": { "path": attachment.path } }).to_string();
+        EntryService::update(
+            &conn,
+            2,
+            UpdateEntryDto {
+                title: None,
+                description: None,
+                metadata: Some(extra),
+            },
+            Some(&storage),
+        )
+        .await
+        .unwrap();
+
+        AttachmentService::remove(&conn, &storage, 1, 1, &attachment.sha256)
+            .await
+            .unwrap();
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
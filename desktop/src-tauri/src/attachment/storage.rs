@@ -0,0 +1,238 @@
+//! Attachment storage management for file system operations.
+
+use std::path::{Component, Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::core::{AppError, AppResult};
+
+use super::StoredAttachment;
+
+/// Extension used for files whose name has no usable extension.
+const FALLBACK_EXTENSION: &str = "bin";
+
+/// Attachment storage handler.
+///
+/// Lives in `attachments/` next to `images/`. Like images, files are named
+/// after the SHA-256 of their contents and sharded by the first two hex
+/// characters, so a file attached twice is stored once.
+pub struct AttachmentStorage {
+    attachments_dir: PathBuf,
+}
+
+impl AttachmentStorage {
+    /// Creates a new AttachmentStorage instance with the given app data directory.
+    pub fn new(app_data_dir: &Path) -> Self {
+        let attachments_dir = app_data_dir.join("attachments");
+        Self { attachments_dir }
+    }
+
+    /// Returns the lowercased extension of a file name, if it is a plain
+    /// alphanumeric one.
+    pub fn extension_of(filename: &str) -> Option<String> {
+        Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| !ext.is_empty() && ext.len() <= 16)
+            .filter(|ext| ext.bytes().all(|b| b.is_ascii_alphanumeric()))
+            .map(|ext| ext.to_ascii_lowercase())
+    }
+
+    /// Guesses a MIME type from a file extension.
+    pub fn mime_type_of(extension: Option<&str>) -> &'static str {
+        match extension.unwrap_or_default() {
+            "pdf" => "application/pdf",
+            "txt" | "log" => "text/plain",
+            "md" | "markdown" => "text/markdown",
+            "csv" => "text/csv",
+            "json" => "application/json",
+            "xml" => "application/xml",
+            "html" | "htm" => "text/html",
+            "srt" => "application/x-subrip",
+            "vtt" => "text/vtt",
+            "ass" | "ssa" => "text/x-ssa",
+            "jpg" | "jpeg" => "image/jpeg",
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "svg" => "image/svg+xml",
+            "mp3" => "audio/mpeg",
+            "flac" => "audio/flac",
+            "mp4" => "video/mp4",
+            "mkv" => "video/x-matroska",
+            "zip" => "application/zip",
+            "epub" => "application/epub+zip",
+            "doc" => "application/msword",
+            "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "xls" => "application/vnd.ms-excel",
+            "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            _ => "application/octet-stream",
+        }
+    }
+
+    /// Copies a file into storage under its content hash.
+    /// Skips the write when an identical file is already stored.
+    pub fn store_file(
+        &self,
+        source_path: &Path,
+        extension: Option<&str>,
+    ) -> AppResult<StoredAttachment> {
+        let bytes = std::fs::read(source_path)
+            .map_err(|e| AppError::Validation(format!("Failed to read file: {}", e)))?;
+
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        let relative_path = format!(
+            "{}/{}.{}",
+            &hash[..2],
+            hash,
+            extension.unwrap_or(FALLBACK_EXTENSION)
+        );
+        let dest_path = self.attachments_dir.join(&relative_path);
+
+        if dest_path.exists() {
+            log::info!("Attachment already stored, reusing: {}", relative_path);
+        } else {
+            let shard_dir = self.attachments_dir.join(&hash[..2]);
+            std::fs::create_dir_all(&shard_dir).map_err(|e| {
+                AppError::Internal(format!("Failed to create attachment directory: {}", e))
+            })?;
+
+            // Write to a temporary name first so a crash never leaves a truncated file
+            let temp_path = dest_path.with_extension("tmp");
+            std::fs::write(&temp_path, &bytes)
+                .map_err(|e| AppError::Internal(format!("Failed to write attachment: {}", e)))?;
+            std::fs::rename(&temp_path, &dest_path).map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                AppError::Internal(format!(
+                    "Failed to move attachment to final location: {}",
+                    e
+                ))
+            })?;
+
+            log::info!("Attachment saved to: {}", relative_path);
+        }
+
+        Ok(StoredAttachment {
+            hash,
+            relative_path,
+            size: bytes.len() as u64,
+        })
+    }
+
+    /// Deletes a stored attachment. Missing files are ignored.
+    pub fn delete_file(&self, relative_path: &str) -> AppResult<()> {
+        let full_path = self.get_full_path(relative_path)?;
+
+        if full_path.exists() {
+            std::fs::remove_file(&full_path)
+                .map_err(|e| AppError::Internal(format!("Failed to delete attachment: {}", e)))?;
+            log::info!("Attachment deleted: {}", relative_path);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the full path for an attachment given its relative path.
+    ///
+    /// Rejects absolute paths, `..` and other non-plain components. Existing
+    /// files are canonicalized so symlinks cannot resolve outside the
+    /// attachments directory.
+    pub fn get_full_path(&self, relative_path: &str) -> AppResult<PathBuf> {
+        let relative = Path::new(relative_path);
+        let is_plain = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+
+        if relative_path.is_empty() || !is_plain {
+            return Err(AppError::Validation(format!(
+                "Invalid attachment path '{}'",
+                relative_path
+            )));
+        }
+
+        let full_path = self.attachments_dir.join(relative);
+
+        if !full_path.exists() {
+            return Ok(full_path);
+        }
+
+        let canonical = full_path
+            .canonicalize()
+            .map_err(|e| AppError::Internal(format!("Failed to resolve attachment path: {}", e)))?;
+        let root = self.attachments_dir.canonicalize().map_err(|e| {
+            AppError::Internal(format!("Failed to resolve attachment directory: {}", e))
+        })?;
+
+        if !canonical.starts_with(&root) {
+            return Err(AppError::Validation(format!(
+                "Attachment path '{}' escapes the attachments directory",
+                relative_path
+            )));
+        }
+
+        Ok(canonical)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_of() {
+        assert_eq!(
+            AttachmentStorage::extension_of("Receipt.PDF"),
+            Some("pdf".to_string())
+        );
+        assert_eq!(
+            AttachmentStorage::extension_of("movie.en.srt"),
+            Some("srt".to_string())
+        );
+        assert_eq!(AttachmentStorage::extension_of("README"), None);
+        assert_eq!(AttachmentStorage::extension_of("odd.p$f"), None);
+    }
+
+    #[test]
+    fn test_store_file_deduplicates() {
+        let root = std::env::temp_dir().join("vaultrs_test_attachment_store");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let source = root.join("notes.txt");
+        std::fs::write(&source, b"hello").unwrap();
+
+        let storage = AttachmentStorage::new(&root);
+        let first = storage.store_file(&source, Some("txt")).unwrap();
+        let second = storage.store_file(&source, Some("txt")).unwrap();
+
+        assert_eq!(first.relative_path, second.relative_path);
+        assert_eq!(
+            first.hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(first.size, 5);
+        assert!(
+            storage
+                .get_full_path(&first.relative_path)
+                .unwrap()
+                .exists()
+        );
+
+        storage.delete_file(&first.relative_path).unwrap();
+        assert!(
+            !storage
+                .get_full_path(&first.relative_path)
+                .unwrap()
+                .exists()
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_get_full_path_rejects_traversal() {
+        let storage = AttachmentStorage::new(Path::new("/tmp/app"));
+        assert!(storage.get_full_path("../secret.txt").is_err());
+        assert!(storage.get_full_path("/etc/passwd").is_err());
+        assert!(storage.get_full_path("").is_err());
+    }
+}
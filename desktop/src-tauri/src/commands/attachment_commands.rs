@@ -0,0 +1,65 @@
+//! File attachment Tauri commands.
+
+use sea_orm::DatabaseConnection;
+use tauri::State;
+
+use crate::attachment::{AttachmentService, AttachmentStorage};
+use crate::core::AppResult;
+use crate::entry::EntryDto;
+
+/// Attaches a local file to an entry's file field.
+///
+/// The file is copied into managed storage, so the original can be moved
+/// or deleted afterwards.
+#[tauri::command]
+pub async fn add_entry_attachment(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    field_id: i32,
+    source_path: String,
+    app_data_dir: String,
+) -> AppResult<EntryDto> {
+    log::info!(
+        "Attaching file to entry {} (field {}) from: {}",
+        entry_id,
+        field_id,
+        source_path
+    );
+
+    let storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    AttachmentService::add(
+        &db,
+        &storage,
+        entry_id,
+        field_id,
+        std::path::Path::new(&source_path),
+    )
+    .await
+}
+
+/// Gets the absolute path of an attachment for opening it.
+#[tauri::command]
+pub async fn get_attachment_path(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    field_id: i32,
+    sha256: String,
+    app_data_dir: String,
+) -> AppResult<String> {
+    let storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    let path = AttachmentService::file_path(&db, &storage, entry_id, field_id, &sha256).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Removes an attachment from an entry's file field.
+#[tauri::command]
+pub async fn remove_entry_attachment(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    field_id: i32,
+    sha256: String,
+    app_data_dir: String,
+) -> AppResult<EntryDto> {
+    let storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    AttachmentService::remove(&db, &storage, entry_id, field_id, &sha256).await
+}
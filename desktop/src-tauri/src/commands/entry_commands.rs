@@ -1,21 +1,28 @@
 //! Entry-related Tauri commands.
 
+use std::collections::HashMap;
+
 use sea_orm::DatabaseConnection;
 use serde::Serialize;
 use tauri::State;
 
+use crate::attachment::AttachmentStorage;
 use crate::core::AppResult;
 use crate::entry::{
-    CreateEntryDto, EntryDto, EntrySearchService, EntryService, MetadataService, PaginatedEntries,
-    SearchResult, UpdateEntryDto,
+    ChoiceCount, CreateEntryDto, EntryDto, EntryExportService, EntryFacetService, EntryFilters,
+    EntryIdentifierService, EntryMarkdownService, EntrySearchService, EntryService, EntrySort, MetadataService,
+    NumericSummary, PaginatedEntries, RenderedMarkdown, SearchResult, UpdateEntryDto, VaultExport,
 };
 use crate::image::ImageStorage;
+use crate::relation::{DeleteImpact, ReferentialActionService};
 
 /// Response DTO for metadata validation.
 #[derive(Debug, Serialize)]
 pub struct MetadataValidationResponse {
     pub is_valid: bool,
     pub errors: Vec<String>,
+    /// Errors by the ID of the field they concern
+    pub field_errors: HashMap<i32, Vec<String>>,
     pub warnings: Vec<String>,
 }
 
@@ -44,15 +51,19 @@ pub async fn get_entry(db: State<'_, DatabaseConnection>, id: i32) -> AppResult<
     EntryService::get(&db, id).await
 }
 
-/// Lists entries for a vault with pagination.
+/// Lists entries for a vault with pagination, optionally sorted by a field
+/// and narrowed by choice and range filters.
 #[tauri::command]
 pub async fn list_entries(
     db: State<'_, DatabaseConnection>,
     vault_id: i32,
     page: u64,
     limit: u64,
+    sort: Option<EntrySort>,
+    filters: Option<EntryFilters>,
 ) -> AppResult<PaginatedEntries> {
-    EntryService::list(&db, vault_id, page, limit).await
+    let filters = filters.unwrap_or_default();
+    EntryService::list(&db, vault_id, page, limit, sort.as_ref(), &filters).await
 }
 
 /// Counts entries in a vault.
@@ -72,6 +83,7 @@ pub async fn update_entry(
     title: Option<String>,
     description: Option<String>,
     metadata: Option<String>,
+    app_data_dir: String,
 ) -> AppResult<EntryDto> {
     let dto = UpdateEntryDto {
         title,
@@ -79,10 +91,20 @@ pub async fn update_entry(
         metadata,
     };
 
-    EntryService::update(&db, id, dto).await
+    let attachment_storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    EntryService::update(&db, id, dto, Some(&attachment_storage)).await
+}
+
+/// Reports what deleting an entry would do to entries that reference it.
+#[tauri::command]
+pub async fn get_entry_delete_impact(
+    db: State<'_, DatabaseConnection>,
+    id: i32,
+) -> AppResult<DeleteImpact> {
+    Ok(ReferentialActionService::plan_entry_delete(&*db, id).await?.impact)
 }
 
-/// Deletes an entry and its cover image.
+/// Deletes an entry, its cover image and its attachments.
 #[tauri::command]
 pub async fn delete_entry(
     db: State<'_, DatabaseConnection>,
@@ -90,7 +112,8 @@ pub async fn delete_entry(
     app_data_dir: String,
 ) -> AppResult<()> {
     let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
-    EntryService::delete(&db, id, Some(&image_storage)).await
+    let attachment_storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    EntryService::delete(&db, id, Some(&image_storage), Some(&attachment_storage)).await
 }
 
 /// Searches entries in a vault using full-text search.
@@ -105,24 +128,78 @@ pub async fn search_entries(
     EntrySearchService::search(&db, vault_id, &query, page, limit).await
 }
 
+/// Exports all entries of a vault with stored and computed field values.
+#[tauri::command]
+pub async fn export_vault_entries(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+) -> AppResult<VaultExport> {
+    EntryExportService::export_vault(&db, vault_id).await
+}
+
+/// Renders markdown to safe HTML, resolving wiki-links within the vault.
+#[tauri::command]
+pub async fn render_markdown(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+    source: String,
+) -> AppResult<RenderedMarkdown> {
+    EntryMarkdownService::render(&db, vault_id, &source).await
+}
+
+/// Counts entries per choice of a select or multiselect field.
+#[tauri::command]
+pub async fn get_choice_counts(
+    db: State<'_, DatabaseConnection>,
+    field_id: i32,
+    filters: Option<EntryFilters>,
+) -> AppResult<Vec<ChoiceCount>> {
+    EntryFacetService::choice_counts(&db, field_id, &filters.unwrap_or_default()).await
+}
+
+/// Summarizes a number or rating field: count, average, minimum and maximum.
+#[tauri::command]
+pub async fn get_field_summary(
+    db: State<'_, DatabaseConnection>,
+    field_id: i32,
+    filters: Option<EntryFilters>,
+) -> AppResult<NumericSummary> {
+    EntryFacetService::numeric_summary(&db, field_id, &filters.unwrap_or_default()).await
+}
+
+/// Finds the entry holding an identifier, typed in any form the identifier
+/// field's scheme accepts (e.g. an ISBN-10 for a stored ISBN-13).
+#[tauri::command]
+pub async fn find_entry_by_identifier(
+    db: State<'_, DatabaseConnection>,
+    field_id: i32,
+    value: String,
+) -> AppResult<Option<EntryDto>> {
+    EntryIdentifierService::find_entry(&db, field_id, &value).await
+}
+
 /// Validates entry metadata against field definitions.
 ///
 /// This command checks:
 /// - Required fields are present
 /// - Field values match their types
 /// - Field values satisfy options constraints (min/max, choices, etc.)
+/// - Values of unique fields are not used by another entry, other than
+///   the entry being edited
 #[tauri::command]
 pub async fn validate_entry_metadata(
     db: State<'_, DatabaseConnection>,
     vault_id: i32,
+    entry_id: Option<i32>,
     metadata: Option<String>,
 ) -> AppResult<MetadataValidationResponse> {
     let result =
-        MetadataService::validate_metadata(&db, vault_id, metadata.as_deref()).await?;
+        MetadataService::validate_metadata(&db, vault_id, entry_id, metadata.as_deref()).await?;
 
     Ok(MetadataValidationResponse {
         is_valid: result.is_valid,
         errors: result.errors,
+        field_errors: result.field_errors,
         warnings: result.warnings,
     })
 }
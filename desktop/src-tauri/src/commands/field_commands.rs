@@ -1,11 +1,15 @@
 //! Tauri commands for field definition management.
 
+use std::collections::HashMap;
+
 use sea_orm::DatabaseConnection;
 use tauri::State;
 
+use crate::attachment::AttachmentStorage;
 use crate::core::AppResult;
 use crate::field::{
-    CreateFieldDto, FieldDefinitionDto, FieldOptions, FieldService, FieldType, UpdateFieldDto,
+    ConversionPreview, ConvertFieldDto, CreateFieldDto, FieldDefinitionDto, FieldOptions,
+    FieldRule, FieldService, FieldType, UpdateFieldDto,
 };
 
 /// Creates a new field definition for a vault.
@@ -17,6 +21,7 @@ pub async fn create_field_definition(
     field_type: FieldType,
     options: Option<FieldOptions>,
     required: Option<bool>,
+    backfill: Option<bool>,
 ) -> AppResult<FieldDefinitionDto> {
     let dto = CreateFieldDto {
         vault_id,
@@ -24,6 +29,7 @@ pub async fn create_field_definition(
         field_type,
         options,
         required: required.unwrap_or(false),
+        backfill: backfill.unwrap_or(false),
     };
 
     FieldService::create(&db, dto).await
@@ -48,6 +54,9 @@ pub async fn list_field_definitions(
 }
 
 /// Updates an existing field definition.
+///
+/// `merge_choices` maps choices removed from a select or multiselect field
+/// to the choice their entries move to.
 #[tauri::command]
 pub async fn update_field_definition(
     db: State<'_, DatabaseConnection>,
@@ -55,23 +64,29 @@ pub async fn update_field_definition(
     name: Option<String>,
     options: Option<FieldOptions>,
     required: Option<bool>,
+    backfill: Option<bool>,
+    merge_choices: Option<HashMap<String, String>>,
 ) -> AppResult<FieldDefinitionDto> {
     let dto = UpdateFieldDto {
         name,
         options,
         required,
+        backfill: backfill.unwrap_or(false),
+        merge_choices: merge_choices.unwrap_or_default(),
     };
 
     FieldService::update(&db, id, dto).await
 }
 
-/// Deletes a field definition.
+/// Deletes a field definition and releases the attachments of a file field.
 #[tauri::command]
 pub async fn delete_field_definition(
     db: State<'_, DatabaseConnection>,
     id: i32,
+    app_data_dir: String,
 ) -> AppResult<()> {
-    FieldService::delete(&db, id).await
+    let attachment_storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    FieldService::delete(&db, id, Some(&attachment_storage)).await
 }
 
 /// Reorders field definitions for a vault.
@@ -83,3 +98,57 @@ pub async fn reorder_field_definitions(
 ) -> AppResult<()> {
     FieldService::reorder(&db, vault_id, ids).await
 }
+
+/// Previews converting a field to another type, without writing anything.
+#[tauri::command]
+pub async fn preview_field_conversion(
+    db: State<'_, DatabaseConnection>,
+    id: i32,
+    field_type: FieldType,
+    options: Option<FieldOptions>,
+) -> AppResult<ConversionPreview> {
+    let dto = ConvertFieldDto {
+        field_type,
+        options,
+        discard_failures: false,
+    };
+
+    FieldService::preview_conversion(&db, id, &dto).await
+}
+
+/// Converts a field to another type, rewriting every entry's value.
+#[tauri::command]
+pub async fn convert_field_definition(
+    db: State<'_, DatabaseConnection>,
+    id: i32,
+    field_type: FieldType,
+    options: Option<FieldOptions>,
+    discard_failures: Option<bool>,
+) -> AppResult<FieldDefinitionDto> {
+    let dto = ConvertFieldDto {
+        field_type,
+        options,
+        discard_failures: discard_failures.unwrap_or(false),
+    };
+
+    FieldService::convert(&db, id, dto).await
+}
+
+/// Lists a vault's validation rules between fields.
+#[tauri::command]
+pub async fn get_field_rules(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+) -> AppResult<Vec<FieldRule>> {
+    FieldService::list_rules(&db, vault_id).await
+}
+
+/// Replaces a vault's validation rules between fields.
+#[tauri::command]
+pub async fn set_field_rules(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+    rules: Vec<FieldRule>,
+) -> AppResult<Vec<FieldRule>> {
+    FieldService::set_rules(&db, vault_id, rules).await
+}
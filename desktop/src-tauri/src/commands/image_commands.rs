@@ -4,16 +4,27 @@ use sea_orm::DatabaseConnection;
 use tauri::State;
 
 use crate::core::AppResult;
-use crate::entry::{EntryDto, EntryImageService, EntryService};
-use crate::image::{ImageProcessor, ImageStorage};
+use crate::entry::{
+    DuplicateCoverCluster, EntryDto, EntryImageService, EntryService, ImageMetadataService,
+    DEFAULT_DUPLICATE_DISTANCE,
+};
+use crate::image::{
+    ImageGcReport, ImageMetadata, ImageProcessor, ImageRegistry, ImageStorage, ImageTagMapping,
+    ImageTransform,
+};
 
 /// Uploads an entry cover image from a local file.
+///
+/// Without a transform the image is auto-oriented and downscaled to the
+/// default maximum cover size.
 #[tauri::command]
 pub async fn upload_entry_cover_image(
     db: State<'_, DatabaseConnection>,
     entry_id: i32,
     file_path: String,
     app_data_dir: String,
+    transform: Option<ImageTransform>,
+    keep_original: Option<bool>,
 ) -> AppResult<EntryDto> {
     log::info!(
         "Uploading cover image for entry {} from file: {}",
@@ -23,7 +34,15 @@ pub async fn upload_entry_cover_image(
 
     let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
 
-    EntryImageService::set_cover_from_file(&db, entry_id, &file_path, &image_storage).await
+    EntryImageService::set_cover_from_file(
+        &db,
+        entry_id,
+        &file_path,
+        &image_storage,
+        &transform.unwrap_or_default(),
+        keep_original.unwrap_or(false),
+    )
+    .await
 }
 
 /// Sets an entry cover image from a URL.
@@ -79,3 +98,92 @@ pub async fn remove_entry_cover(
 
     EntryImageService::remove_cover(&db, entry_id, &image_storage).await
 }
+
+/// Re-edits an entry's cover image, starting from the kept original if any.
+#[tauri::command]
+pub async fn edit_entry_cover(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    transform: ImageTransform,
+    app_data_dir: String,
+) -> AppResult<EntryDto> {
+    log::info!("Editing cover image for entry {}", entry_id);
+
+    let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
+
+    EntryImageService::edit_cover(&db, entry_id, &transform, &image_storage).await
+}
+
+/// Restores an entry's cover image to its unedited original.
+#[tauri::command]
+pub async fn revert_entry_cover(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    app_data_dir: String,
+) -> AppResult<EntryDto> {
+    log::info!("Reverting cover image for entry {}", entry_id);
+
+    let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
+
+    EntryImageService::revert_cover(&db, entry_id, &image_storage).await
+}
+
+/// Reports and removes image files that no entry references.
+///
+/// Also flags entries whose cover file is missing. With `dry_run` set,
+/// nothing is deleted and the report only lists what would be removed.
+#[tauri::command]
+pub async fn collect_image_garbage(
+    db: State<'_, DatabaseConnection>,
+    app_data_dir: String,
+    dry_run: Option<bool>,
+) -> AppResult<ImageGcReport> {
+    let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
+
+    ImageRegistry::collect_garbage(&db, &image_storage, dry_run.unwrap_or(false)).await
+}
+
+/// Lists clusters of entries with near-identical cover images.
+///
+/// Searches a single vault when `vault_id` is given, otherwise all vaults.
+/// `max_distance` is the Hamming-distance threshold between cover hashes.
+#[tauri::command]
+pub async fn find_duplicate_covers(
+    db: State<'_, DatabaseConnection>,
+    vault_id: Option<i32>,
+    max_distance: Option<u32>,
+) -> AppResult<Vec<DuplicateCoverCluster>> {
+    EntryImageService::find_duplicate_covers(
+        &db,
+        vault_id,
+        max_distance.unwrap_or(DEFAULT_DUPLICATE_DISTANCE),
+    )
+    .await
+}
+
+/// Reads EXIF/XMP metadata from a local image file.
+#[tauri::command]
+pub async fn read_image_metadata(file_path: String) -> AppResult<ImageMetadata> {
+    let bytes = ImageStorage::read_local_image(std::path::Path::new(&file_path))?;
+
+    Ok(ImageProcessor::extract_metadata(&bytes))
+}
+
+/// Lists a vault's mappings from image tags to fields.
+#[tauri::command]
+pub async fn get_image_metadata_mappings(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+) -> AppResult<Vec<ImageTagMapping>> {
+    ImageMetadataService::list_mappings(&db, vault_id).await
+}
+
+/// Replaces a vault's mappings from image tags to fields.
+#[tauri::command]
+pub async fn set_image_metadata_mappings(
+    db: State<'_, DatabaseConnection>,
+    vault_id: i32,
+    mappings: Vec<ImageTagMapping>,
+) -> AppResult<Vec<ImageTagMapping>> {
+    ImageMetadataService::set_mappings(&db, vault_id, mappings).await
+}
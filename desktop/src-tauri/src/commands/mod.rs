@@ -1,11 +1,13 @@
 //! Tauri commands module for IPC endpoints.
 
+mod attachment_commands;
 mod entry_commands;
 mod field_commands;
 mod image_commands;
 mod relation_commands;
 mod vault_commands;
 
+pub use attachment_commands::*;
 pub use entry_commands::*;
 pub use field_commands::*;
 pub use image_commands::*;
@@ -6,20 +6,26 @@ use sea_orm::DatabaseConnection;
 use tauri::State;
 
 use crate::core::AppResult;
-use crate::relation::{EntryPickerItem, RelationRef, RelationService, ResolvedRelation};
+use crate::relation::{
+    BacklinkGroup, EntryPickerItem, GraphFilter, RelationGraph, RelationGraphService,
+    RelationIndexReport, RelationIndexService, RelationRef, RelationService, ResolvedRelation,
+};
 
 /// Searches entries in a vault for the relation picker.
 ///
-/// Returns a list of entry summaries matching the search query.
+/// Returns a list of entry summaries matching the search query,
+/// excluding `exclude_ids` (entries already picked).
 #[tauri::command]
 pub async fn search_entries_for_relation(
     db: State<'_, DatabaseConnection>,
     vault_id: i32,
     query: String,
     limit: Option<i32>,
+    exclude_ids: Option<Vec<i32>>,
 ) -> AppResult<Vec<EntryPickerItem>> {
     let limit = limit.unwrap_or(20);
-    RelationService::search_entries_for_picker(&db, vault_id, &query, limit).await
+    let exclude_ids = exclude_ids.unwrap_or_default();
+    RelationService::search_entries_for_picker(&db, vault_id, &query, limit, &exclude_ids).await
 }
 
 /// Resolves multiple relation references in batch.
@@ -32,3 +38,48 @@ pub async fn resolve_relations(
 ) -> AppResult<HashMap<String, ResolvedRelation>> {
     RelationService::resolve_batch(&db, relations).await
 }
+
+/// Lists entries that reference an entry, grouped by source vault and field.
+#[tauri::command]
+pub async fn get_backlinks(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+) -> AppResult<Vec<BacklinkGroup>> {
+    RelationIndexService::get_backlinks(&db, entry_id).await
+}
+
+/// Rebuilds the relation index from entry metadata.
+#[tauri::command]
+pub async fn rebuild_relation_index(
+    db: State<'_, DatabaseConnection>,
+) -> AppResult<RelationIndexReport> {
+    RelationIndexService::rebuild(&db).await
+}
+
+/// Returns the entries within a number of hops of an entry and the
+/// relations between them.
+#[tauri::command]
+pub async fn get_relation_neighborhood(
+    db: State<'_, DatabaseConnection>,
+    entry_id: i32,
+    filter: Option<GraphFilter>,
+) -> AppResult<RelationGraph> {
+    RelationGraphService::neighborhood(&db, entry_id, &filter.unwrap_or_default()).await
+}
+
+/// Finds a shortest chain of relations between two entries.
+#[tauri::command]
+pub async fn find_relation_path(
+    db: State<'_, DatabaseConnection>,
+    from_entry_id: i32,
+    to_entry_id: i32,
+    filter: Option<GraphFilter>,
+) -> AppResult<Option<RelationGraph>> {
+    RelationGraphService::shortest_path(
+        &db,
+        from_entry_id,
+        to_entry_id,
+        &filter.unwrap_or_default(),
+    )
+    .await
+}
@@ -3,7 +3,10 @@
 use sea_orm::DatabaseConnection;
 use tauri::State;
 
+use crate::attachment::AttachmentStorage;
 use crate::core::AppResult;
+use crate::image::ImageStorage;
+use crate::relation::{DeleteImpact, ReferentialActionService};
 use crate::vault::{CreateVaultDto, UpdateVaultDto, VaultDto, VaultService};
 
 /// Creates a new vault.
@@ -57,8 +60,23 @@ pub async fn update_vault(
     VaultService::update(&db, id, dto).await
 }
 
-/// Deletes a vault.
+/// Reports what deleting a vault would do to entries and fields in other vaults.
 #[tauri::command]
-pub async fn delete_vault(db: State<'_, DatabaseConnection>, id: i32) -> AppResult<()> {
-    VaultService::delete(&db, id).await
+pub async fn get_vault_delete_impact(
+    db: State<'_, DatabaseConnection>,
+    id: i32,
+) -> AppResult<DeleteImpact> {
+    Ok(ReferentialActionService::plan_vault_delete(&*db, id).await?.impact)
+}
+
+/// Deletes a vault, its entries, their cover images and attachments.
+#[tauri::command]
+pub async fn delete_vault(
+    db: State<'_, DatabaseConnection>,
+    id: i32,
+    app_data_dir: String,
+) -> AppResult<()> {
+    let image_storage = ImageStorage::new(std::path::Path::new(&app_data_dir));
+    let attachment_storage = AttachmentStorage::new(std::path::Path::new(&app_data_dir));
+    VaultService::delete(&db, id, Some(&image_storage), Some(&attachment_storage)).await
 }
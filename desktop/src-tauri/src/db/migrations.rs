@@ -149,6 +149,549 @@ const MIGRATIONS: &[(&str, &str)] = &[
         CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
         "#,
     ),
+    (
+        "009_create_image_blobs",
+        r#"
+        -- Content-addressed image files with reference counts
+        CREATE TABLE IF NOT EXISTS image_blobs (
+            hash        TEXT PRIMARY KEY,
+            path        TEXT NOT NULL UNIQUE,
+            size        INTEGER NOT NULL,
+            ref_count   INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    ),
+    (
+        "010_add_entry_cover_original",
+        r#"
+        -- Unedited original kept so cover edits can be reverted
+        ALTER TABLE entries ADD COLUMN cover_original_path TEXT;
+        "#,
+    ),
+    (
+        "011_add_entry_cover_placeholder",
+        r#"
+        -- BlurHash and dominant-color palette (JSON array) of the cover
+        ALTER TABLE entries ADD COLUMN cover_blurhash TEXT;
+        ALTER TABLE entries ADD COLUMN cover_palette TEXT;
+        "#,
+    ),
+    (
+        "012_add_entry_cover_phash",
+        r#"
+        -- Perceptual hash of the cover (16 hex digits) for duplicate detection
+        ALTER TABLE entries ADD COLUMN cover_phash TEXT;
+
+        -- The hash split into eight 8-bit bands, indexed so covers with
+        -- similar bands are found without comparing every pair
+        CREATE TABLE IF NOT EXISTS entry_cover_hash_bands (
+            entry_id    INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            band        INTEGER NOT NULL,
+            value       INTEGER NOT NULL,
+            PRIMARY KEY (entry_id, band)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_entry_cover_hash_bands_value
+            ON entry_cover_hash_bands(band, value);
+        "#,
+    ),
+    (
+        "013_create_image_metadata_mappings",
+        r#"
+        -- Per-vault mapping of extracted image tags to field definitions
+        CREATE TABLE IF NOT EXISTS image_metadata_mappings (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            tag         TEXT NOT NULL,
+            field_id    INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, tag)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_image_metadata_mappings_vault ON image_metadata_mappings(vault_id);
+        "#,
+    ),
+    (
+        "014_create_entry_relations",
+        r#"
+        -- Normalized index of relation field values, derived from entries.metadata.
+        -- Targets are not foreign keys so dangling references stay visible.
+        CREATE TABLE IF NOT EXISTS entry_relations (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            field_id        INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+            target_entry_id INTEGER NOT NULL,
+            target_vault_id INTEGER NOT NULL,
+            position        INTEGER NOT NULL DEFAULT 0,
+            UNIQUE(source_entry_id, field_id, target_entry_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_entry_relations_target ON entry_relations(target_entry_id);
+        CREATE INDEX IF NOT EXISTS idx_entry_relations_field ON entry_relations(field_id);
+
+        -- Backfill from existing metadata (single objects and arrays)
+        INSERT OR IGNORE INTO entry_relations (source_entry_id, field_id, target_entry_id, target_vault_id, position)
+        SELECT e.id, f.id, json_extract(r.value, '$.entry_id'), json_extract(r.value, '$.vault_id'), r.key
+        FROM entries e
+        JOIN field_definitions f ON f.vault_id = e.vault_id AND f.field_type = 'relation'
+        JOIN json_each(
+            CASE CASE WHEN json_valid(e.metadata) THEN json_type(e.metadata, '$."' || f.id || '"') END
+                WHEN 'array' THEN json_extract(e.metadata, '$."' || f.id || '"')
+                WHEN 'object' THEN json_array(json_extract(e.metadata, '$."' || f.id || '"'))
+                ELSE '[]'
+            END
+        ) r
+        WHERE r.type = 'object'
+          AND json_type(r.value, '$.entry_id') = 'integer'
+          AND json_type(r.value, '$.vault_id') = 'integer';
+        "#,
+    ),
+    (
+        "015_add_derived_field_types",
+        r#"
+        -- Lookup and rollup fields are computed through relations.
+        -- Foreign keys are switched off while the table is rebuilt so the
+        -- DROP does not cascade into image_metadata_mappings and entry_relations.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'date', 'url', 'boolean', 'select', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "016_add_multiselect_field_type",
+        r#"
+        -- Multiselect fields store an array of choices.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'date', 'url', 'boolean', 'select', 'multiselect', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "017_add_rating_field_type",
+        r#"
+        -- Rating fields store a number on a configurable scale.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'date', 'url', 'boolean', 'select', 'multiselect', 'rating', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "018_add_temporal_field_types",
+        r#"
+        -- Partial dates, timestamps and durations are stored normalized:
+        -- dates and timestamps as sortable strings, durations in seconds.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'boolean', 'select', 'multiselect', 'rating', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "019_add_markdown_field_type",
+        r#"
+        -- Markdown fields store sanitized source text.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'boolean', 'select', 'multiselect', 'rating', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "020_add_entry_search_text",
+        r#"
+        -- Plain text of markdown fields, kept up to date on write
+        ALTER TABLE entries ADD COLUMN search_text TEXT;
+
+        -- Rebuild the FTS index with the search text as a third column
+        DROP TRIGGER IF EXISTS entries_fts_insert;
+        DROP TRIGGER IF EXISTS entries_fts_delete;
+        DROP TRIGGER IF EXISTS entries_fts_update;
+        DROP TABLE IF EXISTS entries_fts;
+
+        CREATE VIRTUAL TABLE entries_fts USING fts5(
+            title,
+            description,
+            search_text,
+            content='entries',
+            content_rowid='id'
+        );
+
+        CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+            INSERT INTO entries_fts(rowid, title, description, search_text)
+            VALUES (new.id, new.title, COALESCE(new.description, ''), COALESCE(new.search_text, ''));
+        END;
+
+        CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+            INSERT INTO entries_fts(entries_fts, rowid, title, description, search_text)
+            VALUES ('delete', old.id, old.title, COALESCE(old.description, ''), COALESCE(old.search_text, ''));
+        END;
+
+        CREATE TRIGGER entries_fts_update AFTER UPDATE ON entries BEGIN
+            INSERT INTO entries_fts(entries_fts, rowid, title, description, search_text)
+            VALUES ('delete', old.id, old.title, COALESCE(old.description, ''), COALESCE(old.search_text, ''));
+            INSERT INTO entries_fts(rowid, title, description, search_text)
+            VALUES (new.id, new.title, COALESCE(new.description, ''), COALESCE(new.search_text, ''));
+        END;
+
+        INSERT INTO entries_fts(rowid, title, description, search_text)
+        SELECT id, title, COALESCE(description, ''), COALESCE(search_text, '') FROM entries;
+        "#,
+    ),
+    (
+        "021_add_file_field_type",
+        r#"
+        -- File fields store attachments kept in managed storage.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'boolean', 'select', 'multiselect', 'rating', 'file', 'relation', 'lookup', 'rollup')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "022_add_formula_field_type",
+        r#"
+        -- Formula fields compute their value from other fields on read.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'boolean', 'select', 'multiselect', 'rating', 'file', 'relation', 'lookup', 'rollup', 'formula')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "023_add_location_field_type",
+        r#"
+        -- Location fields store a point and an optional place name.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'boolean', 'select', 'multiselect', 'rating', 'file', 'location', 'relation', 'lookup', 'rollup', 'formula')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+
+        -- Location values indexed for area and distance filters. x, y and z
+        -- are the point on the unit sphere, so distances need no trigonometry.
+        CREATE TABLE IF NOT EXISTS entry_locations (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id    INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            field_id    INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+            latitude    REAL NOT NULL,
+            longitude   REAL NOT NULL,
+            x           REAL NOT NULL,
+            y           REAL NOT NULL,
+            z           REAL NOT NULL,
+            UNIQUE(entry_id, field_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_entry_locations_field ON entry_locations(field_id);
+
+        -- Spatial index over the same rows, kept in sync by triggers so
+        -- cascading deletes clean it up too.
+        CREATE VIRTUAL TABLE IF NOT EXISTS entry_locations_rtree USING rtree(
+            id, min_lat, max_lat, min_lng, max_lng
+        );
+
+        CREATE TRIGGER IF NOT EXISTS entry_locations_ai AFTER INSERT ON entry_locations BEGIN
+            INSERT INTO entry_locations_rtree (id, min_lat, max_lat, min_lng, max_lng)
+            VALUES (new.id, new.latitude, new.latitude, new.longitude, new.longitude);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS entry_locations_ad AFTER DELETE ON entry_locations BEGIN
+            DELETE FROM entry_locations_rtree WHERE id = old.id;
+        END;
+        "#,
+    ),
+    (
+        "024_add_identifier_field_type",
+        r#"
+        -- Identifier fields hold ISBNs, EANs, IMDb IDs or custom codes.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'identifier', 'boolean', 'select', 'multiselect', 'rating', 'file', 'location', 'relation', 'lookup', 'rollup', 'formula')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "025_add_email_phone_color_currency_json_field_types",
+        r#"
+        -- Email, phone, color, currency and raw JSON fields.
+        -- Rebuilt with foreign keys off, like migration 015.
+        PRAGMA foreign_keys=OFF;
+
+        CREATE TABLE field_definitions_new (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id    INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            name        TEXT NOT NULL,
+            field_type  TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'identifier', 'email', 'phone', 'color', 'currency', 'json', 'boolean', 'select', 'multiselect', 'rating', 'file', 'location', 'relation', 'lookup', 'rollup', 'formula')),
+            options     TEXT,
+            position    INTEGER NOT NULL DEFAULT 0,
+            required    INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE(vault_id, name)
+        );
+
+        INSERT INTO field_definitions_new (id, vault_id, name, field_type, options, position, required, created_at, updated_at)
+        SELECT id, vault_id, name, field_type, options, position, required, created_at, updated_at FROM field_definitions;
+
+        DROP TABLE field_definitions;
+
+        ALTER TABLE field_definitions_new RENAME TO field_definitions;
+
+        CREATE INDEX IF NOT EXISTS idx_field_definitions_vault ON field_definitions(vault_id);
+
+        PRAGMA foreign_keys=ON;
+        "#,
+    ),
+    (
+        "026_create_field_rules",
+        r#"
+        -- Per-vault validation rules: required-if and visible-if conditions
+        -- and comparisons between two fields. Rules go with either field.
+        CREATE TABLE IF NOT EXISTS field_rules (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            vault_id        INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+            field_id        INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+            kind            TEXT NOT NULL CHECK (kind IN ('required-if', 'visible-if', 'compare')),
+            other_field_id  INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+            operator        TEXT NOT NULL,
+            value           TEXT,
+            message         TEXT,
+            position        INTEGER NOT NULL DEFAULT 0,
+            created_at      TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_field_rules_vault ON field_rules(vault_id);
+        "#,
+    ),
+    (
+        "027_create_attachment_blobs",
+        r#"
+        -- Content-addressed attachment files with reference counts
+        CREATE TABLE IF NOT EXISTS attachment_blobs (
+            hash        TEXT PRIMARY KEY,
+            path        TEXT NOT NULL UNIQUE,
+            size        INTEGER NOT NULL,
+            ref_count   INTEGER NOT NULL DEFAULT 0,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Count the attachments file fields already hold
+        INSERT OR IGNORE INTO attachment_blobs (hash, path, size, ref_count)
+        SELECT json_extract(item.value, '$.sha256'),
+               json_extract(item.value, '$.path'),
+               MAX(json_extract(item.value, '$.size')),
+               COUNT(*)
+        FROM entries e
+        JOIN field_definitions f ON f.vault_id = e.vault_id AND f.field_type = 'file'
+        JOIN json_each(
+            CASE WHEN json_valid(e.metadata) THEN e.metadata END,
+            '$."' || f.id || '"'
+        ) item
+        WHERE json_type(item.value, '$.sha256') = 'text'
+          AND json_type(item.value, '$.path') = 'text'
+          AND json_type(item.value, '$.size') = 'integer'
+        GROUP BY json_extract(item.value, '$.sha256');
+        "#,
+    ),
 ];
 
 /// Runs all pending migrations.
@@ -13,6 +13,11 @@ pub struct Model {
     pub description: Option<String>,
     pub metadata: Option<String>,
     pub cover_image_path: Option<String>,
+    pub cover_original_path: Option<String>,
+    pub cover_blurhash: Option<String>,
+    pub cover_palette: Option<String>,
+    pub cover_phash: Option<String>,
+    pub search_text: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -0,0 +1,51 @@
+//! Entry location entity indexing location field values.
+//!
+//! Rows mirror the `entry_locations_rtree` R*Tree, which triggers keep in
+//! sync, and add the point as a unit vector for exact distance checks.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "entry_locations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub entry_id: i32,
+    pub field_id: i32,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entry::Entity",
+        from = "Column::EntryId",
+        to = "super::entry::Column::Id"
+    )]
+    Entry,
+    #[sea_orm(
+        belongs_to = "super::field_definition::Entity",
+        from = "Column::FieldId",
+        to = "super::field_definition::Column::Id"
+    )]
+    FieldDefinition,
+}
+
+impl Related<super::entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Entry.def()
+    }
+}
+
+impl Related<super::field_definition::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FieldDefinition.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,46 @@
+//! Entry relation entity indexing relation field values.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "entry_relations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub source_entry_id: i32,
+    pub field_id: i32,
+    pub target_entry_id: i32,
+    pub target_vault_id: i32,
+    pub position: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entry::Entity",
+        from = "Column::SourceEntryId",
+        to = "super::entry::Column::Id"
+    )]
+    SourceEntry,
+    #[sea_orm(
+        belongs_to = "super::field_definition::Entity",
+        from = "Column::FieldId",
+        to = "super::field_definition::Column::Id"
+    )]
+    FieldDefinition,
+}
+
+impl Related<super::entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SourceEntry.def()
+    }
+}
+
+impl Related<super::field_definition::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FieldDefinition.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
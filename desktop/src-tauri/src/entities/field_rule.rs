@@ -0,0 +1,51 @@
+//! Field rule entity holding the validation rules of a vault.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "field_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub vault_id: i32,
+    pub field_id: i32,
+    pub kind: String,
+    pub other_field_id: i32,
+    pub operator: String,
+    /// JSON value the condition tests against
+    pub value: Option<String>,
+    pub message: Option<String>,
+    pub position: i32,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vault::Entity",
+        from = "Column::VaultId",
+        to = "super::vault::Column::Id"
+    )]
+    Vault,
+    #[sea_orm(
+        belongs_to = "super::field_definition::Entity",
+        from = "Column::FieldId",
+        to = "super::field_definition::Column::Id"
+    )]
+    FieldDefinition,
+}
+
+impl Related<super::vault::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vault.def()
+    }
+}
+
+impl Related<super::field_definition::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FieldDefinition.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,45 @@
+//! Image metadata mapping entity linking image tags to vault fields.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "image_metadata_mappings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub vault_id: i32,
+    pub tag: String,
+    pub field_id: i32,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vault::Entity",
+        from = "Column::VaultId",
+        to = "super::vault::Column::Id"
+    )]
+    Vault,
+    #[sea_orm(
+        belongs_to = "super::field_definition::Entity",
+        from = "Column::FieldId",
+        to = "super::field_definition::Column::Id"
+    )]
+    FieldDefinition,
+}
+
+impl Related<super::vault::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vault.def()
+    }
+}
+
+impl Related<super::field_definition::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FieldDefinition.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
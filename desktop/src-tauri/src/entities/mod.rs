@@ -1,5 +1,11 @@
 //! SeaORM entity definitions.
 
+pub mod attachment_blob;
 pub mod entry;
+pub mod entry_location;
+pub mod entry_relation;
 pub mod field_definition;
+pub mod field_rule;
+pub mod image_blob;
+pub mod image_metadata_mapping;
 pub mod vault;
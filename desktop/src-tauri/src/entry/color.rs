@@ -0,0 +1,151 @@
+//! Parsing, normalization and ordering of colors.
+//!
+//! Colors are stored as lowercase `#rrggbb`, or `#rrggbbaa` when not fully
+//! opaque. They sort around the color wheel: greys first from black to
+//! white, then by hue, lightness and saturation.
+
+/// Accepted color formats, for error messages.
+pub const COLOR_FORMATS: &str =
+    "a hex color such as #3b82f6 or #fff, or rgb(59, 130, 246) / rgba(59, 130, 246, 0.5)";
+
+/// Saturation below which a color sorts with the greys.
+const GREY_SATURATION: f64 = 0.05;
+
+/// Normalizes a hex or `rgb()` / `rgba()` color.
+pub fn normalize_color(input: &str) -> Option<String> {
+    let input = input.trim().to_ascii_lowercase();
+    let [r, g, b, a] = match input.strip_prefix('#') {
+        Some(hex) => parse_hex(hex)?,
+        None => parse_rgb_function(&input)?,
+    };
+
+    Some(if a == u8::MAX {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    })
+}
+
+/// Reads `rgb`, `rgba`, `rrggbb` or `rrggbbaa` hex digits.
+fn parse_hex(hex: &str) -> Option<[u8; 4]> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    match hex.len() {
+        3 | 4 => {
+            let mut channels = [u8::MAX; 4];
+            for (i, channel) in channels.iter_mut().take(hex.len()).enumerate() {
+                *channel = digit(i)? * 17;
+            }
+            Some(channels)
+        }
+        6 | 8 => {
+            let mut channels = [u8::MAX; 4];
+            for (i, channel) in channels.iter_mut().take(hex.len() / 2).enumerate() {
+                *channel = pair(i * 2)?;
+            }
+            Some(channels)
+        }
+        _ => None,
+    }
+}
+
+/// Reads `rgb(r, g, b)` or `rgba(r, g, b, a)`, with channels from 0 to 255
+/// and alpha from 0 to 1.
+fn parse_rgb_function(input: &str) -> Option<[u8; 4]> {
+    let (name, rest) = input.split_once('(')?;
+    let args: Vec<&str> = rest.strip_suffix(')')?.split(',').map(str::trim).collect();
+    let channel = |text: &str| text.parse::<u8>().ok();
+
+    match (name.trim(), args.as_slice()) {
+        ("rgb", [r, g, b]) => Some([channel(r)?, channel(g)?, channel(b)?, u8::MAX]),
+        ("rgba", [r, g, b, a]) => {
+            let alpha = a.parse::<f64>().ok().filter(|a| (0.0..=1.0).contains(a))?;
+            Some([
+                channel(r)?,
+                channel(g)?,
+                channel(b)?,
+                (alpha * 255.0).round() as u8,
+            ])
+        }
+        _ => None,
+    }
+}
+
+/// Sort key of a normalized color: greys get negative keys by lightness,
+/// other colors hue in degrees times 1000, plus lightness and saturation.
+pub fn color_sort_key(color: &str) -> Option<f64> {
+    let [r, g, b, _] = parse_hex(color.strip_prefix('#')?)?;
+    let (r, g, b) = (
+        f64::from(r) / 255.0,
+        f64::from(g) / 255.0,
+        f64::from(b) / 255.0,
+    );
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let chroma = max - min;
+    let saturation = if chroma == 0.0 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    };
+
+    if saturation < GREY_SATURATION {
+        return Some(lightness - 2.0);
+    }
+
+    let hue = if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+
+    Some(hue.round() * 1000.0 + lightness * 100.0 + saturation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_color() {
+        assert_eq!(normalize_color("#3B82F6").as_deref(), Some("#3b82f6"));
+        assert_eq!(normalize_color(" #fff ").as_deref(), Some("#ffffff"));
+        assert_eq!(normalize_color("#f008").as_deref(), Some("#ff000088"));
+        assert_eq!(normalize_color("#3b82f6ff").as_deref(), Some("#3b82f6"));
+        assert_eq!(
+            normalize_color("rgb(59, 130, 246)").as_deref(),
+            Some("#3b82f6")
+        );
+        assert_eq!(
+            normalize_color("RGBA(0,0,0,0.5)").as_deref(),
+            Some("#00000080")
+        );
+
+        assert!(normalize_color("3b82f6").is_none());
+        assert!(normalize_color("#3b82f").is_none());
+        assert!(normalize_color("#ggg").is_none());
+        assert!(normalize_color("rgb(256, 0, 0)").is_none());
+        assert!(normalize_color("rgba(0, 0, 0, 2)").is_none());
+        assert!(normalize_color("blue").is_none());
+    }
+
+    #[test]
+    fn test_color_sort_key_orders_greys_then_hues() {
+        let key = |c| color_sort_key(c).unwrap();
+        assert!(key("#000000") < key("#808080"));
+        assert!(key("#808080") < key("#ffffff"));
+        assert!(key("#ffffff") < key("#ff0000"));
+        assert!(key("#ff0000") < key("#ffff00"));
+        assert!(key("#ffff00") < key("#00ff00"));
+        assert!(key("#00ff00") < key("#0000ff"));
+        assert!(key("#800000") < key("#ff0000"));
+    }
+}
@@ -0,0 +1,368 @@
+//! Entry constraint service for field value constraints.
+//!
+//! Minimum lengths and patterns are checked value by value. Unique fields
+//! are checked against the other entries of the vault, and each one is
+//! backed by a partial unique expression index on its metadata key, so the
+//! database rejects duplicates written by any path.
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Statement,
+};
+use serde_json::{Map, Value};
+
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::field_definition::Entity as FieldDefinition;
+use crate::field::{FieldDefinitionDto, FieldService, can_be_unique, check_value_constraints};
+
+use super::MetadataService;
+use super::metadata_service::{ValidationResult, has_value};
+
+/// Entry metadata, or NULL when it is not valid JSON, which `json_extract`
+/// would fail on. Unlike the facet one, the column is left unqualified so
+/// queries spell the expression exactly as the unique indexes do.
+const METADATA_JSON: &str = "CASE WHEN json_valid(metadata) THEN metadata END";
+
+/// Prefix of the names of unique field indexes, followed by the field ID.
+const UNIQUE_INDEX_PREFIX: &str = "idx_entries_unique_field_";
+
+/// Service for the constraints fields put on entry values.
+pub struct EntryConstraintService;
+
+impl EntryConstraintService {
+    /// Rejects metadata breaking the minimum length, pattern or uniqueness
+    /// of its fields, or the vault's required-if and compare rules.
+    ///
+    /// Expects normalized metadata. `entry_id` is the entry being updated,
+    /// left out of uniqueness checks.
+    pub(crate) async fn check<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        entry_id: Option<i32>,
+        metadata_json: Option<&str>,
+    ) -> AppResult<()> {
+        let Some(metadata) =
+            metadata_json.and_then(|json| serde_json::from_str::<Map<String, Value>>(json).ok())
+        else {
+            return Ok(());
+        };
+
+        let mut result = ValidationResult::success();
+        for (field_id, error) in Self::violations(conn, vault_id, entry_id, &metadata).await? {
+            result.add_field_error(field_id, error);
+        }
+
+        if result.is_valid {
+            Ok(())
+        } else {
+            Err(AppError::Validation(result.errors.join("; ")))
+        }
+    }
+
+    /// Lists what `check` rejects in metadata, as an error for each field.
+    pub(crate) async fn violations<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        entry_id: Option<i32>,
+        metadata: &Map<String, Value>,
+    ) -> AppResult<Vec<(i32, String)>> {
+        let fields = MetadataService::get_field_definitions(conn, vault_id).await?;
+
+        let mut violations = Vec::new();
+        for field in &fields {
+            let Some(value) = metadata.get(&field.id.to_string()).filter(|v| has_value(v)) else {
+                continue;
+            };
+            if let Err(e) = check_value_constraints(field.field_type, field.options.as_ref(), value)
+            {
+                violations.push((field.id, format!("Field '{}': {}", field.name, e)));
+            }
+        }
+        violations.extend(Self::unique_violations(conn, &fields, entry_id, metadata).await?);
+        let rules = FieldService::vault_rules(conn, vault_id).await?;
+        violations.extend(MetadataService::check_rules(&fields, &rules, metadata).errors);
+
+        Ok(violations)
+    }
+
+    /// Finds the values of unique fields another entry of the vault
+    /// already holds, with an error for each.
+    ///
+    /// Values are compared in stored form; values that cannot be
+    /// normalized are left to type validation.
+    pub(crate) async fn unique_violations<C: ConnectionTrait>(
+        conn: &C,
+        fields: &[FieldDefinitionDto],
+        entry_id: Option<i32>,
+        metadata: &Map<String, Value>,
+    ) -> AppResult<Vec<(i32, String)>> {
+        let mut violations = Vec::new();
+        for field in fields.iter().filter(|f| is_unique(f)) {
+            let Some(value) = metadata
+                .get(&field.id.to_string())
+                .filter(|v| has_value(v) && v.as_str() != Some(""))
+            else {
+                continue;
+            };
+            let Ok(value) = MetadataService::normalize_field_value(field, value) else {
+                continue;
+            };
+
+            let mut query = Entry::find()
+                .filter(entry::Column::VaultId.eq(field.vault_id))
+                .filter(Expr::cust_with_values(
+                    format!("{} = json_extract(?, '$')", value_expr(field.id)),
+                    [value.to_string()],
+                ))
+                .order_by_asc(entry::Column::Id);
+            if let Some(entry_id) = entry_id {
+                query = query.filter(entry::Column::Id.ne(entry_id));
+            }
+            let holder: Option<String> = query
+                .select_only()
+                .column(entry::Column::Title)
+                .into_tuple()
+                .one(conn)
+                .await?;
+
+            if let Some(title) = holder {
+                let value = value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_string);
+                violations.push((
+                    field.id,
+                    format!(
+                        "Field '{}': {} is already used by '{}'",
+                        field.name, value, title
+                    ),
+                ));
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Creates the unique index of a field marked unique, and drops it
+    /// otherwise.
+    ///
+    /// Fails when entries of the vault already share a value, so call it
+    /// after checking for duplicates, inside the transaction writing the
+    /// field when there is one.
+    pub async fn sync_unique_index<C: ConnectionTrait>(
+        conn: &C,
+        field: &FieldDefinitionDto,
+    ) -> AppResult<()> {
+        let sql = if is_unique(field) {
+            let value = value_expr(field.id);
+            format!(
+                "CREATE UNIQUE INDEX IF NOT EXISTS {}{} ON entries ({})
+                WHERE vault_id = {} AND {} IS NOT NULL AND {} <> ''",
+                UNIQUE_INDEX_PREFIX, field.id, value, field.vault_id, value, value
+            )
+        } else {
+            format!("DROP INDEX IF EXISTS {}{}", UNIQUE_INDEX_PREFIX, field.id)
+        };
+        conn.execute_unprepared(&sql).await?;
+        Ok(())
+    }
+
+    /// Drops the unique index of a deleted field.
+    pub async fn drop_unique_index<C: ConnectionTrait>(conn: &C, field_id: i32) -> AppResult<()> {
+        conn.execute_unprepared(&format!(
+            "DROP INDEX IF EXISTS {}{}",
+            UNIQUE_INDEX_PREFIX, field_id
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Brings the unique indexes in line with the field definitions: one
+    /// for each unique field, none for fields that are gone or no longer
+    /// unique. Run at startup.
+    ///
+    /// Indexes that cannot be created because entries share a value are
+    /// logged and skipped; the uniqueness checks still apply to new values.
+    pub async fn sync_unique_indexes(conn: &DatabaseConnection) -> AppResult<()> {
+        let fields: Vec<FieldDefinitionDto> = FieldDefinition::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(FieldDefinitionDto::from)
+            .collect();
+
+        let indexes: Vec<String> = conn
+            .query_all(Statement::from_sql_and_values(
+                conn.get_database_backend(),
+                "SELECT name FROM sqlite_master WHERE type = 'index' AND name LIKE ?",
+                [format!("{}%", UNIQUE_INDEX_PREFIX).into()],
+            ))
+            .await?
+            .into_iter()
+            .filter_map(|row| row.try_get::<String>("", "name").ok())
+            .collect();
+        for index in indexes {
+            let field_id = index[UNIQUE_INDEX_PREFIX.len()..].parse::<i32>().ok();
+            if !fields
+                .iter()
+                .any(|f| Some(f.id) == field_id && is_unique(f))
+            {
+                conn.execute_unprepared(&format!("DROP INDEX IF EXISTS {}", index))
+                    .await?;
+            }
+        }
+
+        for field in fields.iter().filter(|f| is_unique(f)) {
+            if let Err(e) = Self::sync_unique_index(conn, field).await {
+                log::warn!(
+                    "Could not index unique field {} (id={}): {}",
+                    field.name,
+                    field.id,
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a field requires unique values.
+fn is_unique(field: &FieldDefinitionDto) -> bool {
+    can_be_unique(field.field_type) && field.options.as_ref().is_some_and(|o| o.is_unique())
+}
+
+/// SQL expression reading a field's value from entry metadata, as indexed
+/// by the field's unique index.
+fn value_expr(field_id: i32) -> String {
+    format!("json_extract({}, '$.\"{}\"')", METADATA_JSON, field_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldOptions;
+    use sea_orm::Database;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        conn.execute_unprepared(
+            r#"
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'Code', 'text', '{"unique": true, "pattern": "[A-Z]+", "patternMessage": "Use capitals"}'),
+                (1, 'Number', 'number', '{"unique": true}'),
+                (1, 'Notes', 'text', '{"minLength": 3}');
+
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'First', '{"1": "ABC", "2": 7}'),
+                (2, 'Elsewhere', '{"1": "XYZ"}');
+            "#,
+        )
+        .await
+        .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_check_constraints() {
+        let conn = setup_test_db().await;
+        let check = |entry_id, metadata: &'static str| {
+            EntryConstraintService::check(&conn, 1, entry_id, Some(metadata))
+        };
+
+        assert!(
+            check(None, r#"{"1": "XYZ", "2": 8, "3": "abc"}"#)
+                .await
+                .is_ok()
+        );
+        assert!(check(Some(1), r#"{"1": "ABC", "2": 7.0}"#).await.is_ok());
+
+        let error = check(None, r#"{"1": "abc", "2": 7.0, "3": "ab"}"#)
+            .await
+            .unwrap_err();
+        let AppError::Validation(error) = error else {
+            panic!("expected a validation error");
+        };
+        assert!(error.contains("Use capitals"));
+        assert!(error.contains("Number': 7.0 is already used by 'First'"));
+        assert!(error.contains("shorter than 3"));
+        assert!(check(None, r#"{"1": "ABC"}"#).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_unique_indexes() {
+        let conn = setup_test_db().await;
+        EntryConstraintService::sync_unique_indexes(&conn)
+            .await
+            .unwrap();
+
+        // The database rejects duplicates, even when written directly
+        let duplicate = r#"INSERT INTO entries (vault_id, title, metadata)
+            VALUES (1, 'Second', '{"1": "ABC"}')"#;
+        assert!(conn.execute_unprepared(duplicate).await.is_err());
+        conn.execute_unprepared(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'Blank', '{"1": "", "2": null}'),
+                (1, 'Blank again', '{"1": ""}'),
+                (1, 'Broken', 'not json')"#,
+        )
+        .await
+        .unwrap();
+
+        let field = FieldDefinitionDto {
+            options: Some(FieldOptions::default()),
+            ..FieldDefinitionDto::from(
+                FieldDefinition::find_by_id(1)
+                    .one(&conn)
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            )
+        };
+        EntryConstraintService::sync_unique_index(&conn, &field)
+            .await
+            .unwrap();
+        assert!(conn.execute_unprepared(duplicate).await.is_ok());
+    }
+}
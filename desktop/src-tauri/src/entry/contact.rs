@@ -0,0 +1,199 @@
+//! Parsing and normalization of email addresses and phone numbers.
+//!
+//! - Email addresses follow the RFC 5322 `addr-spec` grammar, within the
+//!   length limits of RFC 5321, and are stored with a lowercase domain
+//! - Phone numbers are stored in E.164 form (`+442071838750`)
+
+/// Accepted email formats, for error messages.
+pub const EMAIL_FORMATS: &str = "an address such as name@example.com";
+
+/// Accepted phone formats, for error messages.
+pub const PHONE_FORMATS: &str =
+    "a number with its country code, e.g. +44 20 7183 8750 or 0044 20 7183 8750";
+
+/// Longest address allowed by RFC 5321 (the forward-path limit).
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+const MAX_DOMAIN_LABEL_LENGTH: usize = 63;
+
+/// Most digits an E.164 number has, country code included.
+const MAX_PHONE_DIGITS: usize = 15;
+/// Fewest digits of a real E.164 number, country code included.
+const MIN_PHONE_DIGITS: usize = 7;
+
+/// Normalizes an email address, also accepting a `mailto:` link.
+/// The local part keeps its case, which is significant in principle.
+pub fn normalize_email(input: &str) -> Option<String> {
+    let input = input.trim();
+    let address = match input.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &input[7..],
+        _ => input,
+    };
+    if address.len() > MAX_EMAIL_LENGTH {
+        return None;
+    }
+
+    // Quoted local parts may contain '@', the domain never does
+    let (local, domain) = address.rsplit_once('@')?;
+    if local.len() > MAX_LOCAL_PART_LENGTH || !(is_dot_atom(local) || is_quoted_string(local)) {
+        return None;
+    }
+
+    let domain = if domain.starts_with('[') {
+        is_domain_literal(domain).then(|| domain.to_ascii_lowercase())?
+    } else {
+        let domain = domain.to_lowercase();
+        domain.split('.').all(is_domain_label).then_some(domain)?
+    };
+
+    Some(format!("{}@{}", local, domain))
+}
+
+/// RFC 5322 `atext`, extended to non-ASCII characters as RFC 6531 allows.
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || "!#$%&'*+-/=?^_`{|}~".contains(c)
+        || (!c.is_ascii() && !c.is_control())
+}
+
+/// Atoms joined by single dots, without a leading or trailing dot.
+fn is_dot_atom(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .split('.')
+            .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Printable text in double quotes, with `\` escaping any printable
+/// character.
+fn is_quoted_string(text: &str) -> bool {
+    let Some(inner) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) else {
+        return false;
+    };
+
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if !chars
+                    .next()
+                    .is_some_and(|e| e == ' ' || e == '\t' || e.is_ascii_graphic())
+                {
+                    return false;
+                }
+            }
+            '"' => return false,
+            c if c == ' ' || c == '\t' || c.is_ascii_graphic() || !c.is_ascii() => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Host name label: letters, digits and inner hyphens.
+fn is_domain_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_DOMAIN_LABEL_LENGTH
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+/// Address literal such as `[192.0.2.1]` or `[IPv6:2001:db8::1]`.
+fn is_domain_literal(domain: &str) -> bool {
+    let Some(inner) = domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) else {
+        return false;
+    };
+    match inner.get(..5) {
+        Some(tag) if tag.eq_ignore_ascii_case("IPv6:") => {
+            inner[5..].parse::<std::net::Ipv6Addr>().is_ok()
+        }
+        _ => inner.parse::<std::net::Ipv4Addr>().is_ok(),
+    }
+}
+
+/// Normalizes a phone number to E.164.
+///
+/// Spaces, dots, dashes, slashes and parentheses are ignored. Numbers
+/// written without `+` or `00` take the default calling code, dropping a
+/// national trunk `0`; without a default they are rejected.
+pub fn normalize_phone(input: &str, calling_code: Option<&str>) -> Option<String> {
+    let compact: String = input
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '.' | '-' | '/' | '(' | ')' | '\u{a0}'))
+        .collect();
+
+    let digits = if let Some(rest) = compact.strip_prefix('+') {
+        rest.to_string()
+    } else if let Some(rest) = compact.strip_prefix("00") {
+        rest.to_string()
+    } else {
+        let national = compact.strip_prefix('0').unwrap_or(&compact);
+        format!("{}{}", calling_code?, national)
+    };
+
+    let valid = (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits.len())
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && !digits.starts_with('0');
+    valid.then(|| format!("+{}", digits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(
+            normalize_email(" Jane.Doe@Example.COM ").as_deref(),
+            Some("Jane.Doe@example.com")
+        );
+        assert_eq!(
+            normalize_email("mailto:info@example.org").as_deref(),
+            Some("info@example.org")
+        );
+        assert!(normalize_email("user+tag@sub.example.co.uk").is_some());
+        assert!(normalize_email(r#""john@home"@example.com"#).is_some());
+        assert!(normalize_email("admin@[192.0.2.1]").is_some());
+        assert!(normalize_email("admin@[IPv6:2001:db8::1]").is_some());
+        assert!(normalize_email("josé@correo.es").is_some());
+
+        assert!(normalize_email("plainaddress").is_none());
+        assert!(normalize_email("@example.com").is_none());
+        assert!(normalize_email("john..doe@example.com").is_none());
+        assert!(normalize_email(".john@example.com").is_none());
+        assert!(normalize_email("john doe@example.com").is_none());
+        assert!(normalize_email("john@-example.com").is_none());
+        assert!(normalize_email("john@example..com").is_none());
+        assert!(normalize_email("john@[300.0.0.1]").is_none());
+        assert!(normalize_email(&format!("{}@example.com", "a".repeat(65))).is_none());
+    }
+
+    #[test]
+    fn test_normalize_phone() {
+        assert_eq!(
+            normalize_phone("+44 (20) 7183-8750", None).as_deref(),
+            Some("+442071838750")
+        );
+        assert_eq!(
+            normalize_phone("0044 20 7183 8750", None).as_deref(),
+            Some("+442071838750")
+        );
+        // National numbers need a default calling code
+        assert_eq!(
+            normalize_phone("020 7183 8750", Some("44")).as_deref(),
+            Some("+442071838750")
+        );
+        assert_eq!(
+            normalize_phone("(415) 555-2671", Some("1")).as_deref(),
+            Some("+14155552671")
+        );
+        assert!(normalize_phone("020 7183 8750", None).is_none());
+
+        assert!(normalize_phone("+0 123 4567", None).is_none());
+        assert!(normalize_phone("+1 234", None).is_none());
+        assert!(normalize_phone("+1 234 567 890 123 456", None).is_none());
+        assert!(normalize_phone("+1 415 CALL NOW", None).is_none());
+    }
+}
@@ -0,0 +1,441 @@
+//! Default values of custom fields.
+//!
+//! Fields may name a value filled in when an entry leaves them empty: a
+//! fixed value, the current date or time, the next number of a counter, or
+//! the value of another field of the same entry. Defaults are filled in
+//! when an entry is created, before required fields are checked, and can
+//! be backfilled into the existing entries of a vault.
+
+use chrono::NaiveTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+    Statement,
+};
+use serde_json::{Map, Value};
+
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::field_definition::Entity as FieldDefinition;
+use crate::field::{DefaultValue, FieldDefinitionDto, FieldType, choice_labels, convert_value};
+use crate::location::LocationIndexService;
+
+use super::{EntryConstraintService, MetadataService};
+use super::metadata_service::has_value;
+use super::temporal::normalize_local_datetime;
+
+/// Service filling in the default values of fields.
+pub struct EntryDefaultService;
+
+impl EntryDefaultService {
+    /// Fills in the default value of every field the metadata leaves
+    /// empty.
+    ///
+    /// Fixed, date and counter defaults are resolved first, so a field
+    /// copying another can pick up that field's default. Defaults that are
+    /// not valid values of their field, such as a copy that cannot be
+    /// converted, are left out. Metadata that is not a JSON object is
+    /// returned as-is.
+    pub async fn apply<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        metadata_json: Option<&str>,
+    ) -> AppResult<Option<String>> {
+        let fields = MetadataService::get_field_definitions(conn, vault_id).await?;
+        if !fields.iter().any(|f| default_of(f).is_some()) {
+            return Ok(metadata_json.map(str::to_string));
+        }
+
+        let mut metadata: Map<String, Value> = match metadata_json {
+            Some(json) => match serde_json::from_str(json) {
+                Ok(m) => m,
+                Err(_) => return Ok(Some(json.to_string())),
+            },
+            None => Map::new(),
+        };
+
+        let mut changed = false;
+        for copies in [false, true] {
+            for field in &fields {
+                let Some(default) = default_of(field) else {
+                    continue;
+                };
+                let key = field.id.to_string();
+                if matches!(default, DefaultValue::CopyField { .. }) != copies
+                    || metadata.get(&key).is_some_and(has_value)
+                {
+                    continue;
+                }
+
+                let counter = match default {
+                    DefaultValue::Counter { start } => {
+                        Self::next_counter(conn, field, *start).await?
+                    }
+                    _ => 0,
+                };
+                if let Some(value) = resolve(field, default, &fields, &metadata, counter) {
+                    metadata.insert(key, value);
+                    changed = true;
+                }
+            }
+        }
+
+        Ok(if changed {
+            Some(Value::Object(metadata).to_string())
+        } else {
+            metadata_json.map(str::to_string)
+        })
+    }
+
+    /// Writes a field's default value into every entry of its vault that
+    /// leaves the field empty. Counters number the entries in the order
+    /// they were created. Entries whose default is already held by another
+    /// entry are skipped for unique fields.
+    ///
+    /// Returns the number of entries updated.
+    pub async fn backfill<C: ConnectionTrait>(conn: &C, field_id: i32) -> AppResult<u64> {
+        let field = FieldDefinition::find_by_id(field_id)
+            .one(conn)
+            .await?
+            .map(FieldDefinitionDto::from)
+            .ok_or(AppError::FieldNotFound(field_id))?;
+        let Some(default) = default_of(&field) else {
+            return Ok(0);
+        };
+
+        let fields = MetadataService::get_field_definitions(conn, field.vault_id).await?;
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.eq(field.vault_id))
+            .order_by_asc(entry::Column::Id)
+            .all(conn)
+            .await?;
+
+        let mut counter = match default {
+            DefaultValue::Counter { start } => Self::next_counter(conn, &field, *start).await?,
+            _ => 0,
+        };
+        let key = field.id.to_string();
+        let mut count = 0;
+        for entry in entries {
+            let mut metadata: Map<String, Value> = match entry.metadata.as_deref() {
+                Some(json) => match serde_json::from_str(json) {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                },
+                None => Map::new(),
+            };
+            if metadata.get(&key).is_some_and(has_value) {
+                continue;
+            }
+            let Some(value) = resolve(&field, default, &fields, &metadata, counter) else {
+                continue;
+            };
+            metadata.insert(key.clone(), value);
+            // Values a unique field already holds are left out
+            if !EntryConstraintService::unique_violations(
+                conn,
+                std::slice::from_ref(&field),
+                Some(entry.id),
+                &metadata,
+            )
+            .await?
+            .is_empty()
+            {
+                continue;
+            }
+            counter += 1;
+            count += 1;
+
+            let metadata = Value::Object(metadata).to_string();
+            let mut entry_model: entry::ActiveModel = entry.clone().into();
+            if field.field_type == FieldType::Markdown {
+                entry_model.search_text =
+                    Set(MetadataService::search_text(conn, entry.vault_id, Some(&metadata)).await?);
+            }
+            entry_model.metadata = Set(Some(metadata.clone()));
+            entry_model.update(conn).await?;
+
+            if field.field_type == FieldType::Location {
+                LocationIndexService::sync_entry(conn, entry.id, entry.vault_id, Some(&metadata))
+                    .await?;
+            }
+        }
+
+        log::info!(
+            "Backfilled the default value of field {} (id={}) into {} entries",
+            field.name,
+            field.id,
+            count
+        );
+
+        Ok(count)
+    }
+
+    /// Next value of a counter: one more than the largest number the field
+    /// holds in its vault, and at least `start` (default: 1).
+    async fn next_counter<C: ConnectionTrait>(
+        conn: &C,
+        field: &FieldDefinitionDto,
+        start: Option<i64>,
+    ) -> AppResult<i64> {
+        let path = format!("$.\"{}\"", field.id);
+        let sql = "SELECT CAST(MAX(json_extract(metadata, ?)) AS INTEGER) AS value FROM entries
+            WHERE vault_id = ? AND json_valid(metadata)
+            AND json_type(metadata, ?) IN ('integer', 'real')";
+        let largest: Option<i64> = conn
+            .query_one(Statement::from_sql_and_values(
+                conn.get_database_backend(),
+                sql,
+                [path.clone().into(), field.vault_id.into(), path.into()],
+            ))
+            .await?
+            .and_then(|row| row.try_get("", "value").ok())
+            .flatten();
+
+        let start = start.unwrap_or(1);
+        Ok(largest.map_or(start, |largest| (largest + 1).max(start)))
+    }
+}
+
+/// Default value of a field, if it has one.
+fn default_of(field: &FieldDefinitionDto) -> Option<&DefaultValue> {
+    field.options.as_ref()?.default_value.as_ref()
+}
+
+/// Computes a field's default value for an entry holding `metadata`, in
+/// stored form. Returns `None` when there is no valid value to fill in.
+fn resolve(
+    field: &FieldDefinitionDto,
+    default: &DefaultValue,
+    fields: &[FieldDefinitionDto],
+    metadata: &Map<String, Value>,
+    counter: i64,
+) -> Option<Value> {
+    let value = match default {
+        DefaultValue::Value { value } => value.clone(),
+        DefaultValue::Today => {
+            let today = chrono::Local::now().date_naive();
+            match field.field_type {
+                // Local midnight, stored in UTC
+                FieldType::Datetime => {
+                    Value::String(normalize_local_datetime(today.and_time(NaiveTime::MIN))?)
+                }
+                _ => Value::String(today.format("%Y-%m-%d").to_string()),
+            }
+        }
+        DefaultValue::Now => {
+            Value::String(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        }
+        DefaultValue::Counter { .. } => Value::from(counter),
+        DefaultValue::CopyField { field_id } => {
+            let source = fields.iter().find(|f| f.id == *field_id)?;
+            let value = metadata
+                .get(&field_id.to_string())
+                .filter(|v| has_value(v))?;
+            // Choices are copied by label, matched to the field's own choices
+            let value = choice_labels(source, value);
+            if source.field_type == field.field_type {
+                value
+            } else {
+                convert_value(&value, source.field_type, field.field_type).ok()??
+            }
+        }
+    };
+
+    let value = MetadataService::normalize_field_value(field, &value).ok()?;
+    MetadataService::validate_field_value(field, &value).ok()?;
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldOptions;
+    use sea_orm::{Database, DatabaseConnection};
+    use serde_json::json;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        conn.execute_unprepared(
+            r#"
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            "#,
+        )
+        .await
+        .unwrap();
+        conn
+    }
+
+    async fn add_field(
+        conn: &DatabaseConnection,
+        name: &str,
+        field_type: FieldType,
+        default_value: Option<DefaultValue>,
+    ) {
+        let options = FieldOptions {
+            default_value,
+            ..Default::default()
+        };
+        conn.execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            "INSERT INTO field_definitions (vault_id, name, field_type, options, position)
+             VALUES (1, ?, ?, ?, (SELECT COUNT(*) FROM field_definitions))",
+            [
+                name.into(),
+                field_type.as_str().into(),
+                serde_json::to_string(&options).unwrap().into(),
+            ],
+        ))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_apply_defaults() {
+        let conn = setup_test_db().await;
+        add_field(
+            &conn,
+            "Status",
+            FieldType::Text,
+            Some(DefaultValue::Value {
+                value: json!("To read"),
+            }),
+        )
+        .await;
+        add_field(&conn, "Added", FieldType::Date, Some(DefaultValue::Today)).await;
+        add_field(
+            &conn,
+            "Number",
+            FieldType::Number,
+            Some(DefaultValue::Counter { start: Some(100) }),
+        )
+        .await;
+        add_field(
+            &conn,
+            "Label",
+            FieldType::Text,
+            Some(DefaultValue::CopyField { field_id: 3 }),
+        )
+        .await;
+        add_field(
+            &conn,
+            "Started",
+            FieldType::Datetime,
+            Some(DefaultValue::Today),
+        )
+        .await;
+
+        let metadata = EntryDefaultService::apply(&conn, 1, None)
+            .await
+            .unwrap()
+            .unwrap();
+        let metadata: Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata["1"], "To read");
+        assert_eq!(
+            metadata["2"],
+            chrono::Local::now()
+                .date_naive()
+                .format("%Y-%m-%d")
+                .to_string()
+        );
+        assert_eq!(metadata["3"], 100);
+        assert_eq!(metadata["4"], "100");
+        let midnight = chrono::Local::now().date_naive().and_time(NaiveTime::MIN);
+        assert_eq!(
+            metadata["5"],
+            normalize_local_datetime(midnight).unwrap().as_str()
+        );
+
+        // Values already filled in are kept
+        let metadata = EntryDefaultService::apply(&conn, 1, Some(r#"{"1": "Read", "3": 7}"#))
+            .await
+            .unwrap()
+            .unwrap();
+        let metadata: Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata["1"], "Read");
+        assert_eq!(metadata["4"], "7");
+
+        // Counters continue from the largest value
+        conn.execute_unprepared(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES (1, 'A', '{"3": 120}')"#,
+        )
+        .await
+        .unwrap();
+        let metadata = EntryDefaultService::apply(&conn, 1, Some("{}"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&metadata).unwrap()["3"], 121);
+    }
+
+    #[tokio::test]
+    async fn test_backfill() {
+        let conn = setup_test_db().await;
+        add_field(&conn, "Title", FieldType::Text, None).await;
+        conn.execute_unprepared(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'A', '{"1": "42"}'),
+                (1, 'B', NULL),
+                (1, 'C', '{"1": "x"}')"#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(EntryDefaultService::backfill(&conn, 1).await.unwrap(), 0);
+
+        add_field(
+            &conn,
+            "Number",
+            FieldType::Number,
+            Some(DefaultValue::Counter { start: None }),
+        )
+        .await;
+        assert_eq!(EntryDefaultService::backfill(&conn, 2).await.unwrap(), 3);
+        add_field(
+            &conn,
+            "Copy",
+            FieldType::Number,
+            Some(DefaultValue::CopyField { field_id: 1 }),
+        )
+        .await;
+        // Text that is not a number is not copied
+        assert_eq!(EntryDefaultService::backfill(&conn, 3).await.unwrap(), 1);
+
+        let metadata: Vec<Value> = Entry::find()
+            .order_by_asc(entry::Column::Id)
+            .all(&conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| serde_json::from_str(&e.metadata.unwrap()).unwrap())
+            .collect();
+        assert_eq!(metadata[0], json!({"1": "42", "2": 1, "3": 42}));
+        assert_eq!(metadata[1], json!({"2": 2}));
+        assert_eq!(metadata[2], json!({"1": "x", "2": 3}));
+    }
+}
@@ -0,0 +1,80 @@
+//! Entry export service.
+//!
+//! Exports every entry of a vault with its field values keyed by field name,
+//! and choices by label, so the result reads without the vault's field
+//! definitions at hand.
+//! Lookup and rollup values are computed and exported like stored ones.
+
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::Map;
+
+use crate::core::{AppResult, find_vault_or_error};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::field::{FieldService, choice_labels};
+use crate::relation::DerivedFieldService;
+
+use super::{EntryDto, ExportedEntry, MetadataService, VaultExport};
+
+/// Service for exporting vault entries.
+pub struct EntryExportService;
+
+impl EntryExportService {
+    /// Exports all entries of a vault, oldest first.
+    pub async fn export_vault(conn: &DatabaseConnection, vault_id: i32) -> AppResult<VaultExport> {
+        let vault = find_vault_or_error(conn, vault_id).await?;
+        let fields = FieldService::list(conn, vault_id).await?;
+
+        let mut entries: Vec<EntryDto> = Entry::find()
+            .filter(entry::Column::VaultId.eq(vault_id))
+            .order_by_asc(entry::Column::CreatedAt)
+            .order_by_asc(entry::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(EntryDto::from)
+            .collect();
+        DerivedFieldService::attach(conn, &mut entries).await?;
+
+        let entries = entries
+            .into_iter()
+            .map(|e| {
+                let mut stored = MetadataService::parse_metadata(e.metadata.as_deref());
+                let mut values = Map::new();
+
+                for field in &fields {
+                    let value = if field.field_type.is_derived() {
+                        e.computed.get(&field.id.to_string()).cloned()
+                    } else {
+                        stored.remove(&field.id)
+                    };
+                    if let Some(value) = value {
+                        values.insert(field.name.clone(), choice_labels(field, &value));
+                    }
+                }
+
+                ExportedEntry {
+                    id: e.id,
+                    title: e.title,
+                    description: e.description,
+                    values,
+                    created_at: e.created_at,
+                    updated_at: e.updated_at,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        log::info!(
+            "Exported {} entries from vault {} (id={})",
+            entries.len(),
+            vault.name,
+            vault.id
+        );
+
+        Ok(VaultExport {
+            vault_id: vault.id,
+            vault_name: vault.name,
+            fields,
+            entries,
+        })
+    }
+}
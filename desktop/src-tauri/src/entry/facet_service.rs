@@ -0,0 +1,446 @@
+//! Entry facet service for filtering, choice counts and numeric summaries.
+//!
+//! Select values are stored as a choice ID and multiselect values as an
+//! array of choice IDs inside the metadata JSON. SQLite's `json_each` reads both
+//! shapes, so the same filters work for either field type. Number and
+//! rating values are compared with `json_extract`, as are the normalized
+//! strings of dates and timestamps. Location filters search the spatial
+//! index kept by [`LocationIndexService`].
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Select};
+use serde_json::Value;
+
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::field::{FieldService, FieldType};
+use crate::location::LocationIndexService;
+
+use super::{
+    ChoiceCount, ChoiceFilter, ChoiceMatch, EntryFilters, MetadataService, NumericSummary,
+    RangeBound, RangeFilter,
+};
+
+/// Entry metadata as SQL, with unparseable metadata read as NULL.
+pub(super) const METADATA_JSON: &str = "CASE WHEN json_valid(entries.metadata) THEN entries.metadata END";
+
+/// Service for entry filters, per-choice counts and numeric summaries.
+pub struct EntryFacetService;
+
+impl EntryFacetService {
+    /// Builds the entry query for a vault, restricted by filters.
+    ///
+    /// Filters are combined with AND.
+    pub(crate) fn filtered_query(vault_id: i32, filters: &EntryFilters) -> Select<Entry> {
+        let mut query = Entry::find().filter(entry::Column::VaultId.eq(vault_id));
+        for condition in filters.choices.iter().filter_map(Self::choice_condition) {
+            query = query.filter(condition);
+        }
+        for filter in &filters.ranges {
+            query = query.filter(Self::range_condition(filter));
+        }
+        for filter in &filters.within_bounds {
+            query = query.filter(LocationIndexService::bounds_condition(filter));
+        }
+        for filter in &filters.within_distance {
+            query = query.filter(LocationIndexService::distance_condition(filter));
+        }
+        query
+    }
+
+    /// Counts entries per choice of a select or multiselect field.
+    ///
+    /// Counts are taken over the entries matching `filters`, so they narrow
+    /// as filters are added. Every configured choice is listed in option
+    /// order; values that are no longer a choice follow alphabetically.
+    pub async fn choice_counts(
+        conn: &DatabaseConnection,
+        field_id: i32,
+        filters: &EntryFilters,
+    ) -> AppResult<Vec<ChoiceCount>> {
+        let field = FieldService::get(conn, field_id).await?;
+        if !matches!(field.field_type, FieldType::Select | FieldType::Multiselect) {
+            return Err(AppError::Validation(format!(
+                "Field '{}' is not a select or multiselect field",
+                field.name
+            )));
+        }
+
+        let rows = Self::filtered_metadata(conn, field.vault_id, filters).await?;
+
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for metadata in rows {
+            let value = MetadataService::parse_metadata(metadata.as_deref()).remove(&field.id);
+            let selected: HashSet<&str> = match &value {
+                Some(Value::String(choice)) => HashSet::from([choice.as_str()]),
+                Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+                _ => HashSet::new(),
+            };
+            for choice in selected {
+                *counts.entry(choice.to_string()).or_default() += 1;
+            }
+        }
+
+        let choices = field.options.and_then(|o| o.choices).unwrap_or_default();
+        let mut result: Vec<ChoiceCount> = choices
+            .into_iter()
+            .map(|choice| ChoiceCount {
+                count: counts.remove(&choice.id).unwrap_or(0),
+                choice: choice.id,
+                label: choice.label,
+                color: choice.color,
+            })
+            .collect();
+
+        let mut stale: Vec<ChoiceCount> = counts
+            .into_iter()
+            .map(|(choice, count)| ChoiceCount {
+                label: choice.clone(),
+                choice,
+                color: None,
+                count,
+            })
+            .collect();
+        stale.sort_by(|a, b| a.choice.cmp(&b.choice));
+        result.extend(stale);
+
+        Ok(result)
+    }
+
+    /// Summarizes a number, rating or duration field over the entries matching
+    /// `filters`. Entries without a value are left out.
+    pub async fn numeric_summary(
+        conn: &DatabaseConnection,
+        field_id: i32,
+        filters: &EntryFilters,
+    ) -> AppResult<NumericSummary> {
+        let field = FieldService::get(conn, field_id).await?;
+        if !field.field_type.is_numeric() {
+            return Err(AppError::Validation(format!(
+                "Field '{}' is not a number, rating or duration field",
+                field.name
+            )));
+        }
+
+        let values: Vec<f64> = Self::filtered_metadata(conn, field.vault_id, filters)
+            .await?
+            .into_iter()
+            .filter_map(|metadata| {
+                MetadataService::parse_metadata(metadata.as_deref())
+                    .remove(&field.id)
+                    .and_then(|v| v.as_f64())
+            })
+            .collect();
+
+        let count = values.len() as u64;
+        Ok(NumericSummary {
+            count,
+            average: (count > 0).then(|| values.iter().sum::<f64>() / count as f64),
+            min: values.iter().copied().reduce(f64::min),
+            max: values.iter().copied().reduce(f64::max),
+        })
+    }
+
+    /// Loads the raw metadata of the entries matching `filters`.
+    async fn filtered_metadata(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        filters: &EntryFilters,
+    ) -> AppResult<Vec<Option<String>>> {
+        Ok(Self::filtered_query(vault_id, filters)
+            .select_only()
+            .column(entry::Column::Metadata)
+            .into_tuple()
+            .all(conn)
+            .await?)
+    }
+
+    /// Builds the SQL condition for one range filter.
+    /// Entries without a value of the bounds' kind never match.
+    fn range_condition(filter: &RangeFilter) -> SimpleExpr {
+        let path = format!("$.\"{}\"", filter.field_id);
+        let types = match (&filter.min, &filter.max) {
+            (None, None) => "'integer', 'real', 'text'",
+            (Some(RangeBound::Text(_)), _) | (_, Some(RangeBound::Text(_))) => "'text'",
+            _ => "'integer', 'real'",
+        };
+        let value = format!("json_extract({}, ?)", METADATA_JSON);
+
+        let mut sql = format!("json_type({}, ?) IN ({})", METADATA_JSON, types);
+        let mut values: Vec<sea_orm::Value> = vec![path.clone().into()];
+
+        if let Some(min) = &filter.min {
+            sql.push_str(&format!(" AND {} >= ?", value));
+            values.extend([path.clone().into(), Self::bound_value(min)]);
+        }
+        match &filter.max {
+            // Compare only as much of the value as the bound spells out
+            Some(RangeBound::Text(max)) => {
+                sql.push_str(&format!(" AND substr({}, 1, length(?)) <= ?", value));
+                values.extend([path.into(), max.clone().into(), max.clone().into()]);
+            }
+            Some(max) => {
+                sql.push_str(&format!(" AND {} <= ?", value));
+                values.extend([path.into(), Self::bound_value(max)]);
+            }
+            None => {}
+        }
+
+        Expr::cust_with_values(sql, values)
+    }
+
+    fn bound_value(bound: &RangeBound) -> sea_orm::Value {
+        match bound {
+            RangeBound::Number(n) => (*n).into(),
+            RangeBound::Text(t) => t.clone().into(),
+        }
+    }
+
+    /// Builds the SQL condition for one choice filter.
+    /// Returns None for filters without choices, which match every entry.
+    fn choice_condition(filter: &ChoiceFilter) -> Option<SimpleExpr> {
+        let mut seen = HashSet::new();
+        let choices: Vec<&String> = filter.choices.iter().filter(|c| seen.insert(*c)).collect();
+        if choices.is_empty() {
+            return None;
+        }
+
+        let placeholders = vec!["?"; choices.len()].join(", ");
+        let selected = format!(
+            "SELECT value FROM json_each({}, ?) WHERE value IN ({})",
+            METADATA_JSON, placeholders
+        );
+        let sql = match filter.mode {
+            ChoiceMatch::Any => format!("EXISTS ({})", selected),
+            ChoiceMatch::All => format!(
+                "(SELECT COUNT(DISTINCT value) FROM ({})) = {}",
+                selected,
+                choices.len()
+            ),
+        };
+
+        let mut values: Vec<sea_orm::Value> = vec![format!("$.\"{}\"", filter.field_id).into()];
+        values.extend(choices.into_iter().map(|c| c.clone().into()));
+
+        Some(Expr::cust_with_values(sql, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT INTO vaults (name) VALUES ('Movies');
+            INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'Genres', 'multiselect', '{"choices": ["Drama", "Thriller", "Comedy"]}'),
+                (1, 'Format', 'select', '{"choices": ["Film", "Series"]}'),
+                (1, 'Notes', 'text', NULL),
+                (1, 'Score', 'rating', '{"max": 5, "step": 0.5}'),
+                (1, 'Released', 'partialdate', NULL);
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'Heat', '{"1": ["Drama", "Thriller"], "2": "Film", "4": 4.5, "5": "1995-12-15"}'),
+                (1, 'Fargo', '{"1": ["Drama", "Crime"], "2": "Series", "4": 4, "5": "1996"}'),
+                (1, 'Airplane!', '{"1": ["Comedy"], "2": "Film", "4": 3, "5": "1980-07"}'),
+                (1, 'Untagged', NULL),
+                (1, 'Broken', 'not json');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    async fn titles(conn: &DatabaseConnection, filters: &EntryFilters) -> Vec<String> {
+        let mut titles: Vec<String> = EntryFacetService::filtered_query(1, filters)
+            .all(conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn filter(field_id: i32, choices: &[&str], mode: ChoiceMatch) -> ChoiceFilter {
+        ChoiceFilter {
+            field_id,
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+            mode,
+        }
+    }
+
+    fn by_choices(choices: Vec<ChoiceFilter>) -> EntryFilters {
+        EntryFilters {
+            choices,
+            ..Default::default()
+        }
+    }
+
+    fn by_range(field_id: i32, min: Option<RangeBound>, max: Option<RangeBound>) -> EntryFilters {
+        EntryFilters {
+            ranges: vec![RangeFilter { field_id, min, max }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_contains_any_and_all() {
+        let conn = setup_test_db().await;
+
+        let any = filter(1, &["Thriller", "Comedy"], ChoiceMatch::Any);
+        assert_eq!(
+            titles(&conn, &by_choices(vec![any])).await,
+            vec!["Airplane!", "Heat"]
+        );
+
+        let all = filter(1, &["Drama", "Thriller"], ChoiceMatch::All);
+        assert_eq!(titles(&conn, &by_choices(vec![all])).await, vec!["Heat"]);
+
+        // Single-select values and combined filters
+        let drama = filter(1, &["Drama"], ChoiceMatch::Any);
+        let film = filter(2, &["Film"], ChoiceMatch::Any);
+        assert_eq!(
+            titles(&conn, &by_choices(vec![drama, film])).await,
+            vec!["Heat"]
+        );
+
+        // A filter without choices matches everything
+        let empty = by_choices(vec![filter(1, &[], ChoiceMatch::All)]);
+        assert_eq!(titles(&conn, &empty).await.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_range() {
+        let conn = setup_test_db().await;
+        let number = |n| Some(RangeBound::Number(n));
+        let text = |t: &str| Some(RangeBound::Text(t.to_string()));
+
+        assert_eq!(
+            titles(&conn, &by_range(4, number(4.0), None)).await,
+            vec!["Fargo", "Heat"]
+        );
+        assert_eq!(
+            titles(&conn, &by_range(4, number(3.0), number(4.0))).await,
+            vec!["Airplane!", "Fargo"]
+        );
+        // Without bounds the filter keeps entries that have a value
+        assert_eq!(titles(&conn, &by_range(4, None, None)).await.len(), 3);
+
+        // Dates compare as text; an upper bound covers the dates it prefixes
+        assert_eq!(
+            titles(&conn, &by_range(5, text("1990"), None)).await,
+            vec!["Fargo", "Heat"]
+        );
+        assert_eq!(
+            titles(&conn, &by_range(5, None, text("1995"))).await,
+            vec!["Airplane!", "Heat"]
+        );
+        assert_eq!(
+            titles(&conn, &by_range(5, text("1980-07"), text("1980-07"))).await,
+            vec!["Airplane!"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_choice_counts() {
+        let conn = setup_test_db().await;
+
+        let counts = EntryFacetService::choice_counts(&conn, 1, &EntryFilters::default())
+            .await
+            .unwrap();
+        let summary: Vec<_> = counts
+            .iter()
+            .map(|c| (c.choice.as_str(), c.count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Drama", 2), ("Thriller", 1), ("Comedy", 1), ("Crime", 1)]
+        );
+
+        let films = by_choices(vec![filter(2, &["Film"], ChoiceMatch::Any)]);
+        let counts = EntryFacetService::choice_counts(&conn, 1, &films)
+            .await
+            .unwrap();
+        assert_eq!(counts[0].count, 1);
+
+        assert!(
+            EntryFacetService::choice_counts(&conn, 3, &EntryFilters::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_numeric_summary() {
+        let conn = setup_test_db().await;
+
+        let summary = EntryFacetService::numeric_summary(&conn, 4, &EntryFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.min, Some(3.0));
+        assert_eq!(summary.max, Some(4.5));
+        assert!((summary.average.unwrap() - 23.0 / 6.0).abs() < 1e-9);
+
+        let films = by_choices(vec![filter(2, &["Film"], ChoiceMatch::Any)]);
+        let summary = EntryFacetService::numeric_summary(&conn, 4, &films)
+            .await
+            .unwrap();
+        assert_eq!(summary.average, Some(3.75));
+
+        assert!(
+            EntryFacetService::numeric_summary(&conn, 1, &EntryFilters::default())
+                .await
+                .is_err()
+        );
+    }
+}
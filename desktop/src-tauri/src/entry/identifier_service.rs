@@ -0,0 +1,65 @@
+//! Entry identifier service for lookups by identifier.
+//!
+//! Identifier values are stored in their normalized form, so entries are
+//! matched with a plain `json_extract` equality whatever form the
+//! identifier was typed in.
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::field::{FieldDefinitionDto, FieldService, FieldType, normalize_identifier};
+
+use super::facet_service::METADATA_JSON;
+use super::{EntryDto, EntryService};
+
+/// Service for lookups by identifier.
+pub struct EntryIdentifierService;
+
+impl EntryIdentifierService {
+    /// Finds the entry of the field's vault holding an identifier, given in
+    /// any form the field's scheme accepts.
+    ///
+    /// Returns the first match by ID when the field is not unique.
+    pub async fn find_entry(
+        conn: &DatabaseConnection,
+        field_id: i32,
+        value: &str,
+    ) -> AppResult<Option<EntryDto>> {
+        let field = FieldService::get(conn, field_id).await?;
+        if field.field_type != FieldType::Identifier {
+            return Err(AppError::Validation(format!(
+                "Field '{}' is not an identifier field",
+                field.name
+            )));
+        }
+
+        let identifier = normalize_identifier(field.options.as_ref(), value)
+            .map_err(|e| AppError::Validation(format!("Field '{}': {}", field.name, e)))?;
+        let id: Option<i32> = Self::holders(&field, &identifier)
+            .select_only()
+            .column(entry::Column::Id)
+            .into_tuple()
+            .one(conn)
+            .await?;
+
+        match id {
+            Some(id) => Ok(Some(EntryService::get(conn, id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Entries of the field's vault whose value is the normalized identifier.
+    fn holders(field: &FieldDefinitionDto, identifier: &str) -> Select<Entry> {
+        let path = format!("$.\"{}\"", field.id);
+        Entry::find()
+            .filter(entry::Column::VaultId.eq(field.vault_id))
+            .filter(Expr::cust_with_values(
+                format!("json_extract({}, ?) = ?", METADATA_JSON),
+                [path, identifier.to_string()],
+            ))
+            .order_by_asc(entry::Column::Id)
+    }
+}
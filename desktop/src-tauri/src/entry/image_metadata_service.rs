@@ -0,0 +1,627 @@
+//! Image metadata service for mapping EXIF/XMP tags into entry fields.
+//!
+//! Each vault can map extracted image tags (camera, capture date, GPS, ...)
+//! to its field definitions. When a cover is imported, mapped values are
+//! converted to the field's type, validated like any other metadata, and
+//! written into fields that do not have a value yet.
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{AppError, AppResult, find_vault_or_error, now_formatted};
+use crate::entities::entry::{self, ActiveModel as EntryActiveModel};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::entities::image_metadata_mapping::{
+    self, ActiveModel as MappingActiveModel, Entity as ImageMetadataMapping,
+};
+use crate::field::{FieldDefinitionDto, FieldType};
+use crate::image::{ImageMetadata, ImageTag, ImageTagMapping, ImageTagValue};
+use crate::location::LocationIndexService;
+use crate::relation::RelationIndexService;
+
+use super::MetadataService;
+use super::constraint_service::EntryConstraintService;
+use super::temporal::normalize_local_datetime;
+
+/// Service for image tag to field mappings.
+pub struct ImageMetadataService;
+
+impl ImageMetadataService {
+    /// Lists the tag mappings configured for a vault.
+    pub async fn list_mappings(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+    ) -> AppResult<Vec<ImageTagMapping>> {
+        find_vault_or_error(conn, vault_id).await?;
+
+        let mappings = ImageMetadataMapping::find()
+            .filter(image_metadata_mapping::Column::VaultId.eq(vault_id))
+            .order_by_asc(image_metadata_mapping::Column::Id)
+            .all(conn)
+            .await?;
+
+        Ok(mappings
+            .into_iter()
+            .filter_map(|m| {
+                Some(ImageTagMapping {
+                    tag: ImageTag::from_str(&m.tag)?,
+                    field_id: m.field_id,
+                })
+            })
+            .collect())
+    }
+
+    /// Replaces the tag mappings of a vault.
+    ///
+    /// Every field must belong to the vault and have a type the tag's value
+    /// can be converted to. Each tag can be mapped once.
+    pub async fn set_mappings(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        mappings: Vec<ImageTagMapping>,
+    ) -> AppResult<Vec<ImageTagMapping>> {
+        find_vault_or_error(conn, vault_id).await?;
+
+        let fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.eq(vault_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+
+        let mut seen = HashSet::new();
+        for mapping in &mappings {
+            if !seen.insert(mapping.tag) {
+                return Err(AppError::Validation(format!(
+                    "Tag '{}' is mapped more than once",
+                    mapping.tag.as_str()
+                )));
+            }
+
+            let field = fields.get(&mapping.field_id).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Field {} does not belong to this vault",
+                    mapping.field_id
+                ))
+            })?;
+
+            if !Self::is_compatible(mapping.tag, field.field_type) {
+                return Err(AppError::Validation(format!(
+                    "Tag '{}' cannot be mapped to {} field '{}'",
+                    mapping.tag.as_str(),
+                    field.field_type.as_str(),
+                    field.name
+                )));
+            }
+        }
+
+        let txn = conn.begin().await?;
+
+        ImageMetadataMapping::delete_many()
+            .filter(image_metadata_mapping::Column::VaultId.eq(vault_id))
+            .exec(&txn)
+            .await?;
+
+        let now = now_formatted();
+        for mapping in &mappings {
+            MappingActiveModel {
+                vault_id: Set(vault_id),
+                tag: Set(mapping.tag.as_str().to_string()),
+                field_id: Set(mapping.field_id),
+                created_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        log::info!(
+            "Set {} image metadata mapping(s) for vault {}",
+            mappings.len(),
+            vault_id
+        );
+
+        Ok(mappings)
+    }
+
+    /// Writes mapped image metadata into an entry's empty fields.
+    ///
+    /// Values that fail field validation, or would break a unique field or
+    /// a rule of the vault, are skipped with a warning, so an unexpected tag
+    /// never blocks the image import. Returns the updated
+    /// entry, or `None` if nothing was written.
+    pub async fn apply_to_entry(
+        conn: &DatabaseConnection,
+        entry: entry::Model,
+        image_metadata: &ImageMetadata,
+    ) -> AppResult<Option<entry::Model>> {
+        if image_metadata.tags.is_empty() {
+            return Ok(None);
+        }
+
+        let mappings = ImageMetadataMapping::find()
+            .filter(image_metadata_mapping::Column::VaultId.eq(entry.vault_id))
+            .all(conn)
+            .await?;
+        if mappings.is_empty() {
+            return Ok(None);
+        }
+
+        let fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.eq(entry.vault_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+
+        let mut metadata: Map<String, Value> = entry
+            .metadata
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+
+        let violations_before: HashSet<(i32, String)> =
+            EntryConstraintService::violations(conn, entry.vault_id, Some(entry.id), &metadata)
+                .await?
+                .into_iter()
+                .collect();
+
+        let mut changed = false;
+        for mapping in mappings {
+            let (Some(tag), Some(field)) =
+                (ImageTag::from_str(&mapping.tag), fields.get(&mapping.field_id))
+            else {
+                continue;
+            };
+            let Some(tag_value) = image_metadata.tags.get(&tag) else {
+                continue;
+            };
+
+            // Never overwrite what the user already entered
+            let key = field.id.to_string();
+            if metadata.get(&key).is_some_and(|v| !v.is_null()) {
+                continue;
+            }
+
+            let Some(value) = Self::to_field_value(tag_value, field.field_type) else {
+                continue;
+            };
+
+            // Select values are matched to a choice by label
+            let value = MetadataService::normalize_field_value(field, &value).and_then(|value| {
+                MetadataService::validate_field_value(field, &value).map(|_| value)
+            });
+            let value = match value {
+                Ok(value) => value,
+                Err(e) => {
+                    log::warn!(
+                        "Skipping image tag '{}' for entry {}: {}",
+                        tag.as_str(),
+                        entry.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            // Unique fields and rules are checked like any entry update;
+            // only errors the value brings count, not earlier ones
+            metadata.insert(key.clone(), value);
+            let violations =
+                EntryConstraintService::violations(conn, entry.vault_id, Some(entry.id), &metadata)
+                    .await?;
+            if let Some((_, error)) = violations.iter().find(|v| !violations_before.contains(v)) {
+                log::warn!(
+                    "Skipping image tag '{}' for entry {}: {}",
+                    tag.as_str(),
+                    entry.id,
+                    error
+                );
+                metadata.remove(&key);
+                continue;
+            }
+            changed = true;
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        let entry_id = entry.id;
+        let vault_id = entry.vault_id;
+        let metadata = Value::Object(metadata).to_string();
+        let mut active_model: EntryActiveModel = entry.into();
+        active_model.search_text =
+            Set(MetadataService::search_text(conn, vault_id, Some(&metadata)).await?);
+        active_model.metadata = Set(Some(metadata));
+        active_model.updated_at = Set(now_formatted());
+
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        RelationIndexService::sync_entry(&txn, result.id, vault_id, result.metadata.as_deref())
+            .await?;
+        LocationIndexService::sync_entry(&txn, result.id, vault_id, result.metadata.as_deref())
+            .await?;
+        txn.commit().await?;
+        log::info!("Applied image metadata to entry {}", entry_id);
+
+        Ok(Some(result))
+    }
+
+    /// Whether a tag's values can be stored in a field of the given type.
+    fn is_compatible(tag: ImageTag, field_type: FieldType) -> bool {
+        match tag {
+            ImageTag::Make | ImageTag::Model | ImageTag::LensModel => {
+                matches!(field_type, FieldType::Text | FieldType::Select)
+            }
+            ImageTag::DateTimeOriginal => matches!(
+                field_type,
+                FieldType::Text | FieldType::Date | FieldType::PartialDate | FieldType::Datetime
+            ),
+            _ => matches!(field_type, FieldType::Text | FieldType::Number),
+        }
+    }
+
+    /// Converts a tag value to the JSON representation of a field type.
+    fn to_field_value(value: &ImageTagValue, field_type: FieldType) -> Option<Value> {
+        match (field_type, value) {
+            (FieldType::Text | FieldType::Select, ImageTagValue::Text(text)) => {
+                Some(Value::String(text.clone()))
+            }
+            (FieldType::Text, ImageTagValue::Number(number)) => {
+                Some(Value::String(number.to_string()))
+            }
+            (FieldType::Text, ImageTagValue::DateTime(date_time)) => Some(Value::String(
+                date_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            )),
+            (FieldType::Number, ImageTagValue::Number(number)) => {
+                serde_json::Number::from_f64(*number).map(Value::Number)
+            }
+            (FieldType::Date | FieldType::PartialDate, ImageTagValue::DateTime(date_time)) => {
+                Some(Value::String(date_time.format("%Y-%m-%d").to_string()))
+            }
+            // Capture times carry no timezone and are taken as local time
+            (FieldType::Datetime, ImageTagValue::DateTime(date_time)) => {
+                normalize_local_datetime(*date_time).map(Value::String)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::entry::Entity as Entry;
+    use sea_orm::{ConnectionTrait, Database};
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("Failed to connect to test database");
+
+        conn.execute_unprepared(
+            r#"
+            PRAGMA foreign_keys = ON;
+
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            CREATE TABLE image_metadata_mappings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(vault_id, tag)
+            );
+
+            INSERT INTO vaults (name) VALUES ('Photos'), ('Other');
+            INSERT INTO field_definitions (vault_id, name, field_type, position)
+            VALUES (1, 'Taken', 'date', 0), (1, 'Camera', 'text', 1), (1, 'Aperture', 'number', 2),
+                   (2, 'Elsewhere', 'text', 0), (1, 'Year', 'partialdate', 3),
+                   (1, 'Shot at', 'datetime', 4);
+            INSERT INTO entries (vault_id, title, metadata) VALUES (1, 'Sunset', '{"2":"Kept"}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    fn sample_metadata() -> ImageMetadata {
+        let mut metadata = ImageMetadata::default();
+        metadata.tags.insert(
+            ImageTag::DateTimeOriginal,
+            ImageTagValue::DateTime(
+                chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 30, 0)
+                    .unwrap(),
+            ),
+        );
+        metadata
+            .tags
+            .insert(ImageTag::Model, ImageTagValue::Text("X-T5".to_string()));
+        metadata
+            .tags
+            .insert(ImageTag::FNumber, ImageTagValue::Number(2.8));
+        metadata
+    }
+
+    fn mapping(tag: ImageTag, field_id: i32) -> ImageTagMapping {
+        ImageTagMapping { tag, field_id }
+    }
+
+    #[tokio::test]
+    async fn test_set_and_list_mappings() {
+        let conn = setup_test_db().await;
+
+        let mappings = vec![
+            mapping(ImageTag::DateTimeOriginal, 1),
+            mapping(ImageTag::Model, 2),
+        ];
+        ImageMetadataService::set_mappings(&conn, 1, mappings.clone())
+            .await
+            .unwrap();
+
+        let listed = ImageMetadataService::list_mappings(&conn, 1).await.unwrap();
+        assert_eq!(listed, mappings);
+
+        // Setting again replaces the previous mappings
+        ImageMetadataService::set_mappings(&conn, 1, vec![mapping(ImageTag::FNumber, 3)])
+            .await
+            .unwrap();
+        let listed = ImageMetadataService::list_mappings(&conn, 1).await.unwrap();
+        assert_eq!(listed, vec![mapping(ImageTag::FNumber, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_set_mappings_validation() {
+        let conn = setup_test_db().await;
+
+        // Field from another vault
+        assert!(
+            ImageMetadataService::set_mappings(&conn, 1, vec![mapping(ImageTag::Model, 4)])
+                .await
+                .is_err()
+        );
+        // Camera model into a date field
+        assert!(
+            ImageMetadataService::set_mappings(&conn, 1, vec![mapping(ImageTag::Model, 1)])
+                .await
+                .is_err()
+        );
+        // Same tag twice
+        assert!(ImageMetadataService::set_mappings(
+            &conn,
+            1,
+            vec![mapping(ImageTag::Model, 2), mapping(ImageTag::Model, 2)],
+        )
+        .await
+        .is_err());
+        assert!(ImageMetadataService::set_mappings(&conn, 999, vec![])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_to_entry_fills_empty_fields() {
+        let conn = setup_test_db().await;
+
+        ImageMetadataService::set_mappings(
+            &conn,
+            1,
+            vec![
+                mapping(ImageTag::DateTimeOriginal, 1),
+                mapping(ImageTag::Model, 2),
+                mapping(ImageTag::FNumber, 3),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let entry = Entry::find_by_id(1).one(&conn).await.unwrap().unwrap();
+        let updated = ImageMetadataService::apply_to_entry(&conn, entry, &sample_metadata())
+            .await
+            .unwrap()
+            .expect("entry should be updated");
+
+        let metadata: Map<String, Value> =
+            serde_json::from_str(updated.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(metadata["1"], "2024-05-01");
+        // Existing values are kept
+        assert_eq!(metadata["2"], "Kept");
+        assert_eq!(metadata["3"], 2.8);
+    }
+
+    #[tokio::test]
+    async fn test_apply_capture_time_to_date_fields() {
+        let conn = setup_test_db().await;
+
+        for field_id in [5, 6] {
+            ImageMetadataService::set_mappings(
+                &conn,
+                1,
+                vec![mapping(ImageTag::DateTimeOriginal, field_id)],
+            )
+            .await
+            .unwrap();
+
+            let entry = Entry::find_by_id(1).one(&conn).await.unwrap().unwrap();
+            let updated = ImageMetadataService::apply_to_entry(&conn, entry, &sample_metadata())
+                .await
+                .unwrap()
+                .expect("entry should be updated");
+
+            let metadata: Map<String, Value> =
+                serde_json::from_str(updated.metadata.as_deref().unwrap()).unwrap();
+            let expected = match field_id {
+                5 => "2024-05-01".to_string(),
+                _ => normalize_local_datetime(
+                    chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+                        .unwrap()
+                        .and_hms_opt(10, 30, 0)
+                        .unwrap(),
+                )
+                .unwrap(),
+            };
+            assert_eq!(metadata[&field_id.to_string()], expected.as_str());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_to_entry_without_mappings() {
+        let conn = setup_test_db().await;
+
+        let entry = Entry::find_by_id(1).one(&conn).await.unwrap().unwrap();
+        let result = ImageMetadataService::apply_to_entry(&conn, entry, &sample_metadata())
+            .await
+            .unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_to_entry_skips_invalid_values() {
+        let conn = setup_test_db().await;
+
+        conn.execute_unprepared(r#"UPDATE field_definitions SET options = '{"max":2.0}' WHERE id = 3"#)
+            .await
+            .unwrap();
+        ImageMetadataService::set_mappings(&conn, 1, vec![mapping(ImageTag::FNumber, 3)])
+            .await
+            .unwrap();
+
+        let entry = Entry::find_by_id(1).one(&conn).await.unwrap().unwrap();
+        let result = ImageMetadataService::apply_to_entry(&conn, entry, &sample_metadata())
+            .await
+            .unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_to_entry_skips_constraint_violations() {
+        let conn = setup_test_db().await;
+
+        // Another entry already has this camera in a unique field
+        conn.execute_unprepared(
+            r#"
+            UPDATE field_definitions SET options = '{"unique":true}' WHERE id = 2;
+            INSERT INTO entries (vault_id, title, metadata) VALUES (1, 'Dawn', '{"2":"X-T5"}');
+            "#,
+        )
+        .await
+        .unwrap();
+        ImageMetadataService::set_mappings(
+            &conn,
+            1,
+            vec![mapping(ImageTag::Model, 2), mapping(ImageTag::FNumber, 3)],
+        )
+        .await
+        .unwrap();
+
+        let entry = EntryActiveModel {
+            vault_id: Set(1),
+            title: Set("Noon".to_string()),
+            created_at: Set(now_formatted()),
+            updated_at: Set(now_formatted()),
+            ..Default::default()
+        }
+        .insert(&conn)
+        .await
+        .unwrap();
+        let updated = ImageMetadataService::apply_to_entry(&conn, entry, &sample_metadata())
+            .await
+            .unwrap()
+            .expect("entry should be updated");
+
+        let metadata: Map<String, Value> =
+            serde_json::from_str(updated.metadata.as_deref().unwrap()).unwrap();
+        assert!(!metadata.contains_key("2"));
+        assert_eq!(metadata["3"], 2.8);
+    }
+
+    #[tokio::test]
+    async fn test_mappings_removed_with_field() {
+        let conn = setup_test_db().await;
+
+        ImageMetadataService::set_mappings(&conn, 1, vec![mapping(ImageTag::Model, 2)])
+            .await
+            .unwrap();
+        conn.execute_unprepared("DELETE FROM field_definitions WHERE id = 2")
+            .await
+            .unwrap();
+
+        let listed = ImageMetadataService::list_mappings(&conn, 1).await.unwrap();
+        assert!(listed.is_empty());
+    }
+}
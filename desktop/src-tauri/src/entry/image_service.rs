@@ -3,54 +3,161 @@
 //! This service handles all image-related operations for entries:
 //! - Setting cover images from local files
 //! - Setting cover images from URLs
+//! - Editing cover images and reverting to the original
 //! - Removing cover images
+//! - Finding entries with near-identical covers
+//! - Analyzing covers stored before placeholders and hashes were computed
 //! - Generating thumbnails
 
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use std::collections::HashMap;
 
-use crate::core::{AppError, AppResult, now_formatted};
-use crate::entities::entry::{ActiveModel, Entity as Entry};
-use crate::image::{ImageProcessor, ImageStorage};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
+};
 
-use super::EntryDto;
+use crate::core::{AppError, AppResult, find_vault_or_error, now_formatted};
+use crate::entities::entry::{self, ActiveModel, Entity as Entry, Model};
+use crate::image::{ImageProcessor, ImageRegistry, ImageStorage, ImageTransform, StoredImage};
+
+use super::{DuplicateCoverCluster, DuplicateCoverEntry, EntryDto, ImageMetadataService};
+
+/// Default Hamming distance under which covers count as duplicates.
+pub const DEFAULT_DUPLICATE_DISTANCE: u32 = 6;
+
+/// Largest accepted distance threshold; beyond this, unrelated covers match.
+const MAX_DUPLICATE_DISTANCE: u32 = 24;
+
+/// Bits of the perceptual hash in each indexed band.
+const HASH_BAND_BITS: u32 = 8;
+
+/// Entries loaded at a time when analyzing existing covers.
+const COVER_ANALYSIS_BATCH: u64 = 100;
 
 /// Service for entry cover image operations.
 pub struct EntryImageService;
 
 impl EntryImageService {
     /// Sets the cover image for an entry from a local file.
+    ///
+    /// The transform is applied before storing. When `keep_original` is set
+    /// and the transform changed the image, the unedited file is kept so the
+    /// edit can be reverted or redone later. Image metadata mapped to vault
+    /// fields is written into the entry's empty fields.
     pub async fn set_cover_from_file(
         conn: &DatabaseConnection,
         entry_id: i32,
         file_path: &str,
         image_storage: &ImageStorage,
+        transform: &ImageTransform,
+        keep_original: bool,
     ) -> AppResult<EntryDto> {
         let entry = Entry::find_by_id(entry_id)
             .one(conn)
             .await?
             .ok_or(AppError::EntryNotFound(entry_id))?;
 
-        // Delete old image if present
-        if let Some(old_path) = &entry.cover_image_path {
-            let _ = image_storage.delete_image(old_path);
-        }
+        let source_bytes = ImageStorage::read_local_image(std::path::Path::new(file_path))?;
 
-        // Save new image
-        let source_path = std::path::Path::new(file_path);
-        let relative_path = image_storage.save_local_image(entry.vault_id, entry_id, source_path)?;
+        let edited = ImageProcessor::apply_transform(&source_bytes, transform)?;
+        let cover_bytes = edited.as_deref().unwrap_or(&source_bytes);
 
-        // Update entry
-        let now = now_formatted();
-        let mut active_model: ActiveModel = entry.into();
-        active_model.cover_image_path = Set(Some(relative_path));
-        active_model.updated_at = Set(now);
+        // Save new image (deduplicated by content hash)
+        let cover = image_storage.save_image_bytes(cover_bytes)?;
+        let original = if keep_original && edited.is_some() {
+            Some(image_storage.save_image_bytes(&source_bytes)?)
+        } else {
+            None
+        };
+
+        // Read metadata before any edit, since re-encoding drops it
+        let image_metadata = ImageProcessor::extract_metadata(&source_bytes);
+
+        let result =
+            Self::replace_cover(conn, entry, image_storage, cover, cover_bytes, original).await?;
+        let result = ImageMetadataService::apply_to_entry(conn, result.clone(), &image_metadata)
+            .await?
+            .unwrap_or(result);
 
-        let result = active_model.update(conn).await?;
         log::info!("Set cover image for entry {} from file", entry_id);
 
         Ok(EntryDto::from(result))
     }
 
+    /// Re-edits an entry's cover image.
+    ///
+    /// The transform is applied to the kept original when there is one, so
+    /// repeated edits never compound quality loss.
+    pub async fn edit_cover(
+        conn: &DatabaseConnection,
+        entry_id: i32,
+        transform: &ImageTransform,
+        image_storage: &ImageStorage,
+    ) -> AppResult<EntryDto> {
+        let entry = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+
+        let source_path = entry
+            .cover_original_path
+            .clone()
+            .or_else(|| entry.cover_image_path.clone())
+            .ok_or(AppError::Validation("Entry has no cover image".to_string()))?;
+
+        if ImageStorage::is_remote(&source_path) {
+            return Err(AppError::Validation(
+                "Remote cover images cannot be edited".to_string(),
+            ));
+        }
+
+        let source_bytes = image_storage.read_image(&source_path)?;
+        let original = image_storage.save_image_bytes(&source_bytes)?;
+
+        let result = match ImageProcessor::apply_transform(&source_bytes, transform)? {
+            Some(edited) => {
+                let cover = image_storage.save_image_bytes(&edited)?;
+                Self::replace_cover(conn, entry, image_storage, cover, &edited, Some(original))
+                    .await?
+            }
+            // Nothing to edit: the original becomes the cover again
+            None => {
+                Self::replace_cover(conn, entry, image_storage, original, &source_bytes, None)
+                    .await?
+            }
+        };
+
+        log::info!("Edited cover image for entry {}", entry_id);
+
+        Ok(EntryDto::from(result))
+    }
+
+    /// Restores an entry's cover image to its unedited original.
+    pub async fn revert_cover(
+        conn: &DatabaseConnection,
+        entry_id: i32,
+        image_storage: &ImageStorage,
+    ) -> AppResult<EntryDto> {
+        let entry = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+
+        let original_path = entry.cover_original_path.clone().ok_or(AppError::Validation(
+            "Entry has no original cover image to revert to".to_string(),
+        ))?;
+
+        let original_bytes = image_storage.read_image(&original_path)?;
+        let original = image_storage.save_image_bytes(&original_bytes)?;
+        let result =
+            Self::replace_cover(conn, entry, image_storage, original, &original_bytes, None)
+                .await?;
+
+        log::info!("Reverted cover image for entry {}", entry_id);
+
+        Ok(EntryDto::from(result))
+    }
+
     /// Sets the cover image for an entry from a URL.
     pub async fn set_cover_from_url(
         conn: &DatabaseConnection,
@@ -63,21 +170,25 @@ impl EntryImageService {
             .await?
             .ok_or(AppError::EntryNotFound(entry_id))?;
 
-        // Delete old image if present and it's a local file
-        if let Some(old_path) = &entry.cover_image_path {
-            if !old_path.starts_with("http://") && !old_path.starts_with("https://") {
-                let _ = image_storage.delete_image(old_path);
-            }
-        }
+        let old_paths = [entry.cover_image_path.clone(), entry.cover_original_path.clone()];
 
         // Store the URL directly without downloading
         // Update entry
         let now = now_formatted();
         let mut active_model: ActiveModel = entry.into();
         active_model.cover_image_path = Set(Some(url.to_string()));
+        active_model.cover_original_path = Set(None);
+        Self::set_cover_features(&mut active_model, None);
         active_model.updated_at = Set(now);
 
-        let result = active_model.update(conn).await?;
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        Self::sync_hash_bands(&txn, &result).await?;
+        // Release the previous local images, if any
+        let unreferenced = Self::release_covers(&txn, old_paths.iter().flatten()).await?;
+        txn.commit().await?;
+
+        Self::delete_covers(image_storage, &unreferenced);
         log::info!("Set cover image for entry {} from URL", entry_id);
 
         Ok(EntryDto::from(result))
@@ -94,25 +205,231 @@ impl EntryImageService {
             .await?
             .ok_or(AppError::EntryNotFound(entry_id))?;
 
-        // Delete image if present and it's a local file
-        if let Some(old_path) = &entry.cover_image_path {
-            if !old_path.starts_with("http://") && !old_path.starts_with("https://") {
-                let _ = image_storage.delete_image(old_path);
-            }
-        }
+        let old_paths = [entry.cover_image_path.clone(), entry.cover_original_path.clone()];
 
         // Update entry
         let now = now_formatted();
         let mut active_model: ActiveModel = entry.into();
         active_model.cover_image_path = Set(None);
+        active_model.cover_original_path = Set(None);
+        Self::set_cover_features(&mut active_model, None);
         active_model.updated_at = Set(now);
 
-        let result = active_model.update(conn).await?;
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        Self::sync_hash_bands(&txn, &result).await?;
+        // Release the images if present and they're local files
+        let unreferenced = Self::release_covers(&txn, old_paths.iter().flatten()).await?;
+        txn.commit().await?;
+
+        Self::delete_covers(image_storage, &unreferenced);
         log::info!("Removed cover image from entry {}", entry_id);
 
         Ok(EntryDto::from(result))
     }
 
+    /// Lists clusters of entries with near-identical cover images.
+    ///
+    /// Covers are compared by the Hamming distance of their perceptual
+    /// hashes; pairs within `max_distance` end up in the same cluster. Only
+    /// covers the band index pairs up are compared. With no `vault_id`,
+    /// covers are compared across all vaults.
+    pub async fn find_duplicate_covers(
+        conn: &DatabaseConnection,
+        vault_id: Option<i32>,
+        max_distance: u32,
+    ) -> AppResult<Vec<DuplicateCoverCluster>> {
+        if max_distance > MAX_DUPLICATE_DISTANCE {
+            return Err(AppError::Validation(format!(
+                "Distance threshold must be at most {}",
+                MAX_DUPLICATE_DISTANCE
+            )));
+        }
+
+        if let Some(vault_id) = vault_id {
+            find_vault_or_error(conn, vault_id).await?;
+        }
+
+        // Covers of candidate pairs within the threshold, in ID order
+        let pairs: Vec<((i32, u64), (i32, u64))> =
+            Self::candidate_pairs(conn, vault_id, max_distance)
+                .await?
+                .into_iter()
+                .filter(|((_, a), (_, b))| ImageProcessor::hamming_distance(*a, *b) <= max_distance)
+                .collect();
+        let mut covers: Vec<(i32, u64)> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+        covers.sort_unstable();
+        covers.dedup();
+        let index: HashMap<i32, usize> = covers
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (*id, i))
+            .collect();
+
+        // Union-find over the pairs
+        let mut parent: Vec<usize> = (0..covers.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for ((a, _), (b, _)) in &pairs {
+            let (a, b) = (root(&mut parent, index[a]), root(&mut parent, index[b]));
+            parent[a.max(b)] = a.min(b);
+        }
+
+        let entries: HashMap<i32, DuplicateCoverEntry> = Entry::find()
+            .filter(entry::Column::Id.is_in(covers.iter().map(|(id, _)| *id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .filter_map(|e| {
+                Some((
+                    e.id,
+                    DuplicateCoverEntry {
+                        entry_id: e.id,
+                        vault_id: e.vault_id,
+                        title: e.title,
+                        cover_image_path: e.cover_image_path,
+                        cover_phash: e.cover_phash?,
+                    },
+                ))
+            })
+            .collect();
+
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..covers.len() {
+            groups.entry(root(&mut parent, i)).or_default().push(i);
+        }
+
+        let mut clusters: Vec<DuplicateCoverCluster> = groups
+            .into_values()
+            .map(|members| {
+                let max_distance = members
+                    .iter()
+                    .flat_map(|&a| members.iter().map(move |&b| (a, b)))
+                    .map(|(a, b)| ImageProcessor::hamming_distance(covers[a].1, covers[b].1))
+                    .max()
+                    .unwrap_or(0);
+
+                DuplicateCoverCluster {
+                    entries: members
+                        .iter()
+                        .filter_map(|&i| entries.get(&covers[i].0).cloned())
+                        .collect(),
+                    max_distance,
+                }
+            })
+            .filter(|c| c.entries.len() > 1)
+            .collect();
+
+        // Largest clusters first, then by oldest entry
+        clusters.sort_by_key(|c| (std::cmp::Reverse(c.entries.len()), c.entries[0].entry_id));
+
+        Ok(clusters)
+    }
+
+    /// Pairs of covers, with their hashes, that may lie within
+    /// `max_distance` of each other.
+    ///
+    /// Hashes within `max_distance` differ by at most `max_distance / 8`
+    /// bits in one of their eight bands, so each band is looked up in the
+    /// band index with every mask of that many bits flipped.
+    async fn candidate_pairs(
+        conn: &DatabaseConnection,
+        vault_id: Option<i32>,
+        max_distance: u32,
+    ) -> AppResult<Vec<((i32, u64), (i32, u64))>> {
+        let radius = max_distance / HASH_BAND_BITS;
+        let masks: Vec<String> = (0u32..1 << HASH_BAND_BITS)
+            .filter(|mask| mask.count_ones() <= radius)
+            .map(|mask| format!("({})", mask))
+            .collect();
+
+        let mut sql = format!(
+            r#"
+            WITH masks(mask) AS (VALUES {})
+            SELECT DISTINCT a.entry_id, ea.cover_phash, b.entry_id, eb.cover_phash
+            FROM entry_cover_hash_bands a
+            JOIN entries ea ON ea.id = a.entry_id
+            JOIN masks
+            JOIN entry_cover_hash_bands b
+                ON b.band = a.band
+                AND b.value = (a.value | masks.mask) - (a.value & masks.mask)
+            JOIN entries eb ON eb.id = b.entry_id
+            WHERE a.entry_id < b.entry_id
+            "#,
+            masks.join(", ")
+        );
+        let mut values: Vec<sea_orm::Value> = Vec::new();
+        if let Some(vault_id) = vault_id {
+            sql.push_str(" AND ea.vault_id = ? AND eb.vault_id = ?");
+            values.extend([vault_id.into(), vault_id.into()]);
+        }
+
+        let rows = conn
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Sqlite,
+                sql,
+                values,
+            ))
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let hash = |index| {
+                    row.try_get_by_index::<Option<String>>(index)
+                        .ok()
+                        .flatten()
+                        .and_then(|h| ImageProcessor::parse_hash(&h))
+                };
+                Some((
+                    (row.try_get_by_index(0).ok()?, hash(1)?),
+                    (row.try_get_by_index(2).ok()?, hash(3)?),
+                ))
+            })
+            .collect())
+    }
+
+    /// Indexes the bands of an entry's cover hash, replacing those of its
+    /// previous cover.
+    async fn sync_hash_bands<C: ConnectionTrait>(conn: &C, entry: &Model) -> AppResult<()> {
+        conn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            "DELETE FROM entry_cover_hash_bands WHERE entry_id = ?",
+            [entry.id.into()],
+        ))
+        .await?;
+
+        let Some(hash) = entry
+            .cover_phash
+            .as_deref()
+            .and_then(ImageProcessor::parse_hash)
+        else {
+            return Ok(());
+        };
+        let bands = 64 / HASH_BAND_BITS;
+        let mut values: Vec<sea_orm::Value> = Vec::new();
+        for band in 0..bands {
+            let value = (hash >> (band * HASH_BAND_BITS)) & ((1 << HASH_BAND_BITS) - 1);
+            values.extend([entry.id.into(), band.into(), (value as i64).into()]);
+        }
+        conn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            format!(
+                "INSERT INTO entry_cover_hash_bands (entry_id, band, value) VALUES {}",
+                vec!["(?, ?, ?)"; bands as usize].join(", ")
+            ),
+            values,
+        ))
+        .await?;
+
+        Ok(())
+    }
+
     /// Gets the thumbnail for an entry's cover image.
     pub fn get_thumbnail(
         entry: &EntryDto,
@@ -123,7 +440,7 @@ impl EntryImageService {
             .as_ref()
             .ok_or(AppError::Validation("Entry has no cover image".to_string()))?;
 
-        let full_path = image_storage.get_full_path(cover_path);
+        let full_path = image_storage.get_full_path(cover_path)?;
 
         if !full_path.exists() {
             return Err(AppError::Internal(format!(
@@ -134,6 +451,159 @@ impl EntryImageService {
 
         ImageProcessor::generate_thumbnail(&full_path)
     }
+
+    /// Points an entry at newly stored cover images.
+    ///
+    /// New references are taken before the old ones are dropped, in the
+    /// same transaction as the update, so storing the same image again never
+    /// deletes its file. The cover's placeholder is recomputed from
+    /// `cover_bytes`.
+    async fn replace_cover(
+        conn: &DatabaseConnection,
+        entry: Model,
+        image_storage: &ImageStorage,
+        cover: StoredImage,
+        cover_bytes: &[u8],
+        original: Option<StoredImage>,
+    ) -> AppResult<Model> {
+        let old_paths = [entry.cover_image_path.clone(), entry.cover_original_path.clone()];
+
+        // Update entry
+        let now = now_formatted();
+        let mut active_model: ActiveModel = entry.into();
+        active_model.cover_image_path = Set(Some(cover.relative_path.clone()));
+        active_model.cover_original_path = Set(original.as_ref().map(|o| o.relative_path.clone()));
+        Self::set_cover_features(&mut active_model, Some(cover_bytes));
+        active_model.updated_at = Set(now);
+
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        Self::sync_hash_bands(&txn, &result).await?;
+        for stored in std::iter::once(&cover).chain(&original) {
+            ImageRegistry::register(&txn, stored).await?;
+            ImageRegistry::acquire(&txn, &stored.relative_path).await?;
+        }
+        let unreferenced = Self::release_covers(&txn, old_paths.iter().flatten()).await?;
+        txn.commit().await?;
+
+        Self::delete_covers(image_storage, &unreferenced);
+
+        Ok(result)
+    }
+
+    /// Stores the placeholder and perceptual hash derived from a cover.
+    ///
+    /// Passing `None` clears them. Analysis failures are logged instead of
+    /// failing: the image has already been validated, and missing data only
+    /// means a blank placeholder and no duplicate detection for this cover.
+    fn set_cover_features(active_model: &mut ActiveModel, cover_bytes: Option<&[u8]>) {
+        let img = cover_bytes.and_then(|bytes| {
+            ImageProcessor::decode_bytes(bytes)
+                .map_err(|e| log::warn!("Failed to analyze cover image: {}", e))
+                .ok()
+        });
+
+        let placeholder = img.as_ref().map(ImageProcessor::extract_placeholder);
+        active_model.cover_blurhash = Set(placeholder.as_ref().map(|p| p.blurhash.clone()));
+        active_model.cover_palette =
+            Set(placeholder.and_then(|p| serde_json::to_string(&p.palette).ok()));
+        active_model.cover_phash = Set(img
+            .as_ref()
+            .map(|img| ImageProcessor::format_hash(ImageProcessor::perceptual_hash(img))));
+    }
+
+    /// Derives the placeholder, palette and perceptual hash of local covers
+    /// stored before they were computed on save, so list views of an
+    /// existing library do not show blank boxes and its covers take part
+    /// in duplicate detection. Returns the number of entries updated.
+    ///
+    /// Unreadable covers are logged and skipped; they are tried again on
+    /// the next pass.
+    pub async fn analyze_existing_covers(
+        conn: &DatabaseConnection,
+        image_storage: &ImageStorage,
+    ) -> AppResult<usize> {
+        let mut updated = 0;
+        let mut last_id = 0;
+        loop {
+            // Walk by ID: updated entries drop out of the filter
+            let batch = Entry::find()
+                .filter(entry::Column::Id.gt(last_id))
+                .filter(entry::Column::CoverImagePath.is_not_null())
+                .filter(
+                    Condition::any()
+                        .add(entry::Column::CoverBlurhash.is_null())
+                        .add(entry::Column::CoverPhash.is_null()),
+                )
+                .order_by_asc(entry::Column::Id)
+                .limit(COVER_ANALYSIS_BATCH)
+                .all(conn)
+                .await?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            last_id = last.id;
+
+            for entry in batch {
+                let Some(path) = entry.cover_image_path.clone() else {
+                    continue;
+                };
+                if ImageStorage::is_remote(&path) {
+                    continue;
+                }
+                let bytes = match image_storage.read_image(&path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        log::warn!("Skipping cover analysis of entry {}: {}", entry.id, e);
+                        continue;
+                    }
+                };
+
+                let mut active_model: ActiveModel = entry.into();
+                Self::set_cover_features(&mut active_model, Some(&bytes));
+                let txn = conn.begin().await?;
+                let result = active_model.update(&txn).await?;
+                Self::sync_hash_bands(&txn, &result).await?;
+                txn.commit().await?;
+                updated += 1;
+            }
+        }
+
+        if updated > 0 {
+            log::info!("Analyzed {} existing cover image(s)", updated);
+        }
+        Ok(updated)
+    }
+
+    /// Releases cover image references within the caller's transaction.
+    ///
+    /// Returns the paths left unreferenced, to be deleted with
+    /// [`Self::delete_covers`] once the transaction has committed.
+    pub async fn release_covers<'a, C: ConnectionTrait>(
+        conn: &C,
+        cover_paths: impl IntoIterator<Item = &'a String>,
+    ) -> AppResult<Vec<String>> {
+        let mut unreferenced = Vec::new();
+        for cover_path in cover_paths {
+            if ImageRegistry::release(conn, cover_path).await? {
+                unreferenced.push(cover_path.clone());
+            }
+        }
+
+        Ok(unreferenced)
+    }
+
+    /// Deletes cover images left unreferenced, logging instead of failing.
+    ///
+    /// The entry change has already been committed at this point, and any
+    /// file left behind is picked up by image garbage collection.
+    pub fn delete_covers(image_storage: &ImageStorage, cover_paths: &[String]) {
+        for cover_path in cover_paths {
+            if let Err(e) = image_storage.delete_image(cover_path) {
+                log::warn!("Failed to delete cover image '{}': {}", cover_path, e);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -160,6 +630,9 @@ mod tests {
             CREATE TABLE vaults (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
@@ -171,10 +644,75 @@ mod tests {
                 description TEXT,
                 metadata TEXT,
                 cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
 
+            CREATE TABLE entry_cover_hash_bands (
+                entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                band INTEGER NOT NULL,
+                value INTEGER NOT NULL,
+                PRIMARY KEY (entry_id, band)
+            );
+            CREATE INDEX idx_entry_cover_hash_bands_value ON entry_cover_hash_bands(band, value);
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            CREATE TABLE image_metadata_mappings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(vault_id, tag)
+            );
+
+            CREATE TABLE image_blobs (
+                hash TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                ref_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
             INSERT INTO vaults (name, created_at, updated_at) VALUES ('Test Vault', datetime('now'), datetime('now'));
             "#
                 .to_string(),
@@ -197,6 +735,10 @@ mod tests {
             description: Set(None),
             metadata: Set(None),
             cover_image_path: Set(None),
+            cover_original_path: Set(None),
+            cover_blurhash: Set(None),
+            cover_palette: Set(None),
+            cover_phash: Set(None),
             created_at: Set(now.clone()),
             updated_at: Set(now),
             ..Default::default()
@@ -206,6 +748,21 @@ mod tests {
         result.id
     }
 
+    /// Stores a perceptual hash for an entry as if its cover had it.
+    async fn set_test_hash(conn: &DatabaseConnection, entry_id: i32, hash: u64) {
+        let entry = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut entry: ActiveModel = entry.into();
+        entry.cover_phash = Set(Some(ImageProcessor::format_hash(hash)));
+        let entry = entry.update(conn).await.unwrap();
+        EntryImageService::sync_hash_bands(conn, &entry)
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_set_cover_from_file() {
         let conn = setup_test_db().await;
@@ -227,16 +784,22 @@ mod tests {
             entry_id,
             test_image.to_str().unwrap(),
             &image_storage,
+            &ImageTransform::default(),
+            false,
         )
         .await;
 
         assert!(result.is_ok());
         let entry = result.unwrap();
-        assert!(entry.cover_image_path.is_some());
-        assert_eq!(entry.cover_image_path.unwrap(), "1/1.jpg");
+        let cover_path = entry.cover_image_path.unwrap();
+        assert!(cover_path.ends_with(".jpg"));
+
+        // Placeholder data is stored with the entry
+        assert_eq!(entry.cover_blurhash.map(|h| h.len()), Some(28));
+        assert_eq!(entry.cover_palette, vec!["#000000"]);
 
         // Verify file exists
-        let image_path = image_storage.get_full_path("1/1.jpg");
+        let image_path = image_storage.get_full_path(&cover_path).unwrap();
         assert!(image_path.exists());
 
         // Clean up
@@ -258,16 +821,20 @@ mod tests {
         let test_image1 = temp_dir.join("test_image1.jpg");
         create_test_image(&test_image1, 500, 500).unwrap();
 
-        EntryImageService::set_cover_from_file(
+        let first = EntryImageService::set_cover_from_file(
             &conn,
             entry_id,
             test_image1.to_str().unwrap(),
             &image_storage,
+            &ImageTransform::default(),
+            false,
         )
         .await
         .unwrap();
 
-        let first_path = image_storage.get_full_path("1/1.jpg");
+        let first_path = image_storage
+            .get_full_path(first.cover_image_path.as_ref().unwrap())
+            .unwrap();
         assert!(first_path.exists());
 
         // Set second cover (should replace first)
@@ -279,12 +846,376 @@ mod tests {
             entry_id,
             test_image2.to_str().unwrap(),
             &image_storage,
+            &ImageTransform::default(),
+            false,
         )
         .await;
 
         assert!(result.is_ok());
         let entry = result.unwrap();
-        assert_eq!(entry.cover_image_path.unwrap(), "1/1.png");
+        assert!(entry.cover_image_path.unwrap().ends_with(".png"));
+
+        // Old image is no longer referenced and is removed
+        assert!(!first_path.exists());
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_shared_cover_survives_single_removal() {
+        let conn = setup_test_db().await;
+        let first_id = create_test_entry(&conn).await;
+        let second_id = create_test_entry(&conn).await;
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_integration_shared");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        let test_image = temp_dir.join("poster.jpg");
+        create_test_image(&test_image, 300, 450).unwrap();
+
+        let first = EntryImageService::set_cover_from_file(
+            &conn,
+            first_id,
+            test_image.to_str().unwrap(),
+            &image_storage,
+            &ImageTransform::default(),
+            false,
+        )
+        .await
+        .unwrap();
+        let second = EntryImageService::set_cover_from_file(
+            &conn,
+            second_id,
+            test_image.to_str().unwrap(),
+            &image_storage,
+            &ImageTransform::default(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // Same content is stored once
+        let cover_path = first.cover_image_path.unwrap();
+        assert_eq!(second.cover_image_path.as_deref(), Some(cover_path.as_str()));
+
+        EntryImageService::remove_cover(&conn, first_id, &image_storage)
+            .await
+            .unwrap();
+        assert!(image_storage.image_exists(&cover_path));
+
+        EntryImageService::remove_cover(&conn, second_id, &image_storage)
+            .await
+            .unwrap();
+        assert!(!image_storage.image_exists(&cover_path));
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_edit_and_revert_cover() {
+        let conn = setup_test_db().await;
+        let entry_id = create_test_entry(&conn).await;
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_integration_edit");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        let test_image = temp_dir.join("scan.png");
+        create_test_image(&test_image, 600, 400).unwrap();
+
+        // Import with a rotation, keeping the original
+        let rotate = ImageTransform {
+            rotate: 90,
+            ..Default::default()
+        };
+        let entry = EntryImageService::set_cover_from_file(
+            &conn,
+            entry_id,
+            test_image.to_str().unwrap(),
+            &image_storage,
+            &rotate,
+            true,
+        )
+        .await
+        .unwrap();
+
+        let original_path = entry.cover_original_path.clone().unwrap();
+        let rotated_path = entry.cover_image_path.clone().unwrap();
+        let cover = image::load_from_memory(&image_storage.read_image(&rotated_path).unwrap()).unwrap();
+        assert_eq!((cover.width(), cover.height()), (400, 600));
+
+        // Re-editing starts from the original, not the rotated cover
+        let crop = ImageTransform {
+            crop: Some(crate::image::CropRegion::Rect {
+                x: 0,
+                y: 0,
+                width: 300,
+                height: 200,
+            }),
+            ..Default::default()
+        };
+        let entry = EntryImageService::edit_cover(&conn, entry_id, &crop, &image_storage)
+            .await
+            .unwrap();
+
+        assert_eq!(entry.cover_original_path.as_deref(), Some(original_path.as_str()));
+        let cover_path = entry.cover_image_path.clone().unwrap();
+        let cover = image::load_from_memory(&image_storage.read_image(&cover_path).unwrap()).unwrap();
+        assert_eq!((cover.width(), cover.height()), (300, 200));
+        assert!(!image_storage.image_exists(&rotated_path));
+
+        // Reverting restores the original and drops the edited file
+        let entry = EntryImageService::revert_cover(&conn, entry_id, &image_storage)
+            .await
+            .unwrap();
+
+        assert_eq!(entry.cover_image_path.as_deref(), Some(original_path.as_str()));
+        assert!(entry.cover_original_path.is_none());
+        assert!(image_storage.image_exists(&original_path));
+        assert!(!image_storage.image_exists(&cover_path));
+
+        // Nothing left to revert to
+        assert!(EntryImageService::revert_cover(&conn, entry_id, &image_storage)
+            .await
+            .is_err());
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_edit_remote_cover_rejected() {
+        let conn = setup_test_db().await;
+        let entry_id = create_test_entry(&conn).await;
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_edit_remote");
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        EntryImageService::set_cover_from_url(
+            &conn,
+            entry_id,
+            "https://example.com/image.jpg",
+            &image_storage,
+        )
+        .await
+        .unwrap();
+
+        let result = EntryImageService::edit_cover(
+            &conn,
+            entry_id,
+            &ImageTransform::default(),
+            &image_storage,
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_covers() {
+        let conn = setup_test_db().await;
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_integration_duplicates");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        let mut poster = image::RgbImage::new(200, 300);
+        for (x, y, pixel) in poster.enumerate_pixels_mut() {
+            *pixel = image::Rgb([(x + y) as u8, (x * 2) as u8, (255 - y % 256) as u8]);
+        }
+        let poster = image::DynamicImage::ImageRgb8(poster);
+
+        // The same poster as PNG and as a smaller JPEG, plus an unrelated cover
+        let files = [
+            ("poster.png", poster.clone()),
+            ("poster_small.jpg", poster.resize(100, 150, image::imageops::FilterType::Lanczos3)),
+            ("mirrored.png", poster.fliph()),
+        ];
+
+        let mut entry_ids = Vec::new();
+        for (name, img) in &files {
+            let path = temp_dir.join(name);
+            img.save(&path).unwrap();
+
+            let entry_id = create_test_entry(&conn).await;
+            EntryImageService::set_cover_from_file(
+                &conn,
+                entry_id,
+                path.to_str().unwrap(),
+                &image_storage,
+                &ImageTransform::default(),
+                false,
+            )
+            .await
+            .unwrap();
+            entry_ids.push(entry_id);
+        }
+
+        let clusters = EntryImageService::find_duplicate_covers(&conn, Some(1), 6)
+            .await
+            .unwrap();
+        assert_eq!(clusters.len(), 1);
+        let ids: Vec<i32> = clusters[0].entries.iter().map(|e| e.entry_id).collect();
+        assert_eq!(ids, entry_ids[..2]);
+        assert!(clusters[0].max_distance <= 6);
+
+        // Across vaults gives the same result here
+        let all = EntryImageService::find_duplicate_covers(&conn, None, 6)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1);
+
+        // Exact matching only pairs identical hashes
+        let exact = EntryImageService::find_duplicate_covers(&conn, None, 0)
+            .await
+            .unwrap();
+        assert!(exact.iter().all(|c| c.max_distance == 0));
+
+        assert!(EntryImageService::find_duplicate_covers(&conn, None, 64)
+            .await
+            .is_err());
+        assert!(EntryImageService::find_duplicate_covers(&conn, Some(999), 6)
+            .await
+            .is_err());
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_covers_across_bands() {
+        let conn = setup_test_db().await;
+
+        // One bit differs in each of three bands, and a fourth cover is far
+        let hashes = [
+            0u64,
+            1 | 1 << 30 | 1 << 63,
+            1 | 1 << 30 | 1 << 63 | 1 << 40,
+            u64::MAX,
+        ];
+        for hash in hashes {
+            let id = create_test_entry(&conn).await;
+            set_test_hash(&conn, id, hash).await;
+        }
+
+        let clusters = EntryImageService::find_duplicate_covers(&conn, None, 3)
+            .await
+            .unwrap();
+        assert_eq!(clusters.len(), 1);
+        let ids: Vec<i32> = clusters[0].entries.iter().map(|e| e.entry_id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(clusters[0].max_distance, 4);
+
+        let clusters = EntryImageService::find_duplicate_covers(&conn, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].entries.len(), 2);
+
+        // Past eight bits, a band may differ and still be looked up
+        let mut spread = 0u64;
+        for band in 0..8 {
+            spread |= 0b11 << (band * 8);
+        }
+        let id = create_test_entry(&conn).await;
+        set_test_hash(&conn, id, spread).await;
+
+        let clusters = EntryImageService::find_duplicate_covers(&conn, None, 16)
+            .await
+            .unwrap();
+        let ids: Vec<i32> = clusters[0].entries.iter().map(|e| e.entry_id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_existing_covers() {
+        let conn = setup_test_db().await;
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_integration_analyze");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        // Covers saved before placeholders existed, one of them missing
+        let path = temp_dir.join("cover.png");
+        create_test_image(&path, 40, 60).unwrap();
+        let stored = image_storage.save_local_image(&path).unwrap();
+        let analyzed = create_test_entry(&conn).await;
+        let missing = create_test_entry(&conn).await;
+        for (id, cover) in [(analyzed, stored.relative_path.as_str()), (missing, "ab/gone.png")] {
+            conn.execute_unprepared(&format!(
+                "UPDATE entries SET cover_image_path = '{}' WHERE id = {}",
+                cover, id
+            ))
+            .await
+            .unwrap();
+        }
+
+        let updated = EntryImageService::analyze_existing_covers(&conn, &image_storage)
+            .await
+            .unwrap();
+        assert_eq!(updated, 1);
+
+        let entry = Entry::find_by_id(analyzed).one(&conn).await.unwrap().unwrap();
+        assert!(entry.cover_blurhash.is_some());
+        assert_eq!(entry.cover_phash.map(|h| h.len()), Some(16));
+        assert_eq!(entry.cover_palette.as_deref(), Some(r##"["#000000"]"##));
+        let entry = Entry::find_by_id(missing).one(&conn).await.unwrap().unwrap();
+        assert!(entry.cover_blurhash.is_none());
+
+        // Nothing is left to analyze
+        let updated = EntryImageService::analyze_existing_covers(&conn, &image_storage)
+            .await
+            .unwrap();
+        assert_eq!(updated, 0);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_set_cover_from_file_applies_image_metadata() {
+        let conn = setup_test_db().await;
+        let entry_id = create_test_entry(&conn).await;
+
+        conn.execute_unprepared(
+            r#"
+            INSERT INTO field_definitions (vault_id, name, field_type) VALUES (1, 'Width', 'number');
+            INSERT INTO image_metadata_mappings (vault_id, tag, field_id) VALUES (1, 'ImageWidth', 1);
+            "#,
+        )
+        .await
+        .unwrap();
+
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_integration_metadata");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let image_storage = ImageStorage::new(&temp_dir);
+
+        let test_image = temp_dir.join("photo.png");
+        create_test_image(&test_image, 320, 200).unwrap();
+
+        let entry = EntryImageService::set_cover_from_file(
+            &conn,
+            entry_id,
+            test_image.to_str().unwrap(),
+            &image_storage,
+            &ImageTransform::default(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(entry.metadata.as_deref(), Some(r#"{"1":320.0}"#));
 
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
@@ -329,16 +1260,20 @@ mod tests {
         let test_image = temp_dir.join("test_image.jpg");
         create_test_image(&test_image, 500, 500).unwrap();
 
-        EntryImageService::set_cover_from_file(
+        let entry = EntryImageService::set_cover_from_file(
             &conn,
             entry_id,
             test_image.to_str().unwrap(),
             &image_storage,
+            &ImageTransform::default(),
+            false,
         )
         .await
         .unwrap();
 
-        let image_path = image_storage.get_full_path("1/1.jpg");
+        let image_path = image_storage
+            .get_full_path(entry.cover_image_path.as_ref().unwrap())
+            .unwrap();
         assert!(image_path.exists());
 
         // Remove cover
@@ -347,6 +1282,8 @@ mod tests {
         assert!(result.is_ok());
         let entry = result.unwrap();
         assert!(entry.cover_image_path.is_none());
+        assert!(entry.cover_blurhash.is_none());
+        assert!(entry.cover_palette.is_empty());
 
         // Verify file is deleted
         assert!(!image_path.exists());
@@ -375,6 +1312,8 @@ mod tests {
             entry_id,
             test_image.to_str().unwrap(),
             &image_storage,
+            &ImageTransform::default(),
+            false,
         )
         .await
         .unwrap();
@@ -0,0 +1,231 @@
+//! Markdown sanitizing, plain-text extraction and HTML rendering.
+//!
+//! Markdown field values are stored as source text:
+//! - Raw HTML is stripped from the source on write
+//! - Search indexes the text with the markup removed
+//! - `[[Entry Title]]` wiki-links name entries of the same vault
+//! - Rendering goes through an allow-list, so stored values can never
+//!   inject scripts, styles or remote images
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+
+/// Tags kept when rendering to HTML.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "blockquote",
+    "br",
+    "code",
+    "del",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "span",
+    "strong",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// URL schemes links may use.
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Class of a wiki-link that resolved to an entry.
+const WIKI_LINK_CLASS: &str = "wiki-link";
+
+/// Class of a wiki-link naming no entry of the vault.
+const MISSING_WIKI_LINK_CLASS: &str = "wiki-link-missing";
+
+fn parser(source: &str) -> Parser<'_> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_WIKILINKS;
+    Parser::new_ext(source, options)
+}
+
+/// Strips raw HTML and control characters from markdown source.
+///
+/// Removing a tag can expose a new one (`<<b>script>`), so stripping
+/// repeats until the source holds no HTML.
+pub fn sanitize_markdown(source: &str) -> String {
+    let mut text: String = source
+        .replace("\r\n", "\n")
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .collect();
+
+    loop {
+        let html: Vec<Range<usize>> = parser(&text)
+            .into_offset_iter()
+            .filter(|(event, _)| matches!(event, Event::Html(_) | Event::InlineHtml(_)))
+            .map(|(_, range)| range)
+            .collect();
+        if html.is_empty() {
+            return text;
+        }
+        for range in html.into_iter().rev() {
+            text.replace_range(range, "");
+        }
+    }
+}
+
+/// Extracts the text of markdown source for search, without markup.
+/// Wiki-links contribute their label.
+pub fn to_plain_text(source: &str) -> String {
+    let mut text = String::new();
+    for event in parser(source) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell,
+            ) => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns the distinct entry titles named by wiki-links, in order.
+pub fn wiki_link_titles(source: &str) -> Vec<String> {
+    let mut titles: Vec<String> = Vec::new();
+    for event in parser(source) {
+        if let Event::Start(Tag::Link {
+            link_type: LinkType::WikiLink { .. },
+            dest_url,
+            ..
+        }) = event
+        {
+            let title = dest_url.trim();
+            if !title.is_empty() && !titles.iter().any(|t| t.eq_ignore_ascii_case(title)) {
+                titles.push(title.to_string());
+            }
+        }
+    }
+    titles
+}
+
+/// Renders markdown source to safe HTML.
+///
+/// `entry_ids` maps ASCII-lowercased titles to entry IDs. Resolved wiki-links
+/// become anchors carrying a `data-entry-id`; the others are marked as
+/// missing. Anything outside the allow-list is dropped.
+pub fn render_html(source: &str, entry_ids: &HashMap<String, i32>) -> String {
+    // Closing tag of the wiki-link being rendered; links never nest
+    let mut wiki_link_end = None;
+    let events = parser(source).map(|event| match event {
+        Event::Start(Tag::Link {
+            link_type: LinkType::WikiLink { .. },
+            dest_url,
+            ..
+        }) => {
+            let tag = match entry_ids.get(&dest_url.trim().to_ascii_lowercase()) {
+                Some(id) => {
+                    wiki_link_end = Some("</a>");
+                    format!(r#"<a class="{}" data-entry-id="{}">"#, WIKI_LINK_CLASS, id)
+                }
+                None => {
+                    wiki_link_end = Some("</span>");
+                    format!(r#"<span class="{}">"#, MISSING_WIKI_LINK_CLASS)
+                }
+            };
+            Event::InlineHtml(CowStr::from(tag))
+        }
+        Event::End(TagEnd::Link) if wiki_link_end.is_some() => {
+            Event::InlineHtml(CowStr::Borrowed(wiki_link_end.take().unwrap_or_default()))
+        }
+        // Values stored before sanitizing may still hold raw HTML
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+
+    ammonia::Builder::empty()
+        .add_tags(ALLOWED_TAGS)
+        .add_tag_attributes("a", &["href", "title", "data-entry-id"])
+        .add_allowed_classes("a", &[WIKI_LINK_CLASS])
+        .add_allowed_classes("span", &[MISSING_WIKI_LINK_CLASS])
+        .add_tag_attributes("code", &["class"])
+        .add_url_schemes(ALLOWED_SCHEMES)
+        .url_relative(ammonia::UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer"))
+        // Open links outside the app window
+        .set_tag_attribute_value("a", "target", "_blank")
+        .clean(&output)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_markdown_strips_html() {
+        assert_eq!(
+            sanitize_markdown("**Great** <script>alert(1)</script>film"),
+            "**Great** alert(1)film"
+        );
+        assert_eq!(sanitize_markdown("a <<b>script>"), "a ");
+        assert_eq!(sanitize_markdown("line\r\nnext\u{0}"), "line\nnext");
+        assert_eq!(sanitize_markdown("`<b>` stays"), "`<b>` stays");
+    }
+
+    #[test]
+    fn test_to_plain_text() {
+        assert_eq!(
+            to_plain_text("# Review\n\nA **tense** film, see [[Heat|the original]].\n\n- `code`"),
+            "Review A tense film, see the original. code"
+        );
+    }
+
+    #[test]
+    fn test_wiki_link_titles() {
+        assert_eq!(
+            wiki_link_titles("[[Heat]] and [[Ronin|that one]], again [[heat]]"),
+            vec!["Heat".to_string(), "Ronin".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_render_html_resolves_wiki_links() {
+        let ids = HashMap::from([("heat".to_string(), 7)]);
+        let html = render_html("See [[Heat]] and [[Nope]]", &ids);
+        assert!(html.contains(r#"<a class="wiki-link" data-entry-id="7""#));
+        assert!(html.contains(">Heat</a>"));
+        assert!(html.contains(r#"<span class="wiki-link-missing">Nope</span>"#));
+    }
+
+    #[test]
+    fn test_render_html_uses_allow_list() {
+        let html = render_html(
+            "[x](javascript:alert(1)) [y](https://example.com) ![img](https://example.com/a.png)",
+            &HashMap::new(),
+        );
+        assert!(!html.contains("javascript"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains(r#"<a href="https://example.com" target="_blank""#));
+
+        let html = render_html("<img src=x onerror=alert(1)>", &HashMap::new());
+        assert!(!html.contains("<img"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Entry markdown service for rendering markdown field values.
+//!
+//! Wiki-links name entries by title and resolve within the vault of the
+//! entry being rendered. Titles match case-insensitively; when several
+//! entries share a title, the oldest one wins.
+
+use std::collections::HashMap;
+
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::core::{AppResult, find_vault_or_error};
+use crate::entities::entry::{self, Entity as Entry};
+
+use super::markdown::{render_html, wiki_link_titles};
+use super::{RenderedMarkdown, WikiLink};
+
+/// Service for markdown rendering.
+pub struct EntryMarkdownService;
+
+impl EntryMarkdownService {
+    /// Renders markdown to safe HTML, resolving its wiki-links to entries
+    /// of the vault.
+    pub async fn render(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        source: &str,
+    ) -> AppResult<RenderedMarkdown> {
+        find_vault_or_error(conn, vault_id).await?;
+
+        let titles = wiki_link_titles(source);
+        let entry_ids = Self::resolve_titles(conn, vault_id, &titles).await?;
+
+        let links = titles
+            .into_iter()
+            .map(|title| WikiLink {
+                entry_id: entry_ids.get(&title.to_ascii_lowercase()).copied(),
+                title,
+            })
+            .collect();
+
+        Ok(RenderedMarkdown {
+            html: render_html(source, &entry_ids),
+            links,
+        })
+    }
+
+    /// Maps ASCII-lowercased titles to the ID of the oldest entry having
+    /// that title. SQLite's `lower` only folds ASCII, so Rust does the same.
+    async fn resolve_titles(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        titles: &[String],
+    ) -> AppResult<HashMap<String, i32>> {
+        if titles.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let lowered: Vec<String> = titles.iter().map(|t| t.to_ascii_lowercase()).collect();
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.eq(vault_id))
+            .filter(Expr::expr(Func::lower(Expr::col(entry::Column::Title))).is_in(lowered))
+            .order_by_desc(entry::Column::Id)
+            .all(conn)
+            .await?;
+
+        // Later inserts overwrite, so descending order leaves the oldest entry
+        Ok(entries
+            .into_iter()
+            .map(|e| (e.title.to_ascii_lowercase(), e.id))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT INTO vaults (name) VALUES ('Movies'), ('Books');
+            INSERT INTO entries (vault_id, title) VALUES
+                (1, 'Heat'),
+                (1, 'Ronin'),
+                (1, 'heat'),
+                (2, 'Dune');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_render_resolves_wiki_links_within_vault() {
+        let conn = setup_test_db().await;
+
+        let rendered = EntryMarkdownService::render(
+            &conn,
+            1,
+            "Like [[HEAT]], unlike [[Dune]] or [[Ronin|that one]]",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            rendered.links,
+            vec![
+                WikiLink {
+                    title: "HEAT".to_string(),
+                    entry_id: Some(1)
+                },
+                WikiLink {
+                    title: "Dune".to_string(),
+                    entry_id: None
+                },
+                WikiLink {
+                    title: "Ronin".to_string(),
+                    entry_id: Some(2)
+                },
+            ]
+        );
+        assert!(rendered.html.contains(r#"data-entry-id="1""#));
+        assert!(rendered
+            .html
+            .contains(r#"<span class="wiki-link-missing">Dune</span>"#));
+    }
+
+    #[tokio::test]
+    async fn test_render_unknown_vault() {
+        let conn = setup_test_db().await;
+
+        assert!(EntryMarkdownService::render(&conn, 99, "text")
+            .await
+            .is_err());
+    }
+}
@@ -5,14 +5,35 @@
 //! - Required field validation
 //! - Lazy cleanup of orphan data on write
 //! - Field type validation
+//! - Plain search text for markdown fields
 
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use serde_json::{Map, Value};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
-use crate::core::AppResult;
+use crate::attachment::Attachment;
+use crate::core::{AppError, AppResult};
 use crate::entities::field_definition::{self, Entity as FieldDefinition};
-use crate::field::{FieldDefinitionDto, FieldType};
+use crate::field::{
+    FieldDefinitionDto, FieldRule, FieldRuleKind, FieldService, FieldType, RuleOperator,
+    check_value_constraints, choice_labels, compare_values, match_choice, normalize_identifier,
+    validate_json,
+};
+use crate::location::Location;
+
+use super::EntryConstraintService;
+use super::color::{COLOR_FORMATS, normalize_color};
+use super::contact::{EMAIL_FORMATS, PHONE_FORMATS, normalize_email, normalize_phone};
+use super::markdown::{sanitize_markdown, to_plain_text};
+use super::money::{MONEY_FORMATS, normalize_money};
+use super::temporal::{
+    DATETIME_FORMATS, DURATION_FORMATS, PARTIAL_DATE_FORMATS, normalize_datetime,
+    normalize_partial_date, parse_duration,
+};
+
+/// Largest serialized value a JSON field accepts, in bytes.
+const MAX_JSON_LENGTH: usize = 64 * 1024;
 
 /// Service for metadata validation and cleanup operations.
 pub struct MetadataService;
@@ -24,6 +45,9 @@ pub struct ValidationResult {
     pub is_valid: bool,
     /// List of validation errors
     pub errors: Vec<String>,
+    /// Validation errors by the ID of the field they concern, also listed
+    /// in `errors`
+    pub field_errors: HashMap<i32, Vec<String>>,
     /// List of warnings (non-blocking issues)
     pub warnings: Vec<String>,
 }
@@ -33,6 +57,7 @@ impl ValidationResult {
         Self {
             is_valid: true,
             errors: vec![],
+            field_errors: HashMap::new(),
             warnings: vec![],
         }
     }
@@ -41,6 +66,7 @@ impl ValidationResult {
         Self {
             is_valid: false,
             errors: vec![error],
+            field_errors: HashMap::new(),
             warnings: vec![],
         }
     }
@@ -50,11 +76,27 @@ impl ValidationResult {
         self.errors.push(error);
     }
 
+    /// Adds an error concerning a single field.
+    pub fn add_field_error(&mut self, field_id: i32, error: String) {
+        self.field_errors.entry(field_id).or_default().push(error.clone());
+        self.add_error(error);
+    }
+
     pub fn add_warning(&mut self, warning: String) {
         self.warnings.push(warning);
     }
 }
 
+/// Outcome of a vault's validation rules on an entry's metadata.
+#[derive(Debug, Default)]
+pub(crate) struct RuleOutcome {
+    /// Fields whose visible-if conditions do not hold; they are not
+    /// required and their rules are skipped
+    pub hidden: HashSet<i32>,
+    /// Broken required-if and compare rules, by field ID
+    pub errors: Vec<(i32, String)>,
+}
+
 impl MetadataService {
     /// Validates metadata against field definitions for a vault.
     ///
@@ -62,9 +104,16 @@ impl MetadataService {
     /// - Required fields are present
     /// - Field values match their types
     /// - Field values satisfy options constraints
+    /// - Values of unique fields are not used by another entry of the
+    ///   vault, other than `entry_id`
+    /// - The vault's required-if and compare rules hold; fields whose
+    ///   visible-if conditions do not hold are not required
+    ///
+    /// Errors concerning a field are also reported under its ID.
     pub async fn validate_metadata(
         conn: &DatabaseConnection,
         vault_id: i32,
+        entry_id: Option<i32>,
         metadata_json: Option<&str>,
     ) -> AppResult<ValidationResult> {
         // Get field definitions for the vault
@@ -85,13 +134,15 @@ impl MetadataService {
         };
 
         let mut result = ValidationResult::success();
+        let rules = FieldService::vault_rules(conn, vault_id).await?;
+        let outcome = Self::check_rules(&field_definitions, &rules, &metadata);
 
         // Check required fields
         for field in &field_definitions {
-            if field.required {
+            if field.required && !outcome.hidden.contains(&field.id) {
                 let key = field.id.to_string();
-                if !metadata.contains_key(&key) || metadata.get(&key) == Some(&Value::Null) {
-                    result.add_error(format!("Field '{}' is required", field.name));
+                if !metadata.get(&key).is_some_and(has_value) {
+                    result.add_field_error(field.id, format!("Field '{}' is required", field.name));
                 }
             }
         }
@@ -129,16 +180,29 @@ impl MetadataService {
 
             // Validate value by field type
             if let Err(e) = Self::validate_field_value(field, value) {
-                result.add_error(e);
+                result.add_field_error(field_id, e);
             }
         }
 
+        // Check unique fields against the other entries of the vault
+        let violations =
+            EntryConstraintService::unique_violations(conn, &field_definitions, entry_id, &metadata)
+                .await?;
+        for (field_id, error) in violations {
+            result.add_field_error(field_id, error);
+        }
+
+        for (field_id, error) in outcome.errors {
+            result.add_field_error(field_id, error);
+        }
+
         Ok(result)
     }
 
     /// Validates required fields only (for quick validation on create).
-    pub async fn validate_required_fields(
-        conn: &DatabaseConnection,
+    /// Fields hidden by a visible-if rule are not required.
+    pub async fn validate_required_fields<C: ConnectionTrait>(
+        conn: &C,
         vault_id: i32,
         metadata_json: Option<&str>,
     ) -> AppResult<ValidationResult> {
@@ -150,13 +214,15 @@ impl MetadataService {
         };
 
         let mut result = ValidationResult::success();
+        let rules = FieldService::vault_rules(conn, vault_id).await?;
+        let hidden = Self::check_rules(&field_definitions, &rules, &metadata).hidden;
 
         for field in field_definitions {
-            if field.required {
+            if field.required && !hidden.contains(&field.id) {
                 let key = field.id.to_string();
-                let has_value = metadata.get(&key).map(|v| !v.is_null()).unwrap_or(false);
+                let has_value = metadata.get(&key).is_some_and(has_value);
                 if !has_value {
-                    result.add_error(format!("Field '{}' is required", field.name));
+                    result.add_field_error(field.id, format!("Field '{}' is required", field.name));
                 }
             }
         }
@@ -164,11 +230,89 @@ impl MetadataService {
         Ok(result)
     }
 
+    /// Evaluates a vault's validation rules on metadata.
+    ///
+    /// Values are normalized before being tested, so raw form input and
+    /// stored metadata give the same outcome. Compare rules pass while
+    /// either value is empty; required-if rules cover presence.
+    pub(crate) fn check_rules(
+        fields: &[FieldDefinitionDto],
+        rules: &[FieldRule],
+        metadata: &Map<String, Value>,
+    ) -> RuleOutcome {
+        let fields: HashMap<i32, &FieldDefinitionDto> = fields.iter().map(|f| (f.id, f)).collect();
+        let value_of = |field: &FieldDefinitionDto| {
+            let value = metadata.get(&field.id.to_string()).filter(|v| has_value(v))?;
+            Some(Self::normalize_field_value(field, value).unwrap_or_else(|_| value.clone()))
+        };
+
+        let mut outcome = RuleOutcome::default();
+        let involved = |rule: &FieldRule| {
+            Some((*fields.get(&rule.field_id)?, *fields.get(&rule.other_field_id)?))
+        };
+        for rule in rules.iter().filter(|r| r.kind == FieldRuleKind::VisibleIf) {
+            if let Some((field, other)) = involved(rule) {
+                if !condition_holds(other, rule, value_of(other).as_ref()) {
+                    outcome.hidden.insert(field.id);
+                }
+            }
+        }
+
+        for rule in rules {
+            let Some((field, other)) = involved(rule) else {
+                continue;
+            };
+            if outcome.hidden.contains(&field.id) {
+                continue;
+            }
+            let error = match rule.kind {
+                FieldRuleKind::VisibleIf => None,
+                FieldRuleKind::RequiredIf => {
+                    let applies = condition_holds(other, rule, value_of(other).as_ref());
+                    (applies && value_of(field).is_none()).then(|| {
+                        format!(
+                            "Field '{}' is required when {}",
+                            field.name,
+                            describe_condition(other, rule)
+                        )
+                    })
+                }
+                FieldRuleKind::Compare => {
+                    if outcome.hidden.contains(&other.id) {
+                        continue;
+                    }
+                    let (Some(a), Some(b)) = (value_of(field), value_of(other)) else {
+                        continue;
+                    };
+                    let ordering = compare_values(field.field_type, &a, other.field_type, &b);
+                    ordering.filter(|o| !rule.operator.accepts(*o)).map(|_| {
+                        format!(
+                            "Field '{}' must be {} '{}'",
+                            field.name,
+                            rule.operator.describe(field.field_type),
+                            other.name
+                        )
+                    })
+                }
+            };
+            if let Some(error) = error {
+                let error = match &rule.message {
+                    Some(message) => format!("Field '{}': {}", field.name, message),
+                    None => error,
+                };
+                outcome.errors.push((field.id, error));
+            }
+        }
+
+        outcome
+    }
+
     /// Cleans up orphan data from metadata.
     ///
     /// This implements the "Lazy Cleanup on Write" strategy:
     /// - Only removes keys that don't correspond to existing field IDs
-    /// - Called during entry update operations
+    /// - Called during entry update operations, which release the attachments
+    ///   of dropped values once the entry is saved
     pub async fn cleanup_orphan_data(
         conn: &DatabaseConnection,
         vault_id: i32,
@@ -210,17 +354,249 @@ impl MetadataService {
         Ok(serde_json::to_string(&cleaned).unwrap_or_default())
     }
 
+    /// Normalizes partial dates, timestamps, durations, identifiers, email
+    /// addresses, phone numbers, colors and amounts in metadata to their
+    /// stored form, so they sort and compare correctly, strips raw HTML from
+    /// markdown, tidies location place names and checks JSON values against
+    /// their schema.
+    ///
+    /// Invalid values of these types are rejected with the accepted formats.
+    /// Metadata that is not a JSON object is returned as-is.
+    pub async fn normalize_metadata<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        metadata_json: &str,
+    ) -> AppResult<String> {
+        let mut metadata: Map<String, Value> = match serde_json::from_str(metadata_json) {
+            Ok(m) => m,
+            Err(_) => return Ok(metadata_json.to_string()),
+        };
+
+        let mut errors = Vec::new();
+        let mut changed = false;
+        for field in Self::get_field_definitions(conn, vault_id).await? {
+            if !matches!(
+                field.field_type,
+                FieldType::PartialDate
+                    | FieldType::Datetime
+                    | FieldType::Duration
+                    | FieldType::Markdown
+                    | FieldType::Location
+                    | FieldType::Identifier
+                    | FieldType::Email
+                    | FieldType::Phone
+                    | FieldType::Color
+                    | FieldType::Currency
+                    | FieldType::Json
+            ) {
+                continue;
+            }
+            let Some(value) = metadata
+                .get_mut(&field.id.to_string())
+                .filter(|v| !v.is_null())
+            else {
+                continue;
+            };
+
+            match Self::normalize_field_value(&field, value) {
+                Ok(normalized) if normalized != *value => {
+                    *value = normalized;
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors.join("; ")));
+        }
+
+        Ok(if changed {
+            Value::Object(metadata).to_string()
+        } else {
+            metadata_json.to_string()
+        })
+    }
+
+    /// Converts a partial date, timestamp, duration, markdown, location,
+    /// identifier, email, phone, color, currency or JSON value to its stored
+    /// form. Values of other field types are returned unchanged.
+    pub(crate) fn normalize_field_value(
+        field: &FieldDefinitionDto,
+        value: &Value,
+    ) -> Result<Value, String> {
+        let invalid = |kind: &str, formats: &str| {
+            format!(
+                "Field '{}': {} is not a valid {}; expected {}",
+                field.name, value, kind, formats
+            )
+        };
+
+        match field.field_type {
+            FieldType::PartialDate => value
+                .as_str()
+                .and_then(normalize_partial_date)
+                .map(Value::String)
+                .ok_or_else(|| invalid("date", PARTIAL_DATE_FORMATS)),
+            FieldType::Datetime => value
+                .as_str()
+                .and_then(normalize_datetime)
+                .map(Value::String)
+                .ok_or_else(|| invalid("timestamp", DATETIME_FORMATS)),
+            // Stored durations are whole seconds
+            FieldType::Duration => match value {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => parse_duration(s),
+                _ => None,
+            }
+            .map(Value::from)
+            .ok_or_else(|| invalid("duration", DURATION_FORMATS)),
+            FieldType::Markdown => value
+                .as_str()
+                .map(|source| Value::String(sanitize_markdown(source)))
+                .ok_or_else(|| format!("Field '{}': expected text value", field.name)),
+            // Place names are trimmed and dropped when empty
+            FieldType::Location => {
+                let mut location = Self::parse_location(field, value)?;
+                location.name = location
+                    .name
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty());
+                serde_json::to_value(location).map_err(|e| e.to_string())
+            }
+            // Identifiers are checked against their scheme and stored in
+            // canonical form, e.g. ISBN-10 as ISBN-13
+            FieldType::Identifier => value
+                .as_str()
+                .ok_or_else(|| format!("Field '{}': expected text value", field.name))
+                .and_then(|identifier| {
+                    normalize_identifier(field.options.as_ref(), identifier)
+                        .map(Value::String)
+                        .map_err(|e| format!("Field '{}': {}", field.name, e))
+                }),
+            FieldType::Email => value
+                .as_str()
+                .and_then(normalize_email)
+                .map(Value::String)
+                .ok_or_else(|| invalid("email address", EMAIL_FORMATS)),
+            FieldType::Phone => {
+                let calling_code = field.options.as_ref().and_then(|o| o.calling_code.as_deref());
+                value
+                    .as_str()
+                    .and_then(|phone| normalize_phone(phone, calling_code))
+                    .map(Value::String)
+                    .ok_or_else(|| invalid("phone number", PHONE_FORMATS))
+            }
+            FieldType::Color => value
+                .as_str()
+                .and_then(normalize_color)
+                .map(Value::String)
+                .ok_or_else(|| invalid("color", COLOR_FORMATS)),
+            // Amounts are stored as exact decimal text next to their code
+            FieldType::Currency => {
+                let currency = field.options.as_ref().and_then(|o| o.currency.as_deref());
+                match normalize_money(value, currency) {
+                    Some(money) => serde_json::to_value(money).map_err(|e| e.to_string()),
+                    None => Err(match currency {
+                        Some(code) => invalid("amount", &format!("an amount in {}", code)),
+                        None => invalid("amount", MONEY_FORMATS),
+                    }),
+                }
+            }
+            // Choices typed by label are stored by ID
+            FieldType::Select | FieldType::Multiselect => {
+                let choices = field
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.choices.as_deref())
+                    .unwrap_or_default();
+                let to_id = |item: &Value| match item.as_str().and_then(|v| match_choice(v, choices)) {
+                    Some(choice) => Value::String(choice.id.clone()),
+                    None => item.clone(),
+                };
+                Ok(match value {
+                    Value::Array(items) => Value::Array(items.iter().map(to_id).collect()),
+                    other => to_id(other),
+                })
+            }
+            FieldType::Json => {
+                if value.to_string().len() > MAX_JSON_LENGTH {
+                    return Err(format!(
+                        "Field '{}': JSON value exceeds {} bytes",
+                        field.name, MAX_JSON_LENGTH
+                    ));
+                }
+                match field.options.as_ref().and_then(|o| o.json_schema.as_ref()) {
+                    Some(schema) => validate_json(schema, value)
+                        .map(|_| value.clone())
+                        .map_err(|e| format!("Field '{}': {}", field.name, e)),
+                    None => Ok(value.clone()),
+                }
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// Builds the plain text indexed for search from the markdown fields of
+    /// metadata, with the markup removed. Returns `None` when there is none.
+    pub async fn search_text<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+        metadata_json: Option<&str>,
+    ) -> AppResult<Option<String>> {
+        let Some(metadata) =
+            metadata_json.and_then(|json| serde_json::from_str::<Map<String, Value>>(json).ok())
+        else {
+            return Ok(None);
+        };
+
+        let text: Vec<String> = Self::get_field_definitions(conn, vault_id)
+            .await?
+            .into_iter()
+            .filter(|field| field.field_type == FieldType::Markdown)
+            .filter_map(|field| metadata.get(&field.id.to_string())?.as_str().map(to_plain_text))
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        Ok((!text.is_empty()).then(|| text.join("\n")))
+    }
+
     /// Validates a single field value against its type and options.
-    fn validate_field_value(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+    pub(crate) fn validate_field_value(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
         match field.field_type {
-            FieldType::Text => Self::validate_text(field, value),
+            FieldType::Text | FieldType::Markdown => Self::validate_text(field, value),
             FieldType::Number => Self::validate_number(field, value),
             FieldType::Date => Self::validate_date(value),
+            FieldType::PartialDate
+            | FieldType::Datetime
+            | FieldType::Duration
+            | FieldType::Identifier
+            | FieldType::Email
+            | FieldType::Phone
+            | FieldType::Color
+            | FieldType::Currency
+            | FieldType::Json => {
+                Self::normalize_field_value(field, value).map(|_| ())
+            }
             FieldType::Url => Self::validate_url(value),
             FieldType::Boolean => Self::validate_boolean(value),
             FieldType::Select => Self::validate_select(field, value),
+            FieldType::Multiselect => Self::validate_multiselect(field, value),
+            FieldType::Rating => Self::validate_rating(field, value),
+            FieldType::File => Self::validate_file(field, value),
+            FieldType::Location => Self::validate_location(field, value),
             FieldType::Relation => Self::validate_relation(field, value),
-        }
+            FieldType::Lookup | FieldType::Rollup | FieldType::Formula => Err(format!(
+                "Field '{}' is computed and cannot be set",
+                field.name
+            )),
+        }?;
+
+        // Minimum lengths and patterns apply to the stored form of values
+        let stored = Self::normalize_field_value(field, value)?;
+        check_value_constraints(field.field_type, field.options.as_ref(), &stored)
+            .map_err(|e| format!("Field '{}': {}", field.name, e))
     }
 
     fn validate_text(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
@@ -269,6 +645,32 @@ impl MetadataService {
         Ok(())
     }
 
+    /// Validates a rating: a number from 0 to the top of the scale, on a
+    /// multiple of the step.
+    fn validate_rating(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+        let rating = value
+            .as_f64()
+            .ok_or_else(|| format!("Field '{}': expected number value", field.name))?;
+        let options = field.options.clone().unwrap_or_default();
+        let (max, step) = (options.rating_max(), options.rating_step());
+
+        if !(0.0..=max).contains(&rating) {
+            return Err(format!(
+                "Field '{}': rating {} is outside the scale 0 to {}",
+                field.name, rating, max
+            ));
+        }
+        let steps = rating / step;
+        if (steps - steps.round()).abs() > 1e-9 {
+            return Err(format!(
+                "Field '{}': rating {} is not a multiple of {}",
+                field.name, rating, step
+            ));
+        }
+
+        Ok(())
+    }
+
     fn validate_date(value: &Value) -> Result<(), String> {
         let date_str = value.as_str().ok_or("Date field: expected string value")?;
 
@@ -309,12 +711,141 @@ impl MetadataService {
             .as_str()
             .ok_or_else(|| format!("Field '{}': expected string value for select", field.name))?;
 
+        Self::validate_choice(field, selected)
+    }
+
+    /// Checks that a value is the ID of one of the field's choices.
+    fn validate_choice(field: &FieldDefinitionDto, selected: &str) -> Result<(), String> {
+        let Some(choices) = field.options.as_ref().and_then(|o| o.choices.as_ref()) else {
+            return Ok(());
+        };
+        if !choices.iter().any(|c| c.id == selected) {
+            let labels: Vec<&str> = choices.iter().map(|c| c.label.as_str()).collect();
+            return Err(format!(
+                "Field '{}': '{}' is not a valid choice. Valid choices: {}",
+                field.name,
+                selected,
+                labels.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates a location value's shape and coordinates.
+    fn validate_location(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+        Self::parse_location(field, value).map(|_| ())
+    }
+
+    /// Reads a location value and checks its coordinates.
+    fn parse_location(field: &FieldDefinitionDto, value: &Value) -> Result<Location, String> {
+        let location: Location = serde_json::from_value(value.clone()).map_err(|_| {
+            format!(
+                "Field '{}': expected a location like {{\"lat\": 48.85, \"lng\": 2.35}}",
+                field.name
+            )
+        })?;
+        location
+            .validate()
+            .map_err(|e| format!("Field '{}': {}", field.name, e))?;
+        Ok(location)
+    }
+
+    /// Validates a file field value: an array of distinct attachments whose
+    /// paths are the storage paths of their digests.
+    fn validate_file(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+        let attachments: Vec<Attachment> = serde_json::from_value(value.clone())
+            .map_err(|_| format!("Field '{}': expected a list of attachments", field.name))?;
+
+        let mut seen = HashSet::new();
+        for attachment in &attachments {
+            if !attachment.has_storage_path() {
+                return Err(format!(
+                    "Field '{}': '{}' does not point at a stored attachment",
+                    field.name, attachment.filename
+                ));
+            }
+            if !seen.insert(&attachment.sha256) {
+                return Err(format!(
+                    "Field '{}': '{}' is attached more than once",
+                    field.name, attachment.filename
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a multiselect field value: an array of distinct choices.
+    fn validate_multiselect(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| format!("Field '{}': expected a list of choices", field.name))?;
+
+        let mut seen = HashSet::new();
+        for item in items {
+            let selected = item
+                .as_str()
+                .ok_or_else(|| format!("Field '{}': choices must be strings", field.name))?;
+
+            Self::validate_choice(field, selected)?;
+            if !seen.insert(selected) {
+                return Err(format!(
+                    "Field '{}': '{}' is selected more than once",
+                    field.name, selected
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a relation field value.
+    ///
+    /// Single-valued fields expect one reference object. Multi-valued fields
+    /// expect an ordered array of distinct references within the configured
+    /// cardinality; a lone object is accepted as a one-item list.
+    fn validate_relation(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+        let multiple = field.options.as_ref().is_some_and(|o| o.is_multiple());
+
+        let items = match value {
+            Value::Array(items) if multiple => items.as_slice(),
+            Value::Array(_) => {
+                return Err(format!(
+                    "Field '{}': expected a single relation, not a list",
+                    field.name
+                ))
+            }
+            _ => std::slice::from_ref(value),
+        };
+
+        let mut seen = HashSet::new();
+        for item in items {
+            Self::validate_relation_ref(field, item)?;
+
+            let entry_id = item.get("entry_id").and_then(|v| v.as_i64());
+            if !seen.insert(entry_id) {
+                return Err(format!(
+                    "Field '{}': entry {} is referenced more than once",
+                    field.name,
+                    entry_id.unwrap_or_default()
+                ));
+            }
+        }
+
         if let Some(ref options) = field.options {
-            if let Some(ref choices) = options.choices {
-                if !choices.contains(&selected.to_string()) {
+            if let Some(min) = options.min_items {
+                if (items.len() as i32) < min {
                     return Err(format!(
-                        "Field '{}': '{}' is not a valid choice. Valid choices: {:?}",
-                        field.name, selected, choices
+                        "Field '{}': at least {} relation(s) required",
+                        field.name, min
+                    ));
+                }
+            }
+            if let Some(max) = options.max_items {
+                if items.len() as i32 > max {
+                    return Err(format!(
+                        "Field '{}': at most {} relation(s) allowed",
+                        field.name, max
                     ));
                 }
             }
@@ -323,9 +854,9 @@ impl MetadataService {
         Ok(())
     }
 
-    /// Validates a relation field value.
+    /// Validates a single relation reference.
     /// Expects an object with entry_id and vault_id fields.
-    fn validate_relation(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
+    fn validate_relation_ref(field: &FieldDefinitionDto, value: &Value) -> Result<(), String> {
         let obj = value.as_object().ok_or_else(|| {
             format!(
                 "Field '{}': expected object with entry_id and vault_id",
@@ -369,8 +900,8 @@ impl MetadataService {
     }
 
     /// Gets field definitions for a vault, ordered by position.
-    async fn get_field_definitions(
-        conn: &DatabaseConnection,
+    pub(super) async fn get_field_definitions<C: ConnectionTrait>(
+        conn: &C,
         vault_id: i32,
     ) -> AppResult<Vec<FieldDefinitionDto>> {
         let fields = FieldDefinition::find()
@@ -412,9 +943,74 @@ impl MetadataService {
     }
 }
 
+/// Whether the condition of a required-if or visible-if rule holds for
+/// the normalized value of the field it tests. A multiselect value equals
+/// each of its choices.
+fn condition_holds(field: &FieldDefinitionDto, rule: &FieldRule, value: Option<&Value>) -> bool {
+    let expected = rule.value.as_ref();
+    let equals = || {
+        let (Some(value), Some(expected)) = (value, expected) else {
+            return false;
+        };
+        match value {
+            Value::Array(items) => items.contains(expected),
+            value => {
+                value == expected
+                    || compare_values(field.field_type, value, field.field_type, expected)
+                        == Some(Ordering::Equal)
+            }
+        }
+    };
+    match rule.operator {
+        RuleOperator::IsSet => value.is_some(),
+        RuleOperator::IsNotSet => value.is_none(),
+        RuleOperator::Equals => equals(),
+        RuleOperator::NotEquals => !equals(),
+        operator => match (value, expected) {
+            (Some(value), Some(expected)) => {
+                compare_values(field.field_type, value, field.field_type, expected)
+                    .is_some_and(|o| operator.accepts(o))
+            }
+            _ => false,
+        },
+    }
+}
+
+/// A rule's condition in words, like `'Status' is Finished`.
+fn describe_condition(field: &FieldDefinitionDto, rule: &FieldRule) -> String {
+    let value = rule.value.as_ref().map(|v| match choice_labels(field, v) {
+        Value::String(label) => label,
+        other => other.to_string(),
+    });
+    match (rule.operator, value) {
+        (RuleOperator::IsSet | RuleOperator::IsNotSet, _) | (_, None) => {
+            format!("'{}' is {}", field.name, rule.operator.describe(field.field_type))
+        }
+        (RuleOperator::Equals, Some(value)) => format!("'{}' is {}", field.name, value),
+        (RuleOperator::NotEquals, Some(value)) => format!("'{}' is not {}", field.name, value),
+        (operator, Some(value)) => format!(
+            "'{}' is {} {}",
+            field.name,
+            operator.describe(field.field_type),
+            value
+        ),
+    }
+}
+
+/// Whether a metadata value counts as filled in (not null or an empty list).
+pub(super) fn has_value(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::field::{Choice, FieldOptions, FieldRule, FieldRuleKind, RuleOperator};
+    use serde_json::json;
 
     #[test]
     fn test_parse_metadata() {
@@ -449,4 +1045,285 @@ mod tests {
         assert!(parsed.contains_key("1"));
         assert!(parsed.contains_key("2"));
     }
+
+    #[test]
+    fn test_check_rules() {
+        let field = |id, name: &str, field_type| FieldDefinitionDto {
+            id,
+            vault_id: 1,
+            name: name.to_string(),
+            field_type,
+            options: None,
+            position: id,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let status = FieldDefinitionDto {
+            options: Some(FieldOptions {
+                choices: Some(vec![Choice {
+                    id: "c1".to_string(),
+                    label: "Finished".to_string(),
+                    color: None,
+                }]),
+                ..Default::default()
+            }),
+            ..field(1, "Status", FieldType::Select)
+        };
+        let fields = vec![
+            status,
+            field(2, "Finished date", FieldType::Date),
+            field(3, "Start date", FieldType::Date),
+            field(4, "End date", FieldType::Datetime),
+        ];
+        let finished = |kind| FieldRule {
+            field_id: 2,
+            kind,
+            other_field_id: 1,
+            operator: RuleOperator::Equals,
+            value: Some(json!("c1")),
+            message: None,
+        };
+        let rules = vec![
+            finished(FieldRuleKind::VisibleIf),
+            finished(FieldRuleKind::RequiredIf),
+            FieldRule {
+                field_id: 4,
+                kind: FieldRuleKind::Compare,
+                other_field_id: 3,
+                operator: RuleOperator::GreaterOrEqual,
+                value: None,
+                message: None,
+            },
+        ];
+        let check = |metadata: Value| {
+            MetadataService::check_rules(&fields, &rules, metadata.as_object().unwrap())
+        };
+
+        // The finished date is hidden, so not required, until the status
+        // is Finished, given by ID or label
+        let outcome = check(json!({"3": "2024-05-01", "4": "2024-05-01T08:00:00Z"}));
+        assert!(outcome.hidden.contains(&2));
+        assert!(outcome.errors.is_empty());
+        let outcome = check(json!({"1": "finished"}));
+        assert!(outcome.hidden.is_empty());
+        assert_eq!(
+            outcome.errors,
+            vec![(2, "Field 'Finished date' is required when 'Status' is Finished".to_string())]
+        );
+
+        let outcome = check(json!({"1": "c1", "2": "2024-06-01", "3": "2024-05-02", "4": "2024-05-01T23:00:00Z"}));
+        assert_eq!(
+            outcome.errors,
+            vec![(4, "Field 'End date' must be on or after 'Start date'".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_validate_multiselect() {
+        let field = FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Genres".to_string(),
+            field_type: FieldType::Multiselect,
+            options: Some(FieldOptions {
+                choices: Some(vec![
+                    Choice {
+                        id: "c1".to_string(),
+                        label: "Drama".to_string(),
+                        color: None,
+                    },
+                    Choice {
+                        id: "c2".to_string(),
+                        label: "Thriller".to_string(),
+                        color: None,
+                    },
+                ]),
+                ..Default::default()
+            }),
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+
+        assert!(MetadataService::validate_field_value(&field, &json!(["c1", "c2"])).is_ok());
+        assert!(MetadataService::validate_field_value(&field, &json!([])).is_ok());
+        assert!(MetadataService::validate_field_value(&field, &json!("c1")).is_err());
+        assert!(MetadataService::validate_field_value(&field, &json!(["c1", "c3"])).is_err());
+        assert!(MetadataService::validate_field_value(&field, &json!(["c1", "c1"])).is_err());
+        // Entries store IDs, also of choices given by label
+        assert!(MetadataService::validate_field_value(&field, &json!(["Drama"])).is_err());
+        assert_eq!(
+            MetadataService::normalize_field_value(&field, &json!(["thriller", "c1"])),
+            Ok(json!(["c2", "c1"]))
+        );
+    }
+
+    #[test]
+    fn test_validate_rating() {
+        let mut field = FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Score".to_string(),
+            field_type: FieldType::Rating,
+            options: None,
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+
+        // The default scale goes up to 5 in whole steps
+        assert!(MetadataService::validate_field_value(&field, &json!(4)).is_ok());
+        assert!(MetadataService::validate_field_value(&field, &json!(6)).is_err());
+        assert!(MetadataService::validate_field_value(&field, &json!(3.5)).is_err());
+        assert!(MetadataService::validate_field_value(&field, &json!("4")).is_err());
+
+        field.options = Some(FieldOptions {
+            max: Some(10.0),
+            step: Some(0.5),
+            ..Default::default()
+        });
+        assert!(MetadataService::validate_field_value(&field, &json!(7.5)).is_ok());
+        assert!(MetadataService::validate_field_value(&field, &json!(7.25)).is_err());
+        assert!(MetadataService::validate_field_value(&field, &json!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_and_normalize_location() {
+        let field = FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Place".to_string(),
+            field_type: FieldType::Location,
+            options: None,
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let validate = |value| MetadataService::validate_field_value(&field, &value);
+
+        assert!(validate(json!({"lat": 35.0116, "lng": 135.7681, "name": "Kyoto"})).is_ok());
+        assert!(validate(json!({"lat": -90, "lng": 180})).is_ok());
+        assert!(validate(json!({"lat": 91, "lng": 0})).is_err());
+        assert!(validate(json!({"lat": 0, "lng": -180.5})).is_err());
+        assert!(validate(json!({"lat": "35", "lng": 135})).is_err());
+        assert!(validate(json!({"lat": 35, "lon": 135})).is_err());
+
+        let normalized = MetadataService::normalize_field_value(
+            &field,
+            &json!({"lat": 35.0116, "lng": 135.7681, "name": "  "}),
+        )
+        .unwrap();
+        assert_eq!(normalized, json!({"lat": 35.0116, "lng": 135.7681}));
+    }
+
+    #[test]
+    fn test_normalize_currency_and_json() {
+        let mut field = FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Price".to_string(),
+            field_type: FieldType::Currency,
+            options: Some(FieldOptions {
+                currency: Some("EUR".to_string()),
+                ..Default::default()
+            }),
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let normalize = |field: &FieldDefinitionDto, value| {
+            MetadataService::normalize_field_value(field, &value)
+        };
+
+        assert_eq!(
+            normalize(&field, json!("12.5")).unwrap(),
+            json!({"amount": "12.50", "currency": "EUR"})
+        );
+        assert!(normalize(&field, json!("12.50 USD")).is_err());
+
+        field.field_type = FieldType::Json;
+        field.options = Some(FieldOptions {
+            json_schema: Some(json!({"type": "array", "items": {"type": "integer"}})),
+            ..Default::default()
+        });
+        assert_eq!(normalize(&field, json!([1, 2])).unwrap(), json!([1, 2]));
+        assert_eq!(
+            normalize(&field, json!([1, "2"])).unwrap_err(),
+            "Field 'Price': expected integer at '/1'"
+        );
+
+        field.options = None;
+        assert!(normalize(&field, json!({"any": ["shape", null]})).is_ok());
+        assert!(normalize(&field, json!("x".repeat(MAX_JSON_LENGTH))).is_err());
+    }
+
+    fn relation_field(options: FieldOptions) -> FieldDefinitionDto {
+        FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Cast".to_string(),
+            field_type: FieldType::Relation,
+            options: Some(options),
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_validate_single_relation_rejects_list() {
+        let field = relation_field(FieldOptions {
+            target_vault_id: Some(2),
+            ..Default::default()
+        });
+
+        assert!(MetadataService::validate_relation(&field, &json!({"entry_id": 5, "vault_id": 2})).is_ok());
+        assert!(MetadataService::validate_relation(&field, &json!([{"entry_id": 5, "vault_id": 2}])).is_err());
+    }
+
+    #[test]
+    fn test_validate_multiple_relation() {
+        let field = relation_field(FieldOptions {
+            target_vault_id: Some(2),
+            multiple: Some(true),
+            min_items: Some(1),
+            max_items: Some(2),
+            ..Default::default()
+        });
+
+        let valid = json!([{"entry_id": 5, "vault_id": 2}, {"entry_id": 3, "vault_id": 2}]);
+        assert!(MetadataService::validate_relation(&field, &valid).is_ok());
+
+        // A lone reference counts as a one-item list
+        assert!(MetadataService::validate_relation(&field, &json!({"entry_id": 5, "vault_id": 2})).is_ok());
+
+        let duplicate = json!([{"entry_id": 5, "vault_id": 2}, {"entry_id": 5, "vault_id": 2}]);
+        assert!(MetadataService::validate_relation(&field, &duplicate).is_err());
+
+        let wrong_vault = json!([{"entry_id": 5, "vault_id": 2}, {"entry_id": 6, "vault_id": 3}]);
+        assert!(MetadataService::validate_relation(&field, &wrong_vault).is_err());
+
+        assert!(MetadataService::validate_relation(&field, &json!([])).is_err());
+
+        let too_many = json!([
+            {"entry_id": 1, "vault_id": 2},
+            {"entry_id": 2, "vault_id": 2},
+            {"entry_id": 3, "vault_id": 2}
+        ]);
+        assert!(MetadataService::validate_relation(&field, &too_many).is_err());
+    }
+
+    #[test]
+    fn test_has_value_treats_empty_list_as_missing() {
+        assert!(!has_value(&Value::Null));
+        assert!(!has_value(&json!([])));
+        assert!(has_value(&json!([{"entry_id": 1, "vault_id": 2}])));
+        assert!(has_value(&json!(false)));
+    }
 }
@@ -3,16 +3,48 @@
 //! This module is organized following Single Responsibility Principle:
 //! - `service.rs` - Core CRUD operations (create, get, list, count, update, delete)
 //! - `image_service.rs` - Cover image operations (set, remove, thumbnail)
+//! - `image_metadata_service.rs` - Image tag to field mappings
 //! - `search_service.rs` - Full-text search operations
-//! - `metadata_service.rs` - Metadata validation and orphan cleanup
+//! - `export_service.rs` - Vault export with derived field values
+//! - `constraint_service.rs` - Minimum lengths, patterns and unique values of fields
+//! - `default_service.rs` - Default values of fields on new and existing entries
+//! - `facet_service.rs` - Entry filters, per-choice counts and numeric summaries
+//! - `identifier_service.rs` - Lookups by identifier
+//! - `metadata_service.rs` - Metadata validation, normalization and orphan cleanup
+//! - `markdown_service.rs` - Markdown rendering with wiki-links resolved to entries
+//! - `markdown.rs` - Sanitizing, plain text and HTML for markdown values
+//! - `temporal.rs` - Parsing of partial dates, timestamps and durations
+//! - `contact.rs` - Parsing of email addresses and phone numbers
+//! - `color.rs` - Parsing and ordering of colors
+//! - `money.rs` - Parsing of currency amounts
 
+mod color;
+mod constraint_service;
+mod contact;
+mod default_service;
+mod export_service;
+mod facet_service;
+mod identifier_service;
+mod image_metadata_service;
 mod image_service;
+mod markdown;
+mod markdown_service;
 mod metadata_service;
 mod model;
+mod money;
 mod search_service;
 mod service;
+mod temporal;
 
-pub use image_service::EntryImageService;
+pub use color::normalize_color;
+pub use constraint_service::EntryConstraintService;
+pub use default_service::EntryDefaultService;
+pub use export_service::EntryExportService;
+pub use facet_service::EntryFacetService;
+pub use identifier_service::EntryIdentifierService;
+pub use image_metadata_service::ImageMetadataService;
+pub use image_service::{DEFAULT_DUPLICATE_DISTANCE, EntryImageService};
+pub use markdown_service::EntryMarkdownService;
 pub use metadata_service::MetadataService;
 pub use model::*;
 pub use search_service::EntrySearchService;
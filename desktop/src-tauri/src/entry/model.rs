@@ -1,6 +1,7 @@
 //! Entry data transfer objects.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// DTO for creating a new entry.
 #[derive(Debug, Deserialize)]
@@ -28,6 +29,14 @@ pub struct EntryDto {
     pub description: Option<String>,
     pub metadata: Option<String>,
     pub cover_image_path: Option<String>,
+    pub cover_original_path: Option<String>,
+    /// BlurHash placeholder for the cover image
+    pub cover_blurhash: Option<String>,
+    /// Dominant cover colors as `#rrggbb`, most common first
+    pub cover_palette: Vec<String>,
+    /// Lookup, rollup and formula values keyed by field ID, like `metadata`
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub computed: Map<String, Value>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -41,12 +50,140 @@ impl From<crate::entities::entry::Model> for EntryDto {
             description: model.description,
             metadata: model.metadata,
             cover_image_path: model.cover_image_path,
+            cover_original_path: model.cover_original_path,
+            cover_blurhash: model.cover_blurhash,
+            cover_palette: parse_palette(model.cover_palette.as_deref()),
+            computed: Map::new(),
             created_at: model.created_at,
             updated_at: model.updated_at,
         }
     }
 }
 
+/// Parses a stored cover palette, treating missing or invalid data as empty.
+pub(crate) fn parse_palette(palette: Option<&str>) -> Vec<String> {
+    palette
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+/// Sort order for entry listings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntrySort {
+    /// Field to sort by, stored or derived (default: title)
+    #[serde(default)]
+    pub field_id: Option<i32>,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// How a choice filter matches the selected values of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChoiceMatch {
+    /// At least one of the choices is selected
+    #[default]
+    Any,
+    /// Every one of the choices is selected
+    All,
+}
+
+/// Restricts an entry listing by the choices of a select or multiselect field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChoiceFilter {
+    pub field_id: i32,
+    pub choices: Vec<String>,
+    #[serde(default)]
+    pub mode: ChoiceMatch,
+}
+
+/// Bound of a range filter: a number, or a date or timestamp.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum RangeBound {
+    Number(f64),
+    Text(String),
+}
+
+/// Restricts an entry listing to values within a range.
+///
+/// Numbers, ratings and durations compare by value; dates and timestamps
+/// compare by their normalized text. Both bounds are inclusive and a missing
+/// bound is open. A text upper bound also covers values it is a prefix of,
+/// so `2024` includes every date in 2024.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeFilter {
+    pub field_id: i32,
+    #[serde(default)]
+    pub min: Option<RangeBound>,
+    #[serde(default)]
+    pub max: Option<RangeBound>,
+}
+
+/// Restricts an entry listing to locations inside a bounding box.
+///
+/// Bounds are inclusive degrees. A `west` greater than `east` describes a
+/// box crossing the antimeridian.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundsFilter {
+    pub field_id: i32,
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+/// Restricts an entry listing to locations within a distance of a point.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistanceFilter {
+    pub field_id: i32,
+    pub lat: f64,
+    pub lng: f64,
+    /// Great-circle distance in kilometers, inclusive
+    pub radius_km: f64,
+}
+
+/// Filters applied to an entry listing. Every filter must match.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryFilters {
+    #[serde(default)]
+    pub choices: Vec<ChoiceFilter>,
+    #[serde(default)]
+    pub ranges: Vec<RangeFilter>,
+    #[serde(default)]
+    pub within_bounds: Vec<BoundsFilter>,
+    #[serde(default)]
+    pub within_distance: Vec<DistanceFilter>,
+}
+
+/// Number of entries that have a choice selected.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ChoiceCount {
+    /// ID of the choice, as stored in entries
+    pub choice: String,
+    /// Label of the choice; the stored value for values that are no
+    /// longer a choice
+    pub label: String,
+    pub color: Option<String>,
+    pub count: u64,
+}
+
+/// Aggregates of a number, rating or duration field over a vault's entries.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct NumericSummary {
+    /// Entries with a value
+    pub count: u64,
+    pub average: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
 /// Paginated response for entries.
 #[derive(Debug, Serialize)]
 pub struct PaginatedEntries {
@@ -67,3 +204,58 @@ pub struct SearchResult {
     pub limit: u64,
     pub has_more: bool,
 }
+
+/// Entry named by a `[[Title]]` wiki-link in markdown.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub title: String,
+    /// The entry of the same vault with this title, if any
+    pub entry_id: Option<i32>,
+}
+
+/// Markdown rendered to safe HTML, with its wiki-links resolved.
+#[derive(Debug, Serialize)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    pub links: Vec<WikiLink>,
+}
+
+/// Entry as written to a vault export, with values keyed by field name.
+#[derive(Debug, Serialize)]
+pub struct ExportedEntry {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    /// Stored and derived field values
+    pub values: Map<String, Value>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// All entries of a vault, including lookup and rollup values.
+#[derive(Debug, Serialize)]
+pub struct VaultExport {
+    pub vault_id: i32,
+    pub vault_name: String,
+    pub fields: Vec<crate::field::FieldDefinitionDto>,
+    pub entries: Vec<ExportedEntry>,
+}
+
+/// Entry in a cluster of near-identical cover images.
+#[derive(Debug, Serialize, Clone)]
+pub struct DuplicateCoverEntry {
+    pub entry_id: i32,
+    pub vault_id: i32,
+    pub title: String,
+    pub cover_image_path: Option<String>,
+    pub cover_phash: String,
+}
+
+/// Group of entries whose covers are within the distance threshold of
+/// each other, directly or through other members of the group.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCoverCluster {
+    pub entries: Vec<DuplicateCoverEntry>,
+    /// Largest Hamming distance between any two covers in the group
+    pub max_distance: u32,
+}
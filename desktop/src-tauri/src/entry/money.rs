@@ -0,0 +1,192 @@
+//! Parsing and normalization of currency amounts.
+//!
+//! Amounts are stored exactly, as decimal strings with the currency's
+//! number of minor digits, next to their ISO 4217 code:
+//! `{"amount": "12.50", "currency": "EUR"}`. Amounts of different
+//! currencies are never compared, so they sort by code, then amount.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::field::currency_minor_units;
+
+/// Accepted currency formats, for error messages.
+pub const MONEY_FORMATS: &str = "an amount with an ISO 4217 code, e.g. 12.50 EUR, EUR 12.50 or {\"amount\": \"12.50\", \"currency\": \"EUR\"}";
+
+/// Most digits before the decimal point.
+const MAX_INTEGER_DIGITS: usize = 15;
+
+/// Value stored in metadata for currency fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    /// Exact decimal amount, e.g. `-1234.50`
+    pub amount: String,
+    /// Uppercase ISO 4217 code
+    pub currency: String,
+}
+
+/// Normalizes an amount given as `{"amount", "currency"}`, as text such as
+/// `12.50 EUR` or `EUR 12.50`, or as a bare number or text when the field
+/// has a fixed currency. Amounts in another currency than the fixed one,
+/// or with more decimals than the currency has, are rejected.
+pub fn normalize_money(value: &Value, fixed_currency: Option<&str>) -> Option<Money> {
+    let (amount, currency) = match value {
+        Value::Object(object) => {
+            let amount = match object.get("amount")? {
+                Value::String(text) => text.trim().to_string(),
+                Value::Number(number) => number.to_string(),
+                _ => return None,
+            };
+            let currency = match object.get("currency") {
+                Some(Value::String(code)) => Some(code.trim().to_string()),
+                None | Some(Value::Null) => None,
+                Some(_) => return None,
+            };
+            if object.keys().any(|k| k != "amount" && k != "currency") {
+                return None;
+            }
+            (amount, currency)
+        }
+        Value::String(text) => {
+            let parts: Vec<&str> = text.split_whitespace().collect();
+            let is_code =
+                |part: &str| part.len() == 3 && part.bytes().all(|b| b.is_ascii_alphabetic());
+            match parts.as_slice() {
+                [amount] => (amount.to_string(), None),
+                [code, amount] if is_code(code) => (amount.to_string(), Some(code.to_string())),
+                [amount, code] if is_code(code) => (amount.to_string(), Some(code.to_string())),
+                _ => return None,
+            }
+        }
+        Value::Number(number) => (number.to_string(), None),
+        _ => return None,
+    };
+
+    let currency = match (currency.map(|c| c.to_ascii_uppercase()), fixed_currency) {
+        (Some(code), Some(fixed)) if code != fixed => return None,
+        (Some(code), _) => code,
+        (None, Some(fixed)) => fixed.to_string(),
+        (None, None) => return None,
+    };
+    let minor_units = currency_minor_units(&currency)?;
+
+    Some(Money {
+        amount: normalize_amount(&amount, minor_units)?,
+        currency,
+    })
+}
+
+/// Writes a plain decimal with exactly `minor_units` decimals. Extra
+/// decimals are accepted only when they are zeros, so nothing is rounded.
+fn normalize_amount(text: &str, minor_units: u32) -> Option<String> {
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let integer = integer.trim_start_matches('0');
+    let integer = if integer.is_empty() { "0" } else { integer };
+    if integer.len() > MAX_INTEGER_DIGITS {
+        return None;
+    }
+
+    let minor_units = minor_units as usize;
+    let (kept, dropped) = fraction.split_at(fraction.len().min(minor_units));
+    if dropped.bytes().any(|b| b != b'0') {
+        return None;
+    }
+    let fraction = format!("{:0<width$}", kept, width = minor_units);
+
+    let is_zero = integer == "0" && fraction.bytes().all(|b| b == b'0');
+    let sign = if negative && !is_zero { "-" } else { "" };
+    Some(if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    })
+}
+
+/// Sort key of a stored amount: its currency code, then its value.
+pub fn money_sort_key(value: &Value) -> Option<Value> {
+    let money: Money = serde_json::from_value(value.clone()).ok()?;
+    let amount: f64 = money.amount.parse().ok()?;
+    Some(Value::Array(vec![
+        Value::String(money.currency),
+        Value::from(amount),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn money(amount: &str, currency: &str) -> Option<Money> {
+        Some(Money {
+            amount: amount.to_string(),
+            currency: currency.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_normalize_money() {
+        assert_eq!(
+            normalize_money(&json!("12.5 eur"), None),
+            money("12.50", "EUR")
+        );
+        assert_eq!(
+            normalize_money(&json!("USD -0042"), None),
+            money("-42.00", "USD")
+        );
+        assert_eq!(
+            normalize_money(&json!({"amount": 1500, "currency": "JPY"}), None),
+            money("1500", "JPY")
+        );
+        assert_eq!(
+            normalize_money(&json!({"amount": "0.125", "currency": "KWD"}), None),
+            money("0.125", "KWD")
+        );
+        assert_eq!(
+            normalize_money(&json!("-0.00 EUR"), None),
+            money("0.00", "EUR")
+        );
+        assert_eq!(
+            normalize_money(&json!("3.100 EUR"), None),
+            money("3.10", "EUR")
+        );
+
+        // Fixed currencies accept bare amounts and reject other codes
+        assert_eq!(
+            normalize_money(&json!(9.99), Some("GBP")),
+            money("9.99", "GBP")
+        );
+        assert!(normalize_money(&json!("9.99 EUR"), Some("GBP")).is_none());
+        assert!(normalize_money(&json!(9.99), None).is_none());
+
+        // Nothing is rounded
+        assert!(normalize_money(&json!("12.345 EUR"), None).is_none());
+        assert!(normalize_money(&json!("1.5 JPY"), None).is_none());
+        assert!(normalize_money(&json!("12,50 EUR"), None).is_none());
+        assert!(normalize_money(&json!("12.50 XYZ"), None).is_none());
+        assert!(
+            normalize_money(&json!({"amount": "1", "currency": "EUR", "x": 1}), None).is_none()
+        );
+        assert!(normalize_money(&json!("1e3 EUR"), None).is_none());
+    }
+
+    #[test]
+    fn test_money_sort_key() {
+        let key = |value| money_sort_key(&value).unwrap();
+        assert_eq!(
+            key(json!({"amount": "-1.50", "currency": "EUR"})),
+            json!(["EUR", -1.5])
+        );
+        assert!(money_sort_key(&json!("12.50 EUR")).is_none());
+    }
+}
@@ -5,10 +5,12 @@
 //! - Search query building with prefix matching
 
 use sea_orm::DatabaseConnection;
+use serde_json::Map;
 
 use crate::core::{AppResult, find_vault_or_error};
+use crate::relation::DerivedFieldService;
 
-use super::{EntryDto, SearchResult};
+use super::{parse_palette, EntryDto, SearchResult};
 
 /// Service for entry search operations.
 pub struct EntrySearchService;
@@ -66,7 +68,7 @@ impl EntrySearchService {
         // Get matching entries with pagination
         let search_sql = format!(
             r#"
-            SELECT e.id, e.vault_id, e.title, e.description, e.metadata, e.cover_image_path, e.created_at, e.updated_at
+            SELECT e.id, e.vault_id, e.title, e.description, e.metadata, e.cover_image_path, e.cover_original_path, e.cover_blurhash, e.cover_palette, e.created_at, e.updated_at
             FROM entries e
             INNER JOIN entries_fts fts ON e.id = fts.rowid
             WHERE e.vault_id = {} AND entries_fts MATCH '{}'
@@ -82,7 +84,7 @@ impl EntrySearchService {
         )
         .await?;
 
-        let entries: Vec<EntryDto> = rows
+        let mut entries: Vec<EntryDto> = rows
             .into_iter()
             .filter_map(|row| {
                 Some(EntryDto {
@@ -92,12 +94,20 @@ impl EntrySearchService {
                     description: row.try_get_by_index::<Option<String>>(3).ok()?,
                     metadata: row.try_get_by_index::<Option<String>>(4).ok()?,
                     cover_image_path: row.try_get_by_index::<Option<String>>(5).ok()?,
-                    created_at: row.try_get_by_index::<String>(6).ok()?,
-                    updated_at: row.try_get_by_index::<String>(7).ok()?,
+                    cover_original_path: row.try_get_by_index::<Option<String>>(6).ok()?,
+                    cover_blurhash: row.try_get_by_index::<Option<String>>(7).ok()?,
+                    cover_palette: parse_palette(
+                        row.try_get_by_index::<Option<String>>(8).ok()?.as_deref(),
+                    ),
+                    computed: Map::new(),
+                    created_at: row.try_get_by_index::<String>(9).ok()?,
+                    updated_at: row.try_get_by_index::<String>(10).ok()?,
                 })
             })
             .collect();
 
+        DerivedFieldService::attach(conn, &mut entries).await?;
+
         let has_more = ((page + 1) * limit) < total as u64;
 
         log::debug!(
@@ -2,7 +2,7 @@
 //!
 //! This service handles the core entry management operations:
 //! - Create, Read, Update, Delete (CRUD)
-//! - Listing with pagination
+//! - Listing with pagination, sorting and filters
 //! - Counting entries
 //! - Metadata validation and cleanup
 //!
@@ -10,18 +10,31 @@
 //! For search operations, see `search_service.rs`.
 //! For metadata operations, see `metadata_service.rs`.
 
+use sea_orm::sea_query::{Expr, Order, SimpleExpr};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
-    QueryOrder, Set,
+    QueryOrder, Set, TransactionTrait,
 };
+use serde_json::Value;
 
+use crate::attachment::{AttachmentRegistry, AttachmentStorage};
 use crate::core::{AppError, AppResult, now_formatted, find_vault_or_error};
 use crate::entities::entry::{self, ActiveModel, Entity as Entry};
+use crate::entities::field_definition::Entity as FieldDefinition;
+use crate::field::{FieldDefinitionDto, FieldType, choice_sort_key};
 
 use crate::image::ImageStorage;
-
+use crate::location::LocationIndexService;
+use crate::relation::{DerivedFieldService, ReferentialActionService, RelationIndexService};
+
+use super::color::color_sort_key;
+use super::constraint_service::EntryConstraintService;
+use super::default_service::EntryDefaultService;
+use super::facet_service::{EntryFacetService, METADATA_JSON};
+use super::image_service::EntryImageService;
 use super::metadata_service::MetadataService;
-use super::{CreateEntryDto, EntryDto, PaginatedEntries, UpdateEntryDto};
+use super::money::money_sort_key;
+use super::{CreateEntryDto, EntryDto, EntryFilters, EntrySort, PaginatedEntries, UpdateEntryDto};
 
 /// Service for entry CRUD operations.
 pub struct EntryService;
@@ -29,7 +42,11 @@ pub struct EntryService;
 impl EntryService {
     /// Creates a new entry in a vault.
     ///
-    /// Validates required fields if metadata is provided.
+    /// Fills in the default values of fields the metadata leaves empty,
+    /// validates required fields if there is metadata, normalizes dates,
+    /// timestamps, durations, identifiers and markdown, rejects values
+    /// breaking the minimum length, pattern or uniqueness of their field,
+    /// and indexes its relation values and markdown text.
     pub async fn create(conn: &DatabaseConnection, dto: CreateEntryDto) -> AppResult<EntryDto> {
         // Validate title is not empty
         if dto.title.trim().is_empty() {
@@ -39,12 +56,19 @@ impl EntryService {
         // Verify vault exists
         find_vault_or_error(conn, dto.vault_id).await?;
 
+        // Counter defaults are read in the insert's transaction
+        let txn = conn.begin().await?;
+
+        // Fill in default values before checking required fields
+        let metadata =
+            EntryDefaultService::apply(&txn, dto.vault_id, dto.metadata.as_deref()).await?;
+
         // Validate required fields if metadata is provided
-        if dto.metadata.is_some() {
+        if metadata.is_some() {
             let validation = MetadataService::validate_required_fields(
-                conn,
+                &txn,
                 dto.vault_id,
-                dto.metadata.as_deref(),
+                metadata.as_deref(),
             )
             .await?;
 
@@ -53,19 +77,38 @@ impl EntryService {
             }
         }
 
+        let metadata = match metadata {
+            Some(metadata) => {
+                Some(MetadataService::normalize_metadata(&txn, dto.vault_id, &metadata).await?)
+            }
+            None => None,
+        };
+        EntryConstraintService::check(&txn, dto.vault_id, None, metadata.as_deref()).await?;
+        let search_text =
+            MetadataService::search_text(&txn, dto.vault_id, metadata.as_deref()).await?;
+
         let now = now_formatted();
 
         let active_model = ActiveModel {
             vault_id: Set(dto.vault_id),
             title: Set(dto.title.trim().to_string()),
             description: Set(dto.description),
-            metadata: Set(dto.metadata),
+            metadata: Set(metadata),
+            search_text: Set(search_text),
             created_at: Set(now.clone()),
             updated_at: Set(now),
             ..Default::default()
         };
 
-        let result = active_model.insert(conn).await?;
+        let result = active_model.insert(&txn).await?;
+        RelationIndexService::sync_entry(&txn, result.id, result.vault_id, result.metadata.as_deref())
+            .await?;
+        LocationIndexService::sync_entry(&txn, result.id, result.vault_id, result.metadata.as_deref())
+            .await?;
+        AttachmentRegistry::sync_entry(&txn, result.vault_id, None, result.metadata.as_deref())
+            .await?;
+        txn.commit().await?;
+
         log::info!(
             "Created entry: {} (id={}) in vault {}",
             result.title,
@@ -76,38 +119,59 @@ impl EntryService {
         Ok(EntryDto::from(result))
     }
 
-    /// Gets an entry by ID.
+    /// Gets an entry by ID, including its lookup and rollup values.
     pub async fn get(conn: &DatabaseConnection, id: i32) -> AppResult<EntryDto> {
         let entry = Entry::find_by_id(id)
             .one(conn)
             .await?
             .ok_or(AppError::EntryNotFound(id))?;
 
-        Ok(EntryDto::from(entry))
+        let mut entries = [EntryDto::from(entry)];
+        DerivedFieldService::attach(conn, &mut entries).await?;
+        let [entry] = entries;
+
+        Ok(entry)
     }
 
     /// Lists entries for a vault with pagination.
+    ///
+    /// Without a sort order entries are listed newest first.
+    /// Only entries matching every filter are listed.
     pub async fn list(
         conn: &DatabaseConnection,
         vault_id: i32,
         page: u64,
         limit: u64,
+        sort: Option<&EntrySort>,
+        filters: &EntryFilters,
     ) -> AppResult<PaginatedEntries> {
         // Verify vault exists
         find_vault_or_error(conn, vault_id).await?;
 
-        let paginator = Entry::find()
-            .filter(entry::Column::VaultId.eq(vault_id))
-            .order_by_desc(entry::Column::CreatedAt)
-            .paginate(conn, limit);
-
-        let total = paginator.num_items().await?;
-        let entries = paginator.fetch_page(page).await?;
+        let (total, entries) = match sort {
+            Some(sort) => Self::list_sorted(conn, vault_id, filters, sort, page, limit).await?,
+            None => {
+                let paginator = EntryFacetService::filtered_query(vault_id, filters)
+                    .order_by_desc(entry::Column::CreatedAt)
+                    .paginate(conn, limit);
+
+                let total = paginator.num_items().await?;
+                let mut entries: Vec<EntryDto> = paginator
+                    .fetch_page(page)
+                    .await?
+                    .into_iter()
+                    .map(EntryDto::from)
+                    .collect();
+                DerivedFieldService::attach(conn, &mut entries).await?;
+
+                (total, entries)
+            }
+        };
 
-        let has_more = (page + 1) * limit < total as u64;
+        let has_more = (page + 1) * limit < total;
 
         Ok(PaginatedEntries {
-            entries: entries.into_iter().map(EntryDto::from).collect(),
+            entries,
             total: total as i64,
             page,
             limit,
@@ -115,6 +179,195 @@ impl EntryService {
         })
     }
 
+    /// Sorts the entries of a vault by their title or a field, then cuts
+    /// out one page.
+    ///
+    /// Titles and stored values sort in SQL on their normalized stored form,
+    /// amounts by currency, then value, and choices in option order.
+    /// Entries without a value sort last in either direction.
+    async fn list_sorted(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        filters: &EntryFilters,
+        sort: &EntrySort,
+        page: u64,
+        limit: u64,
+    ) -> AppResult<(u64, Vec<EntryDto>)> {
+        let field = match sort.field_id {
+            Some(field_id) => FieldDefinition::find_by_id(field_id)
+                .one(conn)
+                .await?
+                .map(FieldDefinitionDto::from),
+            None => None,
+        };
+        let Some(keys) = Self::sql_sort_keys(sort.field_id, field.as_ref()) else {
+            return Self::list_sorted_in_memory(conn, vault_id, filters, sort, field, page, limit)
+                .await;
+        };
+
+        let mut query = EntryFacetService::filtered_query(vault_id, filters);
+        if let Some(field_id) = sort.field_id {
+            query = query.order_by_asc(Expr::cust_with_values(
+                format!("json_extract({}, ?) IS NULL", METADATA_JSON),
+                [Self::json_path(field_id)],
+            ));
+        }
+        let order = if sort.descending { Order::Desc } else { Order::Asc };
+        for key in keys {
+            query = query.order_by(key, order.clone());
+        }
+        let paginator = query
+            .order_by_desc(entry::Column::CreatedAt)
+            .paginate(conn, limit);
+
+        let total = paginator.num_items().await?;
+        let mut entries: Vec<EntryDto> = paginator
+            .fetch_page(page)
+            .await?
+            .into_iter()
+            .map(EntryDto::from)
+            .collect();
+        DerivedFieldService::attach(conn, &mut entries).await?;
+
+        Ok((total, entries))
+    }
+
+    /// SQL expressions entries sort by, in order. `None` for fields whose
+    /// values only sort in memory: lookup, rollup and formula values have
+    /// no column, colors sort around the color wheel, and multiselect,
+    /// JSON, file, location and relation values are not scalars.
+    fn sql_sort_keys(
+        field_id: Option<i32>,
+        field: Option<&FieldDefinitionDto>,
+    ) -> Option<Vec<SimpleExpr>> {
+        let Some(field_id) = field_id else {
+            return Some(vec![Expr::cust("entries.title COLLATE NOCASE")]);
+        };
+        let path = Self::json_path(field_id);
+        let value = format!("json_extract({}, ?)", METADATA_JSON);
+
+        match field.map(|f| f.field_type) {
+            Some(
+                FieldType::Lookup
+                | FieldType::Rollup
+                | FieldType::Formula
+                | FieldType::Color
+                | FieldType::Multiselect
+                | FieldType::Json
+                | FieldType::File
+                | FieldType::Location
+                | FieldType::Relation,
+            ) => None,
+            Some(FieldType::Currency) => Some(vec![
+                Expr::cust_with_values(value.clone(), [format!("{}.currency", path)]),
+                Expr::cust_with_values(
+                    format!("CAST({} AS REAL)", value),
+                    [format!("{}.amount", path)],
+                ),
+            ]),
+            Some(FieldType::Select) => {
+                let choices = field
+                    .and_then(|f| f.options.as_ref())
+                    .and_then(|o| o.choices.as_deref())
+                    .unwrap_or_default();
+                if choices.is_empty() {
+                    return Some(vec![Expr::cust_with_values(value, [path])]);
+                }
+
+                // Unknown choices sort after the listed ones
+                let mut sql = format!("CASE {}", value);
+                let mut values: Vec<sea_orm::Value> = vec![path.into()];
+                for (position, choice) in choices.iter().enumerate() {
+                    sql.push_str(&format!(" WHEN ? THEN {}", position));
+                    values.push(choice.id.clone().into());
+                }
+                sql.push_str(&format!(" ELSE {} END", choices.len()));
+                Some(vec![Expr::cust_with_values(sql, values)])
+            }
+            _ => Some(vec![Expr::cust_with_values(
+                format!("{} COLLATE NOCASE", value),
+                [path],
+            )]),
+        }
+    }
+
+    fn json_path(field_id: i32) -> String {
+        format!("$.\"{}\"", field_id)
+    }
+
+    /// Sorts all entries of a vault by a field whose values only sort in
+    /// memory, then cuts out one page.
+    async fn list_sorted_in_memory(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        filters: &EntryFilters,
+        sort: &EntrySort,
+        field: Option<FieldDefinitionDto>,
+        page: u64,
+        limit: u64,
+    ) -> AppResult<(u64, Vec<EntryDto>)> {
+        let mut entries: Vec<EntryDto> = EntryFacetService::filtered_query(vault_id, filters)
+            .order_by_desc(entry::Column::CreatedAt)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(EntryDto::from)
+            .collect();
+        DerivedFieldService::attach(conn, &mut entries).await?;
+
+        let mut keyed: Vec<(Value, EntryDto)> = entries
+            .into_iter()
+            .map(|e| {
+                let key = match sort.field_id {
+                    None => Value::String(e.title.clone()),
+                    Some(field_id) => e
+                        .computed
+                        .get(&field_id.to_string())
+                        .cloned()
+                        .or_else(|| {
+                            MetadataService::parse_metadata(e.metadata.as_deref()).remove(&field_id)
+                        })
+                        .map(|value| Self::sort_key(field.as_ref(), value))
+                        .unwrap_or(Value::Null),
+                };
+                (key, e)
+            })
+            .collect();
+
+        keyed.sort_by(|(a, _), (b, _)| {
+            if sort.descending && !a.is_null() && !b.is_null() {
+                DerivedFieldService::compare_values(b, a)
+            } else {
+                DerivedFieldService::compare_values(a, b)
+            }
+        });
+
+        let total = keyed.len() as u64;
+        let entries = keyed
+            .into_iter()
+            .map(|(_, e)| e)
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .collect();
+
+        Ok((total, entries))
+    }
+
+    /// Maps a value to the value it sorts by. Colors sort by hue,
+    /// lightness and saturation, amounts by `[currency, amount]` and
+    /// choices in the order the field lists them.
+    fn sort_key(field: Option<&FieldDefinitionDto>, value: Value) -> Value {
+        let key = match field.map(|f| f.field_type) {
+            Some(FieldType::Color) => value.as_str().and_then(color_sort_key).map(Value::from),
+            Some(FieldType::Currency) => money_sort_key(&value),
+            Some(FieldType::Select | FieldType::Multiselect) => {
+                field.and_then(|f| choice_sort_key(f, &value))
+            }
+            _ => None,
+        };
+        key.unwrap_or(value)
+    }
+
     /// Counts entries in a vault.
     pub async fn count(conn: &DatabaseConnection, vault_id: i32) -> AppResult<i64> {
         let count = Entry::find()
@@ -128,10 +381,15 @@ impl EntryService {
     /// Updates an existing entry.
     ///
     /// Implements lazy cleanup: removes orphan field data when metadata is updated.
+    /// Dates, timestamps, durations, identifiers and markdown are normalized,
+    /// constraints and unique values are checked, and the relation and search indexes
+    /// are kept in sync with the new metadata. Attachments the new metadata no
+    /// longer holds are released when storage is provided.
     pub async fn update(
         conn: &DatabaseConnection,
         id: i32,
         dto: UpdateEntryDto,
+        attachment_storage: Option<&AttachmentStorage>,
     ) -> AppResult<EntryDto> {
         let entry = Entry::find_by_id(id)
             .one(conn)
@@ -153,14 +411,12 @@ impl EntryService {
             active_model.description = Set(Some(description));
         }
 
+        let metadata_changed = dto.metadata.is_some();
         if let Some(metadata) = dto.metadata {
             // Validate required fields
-            let validation = MetadataService::validate_required_fields(
-                conn,
-                entry.vault_id,
-                Some(&metadata),
-            )
-            .await?;
+            let validation =
+                MetadataService::validate_required_fields(conn, entry.vault_id, Some(&metadata))
+                    .await?;
 
             if !validation.is_valid {
                 return Err(AppError::Validation(validation.errors.join("; ")));
@@ -169,42 +425,115 @@ impl EntryService {
             // Cleanup orphan data (Lazy Cleanup on Write strategy)
             let cleaned_metadata =
                 MetadataService::cleanup_orphan_data(conn, entry.vault_id, &metadata).await?;
+            let normalized_metadata =
+                MetadataService::normalize_metadata(conn, entry.vault_id, &cleaned_metadata)
+                    .await?;
+            EntryConstraintService::check(
+                conn,
+                entry.vault_id,
+                Some(id),
+                Some(&normalized_metadata),
+            )
+            .await?;
 
-            active_model.metadata = Set(Some(cleaned_metadata));
+            active_model.search_text = Set(MetadataService::search_text(
+                conn,
+                entry.vault_id,
+                Some(&normalized_metadata),
+            )
+            .await?);
+            active_model.metadata = Set(Some(normalized_metadata));
         }
 
         active_model.updated_at = Set(now);
 
-        let result = active_model.update(conn).await?;
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        let mut unreferenced = Vec::new();
+        if metadata_changed {
+            RelationIndexService::sync_entry(
+                &txn,
+                result.id,
+                result.vault_id,
+                result.metadata.as_deref(),
+            )
+            .await?;
+            LocationIndexService::sync_entry(
+                &txn,
+                result.id,
+                result.vault_id,
+                result.metadata.as_deref(),
+            )
+            .await?;
+            unreferenced = AttachmentRegistry::sync_entry(
+                &txn,
+                result.vault_id,
+                entry.metadata.as_deref(),
+                result.metadata.as_deref(),
+            )
+            .await?;
+        }
+        txn.commit().await?;
+
+        if let Some(storage) = attachment_storage {
+            AttachmentRegistry::delete_files(storage, &unreferenced);
+        }
+
         log::info!("Updated entry: {} (id={})", result.title, result.id);
 
         Ok(EntryDto::from(result))
     }
 
-    /// Deletes an entry and its cover image if present.
+    /// Deletes an entry and releases its cover image if present.
+    ///
+    /// Relation fields referencing the entry apply their on-delete policy:
+    /// restrict blocks the deletion, set-null removes the reference and
+    /// cascade deletes the referencing entries too.
     pub async fn delete(
         conn: &DatabaseConnection,
         id: i32,
         image_storage: Option<&ImageStorage>,
+        attachment_storage: Option<&AttachmentStorage>,
     ) -> AppResult<()> {
-        let entry = Entry::find_by_id(id)
-            .one(conn)
-            .await?
-            .ok_or(AppError::EntryNotFound(id))?;
+        let txn = conn.begin().await?;
+        let plan = ReferentialActionService::plan_entry_delete(&txn, id).await?;
+        ReferentialActionService::ensure_allowed(&plan)?;
 
-        log::info!("Deleting entry: {} (id={})", entry.title, entry.id);
+        for entry in plan.entries() {
+            log::info!("Deleting entry: {} (id={})", entry.title, entry.id);
+        }
 
-        // Delete cover image if present
-        if let Some(cover_path) = &entry.cover_image_path {
-            if let Some(storage) = image_storage {
-                if let Err(e) = storage.delete_image(cover_path) {
-                    log::warn!("Failed to delete cover image during entry deletion: {}", e);
-                    // Continue with entry deletion even if image deletion fails
-                }
-            }
+        ReferentialActionService::apply(&txn, &plan).await?;
+        let mut unreferenced = Vec::new();
+        for entry in plan.entries() {
+            unreferenced.extend(
+                AttachmentRegistry::sync_entry(
+                    &txn,
+                    entry.vault_id,
+                    entry.metadata.as_deref(),
+                    None,
+                )
+                .await?,
+            );
+        }
+        // Release cover images; files are removed once unreferenced
+        let unreferenced_covers = EntryImageService::release_covers(
+            &txn,
+            plan.entries()
+                .iter()
+                .flat_map(|e| [&e.cover_image_path, &e.cover_original_path])
+                .flatten(),
+        )
+        .await?;
+        txn.commit().await?;
+
+        if let Some(storage) = image_storage {
+            EntryImageService::delete_covers(storage, &unreferenced_covers);
         }
 
-        Entry::delete_by_id(id).exec(conn).await?;
+        if let Some(storage) = attachment_storage {
+            AttachmentRegistry::delete_files(storage, &unreferenced);
+        }
 
         Ok(())
     }
@@ -213,6 +542,7 @@ impl EntryService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entry::EntryIdentifierService;
     use sea_orm::{Database, DatabaseConnection};
 
     async fn setup_test_db() -> DatabaseConnection {
@@ -239,9 +569,49 @@ mod tests {
                 description TEXT,
                 metadata TEXT,
                 cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
             
             INSERT INTO vaults (name, created_at, updated_at) VALUES ('Test Vault', datetime('now'), datetime('now'));
             "#,
@@ -322,19 +692,146 @@ mod tests {
         }
 
         // Get first page
-        let page1 = EntryService::list(&conn, 1, 0, 10).await.unwrap();
+        let page1 = EntryService::list(&conn, 1, 0, 10, None, &EntryFilters::default())
+            .await
+            .unwrap();
 
         assert_eq!(page1.entries.len(), 10);
         assert_eq!(page1.total, 25);
         assert!(page1.has_more);
 
         // Get last page
-        let page3 = EntryService::list(&conn, 1, 2, 10).await.unwrap();
+        let page3 = EntryService::list(&conn, 1, 2, 10, None, &EntryFilters::default())
+            .await
+            .unwrap();
 
         assert_eq!(page3.entries.len(), 5);
         assert!(!page3.has_more);
     }
 
+    #[tokio::test]
+    async fn test_list_entries_sorted_by_field() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            "INSERT INTO field_definitions (vault_id, name, field_type) VALUES (1, 'Year', 'number')",
+        )
+        .await
+        .unwrap();
+
+        for (title, metadata) in [
+            ("Alien", Some(r#"{"1": 1979}"#)),
+            ("Untitled", None),
+            ("Heat", Some(r#"{"1": 1995}"#)),
+            ("Brazil", Some(r#"{"1": 1985}"#)),
+        ] {
+            EntryService::create(
+                &conn,
+                CreateEntryDto {
+                    vault_id: 1,
+                    title: title.to_string(),
+                    description: None,
+                    metadata: metadata.map(str::to_string),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let sort = EntrySort {
+            field_id: Some(1),
+            descending: true,
+        };
+        let page = EntryService::list(&conn, 1, 0, 3, Some(&sort), &EntryFilters::default())
+            .await
+            .unwrap();
+        let titles: Vec<_> = page.entries.iter().map(|e| e.title.as_str()).collect();
+
+        // Entries without a value come last even when descending
+        assert_eq!(titles, vec!["Heat", "Brazil", "Alien"]);
+        assert_eq!(page.total, 4);
+        assert!(page.has_more);
+
+        let by_title = EntryService::list(
+            &conn,
+            1,
+            0,
+            10,
+            Some(&EntrySort::default()),
+            &EntryFilters::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(by_title.entries[0].title, "Alien");
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_sorted_by_choice_and_amount() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'Status', 'select', '{"choices": [{"id": "todo", "label": "To do"}, {"id": "doing", "label": "Doing"}, {"id": "done", "label": "Done"}]}'),
+                (1, 'Price', 'currency', NULL)"#,
+        )
+        .await
+        .unwrap();
+
+        for (title, metadata) in [
+            ("Alien", r#"{"1": "done", "2": "9.5 USD"}"#),
+            ("brazil", r#"{"1": "doing", "2": "12 EUR"}"#),
+            ("Heat", r#"{"2": "100 USD"}"#),
+            ("Ronin", r#"{"1": "todo", "2": "8 EUR"}"#),
+        ] {
+            EntryService::create(
+                &conn,
+                CreateEntryDto {
+                    vault_id: 1,
+                    title: title.to_string(),
+                    description: None,
+                    metadata: Some(metadata.to_string()),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let titles = |page: PaginatedEntries| -> Vec<String> {
+            page.entries.into_iter().map(|e| e.title).collect()
+        };
+        let list = |field_id, descending| {
+            let sort = EntrySort {
+                field_id,
+                descending,
+            };
+            let conn = &conn;
+            async move {
+                EntryService::list(conn, 1, 0, 10, Some(&sort), &EntryFilters::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Choices sort in option order, not by ID
+        assert_eq!(
+            titles(list(Some(1), false).await),
+            vec!["Ronin", "brazil", "Alien", "Heat"]
+        );
+        assert_eq!(
+            titles(list(Some(1), true).await),
+            vec!["Alien", "brazil", "Ronin", "Heat"]
+        );
+        // Amounts sort by currency, then numerically
+        assert_eq!(
+            titles(list(Some(2), false).await),
+            vec!["Ronin", "brazil", "Alien", "Heat"]
+        );
+        assert_eq!(
+            titles(list(None, false).await),
+            vec!["Alien", "brazil", "Heat", "Ronin"]
+        );
+    }
+
     #[tokio::test]
     async fn test_count_entries() {
         let conn = setup_test_db().await;
@@ -383,6 +880,7 @@ mod tests {
                 description: None,
                 metadata: None,
             },
+            None,
         )
         .await
         .unwrap();
@@ -390,6 +888,271 @@ mod tests {
         assert_eq!(updated.title, "Inception (2010)");
     }
 
+    #[tokio::test]
+    async fn test_metadata_writes_normalize_temporal_values() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            "INSERT INTO field_definitions (vault_id, name, field_type) VALUES
+                (1, 'Released', 'partialdate'),
+                (1, 'Watched', 'datetime'),
+                (1, 'Runtime', 'duration')",
+        )
+        .await
+        .unwrap();
+
+        let created = EntryService::create(
+            &conn,
+            CreateEntryDto {
+                vault_id: 1,
+                title: "Heat".to_string(),
+                description: None,
+                metadata: Some(
+                    r#"{"1": "1995-12", "2": "2024-05-01T20:30:00+02:00", "3": "2h 50m"}"#
+                        .to_string(),
+                ),
+            },
+        )
+        .await
+        .unwrap();
+
+        let metadata = MetadataService::parse_metadata(created.metadata.as_deref());
+        assert_eq!(metadata[&1], "1995-12");
+        assert_eq!(metadata[&2], "2024-05-01T18:30:00Z");
+        assert_eq!(metadata[&3], 10_200);
+
+        let result = EntryService::update(
+            &conn,
+            created.id,
+            UpdateEntryDto {
+                title: None,
+                description: None,
+                metadata: Some(r#"{"3": "almost three hours"}"#.to_string()),
+            },
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains("2h 28m")));
+    }
+
+    #[tokio::test]
+    async fn test_metadata_writes_sanitize_and_index_markdown() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            "INSERT INTO field_definitions (vault_id, name, field_type) VALUES (1, 'Review', 'markdown')",
+        )
+        .await
+        .unwrap();
+
+        let created = EntryService::create(
+            &conn,
+            CreateEntryDto {
+                vault_id: 1,
+                title: "Heat".to_string(),
+                description: None,
+                metadata: Some(
+                    r#"{"1": "A **tense** <script>x()</script>heist, like [[Ronin]]"}"#.to_string(),
+                ),
+            },
+        )
+        .await
+        .unwrap();
+
+        let metadata = MetadataService::parse_metadata(created.metadata.as_deref());
+        assert_eq!(metadata[&1], "A **tense** x()heist, like [[Ronin]]");
+        let stored = Entry::find_by_id(created.id).one(&conn).await.unwrap().unwrap();
+        assert_eq!(stored.search_text.as_deref(), Some("A tense x()heist, like Ronin"));
+
+        EntryService::update(
+            &conn,
+            created.id,
+            UpdateEntryDto {
+                title: None,
+                description: None,
+                metadata: Some(r#"{"1": null}"#.to_string()),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let stored = Entry::find_by_id(created.id).one(&conn).await.unwrap().unwrap();
+        assert_eq!(stored.search_text, None);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_writes_update_relation_index() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            INSERT INTO vaults (name) VALUES ('People');
+            INSERT INTO field_definitions (vault_id, name, field_type, options)
+                VALUES (1, 'Cast', 'relation', '{"targetVaultId": 2, "multiple": true}');
+            INSERT INTO entries (vault_id, title) VALUES (2, 'Actor A'), (2, 'Actor B');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        let movie = EntryService::create(
+            &conn,
+            CreateEntryDto {
+                vault_id: 1,
+                title: "Heat".to_string(),
+                description: None,
+                metadata: Some(r#"{"1": [{"entry_id": 1, "vault_id": 2}]}"#.to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let backlinks = RelationIndexService::get_backlinks(&conn, 1).await.unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].entries[0].entry_id, movie.id);
+
+        EntryService::update(
+            &conn,
+            movie.id,
+            UpdateEntryDto {
+                title: None,
+                description: None,
+                metadata: Some(r#"{"1": [{"entry_id": 2, "vault_id": 2}]}"#.to_string()),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(RelationIndexService::get_backlinks(&conn, 1)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            RelationIndexService::get_backlinks(&conn, 2)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unique_identifiers() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'ISBN', 'identifier', '{"scheme": "isbn", "unique": true}'),
+                (1, 'Shelf', 'identifier', '{"scheme": "custom", "pattern": "[A-Z]\\d+"}')"#,
+        )
+        .await
+        .unwrap();
+
+        let create = |title: &str, metadata: &str| {
+            EntryService::create(
+                &conn,
+                CreateEntryDto {
+                    vault_id: 1,
+                    title: title.to_string(),
+                    description: None,
+                    metadata: Some(metadata.to_string()),
+                },
+            )
+        };
+
+        let dune = create("Dune", r#"{"1": "0-441-17271-7", "2": "A1"}"#).await.unwrap();
+        let metadata = MetadataService::parse_metadata(dune.metadata.as_deref());
+        assert_eq!(metadata[&1], "9780441172719");
+
+        // The same book in another form is a duplicate
+        let result = create("Dune (again)", r#"{"1": "978-0-441-17271-9"}"#).await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains("'Dune'")));
+        assert!(create("Typo", r#"{"1": "0-441-17271-8"}"#).await.is_err());
+
+        // Saving an entry does not clash with its own identifier
+        EntryService::update(
+            &conn,
+            dune.id,
+            UpdateEntryDto {
+                title: None,
+                description: None,
+                metadata: Some(r#"{"1": "9780441172719", "2": "A1"}"#.to_string()),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let found = EntryIdentifierService::find_entry(&conn, 1, "ISBN 0441172717")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, dune.id);
+        assert!(EntryIdentifierService::find_entry(&conn, 1, "9780553283686")
+            .await
+            .unwrap()
+            .is_none());
+
+        // Shelf codes are shared, so they cannot be made unique
+        create("Emma", r#"{"2": "A1"}"#).await.unwrap();
+        let result = crate::field::FieldService::update(
+            &conn,
+            2,
+            crate::field::UpdateFieldDto {
+                name: None,
+                options: Some(crate::field::FieldOptions {
+                    scheme: Some(crate::field::IdentifierScheme::Custom),
+                    pattern: Some(r"[A-Z]\d+".to_string()),
+                    unique: Some(true),
+                    ..Default::default()
+                }),
+                required: None,
+                backfill: false,
+                merge_choices: Default::default(),
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains("A1")));
+    }
+
+    #[tokio::test]
+    async fn test_create_entry_fills_in_defaults() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"INSERT INTO field_definitions (vault_id, name, field_type, options, required) VALUES
+                (1, 'Status', 'text', '{"defaultValue": {"kind": "value", "value": "New"}}', 1),
+                (1, 'Number', 'number', '{"defaultValue": {"kind": "counter"}}', 1),
+                (1, 'Notes', 'text', NULL, 0)"#,
+        )
+        .await
+        .unwrap();
+
+        let create = |metadata: Option<&str>| {
+            EntryService::create(
+                &conn,
+                CreateEntryDto {
+                    vault_id: 1,
+                    title: "Entry".to_string(),
+                    description: None,
+                    metadata: metadata.map(str::to_string),
+                },
+            )
+        };
+
+        // Required fields with a default can be left empty
+        let first = create(None).await.unwrap();
+        let metadata = MetadataService::parse_metadata(first.metadata.as_deref());
+        assert_eq!(metadata[&1], "New");
+        assert_eq!(metadata[&2], 1);
+
+        let second = create(Some(r#"{"1": "Done", "3": "x"}"#)).await.unwrap();
+        let metadata = MetadataService::parse_metadata(second.metadata.as_deref());
+        assert_eq!(metadata[&1], "Done");
+        assert_eq!(metadata[&2], 2);
+    }
+
     #[tokio::test]
     async fn test_delete_entry() {
         let conn = setup_test_db().await;
@@ -406,7 +1169,7 @@ mod tests {
         .await
         .unwrap();
 
-        EntryService::delete(&conn, created.id, None).await.unwrap();
+        EntryService::delete(&conn, created.id, None, None).await.unwrap();
 
         let result = EntryService::get(&conn, created.id).await;
         assert!(result.is_err());
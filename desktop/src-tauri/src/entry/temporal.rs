@@ -0,0 +1,270 @@
+//! Parsing and normalization of partial dates, timestamps and durations.
+//!
+//! Values are normalized before they are stored so that plain comparisons
+//! order them correctly:
+//! - Partial dates become `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+//! - Timestamps become UTC RFC 3339 strings (`2024-05-01T18:30:00Z`)
+//! - Durations become a whole number of seconds
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+
+/// Accepted partial date formats, for error messages.
+pub const PARTIAL_DATE_FORMATS: &str = "YYYY, YYYY-MM or YYYY-MM-DD";
+
+/// Accepted timestamp formats, for error messages.
+pub const DATETIME_FORMATS: &str =
+    "an RFC 3339 timestamp with a timezone, e.g. 2024-05-01T20:30:00+02:00 or 2024-05-01T18:30:00Z";
+
+/// Accepted duration formats, for error messages.
+pub const DURATION_FORMATS: &str = "e.g. 2h 28m, 148m, 1:30:00 or PT2H28M";
+
+/// Normalizes a year, year-month or full date.
+pub fn normalize_partial_date(input: &str) -> Option<String> {
+    let parts: Vec<&str> = input.trim().split('-').collect();
+    let number = |part: &str, max_len: usize| -> Option<u32> {
+        if part.is_empty() || part.len() > max_len || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+
+    let year = parts
+        .first()
+        .filter(|y| y.len() == 4)
+        .and_then(|y| number(y, 4))?;
+    match parts.len() {
+        1 => Some(format!("{:04}", year)),
+        2 => {
+            let month = number(parts[1], 2).filter(|m| (1..=12).contains(m))?;
+            Some(format!("{:04}-{:02}", year, month))
+        }
+        3 => {
+            let date =
+                NaiveDate::from_ymd_opt(year as i32, number(parts[1], 2)?, number(parts[2], 2)?)?;
+            Some(date.format("%Y-%m-%d").to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Normalizes a timestamp with a timezone to UTC.
+/// Timestamps without a timezone are rejected, as their instant is unknown.
+pub fn normalize_datetime(input: &str) -> Option<String> {
+    let input = input.trim();
+    // Spell out `Z` so the offset formats below accept it too
+    let input = match input.strip_suffix(['Z', 'z']) {
+        Some(rest) => format!("{}+00:00", rest),
+        None => input.to_string(),
+    };
+    DateTime::parse_from_rfc3339(&input)
+        .or_else(|_| DateTime::parse_from_str(&input, "%Y-%m-%d %H:%M:%S%:z"))
+        .or_else(|_| DateTime::parse_from_str(&input, "%Y-%m-%dT%H:%M%:z"))
+        .ok()
+        .map(|dt| {
+            dt.with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Secs, true)
+        })
+}
+
+/// Normalizes a wall-clock time in the local timezone, such as a camera's
+/// capture time, to UTC. Times skipped by a daylight saving change are
+/// rejected; repeated ones resolve to the earlier instant.
+pub fn normalize_local_datetime(local: NaiveDateTime) -> Option<String> {
+    Local.from_local_datetime(&local).earliest().map(|dt| {
+        dt.with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    })
+}
+
+/// Parses a duration into whole seconds.
+///
+/// Accepts unit lists (`2h 28m`, `90 min`, `1.5h`), clock notation
+/// (`1:30:00`, `2:28`) and ISO 8601 durations (`PT2H28M`, `P1DT2H`).
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let seconds = if input.starts_with(['P', 'p']) {
+        parse_iso_duration(&input[1..])?
+    } else if input.contains(':') {
+        parse_clock_duration(input)?
+    } else {
+        parse_unit_duration(input)?
+    };
+
+    Some(seconds.round() as u64)
+}
+
+/// Parses the part of an ISO 8601 duration after the leading `P`.
+/// Years and months are rejected because their length varies.
+fn parse_iso_duration(body: &str) -> Option<f64> {
+    let (date_part, time_part) = match body.split_once(['T', 't']) {
+        Some((date, time)) if !time.is_empty() => (date, time),
+        Some(_) => return None,
+        None => (body, ""),
+    };
+
+    let mut total = 0.0;
+    let mut any = false;
+    for (part, units) in [
+        (date_part, &[('W', 604_800.0), ('D', 86_400.0)][..]),
+        (time_part, &[('H', 3_600.0), ('M', 60.0), ('S', 1.0)][..]),
+    ] {
+        let mut rest = part;
+        let mut allowed = units;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+            let amount: f64 = rest[..end].replace(',', ".").parse().ok()?;
+            let unit = rest[end..].chars().next()?.to_ascii_uppercase();
+            // Units must appear in order, each at most once
+            let position = allowed.iter().position(|(u, _)| *u == unit)?;
+            total += amount * allowed[position].1;
+            allowed = &allowed[position + 1..];
+            rest = &rest[end + 1..];
+            any = true;
+        }
+    }
+
+    any.then_some(total)
+}
+
+/// Parses `h:mm` or `h:mm:ss`.
+fn parse_clock_duration(input: &str) -> Option<f64> {
+    let parts: Vec<&str> = input.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for (index, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let value: f64 = part.parse().ok()?;
+        // Minutes and seconds must stay below 60
+        if index > 0 && (part.len() != 2 || value >= 60.0) {
+            return None;
+        }
+        total = total * 60.0 + value;
+    }
+
+    // `h:mm` counts hours and minutes
+    if parts.len() == 2 {
+        total *= 60.0;
+    }
+    Some(total)
+}
+
+/// Parses amounts followed by units, e.g. `2h 28m` or `1 day 3 hours`.
+fn parse_unit_duration(input: &str) -> Option<f64> {
+    let lower = input.to_lowercase();
+    let mut rest = lower.as_str();
+    let mut total = 0.0;
+    let mut any = false;
+
+    loop {
+        rest = rest.trim_start_matches([' ', ',']);
+        if rest.is_empty() {
+            break;
+        }
+
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..number_end].parse().ok()?;
+        rest = rest[number_end..].trim_start();
+
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit_end] {
+            "d" | "day" | "days" => 86_400.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3_600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            _ => return None,
+        };
+        total += amount * factor;
+        rest = &rest[unit_end..];
+        any = true;
+    }
+
+    any.then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_partial_date() {
+        assert_eq!(normalize_partial_date("1999"), Some("1999".to_string()));
+        assert_eq!(
+            normalize_partial_date("1999-5"),
+            Some("1999-05".to_string())
+        );
+        assert_eq!(
+            normalize_partial_date(" 2024-02-29 "),
+            Some("2024-02-29".to_string())
+        );
+        assert_eq!(normalize_partial_date("2023-02-29"), None);
+        assert_eq!(normalize_partial_date("1999-13"), None);
+        assert_eq!(normalize_partial_date("99"), None);
+        assert_eq!(normalize_partial_date("May 1999"), None);
+    }
+
+    #[test]
+    fn test_normalize_datetime() {
+        assert_eq!(
+            normalize_datetime("2024-05-01T20:30:00+02:00"),
+            Some("2024-05-01T18:30:00Z".to_string())
+        );
+        assert_eq!(
+            normalize_datetime("2024-05-01 20:30:00+02:00"),
+            Some("2024-05-01T18:30:00Z".to_string())
+        );
+        assert_eq!(
+            normalize_datetime("2024-05-01T18:30Z"),
+            Some("2024-05-01T18:30:00Z".to_string())
+        );
+        assert_eq!(normalize_datetime("2024-05-01T20:30:00"), None);
+        assert_eq!(normalize_datetime("2024-05-01"), None);
+    }
+
+    #[test]
+    fn test_normalize_local_datetime() {
+        let local = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap();
+        let normalized = normalize_local_datetime(local).unwrap();
+
+        assert!(normalized.ends_with('Z'));
+        assert_eq!(normalize_datetime(&normalized), Some(normalized.clone()));
+        let instant = DateTime::parse_from_rfc3339(&normalized).unwrap();
+        assert_eq!(instant.with_timezone(&Local).naive_local(), local);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2h 28m"), Some(8_880));
+        assert_eq!(parse_duration("2h28m"), Some(8_880));
+        assert_eq!(parse_duration("148 min"), Some(8_880));
+        assert_eq!(parse_duration("1.5h"), Some(5_400));
+        assert_eq!(parse_duration("1 day, 2 hours"), Some(93_600));
+        assert_eq!(parse_duration("2:28"), Some(8_880));
+        assert_eq!(parse_duration("1:30:15"), Some(5_415));
+        assert_eq!(parse_duration("PT2H28M"), Some(8_880));
+        assert_eq!(parse_duration("P1DT1S"), Some(86_401));
+        assert_eq!(parse_duration("PT0.5M"), Some(30));
+
+        assert_eq!(parse_duration("148"), None);
+        assert_eq!(parse_duration("2 fortnights"), None);
+        assert_eq!(parse_duration("P1Y"), None);
+        assert_eq!(parse_duration("PT28M2H"), None);
+        assert_eq!(parse_duration("1:75"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+}
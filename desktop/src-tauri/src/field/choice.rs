@@ -0,0 +1,301 @@
+//! Choices of select and multiselect fields.
+//!
+//! Entries store choice IDs, so renaming, recoloring or reordering choices
+//! leaves them untouched. Values typed or converted as text are matched to
+//! a choice by ID, then by label, and values are shown, exported and read
+//! by formulas by label.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::entry::normalize_color;
+
+use super::{Choice, FieldDefinitionDto, FieldType};
+
+/// Longest label a choice may have.
+const MAX_CHOICE_LABEL_LENGTH: usize = 100;
+
+/// Matches a value to a choice: by ID, then by label, then by label
+/// ignoring case, so `drama` selects the `Drama` choice.
+pub fn match_choice<'a>(value: &str, choices: &'a [Choice]) -> Option<&'a Choice> {
+    let lowercase = value.to_lowercase();
+    choices
+        .iter()
+        .find(|c| c.id == value)
+        .or_else(|| choices.iter().find(|c| c.label == value))
+        .or_else(|| choices.iter().find(|c| c.label.to_lowercase() == lowercase))
+}
+
+/// Choices of a select or multiselect field; none for other fields.
+fn choices_of(field: &FieldDefinitionDto) -> &[Choice] {
+    match field.field_type {
+        FieldType::Select | FieldType::Multiselect => field
+            .options
+            .as_ref()
+            .and_then(|o| o.choices.as_deref())
+            .unwrap_or_default(),
+        _ => &[],
+    }
+}
+
+/// Replaces the choice IDs of a select or multiselect value with their
+/// labels. Other values, and IDs that are no longer a choice, are kept.
+pub fn choice_labels(field: &FieldDefinitionDto, value: &Value) -> Value {
+    let choices = choices_of(field);
+    let label = |item: &Value| match item
+        .as_str()
+        .and_then(|id| choices.iter().find(|c| c.id == id))
+    {
+        Some(choice) => Value::String(choice.label.clone()),
+        None => item.clone(),
+    };
+    match value {
+        Value::Array(items) if field.field_type == FieldType::Multiselect => {
+            Value::Array(items.iter().map(label).collect())
+        }
+        other => label(other),
+    }
+}
+
+/// Key a select or multiselect value sorts by: the position of its
+/// choice, or of each choice of a list. `None` for other fields.
+pub fn choice_sort_key(field: &FieldDefinitionDto, value: &Value) -> Option<Value> {
+    let choices = choices_of(field);
+    if choices.is_empty() {
+        return None;
+    }
+    let position = |item: &Value| {
+        let id = item.as_str()?;
+        choices.iter().position(|c| c.id == id).map(Value::from)
+    };
+    match value {
+        Value::Array(items) => Some(Value::Array(items.iter().filter_map(position).collect())),
+        other => position(other),
+    }
+}
+
+/// Value a select or multiselect value takes once removed choices are
+/// replaced by the choice they merge into (`Some`) or dropped (`None`).
+///
+/// Returns `None` when the value holds none of the removed choices, and
+/// `Some(None)` when it is left empty.
+pub fn merge_choices(
+    value: &Value,
+    removed: &HashMap<String, Option<String>>,
+) -> Option<Option<Value>> {
+    match value {
+        Value::String(id) => removed
+            .get(id)
+            .map(|target| target.clone().map(Value::String)),
+        Value::Array(items) => {
+            if !items
+                .iter()
+                .any(|i| i.as_str().is_some_and(|id| removed.contains_key(id)))
+            {
+                return None;
+            }
+            let mut merged: Vec<Value> = Vec::new();
+            for item in items {
+                let item = match item.as_str().and_then(|id| removed.get(id)) {
+                    Some(Some(target)) => Value::String(target.clone()),
+                    Some(None) => continue,
+                    None => item.clone(),
+                };
+                if !merged.contains(&item) {
+                    merged.push(item);
+                }
+            }
+            Some((!merged.is_empty()).then_some(Value::Array(merged)))
+        }
+        _ => None,
+    }
+}
+
+/// Checks the choices of a field and gives the new ones an ID.
+///
+/// Labels are trimmed and must be distinct, ignoring case, and colors are
+/// normalized. New IDs are `c1`, `c2`, ..., numbered after the largest one
+/// in use or among the `previous` choices of the field, so the ID of a
+/// removed choice is never given to another.
+pub fn prepare_choices(choices: Vec<Choice>, previous: &[Choice]) -> Result<Vec<Choice>, String> {
+    let mut ids: HashSet<String> = HashSet::new();
+    for choice in choices.iter().filter(|c| !c.id.is_empty()) {
+        if !ids.insert(choice.id.clone()) {
+            return Err(format!("Choice ID '{}' is used more than once", choice.id));
+        }
+    }
+    let mut next = ids
+        .iter()
+        .chain(previous.iter().map(|c| &c.id))
+        .filter_map(|id| id.strip_prefix('c')?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    let mut labels: HashSet<String> = HashSet::new();
+    let mut prepared = Vec::with_capacity(choices.len());
+    for choice in choices {
+        let label = choice.label.trim().to_string();
+        if label.is_empty() {
+            return Err("Choice labels cannot be empty".to_string());
+        }
+        if label.chars().count() > MAX_CHOICE_LABEL_LENGTH {
+            return Err(format!(
+                "Choice '{}' is longer than {} characters",
+                label, MAX_CHOICE_LABEL_LENGTH
+            ));
+        }
+        if !labels.insert(label.to_lowercase()) {
+            return Err(format!("Choice '{}' is listed more than once", label));
+        }
+
+        let color = match choice.color.as_deref().map(str::trim) {
+            Some("") | None => None,
+            Some(color) => Some(
+                normalize_color(color)
+                    .ok_or_else(|| format!("Choice '{}': '{}' is not a color", label, color))?,
+            ),
+        };
+
+        let id = if choice.id.is_empty() {
+            while ids.contains(&format!("c{}", next)) {
+                next += 1;
+            }
+            let id = format!("c{}", next);
+            ids.insert(id.clone());
+            id
+        } else {
+            choice.id
+        };
+
+        prepared.push(Choice { id, label, color });
+    }
+
+    Ok(prepared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldOptions;
+    use serde_json::json;
+
+    fn choice(id: &str, label: &str) -> Choice {
+        Choice {
+            id: id.to_string(),
+            label: label.to_string(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_legacy_choices() {
+        // Choices saved as strings keep their label as their ID
+        let options: FieldOptions = serde_json::from_value(json!({
+            "choices": ["Drama", {"id": "c1", "label": "Comedy", "color": "#f00"}]
+        }))
+        .unwrap();
+        assert_eq!(
+            options.choices.unwrap(),
+            vec![
+                choice("Drama", "Drama"),
+                Choice {
+                    color: Some("#f00".to_string()),
+                    ..choice("c1", "Comedy")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_choice() {
+        let choices = vec![
+            choice("c1", "Drama"),
+            choice("c2", "drama"),
+            choice("c3", "Comedy"),
+        ];
+        assert_eq!(match_choice("c3", &choices), Some(&choices[2]));
+        assert_eq!(match_choice("drama", &choices), Some(&choices[1]));
+        assert_eq!(match_choice("COMEDY", &choices), Some(&choices[2]));
+        assert_eq!(match_choice("Horror", &choices), None);
+    }
+
+    #[test]
+    fn test_prepare_choices() {
+        let prepared = prepare_choices(
+            vec![
+                choice("Drama", "Drama"),
+                choice("", " Comedy "),
+                choice("c4", "Horror"),
+                Choice {
+                    color: Some("#FFF".to_string()),
+                    ..choice("", "Thriller")
+                },
+            ],
+            &[],
+        )
+        .unwrap();
+        let ids: Vec<&str> = prepared.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["Drama", "c5", "c4", "c6"]);
+        assert_eq!(prepared[1].label, "Comedy");
+        assert_eq!(prepared[3].color.as_deref(), Some("#ffffff"));
+
+        // IDs of removed choices are not reused
+        let prepared = prepare_choices(vec![choice("", "Horror")], &[choice("c7", "Drama")]);
+        assert_eq!(prepared.unwrap()[0].id, "c8");
+
+        assert!(prepare_choices(vec![choice("", "Drama"), choice("", "DRAMA")], &[]).is_err());
+        assert!(prepare_choices(vec![choice("c1", "Drama"), choice("c1", "Comedy")], &[]).is_err());
+        assert!(prepare_choices(vec![choice("", " ")], &[]).is_err());
+    }
+
+    #[test]
+    fn test_choice_labels_and_sort_key() {
+        let field = FieldDefinitionDto {
+            id: 1,
+            vault_id: 1,
+            name: "Genres".to_string(),
+            field_type: FieldType::Multiselect,
+            options: Some(FieldOptions {
+                choices: Some(vec![choice("c1", "Drama"), choice("c2", "Comedy")]),
+                ..Default::default()
+            }),
+            position: 0,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+
+        assert_eq!(
+            choice_labels(&field, &json!(["c2", "c1", "gone"])),
+            json!(["Comedy", "Drama", "gone"])
+        );
+        assert_eq!(
+            choice_sort_key(&field, &json!(["c2", "c1"])),
+            Some(json!([1, 0]))
+        );
+    }
+
+    #[test]
+    fn test_merge_choices() {
+        let removed = HashMap::from([
+            ("c1".to_string(), Some("c2".to_string())),
+            ("c3".to_string(), None),
+        ]);
+
+        assert_eq!(
+            merge_choices(&json!("c1"), &removed),
+            Some(Some(json!("c2")))
+        );
+        assert_eq!(merge_choices(&json!("c3"), &removed), Some(None));
+        assert_eq!(merge_choices(&json!("c2"), &removed), None);
+        // Merged choices are listed once
+        assert_eq!(
+            merge_choices(&json!(["c2", "c1", "c3"]), &removed),
+            Some(Some(json!(["c2"])))
+        );
+        assert_eq!(merge_choices(&json!(["c3"]), &removed), Some(None));
+        assert_eq!(merge_choices(&json!(["c2", "c4"]), &removed), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Value constraints shared by field types.
+//!
+//! Text-like fields can require a minimum length and a pattern, with a
+//! message of their own shown when a value does not match. Fields holding
+//! single values can be made unique within their vault; the check itself
+//! runs against the stored entries, see `EntryConstraintService`.
+
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+use super::{FieldOptions, FieldType};
+
+/// Longest pattern a field may be configured with.
+const MAX_PATTERN_LENGTH: usize = 500;
+
+/// Longest custom message shown for values not matching a pattern.
+const MAX_PATTERN_MESSAGE_LENGTH: usize = 200;
+
+/// Whether values of a field type can be required to be unique: single
+/// values that compare by equality, unlike lists, free-form documents and
+/// yes/no answers.
+pub fn can_be_unique(field_type: FieldType) -> bool {
+    !field_type.is_derived()
+        && !matches!(
+            field_type,
+            FieldType::Markdown
+                | FieldType::Boolean
+                | FieldType::Multiselect
+                | FieldType::Json
+                | FieldType::File
+                | FieldType::Location
+                | FieldType::Relation
+        )
+}
+
+/// Whether values of a field type can be checked against a pattern.
+/// Identifier fields use their pattern as the format of custom schemes.
+fn takes_pattern(field_type: FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Text | FieldType::Url | FieldType::Email | FieldType::Phone
+    )
+}
+
+/// Checks the unique, pattern and minimum length options of a field.
+pub fn check_constraint_options(
+    field_type: FieldType,
+    options: Option<&FieldOptions>,
+) -> Result<(), String> {
+    let Some(options) = options else {
+        return Ok(());
+    };
+
+    if options.is_unique() && !can_be_unique(field_type) {
+        return Err(format!("{} fields cannot be unique", field_type.as_str()));
+    }
+
+    if field_type != FieldType::Identifier {
+        match &options.pattern {
+            Some(_) if !takes_pattern(field_type) => {
+                return Err(format!(
+                    "{} fields do not take a pattern",
+                    field_type.as_str()
+                ));
+            }
+            Some(pattern) => {
+                compile_pattern(pattern)?;
+            }
+            None if options.pattern_message.is_some() => {
+                return Err("A pattern message needs a pattern".to_string());
+            }
+            None => {}
+        }
+    }
+    if let Some(message) = &options.pattern_message {
+        if message.trim().is_empty() || message.len() > MAX_PATTERN_MESSAGE_LENGTH {
+            return Err(format!(
+                "Pattern message must be 1 to {} characters",
+                MAX_PATTERN_MESSAGE_LENGTH
+            ));
+        }
+    }
+
+    if let Some(min_length) = options.min_length {
+        if !matches!(field_type, FieldType::Text | FieldType::Markdown) {
+            return Err(format!(
+                "{} fields do not take a minimum length",
+                field_type.as_str()
+            ));
+        }
+        if min_length < 0 {
+            return Err("Minimum length cannot be negative".to_string());
+        }
+        if options.max_length.is_some_and(|max| min_length > max) {
+            return Err("Minimum length cannot exceed the maximum length".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a stored value against the minimum length and pattern of its
+/// field. Lengths count characters.
+pub fn check_value_constraints(
+    field_type: FieldType,
+    options: Option<&FieldOptions>,
+    value: &Value,
+) -> Result<(), String> {
+    let (Some(options), Some(text)) = (options, value.as_str()) else {
+        return Ok(());
+    };
+
+    if let Some(min_length) = options.min_length {
+        if text.chars().count() < min_length.max(0) as usize {
+            return Err(format!("text is shorter than {} characters", min_length));
+        }
+    }
+
+    if let Some(pattern) = options
+        .pattern
+        .as_deref()
+        .filter(|_| takes_pattern(field_type))
+    {
+        if !compile_pattern(pattern)?.is_match(text) {
+            return Err(pattern_error(options, text, pattern));
+        }
+    }
+
+    Ok(())
+}
+
+/// Error for a value not matching a pattern: the field's own message, or
+/// one naming the pattern.
+pub(super) fn pattern_error(options: &FieldOptions, value: &str, pattern: &str) -> String {
+    match &options.pattern_message {
+        Some(message) => message.clone(),
+        None => format!("'{}' does not match the pattern {}", value, pattern),
+    }
+}
+
+/// Compiles a pattern anchored at both ends, so it must match the whole
+/// value.
+pub(super) fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    if pattern.is_empty() || pattern.len() > MAX_PATTERN_LENGTH {
+        return Err(format!(
+            "Pattern must be 1 to {} characters",
+            MAX_PATTERN_LENGTH
+        ));
+    }
+    RegexBuilder::new(&format!("^(?:{})$", pattern))
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_constraint_options() {
+        let options = |pattern: Option<&str>, min_length: Option<i32>| FieldOptions {
+            pattern: pattern.map(str::to_string),
+            min_length,
+            max_length: Some(10),
+            ..Default::default()
+        };
+        let unique = FieldOptions {
+            unique: Some(true),
+            ..Default::default()
+        };
+
+        assert!(
+            check_constraint_options(FieldType::Text, Some(&options(Some(r"\d+"), Some(2))))
+                .is_ok()
+        );
+        assert!(
+            check_constraint_options(FieldType::Text, Some(&options(Some("("), None))).is_err()
+        );
+        assert!(
+            check_constraint_options(FieldType::Number, Some(&options(Some(r"\d+"), None)))
+                .is_err()
+        );
+        assert!(check_constraint_options(FieldType::Text, Some(&options(None, Some(11)))).is_err());
+        assert!(check_constraint_options(FieldType::Number, Some(&unique)).is_ok());
+        assert!(check_constraint_options(FieldType::Multiselect, Some(&unique)).is_err());
+
+        let message = FieldOptions {
+            pattern_message: Some("Use a code".to_string()),
+            ..Default::default()
+        };
+        assert!(check_constraint_options(FieldType::Text, Some(&message)).is_err());
+    }
+
+    #[test]
+    fn test_value_constraints() {
+        let options = FieldOptions {
+            pattern: Some(r"[A-Z]{2}\d+".to_string()),
+            pattern_message: Some("Use two capitals and a number, like AB12".to_string()),
+            min_length: Some(4),
+            ..Default::default()
+        };
+        let check = |value: Value| check_value_constraints(FieldType::Text, Some(&options), &value);
+
+        assert!(check(json!("AB12")).is_ok());
+        assert_eq!(
+            check(json!("AB1")).unwrap_err(),
+            "text is shorter than 4 characters"
+        );
+        // The pattern must match the whole value
+        assert_eq!(
+            check(json!("xAB12")).unwrap_err(),
+            "Use two capitals and a number, like AB12"
+        );
+        assert!(check(json!(12345)).is_ok());
+    }
+}
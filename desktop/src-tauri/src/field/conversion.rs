@@ -0,0 +1,367 @@
+//! Conversion of stored values from one field type to another.
+//!
+//! Values are first turned into the raw form entries of the new type are
+//! written in: text for text-like types, numbers, booleans or choice lists.
+//! The metadata normalizer and validator of the new type then have the
+//! final say, exactly as when an entry is saved.
+
+use serde_json::Value;
+
+use super::FieldType;
+
+/// Checks that a field of type `from` can be converted to type `to`.
+///
+/// Computed fields have no stored values, and file and relation values
+/// are tied to attachments and the relation index, so none of them
+/// convert either way.
+pub fn check_conversion(from: FieldType, to: FieldType) -> Result<(), String> {
+    let fixed = |t: FieldType| t.is_derived() || matches!(t, FieldType::File | FieldType::Relation);
+
+    if from == to {
+        return Err(format!("Field is already of type {}", to.as_str()));
+    }
+    if fixed(from) || fixed(to) {
+        return Err(format!(
+            "Fields cannot be converted from {} to {}",
+            from.as_str(),
+            to.as_str()
+        ));
+    }
+    Ok(())
+}
+
+/// Converts a stored value to the raw value of the target type.
+///
+/// Returns `Ok(None)` for values that become empty, such as blank text or
+/// an empty choice list, and an error for values with no sensible
+/// counterpart, such as text that is not a number.
+pub fn convert_value(
+    value: &Value,
+    from: FieldType,
+    to: FieldType,
+) -> Result<Option<Value>, String> {
+    let is_blank = value.as_str().is_some_and(|text| text.trim().is_empty());
+    if value.is_null() || is_blank {
+        return Ok(None);
+    }
+
+    let converted = match to {
+        FieldType::Number | FieldType::Rating => number_value(to_number(value, from)?),
+        FieldType::Duration => match value {
+            Value::Number(_) => value.clone(),
+            _ => Value::String(to_text(value, from)),
+        },
+        FieldType::Boolean => Value::Bool(to_boolean(value)?),
+        FieldType::Date => Value::String(to_date(value, from)?),
+        FieldType::PartialDate => match from {
+            FieldType::Datetime => Value::String(utc_date(value)?),
+            _ => Value::String(to_text(value, from)),
+        },
+        FieldType::Datetime => match from {
+            FieldType::Date | FieldType::PartialDate => {
+                let date = to_date(value, from)?;
+                Value::String(format!("{}T00:00:00Z", date))
+            }
+            _ => Value::String(to_text(value, from)),
+        },
+        FieldType::Select => match value {
+            Value::Array(items) => match items.as_slice() {
+                [] => return Ok(None),
+                [item] => Value::String(to_text(item, FieldType::Text)),
+                _ => {
+                    return Err(format!(
+                        "holds {} choices, a dropdown holds one",
+                        items.len()
+                    ));
+                }
+            },
+            _ => Value::String(to_text(value, from)),
+        },
+        FieldType::Multiselect => {
+            let items: Vec<String> = match value {
+                Value::Array(items) => items.iter().map(|i| to_text(i, FieldType::Text)).collect(),
+                // Free text lists its choices between commas
+                Value::String(text) if from != FieldType::Select => {
+                    text.split([',', ';']).map(str::to_string).collect()
+                }
+                _ => vec![to_text(value, from)],
+            };
+            let mut choices: Vec<String> = Vec::new();
+            for item in items.iter().map(|i| i.trim()).filter(|i| !i.is_empty()) {
+                if !choices.iter().any(|c| c == item) {
+                    choices.push(item.to_string());
+                }
+            }
+            if choices.is_empty() {
+                return Ok(None);
+            }
+            Value::from(choices)
+        }
+        FieldType::Currency => match value {
+            Value::Number(_) => value.clone(),
+            _ => Value::String(to_text(value, from)),
+        },
+        FieldType::Location => match value {
+            Value::Object(_) => value.clone(),
+            _ => to_location(&to_text(value, from))?,
+        },
+        // Text holding a JSON document becomes that document
+        FieldType::Json => match value {
+            Value::String(text) => serde_json::from_str(text).unwrap_or_else(|_| value.clone()),
+            _ => value.clone(),
+        },
+        _ => Value::String(to_text(value, from)),
+    };
+
+    Ok(match &converted {
+        Value::String(text) if text.trim().is_empty() => None,
+        _ => Some(converted),
+    })
+}
+
+/// Text form of a value, as shown to users.
+fn to_text(value: &Value, from: FieldType) -> String {
+    match value {
+        Value::String(text) => text.trim().to_string(),
+        Value::Bool(b) => if *b { "Yes" } else { "No" }.to_string(),
+        Value::Number(n) if from == FieldType::Duration => {
+            n.as_u64().map_or_else(|| n.to_string(), format_duration)
+        }
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| to_text(item, FieldType::Text))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Object(object) => match from {
+            FieldType::Currency => format!(
+                "{} {}",
+                object
+                    .get("amount")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+                object
+                    .get("currency")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+            ),
+            FieldType::Location => match object.get("name").and_then(Value::as_str) {
+                Some(name) => name.to_string(),
+                None => format!(
+                    "{}, {}",
+                    object.get("lat").unwrap_or(&Value::Null),
+                    object.get("lng").unwrap_or(&Value::Null)
+                ),
+            },
+            _ => value.to_string(),
+        },
+        Value::Null => String::new(),
+    }
+}
+
+fn to_number(value: &Value, from: FieldType) -> Result<f64, String> {
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        Value::Object(object) if from == FieldType::Currency => object
+            .get("amount")
+            .and_then(Value::as_str)
+            .and_then(|amount| amount.parse().ok()),
+        _ => None,
+    };
+    number
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| "is not a number".to_string())
+}
+
+/// Writes whole numbers without a fraction, as they are typed.
+fn number_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < 9_007_199_254_740_992.0 {
+        Value::from(number as i64)
+    } else {
+        Value::from(number)
+    }
+}
+
+fn to_boolean(value: &Value) -> Result<bool, String> {
+    let boolean = match value {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) => match n.as_f64() {
+            Some(0.0) => Some(false),
+            Some(1.0) => Some(true),
+            _ => None,
+        },
+        Value::String(text) => match text.trim().to_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" | "x" => Some(true),
+            "false" | "no" | "n" | "off" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    };
+    boolean.ok_or_else(|| "is not yes or no".to_string())
+}
+
+/// Full `YYYY-MM-DD` date of a value. Partial dates without a day have
+/// none, timestamps take their UTC date.
+fn to_date(value: &Value, from: FieldType) -> Result<String, String> {
+    if from == FieldType::Datetime {
+        return utc_date(value);
+    }
+    let text = value.as_str().map(str::trim).unwrap_or_default();
+    let is_full_date =
+        text.len() == 10 && chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok();
+    if is_full_date {
+        Ok(text.to_string())
+    } else {
+        Err("is not a full date".to_string())
+    }
+}
+
+/// Date part of a stored UTC timestamp.
+fn utc_date(value: &Value) -> Result<String, String> {
+    value
+        .as_str()
+        .and_then(|timestamp| timestamp.get(..10))
+        .map(str::to_string)
+        .ok_or_else(|| "is not a timestamp".to_string())
+}
+
+/// Reads a location written as `lat, lng`.
+fn to_location(text: &str) -> Result<Value, String> {
+    let (lat, lng) = text
+        .split_once(',')
+        .and_then(|(lat, lng)| {
+            Some((
+                lat.trim().parse::<f64>().ok()?,
+                lng.trim().parse::<f64>().ok()?,
+            ))
+        })
+        .ok_or_else(|| "is not a 'latitude, longitude' pair".to_string())?;
+    Ok(serde_json::json!({"lat": lat, "lng": lng}))
+}
+
+/// Writes seconds as units, e.g. `2h 28m`, in a form durations parse back.
+fn format_duration(seconds: u64) -> String {
+    let units = [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)];
+    let mut rest = seconds;
+    let parts: Vec<String> = units
+        .iter()
+        .filter_map(|(unit, size)| {
+            let count = rest / size;
+            rest %= size;
+            (count > 0).then(|| format!("{}{}", count, unit))
+        })
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_conversion() {
+        assert!(check_conversion(FieldType::Text, FieldType::Number).is_ok());
+        assert!(check_conversion(FieldType::Select, FieldType::Multiselect).is_ok());
+        assert!(check_conversion(FieldType::Text, FieldType::Text).is_err());
+        assert!(check_conversion(FieldType::Relation, FieldType::Text).is_err());
+        assert!(check_conversion(FieldType::Text, FieldType::File).is_err());
+        assert!(check_conversion(FieldType::Number, FieldType::Formula).is_err());
+    }
+
+    #[test]
+    fn test_convert_value() {
+        let convert = |value, from, to| convert_value(&value, from, to);
+
+        assert_eq!(
+            convert(json!(" 42.5 "), FieldType::Text, FieldType::Number),
+            Ok(Some(json!(42.5)))
+        );
+        assert!(convert(json!("forty"), FieldType::Text, FieldType::Number).is_err());
+        assert_eq!(
+            convert(json!("  "), FieldType::Text, FieldType::Number),
+            Ok(None)
+        );
+        assert_eq!(
+            convert(json!(3), FieldType::Number, FieldType::Text),
+            Ok(Some(json!("3")))
+        );
+        assert_eq!(
+            convert(json!(8880), FieldType::Duration, FieldType::Text),
+            Ok(Some(json!("2h 28m")))
+        );
+
+        assert_eq!(
+            convert(json!("Yes"), FieldType::Text, FieldType::Boolean),
+            Ok(Some(json!(true)))
+        );
+        assert!(convert(json!(2), FieldType::Number, FieldType::Boolean).is_err());
+        assert_eq!(
+            convert(json!(false), FieldType::Boolean, FieldType::Text),
+            Ok(Some(json!("No")))
+        );
+
+        assert_eq!(
+            convert(json!("Drama"), FieldType::Select, FieldType::Multiselect),
+            Ok(Some(json!(["Drama"])))
+        );
+        assert_eq!(
+            convert(
+                json!("sf, drama; sf,"),
+                FieldType::Text,
+                FieldType::Multiselect
+            ),
+            Ok(Some(json!(["sf", "drama"])))
+        );
+        assert_eq!(
+            convert(json!(["Drama"]), FieldType::Multiselect, FieldType::Select),
+            Ok(Some(json!("Drama")))
+        );
+        assert_eq!(
+            convert(json!([]), FieldType::Multiselect, FieldType::Select),
+            Ok(None)
+        );
+        assert!(convert(json!(["a", "b"]), FieldType::Multiselect, FieldType::Select).is_err());
+
+        assert_eq!(
+            convert(
+                json!("2024-05-01T22:30:00Z"),
+                FieldType::Datetime,
+                FieldType::Date
+            ),
+            Ok(Some(json!("2024-05-01")))
+        );
+        assert!(convert(json!("2024-05"), FieldType::PartialDate, FieldType::Date).is_err());
+        assert_eq!(
+            convert(json!("2024-05-01"), FieldType::Date, FieldType::Datetime),
+            Ok(Some(json!("2024-05-01T00:00:00Z")))
+        );
+
+        assert_eq!(
+            convert(
+                json!({"amount": "12.50", "currency": "EUR"}),
+                FieldType::Currency,
+                FieldType::Number
+            ),
+            Ok(Some(json!(12.5)))
+        );
+        assert_eq!(
+            convert(json!("48.85, 2.35"), FieldType::Text, FieldType::Location),
+            Ok(Some(json!({"lat": 48.85, "lng": 2.35})))
+        );
+        assert_eq!(
+            convert(json!("{\"a\": [1]}"), FieldType::Text, FieldType::Json),
+            Ok(Some(json!({"a": [1]})))
+        );
+        assert_eq!(
+            convert(json!("plain"), FieldType::Text, FieldType::Json),
+            Ok(Some(json!("plain")))
+        );
+    }
+}
@@ -0,0 +1,1334 @@
+//! Expressions of formula fields.
+//!
+//! A formula computes a value from other fields of the same entry, which it
+//! references by ID in braces:
+//!
+//! ```text
+//! years_between({3}, today())
+//! if({5} > 0, {4} / {5}, null)
+//! {1} & " (" & year({3}) & ")"
+//! ```
+//!
+//! Values are numbers, text, booleans, dates or `null`. Formulas support
+//! arithmetic (`+ - * / %`, with dates plus or minus days), text
+//! concatenation (`&`), comparisons (`= != < <= > >=`), `and`, `or`, `not`
+//! and the functions listed in [`Function`]. Operators return `null` when
+//! an operand is `null`, except `&`, which reads it as empty text, and the
+//! boolean operators, which read it as false.
+//!
+//! Formulas are type-checked against the fields they read before they are
+//! saved, and evaluated whenever entries are read.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+use serde_json::{Map, Number, Value};
+
+use super::{FieldDefinitionDto, FieldType, RollupFunction, choice_labels};
+
+/// Longest formula source accepted.
+const MAX_FORMULA_LENGTH: usize = 2000;
+
+/// Deepest nesting of parentheses, calls and prefix operators, so parsing
+/// and evaluation cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Type of a formula, or of a value used in one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormulaType {
+    Number,
+    Text,
+    Boolean,
+    Date,
+    /// Type of the `null` literal, accepted wherever another type is
+    Null,
+}
+
+impl FormulaType {
+    fn name(self) -> &'static str {
+        match self {
+            FormulaType::Number => "a number",
+            FormulaType::Text => "text",
+            FormulaType::Boolean => "a boolean",
+            FormulaType::Date => "a date",
+            FormulaType::Null => "null",
+        }
+    }
+
+    /// Type the values of a stored or relation-derived field have in
+    /// formulas. Formula fields are checked through their own formula.
+    fn of_field(field: &FieldDefinitionDto) -> Result<Self, String> {
+        let rollup = field.options.as_ref().and_then(|o| o.rollup);
+        match field.field_type {
+            FieldType::Text
+            | FieldType::Markdown
+            | FieldType::Url
+            | FieldType::Identifier
+            | FieldType::Email
+            | FieldType::Phone
+            | FieldType::Color
+            | FieldType::Select
+            | FieldType::PartialDate => Ok(FormulaType::Text),
+            FieldType::Number | FieldType::Rating | FieldType::Duration => Ok(FormulaType::Number),
+            FieldType::Date | FieldType::Datetime => Ok(FormulaType::Date),
+            FieldType::Boolean => Ok(FormulaType::Boolean),
+            FieldType::Rollup
+                if matches!(
+                    rollup,
+                    Some(RollupFunction::Count | RollupFunction::Sum | RollupFunction::Average)
+                ) =>
+            {
+                Ok(FormulaType::Number)
+            }
+            _ => Err(format!(
+                "Field '{}' ({}) cannot be used in formulas",
+                field.name,
+                field.field_type.as_str()
+            )),
+        }
+    }
+
+    /// Common type of two values that may take each other's place.
+    fn unify(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (a, b) if a == b => Some(a),
+            (FormulaType::Null, t) | (t, FormulaType::Null) => Some(t),
+            _ => None,
+        }
+    }
+
+    fn is_any_of(self, types: &[FormulaType]) -> bool {
+        self == FormulaType::Null || types.contains(&self)
+    }
+}
+
+/// Value computed while evaluating a formula.
+#[derive(Debug, Clone, PartialEq)]
+enum Scalar {
+    Null,
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Date(NaiveDate),
+}
+
+impl Scalar {
+    /// Numbers that are not finite, e.g. from overflow, become null.
+    fn number(n: f64) -> Self {
+        if n.is_finite() {
+            Scalar::Number(n)
+        } else {
+            Scalar::Null
+        }
+    }
+
+    /// Reads a metadata value as the given type. Values of another shape
+    /// read as null.
+    fn from_json(value: &Value, ty: FormulaType) -> Self {
+        match ty {
+            FormulaType::Number => value.as_f64().map_or(Scalar::Null, Scalar::number),
+            FormulaType::Text => value
+                .as_str()
+                .map_or(Scalar::Null, |s| Scalar::Text(s.to_string())),
+            FormulaType::Boolean => value.as_bool().map_or(Scalar::Null, Scalar::Boolean),
+            FormulaType::Date => value
+                .as_str()
+                .and_then(|s| {
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().or_else(|| {
+                        DateTime::parse_from_rfc3339(s)
+                            .ok()
+                            .map(|d| d.with_timezone(&Utc).date_naive())
+                    })
+                })
+                .map_or(Scalar::Null, Scalar::Date),
+            FormulaType::Null => Scalar::Null,
+        }
+    }
+
+    fn into_json(self) -> Value {
+        match self {
+            Scalar::Null => Value::Null,
+            Scalar::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                Value::from(n as i64)
+            }
+            Scalar::Number(n) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+            Scalar::Text(s) => Value::String(s),
+            Scalar::Boolean(b) => Value::Bool(b),
+            Scalar::Date(d) => Value::String(d.format("%Y-%m-%d").to_string()),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            Scalar::Null => String::new(),
+            Scalar::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => (*n as i64).to_string(),
+            Scalar::Number(n) => n.to_string(),
+            Scalar::Text(s) => s.clone(),
+            Scalar::Boolean(b) => b.to_string(),
+            Scalar::Date(d) => d.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        matches!(self, Scalar::Boolean(true))
+    }
+
+    /// Orders two values of the same type; other pairs do not compare.
+    fn compare(&self, other: &Scalar) -> Option<Ordering> {
+        match (self, other) {
+            (Scalar::Number(a), Scalar::Number(b)) => a.partial_cmp(b),
+            (Scalar::Text(a), Scalar::Text(b)) => Some(a.cmp(b)),
+            (Scalar::Boolean(a), Scalar::Boolean(b)) => Some(a.cmp(b)),
+            (Scalar::Date(a), Scalar::Date(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Concat => "&",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+        }
+    }
+
+    fn comparison(symbol: &str) -> Option<Self> {
+        match symbol {
+            "=" => Some(BinaryOp::Eq),
+            "!=" => Some(BinaryOp::Ne),
+            "<" => Some(BinaryOp::Lt),
+            "<=" => Some(BinaryOp::Le),
+            ">" => Some(BinaryOp::Gt),
+            ">=" => Some(BinaryOp::Ge),
+            _ => None,
+        }
+    }
+}
+
+/// Functions available in formulas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    /// `if(condition, then, else)`
+    If,
+    /// `coalesce(a, b, ...)`: the first value that is not null
+    Coalesce,
+    /// `is_null(value)`
+    IsNull,
+    /// `today()`: the current local date
+    Today,
+    /// `days_between(from, to)`: whole days from one date to another
+    DaysBetween,
+    /// `years_between(from, to)`: whole years, e.g. an age
+    YearsBetween,
+    Year,
+    Month,
+    Day,
+    /// `round(number)` or `round(number, digits)`
+    Round,
+    Floor,
+    Ceil,
+    Abs,
+    /// `min(a, b, ...)`, ignoring nulls
+    Min,
+    /// `max(a, b, ...)`, ignoring nulls
+    Max,
+    /// `len(text)`: number of characters
+    Len,
+    Upper,
+    Lower,
+    Trim,
+    /// `text(value)`: any value as text
+    Text,
+}
+
+impl Function {
+    const ALL: [Function; 20] = [
+        Function::If,
+        Function::Coalesce,
+        Function::IsNull,
+        Function::Today,
+        Function::DaysBetween,
+        Function::YearsBetween,
+        Function::Year,
+        Function::Month,
+        Function::Day,
+        Function::Round,
+        Function::Floor,
+        Function::Ceil,
+        Function::Abs,
+        Function::Min,
+        Function::Max,
+        Function::Len,
+        Function::Upper,
+        Function::Lower,
+        Function::Trim,
+        Function::Text,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::If => "if",
+            Function::Coalesce => "coalesce",
+            Function::IsNull => "is_null",
+            Function::Today => "today",
+            Function::DaysBetween => "days_between",
+            Function::YearsBetween => "years_between",
+            Function::Year => "year",
+            Function::Month => "month",
+            Function::Day => "day",
+            Function::Round => "round",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Abs => "abs",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Len => "len",
+            Function::Upper => "upper",
+            Function::Lower => "lower",
+            Function::Trim => "trim",
+            Function::Text => "text",
+        }
+    }
+
+    /// Smallest and largest number of arguments (None: no limit).
+    fn arity(self) -> (usize, Option<usize>) {
+        match self {
+            Function::Today => (0, Some(0)),
+            Function::If => (3, Some(3)),
+            Function::DaysBetween | Function::YearsBetween => (2, Some(2)),
+            Function::Round => (1, Some(2)),
+            Function::Coalesce | Function::Min | Function::Max => (1, None),
+            _ => (1, Some(1)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Scalar),
+    Field(i32),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Field(i32),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// Splits a formula into tokens, each with its 1-based position.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let token = if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}' at position {}", text, position))?;
+            Token::Number(number)
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("Unterminated text at position {}", position)),
+                    Some('"') => break,
+                    Some('\\') => {
+                        match chars.get(i + 1) {
+                            Some('"') => text.push('"'),
+                            Some('\\') => text.push('\\'),
+                            Some('n') => text.push('\n'),
+                            _ => return Err(format!("Invalid escape at position {}", i + 1)),
+                        }
+                        i += 2;
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            Token::Text(text)
+        } else if c == '{' {
+            let start = i + 1;
+            let end = chars[start..]
+                .iter()
+                .position(|&c| c == '}')
+                .map(|offset| start + offset)
+                .ok_or_else(|| format!("Unclosed field reference at position {}", position))?;
+            let id: String = chars[start..end].iter().collect();
+            let id = id.trim().parse::<i32>().map_err(|_| {
+                format!(
+                    "Field references must be IDs, like {{12}}, at position {}",
+                    position
+                )
+            })?;
+            i = end + 1;
+            Token::Field(id)
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Ident(chars[start..i].iter().collect::<String>().to_lowercase())
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (op, width) = match (c, next) {
+                ('!', Some('=')) | ('<', Some('>')) => ("!=", 2),
+                ('<', Some('=')) => ("<=", 2),
+                ('>', Some('=')) => (">=", 2),
+                ('=', Some('=')) => ("=", 2),
+                ('(', _) | (')', _) | (',', _) => ("", 1),
+                ('+', _) => ("+", 1),
+                ('-', _) => ("-", 1),
+                ('*', _) => ("*", 1),
+                ('/', _) => ("/", 1),
+                ('%', _) => ("%", 1),
+                ('&', _) => ("&", 1),
+                ('=', _) => ("=", 1),
+                ('<', _) => ("<", 1),
+                ('>', _) => (">", 1),
+                _ => {
+                    return Err(format!(
+                        "Unexpected character '{}' at position {}",
+                        c, position
+                    ));
+                }
+            };
+            i += width;
+            match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                _ => Token::Op(op),
+            }
+        };
+
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser. From loosest to tightest binding: `or`, `and`,
+/// `not`, comparisons, `&`, `+ -`, `* / %`, unary minus.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    depth: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _)| *p)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token, description: &str) -> Result<(), String> {
+        if self.peek() == Some(&expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {} at position {}",
+                description,
+                self.position()
+            ))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Formula is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("not") {
+            self.enter()?;
+            let operand = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(operand)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        const COMPARISONS: [&str; 6] = ["=", "!=", "<", "<=", ">", ">="];
+        let left = self.parse_concat()?;
+        let Some(op) = self.eat_op(&COMPARISONS) else {
+            return Ok(left);
+        };
+        let right = self.parse_concat()?;
+        if self.eat_op(&COMPARISONS).is_some() {
+            return Err("Comparisons cannot be chained; combine them with 'and'".to_string());
+        }
+        let op = BinaryOp::comparison(op).unwrap_or(BinaryOp::Eq);
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_concat(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_additive()?;
+        while self.eat_op(&["&"]).is_some() {
+            let right = self.parse_additive()?;
+            left = Expr::Binary(BinaryOp::Concat, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => BinaryOp::Mul,
+                "/" => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            let right = self.parse_unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op(&["-"]).is_some() {
+            self.enter()?;
+            let operand = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Neg(Box::new(operand)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(Scalar::Number(n))),
+            Some(Token::Text(s)) => Ok(Expr::Literal(Scalar::Text(s))),
+            Some(Token::Field(id)) => Ok(Expr::Field(id)),
+            Some(Token::LParen) => {
+                self.enter()?;
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen, "')'")?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Scalar::Boolean(true))),
+                "false" => Ok(Expr::Literal(Scalar::Boolean(false))),
+                "null" => Ok(Expr::Literal(Scalar::Null)),
+                _ => self.parse_call(&name, position),
+            },
+            Some(_) => Err(format!("Unexpected symbol at position {}", position)),
+            None => Err("Formula ends unexpectedly".to_string()),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str, position: usize) -> Result<Expr, String> {
+        let function = Function::ALL
+            .into_iter()
+            .find(|f| f.name() == name)
+            .ok_or_else(|| format!("Unknown function '{}' at position {}", name, position))?;
+        self.expect(Token::LParen, &format!("'(' after '{}'", name))?;
+        self.enter()?;
+
+        let mut args = Vec::new();
+        if self.peek() != Some(&Token::RParen) {
+            loop {
+                args.push(self.parse_or()?);
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+        self.expect(Token::RParen, "')'")?;
+        self.depth -= 1;
+
+        let (min, max) = function.arity();
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            let expected = match max {
+                Some(max) if max == min => format!("{}", min),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            return Err(format!(
+                "{}() takes {} argument(s), not {}",
+                name,
+                expected,
+                args.len()
+            ));
+        }
+
+        Ok(Expr::Call(function, args))
+    }
+}
+
+/// A parsed formula.
+#[derive(Debug, Clone)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    /// Parses formula source text.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.trim().is_empty() {
+            return Err("Formula is empty".to_string());
+        }
+        if source.len() > MAX_FORMULA_LENGTH {
+            return Err(format!(
+                "Formula is longer than {} characters",
+                MAX_FORMULA_LENGTH
+            ));
+        }
+
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            end: source.chars().count() + 1,
+        };
+        let expr = parser.parse_or()?;
+        if parser.peek().is_some() {
+            return Err(format!(
+                "Unexpected symbol at position {}",
+                parser.position()
+            ));
+        }
+
+        Ok(Self { expr })
+    }
+}
+
+/// Parses a formula and infers its result type.
+///
+/// `fields` are the fields of the formula's vault; formulas it reads are
+/// checked in turn. `field_id` is the formula's own field once it exists,
+/// so formulas reading back from it are rejected as a cycle.
+pub fn check_formula(
+    source: &str,
+    field_id: Option<i32>,
+    fields: &HashMap<i32, FieldDefinitionDto>,
+) -> Result<FormulaType, String> {
+    let mut stack: Vec<i32> = field_id.into_iter().collect();
+    let mut memo: HashMap<i32, FormulaType> = HashMap::new();
+    infer(source, fields, &mut stack, &mut memo)
+}
+
+fn infer(
+    source: &str,
+    fields: &HashMap<i32, FieldDefinitionDto>,
+    stack: &mut Vec<i32>,
+    memo: &mut HashMap<i32, FormulaType>,
+) -> Result<FormulaType, String> {
+    let formula = Formula::parse(source)?;
+    check_expr(&formula.expr, &mut |id| {
+        let field = fields
+            .get(&id)
+            .ok_or_else(|| format!("Field {} does not exist in this vault", id))?;
+        if field.field_type != FieldType::Formula {
+            return FormulaType::of_field(field);
+        }
+        if let Some(ty) = memo.get(&id) {
+            return Ok(*ty);
+        }
+        if stack.contains(&id) {
+            return Err(format!(
+                "Formulas cannot read each other in a cycle (through '{}')",
+                field.name
+            ));
+        }
+
+        let source = field
+            .options
+            .as_ref()
+            .and_then(|o| o.formula.as_deref())
+            .unwrap_or_default();
+        stack.push(id);
+        let result = infer(source, fields, stack, memo);
+        stack.pop();
+        let ty = result.map_err(|e| format!("Field '{}': {}", field.name, e))?;
+        memo.insert(id, ty);
+        Ok(ty)
+    })
+}
+
+fn check_expr(
+    expr: &Expr,
+    field_type: &mut dyn FnMut(i32) -> Result<FormulaType, String>,
+) -> Result<FormulaType, String> {
+    use FormulaType::{Boolean, Date, Null, Number, Text};
+
+    match expr {
+        Expr::Literal(value) => Ok(match value {
+            Scalar::Null => Null,
+            Scalar::Number(_) => Number,
+            Scalar::Text(_) => Text,
+            Scalar::Boolean(_) => Boolean,
+            Scalar::Date(_) => Date,
+        }),
+        Expr::Field(id) => field_type(*id),
+        Expr::Neg(operand) => match check_expr(operand, field_type)? {
+            Number | Null => Ok(Number),
+            other => Err(format!("Cannot negate {}", other.name())),
+        },
+        Expr::Not(operand) => match check_expr(operand, field_type)? {
+            Boolean | Null => Ok(Boolean),
+            other => Err(format!("'not' expects a boolean, not {}", other.name())),
+        },
+        Expr::Binary(op, left, right) => {
+            let (l, r) = (
+                check_expr(left, field_type)?,
+                check_expr(right, field_type)?,
+            );
+            let result = match op {
+                BinaryOp::Concat => Some(Text),
+                BinaryOp::Add => match (l, r) {
+                    (Number | Null, Number | Null) => Some(Number),
+                    (Date, Number | Null) | (Number | Null, Date) => Some(Date),
+                    _ => None,
+                },
+                BinaryOp::Sub => match (l, r) {
+                    (Number | Null, Number | Null) => Some(Number),
+                    (Date, Number) => Some(Date),
+                    (Date | Null, Date) => Some(Number),
+                    (Date, Null) => Some(Null),
+                    _ => None,
+                },
+                BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+                    (l.is_any_of(&[Number]) && r.is_any_of(&[Number])).then_some(Number)
+                }
+                BinaryOp::Eq | BinaryOp::Ne => l.unify(r).map(|_| Boolean),
+                BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => l
+                    .unify(r)
+                    .filter(|t| t.is_any_of(&[Number, Text, Date]))
+                    .map(|_| Boolean),
+                BinaryOp::And | BinaryOp::Or => {
+                    (l.is_any_of(&[Boolean]) && r.is_any_of(&[Boolean])).then_some(Boolean)
+                }
+            };
+            result.ok_or_else(|| {
+                format!(
+                    "'{}' does not accept {} and {}",
+                    op.symbol(),
+                    l.name(),
+                    r.name()
+                )
+            })
+        }
+        Expr::Call(function, args) => {
+            let types = args
+                .iter()
+                .map(|arg| check_expr(arg, field_type))
+                .collect::<Result<Vec<_>, _>>()?;
+            check_call(*function, &types)
+        }
+    }
+}
+
+fn check_call(function: Function, args: &[FormulaType]) -> Result<FormulaType, String> {
+    use FormulaType::{Boolean, Date, Number, Text};
+
+    let expect = |index: usize, allowed: &[FormulaType]| -> Result<(), String> {
+        if args[index].is_any_of(allowed) {
+            return Ok(());
+        }
+        let names: Vec<&str> = allowed.iter().map(|t| t.name()).collect();
+        Err(format!(
+            "{}() expects {}, not {}",
+            function.name(),
+            names.join(" or "),
+            args[index].name()
+        ))
+    };
+    let unify_all = |types: &[FormulaType]| {
+        types
+            .iter()
+            .try_fold(FormulaType::Null, |acc, t| acc.unify(*t))
+            .ok_or_else(|| format!("Arguments of {}() must have the same type", function.name()))
+    };
+
+    match function {
+        Function::If => {
+            expect(0, &[Boolean])?;
+            unify_all(&args[1..])
+        }
+        Function::Coalesce => unify_all(args),
+        Function::IsNull => Ok(Boolean),
+        Function::Today => Ok(Date),
+        Function::DaysBetween | Function::YearsBetween => {
+            expect(0, &[Date])?;
+            expect(1, &[Date])?;
+            Ok(Number)
+        }
+        Function::Year | Function::Month | Function::Day => {
+            expect(0, &[Date])?;
+            Ok(Number)
+        }
+        Function::Round | Function::Floor | Function::Ceil | Function::Abs => {
+            for index in 0..args.len() {
+                expect(index, &[Number])?;
+            }
+            Ok(Number)
+        }
+        Function::Min | Function::Max => {
+            let ty = unify_all(args)?;
+            if !ty.is_any_of(&[Number, Text, Date]) {
+                return Err(format!(
+                    "{}() expects numbers, text or dates",
+                    function.name()
+                ));
+            }
+            Ok(ty)
+        }
+        Function::Len => {
+            expect(0, &[Text])?;
+            Ok(Number)
+        }
+        Function::Upper | Function::Lower | Function::Trim => {
+            expect(0, &[Text])?;
+            Ok(Text)
+        }
+        Function::Text => Ok(Text),
+    }
+}
+
+fn eval(expr: &Expr, field_value: &mut dyn FnMut(i32) -> Scalar, today: NaiveDate) -> Scalar {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Field(id) => field_value(*id),
+        Expr::Neg(operand) => match eval(operand, field_value, today) {
+            Scalar::Number(n) => Scalar::Number(-n),
+            _ => Scalar::Null,
+        },
+        Expr::Not(operand) => Scalar::Boolean(!eval(operand, field_value, today).is_true()),
+        Expr::Binary(op, left, right) => {
+            let l = eval(left, field_value, today);
+            let r = eval(right, field_value, today);
+            eval_binary(*op, l, r)
+        }
+        Expr::Call(function, args) => eval_call(*function, args, field_value, today),
+    }
+}
+
+fn eval_binary(op: BinaryOp, l: Scalar, r: Scalar) -> Scalar {
+    match op {
+        BinaryOp::Concat => return Scalar::Text(l.to_text() + &r.to_text()),
+        BinaryOp::And => return Scalar::Boolean(l.is_true() && r.is_true()),
+        BinaryOp::Or => return Scalar::Boolean(l.is_true() || r.is_true()),
+        _ => {}
+    }
+
+    match (op, l, r) {
+        (_, Scalar::Null, _) | (_, _, Scalar::Null) => Scalar::Null,
+        (BinaryOp::Add, Scalar::Number(a), Scalar::Number(b)) => Scalar::number(a + b),
+        (BinaryOp::Add, Scalar::Date(d), Scalar::Number(n))
+        | (BinaryOp::Add, Scalar::Number(n), Scalar::Date(d)) => add_days(d, n),
+        (BinaryOp::Sub, Scalar::Number(a), Scalar::Number(b)) => Scalar::number(a - b),
+        (BinaryOp::Sub, Scalar::Date(d), Scalar::Number(n)) => add_days(d, -n),
+        (BinaryOp::Sub, Scalar::Date(a), Scalar::Date(b)) => {
+            Scalar::Number((a - b).num_days() as f64)
+        }
+        (BinaryOp::Mul, Scalar::Number(a), Scalar::Number(b)) => Scalar::number(a * b),
+        (BinaryOp::Div | BinaryOp::Rem, Scalar::Number(_), Scalar::Number(0.0)) => Scalar::Null,
+        (BinaryOp::Div, Scalar::Number(a), Scalar::Number(b)) => Scalar::number(a / b),
+        (BinaryOp::Rem, Scalar::Number(a), Scalar::Number(b)) => Scalar::number(a % b),
+        (op, l, r) => match l.compare(&r) {
+            Some(ordering) => Scalar::Boolean(match op {
+                BinaryOp::Eq => ordering == Ordering::Equal,
+                BinaryOp::Ne => ordering != Ordering::Equal,
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                BinaryOp::Ge => ordering != Ordering::Less,
+                _ => return Scalar::Null,
+            }),
+            None => Scalar::Null,
+        },
+    }
+}
+
+fn eval_call(
+    function: Function,
+    args: &[Expr],
+    field_value: &mut dyn FnMut(i32) -> Scalar,
+    today: NaiveDate,
+) -> Scalar {
+    // `if` and `coalesce` only evaluate the arguments they need
+    match function {
+        Function::If => {
+            let branch = if eval(&args[0], field_value, today).is_true() {
+                1
+            } else {
+                2
+            };
+            return eval(&args[branch], field_value, today);
+        }
+        Function::Coalesce => {
+            return args
+                .iter()
+                .map(|arg| eval(arg, field_value, today))
+                .find(|value| *value != Scalar::Null)
+                .unwrap_or(Scalar::Null);
+        }
+        _ => {}
+    }
+
+    let values: Vec<Scalar> = args
+        .iter()
+        .map(|arg| eval(arg, field_value, today))
+        .collect();
+
+    match (function, values.as_slice()) {
+        (Function::IsNull, [value]) => Scalar::Boolean(*value == Scalar::Null),
+        (Function::Today, []) => Scalar::Date(today),
+        (Function::DaysBetween, [Scalar::Date(from), Scalar::Date(to)]) => {
+            Scalar::Number((*to - *from).num_days() as f64)
+        }
+        (Function::YearsBetween, [Scalar::Date(from), Scalar::Date(to)]) => {
+            let mut years = to.year() - from.year();
+            if (to.month(), to.day()) < (from.month(), from.day()) {
+                years -= 1;
+            }
+            Scalar::Number(years as f64)
+        }
+        (Function::Year, [Scalar::Date(d)]) => Scalar::Number(d.year() as f64),
+        (Function::Month, [Scalar::Date(d)]) => Scalar::Number(d.month() as f64),
+        (Function::Day, [Scalar::Date(d)]) => Scalar::Number(d.day() as f64),
+        (Function::Round, [Scalar::Number(n)]) => Scalar::number(n.round()),
+        (Function::Round, [Scalar::Number(n), Scalar::Number(digits)]) => {
+            let factor = 10f64.powi(digits.clamp(0.0, 10.0) as i32);
+            Scalar::number((n * factor).round() / factor)
+        }
+        (Function::Floor, [Scalar::Number(n)]) => Scalar::number(n.floor()),
+        (Function::Ceil, [Scalar::Number(n)]) => Scalar::number(n.ceil()),
+        (Function::Abs, [Scalar::Number(n)]) => Scalar::number(n.abs()),
+        (Function::Min | Function::Max, values) => values
+            .iter()
+            .filter(|value| **value != Scalar::Null)
+            .cloned()
+            .reduce(|a, b| {
+                let keep_a = match a.compare(&b) {
+                    Some(Ordering::Greater) => function == Function::Max,
+                    _ => function == Function::Min,
+                };
+                if keep_a { a } else { b }
+            })
+            .unwrap_or(Scalar::Null),
+        (Function::Len, [Scalar::Text(s)]) => Scalar::Number(s.chars().count() as f64),
+        (Function::Upper, [Scalar::Text(s)]) => Scalar::Text(s.to_uppercase()),
+        (Function::Lower, [Scalar::Text(s)]) => Scalar::Text(s.to_lowercase()),
+        (Function::Trim, [Scalar::Text(s)]) => Scalar::Text(s.trim().to_string()),
+        (Function::Text, [value]) => Scalar::Text(value.to_text()),
+        _ => Scalar::Null,
+    }
+}
+
+/// Moves a date by a number of days, rounded to whole days.
+fn add_days(date: NaiveDate, days: f64) -> Scalar {
+    TimeDelta::try_days(days.round() as i64)
+        .and_then(|delta| date.checked_add_signed(delta))
+        .map_or(Scalar::Null, Scalar::Date)
+}
+
+/// Formula fields of one vault, checked and ready to evaluate.
+pub struct VaultFormulas {
+    /// Value types of the other fields formulas may read
+    field_types: HashMap<i32, FormulaType>,
+    /// Select fields, whose choice IDs formulas read as labels
+    select_fields: HashMap<i32, FieldDefinitionDto>,
+    /// Formulas by field ID; None when the formula no longer checks
+    formulas: HashMap<i32, Option<Formula>>,
+}
+
+impl VaultFormulas {
+    /// Checks the formula fields among a vault's fields.
+    ///
+    /// A formula that no longer checks, e.g. because a field it read was
+    /// deleted, evaluates to null.
+    pub fn new(fields: &[FieldDefinitionDto]) -> Self {
+        let by_id: HashMap<i32, FieldDefinitionDto> =
+            fields.iter().map(|f| (f.id, f.clone())).collect();
+
+        let field_types = fields
+            .iter()
+            .filter_map(|f| Some((f.id, FormulaType::of_field(f).ok()?)))
+            .collect();
+        let select_fields = fields
+            .iter()
+            .filter(|f| f.field_type == FieldType::Select)
+            .map(|f| (f.id, f.clone()))
+            .collect();
+
+        let formulas = fields
+            .iter()
+            .filter(|f| f.field_type == FieldType::Formula)
+            .map(|f| {
+                let source = f
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.formula.as_deref())
+                    .unwrap_or_default();
+                let formula = match check_formula(source, Some(f.id), &by_id) {
+                    Ok(_) => Formula::parse(source).ok(),
+                    Err(e) => {
+                        log::warn!("Formula field '{}' (id={}) is invalid: {}", f.name, f.id, e);
+                        None
+                    }
+                };
+                (f.id, formula)
+            })
+            .collect();
+
+        Self {
+            field_types,
+            select_fields,
+            formulas,
+        }
+    }
+
+    /// Evaluates every formula for one entry.
+    ///
+    /// `value_of` returns the stored or computed value of a field. Returns
+    /// a metadata-style map (field ID string -> value).
+    pub fn evaluate(
+        &self,
+        value_of: &dyn Fn(i32) -> Option<Value>,
+        today: NaiveDate,
+    ) -> Map<String, Value> {
+        let mut memo: HashMap<i32, Scalar> = HashMap::new();
+
+        self.formulas
+            .keys()
+            .map(|&id| {
+                let value = self.field_value(id, value_of, today, &mut memo);
+                (id.to_string(), value.into_json())
+            })
+            .collect()
+    }
+
+    /// Value of a field as read by formulas. Checked formulas never read
+    /// each other in a cycle, so the recursion ends.
+    fn field_value(
+        &self,
+        id: i32,
+        value_of: &dyn Fn(i32) -> Option<Value>,
+        today: NaiveDate,
+        memo: &mut HashMap<i32, Scalar>,
+    ) -> Scalar {
+        let Some(formula) = self.formulas.get(&id) else {
+            return match (value_of(id), self.field_types.get(&id)) {
+                (Some(value), Some(ty)) => match self.select_fields.get(&id) {
+                    Some(field) => Scalar::from_json(&choice_labels(field, &value), *ty),
+                    None => Scalar::from_json(&value, *ty),
+                },
+                _ => Scalar::Null,
+            };
+        };
+        if let Some(value) = memo.get(&id) {
+            return value.clone();
+        }
+
+        let value = match formula {
+            Some(formula) => eval(
+                &formula.expr,
+                &mut |ref_id| self.field_value(ref_id, value_of, today, memo),
+                today,
+            ),
+            None => Scalar::Null,
+        };
+        memo.insert(id, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldOptions;
+    use serde_json::json;
+
+    fn field(id: i32, field_type: FieldType, formula: Option<&str>) -> FieldDefinitionDto {
+        FieldDefinitionDto {
+            id,
+            vault_id: 1,
+            name: format!("Field {}", id),
+            field_type,
+            options: formula.map(|f| FieldOptions {
+                formula: Some(f.to_string()),
+                ..Default::default()
+            }),
+            position: id,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn fields() -> HashMap<i32, FieldDefinitionDto> {
+        [
+            field(1, FieldType::Text, None),
+            field(2, FieldType::Number, None),
+            field(3, FieldType::Number, None),
+            field(4, FieldType::Date, None),
+            field(5, FieldType::Multiselect, None),
+        ]
+        .into_iter()
+        .map(|f| (f.id, f))
+        .collect()
+    }
+
+    /// Evaluates a formula over `{1}` = "Dune", `{2}` = 42.5, `{3}` = 412
+    /// and `{4}` = 1990-06-15, as of 2024-03-01.
+    fn eval_source(source: &str) -> Value {
+        let values = json!({"1": "Dune", "2": 42.5, "3": 412, "4": "1990-06-15"});
+        let mut all: Vec<FieldDefinitionDto> = fields().into_values().collect();
+        all.push(field(10, FieldType::Formula, Some(source)));
+
+        let formulas = VaultFormulas::new(&all);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        formulas.evaluate(&|id| values.get(id.to_string()).cloned(), today)["10"].clone()
+    }
+
+    #[test]
+    fn test_arithmetic_text_and_dates() {
+        assert_eq!(eval_source("1 + 2 * 3 - 4 / 2"), json!(5));
+        assert_eq!(eval_source("round({2} / {3} * 100, 2)"), json!(10.32));
+        assert_eq!(eval_source("-(2 + 3) % 3"), json!(-2));
+        assert_eq!(
+            eval_source(r#"{1} & " (" & year({4}) & ")""#),
+            json!("Dune (1990)")
+        );
+        assert_eq!(eval_source("years_between({4}, today())"), json!(33));
+        assert_eq!(eval_source("{4} + 20"), json!("1990-07-05"));
+        assert_eq!(eval_source("today() - {4}"), json!(12313));
+        assert_eq!(
+            eval_source(r#"if({3} > 400 and not ({2} < 10), "long", "short")"#),
+            json!("long")
+        );
+        assert_eq!(eval_source("max({2}, {3}, null)"), json!(412));
+    }
+
+    #[test]
+    fn test_null_handling() {
+        // Field 9 is unset; formulas reading it are still valid
+        let mut all: Vec<FieldDefinitionDto> = fields().into_values().collect();
+        all.push(field(9, FieldType::Number, None));
+        all.push(field(10, FieldType::Formula, Some("{9} * 2")));
+        all.push(field(11, FieldType::Formula, Some(r#""n=" & {9}"#)));
+        all.push(field(
+            12,
+            FieldType::Formula,
+            Some("coalesce({9}, {10}, 7)"),
+        ));
+        all.push(field(13, FieldType::Formula, Some("{3} / 0")));
+        all.push(field(14, FieldType::Formula, Some("is_null({9})")));
+
+        let formulas = VaultFormulas::new(&all);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let values = formulas.evaluate(&|id| (id == 3).then(|| json!(5)), today);
+
+        assert_eq!(values["10"], Value::Null);
+        assert_eq!(values["11"], json!("n="));
+        assert_eq!(values["12"], json!(7));
+        assert_eq!(values["13"], Value::Null);
+        assert_eq!(values["14"], json!(true));
+    }
+
+    #[test]
+    fn test_check_rejects_type_errors() {
+        let fields = fields();
+        let check = |source: &str| check_formula(source, None, &fields);
+
+        assert_eq!(check("{2} / {3}"), Ok(FormulaType::Number));
+        assert_eq!(check("{4} + 7"), Ok(FormulaType::Date));
+        assert_eq!(check("if({2} > 1, {1}, null)"), Ok(FormulaType::Text));
+        assert!(
+            check("{1} + 1")
+                .unwrap_err()
+                .contains("'+' does not accept text")
+        );
+        assert!(
+            check("if({2}, 1, 2)")
+                .unwrap_err()
+                .contains("expects a boolean")
+        );
+        assert!(check("if(true, 1, \"a\")").is_err());
+        assert!(
+            check("{5}")
+                .unwrap_err()
+                .contains("cannot be used in formulas")
+        );
+        assert!(check("{99}").unwrap_err().contains("does not exist"));
+        assert!(check("1 < 2 < 3").unwrap_err().contains("chained"));
+        assert!(check("sqrt(4)").unwrap_err().contains("Unknown function"));
+        assert!(check("round()").unwrap_err().contains("1 to 2 argument(s)"));
+        assert!(check("(1 + 2").is_err());
+        assert!(
+            check(&"(".repeat(100))
+                .unwrap_err()
+                .contains("nested too deeply")
+        );
+    }
+
+    #[test]
+    fn test_check_rejects_cycles() {
+        let mut fields = fields();
+        fields.insert(10, field(10, FieldType::Formula, Some("{2} * 2")));
+        fields.insert(11, field(11, FieldType::Formula, Some("{10} + 1")));
+
+        // A new formula may read existing ones
+        assert_eq!(
+            check_formula("{11} / 2", None, &fields),
+            Ok(FormulaType::Number)
+        );
+        // Field 10 may not be changed to read field 11, which reads field 10
+        let cycle = check_formula("{11} - 1", Some(10), &fields).unwrap_err();
+        assert!(cycle.contains("cycle"), "{}", cycle);
+        assert!(check_formula("{10}", Some(10), &fields).is_err());
+    }
+
+    #[test]
+    fn test_check_shared_references_once() {
+        // Each formula reads the previous one twice, so checking the last
+        // one without reusing results would take 2^60 steps
+        let mut fields = fields();
+        fields.insert(10, field(10, FieldType::Formula, Some("{2} * 2")));
+        for id in 11..70 {
+            let source = format!("{{{0}}} + {{{0}}}", id - 1);
+            fields.insert(id, field(id, FieldType::Formula, Some(&source)));
+        }
+
+        assert_eq!(
+            check_formula("{69} / 2", None, &fields),
+            Ok(FormulaType::Number)
+        );
+        let cycle = check_formula("{69} - 1", Some(10), &fields).unwrap_err();
+        assert!(cycle.contains("cycle"), "{}", cycle);
+    }
+}
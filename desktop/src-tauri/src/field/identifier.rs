@@ -0,0 +1,297 @@
+//! Identifier checking and normalization.
+//!
+//! Identifiers are stored in a canonical form, so that the differently
+//! typed forms of one identifier (hyphenated or not, ISBN-10 or ISBN-13,
+//! an IMDb link or the bare ID) compare equal for uniqueness checks and
+//! lookups.
+
+use super::constraint::{compile_pattern, pattern_error};
+use super::{FieldOptions, IdentifierScheme};
+
+/// Longest identifier accepted by any scheme.
+const MAX_IDENTIFIER_LENGTH: usize = 200;
+
+/// Checks that identifier field options name a scheme, and that custom
+/// schemes have a pattern that compiles.
+pub fn check_identifier_options(options: Option<&FieldOptions>) -> Result<(), String> {
+    let Some(scheme) = options.and_then(|o| o.scheme) else {
+        return Err("Identifier fields need a scheme".to_string());
+    };
+
+    let pattern = options.and_then(|o| o.pattern.as_deref());
+    match (scheme, pattern) {
+        (IdentifierScheme::Custom, None) => {
+            Err("Custom identifier fields need a pattern".to_string())
+        }
+        (IdentifierScheme::Custom, Some(pattern)) => compile_pattern(pattern).map(|_| ()),
+        (_, Some(_)) => Err(format!(
+            "Only custom identifier fields take a pattern, not {}",
+            scheme.as_str()
+        )),
+        (_, None) => Ok(()),
+    }
+}
+
+/// Checks an identifier against the field's scheme and returns its
+/// canonical form.
+pub fn normalize_identifier(options: Option<&FieldOptions>, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("identifier is empty".to_string());
+    }
+    if value.len() > MAX_IDENTIFIER_LENGTH {
+        return Err(format!(
+            "identifier is longer than {} characters",
+            MAX_IDENTIFIER_LENGTH
+        ));
+    }
+
+    match options.and_then(|o| o.scheme) {
+        Some(IdentifierScheme::Isbn) => normalize_isbn(value),
+        Some(IdentifierScheme::Ean) => normalize_ean(value),
+        Some(IdentifierScheme::Imdb) => normalize_imdb(value),
+        Some(IdentifierScheme::Custom) => {
+            let pattern = options
+                .and_then(|o| o.pattern.as_deref())
+                .unwrap_or_default();
+            if compile_pattern(pattern)?.is_match(value) {
+                Ok(value.to_string())
+            } else {
+                Err(pattern_error(&options.cloned().unwrap_or_default(), value, pattern))
+            }
+        }
+        None => Err("the field has no identifier scheme".to_string()),
+    }
+}
+
+/// Removes the separators people type inside numeric identifiers.
+fn strip_separators(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' ' | '\u{2010}' | '\u{2011}'))
+        .collect()
+}
+
+/// Check digit of a GTIN (EAN-8, UPC-A, EAN-13 and ISBN-13) body, weighting
+/// digits 3 and 1 alternately from the right.
+fn gtin_check_digit(body: &[u8]) -> u8 {
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| u32::from(*d) * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+fn digits(value: &str) -> Option<Vec<u8>> {
+    value
+        .bytes()
+        .map(|b| b.is_ascii_digit().then(|| b - b'0'))
+        .collect()
+}
+
+fn to_string(digits: &[u8]) -> String {
+    digits.iter().map(|d| char::from(b'0' + d)).collect()
+}
+
+/// Accepts ISBN-10 and ISBN-13, with or without an `ISBN` prefix and
+/// hyphens, and returns the ISBN-13.
+fn normalize_isbn(value: &str) -> Result<String, String> {
+    let mut compact = strip_separators(value).to_ascii_uppercase();
+    for prefix in ["ISBN13:", "ISBN10:", "ISBN:", "ISBN"] {
+        if let Some(rest) = compact.strip_prefix(prefix) {
+            compact = rest.to_string();
+            break;
+        }
+    }
+
+    match compact.len() {
+        10 => {
+            // Checked as a whole so that multi-byte characters are rejected
+            // before anything is split
+            let mut number = match compact.strip_suffix('X') {
+                Some(body) => digits(body).map(|mut d| {
+                    d.push(10);
+                    d
+                }),
+                None => digits(&compact),
+            }
+            .ok_or_else(|| format!("'{}' is not an ISBN", value))?;
+            let sum: u32 = number
+                .iter()
+                .enumerate()
+                .map(|(i, d)| u32::from(*d) * (10 - i as u32))
+                .sum();
+            if !sum.is_multiple_of(11) {
+                return Err(format!("'{}' has an invalid ISBN-10 check digit", value));
+            }
+            number.truncate(9);
+
+            let mut isbn13 = vec![9, 7, 8];
+            isbn13.extend_from_slice(&number);
+            isbn13.push(gtin_check_digit(&isbn13));
+            Ok(to_string(&isbn13))
+        }
+        13 => {
+            let number = digits(&compact).ok_or_else(|| format!("'{}' is not an ISBN", value))?;
+            if !(compact.starts_with("978") || compact.starts_with("979")) {
+                return Err(format!(
+                    "'{}' is not an ISBN: it must start with 978 or 979",
+                    value
+                ));
+            }
+            if gtin_check_digit(&number[..12]) != number[12] {
+                return Err(format!("'{}' has an invalid ISBN-13 check digit", value));
+            }
+            Ok(compact)
+        }
+        _ => Err(format!(
+            "'{}' is not an ISBN: expected 10 or 13 digits",
+            value
+        )),
+    }
+}
+
+/// Accepts EAN-8, UPC-A and EAN-13 codes, and returns UPC-A codes as the
+/// equivalent EAN-13.
+fn normalize_ean(value: &str) -> Result<String, String> {
+    let compact = strip_separators(value);
+    let number = digits(&compact)
+        .filter(|d| matches!(d.len(), 8 | 12 | 13))
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not an EAN or UPC: expected 8, 12 or 13 digits",
+                value
+            )
+        })?;
+
+    let (body, check) = number.split_at(number.len() - 1);
+    if gtin_check_digit(body) != check[0] {
+        return Err(format!("'{}' has an invalid check digit", value));
+    }
+
+    Ok(if number.len() == 12 {
+        format!("0{}", compact)
+    } else {
+        compact
+    })
+}
+
+/// Accepts an IMDb title ID or a link to the title page, and returns the
+/// lowercase ID.
+fn normalize_imdb(value: &str) -> Result<String, String> {
+    let lower = value.to_ascii_lowercase();
+    let id = match lower.find("imdb.com/title/") {
+        Some(start) => {
+            let rest = &lower[start + "imdb.com/title/".len()..];
+            rest.split(['/', '?', '#']).next().unwrap_or_default()
+        }
+        None => lower.as_str(),
+    };
+
+    let is_title_id = id
+        .strip_prefix("tt")
+        .is_some_and(|n| (7..=10).contains(&n.len()) && n.bytes().all(|b| b.is_ascii_digit()));
+    if !is_title_id {
+        return Err(format!(
+            "'{}' is not an IMDb title ID such as tt0111161",
+            value
+        ));
+    }
+
+    Ok(id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(scheme: IdentifierScheme, pattern: Option<&str>) -> FieldOptions {
+        FieldOptions {
+            scheme: Some(scheme),
+            pattern: pattern.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_isbn() {
+        let isbn = options(IdentifierScheme::Isbn, None);
+        let normalize = |value| normalize_identifier(Some(&isbn), value);
+
+        // ISBN-10 forms of Dune become the same ISBN-13
+        assert_eq!(normalize("0-441-17271-7").unwrap(), "9780441172719");
+        assert_eq!(normalize("ISBN 0441172717").unwrap(), "9780441172719");
+        assert_eq!(normalize("978-0-441-17271-9").unwrap(), "9780441172719");
+        assert_eq!(normalize("080442957X").unwrap(), "9780804429573");
+
+        assert!(normalize("0-441-17271-8").is_err());
+        assert!(normalize("978-0-441-17271-0").is_err());
+        assert!(normalize("5901234123457").is_err());
+        assert!(normalize("12345").is_err());
+    }
+
+    #[test]
+    fn test_normalize_isbn_rejects_non_ascii() {
+        let isbn = options(IdentifierScheme::Isbn, None);
+        let normalize = |value| normalize_identifier(Some(&isbn), value);
+
+        // Ten and thirteen bytes long, but not ten or thirteen characters
+        assert!(normalize("12345678é").is_err());
+        assert!(normalize("0441172é7").is_err());
+        assert!(normalize("97804411727é").is_err());
+    }
+
+    #[test]
+    fn test_normalize_ean_and_imdb() {
+        let ean = options(IdentifierScheme::Ean, None);
+        assert_eq!(
+            normalize_identifier(Some(&ean), "5901234123457").unwrap(),
+            "5901234123457"
+        );
+        assert_eq!(
+            normalize_identifier(Some(&ean), "036000291452").unwrap(),
+            "0036000291452"
+        );
+        assert_eq!(
+            normalize_identifier(Some(&ean), "9638-5074").unwrap(),
+            "96385074"
+        );
+        assert!(normalize_identifier(Some(&ean), "5901234123458").is_err());
+
+        let imdb = options(IdentifierScheme::Imdb, None);
+        assert_eq!(
+            normalize_identifier(Some(&imdb), "TT0111161").unwrap(),
+            "tt0111161"
+        );
+        assert_eq!(
+            normalize_identifier(Some(&imdb), "https://www.imdb.com/title/tt0111161/?ref_=nv")
+                .unwrap(),
+            "tt0111161"
+        );
+        assert!(normalize_identifier(Some(&imdb), "nm0000151").is_err());
+        assert!(normalize_identifier(Some(&imdb), "tt12").is_err());
+    }
+
+    #[test]
+    fn test_custom_pattern() {
+        let custom = options(IdentifierScheme::Custom, Some(r"[A-Z]{3}-\d{4}"));
+        assert!(check_identifier_options(Some(&custom)).is_ok());
+        assert_eq!(
+            normalize_identifier(Some(&custom), " ABC-1234 ").unwrap(),
+            "ABC-1234"
+        );
+        // The pattern must match the whole value
+        assert!(normalize_identifier(Some(&custom), "xABC-1234").is_err());
+
+        assert!(check_identifier_options(None).is_err());
+        assert!(check_identifier_options(Some(&options(IdentifierScheme::Custom, None))).is_err());
+        assert!(
+            check_identifier_options(Some(&options(IdentifierScheme::Custom, Some("(")))).is_err()
+        );
+        assert!(
+            check_identifier_options(Some(&options(IdentifierScheme::Isbn, Some("x")))).is_err()
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Validation of raw JSON values against a JSON Schema.
+//!
+//! Supports the commonly used validation keywords of JSON Schema 2020-12:
+//! types, enums and constants, string, number, object and array bounds,
+//! and the `allOf` / `anyOf` / `oneOf` / `not` combinators. Schemas using
+//! anything else, such as `$ref`, are rejected when the field is
+//! configured, instead of being silently ignored.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// Keywords that describe a schema without constraining values.
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+const TYPES: &[&str] = &[
+    "null", "boolean", "object", "array", "number", "string", "integer",
+];
+
+/// Checks that a schema only uses supported keywords, with well-formed
+/// arguments.
+pub fn check_json_schema(schema: &Value) -> Result<(), String> {
+    check_schema(schema, "")
+}
+
+/// Validates a value against a schema accepted by [`check_json_schema`].
+/// Returns the first violation, located by its JSON pointer.
+pub fn validate_json(schema: &Value, value: &Value) -> Result<(), String> {
+    validate(schema, value, "")
+}
+
+fn check_schema(schema: &Value, path: &str) -> Result<(), String> {
+    let object = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(object) => object,
+        _ => {
+            return Err(format!(
+                "schema at '{}' must be an object or a boolean",
+                path
+            ));
+        }
+    };
+
+    let invalid = |keyword: &str, expected: &str| {
+        Err(format!("'{}' at '{}' must be {}", keyword, path, expected))
+    };
+
+    for (keyword, argument) in object {
+        let nested = |suffix: &str| format!("{}/{}{}", path, keyword, suffix);
+        match keyword.as_str() {
+            k if ANNOTATIONS.contains(&k) => {}
+            "type" => {
+                let names: Vec<&Value> = match argument {
+                    Value::Array(names) if !names.is_empty() => names.iter().collect(),
+                    name => vec![name],
+                };
+                if !names
+                    .iter()
+                    .all(|n| n.as_str().is_some_and(|n| TYPES.contains(&n)))
+                {
+                    return invalid(keyword, "a type name or a list of them");
+                }
+            }
+            "enum" => {
+                if !argument.is_array() {
+                    return invalid(keyword, "a list");
+                }
+            }
+            "const" => {}
+            "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties"
+            | "maxProperties" => {
+                if argument.as_u64().is_none() {
+                    return invalid(keyword, "a non-negative integer");
+                }
+            }
+            "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" => {
+                if !argument.is_number() {
+                    return invalid(keyword, "a number");
+                }
+            }
+            "multipleOf" => {
+                if !argument.as_f64().is_some_and(|m| m > 0.0) {
+                    return invalid(keyword, "a positive number");
+                }
+            }
+            "uniqueItems" => {
+                if !argument.is_boolean() {
+                    return invalid(keyword, "a boolean");
+                }
+            }
+            "pattern" => {
+                let Some(pattern) = argument.as_str() else {
+                    return invalid(keyword, "a regular expression");
+                };
+                Regex::new(pattern)
+                    .map_err(|e| format!("'pattern' at '{}' is invalid: {}", path, e))?;
+            }
+            "required" => {
+                if !argument
+                    .as_array()
+                    .is_some_and(|r| r.iter().all(Value::is_string))
+                {
+                    return invalid(keyword, "a list of property names");
+                }
+            }
+            "properties" => {
+                let Some(properties) = argument.as_object() else {
+                    return invalid(keyword, "an object of schemas");
+                };
+                for (name, property) in properties {
+                    check_schema(property, &nested(&format!("/{}", escape(name))))?;
+                }
+            }
+            "additionalProperties" | "items" | "not" => check_schema(argument, &nested(""))?,
+            "allOf" | "anyOf" | "oneOf" => {
+                let Some(schemas) = argument.as_array().filter(|s| !s.is_empty()) else {
+                    return invalid(keyword, "a non-empty list of schemas");
+                };
+                for (i, schema) in schemas.iter().enumerate() {
+                    check_schema(schema, &nested(&format!("/{}", i)))?;
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "unsupported schema keyword '{}' at '{}'",
+                    keyword, path
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => return Err(fail(path, "no value is allowed here")),
+        Value::Object(schema) => schema,
+        _ => return Ok(()),
+    };
+
+    if let Some(types) = schema.get("type") {
+        let names: Vec<&str> = match types {
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            name => name.as_str().into_iter().collect(),
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            return Err(fail(path, &format!("expected {}", names.join(" or "))));
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            return Err(fail(path, "value is not one of the allowed values"));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            return Err(fail(path, &format!("expected {}", constant)));
+        }
+    }
+
+    match value {
+        Value::String(text) => validate_string(schema, text, path)?,
+        Value::Number(number) => validate_number(schema, number.as_f64().unwrap_or(0.0), path)?,
+        Value::Object(object) => validate_object(schema, object, path)?,
+        Value::Array(items) => validate_array(schema, items, path)?,
+        _ => {}
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for schema in schemas {
+            validate(schema, value, path)?;
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+        if !schemas.iter().any(|s| validate(s, value, path).is_ok()) {
+            return Err(fail(path, "value matches none of the 'anyOf' schemas"));
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+        let matches = schemas
+            .iter()
+            .filter(|s| validate(s, value, path).is_ok())
+            .count();
+        if matches != 1 {
+            return Err(fail(
+                path,
+                &format!(
+                    "value matches {} of the 'oneOf' schemas instead of one",
+                    matches
+                ),
+            ));
+        }
+    }
+    if let Some(not) = schema.get("not") {
+        if validate(not, value, path).is_ok() {
+            return Err(fail(path, "value matches the 'not' schema"));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_string(schema: &Map<String, Value>, text: &str, path: &str) -> Result<(), String> {
+    let length = text.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if length < min {
+            return Err(fail(path, &format!("expected at least {} characters", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if length > max {
+            return Err(fail(path, &format!("expected at most {} characters", max)));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        if !Regex::new(pattern).is_ok_and(|re| re.is_match(text)) {
+            return Err(fail(path, &format!("text does not match {}", pattern)));
+        }
+    }
+    Ok(())
+}
+
+fn validate_number(schema: &Map<String, Value>, number: f64, path: &str) -> Result<(), String> {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+    if let Some(min) = bound("minimum").filter(|min| number < *min) {
+        return Err(fail(path, &format!("expected at least {}", min)));
+    }
+    if let Some(max) = bound("maximum").filter(|max| number > *max) {
+        return Err(fail(path, &format!("expected at most {}", max)));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+        return Err(fail(path, &format!("expected more than {}", min)));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+        return Err(fail(path, &format!("expected less than {}", max)));
+    }
+    if let Some(step) = bound("multipleOf") {
+        let quotient = number / step;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            return Err(fail(path, &format!("expected a multiple of {}", step)));
+        }
+    }
+    Ok(())
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+) -> Result<(), String> {
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if let Some(name) = name.as_str().filter(|n| !object.contains_key(*n)) {
+            return Err(fail(path, &format!("missing required property '{}'", name)));
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, value) in object {
+        let child = format!("{}/{}", path, escape(name));
+        match properties.and_then(|p| p.get(name)) {
+            Some(property) => validate(property, value, &child)?,
+            None => {
+                if let Some(additional) = schema.get("additionalProperties") {
+                    if additional == &Value::Bool(false) {
+                        return Err(fail(path, &format!("unexpected property '{}'", name)));
+                    }
+                    validate(additional, value, &child)?;
+                }
+            }
+        }
+    }
+
+    let count = object.len() as u64;
+    if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+        if count < min {
+            return Err(fail(path, &format!("expected at least {} properties", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+        if count > max {
+            return Err(fail(path, &format!("expected at most {} properties", max)));
+        }
+    }
+    Ok(())
+}
+
+fn validate_array(schema: &Map<String, Value>, items: &[Value], path: &str) -> Result<(), String> {
+    if let Some(item_schema) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            validate(item_schema, item, &format!("{}/{}", path, i))?;
+        }
+    }
+
+    let count = items.len() as u64;
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if count < min {
+            return Err(fail(path, &format!("expected at least {} items", min)));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if count > max {
+            return Err(fail(path, &format!("expected at most {} items", max)));
+        }
+    }
+    if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].contains(item) {
+                return Err(fail(path, &format!("item {} repeats an earlier item", i)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+/// Escapes a property name for a JSON pointer.
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+fn fail(path: &str, message: &str) -> String {
+    if path.is_empty() {
+        message.to_string()
+    } else {
+        format!("{} at '{}'", message, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_json_schema() {
+        assert!(check_json_schema(&json!(true)).is_ok());
+        assert!(
+            check_json_schema(&json!({
+                "title": "Edition",
+                "type": "object",
+                "properties": {"pages": {"type": "integer", "minimum": 1}},
+                "required": ["pages"],
+                "additionalProperties": false
+            }))
+            .is_ok()
+        );
+
+        assert!(check_json_schema(&json!({"$ref": "#/$defs/x"})).is_err());
+        assert!(check_json_schema(&json!({"type": "text"})).is_err());
+        assert!(check_json_schema(&json!({"properties": {"a": {"minLength": -1}}})).is_err());
+        assert!(check_json_schema(&json!({"pattern": "("})).is_err());
+        assert!(check_json_schema(&json!({"anyOf": []})).is_err());
+        assert!(check_json_schema(&json!("object")).is_err());
+    }
+
+    #[test]
+    fn test_validate_json() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "pages": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true},
+                "format": {"enum": ["hardcover", "paperback"]}
+            },
+            "required": ["pages"],
+            "additionalProperties": false
+        });
+        let validate = |value| validate_json(&schema, &value);
+
+        assert!(validate(json!({"pages": 412, "tags": ["sf"], "format": "paperback"})).is_ok());
+        assert!(validate(json!({"pages": 412.0})).is_ok());
+
+        assert_eq!(validate(json!([])).unwrap_err(), "expected object");
+        assert_eq!(
+            validate(json!({"tags": []})).unwrap_err(),
+            "missing required property 'pages'"
+        );
+        assert_eq!(
+            validate(json!({"pages": 1, "tags": ["a", 2]})).unwrap_err(),
+            "expected string at '/tags/1'"
+        );
+        assert!(validate(json!({"pages": 0})).is_err());
+        assert!(validate(json!({"pages": 1.5})).is_err());
+        assert!(validate(json!({"pages": 1, "tags": ["a", "a"]})).is_err());
+        assert!(validate(json!({"pages": 1, "format": "ebook"})).is_err());
+        assert!(validate(json!({"pages": 1, "isbn": "x"})).is_err());
+
+        let one_of = json!({"oneOf": [{"type": "integer"}, {"minimum": 0}]});
+        assert!(validate_json(&one_of, &json!(-1)).is_ok());
+        assert!(validate_json(&one_of, &json!(1)).is_err());
+        assert!(validate_json(&json!({"not": {"type": "null"}}), &json!(null)).is_err());
+    }
+}
@@ -1,7 +1,23 @@
 //! Field definition module for custom vault fields.
 
+mod choice;
+mod constraint;
+mod conversion;
+mod currency;
+mod formula;
+mod identifier;
+mod json_schema;
 mod model;
+mod rule;
 mod service;
 
+pub use choice::{choice_labels, choice_sort_key, match_choice, merge_choices, prepare_choices};
+pub use constraint::{can_be_unique, check_constraint_options, check_value_constraints};
+pub use conversion::{check_conversion, convert_value};
+pub use currency::currency_minor_units;
+pub use formula::{VaultFormulas, check_formula};
+pub use identifier::{check_identifier_options, normalize_identifier};
+pub use json_schema::{check_json_schema, validate_json};
 pub use model::*;
+pub use rule::{check_rule, compare_values};
 pub use service::*;
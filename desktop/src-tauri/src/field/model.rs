@@ -1,5 +1,7 @@
 //! Field definition data transfer objects.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Supported field types.
@@ -7,65 +9,411 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "lowercase")]
 pub enum FieldType {
     Text,
+    Markdown,
     Number,
     Date,
+    PartialDate,
+    Datetime,
+    Duration,
     Url,
+    Identifier,
+    Email,
+    Phone,
+    Color,
+    Currency,
+    Json,
     Boolean,
     Select,
+    Multiselect,
+    Rating,
+    File,
+    Location,
     Relation,
+    Lookup,
+    Rollup,
+    Formula,
 }
 
 impl FieldType {
     pub fn as_str(&self) -> &'static str {
         match self {
             FieldType::Text => "text",
+            FieldType::Markdown => "markdown",
             FieldType::Number => "number",
             FieldType::Date => "date",
+            FieldType::PartialDate => "partialdate",
+            FieldType::Datetime => "datetime",
+            FieldType::Duration => "duration",
             FieldType::Url => "url",
+            FieldType::Identifier => "identifier",
+            FieldType::Email => "email",
+            FieldType::Phone => "phone",
+            FieldType::Color => "color",
+            FieldType::Currency => "currency",
+            FieldType::Json => "json",
             FieldType::Boolean => "boolean",
             FieldType::Select => "select",
+            FieldType::Multiselect => "multiselect",
+            FieldType::Rating => "rating",
+            FieldType::File => "file",
+            FieldType::Location => "location",
             FieldType::Relation => "relation",
+            FieldType::Lookup => "lookup",
+            FieldType::Rollup => "rollup",
+            FieldType::Formula => "formula",
         }
     }
 
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "text" => Some(FieldType::Text),
+            "markdown" => Some(FieldType::Markdown),
             "number" => Some(FieldType::Number),
             "date" => Some(FieldType::Date),
+            "partialdate" => Some(FieldType::PartialDate),
+            "datetime" => Some(FieldType::Datetime),
+            "duration" => Some(FieldType::Duration),
             "url" => Some(FieldType::Url),
+            "identifier" => Some(FieldType::Identifier),
+            "email" => Some(FieldType::Email),
+            "phone" => Some(FieldType::Phone),
+            "color" => Some(FieldType::Color),
+            "currency" => Some(FieldType::Currency),
+            "json" => Some(FieldType::Json),
             "boolean" => Some(FieldType::Boolean),
             "select" => Some(FieldType::Select),
+            "multiselect" => Some(FieldType::Multiselect),
+            "rating" => Some(FieldType::Rating),
+            "file" => Some(FieldType::File),
+            "location" => Some(FieldType::Location),
             "relation" => Some(FieldType::Relation),
+            "lookup" => Some(FieldType::Lookup),
+            "rollup" => Some(FieldType::Rollup),
+            "formula" => Some(FieldType::Formula),
             _ => None,
         }
     }
 }
 
+impl FieldType {
+    /// Whether values are computed on read instead of stored in entry
+    /// metadata, through relations or from a formula.
+    pub fn is_derived(&self) -> bool {
+        matches!(self, FieldType::Lookup | FieldType::Rollup | FieldType::Formula)
+    }
+
+    /// Whether values are numbers that can be summed and averaged.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, FieldType::Number | FieldType::Rating | FieldType::Duration)
+    }
+}
+
+/// Icon used to draw a rating scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RatingIcon {
+    #[default]
+    Star,
+    Heart,
+    Circle,
+}
+
+/// Kind of identifier held by identifier fields, deciding how values are
+/// checked and normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifierScheme {
+    /// ISBN-10 or ISBN-13, stored as ISBN-13
+    Isbn,
+    /// EAN-8, EAN-13 or UPC-A, with UPC-A stored as EAN-13
+    Ean,
+    /// IMDb title ID such as `tt0111161`
+    Imdb,
+    /// Any value matching the field's `pattern`
+    Custom,
+}
+
+impl IdentifierScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentifierScheme::Isbn => "isbn",
+            IdentifierScheme::Ean => "ean",
+            IdentifierScheme::Imdb => "imdb",
+            IdentifierScheme::Custom => "custom",
+        }
+    }
+}
+
+/// Aggregate applied by rollup fields to the related entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RollupFunction {
+    Count,
+    Sum,
+    Average,
+    Min,
+    Max,
+    DistinctList,
+}
+
+impl RollupFunction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupFunction::Count => "count",
+            RollupFunction::Sum => "sum",
+            RollupFunction::Average => "average",
+            RollupFunction::Min => "min",
+            RollupFunction::Max => "max",
+            RollupFunction::DistinctList => "distinct-list",
+        }
+    }
+}
+
+/// What happens to relation values when the referenced entry is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelationDeleteAction {
+    /// Block the deletion while references exist
+    Restrict,
+    /// Remove the reference from the referencing entry
+    #[default]
+    SetNull,
+    /// Delete the referencing entry as well
+    Cascade,
+}
+
+impl RelationDeleteAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationDeleteAction::Restrict => "restrict",
+            RelationDeleteAction::SetNull => "set-null",
+            RelationDeleteAction::Cascade => "cascade",
+        }
+    }
+}
+
+/// Value a field takes on new entries that leave it empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DefaultValue {
+    /// Fixed value of the field's type
+    Value { value: serde_json::Value },
+    /// Current local date, for date, partial date and timestamp fields
+    Today,
+    /// Current time, for timestamp fields
+    Now,
+    /// One more than the largest value in the vault, for number fields
+    Counter {
+        /// First value of the counter (default: 1)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start: Option<i64>,
+    },
+    /// Value of another field of the same entry, converted to the
+    /// field's type
+    #[serde(rename_all = "camelCase")]
+    CopyField { field_id: i32 },
+}
+
+/// Choice of a select or multiselect field.
+///
+/// Entries store the choice's ID, so its label and color can change
+/// without touching them. Choices saved before IDs existed were plain
+/// strings; they read back with their label as their ID, which is the
+/// value entries already hold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ChoiceRepr")]
+pub struct Choice {
+    /// Stable ID; empty for a choice being added, which gets one when the
+    /// field is saved
+    pub id: String,
+    pub label: String,
+    /// Color the choice is shown in, as `#rrggbb`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// Stored or submitted form of a choice.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChoiceRepr {
+    Label(String),
+    Choice {
+        #[serde(default)]
+        id: String,
+        label: String,
+        #[serde(default)]
+        color: Option<String>,
+    },
+}
+
+impl From<ChoiceRepr> for Choice {
+    fn from(repr: ChoiceRepr) -> Self {
+        match repr {
+            ChoiceRepr::Label(label) => Choice {
+                id: label.clone(),
+                label,
+                color: None,
+            },
+            ChoiceRepr::Choice { id, label, color } => Choice { id, label, color },
+        }
+    }
+}
+
 /// Options for field types (type-specific configuration).
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct FieldOptions {
+    /// Minimum length for text fields, in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<i32>,
     /// Maximum length for text fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_length: Option<i32>,
     /// Minimum value for number fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min: Option<f64>,
-    /// Maximum value for number fields
+    /// Maximum value for number fields, or the top of a rating scale
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max: Option<f64>,
-    /// Available choices for select fields
+    /// Increment between rating values, e.g. 0.5 for half-stars (default: 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+    /// Icon used to draw a rating scale (default: star)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<RatingIcon>,
+    /// Largest file, in bytes, a file field accepts (default and cap: 100MB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// Lowercase extensions, without the dot, a file field accepts
+    /// (default: any)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub choices: Option<Vec<String>>,
+    pub allowed_extensions: Option<Vec<String>>,
+    /// Available choices for select and multiselect fields, in the order
+    /// they are offered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<Choice>>,
     /// Target vault ID for relation fields
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_vault_id: Option<i32>,
     /// Display fields from target entry for relation fields (default: ["title"])
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_fields: Option<Vec<String>>,
+    /// Whether a relation field holds an ordered list of references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiple: Option<bool>,
+    /// Minimum number of references for multi-valued relation fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<i32>,
+    /// Maximum number of references for multi-valued relation fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<i32>,
+    /// Action applied to relation values when the target entry is deleted
+    /// (default: set-null)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_delete: Option<RelationDeleteAction>,
+    /// Relation field followed by lookup and rollup fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via_field_id: Option<i32>,
+    /// Whether a rollup follows the relation field backwards, over the
+    /// entries that reference this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlinks: Option<bool>,
+    /// Field read from related entries by lookup and rollup fields
+    /// (default: title)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_field_id: Option<i32>,
+    /// Aggregate for rollup fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollup: Option<RollupFunction>,
+    /// Expression computing a formula field, e.g. `{3} / {4}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+    /// Kind of identifier held by identifier fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<IdentifierScheme>,
+    /// Regular expression values must match in full: the format of custom
+    /// identifiers, or a constraint on text, URL, email and phone values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Error shown for values that do not match the pattern
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_message: Option<String>,
+    /// Whether no two entries of the vault may share a value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique: Option<bool>,
+    /// Calling code phone fields assume for numbers written without one,
+    /// e.g. `44`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calling_code: Option<String>,
+    /// ISO 4217 code every amount of a currency field is in (default: any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// JSON Schema values of JSON fields must satisfy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<serde_json::Value>,
+    /// Value filled in on new entries that leave the field empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<DefaultValue>,
+}
+
+impl FieldOptions {
+    /// Whether a relation field accepts multiple references.
+    pub fn is_multiple(&self) -> bool {
+        self.multiple.unwrap_or(false)
+    }
+
+    /// Action applied when an entry referenced by a relation field is deleted.
+    pub fn delete_action(&self) -> RelationDeleteAction {
+        self.on_delete.unwrap_or_default()
+    }
+
+    /// Whether a rollup aggregates over backlinks instead of outgoing
+    /// relations.
+    pub fn is_backlinks(&self) -> bool {
+        self.backlinks.unwrap_or(false)
+    }
+
+    /// Whether values must be unique within the vault.
+    pub fn is_unique(&self) -> bool {
+        self.unique.unwrap_or(false)
+    }
+
+    /// Top of a rating scale (default: 5).
+    pub fn rating_max(&self) -> f64 {
+        self.max.unwrap_or(DEFAULT_RATING_MAX)
+    }
+
+    /// Increment between rating values (default: 1).
+    pub fn rating_step(&self) -> f64 {
+        self.step.unwrap_or(1.0)
+    }
+
+    /// Largest file a file field accepts, in bytes.
+    pub fn file_size_limit(&self) -> u64 {
+        self.max_file_size
+            .map_or(MAX_ATTACHMENT_SIZE, |size| size.min(MAX_ATTACHMENT_SIZE))
+    }
+
+    /// Whether a file field accepts files with the given lowercase extension.
+    pub fn accepts_extension(&self, extension: Option<&str>) -> bool {
+        match &self.allowed_extensions {
+            Some(allowed) if !allowed.is_empty() => {
+                extension.is_some_and(|ext| allowed.iter().any(|a| a == ext))
+            }
+            _ => true,
+        }
+    }
 }
 
+/// Top of a rating scale when none is configured.
+pub const DEFAULT_RATING_MAX: f64 = 5.0;
+
+/// Largest rating scale a field may be configured with.
+pub const MAX_RATING_SCALE: f64 = 100.0;
+
+/// Largest file that can be attached, whatever the field options say (100MB).
+pub const MAX_ATTACHMENT_SIZE: u64 = 100 * 1024 * 1024;
+
 /// DTO for creating a new field definition.
 #[derive(Debug, Deserialize)]
 pub struct CreateFieldDto {
@@ -76,6 +424,9 @@ pub struct CreateFieldDto {
     pub options: Option<FieldOptions>,
     #[serde(default)]
     pub required: bool,
+    /// Whether existing entries get the field's default value
+    #[serde(default)]
+    pub backfill: bool,
 }
 
 /// DTO for updating an existing field definition.
@@ -84,6 +435,57 @@ pub struct UpdateFieldDto {
     pub name: Option<String>,
     pub options: Option<FieldOptions>,
     pub required: Option<bool>,
+    /// Whether existing entries without a value get the field's default
+    /// value
+    #[serde(default)]
+    pub backfill: bool,
+    /// Choices removed from a select or multiselect field, mapped to the
+    /// choice their entries move to. Entries of removed choices left out
+    /// lose the choice.
+    #[serde(default)]
+    pub merge_choices: HashMap<String, String>,
+}
+
+/// DTO for converting a field to another type.
+#[derive(Debug, Deserialize)]
+pub struct ConvertFieldDto {
+    pub field_type: FieldType,
+    /// Options of the converted field; select and multiselect fields
+    /// without choices keep those of a select or multiselect field, and
+    /// otherwise get one per distinct converted value
+    #[serde(default)]
+    pub options: Option<FieldOptions>,
+    /// Whether values that cannot be converted are cleared instead of
+    /// blocking the conversion
+    #[serde(default)]
+    pub discard_failures: bool,
+}
+
+/// Outcome of converting a field's values, before or after applying it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionPreview {
+    pub field_id: i32,
+    pub from: FieldType,
+    pub to: FieldType,
+    /// Values that have a counterpart of the new type
+    pub converted: u64,
+    /// Values that are empty of the new type, such as blank text
+    pub emptied: u64,
+    /// Values that cannot be converted
+    pub failed: u64,
+    /// Choices the converted field will offer, for select and multiselect
+    pub choices: Option<Vec<Choice>>,
+    /// First values that cannot be converted, with the reason
+    pub failures: Vec<ConversionFailure>,
+}
+
+/// Entry value that cannot be converted to a field's new type.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionFailure {
+    pub entry_id: i32,
+    pub title: String,
+    pub value: serde_json::Value,
+    pub error: String,
 }
 
 /// Response DTO for field definition data.
@@ -121,3 +523,104 @@ impl From<crate::entities::field_definition::Model> for FieldDefinitionDto {
         }
     }
 }
+
+/// Kind of a vault validation rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldRuleKind {
+    /// The field is required while the condition on the other field holds
+    RequiredIf,
+    /// The field applies, and is shown, only while the condition on the
+    /// other field holds
+    VisibleIf,
+    /// The field's value must compare to the other field's as the operator
+    /// says
+    Compare,
+}
+
+impl FieldRuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldRuleKind::RequiredIf => "required-if",
+            FieldRuleKind::VisibleIf => "visible-if",
+            FieldRuleKind::Compare => "compare",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "required-if" => Some(FieldRuleKind::RequiredIf),
+            "visible-if" => Some(FieldRuleKind::VisibleIf),
+            "compare" => Some(FieldRuleKind::Compare),
+            _ => None,
+        }
+    }
+}
+
+/// Operator of a vault validation rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleOperator {
+    Equals,
+    NotEquals,
+    /// Conditions only: the other field has a value
+    IsSet,
+    /// Conditions only: the other field is empty
+    IsNotSet,
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+}
+
+impl RuleOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleOperator::Equals => "equals",
+            RuleOperator::NotEquals => "not-equals",
+            RuleOperator::IsSet => "is-set",
+            RuleOperator::IsNotSet => "is-not-set",
+            RuleOperator::LessThan => "less-than",
+            RuleOperator::LessOrEqual => "less-or-equal",
+            RuleOperator::GreaterThan => "greater-than",
+            RuleOperator::GreaterOrEqual => "greater-or-equal",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "equals" => Some(RuleOperator::Equals),
+            "not-equals" => Some(RuleOperator::NotEquals),
+            "is-set" => Some(RuleOperator::IsSet),
+            "is-not-set" => Some(RuleOperator::IsNotSet),
+            "less-than" => Some(RuleOperator::LessThan),
+            "less-or-equal" => Some(RuleOperator::LessOrEqual),
+            "greater-than" => Some(RuleOperator::GreaterThan),
+            "greater-or-equal" => Some(RuleOperator::GreaterOrEqual),
+            _ => None,
+        }
+    }
+}
+
+/// Vault validation rule on a field, involving another field of the vault.
+///
+/// Required-if and visible-if rules hold a condition testing the other
+/// field with the operator, against `value` unless the operator is
+/// `is-set` or `is-not-set`. Compare rules test the field's value against
+/// the other field's.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldRule {
+    /// Field the rule applies to
+    pub field_id: i32,
+    pub kind: FieldRuleKind,
+    /// Field the condition tests, or the field compared with
+    pub other_field_id: i32,
+    pub operator: RuleOperator,
+    /// Value the condition tests the other field against, stored like a
+    /// value of that field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Error shown instead of the generated one when the rule fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
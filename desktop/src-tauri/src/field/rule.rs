@@ -0,0 +1,342 @@
+//! Vault validation rules between fields.
+//!
+//! Required-if and visible-if rules make a field depend on a condition on
+//! another field, like a finished date that only applies once the status
+//! is Finished. Compare rules order two values, like an end date that
+//! cannot come before the start date. Rules are evaluated with the rest of
+//! entry validation, see `MetadataService::validate_metadata`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::entry::MetadataService;
+
+use super::{FieldDefinitionDto, FieldRule, FieldRuleKind, FieldType, RuleOperator};
+
+/// Longest custom message a rule may show.
+const MAX_RULE_MESSAGE_LENGTH: usize = 200;
+
+/// Field types whose values order against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scale {
+    Number,
+    Duration,
+    Time,
+}
+
+fn scale(field_type: FieldType) -> Option<Scale> {
+    match field_type {
+        FieldType::Number | FieldType::Rating => Some(Scale::Number),
+        FieldType::Duration => Some(Scale::Duration),
+        FieldType::Date | FieldType::PartialDate | FieldType::Datetime => Some(Scale::Time),
+        _ => None,
+    }
+}
+
+/// Orders two stored values of fields of the given types; `None` when the
+/// types or the values do not order.
+///
+/// Dates, partial dates and UTC timestamps order as text, compared at the
+/// precision of the coarser one, so `2024` equals `2024-05-01`.
+pub fn compare_values(
+    a_type: FieldType,
+    a: &Value,
+    b_type: FieldType,
+    b: &Value,
+) -> Option<Ordering> {
+    let a_scale = scale(a_type)?;
+    if scale(b_type)? != a_scale {
+        return None;
+    }
+    match a_scale {
+        Scale::Number | Scale::Duration => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        Scale::Time => {
+            let (a, b) = (a.as_str()?, b.as_str()?);
+            let length = a.len().min(b.len());
+            Some(a.get(..length)?.cmp(b.get(..length)?))
+        }
+    }
+}
+
+impl RuleOperator {
+    /// Whether the operator orders values rather than testing equality or
+    /// presence.
+    pub fn is_ordering(&self) -> bool {
+        matches!(
+            self,
+            RuleOperator::LessThan
+                | RuleOperator::LessOrEqual
+                | RuleOperator::GreaterThan
+                | RuleOperator::GreaterOrEqual
+        )
+    }
+
+    /// Whether values ordered as given pass the operator.
+    pub fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            RuleOperator::Equals => ordering == Ordering::Equal,
+            RuleOperator::NotEquals => ordering != Ordering::Equal,
+            RuleOperator::LessThan => ordering == Ordering::Less,
+            RuleOperator::LessOrEqual => ordering != Ordering::Greater,
+            RuleOperator::GreaterThan => ordering == Ordering::Greater,
+            RuleOperator::GreaterOrEqual => ordering != Ordering::Less,
+            RuleOperator::IsSet | RuleOperator::IsNotSet => true,
+        }
+    }
+
+    /// The operator in words, with dates read as points in time.
+    pub fn describe(&self, field_type: FieldType) -> &'static str {
+        let time = scale(field_type) == Some(Scale::Time);
+        match self {
+            RuleOperator::Equals if time => "on",
+            RuleOperator::Equals => "equal to",
+            RuleOperator::NotEquals => "different from",
+            RuleOperator::IsSet => "set",
+            RuleOperator::IsNotSet => "empty",
+            RuleOperator::LessThan if time => "before",
+            RuleOperator::LessThan => "less than",
+            RuleOperator::LessOrEqual if time => "on or before",
+            RuleOperator::LessOrEqual => "at most",
+            RuleOperator::GreaterThan if time => "after",
+            RuleOperator::GreaterThan => "greater than",
+            RuleOperator::GreaterOrEqual if time => "on or after",
+            RuleOperator::GreaterOrEqual => "at least",
+        }
+    }
+}
+
+/// Checks a rule against the fields of its vault and returns it as
+/// stored: the condition value normalized like a value of the tested
+/// field, so choices are given by ID, and the message trimmed.
+pub fn check_rule(
+    rule: &FieldRule,
+    fields: &HashMap<i32, FieldDefinitionDto>,
+) -> Result<FieldRule, String> {
+    let field = fields
+        .get(&rule.field_id)
+        .ok_or_else(|| format!("Field {} is not in this vault", rule.field_id))?;
+    let other = fields
+        .get(&rule.other_field_id)
+        .ok_or_else(|| format!("Field {} is not in this vault", rule.other_field_id))?;
+    if field.id == other.id {
+        return Err(format!(
+            "A rule on '{}' cannot involve the field itself",
+            field.name
+        ));
+    }
+
+    let mut checked = rule.clone();
+    match rule.kind {
+        FieldRuleKind::Compare => {
+            if matches!(rule.operator, RuleOperator::IsSet | RuleOperator::IsNotSet) {
+                return Err("Comparisons must test equality or order".to_string());
+            }
+            if rule.value.is_some() {
+                return Err("Comparisons do not take a value".to_string());
+            }
+            if field.field_type.is_derived() || other.field_type.is_derived() {
+                return Err("Computed fields cannot be compared".to_string());
+            }
+            if scale(field.field_type).is_none()
+                || scale(field.field_type) != scale(other.field_type)
+            {
+                return Err(format!(
+                    "'{}' ({}) cannot be compared with '{}' ({})",
+                    field.name,
+                    field.field_type.as_str(),
+                    other.name,
+                    other.field_type.as_str()
+                ));
+            }
+        }
+        FieldRuleKind::RequiredIf | FieldRuleKind::VisibleIf => {
+            if rule.kind == FieldRuleKind::RequiredIf && field.field_type.is_derived() {
+                return Err("Computed fields cannot be required".to_string());
+            }
+            if other.field_type.is_derived() {
+                return Err("Conditions cannot test computed fields".to_string());
+            }
+            checked.value = check_condition_value(other, rule.operator, rule.value.as_ref())?;
+        }
+    }
+
+    checked.message = match rule.message.as_deref().map(str::trim) {
+        Some("") | None => None,
+        Some(message) if message.chars().count() > MAX_RULE_MESSAGE_LENGTH => {
+            return Err(format!(
+                "Rule message must be at most {} characters",
+                MAX_RULE_MESSAGE_LENGTH
+            ));
+        }
+        Some(message) => Some(message.to_string()),
+    };
+
+    Ok(checked)
+}
+
+/// Checks the value a condition tests a field against, and normalizes it.
+/// Multiselect fields are tested against one of their choices.
+fn check_condition_value(
+    field: &FieldDefinitionDto,
+    operator: RuleOperator,
+    value: Option<&Value>,
+) -> Result<Option<Value>, String> {
+    if matches!(operator, RuleOperator::IsSet | RuleOperator::IsNotSet) {
+        return match value {
+            Some(_) => Err(format!("'{}' conditions take no value", operator.as_str())),
+            None => Ok(None),
+        };
+    }
+    let value = value
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| format!("The condition on '{}' needs a value", field.name))?;
+
+    if operator.is_ordering() && scale(field.field_type).is_none() {
+        return Err(format!(
+            "{} fields cannot be tested for order",
+            field.field_type.as_str()
+        ));
+    }
+    if matches!(
+        field.field_type,
+        FieldType::File | FieldType::Location | FieldType::Relation | FieldType::Json
+    ) {
+        return Err(format!(
+            "{} fields can only be tested for being set",
+            field.field_type.as_str()
+        ));
+    }
+
+    let mut tested = field.clone();
+    if tested.field_type == FieldType::Multiselect {
+        tested.field_type = FieldType::Select;
+    }
+    MetadataService::normalize_field_value(&tested, value)
+        .and_then(|v| MetadataService::validate_field_value(&tested, &v).map(|_| v))
+        .map(Some)
+        .map_err(|e| format!("Invalid condition value: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Choice, FieldOptions};
+    use serde_json::json;
+
+    fn field(id: i32, name: &str, field_type: FieldType) -> FieldDefinitionDto {
+        FieldDefinitionDto {
+            id,
+            vault_id: 1,
+            name: name.to_string(),
+            field_type,
+            options: None,
+            position: id,
+            required: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn rule(kind: FieldRuleKind, operator: RuleOperator, value: Option<Value>) -> FieldRule {
+        FieldRule {
+            field_id: 1,
+            kind,
+            other_field_id: 2,
+            operator,
+            value,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_values() {
+        use FieldType::*;
+        assert_eq!(
+            compare_values(Number, &json!(3), Rating, &json!(2.5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare_values(
+                Date,
+                &json!("2024-05-01"),
+                Datetime,
+                &json!("2024-05-01T22:00:00Z")
+            ),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_values(PartialDate, &json!("2023"), Date, &json!("2024-01-01")),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare_values(Number, &json!(3), Duration, &json!(3)), None);
+        assert_eq!(compare_values(Text, &json!("a"), Text, &json!("b")), None);
+    }
+
+    #[test]
+    fn test_check_rule() {
+        let status = FieldDefinitionDto {
+            options: Some(FieldOptions {
+                choices: Some(vec![Choice {
+                    id: "c1".to_string(),
+                    label: "Finished".to_string(),
+                    color: None,
+                }]),
+                ..Default::default()
+            }),
+            ..field(2, "Status", FieldType::Select)
+        };
+        let fields: HashMap<i32, FieldDefinitionDto> = [
+            (1, field(1, "Finished", FieldType::Date)),
+            (2, status),
+            (3, field(3, "Started", FieldType::Datetime)),
+            (4, field(4, "Pages", FieldType::Number)),
+        ]
+        .into_iter()
+        .collect();
+
+        // Conditions on choices are stored by ID
+        let required = rule(
+            FieldRuleKind::RequiredIf,
+            RuleOperator::Equals,
+            Some(json!("finished")),
+        );
+        assert_eq!(
+            check_rule(&required, &fields).unwrap().value,
+            Some(json!("c1"))
+        );
+        let unknown = rule(
+            FieldRuleKind::VisibleIf,
+            RuleOperator::Equals,
+            Some(json!("Reading")),
+        );
+        assert!(check_rule(&unknown, &fields).is_err());
+        let ordered = rule(
+            FieldRuleKind::VisibleIf,
+            RuleOperator::LessThan,
+            Some(json!("c1")),
+        );
+        assert!(check_rule(&ordered, &fields).is_err());
+        let set = rule(
+            FieldRuleKind::VisibleIf,
+            RuleOperator::IsSet,
+            Some(json!("c1")),
+        );
+        assert!(check_rule(&set, &fields).is_err());
+
+        let compare = |other_field_id| FieldRule {
+            other_field_id,
+            message: Some("  Finished before it started ".to_string()),
+            ..rule(FieldRuleKind::Compare, RuleOperator::GreaterOrEqual, None)
+        };
+        let checked = check_rule(&compare(3), &fields).unwrap();
+        assert_eq!(
+            checked.message.as_deref(),
+            Some("Finished before it started")
+        );
+        assert!(check_rule(&compare(4), &fields).is_err());
+        assert!(check_rule(&compare(1), &fields).is_err());
+        assert!(check_rule(&compare(9), &fields).is_err());
+    }
+}
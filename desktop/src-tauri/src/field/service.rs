@@ -1,14 +1,44 @@
 //! Field definition service for business logic.
 
+use std::collections::{HashMap, HashSet};
+
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, Statement, TransactionTrait,
 };
+use serde_json::{Map, Value};
 
+use crate::attachment::{AttachmentRegistry, AttachmentStorage};
 use crate::core::{AppError, AppResult, now_formatted, find_vault_or_error};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::entry_location::{self, Entity as EntryLocation};
 use crate::entities::field_definition::{self, ActiveModel, Entity as FieldDefinition};
+use crate::entities::field_rule::{self, Entity as FieldRuleEntity};
 use crate::entities::vault::Entity as Vault;
+use crate::entry::{EntryConstraintService, EntryDefaultService, MetadataService};
+use crate::location::LocationIndexService;
+
+use super::{
+    Choice, ConversionFailure, ConversionPreview, ConvertFieldDto, CreateFieldDto, DefaultValue,
+    FieldDefinitionDto, FieldOptions, FieldRule, FieldRuleKind, FieldType, MAX_ATTACHMENT_SIZE,
+    MAX_RATING_SCALE, RollupFunction, RuleOperator, UpdateFieldDto, can_be_unique,
+    check_constraint_options, check_conversion, check_formula, check_identifier_options,
+    check_json_schema, check_rule, choice_labels, convert_value, currency_minor_units,
+    match_choice, merge_choices, prepare_choices,
+};
 
-use super::{CreateFieldDto, FieldDefinitionDto, FieldOptions, FieldType, UpdateFieldDto};
+/// Most failing values listed in a conversion preview.
+const MAX_CONVERSION_FAILURES: usize = 20;
+
+/// Values of a field converted to a new type, ready to be written.
+struct ConversionPlan {
+    /// The field as it will be after the conversion
+    target: FieldDefinitionDto,
+    /// Entries holding a value, with the value they will hold. Values that
+    /// are empty or cannot be converted are `None`.
+    values: Vec<(entry::Model, Option<Value>)>,
+    preview: ConversionPreview,
+}
 
 /// Service for field definition CRUD operations.
 pub struct FieldService;
@@ -17,13 +47,18 @@ impl FieldService {
     /// Creates a new field definition for a vault.
     pub async fn create(
         conn: &DatabaseConnection,
-        dto: CreateFieldDto,
+        mut dto: CreateFieldDto,
     ) -> AppResult<FieldDefinitionDto> {
         // Validate name is not empty
         if dto.name.trim().is_empty() {
             return Err(AppError::Validation("Field name is required".to_string()));
         }
 
+        // Validate choices and give them an ID
+        if let Some(options) = dto.options.as_mut() {
+            Self::prepare_choice_options(dto.field_type, options, &[])?;
+        }
+
         // Verify vault exists
         find_vault_or_error(conn, dto.vault_id).await?;
 
@@ -32,6 +67,59 @@ impl FieldService {
             Self::validate_relation_options(conn, dto.options.as_ref()).await?;
         }
 
+        // Validate rating scale
+        if dto.field_type == FieldType::Rating {
+            Self::validate_rating_options(dto.options.as_ref())?;
+        }
+
+        // Validate attachment limits
+        if dto.field_type == FieldType::File {
+            Self::validate_file_options(dto.options.as_ref())?;
+        }
+
+        // Validate calling codes, currency codes and JSON schemas
+        Self::validate_format_options(dto.field_type, dto.options.as_ref())?;
+
+        // Validate identifier scheme
+        if dto.field_type == FieldType::Identifier {
+            Self::validate_identifier_options(None, dto.options.as_ref())?;
+        }
+
+        // Validate unique, pattern and minimum length constraints
+        Self::validate_constraint_options(conn, None, dto.field_type, dto.options.as_ref()).await?;
+
+        // Validate the default value
+        Self::validate_default_options(
+            conn,
+            dto.vault_id,
+            None,
+            dto.name.trim(),
+            dto.field_type,
+            dto.options.as_ref(),
+        )
+        .await?;
+
+        // Validate lookup, rollup and formula field options
+        if dto.field_type.is_derived() {
+            if dto.required {
+                return Err(AppError::Validation(
+                    "Computed fields cannot be required".to_string(),
+                ));
+            }
+            if dto.field_type == FieldType::Formula {
+                Self::validate_formula_options(conn, dto.vault_id, None, dto.options.as_ref())
+                    .await?;
+            } else {
+                Self::validate_derived_options(
+                    conn,
+                    dto.vault_id,
+                    dto.field_type,
+                    dto.options.as_ref(),
+                )
+                .await?;
+            }
+        }
+
         // Check for duplicate field name in vault
         let existing = FieldDefinition::find()
             .filter(field_definition::Column::VaultId.eq(dto.vault_id))
@@ -73,7 +161,15 @@ impl FieldService {
             ..Default::default()
         };
 
-        let result = active_model.insert(conn).await?;
+        let txn = conn.begin().await?;
+        let result = active_model.insert(&txn).await?;
+        if dto.backfill {
+            EntryDefaultService::backfill(&txn, result.id).await?;
+        }
+        EntryConstraintService::sync_unique_index(&txn, &FieldDefinitionDto::from(result.clone()))
+            .await?;
+        txn.commit().await?;
+
         log::info!(
             "Created field definition: {} (id={}) for vault {}",
             result.name,
@@ -112,6 +208,10 @@ impl FieldService {
     }
 
     /// Updates an existing field definition.
+    ///
+    /// Entries store choice IDs, so renamed, recolored and reordered
+    /// choices carry over to them as is. Entries of removed choices move
+    /// to the choice `merge_choices` names, or lose the choice.
     pub async fn update(
         conn: &DatabaseConnection,
         id: i32,
@@ -151,18 +251,82 @@ impl FieldService {
             active_model.name = Set(name.trim().to_string());
         }
 
-        if let Some(options) = dto.options {
+        let mut removed_choices = HashMap::new();
+        if let Some(mut options) = dto.options {
+            if let Some(field_type) = FieldType::from_str(&field.field_type) {
+                let current = FieldDefinitionDto::from(field.clone())
+                    .options
+                    .and_then(|o| o.choices)
+                    .unwrap_or_default();
+                Self::prepare_choice_options(field_type, &mut options, &current)?;
+                removed_choices = Self::removed_choices(&field, &mut options, &dto.merge_choices)?;
+            }
+            if field.field_type == FieldType::Relation.as_str() {
+                Self::validate_relation_options(conn, Some(&options)).await?;
+            }
+            if field.field_type == FieldType::Rating.as_str() {
+                Self::validate_rating_options(Some(&options))?;
+            }
+            if field.field_type == FieldType::File.as_str() {
+                Self::validate_file_options(Some(&options))?;
+            }
+            if let Some(field_type) = FieldType::from_str(&field.field_type) {
+                Self::validate_format_options(field_type, Some(&options))?;
+            }
+            if field.field_type == FieldType::Identifier.as_str() {
+                Self::validate_identifier_options(Some(&field), Some(&options))?;
+            }
+            if let Some(field_type) = FieldType::from_str(&field.field_type) {
+                Self::validate_constraint_options(conn, Some(&field), field_type, Some(&options))
+                    .await?;
+                Self::validate_default_options(
+                    conn,
+                    field.vault_id,
+                    Some(field.id),
+                    &field.name,
+                    field_type,
+                    Some(&options),
+                )
+                .await?;
+            }
+            if field.field_type == FieldType::Formula.as_str() {
+                Self::validate_formula_options(conn, field.vault_id, Some(field.id), Some(&options))
+                    .await?;
+            } else if let Some(field_type) =
+                FieldType::from_str(&field.field_type).filter(|t| t.is_derived())
+            {
+                Self::validate_derived_options(conn, field.vault_id, field_type, Some(&options))
+                    .await?;
+            }
+
             let options_json = serde_json::to_string(&options).unwrap_or_default();
             active_model.options = Set(Some(options_json));
         }
 
         if let Some(required) = dto.required {
+            let derived = FieldType::from_str(&field.field_type).is_some_and(|t| t.is_derived());
+            if required && derived {
+                return Err(AppError::Validation(
+                    "Computed fields cannot be required".to_string(),
+                ));
+            }
             active_model.required = Set(if required { 1 } else { 0 });
         }
 
         active_model.updated_at = Set(now);
 
-        let result = active_model.update(conn).await?;
+        let txn = conn.begin().await?;
+        let result = active_model.update(&txn).await?;
+        if !removed_choices.is_empty() {
+            Self::remove_choices(&txn, &result, &removed_choices).await?;
+        }
+        if dto.backfill {
+            EntryDefaultService::backfill(&txn, result.id).await?;
+        }
+        EntryConstraintService::sync_unique_index(&txn, &FieldDefinitionDto::from(result.clone()))
+            .await?;
+        txn.commit().await?;
+
         log::info!(
             "Updated field definition: {} (id={})",
             result.name,
@@ -172,8 +336,378 @@ impl FieldService {
         Ok(FieldDefinitionDto::from(result))
     }
 
+    /// Previews converting a field to another type.
+    ///
+    /// Reports how many entry values convert, become empty or cannot be
+    /// converted, and the choices a select or multiselect field would get.
+    /// Nothing is written.
+    pub async fn preview_conversion(
+        conn: &DatabaseConnection,
+        id: i32,
+        dto: &ConvertFieldDto,
+    ) -> AppResult<ConversionPreview> {
+        let field = FieldDefinition::find_by_id(id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::FieldNotFound(id))?;
+
+        Ok(Self::plan_conversion(conn, &field, dto).await?.preview)
+    }
+
+    /// Converts a field to another type, rewriting the value of every
+    /// entry in one transaction.
+    ///
+    /// Values that cannot be converted block the conversion, unless
+    /// `discard_failures` is set, in which case they are cleared.
+    pub async fn convert(
+        conn: &DatabaseConnection,
+        id: i32,
+        dto: ConvertFieldDto,
+    ) -> AppResult<FieldDefinitionDto> {
+        let field = FieldDefinition::find_by_id(id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::FieldNotFound(id))?;
+
+        let plan = Self::plan_conversion(conn, &field, &dto).await?;
+        let ConversionPreview { from, to, failed, .. } = plan.preview;
+        if failed > 0 && !dto.discard_failures {
+            let first = &plan.preview.failures[0];
+            return Err(AppError::Validation(format!(
+                "{} value(s) of field '{}' cannot be converted to {}, e.g. on '{}': {}",
+                failed,
+                field.name,
+                to.as_str(),
+                first.title,
+                first.error
+            )));
+        }
+
+        let txn = conn.begin().await?;
+
+        let mut active_model: ActiveModel = field.clone().into();
+        active_model.field_type = Set(to.as_str().to_string());
+        active_model.options = Set(plan
+            .target
+            .options
+            .as_ref()
+            .map(|o| serde_json::to_string(o).unwrap_or_default()));
+        active_model.updated_at = Set(now_formatted());
+        let result = active_model.update(&txn).await?;
+
+        // Locations of a former location field leave the spatial index
+        if from == FieldType::Location {
+            EntryLocation::delete_many()
+                .filter(entry_location::Column::FieldId.eq(id))
+                .exec(&txn)
+                .await?;
+        }
+        let reindex_search = from == FieldType::Markdown || to == FieldType::Markdown;
+
+        let key = id.to_string();
+        for (entry, value) in plan.values {
+            let mut metadata: Map<String, Value> = entry
+                .metadata
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default();
+            match value {
+                Some(value) => metadata.insert(key.clone(), value),
+                None => metadata.remove(&key),
+            };
+            let metadata = Value::Object(metadata).to_string();
+
+            let mut entry_model: entry::ActiveModel = entry.clone().into();
+            if reindex_search {
+                entry_model.search_text = Set(MetadataService::search_text(
+                    &txn,
+                    entry.vault_id,
+                    Some(&metadata),
+                )
+                .await?);
+            }
+            entry_model.metadata = Set(Some(metadata.clone()));
+            entry_model.update(&txn).await?;
+
+            if to == FieldType::Location {
+                LocationIndexService::sync_entry(&txn, entry.id, entry.vault_id, Some(&metadata))
+                    .await?;
+            }
+        }
+        EntryConstraintService::sync_unique_index(&txn, &FieldDefinitionDto::from(result.clone()))
+            .await?;
+
+        txn.commit().await?;
+
+        log::info!(
+            "Converted field {} (id={}) from {} to {}",
+            result.name,
+            result.id,
+            from.as_str(),
+            to.as_str()
+        );
+
+        Ok(FieldDefinitionDto::from(result))
+    }
+
+    /// Converts the values of a field without writing them, and checks
+    /// the new type against the options and the fields that read it.
+    async fn plan_conversion(
+        conn: &DatabaseConnection,
+        field: &field_definition::Model,
+        dto: &ConvertFieldDto,
+    ) -> AppResult<ConversionPlan> {
+        let current = FieldDefinitionDto::from(field.clone());
+        let (from, to) = (current.field_type, dto.field_type);
+        check_conversion(from, to).map_err(AppError::Validation)?;
+
+        let mut options = dto.options.clone().unwrap_or_default();
+        if to == FieldType::Rating {
+            Self::validate_rating_options(Some(&options))?;
+        }
+        if to == FieldType::Identifier {
+            check_identifier_options(Some(&options)).map_err(AppError::Validation)?;
+        }
+        Self::validate_format_options(to, Some(&options))?;
+        check_constraint_options(to, Some(&options)).map_err(AppError::Validation)?;
+        Self::validate_default_options(
+            conn,
+            field.vault_id,
+            Some(field.id),
+            &field.name,
+            to,
+            Some(&options),
+        )
+        .await?;
+        Self::check_field_readers(conn, &current, to).await?;
+
+        let entries: Vec<(entry::Model, Value)> = Entry::find()
+            .filter(entry::Column::VaultId.eq(field.vault_id))
+            .order_by_asc(entry::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .filter_map(|entry| {
+                let value = MetadataService::parse_metadata(entry.metadata.as_deref())
+                    .remove(&field.id)
+                    .filter(|v| !v.is_null())?;
+                Some((entry, value))
+            })
+            .collect();
+
+        // Choices convert by label
+        let raw: Vec<Result<Option<Value>, String>> = entries
+            .iter()
+            .map(|(_, value)| convert_value(&choice_labels(&current, value), from, to))
+            .collect();
+
+        // Select and multiselect fields converted to each other keep their
+        // choices; others get one per distinct value when none are given
+        let has_choices = matches!(to, FieldType::Select | FieldType::Multiselect);
+        let had_choices = matches!(from, FieldType::Select | FieldType::Multiselect);
+        if has_choices && options.choices.as_ref().is_none_or(|c| c.is_empty()) {
+            let current_choices = current.options.as_ref().and_then(|o| o.choices.clone());
+            options.choices = match current_choices {
+                Some(choices) if had_choices => Some(choices),
+                _ => {
+                    let mut choices: Vec<Choice> = Vec::new();
+                    for value in raw.iter().flatten().flatten() {
+                        let items = match value {
+                            Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+                            other => other.as_str().into_iter().collect::<Vec<_>>(),
+                        };
+                        for item in items {
+                            if match_choice(item, &choices).is_none() {
+                                choices.push(Choice {
+                                    id: String::new(),
+                                    label: item.to_string(),
+                                    color: None,
+                                });
+                            }
+                        }
+                    }
+                    choices.sort_by_key(|c| c.label.to_lowercase());
+                    Some(choices)
+                }
+            };
+        }
+        Self::prepare_choice_options(to, &mut options, &[])?;
+        let choices = options.choices.clone().unwrap_or_default();
+
+        let target = FieldDefinitionDto {
+            field_type: to,
+            options: (dto.options.is_some() || has_choices).then_some(options),
+            ..current
+        };
+        let unique = can_be_unique(to) && target.options.as_ref().is_some_and(|o| o.is_unique());
+
+        let mut preview = ConversionPreview {
+            field_id: field.id,
+            from,
+            to,
+            converted: 0,
+            emptied: 0,
+            failed: 0,
+            choices: has_choices.then(|| choices.clone()),
+            failures: Vec::new(),
+        };
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut values = Vec::with_capacity(entries.len());
+
+        for ((entry, original), raw) in entries.into_iter().zip(raw) {
+            let converted = raw.and_then(|value| {
+                let Some(value) = value else {
+                    return Ok(None);
+                };
+                let value = match value {
+                    Value::String(text) if has_choices => {
+                        Value::String(Self::fit_choice(&text, &choices)?)
+                    }
+                    Value::Array(items) if has_choices => Value::Array(
+                        items
+                            .iter()
+                            .map(|i| Self::fit_choice(i.as_str().unwrap_or_default(), &choices))
+                            .collect::<Result<Vec<String>, String>>()?
+                            .into_iter()
+                            .map(Value::String)
+                            .collect(),
+                    ),
+                    other => other,
+                };
+                let value = MetadataService::normalize_field_value(&target, &value)?;
+                MetadataService::validate_field_value(&target, &value)?;
+                if unique && !seen.insert(value.to_string()) {
+                    return Err(format!("{} is already used by another entry", value));
+                }
+                Ok(Some(value))
+            });
+
+            match converted {
+                Ok(Some(value)) => {
+                    preview.converted += 1;
+                    values.push((entry, Some(value)));
+                }
+                Ok(None) => {
+                    preview.emptied += 1;
+                    values.push((entry, None));
+                }
+                Err(error) => {
+                    preview.failed += 1;
+                    if preview.failures.len() < MAX_CONVERSION_FAILURES {
+                        preview.failures.push(ConversionFailure {
+                            entry_id: entry.id,
+                            title: entry.title.clone(),
+                            value: original,
+                            error,
+                        });
+                    }
+                    values.push((entry, None));
+                }
+            }
+        }
+
+        log::debug!(
+            "Planned conversion of field {} to {}: {} converted, {} emptied, {} failed",
+            field.id,
+            to.as_str(),
+            preview.converted,
+            preview.emptied,
+            preview.failed
+        );
+
+        Ok(ConversionPlan {
+            target,
+            values,
+            preview,
+        })
+    }
+
+    /// ID of the target field's choice a converted value matches.
+    fn fit_choice(value: &str, choices: &[Choice]) -> Result<String, String> {
+        match_choice(value, choices)
+            .map(|choice| choice.id.clone())
+            .ok_or_else(|| format!("'{}' is not one of the choices", value))
+    }
+
+    /// Ensures the formulas and rollups reading a field still type-check
+    /// once it has a new type.
+    async fn check_field_readers(
+        conn: &DatabaseConnection,
+        field: &FieldDefinitionDto,
+        to: FieldType,
+    ) -> AppResult<()> {
+        let mut fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.eq(field.vault_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+        if let Some(converted) = fields.get_mut(&field.id) {
+            converted.field_type = to;
+        }
+
+        for formula in fields.values().filter(|f| f.field_type == FieldType::Formula) {
+            let Some(source) = formula.options.as_ref().and_then(|o| o.formula.as_deref()) else {
+                continue;
+            };
+            check_formula(source, Some(formula.id), &fields).map_err(|e| {
+                AppError::Validation(format!(
+                    "Formula field '{}' would no longer be valid: {}",
+                    formula.name, e
+                ))
+            })?;
+        }
+
+        for rule in Self::vault_rules(conn, field.vault_id).await? {
+            if rule.field_id != field.id && rule.other_field_id != field.id {
+                continue;
+            }
+            check_rule(&rule, &fields).map_err(|e| {
+                AppError::Validation(format!(
+                    "A validation rule on this field would no longer be valid: {}",
+                    e
+                ))
+            })?;
+        }
+
+        // Rollups may live in any vault related to this one
+        if !to.is_numeric() {
+            let rollups = FieldDefinition::find()
+                .filter(field_definition::Column::FieldType.eq(FieldType::Rollup.as_str()))
+                .all(conn)
+                .await?
+                .into_iter()
+                .map(FieldDefinitionDto::from);
+            for rollup in rollups {
+                let Some(options) = rollup.options.as_ref() else {
+                    continue;
+                };
+                let sums = matches!(
+                    options.rollup,
+                    Some(RollupFunction::Sum | RollupFunction::Average)
+                );
+                if sums && options.source_field_id == Some(field.id) {
+                    return Err(AppError::Validation(format!(
+                        "Rollup field '{}' sums this field, which must stay numeric",
+                        rollup.name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Deletes a field definition.
-    pub async fn delete(conn: &DatabaseConnection, id: i32) -> AppResult<()> {
+    ///
+    /// The values of a file field are removed from its entries, and their
+    /// attachments released when storage is provided.
+    pub async fn delete(
+        conn: &DatabaseConnection,
+        id: i32,
+        attachment_storage: Option<&AttachmentStorage>,
+    ) -> AppResult<()> {
         let field = FieldDefinition::find_by_id(id)
             .one(conn)
             .await?
@@ -185,11 +719,60 @@ impl FieldService {
             field.id
         );
 
-        FieldDefinition::delete_by_id(id).exec(conn).await?;
+        let txn = conn.begin().await?;
+        // Left as orphan data, attachments would stay referenced
+        let removed = if field.field_type == FieldType::File.as_str() {
+            Self::remove_values(&txn, &field).await?
+        } else {
+            Vec::new()
+        };
+        let unreferenced = AttachmentRegistry::release_values(&txn, &removed).await?;
+        FieldDefinition::delete_by_id(id).exec(&txn).await?;
+        EntryConstraintService::drop_unique_index(&txn, id).await?;
+        txn.commit().await?;
+
+        if let Some(storage) = attachment_storage {
+            AttachmentRegistry::delete_files(storage, &unreferenced);
+        }
 
         Ok(())
     }
 
+    /// Removes a field's values from the entries of its vault.
+    ///
+    /// Returns the removed values.
+    async fn remove_values<C: ConnectionTrait>(
+        conn: &C,
+        field: &field_definition::Model,
+    ) -> AppResult<Vec<Value>> {
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.eq(field.vault_id))
+            .all(conn)
+            .await?;
+
+        let key = field.id.to_string();
+        let mut removed = Vec::new();
+        for entry in entries {
+            let Some(mut metadata) = entry
+                .metadata
+                .as_deref()
+                .and_then(|json| serde_json::from_str::<Map<String, Value>>(json).ok())
+            else {
+                continue;
+            };
+            let Some(value) = metadata.remove(&key) else {
+                continue;
+            };
+
+            let mut entry_model: entry::ActiveModel = entry.into();
+            entry_model.metadata = Set(Some(Value::Object(metadata).to_string()));
+            entry_model.update(conn).await?;
+            removed.push(value);
+        }
+
+        Ok(removed)
+    }
+
     /// Reorders field definitions for a vault.
     pub async fn reorder(conn: &DatabaseConnection, vault_id: i32, ids: Vec<i32>) -> AppResult<()> {
         // Verify vault exists
@@ -221,152 +804,1588 @@ impl FieldService {
         Ok(())
     }
 
-    /// Validates relation field options.
-    /// Ensures target_vault_id is provided and the target vault exists.
-    async fn validate_relation_options(
-        conn: &DatabaseConnection,
-        options: Option<&FieldOptions>,
-    ) -> AppResult<()> {
-        let options = options.ok_or_else(|| {
-            AppError::Validation("Relation field requires options with targetVaultId".to_string())
-        })?;
+    /// Lists the validation rules of a vault, in the order they were set.
+    pub async fn list_rules(conn: &DatabaseConnection, vault_id: i32) -> AppResult<Vec<FieldRule>> {
+        find_vault_or_error(conn, vault_id).await?;
+        Self::vault_rules(conn, vault_id).await
+    }
 
-        let target_vault_id = options.target_vault_id.ok_or_else(|| {
-            AppError::Validation("Relation field requires targetVaultId in options".to_string())
-        })?;
+    /// Replaces the validation rules of a vault.
+    ///
+    /// Both fields of each rule must belong to the vault; see `check_rule`
+    /// for what each kind of rule accepts. Returns the rules as stored,
+    /// with condition values normalized.
+    pub async fn set_rules(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        rules: Vec<FieldRule>,
+    ) -> AppResult<Vec<FieldRule>> {
+        find_vault_or_error(conn, vault_id).await?;
 
-        // Verify target vault exists
-        Vault::find_by_id(target_vault_id)
-            .one(conn)
+        let fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.eq(vault_id))
+            .all(conn)
             .await?
-            .ok_or_else(|| {
-                AppError::Validation(format!("Target vault {} does not exist", target_vault_id))
-            })?;
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+        let rules = rules
+            .iter()
+            .map(|rule| check_rule(rule, &fields))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::Validation)?;
+
+        let txn = conn.begin().await?;
+        FieldRuleEntity::delete_many()
+            .filter(field_rule::Column::VaultId.eq(vault_id))
+            .exec(&txn)
+            .await?;
 
-        Ok(())
-    }
-}
+        let now = now_formatted();
+        for (position, rule) in rules.iter().enumerate() {
+            field_rule::ActiveModel {
+                vault_id: Set(vault_id),
+                field_id: Set(rule.field_id),
+                kind: Set(rule.kind.as_str().to_string()),
+                other_field_id: Set(rule.other_field_id),
+                operator: Set(rule.operator.as_str().to_string()),
+                value: Set(rule.value.as_ref().map(Value::to_string)),
+                message: Set(rule.message.clone()),
+                position: Set(position as i32),
+                created_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+        txn.commit().await?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::field::FieldType;
-    use sea_orm::{Database, DatabaseConnection};
+        log::info!("Set {} validation rule(s) for vault {}", rules.len(), vault_id);
 
-    async fn setup_test_db() -> DatabaseConnection {
-        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        Ok(rules)
+    }
 
-        // Run migrations
-        sea_orm::ConnectionTrait::execute_unprepared(
-            &conn,
-            r#"
-            CREATE TABLE vaults (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                description TEXT,
-                icon TEXT,
-                color TEXT,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-            );
-            
-            CREATE TABLE field_definitions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
-                name TEXT NOT NULL,
-                field_type TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'date', 'url', 'boolean', 'select', 'relation')),
-                options TEXT,
-                position INTEGER NOT NULL DEFAULT 0,
-                required INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
-                UNIQUE(vault_id, name)
-            );
-            
-            INSERT INTO vaults (name, created_at, updated_at) VALUES ('Test Vault', datetime('now'), datetime('now'));
-            "#,
-        )
-        .await
-        .unwrap();
+    /// Loads the validation rules of a vault. Rows that no longer parse
+    /// are skipped.
+    pub(crate) async fn vault_rules<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+    ) -> AppResult<Vec<FieldRule>> {
+        let rules = FieldRuleEntity::find()
+            .filter(field_rule::Column::VaultId.eq(vault_id))
+            .order_by_asc(field_rule::Column::Position)
+            .order_by_asc(field_rule::Column::Id)
+            .all(conn)
+            .await?;
 
-        conn
+        Ok(rules
+            .into_iter()
+            .filter_map(|rule| {
+                Some(FieldRule {
+                    field_id: rule.field_id,
+                    kind: FieldRuleKind::from_str(&rule.kind)?,
+                    other_field_id: rule.other_field_id,
+                    operator: RuleOperator::from_str(&rule.operator)?,
+                    value: rule.value.as_deref().and_then(|v| serde_json::from_str(v).ok()),
+                    message: rule.message,
+                })
+            })
+            .collect())
     }
 
-    #[tokio::test]
-    async fn test_create_field_definition() {
-        let conn = setup_test_db().await;
+    /// Validates rating field options.
+    /// Ensures the scale is between 1 and 100 and divides into whole steps.
+    fn validate_rating_options(options: Option<&FieldOptions>) -> AppResult<()> {
+        let options = options.cloned().unwrap_or_default();
+        let (max, step) = (options.rating_max(), options.rating_step());
 
-        let dto = CreateFieldDto {
-            vault_id: 1,
-            name: "Director".to_string(),
-            field_type: FieldType::Text,
-            options: None,
-            required: false,
+        if !(1.0..=MAX_RATING_SCALE).contains(&max) || max.fract() != 0.0 {
+            return Err(AppError::Validation(format!(
+                "Rating scale must be a whole number from 1 to {}",
+                MAX_RATING_SCALE
+            )));
+        }
+        if step <= 0.0 || step > max || (max / step).fract() != 0.0 {
+            return Err(AppError::Validation(format!(
+                "Rating step {} does not divide the scale of {}",
+                step, max
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validates file field options.
+    /// Ensures the size limit is within the 100MB cap and extensions are
+    /// plain lowercase names without the dot.
+    fn validate_file_options(options: Option<&FieldOptions>) -> AppResult<()> {
+        let Some(options) = options else {
+            return Ok(());
         };
 
-        let result = FieldService::create(&conn, dto).await.unwrap();
+        if let Some(size) = options.max_file_size {
+            if size == 0 || size > MAX_ATTACHMENT_SIZE {
+                return Err(AppError::Validation(format!(
+                    "File size limit must be between 1 byte and {}MB",
+                    MAX_ATTACHMENT_SIZE / (1024 * 1024)
+                )));
+            }
+        }
 
-        assert_eq!(result.name, "Director");
-        assert_eq!(result.vault_id, 1);
-        assert_eq!(result.field_type, FieldType::Text);
-        assert_eq!(result.position, 0);
-        assert!(!result.required);
-    }
+        for extension in options.allowed_extensions.iter().flatten() {
+            let is_plain = !extension.is_empty()
+                && extension.len() <= 16
+                && extension
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit());
+            if !is_plain {
+                return Err(AppError::Validation(format!(
+                    "Invalid file extension '{}': use lowercase letters and digits, without the dot",
+                    extension
+                )));
+            }
+        }
 
-    #[tokio::test]
-    async fn test_create_field_duplicate_name() {
-        let conn = setup_test_db().await;
+        Ok(())
+    }
 
-        let dto1 = CreateFieldDto {
-            vault_id: 1,
-            name: "Director".to_string(),
-            field_type: FieldType::Text,
-            options: None,
-            required: false,
+    /// Validates phone, currency and JSON field options.
+    /// Ensures default calling codes are one to three digits, fixed
+    /// currencies are ISO 4217 codes and JSON schemas are supported.
+    fn validate_format_options(field_type: FieldType, options: Option<&FieldOptions>) -> AppResult<()> {
+        let Some(options) = options else {
+            return Ok(());
         };
 
-        FieldService::create(&conn, dto1).await.unwrap();
+        match field_type {
+            FieldType::Phone => {
+                if let Some(code) = &options.calling_code {
+                    let valid = (1..=3).contains(&code.len())
+                        && code.bytes().all(|b| b.is_ascii_digit())
+                        && !code.starts_with('0');
+                    if !valid {
+                        return Err(AppError::Validation(format!(
+                            "Invalid calling code '{}': use one to three digits without '+', e.g. 44",
+                            code
+                        )));
+                    }
+                }
+            }
+            FieldType::Currency => {
+                if let Some(code) = &options.currency {
+                    if currency_minor_units(code).is_none() {
+                        return Err(AppError::Validation(format!(
+                            "Unknown currency '{}': use an uppercase ISO 4217 code, e.g. EUR",
+                            code
+                        )));
+                    }
+                }
+            }
+            FieldType::Json => {
+                if let Some(schema) = &options.json_schema {
+                    check_json_schema(schema)
+                        .map_err(|e| AppError::Validation(format!("Invalid JSON schema: {}", e)))?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Validates the default value of a field.
+    /// Ensures fixed defaults are valid values of the field and not set on
+    /// unique fields, dynamic defaults suit its type and copied fields
+    /// exist in the same vault and convert to it.
+    async fn validate_default_options(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        field_id: Option<i32>,
+        name: &str,
+        field_type: FieldType,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        let Some(default) = options.and_then(|o| o.default_value.as_ref()) else {
+            return Ok(());
+        };
+
+        if field_type.is_derived()
+            || matches!(field_type, FieldType::File | FieldType::Relation)
+        {
+            return Err(AppError::Validation(
+                "Computed, file and relation fields cannot have a default value".to_string(),
+            ));
+        }
+
+        match default {
+            DefaultValue::Value { .. } if options.is_some_and(|o| o.is_unique()) => {
+                return Err(AppError::Validation(
+                    "Unique fields cannot have a fixed default value".to_string(),
+                ));
+            }
+            DefaultValue::Value { value } => {
+                let field = FieldDefinitionDto {
+                    id: field_id.unwrap_or_default(),
+                    vault_id,
+                    name: name.to_string(),
+                    field_type,
+                    options: options.cloned(),
+                    position: 0,
+                    required: false,
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                };
+                MetadataService::normalize_field_value(&field, value)
+                    .and_then(|value| MetadataService::validate_field_value(&field, &value))
+                    .map_err(|e| AppError::Validation(format!("Invalid default value: {}", e)))?;
+            }
+            DefaultValue::Today => {
+                if !matches!(
+                    field_type,
+                    FieldType::Date | FieldType::PartialDate | FieldType::Datetime
+                ) {
+                    return Err(AppError::Validation(
+                        "Only date fields can default to today".to_string(),
+                    ));
+                }
+            }
+            DefaultValue::Now => {
+                if field_type != FieldType::Datetime {
+                    return Err(AppError::Validation(
+                        "Only date and time fields can default to now".to_string(),
+                    ));
+                }
+            }
+            DefaultValue::Counter { .. } => {
+                if field_type != FieldType::Number {
+                    return Err(AppError::Validation(
+                        "Only number fields can default to a counter".to_string(),
+                    ));
+                }
+            }
+            DefaultValue::CopyField { field_id: source_id } => {
+                if Some(*source_id) == field_id {
+                    return Err(AppError::Validation(
+                        "A field cannot default to its own value".to_string(),
+                    ));
+                }
+                let source = FieldDefinition::find_by_id(*source_id)
+                    .one(conn)
+                    .await?
+                    .filter(|source| source.vault_id == vault_id)
+                    .map(FieldDefinitionDto::from)
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "Field {} copied by the default value does not exist in this vault",
+                            source_id
+                        ))
+                    })?;
+                if source.field_type != field_type {
+                    check_conversion(source.field_type, field_type).map_err(|e| {
+                        AppError::Validation(format!(
+                            "Field '{}' cannot be copied: {}",
+                            source.name, e
+                        ))
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the choices of select and multiselect fields and gives
+    /// new ones an ID, unused by the field's `previous` choices. A fixed
+    /// default value given by label is stored as choice IDs.
+    fn prepare_choice_options(
+        field_type: FieldType,
+        options: &mut FieldOptions,
+        previous: &[Choice],
+    ) -> AppResult<()> {
+        if !matches!(field_type, FieldType::Select | FieldType::Multiselect) {
+            return Ok(());
+        }
+        if let Some(choices) = options.choices.take() {
+            options.choices = Some(prepare_choices(choices, previous).map_err(AppError::Validation)?);
+        }
+
+        let choices = options.choices.as_deref().unwrap_or_default();
+        if let Some(DefaultValue::Value { value }) = options.default_value.as_mut() {
+            let to_id = |item: &mut Value| {
+                if let Some(choice) = item.as_str().and_then(|label| match_choice(label, choices)) {
+                    *item = Value::String(choice.id.clone());
+                }
+            };
+            match value {
+                Value::Array(items) => items.iter_mut().for_each(to_id),
+                other => to_id(other),
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the choices an update removes, each with the choice its
+    /// entries merge into, if any. A fixed default value follows its
+    /// choice.
+    fn removed_choices(
+        field: &field_definition::Model,
+        options: &mut FieldOptions,
+        merges: &HashMap<String, String>,
+    ) -> AppResult<HashMap<String, Option<String>>> {
+        let current = FieldDefinitionDto::from(field.clone())
+            .options
+            .and_then(|o| o.choices)
+            .unwrap_or_default();
+        let choices = options.choices.as_deref().unwrap_or_default();
+        let kept = |id: &str| choices.iter().any(|c| c.id == id);
+
+        for (from, to) in merges {
+            if kept(from) || !current.iter().any(|c| &c.id == from) {
+                return Err(AppError::Validation(format!(
+                    "Choice '{}' is not being removed",
+                    from
+                )));
+            }
+            if !kept(to) {
+                return Err(AppError::Validation(format!(
+                    "Choices can only be merged into a remaining choice, not '{}'",
+                    to
+                )));
+            }
+        }
+
+        let removed: HashMap<String, Option<String>> = current
+            .into_iter()
+            .filter(|c| !kept(&c.id))
+            .map(|c| {
+                let target = merges.get(&c.id).cloned();
+                (c.id, target)
+            })
+            .collect();
+
+        if let Some(DefaultValue::Value { value }) = options.default_value.as_mut() {
+            if let Some(merged) = merge_choices(value, &removed) {
+                options.default_value = merged.map(|value| DefaultValue::Value { value });
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Moves the entries of removed choices to the choice they merge
+    /// into, or removes the choice from them.
+    ///
+    /// Returns the number of entries updated.
+    async fn remove_choices<C: ConnectionTrait>(
+        conn: &C,
+        field: &field_definition::Model,
+        removed: &HashMap<String, Option<String>>,
+    ) -> AppResult<u64> {
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.eq(field.vault_id))
+            .all(conn)
+            .await?;
+
+        let key = field.id.to_string();
+        let mut count = 0;
+        for entry in entries {
+            let Some(mut metadata) = entry
+                .metadata
+                .as_deref()
+                .and_then(|json| serde_json::from_str::<Map<String, Value>>(json).ok())
+            else {
+                continue;
+            };
+            let Some(value) = metadata.get(&key).and_then(|v| merge_choices(v, removed)) else {
+                continue;
+            };
+            match value {
+                Some(value) => metadata.insert(key.clone(), value),
+                None => metadata.remove(&key),
+            };
+
+            let mut entry_model: entry::ActiveModel = entry.into();
+            entry_model.metadata = Set(Some(Value::Object(metadata).to_string()));
+            entry_model.update(conn).await?;
+            count += 1;
+        }
+
+        log::info!(
+            "Removed {} choice(s) of field {} (id={}) from {} entries",
+            removed.len(),
+            field.name,
+            field.id,
+            count
+        );
+
+        Ok(count)
+    }
+
+    /// Validates identifier field options.
+    /// Ensures a scheme is set and, for existing fields, that it is left
+    /// unchanged, since stored values are normalized for it.
+    fn validate_identifier_options(
+        field: Option<&field_definition::Model>,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        check_identifier_options(options).map_err(AppError::Validation)?;
+
+        let Some(field) = field else {
+            return Ok(());
+        };
+        let current = FieldDefinitionDto::from(field.clone()).options.unwrap_or_default();
+
+        if current.scheme.is_some() && current.scheme != options.and_then(|o| o.scheme) {
+            return Err(AppError::Validation(
+                "The identifier scheme cannot be changed once set".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Validates unique, pattern and minimum length options.
+    /// Ensures, for existing fields, that entries share no value before the
+    /// field is made unique, as its unique index could not be created.
+    async fn validate_constraint_options(
+        conn: &DatabaseConnection,
+        field: Option<&field_definition::Model>,
+        field_type: FieldType,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        check_constraint_options(field_type, options).map_err(AppError::Validation)?;
+
+        let Some(field) = field else {
+            return Ok(());
+        };
+        let current = FieldDefinitionDto::from(field.clone()).options.unwrap_or_default();
+
+        if options.is_some_and(|o| o.is_unique()) && !current.is_unique() {
+            let sql = "SELECT CAST(json_extract(metadata, ?) AS TEXT) AS value FROM entries
+                WHERE vault_id = ? AND json_valid(metadata)
+                GROUP BY json_extract(metadata, ?)
+                HAVING value IS NOT NULL AND value <> '' AND COUNT(*) > 1
+                LIMIT 1";
+            let path = format!("$.\"{}\"", field.id);
+            let duplicate = conn
+                .query_one(Statement::from_sql_and_values(
+                    conn.get_database_backend(),
+                    sql,
+                    [path.clone().into(), field.vault_id.into(), path.into()],
+                ))
+                .await?;
+            if let Some(row) = duplicate {
+                let value: String = row.try_get("", "value").unwrap_or_default();
+                return Err(AppError::Validation(format!(
+                    "Field '{}' cannot be made unique: {} is used by several entries",
+                    field.name, value
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates relation field options.
+    /// Ensures target_vault_id is provided, the target vault exists and
+    /// cardinality limits are consistent.
+    async fn validate_relation_options(
+        conn: &DatabaseConnection,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        let options = options.ok_or_else(|| {
+            AppError::Validation("Relation field requires options with targetVaultId".to_string())
+        })?;
+
+        let target_vault_id = options.target_vault_id.ok_or_else(|| {
+            AppError::Validation("Relation field requires targetVaultId in options".to_string())
+        })?;
+
+        // Cardinality limits only apply to multi-valued relations
+        if !options.is_multiple() && (options.min_items.is_some() || options.max_items.is_some()) {
+            return Err(AppError::Validation(
+                "minItems and maxItems require a multiple relation field".to_string(),
+            ));
+        }
+        if options.min_items.is_some_and(|min| min < 0) {
+            return Err(AppError::Validation("minItems cannot be negative".to_string()));
+        }
+        if options.max_items.is_some_and(|max| max < 1) {
+            return Err(AppError::Validation("maxItems must be at least 1".to_string()));
+        }
+        if let (Some(min), Some(max)) = (options.min_items, options.max_items) {
+            if min > max {
+                return Err(AppError::Validation(
+                    "minItems cannot be greater than maxItems".to_string(),
+                ));
+            }
+        }
+
+        // Verify target vault exists
+        Vault::find_by_id(target_vault_id)
+            .one(conn)
+            .await?
+            .ok_or_else(|| {
+                AppError::Validation(format!("Target vault {} does not exist", target_vault_id))
+            })?;
+
+        Ok(())
+    }
+
+    /// Validates lookup and rollup field options.
+    /// Ensures the relation field they follow connects to this vault, the
+    /// source field belongs to the related vault and the rollup function
+    /// fits the source field.
+    async fn validate_derived_options(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        field_type: FieldType,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        let (options, via_field_id) = options
+            .and_then(|o| Some((o, o.via_field_id?)))
+            .ok_or_else(|| {
+                AppError::Validation("Computed field requires viaFieldId in options".to_string())
+            })?;
+
+        let via = FieldDefinition::find_by_id(via_field_id)
+            .one(conn)
+            .await?
+            .map(FieldDefinitionDto::from)
+            .filter(|f| f.field_type == FieldType::Relation)
+            .ok_or_else(|| {
+                AppError::Validation(format!("Field {} is not a relation field", via_field_id))
+            })?;
+        let target_vault_id = via.options.as_ref().and_then(|o| o.target_vault_id);
+
+        // Lookups read from the entries this entry links to; rollups may also
+        // aggregate over the entries linking to it.
+        let related_vault_id = if options.is_backlinks() {
+            if field_type == FieldType::Lookup {
+                return Err(AppError::Validation(
+                    "Lookup fields cannot follow backlinks".to_string(),
+                ));
+            }
+            if target_vault_id != Some(vault_id) {
+                return Err(AppError::Validation(format!(
+                    "Relation field '{}' does not reference this vault",
+                    via.name
+                )));
+            }
+            via.vault_id
+        } else {
+            if via.vault_id != vault_id {
+                return Err(AppError::Validation(format!(
+                    "Relation field '{}' belongs to another vault",
+                    via.name
+                )));
+            }
+            target_vault_id.unwrap_or(vault_id)
+        };
+
+        let source = match options.source_field_id {
+            Some(source_field_id) => {
+                let source = FieldDefinition::find_by_id(source_field_id)
+                    .one(conn)
+                    .await?
+                    .map(FieldDefinitionDto::from)
+                    .filter(|f| f.vault_id == related_vault_id)
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "Source field {} does not belong to the related vault",
+                            source_field_id
+                        ))
+                    })?;
+                if source.field_type.is_derived() {
+                    return Err(AppError::Validation(format!(
+                        "Source field '{}' is computed and cannot be looked up",
+                        source.name
+                    )));
+                }
+                Some(source)
+            }
+            None => None,
+        };
+
+        if field_type == FieldType::Rollup {
+            let function = options.rollup.ok_or_else(|| {
+                AppError::Validation("Rollup field requires rollup in options".to_string())
+            })?;
+            let numeric = source.is_some_and(|f| f.field_type.is_numeric());
+            if matches!(function, RollupFunction::Sum | RollupFunction::Average) && !numeric {
+                return Err(AppError::Validation(format!(
+                    "The {} rollup requires a numeric source field",
+                    function.as_str()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a formula field's expression.
+    /// Parses it and checks it against the types of the vault's fields it
+    /// reads, including other formulas, which must not read it back.
+    async fn validate_formula_options(
+        conn: &DatabaseConnection,
+        vault_id: i32,
+        field_id: Option<i32>,
+        options: Option<&FieldOptions>,
+    ) -> AppResult<()> {
+        let source = options
+            .and_then(|o| o.formula.as_deref())
+            .filter(|f| !f.trim().is_empty())
+            .ok_or_else(|| {
+                AppError::Validation("Formula field requires formula in options".to_string())
+            })?;
+
+        let fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.eq(vault_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+
+        check_formula(source, field_id, &fields)
+            .map_err(|e| AppError::Validation(format!("Invalid formula: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{FieldType, IdentifierScheme};
+    use sea_orm::{Database, DatabaseConnection};
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        // Run migrations
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL CHECK (field_type IN ('text', 'markdown', 'number', 'date', 'partialdate', 'datetime', 'duration', 'url', 'identifier', 'email', 'phone', 'color', 'currency', 'json', 'boolean', 'select', 'multiselect', 'rating', 'file', 'location', 'relation', 'lookup', 'rollup', 'formula')),
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(vault_id, name)
+            );
+
+            CREATE TABLE field_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                other_field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                operator TEXT NOT NULL,
+                value TEXT,
+                message TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            
+            INSERT INTO vaults (name, created_at, updated_at) VALUES ('Test Vault', datetime('now'), datetime('now'));
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    /// A new choice, given its ID when saved.
+    fn choice(label: &str) -> Choice {
+        Choice {
+            id: String::new(),
+            label: label.to_string(),
+            color: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_field_definition() {
+        let conn = setup_test_db().await;
+
+        let dto = CreateFieldDto {
+            vault_id: 1,
+            name: "Director".to_string(),
+            field_type: FieldType::Text,
+            options: None,
+            required: false,
+            backfill: false,
+        };
+
+        let result = FieldService::create(&conn, dto).await.unwrap();
+
+        assert_eq!(result.name, "Director");
+        assert_eq!(result.vault_id, 1);
+        assert_eq!(result.field_type, FieldType::Text);
+        assert_eq!(result.position, 0);
+        assert!(!result.required);
+    }
+
+    #[tokio::test]
+    async fn test_create_field_duplicate_name() {
+        let conn = setup_test_db().await;
+
+        let dto1 = CreateFieldDto {
+            vault_id: 1,
+            name: "Director".to_string(),
+            field_type: FieldType::Text,
+            options: None,
+            required: false,
+            backfill: false,
+        };
+
+        FieldService::create(&conn, dto1).await.unwrap();
+
+        let dto2 = CreateFieldDto {
+            vault_id: 1,
+            name: "Director".to_string(),
+            field_type: FieldType::Text,
+            options: None,
+            required: false,
+            backfill: false,
+        };
+
+        let result = FieldService::create(&conn, dto2).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_fields_ordered() {
+        let conn = setup_test_db().await;
+
+        for name in ["Director", "Year", "Genre"] {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type: FieldType::Text,
+                    options: None,
+                    required: false,
+                    backfill: false,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let fields = FieldService::list(&conn, 1).await.unwrap();
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].name, "Director");
+        assert_eq!(fields[0].position, 0);
+        assert_eq!(fields[1].name, "Year");
+        assert_eq!(fields[1].position, 1);
+        assert_eq!(fields[2].name, "Genre");
+        assert_eq!(fields[2].position, 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_multiple_relation_options() {
+        let conn = setup_test_db().await;
+
+        let relation_dto = |name: &str, min_items, max_items, multiple| CreateFieldDto {
+            vault_id: 1,
+            name: name.to_string(),
+            field_type: FieldType::Relation,
+            options: Some(FieldOptions {
+                target_vault_id: Some(1),
+                multiple,
+                min_items,
+                max_items,
+                ..Default::default()
+            }),
+            required: false,
+            backfill: false,
+        };
+
+        let created = FieldService::create(&conn, relation_dto("Cast", Some(1), Some(5), Some(true)))
+            .await
+            .unwrap();
+        assert!(created.options.unwrap().is_multiple());
+
+        // Limits without `multiple`, or inverted limits, are rejected
+        assert!(FieldService::create(&conn, relation_dto("Director", None, Some(2), None))
+            .await
+            .is_err());
+        assert!(FieldService::create(&conn, relation_dto("Crew", Some(3), Some(2), Some(true)))
+            .await
+            .is_err());
+        assert!(FieldService::create(&conn, relation_dto("Extras", None, Some(0), Some(true)))
+            .await
+            .is_err());
+
+        // Updates are validated too
+        let result = FieldService::update(
+            &conn,
+            created.id,
+            UpdateFieldDto {
+                name: None,
+                options: Some(FieldOptions {
+                    target_vault_id: Some(1),
+                    min_items: Some(2),
+                    ..Default::default()
+                }),
+                required: None,
+                backfill: false,
+                merge_choices: HashMap::new(),
+            },
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_lookup_and_rollup_options() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            "INSERT INTO vaults (name) VALUES ('Books')",
+        )
+        .await
+        .unwrap();
+
+        let create = |vault_id, name: &str, field_type, options: Option<FieldOptions>| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id,
+                    name: name.to_string(),
+                    field_type,
+                    options,
+                    required: false,
+                    backfill: false,
+                },
+            )
+        };
+
+        let nationality = create(1, "Nationality", FieldType::Text, None)
+            .await
+            .unwrap();
+        let pages = create(2, "Pages", FieldType::Number, None).await.unwrap();
+        let authors = create(
+            2,
+            "Authors",
+            FieldType::Relation,
+            Some(FieldOptions {
+                target_vault_id: Some(1),
+                multiple: Some(true),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let lookup = FieldOptions {
+            via_field_id: Some(authors.id),
+            source_field_id: Some(nationality.id),
+            ..Default::default()
+        };
+        assert!(
+            create(2, "Nationality", FieldType::Lookup, Some(lookup.clone()))
+                .await
+                .is_ok()
+        );
+        // The relation field must belong to the vault the lookup lives in
+        assert!(
+            create(1, "Own Nationality", FieldType::Lookup, Some(lookup))
+                .await
+                .is_err()
+        );
+
+        let rollup = |source_field_id, rollup| FieldOptions {
+            via_field_id: Some(authors.id),
+            backlinks: Some(true),
+            source_field_id,
+            rollup: Some(rollup),
+            ..Default::default()
+        };
+        assert!(create(
+            1,
+            "Pages Written",
+            FieldType::Rollup,
+            Some(rollup(Some(pages.id), RollupFunction::Sum))
+        )
+        .await
+        .is_ok());
+        // Sums need a number source; source fields come from the related vault
+        assert!(create(
+            1,
+            "Title Sum",
+            FieldType::Rollup,
+            Some(rollup(None, RollupFunction::Sum))
+        )
+        .await
+        .is_err());
+        assert!(create(
+            1,
+            "Own",
+            FieldType::Rollup,
+            Some(rollup(Some(nationality.id), RollupFunction::Count))
+        )
+        .await
+        .is_err());
+        assert!(create(1, "Missing", FieldType::Rollup, None).await.is_err());
+    }
 
-        let dto2 = CreateFieldDto {
-            vault_id: 1,
-            name: "Director".to_string(),
-            field_type: FieldType::Text,
-            options: None,
-            required: false,
+    #[tokio::test]
+    async fn test_create_rating_options() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, max, step| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type: FieldType::Rating,
+                    options: Some(FieldOptions {
+                        max,
+                        step,
+                        ..Default::default()
+                    }),
+                    required: false,
+                    backfill: false,
+                },
+            )
         };
 
-        let result = FieldService::create(&conn, dto2).await;
+        assert!(create("Stars", None, None).await.is_ok());
+        assert!(create("Score", Some(10.0), Some(0.5)).await.is_ok());
+        assert!(create("Huge", Some(1000.0), None).await.is_err());
+        assert!(create("Fractional", Some(4.5), None).await.is_err());
+        assert!(create("Uneven", Some(5.0), Some(2.0)).await.is_err());
+        assert!(create("Zero Step", Some(5.0), Some(0.0)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_format_options() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, field_type, options| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type,
+                    options: Some(options),
+                    required: false,
+                    backfill: false,
+                },
+            )
+        };
+        let phone = |code: &str| FieldOptions {
+            calling_code: Some(code.to_string()),
+            ..Default::default()
+        };
+        let currency = |code: &str| FieldOptions {
+            currency: Some(code.to_string()),
+            ..Default::default()
+        };
+        let schema = |schema| FieldOptions {
+            json_schema: Some(schema),
+            ..Default::default()
+        };
+
+        assert!(create("Phone", FieldType::Phone, phone("44")).await.is_ok());
+        assert!(create("Bad Phone", FieldType::Phone, phone("+44")).await.is_err());
+        assert!(create("Price", FieldType::Currency, currency("EUR")).await.is_ok());
+        assert!(create("Bad Price", FieldType::Currency, currency("eur")).await.is_err());
+        assert!(create("Specs", FieldType::Json, schema(serde_json::json!({"type": "object"})))
+            .await
+            .is_ok());
+        assert!(create("Bad Specs", FieldType::Json, schema(serde_json::json!({"$ref": "#"})))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_identifier_options() {
+        let conn = setup_test_db().await;
+
+        let options = |scheme, pattern: Option<&str>| FieldOptions {
+            scheme,
+            pattern: pattern.map(str::to_string),
+            ..Default::default()
+        };
+        let create = |name: &str, options| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type: FieldType::Identifier,
+                    options,
+                    required: false,
+                    backfill: false,
+                },
+            )
+        };
+
+        let isbn = create("ISBN", Some(options(Some(IdentifierScheme::Isbn), None)))
+            .await
+            .unwrap();
+        assert!(create("Code", Some(options(Some(IdentifierScheme::Custom), Some("[0-9]{4}"))))
+            .await
+            .is_ok());
+        assert!(create("Missing", None).await.is_err());
+        assert!(create("Broken", Some(options(Some(IdentifierScheme::Custom), Some("[0-9"))))
+            .await
+            .is_err());
+
+        // Stored values are normalized for the scheme, so it stays fixed
+        let result = FieldService::update(
+            &conn,
+            isbn.id,
+            UpdateFieldDto {
+                name: None,
+                options: Some(options(Some(IdentifierScheme::Ean), None)),
+                required: None,
+                backfill: false,
+                merge_choices: HashMap::new(),
+            },
+        )
+        .await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_list_fields_ordered() {
+    async fn test_unique_constraint() {
         let conn = setup_test_db().await;
+        let field = FieldService::create(
+            &conn,
+            CreateFieldDto {
+                vault_id: 1,
+                name: "Code".to_string(),
+                field_type: FieldType::Text,
+                options: None,
+                required: false,
+                backfill: false,
+            },
+        )
+        .await
+        .unwrap();
+        conn.execute_unprepared(&format!(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'First', '{{"{id}": "A1"}}'),
+                (1, 'Second', '{{"{id}": "A1"}}'),
+                (1, 'Blank', '{{"{id}": ""}}'),
+                (1, 'Blank again', '{{"{id}": ""}}')"#,
+            id = field.id
+        ))
+        .await
+        .unwrap();
 
-        for name in ["Director", "Year", "Genre"] {
+        let make_unique = || {
+            FieldService::update(
+                &conn,
+                field.id,
+                UpdateFieldDto {
+                    name: None,
+                    options: Some(FieldOptions {
+                        unique: Some(true),
+                        ..Default::default()
+                    }),
+                    required: None,
+                    backfill: false,
+                    merge_choices: HashMap::new(),
+                },
+            )
+        };
+        let error = make_unique().await.unwrap_err();
+        assert!(error.to_string().contains("A1 is used by several entries"));
+
+        // Once the duplicate is gone, the database enforces the constraint
+        conn.execute_unprepared("DELETE FROM entries WHERE title = 'Second'")
+            .await
+            .unwrap();
+        make_unique().await.unwrap();
+        let duplicate = format!(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES (1, 'Third', '{{"{}": "A1"}}')"#,
+            field.id
+        );
+        assert!(conn.execute_unprepared(&duplicate).await.is_err());
+
+        FieldService::delete(&conn, field.id, None).await.unwrap();
+        assert!(conn.execute_unprepared(&duplicate).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_choices() {
+        let conn = setup_test_db().await;
+        let field = FieldService::create(
+            &conn,
+            CreateFieldDto {
+                vault_id: 1,
+                name: "Tags".to_string(),
+                field_type: FieldType::Multiselect,
+                options: Some(FieldOptions {
+                    choices: Some(vec![choice("Red"), choice("Grean"), choice("Blue")]),
+                    ..Default::default()
+                }),
+                required: false,
+                backfill: false,
+            },
+        )
+        .await
+        .unwrap();
+        let mut choices = field.options.unwrap().choices.unwrap();
+        let ids: Vec<&str> = choices.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["c1", "c2", "c3"]);
+
+        conn.execute_unprepared(&format!(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'First', '{{"{id}": ["c1", "c2"]}}'),
+                (1, 'Second', '{{"{id}": ["c3"]}}'),
+                (1, 'Third', '{{"{id}": ["c3", "c1"]}}'),
+                (1, 'Fourth', '{{"{id}": ["c2"]}}')"#,
+            id = field.id
+        ))
+        .await
+        .unwrap();
+        let metadata = || async {
+            Entry::find()
+                .all(&conn)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|e| serde_json::from_str::<Value>(&e.metadata.unwrap()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let update = |choices: Vec<Choice>, merges: &[(&str, &str)]| {
+            FieldService::update(
+                &conn,
+                field.id,
+                UpdateFieldDto {
+                    name: None,
+                    options: Some(FieldOptions {
+                        choices: Some(choices),
+                        ..Default::default()
+                    }),
+                    required: None,
+                    backfill: false,
+                    merge_choices: merges
+                        .iter()
+                        .map(|(from, to)| (from.to_string(), to.to_string()))
+                        .collect(),
+                },
+            )
+        };
+
+        // Renaming a choice leaves entries alone; removing one merges its
+        // entries into another
+        choices[1].label = "Green".to_string();
+        let blue = choices.pop().unwrap();
+        assert!(update(choices.clone(), &[("c3", "c3")]).await.is_err());
+        assert!(update(choices.clone(), &[("c2", "c1")]).await.is_err());
+        update(choices.clone(), &[("c3", "c1")]).await.unwrap();
+        let key = field.id.to_string();
+        let values: Vec<Value> = metadata().await.iter().map(|m| m[&key].clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!(["c1", "c2"]),
+                serde_json::json!(["c1"]),
+                serde_json::json!(["c1"]),
+                serde_json::json!(["c2"]),
+            ]
+        );
+
+        // Choices removed without a merge are cleared from entries, and new
+        // ones never take the ID of a removed one
+        choices.pop();
+        choices.push(choice(&blue.label));
+        let updated = update(choices, &[]).await.unwrap();
+        assert_eq!(updated.options.unwrap().choices.unwrap()[1].id, "c3");
+        let metadata = metadata().await;
+        assert_eq!(metadata[0][&key], serde_json::json!(["c1"]));
+        assert!(metadata[3].get(&key).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_default_value_options() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, field_type, default_value, backfill| {
             FieldService::create(
                 &conn,
                 CreateFieldDto {
                     vault_id: 1,
                     name: name.to_string(),
-                    field_type: FieldType::Text,
+                    field_type,
+                    options: Some(FieldOptions {
+                        choices: Some(vec![choice("Low"), choice("High")]),
+                        default_value: Some(default_value),
+                        ..Default::default()
+                    }),
+                    required: false,
+                    backfill,
+                },
+            )
+        };
+
+        // Fixed defaults must be valid values of the field
+        let low = DefaultValue::Value { value: serde_json::json!("Low") };
+        let none = DefaultValue::Value { value: serde_json::json!("None") };
+        assert!(create("Bad", FieldType::Select, none, false).await.is_err());
+        assert!(create("Bad", FieldType::Text, DefaultValue::Now, false).await.is_err());
+        assert!(create("Bad", FieldType::Date, DefaultValue::Counter { start: None }, false)
+            .await
+            .is_err());
+        assert!(create("Bad", FieldType::Text, DefaultValue::CopyField { field_id: 99 }, false)
+            .await
+            .is_err());
+
+        conn.execute_unprepared(
+            r#"INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'A', NULL),
+                (1, 'B', '{}')"#,
+        )
+        .await
+        .unwrap();
+
+        let priority = create("Priority", FieldType::Select, low, true).await.unwrap();
+        // A default given by label is kept as the choice ID
+        assert_eq!(
+            priority.options.as_ref().unwrap().default_value,
+            Some(DefaultValue::Value { value: serde_json::json!("c1") })
+        );
+        let counter = DefaultValue::Counter { start: Some(10) };
+        let number = create("Number", FieldType::Number, counter, false).await.unwrap();
+        // A field cannot copy itself
+        let copy = DefaultValue::CopyField { field_id: number.id };
+        let update = UpdateFieldDto {
+            name: None,
+            options: Some(FieldOptions {
+                default_value: Some(copy),
+                ..Default::default()
+            }),
+            required: None,
+            backfill: false,
+            merge_choices: HashMap::new(),
+        };
+        assert!(FieldService::update(&conn, number.id, update).await.is_err());
+
+        // Backfilling when the field is updated
+        FieldService::update(
+            &conn,
+            number.id,
+            UpdateFieldDto {
+                name: None,
+                options: None,
+                required: Some(true),
+                backfill: true,
+                merge_choices: HashMap::new(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let metadata: Vec<Value> = Entry::find()
+            .order_by_asc(entry::Column::Id)
+            .all(&conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| serde_json::from_str(&e.metadata.unwrap()).unwrap())
+            .collect();
+        let (priority, number) = (priority.id.to_string(), number.id.to_string());
+        assert_eq!(metadata[0][&priority], "c1");
+        assert_eq!(metadata[0][&number], 10);
+        assert_eq!(metadata[1][&number], 11);
+    }
+
+    #[tokio::test]
+    async fn test_convert_field_type() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, field_type| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type,
                     options: None,
                     required: false,
+                    backfill: false,
                 },
             )
+        };
+        let year = create("Year", FieldType::Text).await.unwrap();
+        let genre = create("Genre", FieldType::Text).await.unwrap();
+
+        for (title, metadata) in [
+            ("Matrix", r#"{"1": "1999", "2": "drama"}"#),
+            ("Primer", r#"{"1": " 2004 ", "2": "Drama"}"#),
+            ("Unknown", r#"{"1": "soon", "2": "Comedy"}"#),
+            ("Blank", r#"{"1": ""}"#),
+            ("Empty", "{}"),
+        ] {
+            conn.execute_unprepared(&format!(
+                "INSERT INTO entries (vault_id, title, metadata) VALUES (1, '{}', '{}')",
+                title, metadata
+            ))
             .await
             .unwrap();
         }
+        let metadata = || async {
+            Entry::find()
+                .order_by_asc(entry::Column::Id)
+                .all(&conn)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|e| serde_json::from_str::<Value>(&e.metadata.unwrap()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let to = |field_type, discard_failures| ConvertFieldDto {
+            field_type,
+            options: None,
+            discard_failures,
+        };
 
-        let fields = FieldService::list(&conn, 1).await.unwrap();
+        let preview = FieldService::preview_conversion(&conn, year.id, &to(FieldType::Number, false))
+            .await
+            .unwrap();
+        assert_eq!((preview.converted, preview.emptied, preview.failed), (2, 1, 1));
+        assert_eq!(preview.failures[0].title, "Unknown");
 
-        assert_eq!(fields.len(), 3);
-        assert_eq!(fields[0].name, "Director");
-        assert_eq!(fields[0].position, 0);
-        assert_eq!(fields[1].name, "Year");
-        assert_eq!(fields[1].position, 1);
-        assert_eq!(fields[2].name, "Genre");
-        assert_eq!(fields[2].position, 2);
+        // Failures block the conversion unless they are discarded
+        assert!(FieldService::convert(&conn, year.id, to(FieldType::Number, false)).await.is_err());
+        assert_eq!(metadata().await[0]["1"], "1999");
+
+        let converted = FieldService::convert(&conn, year.id, to(FieldType::Number, true))
+            .await
+            .unwrap();
+        assert_eq!(converted.field_type, FieldType::Number);
+        let values = metadata().await;
+        assert_eq!(values[0]["1"], 1999);
+        assert_eq!(values[1]["1"], 2004);
+        assert!(values[2].get("1").is_none());
+        assert!(values[3].get("1").is_none());
+
+        // Choices are generated from the distinct values, ignoring case
+        let preview = FieldService::preview_conversion(&conn, genre.id, &to(FieldType::Select, false))
+            .await
+            .unwrap();
+        let labels: Vec<String> = preview.choices.unwrap().into_iter().map(|c| c.label).collect();
+        assert_eq!(labels, vec!["Comedy", "drama"]);
+        let converted = FieldService::convert(&conn, genre.id, to(FieldType::Select, false))
+            .await
+            .unwrap();
+        assert_eq!(converted.options.unwrap().choices.unwrap().len(), 2);
+        assert_eq!(metadata().await[1]["2"], "c2");
+
+        // Select and multiselect fields keep their choices
+        FieldService::convert(&conn, genre.id, to(FieldType::Multiselect, false))
+            .await
+            .unwrap();
+        assert_eq!(metadata().await[2]["2"], serde_json::json!(["c1"]));
+        let converted = FieldService::convert(&conn, genre.id, to(FieldType::Text, false))
+            .await
+            .unwrap();
+        assert!(converted.options.is_none());
+        assert_eq!(metadata().await[2]["2"], "Comedy");
+
+        // Formulas reading the field must still type-check
+        FieldService::create(
+            &conn,
+            CreateFieldDto {
+                vault_id: 1,
+                name: "Age".to_string(),
+                field_type: FieldType::Formula,
+                options: Some(FieldOptions {
+                    formula: Some(format!("2024 - {{{}}}", year.id)),
+                    ..Default::default()
+                }),
+                required: false,
+                backfill: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(FieldService::preview_conversion(&conn, year.id, &to(FieldType::Text, false))
+            .await
+            .is_err());
+        assert!(FieldService::preview_conversion(&conn, year.id, &to(FieldType::Relation, false))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_and_update_formula() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, field_type, formula: Option<String>| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type,
+                    options: formula.map(|formula| FieldOptions {
+                        formula: Some(formula),
+                        ..Default::default()
+                    }),
+                    required: false,
+                    backfill: false,
+                },
+            )
+        };
+
+        let pages = create("Pages", FieldType::Number, None).await.unwrap();
+        let read = create("Read", FieldType::Number, None).await.unwrap();
+        let progress = create(
+            "Progress",
+            FieldType::Formula,
+            Some(format!("round({{{}}} / {{{}}} * 100)", read.id, pages.id)),
+        )
+        .await
+        .unwrap();
+        let label = create(
+            "Label",
+            FieldType::Formula,
+            Some(format!("{{{}}} & \"%\"", progress.id)),
+        )
+        .await
+        .unwrap();
+
+        assert!(create("Empty", FieldType::Formula, None).await.is_err());
+        assert!(create("Mismatch", FieldType::Formula, Some(format!("{{{}}} - 1", label.id)))
+            .await
+            .is_err());
+
+        // Progress may not read Label, which reads Progress
+        let cycle = FieldService::update(
+            &conn,
+            progress.id,
+            UpdateFieldDto {
+                name: None,
+                options: Some(FieldOptions {
+                    formula: Some(format!("len({{{}}})", label.id)),
+                    ..Default::default()
+                }),
+                required: None,
+                backfill: false,
+                merge_choices: HashMap::new(),
+            },
+        )
+        .await;
+        assert!(cycle.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_field_rules() {
+        let conn = setup_test_db().await;
+
+        let create = |name: &str, field_type, options| {
+            FieldService::create(
+                &conn,
+                CreateFieldDto {
+                    vault_id: 1,
+                    name: name.to_string(),
+                    field_type,
+                    options,
+                    required: false,
+                    backfill: false,
+                },
+            )
+        };
+        let status = create(
+            "Status",
+            FieldType::Select,
+            Some(FieldOptions {
+                choices: Some(vec![choice("Reading"), choice("Finished")]),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        let started = create("Started", FieldType::Date, None).await.unwrap();
+        let finished = create("Finished", FieldType::Date, None).await.unwrap();
+
+        let rules = vec![
+            FieldRule {
+                field_id: finished.id,
+                kind: FieldRuleKind::RequiredIf,
+                other_field_id: status.id,
+                operator: RuleOperator::Equals,
+                value: Some(Value::from("Finished")),
+                message: None,
+            },
+            FieldRule {
+                field_id: finished.id,
+                kind: FieldRuleKind::Compare,
+                other_field_id: started.id,
+                operator: RuleOperator::GreaterOrEqual,
+                value: None,
+                message: Some("Cannot finish before starting".to_string()),
+            },
+        ];
+
+        // Conditions on choices are stored by choice ID
+        let saved = FieldService::set_rules(&conn, 1, rules.clone()).await.unwrap();
+        assert_eq!(saved[0].value, Some(Value::from("c2")));
+        assert_eq!(FieldService::list_rules(&conn, 1).await.unwrap(), saved);
+
+        let invalid = FieldRule {
+            other_field_id: status.id,
+            ..rules[1].clone()
+        };
+        assert!(FieldService::set_rules(&conn, 1, vec![invalid]).await.is_err());
+        assert_eq!(FieldService::list_rules(&conn, 1).await.unwrap().len(), 2);
+
+        // Compared fields must stay comparable
+        let to_text = ConvertFieldDto {
+            field_type: FieldType::Text,
+            options: None,
+            discard_failures: false,
+        };
+        let result = FieldService::convert(&conn, started.id, to_text).await;
+        assert!(result.is_err());
+
+        FieldService::set_rules(&conn, 1, Vec::new()).await.unwrap();
+        assert!(FieldService::list_rules(&conn, 1).await.unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -381,6 +2400,7 @@ mod tests {
                 field_type: FieldType::Text,
                 options: None,
                 required: false,
+                backfill: false,
             },
         )
         .await
@@ -393,6 +2413,8 @@ mod tests {
                 name: Some("Film Director".to_string()),
                 options: None,
                 required: Some(true),
+                backfill: false,
+                merge_choices: HashMap::new(),
             },
         )
         .await
@@ -414,12 +2436,13 @@ mod tests {
                 field_type: FieldType::Text,
                 options: None,
                 required: false,
+                backfill: false,
             },
         )
         .await
         .unwrap();
 
-        FieldService::delete(&conn, created.id).await.unwrap();
+        FieldService::delete(&conn, created.id, None).await.unwrap();
 
         let result = FieldService::get(&conn, created.id).await;
         assert!(result.is_err());
@@ -439,6 +2462,7 @@ mod tests {
                     field_type: FieldType::Text,
                     options: None,
                     required: false,
+                    backfill: false,
                 },
             )
             .await
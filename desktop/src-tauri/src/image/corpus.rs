@@ -0,0 +1,101 @@
+//! Malicious and malformed image files for hardening tests.
+//!
+//! Every payload is generated in memory so the corpus needs no binary
+//! fixtures and stays readable.
+
+/// A plain-text file, typically given an image extension.
+pub fn plain_text() -> Vec<u8> {
+    b"not an image".to_vec()
+}
+
+/// An SVG document, which can carry scripts and is never accepted.
+pub fn svg_document() -> Vec<u8> {
+    br#"<svg xmlns="http://www.w3.org/2000/svg"><script>alert(1)</script></svg>"#.to_vec()
+}
+
+/// JPEG magic bytes followed by an HTML payload.
+pub fn jpeg_html_polyglot() -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xD8, 0xFF, 0xE0];
+    bytes.extend_from_slice(b"<html><script>alert(1)</script></html>");
+    bytes
+}
+
+/// A RIFF container that is a WAV file rather than WebP.
+pub fn riff_wave() -> Vec<u8> {
+    let mut bytes = b"RIFF".to_vec();
+    bytes.extend_from_slice(&36u32.to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&[0; 24]);
+    bytes
+}
+
+/// A valid PNG cut off in the middle of its pixel data.
+pub fn truncated_png() -> Vec<u8> {
+    let img = image::DynamicImage::new_rgb8(64, 64);
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buffer, image::ImageFormat::Png).unwrap();
+
+    let mut bytes = buffer.into_inner();
+    bytes.truncate(bytes.len() / 2);
+    bytes
+}
+
+/// A tiny PNG whose header declares dimensions far beyond the limits.
+pub fn png_oversized_dimensions() -> Vec<u8> {
+    png_with_header(100_000, 100_000)
+}
+
+/// A PNG within the per-side limit whose decoded size exceeds the memory budget.
+pub fn png_allocation_bomb() -> Vec<u8> {
+    png_with_header(16_000, 16_000)
+}
+
+/// Relative paths that try to escape the images directory.
+pub fn traversal_paths() -> Vec<&'static str> {
+    vec![
+        "",
+        "../secret.jpg",
+        "ab/../../secret.jpg",
+        "/etc/passwd",
+        "./ab/cd.jpg",
+    ]
+}
+
+/// Builds a PNG with an RGBA header of the given size and an empty IDAT.
+fn png_with_header(width: u32, height: u32) -> Vec<u8> {
+    let mut bytes = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type RGBA, default compression/filter/interlace
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    write_chunk(&mut bytes, b"IHDR", &ihdr);
+    // Empty zlib stream
+    write_chunk(&mut bytes, b"IDAT", &[0x78, 0x9C, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    write_chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let mut crc_input = kind.to_vec();
+    crc_input.extend_from_slice(data);
+
+    out.extend_from_slice(&crc_input);
+    out.extend_from_slice(&crc32(&crc_input).to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
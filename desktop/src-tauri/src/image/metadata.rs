@@ -0,0 +1,363 @@
+//! EXIF and XMP metadata extraction from image files.
+//!
+//! Only a fixed set of tags is extracted (see [`ImageTag`]). EXIF values take
+//! precedence; XMP fills in tags the EXIF block does not carry.
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use chrono::NaiveDateTime;
+use exif::{In, Reader, Tag, Value};
+use image::ImageReader;
+
+use super::{ImageMetadata, ImageTag, ImageTagValue};
+
+/// XMP properties read for each tag, in order of preference.
+const XMP_PROPERTIES: &[(ImageTag, &[&str])] = &[
+    (ImageTag::Make, &["tiff:Make"]),
+    (ImageTag::Model, &["tiff:Model"]),
+    (ImageTag::LensModel, &["exifEX:LensModel", "aux:Lens"]),
+    (
+        ImageTag::DateTimeOriginal,
+        &["exif:DateTimeOriginal", "photoshop:DateCreated", "xmp:CreateDate"],
+    ),
+];
+
+/// Reads the supported tags from an image file.
+///
+/// Never fails: unreadable or missing metadata yields an empty result.
+pub fn extract(bytes: &[u8]) -> ImageMetadata {
+    let mut tags = BTreeMap::new();
+
+    if let Ok((width, height)) = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| ())
+        .and_then(|reader| reader.into_dimensions().map_err(|_| ()))
+    {
+        tags.insert(ImageTag::ImageWidth, ImageTagValue::Number(width as f64));
+        tags.insert(ImageTag::ImageHeight, ImageTagValue::Number(height as f64));
+    }
+
+    match Reader::new().read_from_container(&mut Cursor::new(bytes)) {
+        Ok(exif) => read_exif(&exif, &mut tags),
+        Err(e) => log::debug!("No EXIF data: {}", e),
+    }
+
+    if let Some(xmp) = find_xmp_packet(bytes) {
+        for (tag, properties) in XMP_PROPERTIES {
+            if tags.contains_key(tag) {
+                continue;
+            }
+            let value = properties.iter().find_map(|p| xmp_property(xmp, p));
+            if let Some(value) = value.and_then(|v| parse_tag_value(*tag, &v)) {
+                tags.insert(*tag, value);
+            }
+        }
+    }
+
+    ImageMetadata { tags }
+}
+
+fn read_exif(exif: &exif::Exif, tags: &mut BTreeMap<ImageTag, ImageTagValue>) {
+    let text = |tag: Tag| {
+        exif.get_field(tag, In::PRIMARY).and_then(|f| match &f.value {
+            Value::Ascii(parts) => parts
+                .first()
+                .map(|p| String::from_utf8_lossy(p).trim().to_string())
+                .filter(|s| !s.is_empty()),
+            _ => None,
+        })
+    };
+    let number = |tag: Tag| {
+        exif.get_field(tag, In::PRIMARY).and_then(|f| match &f.value {
+            Value::Rational(v) => v.first().map(|r| r.to_f64()),
+            Value::SRational(v) => v.first().map(|r| r.to_f64()),
+            _ => f.value.get_uint(0).map(|n| n as f64),
+        })
+    };
+
+    for (tag, exif_tag) in [
+        (ImageTag::Make, Tag::Make),
+        (ImageTag::Model, Tag::Model),
+        (ImageTag::LensModel, Tag::LensModel),
+    ] {
+        if let Some(value) = text(exif_tag) {
+            tags.insert(tag, ImageTagValue::Text(value));
+        }
+    }
+
+    if let Some(value) = text(Tag::DateTimeOriginal)
+        .or_else(|| text(Tag::DateTime))
+        .and_then(|v| parse_tag_value(ImageTag::DateTimeOriginal, &v))
+    {
+        tags.insert(ImageTag::DateTimeOriginal, value);
+    }
+
+    for (tag, exif_tag) in [
+        (ImageTag::FNumber, Tag::FNumber),
+        (ImageTag::ExposureTime, Tag::ExposureTime),
+        (ImageTag::Iso, Tag::PhotographicSensitivity),
+        (ImageTag::FocalLength, Tag::FocalLength),
+    ] {
+        if let Some(value) = number(exif_tag).filter(|n| n.is_finite()) {
+            tags.insert(tag, ImageTagValue::Number(value));
+        }
+    }
+
+    let coordinate = |value_tag: Tag, ref_tag: Tag, negative: &str| {
+        let field = exif.get_field(value_tag, In::PRIMARY)?;
+        let Value::Rational(parts) = &field.value else {
+            return None;
+        };
+        if parts.len() < 3 {
+            return None;
+        }
+        let degrees = parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0;
+        let sign = if text(ref_tag).as_deref() == Some(negative) { -1.0 } else { 1.0 };
+        Some(sign * degrees).filter(|d| d.is_finite())
+    };
+
+    if let Some(latitude) = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S") {
+        tags.insert(ImageTag::GpsLatitude, ImageTagValue::Number(latitude));
+    }
+    if let Some(longitude) = coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W") {
+        tags.insert(ImageTag::GpsLongitude, ImageTagValue::Number(longitude));
+    }
+    if let Some(altitude) = number(Tag::GPSAltitude).filter(|n| n.is_finite()) {
+        // Altitude ref 1 means below sea level
+        let below = exif
+            .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+            .and_then(|f| f.value.get_uint(0))
+            == Some(1);
+        let altitude = if below { -altitude } else { altitude };
+        tags.insert(ImageTag::GpsAltitude, ImageTagValue::Number(altitude));
+    }
+}
+
+/// Parses a raw text value for a tag.
+fn parse_tag_value(tag: ImageTag, raw: &str) -> Option<ImageTagValue> {
+    let raw = raw.trim();
+    match tag {
+        ImageTag::DateTimeOriginal => parse_date_time(raw).map(ImageTagValue::DateTime),
+        _ if raw.is_empty() => None,
+        _ => Some(ImageTagValue::Text(raw.to_string())),
+    }
+}
+
+/// Parses EXIF (`2024:05:01 10:30:00`) and XMP (`2024-05-01T10:30:00+02:00`)
+/// timestamps. Time zones are dropped; a date alone is taken as midnight.
+fn parse_date_time(raw: &str) -> Option<NaiveDateTime> {
+    const FORMATS: &[&str] = &["%Y:%m:%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"];
+
+    // Strip fractional seconds and time zone suffixes
+    let trimmed = raw
+        .split(['.', '+', 'Z'])
+        .next()
+        .unwrap_or(raw);
+    let trimmed = match trimmed.rfind('-') {
+        // A '-' after the time separator starts a negative offset
+        Some(i) if trimmed.find('T').is_some_and(|t| i > t) => &trimmed[..i],
+        _ => trimmed,
+    };
+
+    FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(trimmed, f).ok())
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Finds the XMP packet embedded in an image file.
+fn find_xmp_packet(bytes: &[u8]) -> Option<&str> {
+    const START: &[u8] = b"<x:xmpmeta";
+    const END: &[u8] = b"</x:xmpmeta>";
+
+    let start = bytes.windows(START.len()).position(|w| w == START)?;
+    let length = bytes[start..].windows(END.len()).position(|w| w == END)?;
+    std::str::from_utf8(&bytes[start..start + length + END.len()]).ok()
+}
+
+/// Reads a simple XMP property, written either as an attribute
+/// (`tiff:Model="X"`) or as an element (`<tiff:Model>X</tiff:Model>`).
+fn xmp_property(xmp: &str, name: &str) -> Option<String> {
+    let attribute = format!("{}=\"", name);
+    if let Some(start) = xmp.find(&attribute).map(|i| i + attribute.len()) {
+        let end = xmp[start..].find('"')?;
+        return Some(unescape_xml(&xmp[start..start + end]));
+    }
+
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xmp.find(&open)? + open.len();
+    let end = xmp[start..].find(&close)?;
+    let content = &xmp[start..start + end];
+
+    // Language alternatives wrap the value in rdf:li elements
+    let content = match content.find("<rdf:li") {
+        Some(li) => {
+            let value_start = li + content[li..].find('>')? + 1;
+            let value_end = value_start + content[value_start..].find("</rdf:li>")?;
+            &content[value_start..value_end]
+        }
+        None => content,
+    };
+
+    Some(unescape_xml(content.trim()))
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+
+    fn ascii(tag: Tag, value: &str) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+        }
+    }
+
+    fn rational(tag: Tag, values: &[(u32, u32)]) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(
+                values
+                    .iter()
+                    .map(|&(num, denom)| Rational { num, denom })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Builds a JPEG with the given EXIF fields in an APP1 segment.
+    fn jpeg_with_exif(fields: &[Field]) -> Vec<u8> {
+        let mut writer = Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut jpeg = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(40, 30)
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        let jpeg = jpeg.into_inner();
+
+        let mut segment = b"Exif\0\0".to_vec();
+        segment.extend_from_slice(&tiff);
+
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&((segment.len() + 2) as u16).to_be_bytes());
+        out.extend_from_slice(&segment);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    #[test]
+    fn test_extract_exif_tags() {
+        let bytes = jpeg_with_exif(&[
+            ascii(Tag::Make, "Fujifilm"),
+            ascii(Tag::Model, "X-T5"),
+            ascii(Tag::DateTimeOriginal, "2024:05:01 10:30:00"),
+            rational(Tag::FNumber, &[(28, 10)]),
+            rational(Tag::GPSLatitude, &[(48, 1), (51, 1), (30, 1)]),
+            ascii(Tag::GPSLatitudeRef, "N"),
+            rational(Tag::GPSLongitude, &[(2, 1), (21, 1), (0, 1)]),
+            ascii(Tag::GPSLongitudeRef, "W"),
+        ]);
+
+        let metadata = extract(&bytes);
+        let tags = &metadata.tags;
+
+        assert_eq!(tags[&ImageTag::Make], ImageTagValue::Text("Fujifilm".to_string()));
+        assert_eq!(tags[&ImageTag::Model], ImageTagValue::Text("X-T5".to_string()));
+        assert_eq!(
+            tags[&ImageTag::DateTimeOriginal],
+            ImageTagValue::DateTime(parse_date_time("2024-05-01T10:30:00").unwrap())
+        );
+        assert_eq!(tags[&ImageTag::FNumber], ImageTagValue::Number(2.8));
+        assert_eq!(tags[&ImageTag::ImageWidth], ImageTagValue::Number(40.0));
+        assert_eq!(tags[&ImageTag::ImageHeight], ImageTagValue::Number(30.0));
+
+        let ImageTagValue::Number(latitude) = tags[&ImageTag::GpsLatitude] else {
+            panic!("latitude should be a number");
+        };
+        let ImageTagValue::Number(longitude) = tags[&ImageTag::GpsLongitude] else {
+            panic!("longitude should be a number");
+        };
+        assert!((latitude - 48.858_333).abs() < 1e-5);
+        assert!((longitude + 2.35).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_extract_xmp_fallback() {
+        let mut bytes = jpeg_with_exif(&[ascii(Tag::Model, "X-T5")]);
+        bytes.extend_from_slice(
+            br#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description
+                tiff:Model="Ignored" aux:Lens="XF 23mm F1.4 R"
+                xmp:CreateDate="2023-12-24T18:05:00+01:00"/></x:xmpmeta>"#,
+        );
+
+        let tags = extract(&bytes).tags;
+
+        // EXIF wins over XMP
+        assert_eq!(tags[&ImageTag::Model], ImageTagValue::Text("X-T5".to_string()));
+        assert_eq!(
+            tags[&ImageTag::LensModel],
+            ImageTagValue::Text("XF 23mm F1.4 R".to_string())
+        );
+        assert_eq!(
+            tags[&ImageTag::DateTimeOriginal],
+            ImageTagValue::DateTime(parse_date_time("2023:12:24 18:05:00").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_extract_without_metadata() {
+        let mut png = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(8, 4)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+
+        let tags = extract(&png.into_inner()).tags;
+        assert_eq!(tags.len(), 2);
+        assert!(extract(b"not an image").tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_date_time_formats() {
+        let expected = parse_date_time("2024:05:01 10:30:00").unwrap();
+        assert_eq!(parse_date_time("2024-05-01T10:30:00.123Z"), Some(expected));
+        assert_eq!(parse_date_time("2024-05-01T10:30:00-05:00"), Some(expected));
+        assert!(parse_date_time("2024-05-01").is_some());
+        assert!(parse_date_time("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_xmp_element_property() {
+        let xmp = r#"<x:xmpmeta><photoshop:DateCreated>2020-01-02</photoshop:DateCreated>
+            <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Tom &amp; Jerry</rdf:li></rdf:Alt></dc:title></x:xmpmeta>"#;
+
+        assert_eq!(xmp_property(xmp, "photoshop:DateCreated").as_deref(), Some("2020-01-02"));
+        assert_eq!(xmp_property(xmp, "dc:title").as_deref(), Some("Tom & Jerry"));
+        assert_eq!(xmp_property(xmp, "tiff:Model"), None);
+    }
+}
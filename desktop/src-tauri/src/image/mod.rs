@@ -1,7 +1,15 @@
 //! Image management module for cover images.
 
+#[cfg(test)]
+mod corpus;
+mod metadata;
+mod model;
+mod placeholder;
 pub mod processor;
+mod registry;
 pub mod storage;
 
+pub use model::*;
 pub use processor::ImageProcessor;
-pub use storage::ImageStorage;
+pub use registry::ImageRegistry;
+pub use storage::{ImageStorage, StoredImage};
@@ -0,0 +1,203 @@
+//! Image data transfer objects.
+
+use serde::{Deserialize, Serialize};
+
+/// Entry whose cover image file is missing from storage.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingCover {
+    pub entry_id: i32,
+    pub vault_id: i32,
+    pub cover_image_path: String,
+}
+
+/// Report produced by an image garbage collection run.
+#[derive(Debug, Serialize)]
+pub struct ImageGcReport {
+    /// Whether files were only reported and not removed
+    pub dry_run: bool,
+    /// Number of files found under the images directory
+    pub scanned_files: usize,
+    /// Relative paths of files that no entry references
+    pub unreferenced_files: Vec<String>,
+    /// Total size of unreferenced files in bytes
+    pub reclaimed_bytes: u64,
+    /// Entries pointing at a cover file that does not exist
+    pub missing_covers: Vec<MissingCover>,
+    /// Number of blob reference counts that were out of sync
+    pub repaired_ref_counts: usize,
+}
+
+/// Placeholder data derived from a cover image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverPlaceholder {
+    /// BlurHash string for rendering a blurred preview
+    pub blurhash: String,
+    /// Dominant colors as `#rrggbb`, most common first
+    pub palette: Vec<String>,
+}
+
+/// Aspect-ratio presets for cover cropping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AspectPreset {
+    /// 2:3 movie or book poster
+    Poster,
+    /// 1:1 album cover
+    Album,
+    /// 16:9 screenshot or backdrop
+    Widescreen,
+}
+
+impl AspectPreset {
+    /// Returns the ratio as (width, height).
+    pub fn ratio(&self) -> (u32, u32) {
+        match self {
+            AspectPreset::Poster => (2, 3),
+            AspectPreset::Album => (1, 1),
+            AspectPreset::Widescreen => (16, 9),
+        }
+    }
+}
+
+/// Crop region, applied after rotation and flipping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CropRegion {
+    /// Explicit rectangle in pixels
+    Rect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Largest centered rectangle with the preset's aspect ratio
+    Aspect { preset: AspectPreset },
+}
+
+/// Edits applied to a cover image on import or later editing.
+///
+/// Steps run in order: EXIF auto-orientation, rotation, flipping, cropping,
+/// then downscaling to `max_dimension`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageTransform {
+    /// Apply the EXIF orientation tag to the pixels
+    pub auto_orient: bool,
+    /// Clockwise rotation in degrees (0, 90, 180 or 270)
+    pub rotate: u16,
+    /// Mirror left to right
+    pub flip_horizontal: bool,
+    /// Mirror top to bottom
+    pub flip_vertical: bool,
+    /// Optional crop region
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crop: Option<CropRegion>,
+    /// Longest side after import; larger images are downscaled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+}
+
+impl Default for ImageTransform {
+    fn default() -> Self {
+        Self {
+            auto_orient: true,
+            rotate: 0,
+            flip_horizontal: false,
+            flip_vertical: false,
+            crop: None,
+            max_dimension: Some(super::processor::DEFAULT_MAX_COVER_DIMENSION),
+        }
+    }
+}
+
+/// Image metadata tags that can be extracted and mapped to fields.
+///
+/// Names follow the EXIF tag names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ImageTag {
+    Make,
+    Model,
+    LensModel,
+    DateTimeOriginal,
+    ImageWidth,
+    ImageHeight,
+    FNumber,
+    ExposureTime,
+    Iso,
+    FocalLength,
+    GpsLatitude,
+    GpsLongitude,
+    GpsAltitude,
+}
+
+impl ImageTag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageTag::Make => "Make",
+            ImageTag::Model => "Model",
+            ImageTag::LensModel => "LensModel",
+            ImageTag::DateTimeOriginal => "DateTimeOriginal",
+            ImageTag::ImageWidth => "ImageWidth",
+            ImageTag::ImageHeight => "ImageHeight",
+            ImageTag::FNumber => "FNumber",
+            ImageTag::ExposureTime => "ExposureTime",
+            ImageTag::Iso => "Iso",
+            ImageTag::FocalLength => "FocalLength",
+            ImageTag::GpsLatitude => "GpsLatitude",
+            ImageTag::GpsLongitude => "GpsLongitude",
+            ImageTag::GpsAltitude => "GpsAltitude",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "Make" => Some(ImageTag::Make),
+            "Model" => Some(ImageTag::Model),
+            "LensModel" => Some(ImageTag::LensModel),
+            "DateTimeOriginal" => Some(ImageTag::DateTimeOriginal),
+            "ImageWidth" => Some(ImageTag::ImageWidth),
+            "ImageHeight" => Some(ImageTag::ImageHeight),
+            "FNumber" => Some(ImageTag::FNumber),
+            "ExposureTime" => Some(ImageTag::ExposureTime),
+            "Iso" => Some(ImageTag::Iso),
+            "FocalLength" => Some(ImageTag::FocalLength),
+            "GpsLatitude" => Some(ImageTag::GpsLatitude),
+            "GpsLongitude" => Some(ImageTag::GpsLongitude),
+            "GpsAltitude" => Some(ImageTag::GpsAltitude),
+            _ => None,
+        }
+    }
+}
+
+/// Value of an extracted image tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageTagValue {
+    Text(String),
+    Number(f64),
+    DateTime(chrono::NaiveDateTime),
+}
+
+impl Serialize for ImageTagValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ImageTagValue::Text(text) => serializer.serialize_str(text),
+            ImageTagValue::Number(number) => serializer.serialize_f64(*number),
+            ImageTagValue::DateTime(date_time) => {
+                serializer.serialize_str(&date_time.format("%Y-%m-%dT%H:%M:%S").to_string())
+            }
+        }
+    }
+}
+
+/// Metadata read from an image file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImageMetadata {
+    pub tags: std::collections::BTreeMap<ImageTag, ImageTagValue>,
+}
+
+/// Maps an image metadata tag to a vault field definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageTagMapping {
+    pub tag: ImageTag,
+    pub field_id: i32,
+}
@@ -0,0 +1,227 @@
+//! BlurHash and dominant-color extraction for cover placeholders.
+//!
+//! Both work on a small downscaled copy of the image, so the cost does not
+//! depend on the size of the original.
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use image::{DynamicImage, GenericImageView};
+
+/// Size of the downscaled copy used for extraction.
+const SAMPLE_SIZE: u32 = 64;
+
+/// BlurHash components along the horizontal and vertical axes.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Maximum number of colors in a palette.
+const PALETTE_SIZE: usize = 5;
+
+/// Bits kept per channel when bucketing colors.
+const PALETTE_BITS: u8 = 4;
+
+const BASE83_CHARS: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Downscales an image for placeholder extraction.
+pub fn sample(img: &DynamicImage) -> DynamicImage {
+    img.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+}
+
+/// Encodes an image as a BlurHash string.
+pub fn blurhash(img: &DynamicImage) -> String {
+    let (components_x, components_y) = BLURHASH_COMPONENTS;
+    let (width, height) = img.dimensions();
+    let rgb = img.to_rgb8();
+
+    let mut factors = Vec::with_capacity((components_x * components_y) as usize);
+    for j in 0..components_y {
+        for i in 0..components_x {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut factor = [0.0f32; 3];
+
+            for (x, y, pixel) in rgb.enumerate_pixels() {
+                let basis = normalisation
+                    * (PI * i as f32 * x as f32 / width as f32).cos()
+                    * (PI * j as f32 * y as f32 / height as f32).cos();
+                for (channel, value) in factor.iter_mut().zip(pixel.0) {
+                    *channel += basis * srgb_to_linear(value);
+                }
+            }
+
+            let scale = 1.0 / (width * height) as f32;
+            factors.push(factor.map(|channel| channel * scale));
+        }
+    }
+
+    let mut hash = String::with_capacity(28);
+    encode_base83((components_x - 1) + (components_y - 1) * 9, 1, &mut hash);
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let maximum_value = if ac.is_empty() {
+        encode_base83(0, 1, &mut hash);
+        1.0
+    } else {
+        let actual_maximum = ac
+            .iter()
+            .flat_map(|factor| factor.iter())
+            .fold(0.0f32, |max, value| max.max(value.abs()));
+        let quantised = (actual_maximum * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        encode_base83(quantised, 1, &mut hash);
+        (quantised + 1) as f32 / 166.0
+    };
+
+    let dc_value = (linear_to_srgb(dc[0]) << 16) | (linear_to_srgb(dc[1]) << 8) | linear_to_srgb(dc[2]);
+    encode_base83(dc_value, 4, &mut hash);
+
+    for factor in ac {
+        let quantise = |value: f32| {
+            (sign_pow(value / maximum_value, 0.5) * 9.0 + 9.5)
+                .floor()
+                .clamp(0.0, 18.0) as u32
+        };
+        let value = quantise(factor[0]) * 19 * 19 + quantise(factor[1]) * 19 + quantise(factor[2]);
+        encode_base83(value, 2, &mut hash);
+    }
+
+    hash
+}
+
+/// Extracts the dominant colors of an image as `#rrggbb` strings.
+///
+/// Pixels are bucketed by their high bits; the busiest buckets are returned
+/// most common first, each as the average of its pixels. Transparent pixels
+/// are ignored.
+pub fn dominant_colors(img: &DynamicImage) -> Vec<String> {
+    let shift = 8 - PALETTE_BITS;
+    let mut buckets: HashMap<(u8, u8, u8), (u64, [u64; 3])> = HashMap::new();
+
+    for (_, _, pixel) in img.to_rgba8().enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+
+        let bucket = buckets
+            .entry((r >> shift, g >> shift, b >> shift))
+            .or_insert((0, [0; 3]));
+        bucket.0 += 1;
+        bucket.1[0] += r as u64;
+        bucket.1[1] += g as u64;
+        bucket.1[2] += b as u64;
+    }
+
+    let mut buckets: Vec<_> = buckets.into_iter().collect();
+    // Ties are broken by bucket key so the palette is deterministic
+    buckets.sort_by(|(key_a, (count_a, _)), (key_b, (count_b, _))| {
+        count_b.cmp(count_a).then(key_a.cmp(key_b))
+    });
+
+    buckets
+        .into_iter()
+        .take(PALETTE_SIZE)
+        .map(|(_, (count, sums))| {
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                sums[0] / count,
+                sums[1] / count,
+                sums[2] / count
+            )
+        })
+        .collect()
+}
+
+fn encode_base83(value: u32, length: u32, out: &mut String) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        out.push(BASE83_CHARS[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u32 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.003_130_8 {
+        (v * 12.92 * 255.0 + 0.5) as u32
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u32
+    }
+}
+
+fn sign_pow(value: f32, exponent: f32) -> f32 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn decode_base83(chars: &str) -> u32 {
+        chars.bytes().fold(0, |value, c| {
+            value * 83 + BASE83_CHARS.iter().position(|&b| b == c).unwrap() as u32
+        })
+    }
+
+    #[test]
+    fn test_blurhash_solid_color() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(32, 32, Rgb([200, 40, 90])));
+        let hash = blurhash(&img);
+
+        // Size flag, max AC, 4-char DC and 11 two-char AC components
+        assert_eq!(hash.len(), 28);
+        assert_eq!(&hash[..1], "L");
+
+        // The DC component carries the average color
+        let dc = decode_base83(&hash[2..6]);
+        assert_eq!((dc >> 16, (dc >> 8) & 0xFF, dc & 0xFF), (200, 40, 90));
+
+        assert!(hash.bytes().all(|c| BASE83_CHARS.contains(&c)));
+    }
+
+    #[test]
+    fn test_blurhash_is_deterministic() {
+        let mut img = RgbImage::new(40, 20);
+        for (x, _, pixel) in img.enumerate_pixels_mut() {
+            *pixel = Rgb([(x * 6) as u8, 80, 160]);
+        }
+        let img = DynamicImage::ImageRgb8(img);
+
+        assert_eq!(blurhash(&img), blurhash(&img));
+        assert_ne!(blurhash(&img), blurhash(&img.fliph()));
+    }
+
+    #[test]
+    fn test_dominant_colors_ordered_by_coverage() {
+        let mut img = RgbImage::from_pixel(10, 10, Rgb([0, 0, 255]));
+        for y in 0..3 {
+            for x in 0..10 {
+                img.put_pixel(x, y, Rgb([255, 0, 0]));
+            }
+        }
+
+        let colors = dominant_colors(&DynamicImage::ImageRgb8(img));
+        assert_eq!(colors, vec!["#0000ff", "#ff0000"]);
+    }
+
+    #[test]
+    fn test_dominant_colors_limited_and_skip_transparent() {
+        let mut img = image::RgbaImage::new(10, 10);
+        for (x, y, pixel) in img.enumerate_pixels_mut() {
+            *pixel = image::Rgba([(x * 25) as u8, (y * 25) as u8, 0, 255]);
+        }
+        img.put_pixel(0, 0, image::Rgba([255, 255, 255, 0]));
+
+        let colors = dominant_colors(&DynamicImage::ImageRgba8(img));
+        assert_eq!(colors.len(), PALETTE_SIZE);
+        assert!(!colors.contains(&"#ffffff".to_string()));
+    }
+}
@@ -1,18 +1,37 @@
-//! Image processing for thumbnail generation.
+//! Image processing for decoding, validation and thumbnail generation.
 
 use image::imageops::FilterType;
-use image::{DynamicImage, GenericImageView};
+use image::metadata::Orientation;
+use image::{DynamicImage, GenericImageView, ImageDecoder, ImageFormat, ImageReader, Limits};
 use std::io::Cursor;
 use std::path::Path;
 
 use crate::core::{AppError, AppResult};
 
+use super::{metadata, placeholder};
+use super::{CoverPlaceholder, CropRegion, ImageMetadata, ImageTransform};
+
 /// Maximum thumbnail dimensions (maintains aspect ratio).
 const THUMBNAIL_MAX_SIZE: u32 = 300;
 
 /// JPEG quality for thumbnails (0-100).
 const THUMBNAIL_JPEG_QUALITY: u8 = 85;
 
+/// Default longest side for imported covers; larger originals are downscaled.
+pub const DEFAULT_MAX_COVER_DIMENSION: u32 = 2048;
+
+/// JPEG quality for edited covers (0-100).
+const COVER_JPEG_QUALITY: u8 = 92;
+
+/// Side of the grayscale grid compared for perceptual hashes (8x8 = 64 bits).
+const PHASH_SIZE: u32 = 8;
+
+/// Maximum accepted image width or height in pixels.
+pub const MAX_IMAGE_DIMENSION: u32 = 16_384;
+
+/// Maximum memory a single decode may allocate (256MB).
+pub const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
 /// Image processor for thumbnail generation and format conversion.
 pub struct ImageProcessor;
 
@@ -23,9 +42,7 @@ impl ImageProcessor {
         log::debug!("Generating thumbnail for: {}", source_path.display());
 
         // Load the image
-        let img = image::open(source_path).map_err(|e| {
-            AppError::Internal(format!("Failed to open image for thumbnail generation: {}", e))
-        })?;
+        let img = Self::open_limited(source_path)?;
 
         // Generate thumbnail
         let thumbnail = Self::resize_image(img, THUMBNAIL_MAX_SIZE)?;
@@ -41,6 +58,289 @@ impl ImageProcessor {
         Ok(jpeg_bytes)
     }
 
+    /// Computes the BlurHash and dominant-color palette of an image.
+    pub fn extract_placeholder(img: &DynamicImage) -> CoverPlaceholder {
+        let sample = placeholder::sample(img);
+
+        CoverPlaceholder {
+            blurhash: placeholder::blurhash(&sample),
+            palette: placeholder::dominant_colors(&sample),
+        }
+    }
+
+    /// Computes a 64-bit difference hash (dHash) of an image.
+    ///
+    /// Visually similar images (re-encoded, resized or lightly edited) have
+    /// hashes within a small Hamming distance of each other.
+    pub fn perceptual_hash(img: &DynamicImage) -> u64 {
+        let gray = img
+            .resize_exact(PHASH_SIZE + 1, PHASH_SIZE, FilterType::Triangle)
+            .to_luma8();
+
+        let mut hash = 0u64;
+        for y in 0..PHASH_SIZE {
+            for x in 0..PHASH_SIZE {
+                let brighter = gray.get_pixel(x, y).0[0] > gray.get_pixel(x + 1, y).0[0];
+                hash = (hash << 1) | brighter as u64;
+            }
+        }
+        hash
+    }
+
+    /// Number of differing bits between two perceptual hashes.
+    pub fn hamming_distance(a: u64, b: u64) -> u32 {
+        (a ^ b).count_ones()
+    }
+
+    /// Reads EXIF/XMP metadata and dimensions from image bytes.
+    ///
+    /// Missing or unreadable metadata yields an empty result.
+    pub fn extract_metadata(bytes: &[u8]) -> ImageMetadata {
+        metadata::extract(bytes)
+    }
+
+    /// Formats a perceptual hash as 16 hex digits for storage.
+    pub fn format_hash(hash: u64) -> String {
+        format!("{:016x}", hash)
+    }
+
+    /// Parses a stored perceptual hash.
+    pub fn parse_hash(hash: &str) -> Option<u64> {
+        u64::from_str_radix(hash, 16).ok()
+    }
+
+    /// Decodes image bytes of any supported format with limits applied.
+    pub fn decode_bytes(bytes: &[u8]) -> AppResult<DynamicImage> {
+        let format = Self::sniff_format(bytes).ok_or_else(|| {
+            AppError::Validation(
+                "Invalid image format. Supported: JPEG, PNG, WebP, GIF".to_string(),
+            )
+        })?;
+
+        Self::decode_limited(bytes, format)
+    }
+
+    /// Identifies a supported image format from its magic bytes.
+    ///
+    /// Only JPEG, PNG, WebP and GIF signatures are recognized; anything else
+    /// (including SVG, HTML or RIFF containers that are not WebP) is rejected.
+    pub fn sniff_format(bytes: &[u8]) -> Option<ImageFormat> {
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(ImageFormat::Png)
+        } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(ImageFormat::WebP)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+
+    /// Decoder limits applied to every image read from disk or the network.
+    pub fn decode_limits() -> Limits {
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+        limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+        limits.max_alloc = Some(MAX_DECODE_ALLOC);
+        limits
+    }
+
+    /// Rejects dimensions that exceed the decode limits.
+    ///
+    /// Runs on header data only, before any pixel buffer is allocated.
+    pub fn check_dimensions(width: u32, height: u32) -> AppResult<()> {
+        if width == 0 || height == 0 {
+            return Err(AppError::Validation(
+                "Image has zero width or height".to_string(),
+            ));
+        }
+
+        if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+            return Err(AppError::Validation(format!(
+                "Image dimensions {}x{} exceed the {}px limit",
+                width, height, MAX_IMAGE_DIMENSION
+            )));
+        }
+
+        // Decoded images use at most 4 channels of 16 bits each
+        let estimated_bytes = width as u64 * height as u64 * 8;
+        if estimated_bytes > MAX_DECODE_ALLOC {
+            return Err(AppError::Validation(format!(
+                "Image {}x{} would need more than {}MB to decode",
+                width,
+                height,
+                MAX_DECODE_ALLOC / (1024 * 1024)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Decodes image bytes in the given format with dimension and memory limits.
+    pub fn decode_limited(bytes: &[u8], format: ImageFormat) -> AppResult<DynamicImage> {
+        let (width, height) = ImageReader::with_format(Cursor::new(bytes), format)
+            .into_dimensions()
+            .map_err(|e| AppError::Validation(format!("Failed to read image header: {}", e)))?;
+
+        Self::check_dimensions(width, height)?;
+
+        let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+        reader.limits(Self::decode_limits());
+        reader
+            .decode()
+            .map_err(|e| AppError::Validation(format!("Failed to decode image: {}", e)))
+    }
+
+    /// Opens an image file after sniffing its format and applying decode limits.
+    pub fn open_limited(path: &Path) -> AppResult<DynamicImage> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| AppError::Internal(format!("Failed to read image file: {}", e)))?;
+
+        let format = Self::sniff_format(&bytes).ok_or_else(|| {
+            AppError::Validation(
+                "Invalid image format. Supported: JPEG, PNG, WebP, GIF".to_string(),
+            )
+        })?;
+
+        Self::decode_limited(&bytes, format)
+    }
+
+    /// Reads the EXIF orientation of an image, defaulting to no transform.
+    fn read_orientation(bytes: &[u8], format: ImageFormat) -> Orientation {
+        ImageReader::with_format(Cursor::new(bytes), format)
+            .into_decoder()
+            .and_then(|mut decoder| decoder.orientation())
+            .unwrap_or(Orientation::NoTransforms)
+    }
+
+    /// Applies a transform pipeline to encoded image bytes.
+    ///
+    /// Returns `None` when the transform leaves the image unchanged, so the
+    /// original bytes can be stored as-is. Edited JPEGs stay JPEG; every
+    /// other format is re-encoded as PNG.
+    pub fn apply_transform(bytes: &[u8], transform: &ImageTransform) -> AppResult<Option<Vec<u8>>> {
+        let format = Self::sniff_format(bytes).ok_or_else(|| {
+            AppError::Validation(
+                "Invalid image format. Supported: JPEG, PNG, WebP, GIF".to_string(),
+            )
+        })?;
+
+        if !matches!(transform.rotate, 0 | 90 | 180 | 270) {
+            return Err(AppError::Validation(format!(
+                "Invalid rotation {}: expected 0, 90, 180 or 270",
+                transform.rotate
+            )));
+        }
+
+        let mut img = Self::decode_limited(bytes, format)?;
+        let mut changed = false;
+
+        if transform.auto_orient {
+            let orientation = Self::read_orientation(bytes, format);
+            if !matches!(orientation, Orientation::NoTransforms) {
+                img.apply_orientation(orientation);
+                changed = true;
+            }
+        }
+
+        img = match transform.rotate {
+            90 => img.rotate90(),
+            180 => img.rotate180(),
+            270 => img.rotate270(),
+            _ => img,
+        };
+        changed |= transform.rotate != 0;
+
+        if transform.flip_horizontal {
+            img = img.fliph();
+            changed = true;
+        }
+
+        if transform.flip_vertical {
+            img = img.flipv();
+            changed = true;
+        }
+
+        if let Some(crop) = &transform.crop {
+            let (width, height) = img.dimensions();
+            let (x, y, crop_width, crop_height) = Self::crop_rect(width, height, crop)?;
+
+            if (x, y, crop_width, crop_height) != (0, 0, width, height) {
+                img = img.crop_imm(x, y, crop_width, crop_height);
+                changed = true;
+            }
+        }
+
+        if let Some(max_dimension) = transform.max_dimension {
+            if max_dimension == 0 {
+                return Err(AppError::Validation(
+                    "Maximum dimension must be positive".to_string(),
+                ));
+            }
+
+            let (width, height) = img.dimensions();
+            if width > max_dimension || height > max_dimension {
+                img = Self::resize_image(img, max_dimension)?;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        let encoded = match format {
+            ImageFormat::Jpeg => Self::encode_jpeg(img, COVER_JPEG_QUALITY)?,
+            _ => Self::encode_png(img)?,
+        };
+
+        Ok(Some(encoded))
+    }
+
+    /// Computes the crop rectangle `(x, y, width, height)` for an image.
+    fn crop_rect(width: u32, height: u32, crop: &CropRegion) -> AppResult<(u32, u32, u32, u32)> {
+        match *crop {
+            CropRegion::Rect {
+                x,
+                y,
+                width: crop_width,
+                height: crop_height,
+            } => {
+                let fits_x = x.checked_add(crop_width).is_some_and(|right| right <= width);
+                let fits_y = y.checked_add(crop_height).is_some_and(|bottom| bottom <= height);
+
+                if crop_width == 0 || crop_height == 0 || !fits_x || !fits_y {
+                    return Err(AppError::Validation(format!(
+                        "Crop {}x{} at ({}, {}) is outside the {}x{} image",
+                        crop_width, crop_height, x, y, width, height
+                    )));
+                }
+
+                Ok((x, y, crop_width, crop_height))
+            }
+            CropRegion::Aspect { preset } => {
+                let (ratio_w, ratio_h) = preset.ratio();
+                let (w, h) = (width as u64, height as u64);
+
+                // Keep the full height if the image is wider than the ratio, else the full width
+                let (crop_width, crop_height) = if w * ratio_h as u64 > h * ratio_w as u64 {
+                    ((h * ratio_w as u64 / ratio_h as u64).max(1), h)
+                } else {
+                    (w, (w * ratio_h as u64 / ratio_w as u64).max(1))
+                };
+
+                Ok((
+                    ((w - crop_width) / 2) as u32,
+                    ((h - crop_height) / 2) as u32,
+                    crop_width as u32,
+                    crop_height as u32,
+                ))
+            }
+        }
+    }
+
     /// Resizes an image to fit within the given max dimension while maintaining aspect ratio.
     fn resize_image(img: DynamicImage, max_size: u32) -> AppResult<DynamicImage> {
         let (width, height) = img.dimensions();
@@ -89,6 +389,16 @@ impl ImageProcessor {
         Ok(buffer.into_inner())
     }
 
+    /// Encodes an image as PNG.
+    fn encode_png(img: DynamicImage) -> AppResult<Vec<u8>> {
+        let mut buffer = Cursor::new(Vec::new());
+
+        img.write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|e| AppError::Internal(format!("Failed to encode image as PNG: {}", e)))?;
+
+        Ok(buffer.into_inner())
+    }
+
     /// Converts thumbnail bytes to base64 for frontend display.
     pub fn to_base64(bytes: &[u8]) -> String {
         use base64::{engine::general_purpose, Engine as _};
@@ -104,6 +414,7 @@ impl ImageProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::image::{corpus, AspectPreset};
     use std::fs;
 
     /// Helper function to create a temporary test image file.
@@ -278,4 +589,210 @@ mod tests {
         let result = ImageProcessor::generate_thumbnail(&nonexistent);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_sniff_format() {
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(4, 4)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+
+        assert_eq!(
+            ImageProcessor::sniff_format(&png.into_inner()),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(ImageProcessor::sniff_format(b"GIF89a...."), Some(ImageFormat::Gif));
+        assert_eq!(ImageProcessor::sniff_format(&corpus::svg_document()), None);
+        assert_eq!(ImageProcessor::sniff_format(&corpus::riff_wave()), None);
+        assert_eq!(ImageProcessor::sniff_format(&[]), None);
+    }
+
+    #[test]
+    fn test_check_dimensions() {
+        assert!(ImageProcessor::check_dimensions(1920, 1080).is_ok());
+        assert!(ImageProcessor::check_dimensions(0, 100).is_err());
+        assert!(ImageProcessor::check_dimensions(MAX_IMAGE_DIMENSION + 1, 10).is_err());
+        // Within the per-side limit but over the memory budget
+        assert!(ImageProcessor::check_dimensions(16_000, 16_000).is_err());
+    }
+
+    #[test]
+    fn test_decode_limited_rejects_bombs() {
+        for bytes in [
+            corpus::png_oversized_dimensions(),
+            corpus::png_allocation_bomb(),
+        ] {
+            let result = ImageProcessor::decode_limited(&bytes, ImageFormat::Png);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_generate_thumbnail_rejects_bomb_on_disk() {
+        let temp_dir = std::env::temp_dir();
+        let bomb_file = temp_dir.join("test_thumbnail_bomb.png");
+
+        fs::write(&bomb_file, corpus::png_oversized_dimensions()).unwrap();
+
+        let result = ImageProcessor::generate_thumbnail(&bomb_file);
+        assert!(result.is_err());
+
+        // Clean up
+        let _ = fs::remove_file(bomb_file);
+    }
+
+    /// Encodes a blank image in the given format.
+    fn encode_test_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        encode_test_image_from(&DynamicImage::new_rgb8(width, height), format)
+    }
+
+    fn encode_test_image_from(img: &DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buffer, format).unwrap();
+        buffer.into_inner()
+    }
+
+    fn transformed_dimensions(bytes: &[u8], transform: &ImageTransform) -> (u32, u32) {
+        let output = ImageProcessor::apply_transform(bytes, transform)
+            .unwrap()
+            .expect("transform should change the image");
+        image::load_from_memory(&output).unwrap().dimensions()
+    }
+
+    #[test]
+    fn test_apply_transform_unchanged_returns_none() {
+        let bytes = encode_test_image(400, 300, ImageFormat::Png);
+
+        let result = ImageProcessor::apply_transform(&bytes, &ImageTransform::default()).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_apply_transform_crop_rect() {
+        let bytes = encode_test_image(400, 300, ImageFormat::Png);
+        let transform = ImageTransform {
+            crop: Some(CropRegion::Rect {
+                x: 50,
+                y: 20,
+                width: 200,
+                height: 100,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(transformed_dimensions(&bytes, &transform), (200, 100));
+    }
+
+    #[test]
+    fn test_apply_transform_crop_out_of_bounds() {
+        let bytes = encode_test_image(400, 300, ImageFormat::Png);
+        let transform = ImageTransform {
+            crop: Some(CropRegion::Rect {
+                x: 300,
+                y: 0,
+                width: 200,
+                height: 100,
+            }),
+            ..Default::default()
+        };
+
+        assert!(ImageProcessor::apply_transform(&bytes, &transform).is_err());
+    }
+
+    #[test]
+    fn test_apply_transform_aspect_presets() {
+        let bytes = encode_test_image(600, 600, ImageFormat::Png);
+
+        let poster = ImageTransform {
+            crop: Some(CropRegion::Aspect {
+                preset: AspectPreset::Poster,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(transformed_dimensions(&bytes, &poster), (400, 600));
+
+        let widescreen = ImageTransform {
+            crop: Some(CropRegion::Aspect {
+                preset: AspectPreset::Widescreen,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(transformed_dimensions(&bytes, &widescreen), (600, 337));
+    }
+
+    #[test]
+    fn test_apply_transform_rotate_swaps_dimensions() {
+        let bytes = encode_test_image(400, 300, ImageFormat::Jpeg);
+        let transform = ImageTransform {
+            rotate: 90,
+            ..Default::default()
+        };
+
+        let output = ImageProcessor::apply_transform(&bytes, &transform)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ImageProcessor::sniff_format(&output), Some(ImageFormat::Jpeg));
+        assert_eq!(
+            image::load_from_memory(&output).unwrap().dimensions(),
+            (300, 400)
+        );
+    }
+
+    #[test]
+    fn test_apply_transform_invalid_rotation() {
+        let bytes = encode_test_image(100, 100, ImageFormat::Png);
+        let transform = ImageTransform {
+            rotate: 45,
+            ..Default::default()
+        };
+
+        assert!(ImageProcessor::apply_transform(&bytes, &transform).is_err());
+    }
+
+    #[test]
+    fn test_apply_transform_downscales_large_images() {
+        let bytes = encode_test_image(3000, 1500, ImageFormat::Png);
+
+        assert_eq!(
+            transformed_dimensions(&bytes, &ImageTransform::default()),
+            (DEFAULT_MAX_COVER_DIMENSION, DEFAULT_MAX_COVER_DIMENSION / 2)
+        );
+
+        let zero = ImageTransform {
+            max_dimension: Some(0),
+            ..Default::default()
+        };
+        assert!(ImageProcessor::apply_transform(&bytes, &zero).is_err());
+    }
+
+    #[test]
+    fn test_perceptual_hash_similar_images() {
+        let mut img = image::RgbImage::new(200, 300);
+        for (x, y, pixel) in img.enumerate_pixels_mut() {
+            *pixel = image::Rgb([(x + y) as u8, (x * 2) as u8, (255 - y % 256) as u8]);
+        }
+        let img = DynamicImage::ImageRgb8(img);
+
+        // Re-encoded and resized copies stay close
+        let jpeg = encode_test_image_from(&img, ImageFormat::Jpeg);
+        let resized = img.resize(100, 150, FilterType::Lanczos3);
+
+        let hash = ImageProcessor::perceptual_hash(&img);
+        let jpeg_hash = ImageProcessor::perceptual_hash(&image::load_from_memory(&jpeg).unwrap());
+        let resized_hash = ImageProcessor::perceptual_hash(&resized);
+
+        assert!(ImageProcessor::hamming_distance(hash, jpeg_hash) <= 4);
+        assert!(ImageProcessor::hamming_distance(hash, resized_hash) <= 4);
+
+        // A mirrored image is a different cover
+        let flipped_hash = ImageProcessor::perceptual_hash(&img.fliph());
+        assert!(ImageProcessor::hamming_distance(hash, flipped_hash) > 10);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(ImageProcessor::hamming_distance(0, 0), 0);
+        assert_eq!(ImageProcessor::hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(ImageProcessor::hamming_distance(u64::MAX, 0), 64);
+    }
 }
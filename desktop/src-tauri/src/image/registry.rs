@@ -0,0 +1,381 @@
+//! Reference counting for content-addressed images.
+//!
+//! Every stored image has a row in `image_blobs` counting how many entries
+//! use it. References are taken and dropped in the same transaction as the
+//! entry update, files are removed from disk once the last reference is gone
+//! and that transaction has committed, and garbage collection reconciles the
+//! table with the disk.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseBackend,
+    DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, Set, Statement,
+};
+
+use crate::core::{now_formatted, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::image_blob::{self, ActiveModel, Entity as ImageBlob};
+
+use super::{ImageGcReport, ImageStorage, MissingCover, StoredImage};
+
+/// Registry tracking references to stored images.
+pub struct ImageRegistry;
+
+impl ImageRegistry {
+    /// Registers a stored image so its references can be counted.
+    ///
+    /// New blobs start without references; existing blobs are left untouched.
+    pub async fn register<C: ConnectionTrait>(conn: &C, stored: &StoredImage) -> AppResult<()> {
+        conn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            r#"
+            INSERT INTO image_blobs (hash, path, size, ref_count, created_at)
+            VALUES (?, ?, ?, 0, ?)
+            ON CONFLICT(hash) DO NOTHING
+            "#,
+            [
+                stored.hash.clone().into(),
+                stored.relative_path.clone().into(),
+                (stored.size as i64).into(),
+                now_formatted().into(),
+            ],
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a new reference to a registered image.
+    ///
+    /// Remote URLs and legacy files without a blob row are not counted.
+    pub async fn acquire<C: ConnectionTrait>(conn: &C, relative_path: &str) -> AppResult<()> {
+        conn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            "UPDATE image_blobs SET ref_count = ref_count + 1 WHERE path = ?",
+            [relative_path.into()],
+        ))
+        .await?;
+
+        log::debug!("Acquired image reference: {}", relative_path);
+        Ok(())
+    }
+
+    /// Releases a reference to a stored image.
+    ///
+    /// Returns whether nothing references the file anymore, in which case it
+    /// is to be deleted once the change is committed. Remote URLs are
+    /// ignored. Legacy per-entry files without a blob row are unreferenced
+    /// when no entry points at them anymore.
+    pub async fn release<C: ConnectionTrait>(conn: &C, relative_path: &str) -> AppResult<bool> {
+        if ImageStorage::is_remote(relative_path) {
+            return Ok(false);
+        }
+
+        let blob = ImageBlob::find()
+            .filter(image_blob::Column::Path.eq(relative_path))
+            .one(conn)
+            .await?;
+
+        let unreferenced = match blob {
+            Some(blob) if blob.ref_count > 1 => {
+                let ref_count = blob.ref_count - 1;
+                let mut active_model: ActiveModel = blob.into();
+                active_model.ref_count = Set(ref_count);
+                active_model.update(conn).await?;
+                log::debug!(
+                    "Released image reference: {} ({} remaining)",
+                    relative_path,
+                    ref_count
+                );
+                false
+            }
+            Some(blob) => {
+                ImageBlob::delete_by_id(blob.hash).exec(conn).await?;
+                true
+            }
+            None => {
+                let still_referenced = Entry::find()
+                    .filter(
+                        Condition::any()
+                            .add(entry::Column::CoverImagePath.eq(relative_path))
+                            .add(entry::Column::CoverOriginalPath.eq(relative_path)),
+                    )
+                    .count(conn)
+                    .await?
+                    > 0;
+
+                !still_referenced
+            }
+        };
+
+        Ok(unreferenced)
+    }
+
+    /// Finds unreferenced image files and entries with missing covers.
+    ///
+    /// Also repairs `image_blobs` reference counts from the entries table.
+    /// When `dry_run` is true nothing is modified.
+    pub async fn collect_garbage(
+        conn: &DatabaseConnection,
+        image_storage: &ImageStorage,
+        dry_run: bool,
+    ) -> AppResult<ImageGcReport> {
+        log::info!("Collecting image garbage (dry_run={})", dry_run);
+
+        // Count references per local cover and kept-original path
+        let entries = Entry::find()
+            .filter(
+                Condition::any()
+                    .add(entry::Column::CoverImagePath.is_not_null())
+                    .add(entry::Column::CoverOriginalPath.is_not_null()),
+            )
+            .all(conn)
+            .await?;
+
+        let mut references: HashMap<String, i32> = HashMap::new();
+        for entry in &entries {
+            for path in [&entry.cover_image_path, &entry.cover_original_path]
+                .into_iter()
+                .flatten()
+            {
+                if !ImageStorage::is_remote(path) {
+                    *references.entry(path.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // Remove files nobody references
+        let files = image_storage.list_files()?;
+        let file_paths: HashSet<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+
+        let mut unreferenced_files = Vec::new();
+        let mut reclaimed_bytes = 0;
+
+        for (path, size) in &files {
+            if references.contains_key(path) {
+                continue;
+            }
+
+            if !dry_run {
+                image_storage.delete_image(path)?;
+            }
+
+            unreferenced_files.push(path.clone());
+            reclaimed_bytes += size;
+        }
+
+        // Flag entries whose cover file is gone
+        let missing_covers: Vec<MissingCover> = entries
+            .iter()
+            .filter_map(|e| {
+                let path = e.cover_image_path.as_ref()?;
+                if ImageStorage::is_remote(path) || file_paths.contains(path.as_str()) {
+                    return None;
+                }
+                Some(MissingCover {
+                    entry_id: e.id,
+                    vault_id: e.vault_id,
+                    cover_image_path: path.clone(),
+                })
+            })
+            .collect();
+
+        // Reconcile blob reference counts
+        let blobs = ImageBlob::find().all(conn).await?;
+        let mut repaired_ref_counts = 0;
+
+        for blob in blobs {
+            let actual = references.get(&blob.path).copied().unwrap_or(0);
+            if actual == blob.ref_count {
+                continue;
+            }
+
+            repaired_ref_counts += 1;
+            if dry_run {
+                continue;
+            }
+
+            if actual == 0 {
+                ImageBlob::delete_by_id(blob.hash).exec(conn).await?;
+            } else {
+                let mut active_model: ActiveModel = blob.into();
+                active_model.ref_count = Set(actual);
+                active_model.update(conn).await?;
+            }
+        }
+
+        log::info!(
+            "Image GC: {} file(s) scanned, {} unreferenced ({} bytes), {} missing cover(s)",
+            files.len(),
+            unreferenced_files.len(),
+            reclaimed_bytes,
+            missing_covers.len()
+        );
+
+        Ok(ImageGcReport {
+            dry_run,
+            scanned_files: files.len(),
+            unreferenced_files,
+            reclaimed_bytes,
+            missing_covers,
+            repaired_ref_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::entry::ActiveModel as EntryActiveModel;
+    use sea_orm::Database;
+    use std::fs;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE image_blobs (
+                hash TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                ref_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            INSERT INTO vaults (name) VALUES ('Test Vault');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    async fn create_entry_with_cover(conn: &DatabaseConnection, cover: Option<&str>) -> i32 {
+        let now = now_formatted();
+        let entry = EntryActiveModel {
+            vault_id: Set(1),
+            title: Set("Entry".to_string()),
+            description: Set(None),
+            metadata: Set(None),
+            cover_image_path: Set(cover.map(|c| c.to_string())),
+            cover_original_path: Set(None),
+            cover_blurhash: Set(None),
+            cover_palette: Set(None),
+            cover_phash: Set(None),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+        entry.insert(conn).await.unwrap().id
+    }
+
+    fn store_test_image(storage: &ImageStorage, dir: &std::path::Path) -> StoredImage {
+        let source = dir.join("source.png");
+        image::DynamicImage::new_rgb8(32, 32).save(&source).unwrap();
+        storage.save_local_image(&source).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_release_reports_last_reference() {
+        let conn = setup_test_db().await;
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_registry_release");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let storage = ImageStorage::new(&temp_dir);
+        let stored = store_test_image(&storage, &temp_dir);
+
+        ImageRegistry::register(&conn, &stored).await.unwrap();
+        ImageRegistry::acquire(&conn, &stored.relative_path).await.unwrap();
+        ImageRegistry::acquire(&conn, &stored.relative_path).await.unwrap();
+
+        let unreferenced = ImageRegistry::release(&conn, &stored.relative_path)
+            .await
+            .unwrap();
+        assert!(!unreferenced);
+
+        let unreferenced = ImageRegistry::release(&conn, &stored.relative_path)
+            .await
+            .unwrap();
+        assert!(unreferenced);
+        assert_eq!(ImageBlob::find().count(&conn).await.unwrap(), 0);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage() {
+        let conn = setup_test_db().await;
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_registry_gc");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let storage = ImageStorage::new(&temp_dir);
+        let stored = store_test_image(&storage, &temp_dir);
+        ImageRegistry::register(&conn, &stored).await.unwrap();
+        ImageRegistry::acquire(&conn, &stored.relative_path).await.unwrap();
+
+        // Referenced twice, but the blob row only counts one reference
+        create_entry_with_cover(&conn, Some(&stored.relative_path)).await;
+        create_entry_with_cover(&conn, Some(&stored.relative_path)).await;
+
+        // Orphaned legacy file and an entry whose file is gone
+        fs::create_dir_all(temp_dir.join("images").join("1")).unwrap();
+        fs::write(temp_dir.join("images").join("1").join("9.jpg"), b"orphan").unwrap();
+        let missing_id = create_entry_with_cover(&conn, Some("1/404.jpg")).await;
+        create_entry_with_cover(&conn, Some("https://example.com/a.jpg")).await;
+
+        let report = ImageRegistry::collect_garbage(&conn, &storage, true)
+            .await
+            .unwrap();
+        assert_eq!(report.unreferenced_files, vec!["1/9.jpg".to_string()]);
+        assert_eq!(report.reclaimed_bytes, 6);
+        assert_eq!(report.missing_covers.len(), 1);
+        assert_eq!(report.missing_covers[0].entry_id, missing_id);
+        assert_eq!(report.repaired_ref_counts, 1);
+        assert!(storage.image_exists("1/9.jpg"));
+
+        let report = ImageRegistry::collect_garbage(&conn, &storage, false)
+            .await
+            .unwrap();
+        assert!(!report.dry_run);
+        assert!(!storage.image_exists("1/9.jpg"));
+        assert!(storage.image_exists(&stored.relative_path));
+
+        let blob = ImageBlob::find_by_id(stored.hash.clone())
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(blob.ref_count, 2);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,18 +1,40 @@
 //! Image storage management for file system operations.
 
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
 
+use sha2::{Digest, Sha256};
+
 use crate::core::{AppError, AppResult};
 
+use super::ImageProcessor;
+
 /// Maximum allowed image file size (10MB).
 const MAX_IMAGE_SIZE: u64 = 10 * 1024 * 1024;
 
+/// Directory under the images directory holding content-addressed blobs.
+const BLOB_DIR: &str = "blobs";
+
 /// Timeout for URL downloads (30 seconds).
 #[allow(dead_code)]
 const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Result of writing an image into content-addressed storage.
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    /// SHA-256 hex digest of the file contents
+    pub hash: String,
+    /// Path relative to the images directory (`blobs/ab/abcdef....jpg`)
+    pub relative_path: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
 /// Image storage handler.
+///
+/// Files are named after the SHA-256 of their contents and sharded under
+/// `blobs/` by the first two hex characters, so identical images are stored
+/// only once.
 pub struct ImageStorage {
     images_dir: PathBuf,
 }
@@ -24,29 +46,43 @@ impl ImageStorage {
         Self { images_dir }
     }
 
-    /// Gets the path for an entry's cover image.
-    fn get_image_path(&self, vault_id: i32, entry_id: i32, extension: &str) -> PathBuf {
-        self.images_dir
-            .join(vault_id.to_string())
-            .join(format!("{}.{}", entry_id, extension))
+    /// Returns true if a cover path is a remote URL rather than a stored file.
+    pub fn is_remote(path: &str) -> bool {
+        path.starts_with("http://") || path.starts_with("https://")
+    }
+
+    /// Gets the relative path for a content hash.
+    fn get_relative_path(hash: &str, extension: &str) -> String {
+        format!("{}/{}/{}.{}", BLOB_DIR, &hash[..2], hash, extension)
+    }
+
+    /// Gets the shard directory path for a content hash.
+    fn get_shard_dir(&self, hash: &str) -> PathBuf {
+        self.images_dir.join(BLOB_DIR).join(&hash[..2])
     }
 
-    /// Gets the vault directory path.
+    /// Gets the legacy per-vault directory path (`images/<vault_id>/`).
     fn get_vault_dir(&self, vault_id: i32) -> PathBuf {
         self.images_dir.join(vault_id.to_string())
     }
 
-    /// Ensures the vault directory exists.
-    fn ensure_vault_dir(&self, vault_id: i32) -> AppResult<()> {
-        let vault_dir = self.get_vault_dir(vault_id);
-        if !vault_dir.exists() {
-            std::fs::create_dir_all(&vault_dir).map_err(|e| {
-                AppError::Internal(format!("Failed to create vault image directory: {}", e))
+    /// Ensures the shard directory for a hash exists.
+    fn ensure_shard_dir(&self, hash: &str) -> AppResult<()> {
+        let shard_dir = self.get_shard_dir(hash);
+        if !shard_dir.exists() {
+            std::fs::create_dir_all(&shard_dir).map_err(|e| {
+                AppError::Internal(format!("Failed to create image directory: {}", e))
             })?;
         }
         Ok(())
     }
 
+    /// Computes the SHA-256 hex digest of the given bytes.
+    fn hash_bytes(bytes: &[u8]) -> String {
+        let digest = Sha256::digest(bytes);
+        format!("{:x}", digest)
+    }
+
     /// Validates image file size.
     fn validate_file_size(path: &Path) -> AppResult<()> {
         let metadata = std::fs::metadata(path)
@@ -62,74 +98,104 @@ impl ImageStorage {
         Ok(())
     }
 
-    /// Detects the image format and returns the file extension.
-    fn detect_format(path: &Path) -> AppResult<String> {
-        let format = image::ImageReader::open(path)
-            .map_err(|e| AppError::Validation(format!("Failed to read image file: {}", e)))?
-            .format();
+    /// Validates image bytes and returns the file extension.
+    ///
+    /// The format comes from the magic bytes, never from the file name, and
+    /// the image must fully decode within the processor's limits so that
+    /// truncated files, polyglots and decompression bombs are rejected.
+    fn detect_format_bytes(bytes: &[u8]) -> AppResult<String> {
+        let format = ImageProcessor::sniff_format(bytes).ok_or_else(|| {
+            AppError::Validation(
+                "Invalid image format. Supported: JPEG, PNG, WebP, GIF".to_string(),
+            )
+        })?;
+
+        ImageProcessor::decode_limited(bytes, format)?;
 
         match format {
-            Some(image::ImageFormat::Jpeg) => Ok("jpg".to_string()),
-            Some(image::ImageFormat::Png) => Ok("png".to_string()),
-            Some(image::ImageFormat::WebP) => Ok("webp".to_string()),
-            Some(image::ImageFormat::Gif) => Ok("gif".to_string()),
+            image::ImageFormat::Jpeg => Ok("jpg".to_string()),
+            image::ImageFormat::Png => Ok("png".to_string()),
+            image::ImageFormat::WebP => Ok("webp".to_string()),
+            image::ImageFormat::Gif => Ok("gif".to_string()),
             _ => Err(AppError::Validation(
                 "Invalid image format. Supported: JPEG, PNG, WebP, GIF".to_string(),
             )),
         }
     }
 
-    /// Saves a local image file to the storage.
-    pub fn save_local_image(
-        &self,
-        vault_id: i32,
-        entry_id: i32,
-        source_path: &Path,
-    ) -> AppResult<String> {
-        log::info!(
-            "Saving local image for vault {} entry {}",
-            vault_id,
-            entry_id
-        );
+    /// Writes image bytes under their content hash.
+    /// Skips the write when an identical file is already stored.
+    fn store_bytes(&self, bytes: &[u8], extension: &str) -> AppResult<StoredImage> {
+        let hash = Self::hash_bytes(bytes);
+        let relative_path = Self::get_relative_path(&hash, extension);
+        let dest_path = self.images_dir.join(&relative_path);
 
+        if dest_path.exists() {
+            log::info!("Image already stored, reusing: {}", relative_path);
+        } else {
+            self.ensure_shard_dir(&hash)?;
+
+            // Write to a temporary name first so a crash never leaves a truncated blob
+            let temp_path = dest_path.with_extension("tmp");
+            std::fs::write(&temp_path, bytes)
+                .map_err(|e| AppError::Internal(format!("Failed to write image file: {}", e)))?;
+            std::fs::rename(&temp_path, &dest_path).map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                AppError::Internal(format!("Failed to move image to final location: {}", e))
+            })?;
+
+            log::info!("Image saved to: {}", relative_path);
+        }
+
+        Ok(StoredImage {
+            hash,
+            relative_path,
+            size: bytes.len() as u64,
+        })
+    }
+
+    /// Reads a local image file, enforcing the upload size limit.
+    pub fn read_local_image(source_path: &Path) -> AppResult<Vec<u8>> {
         // Validate file size
         Self::validate_file_size(source_path)?;
 
-        // Detect image format
-        let extension = Self::detect_format(source_path)?;
+        std::fs::read(source_path)
+            .map_err(|e| AppError::Internal(format!("Failed to read image file: {}", e)))
+    }
 
-        // Ensure vault directory exists
-        self.ensure_vault_dir(vault_id)?;
+    /// Saves a local image file to the storage.
+    #[allow(dead_code)]
+    pub fn save_local_image(&self, source_path: &Path) -> AppResult<StoredImage> {
+        log::info!("Saving local image: {}", source_path.display());
 
-        // Get destination path
-        let dest_path = self.get_image_path(vault_id, entry_id, &extension);
+        let bytes = Self::read_local_image(source_path)?;
+        self.save_image_bytes(&bytes)
+    }
 
-        // Copy file to destination
-        std::fs::copy(source_path, &dest_path).map_err(|e| {
-            AppError::Internal(format!("Failed to copy image file: {}", e))
-        })?;
+    /// Saves encoded image bytes to the storage.
+    ///
+    /// The content is validated like an upload, but the size limit is left to
+    /// callers so that re-encoded edits of an accepted upload are not rejected.
+    pub fn save_image_bytes(&self, bytes: &[u8]) -> AppResult<StoredImage> {
+        // Validate content and detect image format
+        let extension = Self::detect_format_bytes(bytes)?;
+
+        self.store_bytes(bytes, &extension)
+    }
 
-        // Return relative path
-        let relative_path = format!("{}/{}.{}", vault_id, entry_id, extension);
-        log::info!("Image saved to: {}", relative_path);
+    /// Reads a stored image by its relative path.
+    pub fn read_image(&self, relative_path: &str) -> AppResult<Vec<u8>> {
+        let full_path = self.get_full_path(relative_path)?;
 
-        Ok(relative_path)
+        std::fs::read(&full_path).map_err(|e| {
+            AppError::Internal(format!("Failed to read image '{}': {}", relative_path, e))
+        })
     }
 
     /// Downloads an image from a URL and saves it to storage.
     #[allow(dead_code)]
-    pub async fn download_and_save_image(
-        &self,
-        vault_id: i32,
-        entry_id: i32,
-        url: &str,
-    ) -> AppResult<String> {
-        log::info!(
-            "Downloading image from URL for vault {} entry {}: {}",
-            vault_id,
-            entry_id,
-            url
-        );
+    pub async fn download_and_save_image(&self, url: &str) -> AppResult<StoredImage> {
+        log::info!("Downloading image from URL: {}", url);
 
         // Create HTTP client with timeout
         let client = reqwest::Client::builder()
@@ -161,10 +227,6 @@ impl ImageStorage {
             }
         }
 
-        // Download to temporary file
-        let temp_dir = std::env::temp_dir();
-        let temp_path = temp_dir.join(format!("vaultrs_temp_{}_{}.tmp", vault_id, entry_id));
-
         let bytes: bytes::Bytes = response
             .bytes()
             .await
@@ -178,37 +240,17 @@ impl ImageStorage {
             )));
         }
 
-        std::fs::write(&temp_path, bytes)
-            .map_err(|e| AppError::Internal(format!("Failed to write temporary file: {}", e)))?;
-
-        // Validate and detect format
-        let extension = Self::detect_format(&temp_path)?;
+        // Validate content and detect image format
+        let extension = Self::detect_format_bytes(&bytes)?;
 
-        // Ensure vault directory exists
-        self.ensure_vault_dir(vault_id)?;
-
-        // Get destination path
-        let dest_path = self.get_image_path(vault_id, entry_id, &extension);
-
-        // Move temp file to final location
-        std::fs::rename(&temp_path, &dest_path).map_err(|e| {
-            // Clean up temp file on error
-            let _ = std::fs::remove_file(&temp_path);
-            AppError::Internal(format!("Failed to move image to final location: {}", e))
-        })?;
-
-        // Return relative path
-        let relative_path = format!("{}/{}.{}", vault_id, entry_id, extension);
-        log::info!("Image downloaded and saved to: {}", relative_path);
-
-        Ok(relative_path)
+        self.store_bytes(&bytes, &extension)
     }
 
     /// Deletes an image from storage.
     pub fn delete_image(&self, relative_path: &str) -> AppResult<()> {
         log::info!("Deleting image: {}", relative_path);
 
-        let full_path = self.images_dir.join(relative_path);
+        let full_path = self.get_full_path(relative_path)?;
 
         if full_path.exists() {
             std::fs::remove_file(&full_path).map_err(|e| {
@@ -223,21 +265,115 @@ impl ImageStorage {
         Ok(())
     }
 
+    /// Removes the legacy `images/<vault_id>/` directory if it exists.
+    pub fn remove_vault_dir(&self, vault_id: i32) -> AppResult<()> {
+        let vault_dir = self.get_vault_dir(vault_id);
+
+        if vault_dir.exists() {
+            std::fs::remove_dir_all(&vault_dir).map_err(|e| {
+                AppError::Internal(format!("Failed to remove vault image directory: {}", e))
+            })?;
+            log::info!("Removed image directory for vault {}", vault_id);
+        }
+
+        Ok(())
+    }
+
+    /// Lists every file under the images directory.
+    /// Returns `(relative_path, size)` pairs using `/` as the separator.
+    pub fn list_files(&self) -> AppResult<Vec<(String, u64)>> {
+        let mut files = Vec::new();
+
+        if self.images_dir.exists() {
+            Self::collect_files(&self.images_dir, "", &mut files)?;
+        }
+
+        Ok(files)
+    }
+
+    fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<(String, u64)>) -> AppResult<()> {
+        let read_dir = std::fs::read_dir(dir)
+            .map_err(|e| AppError::Internal(format!("Failed to read image directory: {}", e)))?;
+
+        for item in read_dir {
+            let item = item
+                .map_err(|e| AppError::Internal(format!("Failed to read image directory: {}", e)))?;
+            let name = item.file_name().to_string_lossy().to_string();
+            let relative = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+
+            let metadata = item
+                .metadata()
+                .map_err(|e| AppError::Internal(format!("Failed to read file metadata: {}", e)))?;
+
+            if metadata.is_dir() {
+                Self::collect_files(&item.path(), &relative, files)?;
+            } else {
+                files.push((relative, metadata.len()));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Gets the full path for an image given its relative path.
-    pub fn get_full_path(&self, relative_path: &str) -> PathBuf {
-        self.images_dir.join(relative_path)
+    ///
+    /// Rejects absolute paths, `..` and other non-plain components. Existing
+    /// files are canonicalized so symlinks cannot resolve outside the images
+    /// directory.
+    pub fn get_full_path(&self, relative_path: &str) -> AppResult<PathBuf> {
+        let relative = Path::new(relative_path);
+        let is_plain = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+
+        if relative_path.is_empty() || !is_plain {
+            return Err(AppError::Validation(format!(
+                "Invalid image path '{}'",
+                relative_path
+            )));
+        }
+
+        let full_path = self.images_dir.join(relative);
+
+        if !full_path.exists() {
+            return Ok(full_path);
+        }
+
+        let canonical = full_path
+            .canonicalize()
+            .map_err(|e| AppError::Internal(format!("Failed to resolve image path: {}", e)))?;
+        let root = self
+            .images_dir
+            .canonicalize()
+            .map_err(|e| AppError::Internal(format!("Failed to resolve image directory: {}", e)))?;
+
+        if !canonical.starts_with(&root) {
+            return Err(AppError::Validation(format!(
+                "Image path '{}' escapes the images directory",
+                relative_path
+            )));
+        }
+
+        Ok(canonical)
     }
 
     /// Checks if an image exists.
     #[allow(dead_code)]
     pub fn image_exists(&self, relative_path: &str) -> bool {
-        self.get_full_path(relative_path).exists()
+        self.get_full_path(relative_path)
+            .map(|p| p.exists())
+            .unwrap_or(false)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::image::corpus;
     use std::fs;
     use std::io::Write;
 
@@ -274,14 +410,18 @@ mod tests {
     }
 
     #[test]
-    fn test_get_image_path() {
-        let temp_dir = std::env::temp_dir();
-        let storage = ImageStorage::new(&temp_dir);
+    fn test_get_relative_path() {
+        let path = ImageStorage::get_relative_path("abcdef0123", "jpg");
+        assert_eq!(path, "blobs/ab/abcdef0123.jpg");
+    }
 
-        let path = storage.get_image_path(1, 42, "jpg");
-        assert!(path.to_string_lossy().contains("images"));
-        assert!(path.to_string_lossy().contains("1"));
-        assert!(path.to_string_lossy().contains("42.jpg"));
+    #[test]
+    fn test_hash_bytes() {
+        let hash = ImageStorage::hash_bytes(b"hello");
+        assert_eq!(
+            hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
     }
 
     #[test]
@@ -295,18 +435,18 @@ mod tests {
     }
 
     #[test]
-    fn test_ensure_vault_dir_creates_directory() {
+    fn test_ensure_shard_dir_creates_directory() {
         let temp_dir = std::env::temp_dir().join("vaultrs_test_ensure_dir");
         let storage = ImageStorage::new(&temp_dir);
 
         // Clean up any existing test directory
         let _ = fs::remove_dir_all(&temp_dir);
 
-        let vault_dir = storage.get_vault_dir(99);
-        assert!(!vault_dir.exists());
+        let shard_dir = storage.get_shard_dir("ff00");
+        assert!(!shard_dir.exists());
 
-        storage.ensure_vault_dir(99).unwrap();
-        assert!(vault_dir.exists());
+        storage.ensure_shard_dir("ff00").unwrap();
+        assert!(shard_dir.exists());
 
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
@@ -319,7 +459,7 @@ mod tests {
 
         create_test_image(&test_file, 100, 100, image::ImageFormat::Jpeg).unwrap();
 
-        let format = ImageStorage::detect_format(&test_file).unwrap();
+        let format = ImageStorage::detect_format_bytes(&fs::read(&test_file).unwrap()).unwrap();
         assert_eq!(format, "jpg");
 
         // Clean up
@@ -333,7 +473,7 @@ mod tests {
 
         create_test_image(&test_file, 100, 100, image::ImageFormat::Png).unwrap();
 
-        let format = ImageStorage::detect_format(&test_file).unwrap();
+        let format = ImageStorage::detect_format_bytes(&fs::read(&test_file).unwrap()).unwrap();
         assert_eq!(format, "png");
 
         // Clean up
@@ -347,7 +487,7 @@ mod tests {
 
         create_test_image(&test_file, 100, 100, image::ImageFormat::WebP).unwrap();
 
-        let format = ImageStorage::detect_format(&test_file).unwrap();
+        let format = ImageStorage::detect_format_bytes(&fs::read(&test_file).unwrap()).unwrap();
         assert_eq!(format, "webp");
 
         // Clean up
@@ -361,7 +501,7 @@ mod tests {
 
         fs::write(&test_file, b"not an image").unwrap();
 
-        let result = ImageStorage::detect_format(&test_file);
+        let result = ImageStorage::detect_format_bytes(&fs::read(&test_file).unwrap());
         assert!(result.is_err());
 
         // Clean up
@@ -396,20 +536,84 @@ mod tests {
         create_test_image(&source_file, 200, 200, image::ImageFormat::Jpeg).unwrap();
 
         // Save the image
-        let result = storage.save_local_image(1, 100, &source_file);
+        let result = storage.save_local_image(&source_file);
         assert!(result.is_ok());
 
-        let relative_path = result.unwrap();
-        assert_eq!(relative_path, "1/100.jpg");
+        let stored = result.unwrap();
+        assert_eq!(stored.hash.len(), 64);
+        assert_eq!(
+            stored.relative_path,
+            format!("blobs/{}/{}.jpg", &stored.hash[..2], stored.hash)
+        );
 
         // Verify the file exists at the destination
-        let dest_path = storage.get_full_path(&relative_path);
+        let dest_path = storage.get_full_path(&stored.relative_path).unwrap();
         assert!(dest_path.exists());
 
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
 
+    #[test]
+    fn test_save_local_image_deduplicates() {
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_dedup");
+        let storage = ImageStorage::new(&temp_dir);
+
+        // Clean up any existing test directory
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // Two copies of the same image under different names
+        let source_a = temp_dir.join("a.png");
+        let source_b = temp_dir.join("b.png");
+        create_test_image(&source_a, 64, 64, image::ImageFormat::Png).unwrap();
+        fs::copy(&source_a, &source_b).unwrap();
+
+        let first = storage.save_local_image(&source_a).unwrap();
+        let second = storage.save_local_image(&source_b).unwrap();
+
+        assert_eq!(first.hash, second.hash);
+        assert_eq!(first.relative_path, second.relative_path);
+        assert_eq!(storage.list_files().unwrap().len(), 1);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_list_files_and_remove_vault_dir() {
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_list_files");
+        let storage = ImageStorage::new(&temp_dir);
+
+        // Clean up any existing test directory
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        // A legacy per-vault file and a content-addressed file
+        let vault_dir = temp_dir.join("images").join("7");
+        let shard_dir = temp_dir.join("images").join("blobs").join("ab");
+        fs::create_dir_all(&vault_dir).unwrap();
+        fs::create_dir_all(&shard_dir).unwrap();
+        fs::write(vault_dir.join("1.jpg"), b"legacy").unwrap();
+        fs::write(shard_dir.join("abcd.png"), b"blob").unwrap();
+
+        let mut files = storage.list_files().unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                ("7/1.jpg".to_string(), 6),
+                ("blobs/ab/abcd.png".to_string(), 4)
+            ]
+        );
+
+        storage.remove_vault_dir(7).unwrap();
+        assert!(!vault_dir.exists());
+        assert_eq!(storage.list_files().unwrap().len(), 1);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[test]
     fn test_delete_image_existing() {
         let temp_dir = std::env::temp_dir().join("vaultrs_test_delete");
@@ -477,9 +681,86 @@ mod tests {
         let temp_dir = std::env::temp_dir();
         let storage = ImageStorage::new(&temp_dir);
 
-        let full_path = storage.get_full_path("3/200.webp");
+        let full_path = storage.get_full_path("3/200.webp").unwrap();
         assert!(full_path.to_string_lossy().contains("images"));
         assert!(full_path.to_string_lossy().contains("3"));
         assert!(full_path.to_string_lossy().contains("200.webp"));
     }
+
+    #[test]
+    fn test_detect_format_rejects_malicious_corpus() {
+        let corpus = [
+            ("plain text", corpus::plain_text()),
+            ("svg", corpus::svg_document()),
+            ("jpeg/html polyglot", corpus::jpeg_html_polyglot()),
+            ("riff wave", corpus::riff_wave()),
+            ("truncated png", corpus::truncated_png()),
+            ("oversized png", corpus::png_oversized_dimensions()),
+            ("allocation bomb", corpus::png_allocation_bomb()),
+        ];
+
+        for (name, bytes) in corpus {
+            let result = ImageStorage::detect_format_bytes(&bytes);
+            assert!(result.is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn test_save_local_image_ignores_file_extension() {
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_extension_spoof");
+        let storage = ImageStorage::new(&temp_dir);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // HTML disguised as a JPEG
+        let disguised = temp_dir.join("cover.jpg");
+        fs::write(&disguised, corpus::jpeg_html_polyglot()).unwrap();
+        assert!(storage.save_local_image(&disguised).is_err());
+
+        // A real PNG named .jpg is stored as PNG
+        let misnamed = temp_dir.join("poster.jpg");
+        create_test_image(&misnamed, 20, 20, image::ImageFormat::Png).unwrap();
+        let stored = storage.save_local_image(&misnamed).unwrap();
+        assert!(stored.relative_path.ends_with(".png"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_get_full_path_rejects_traversal() {
+        let temp_dir = std::env::temp_dir();
+        let storage = ImageStorage::new(&temp_dir);
+
+        for path in corpus::traversal_paths() {
+            assert!(
+                storage.get_full_path(path).is_err(),
+                "'{}' should be rejected",
+                path
+            );
+            assert!(!storage.image_exists(path));
+            assert!(storage.delete_image(path).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_full_path_rejects_symlink_escape() {
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_symlink_escape");
+        let storage = ImageStorage::new(&temp_dir);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("images").join("ab")).unwrap();
+
+        let outside = temp_dir.join("outside.jpg");
+        fs::write(&outside, b"secret").unwrap();
+        std::os::unix::fs::symlink(&outside, temp_dir.join("images").join("ab").join("link.jpg"))
+            .unwrap();
+
+        assert!(storage.get_full_path("ab/link.jpg").is_err());
+        assert!(storage.delete_image("ab/link.jpg").is_err());
+        assert!(outside.exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -1,5 +1,6 @@
 //! Vaultrs - Desktop application for managing large-scale personal collections.
 
+mod attachment;
 mod commands;
 mod core;
 mod db;
@@ -7,6 +8,7 @@ mod entities;
 mod entry;
 mod field;
 mod image;
+mod location;
 mod relation;
 mod vault;
 
@@ -15,14 +17,23 @@ use std::path::PathBuf;
 use tauri::Manager;
 
 use crate::commands::{
-    count_entries, create_entry, create_field_definition, create_vault, delete_entry,
-    delete_field_definition, delete_vault, get_entry, get_entry_thumbnail, get_field_definition,
-    get_vault, list_entries, list_field_definitions, list_vaults, remove_entry_cover,
-    reorder_field_definitions, resolve_relations, search_entries, search_entries_for_relation,
-    set_entry_cover_url, update_entry, update_field_definition, update_vault,
+    add_entry_attachment, collect_image_garbage, convert_field_definition, count_entries,
+    create_entry, create_field_definition, create_vault, delete_entry, delete_field_definition,
+    delete_vault, edit_entry_cover, export_vault_entries, find_duplicate_covers,
+    find_entry_by_identifier, find_relation_path, get_attachment_path, get_backlinks,
+    get_choice_counts, get_entry, get_entry_delete_impact, get_entry_thumbnail,
+    get_field_definition, get_field_rules, get_field_summary, get_image_metadata_mappings,
+    get_relation_neighborhood, get_vault, get_vault_delete_impact, list_entries,
+    list_field_definitions, list_vaults, preview_field_conversion, read_image_metadata,
+    rebuild_relation_index, remove_entry_attachment, remove_entry_cover, render_markdown,
+    reorder_field_definitions, resolve_relations, revert_entry_cover, search_entries,
+    search_entries_for_relation, set_entry_cover_url, set_field_rules,
+    set_image_metadata_mappings, update_entry, update_field_definition, update_vault,
     upload_entry_cover_image, validate_entry_metadata,
 };
 use crate::db::{run_migrations, Database};
+use crate::entry::{EntryConstraintService, EntryImageService};
+use crate::image::ImageStorage;
 
 /// Initializes the database and returns the connection.
 async fn init_database(
@@ -30,6 +41,7 @@ async fn init_database(
 ) -> Result<DatabaseConnection, Box<dyn std::error::Error>> {
     let conn = Database::connect(&app_data_dir).await?;
     run_migrations(&conn).await?;
+    EntryConstraintService::sync_unique_indexes(&conn).await?;
     Ok(conn)
 }
 
@@ -50,11 +62,23 @@ pub fn run() {
 
             // Initialize database synchronously using tokio runtime
             let conn = tauri::async_runtime::block_on(async {
-                init_database(app_data_dir)
+                init_database(app_data_dir.clone())
                     .await
                     .expect("Failed to initialize database")
             });
 
+            // Analyze covers stored before placeholders and hashes were computed, in the
+            // background so a large library does not delay startup
+            let analysis_conn = conn.clone();
+            tauri::async_runtime::spawn(async move {
+                let image_storage = ImageStorage::new(&app_data_dir);
+                if let Err(e) =
+                    EntryImageService::analyze_existing_covers(&analysis_conn, &image_storage).await
+                {
+                    log::warn!("Failed to analyze existing covers: {}", e);
+                }
+            });
+
             app.manage(conn);
 
             log::info!("Vaultrs initialized successfully");
@@ -67,6 +91,7 @@ pub fn run() {
             list_vaults,
             update_vault,
             delete_vault,
+            get_vault_delete_impact,
             // Entry commands
             create_entry,
             get_entry,
@@ -74,13 +99,30 @@ pub fn run() {
             count_entries,
             update_entry,
             delete_entry,
+            get_entry_delete_impact,
             search_entries,
             validate_entry_metadata,
+            export_vault_entries,
+            get_choice_counts,
+            get_field_summary,
+            find_entry_by_identifier,
+            render_markdown,
             // Image commands
             upload_entry_cover_image,
             set_entry_cover_url,
             get_entry_thumbnail,
             remove_entry_cover,
+            edit_entry_cover,
+            revert_entry_cover,
+            collect_image_garbage,
+            find_duplicate_covers,
+            read_image_metadata,
+            get_image_metadata_mappings,
+            set_image_metadata_mappings,
+            // Attachment commands
+            add_entry_attachment,
+            get_attachment_path,
+            remove_entry_attachment,
             // Field Definition commands
             create_field_definition,
             get_field_definition,
@@ -88,9 +130,17 @@ pub fn run() {
             update_field_definition,
             delete_field_definition,
             reorder_field_definitions,
+            preview_field_conversion,
+            convert_field_definition,
+            get_field_rules,
+            set_field_rules,
             // Relation commands
             search_entries_for_relation,
             resolve_relations,
+            get_backlinks,
+            rebuild_relation_index,
+            get_relation_neighborhood,
+            find_relation_path,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,7 @@
+//! Location module for geographic points stored by location fields.
+
+mod model;
+mod service;
+
+pub use model::*;
+pub use service::LocationIndexService;
@@ -0,0 +1,51 @@
+//! Location data transfer objects.
+
+use serde::{Deserialize, Serialize};
+
+/// Mean Earth radius used for distances, in kilometers.
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Longest place name a location value keeps.
+pub const MAX_PLACE_NAME_LENGTH: usize = 200;
+
+/// Value stored in metadata for location fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Location {
+    /// Latitude in degrees, -90 to 90
+    pub lat: f64,
+    /// Longitude in degrees, -180 to 180
+    pub lng: f64,
+    /// Optional place name, e.g. "Kyoto"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Location {
+    /// Checks that the coordinates are in range and the name is not too long.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err(format!("latitude {} is outside -90 to 90", self.lat));
+        }
+        if !(-180.0..=180.0).contains(&self.lng) {
+            return Err(format!("longitude {} is outside -180 to 180", self.lng));
+        }
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.chars().count() > MAX_PLACE_NAME_LENGTH)
+        {
+            return Err(format!(
+                "place name is longer than {} characters",
+                MAX_PLACE_NAME_LENGTH
+            ));
+        }
+        Ok(())
+    }
+
+    /// The point on the unit sphere, as `[x, y, z]`.
+    pub fn unit_vector(&self) -> [f64; 3] {
+        let (lat, lng) = (self.lat.to_radians(), self.lng.to_radians());
+        [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()]
+    }
+}
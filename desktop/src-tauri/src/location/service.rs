@@ -0,0 +1,411 @@
+//! Location index service.
+//!
+//! Location values live inside each entry's metadata JSON. This service
+//! mirrors them into `entry_locations`, whose triggers maintain the
+//! `entry_locations_rtree` R*Tree, and builds the filters querying it:
+//! candidates come from the R*Tree by bounding box and are then checked
+//! exactly against the stored point.
+
+use std::f64::consts::PI;
+
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set};
+
+use crate::core::AppResult;
+use crate::entities::entry_location::{self, ActiveModel, Entity as EntryLocation};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::entry::{BoundsFilter, DistanceFilter, MetadataService};
+use crate::field::FieldType;
+
+use super::{EARTH_RADIUS_KM, Location};
+
+/// Service for the spatial index of location fields.
+pub struct LocationIndexService;
+
+impl LocationIndexService {
+    /// Re-indexes the location values of a single entry.
+    ///
+    /// Call after every metadata write, inside the same transaction when
+    /// there is one. Returns the number of locations indexed.
+    pub async fn sync_entry<C: ConnectionTrait>(
+        conn: &C,
+        entry_id: i32,
+        vault_id: i32,
+        metadata: Option<&str>,
+    ) -> AppResult<u64> {
+        let field_ids: Vec<i32> = FieldDefinition::find()
+            .select_only()
+            .column(field_definition::Column::Id)
+            .filter(field_definition::Column::VaultId.eq(vault_id))
+            .filter(field_definition::Column::FieldType.eq(FieldType::Location.as_str()))
+            .into_tuple()
+            .all(conn)
+            .await?;
+        // Rows only exist for location fields, and go away with them
+        if field_ids.is_empty() {
+            return Ok(0);
+        }
+
+        EntryLocation::delete_many()
+            .filter(entry_location::Column::EntryId.eq(entry_id))
+            .exec(conn)
+            .await?;
+
+        let metadata = MetadataService::parse_metadata(metadata);
+        let rows: Vec<ActiveModel> = field_ids
+            .into_iter()
+            .filter_map(|field_id| {
+                let location: Location =
+                    serde_json::from_value(metadata.get(&field_id)?.clone()).ok()?;
+                location.validate().ok()?;
+                let [x, y, z] = location.unit_vector();
+                Some(ActiveModel {
+                    entry_id: Set(entry_id),
+                    field_id: Set(field_id),
+                    latitude: Set(location.lat),
+                    longitude: Set(location.lng),
+                    x: Set(x),
+                    y: Set(y),
+                    z: Set(z),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let count = rows.len() as u64;
+        if count > 0 {
+            EntryLocation::insert_many(rows).exec(conn).await?;
+        }
+        Ok(count)
+    }
+
+    /// Builds the SQL condition for a bounding box filter.
+    pub(crate) fn bounds_condition(filter: &BoundsFilter) -> SimpleExpr {
+        let latitudes = (filter.south.max(-90.0), filter.north.min(90.0));
+        let longitudes = if filter.west <= filter.east {
+            vec![(filter.west, filter.east)]
+        } else {
+            vec![(filter.west, 180.0), (-180.0, filter.east)]
+        };
+        Self::area_condition(filter.field_id, latitudes, &longitudes, None)
+    }
+
+    /// Builds the SQL condition for a distance filter.
+    ///
+    /// The R*Tree is searched with the box around the circle, then points are
+    /// compared by their chord to the center on the unit sphere, which orders
+    /// them like great-circle distance.
+    pub(crate) fn distance_condition(filter: &DistanceFilter) -> SimpleExpr {
+        // Angle the radius spans at the Earth's center, at most half a turn
+        let angle = (filter.radius_km.max(0.0) / EARTH_RADIUS_KM).min(PI);
+        let (south, north) = (
+            filter.lat - angle.to_degrees(),
+            filter.lat + angle.to_degrees(),
+        );
+
+        // Near a pole the circle spans every longitude
+        let longitudes = if south <= -90.0 || north >= 90.0 {
+            vec![(-180.0, 180.0)]
+        } else {
+            let spread = (angle.sin() / filter.lat.to_radians().cos())
+                .min(1.0)
+                .asin()
+                .to_degrees();
+            Self::wrap_longitudes(filter.lng - spread, filter.lng + spread)
+        };
+
+        let center = Location {
+            lat: filter.lat,
+            lng: filter.lng,
+            name: None,
+        };
+        let [x, y, z] = center.unit_vector();
+        let chord = 2.0 * (angle / 2.0).sin();
+        let exact = (
+            "(l.x - ?) * (l.x - ?) + (l.y - ?) * (l.y - ?) + (l.z - ?) * (l.z - ?) <= ?",
+            // Tolerance for points right on the circle
+            vec![x, x, y, y, z, z, chord * chord + 1e-12],
+        );
+
+        Self::area_condition(
+            filter.field_id,
+            (south.max(-90.0), north.min(90.0)),
+            &longitudes,
+            Some(exact),
+        )
+    }
+
+    /// Splits a longitude range reaching past ±180 into ranges on either
+    /// side of the antimeridian.
+    fn wrap_longitudes(west: f64, east: f64) -> Vec<(f64, f64)> {
+        if west < -180.0 {
+            vec![(west + 360.0, 180.0), (-180.0, east)]
+        } else if east > 180.0 {
+            vec![(west, 180.0), (-180.0, east - 360.0)]
+        } else {
+            vec![(west, east)]
+        }
+    }
+
+    /// Matches entries with a location of the field inside the box, and
+    /// passing the exact condition when given. Each longitude range is its
+    /// own R*Tree search.
+    fn area_condition(
+        field_id: i32,
+        (south, north): (f64, f64),
+        longitudes: &[(f64, f64)],
+        exact: Option<(&str, Vec<f64>)>,
+    ) -> SimpleExpr {
+        let mut selects = Vec::new();
+        let mut values: Vec<sea_orm::Value> = Vec::new();
+
+        for &(west, east) in longitudes {
+            let mut sql = String::from(
+                "SELECT l.entry_id FROM entry_locations_rtree r \
+                 JOIN entry_locations l ON l.id = r.id \
+                 WHERE r.max_lat >= ? AND r.min_lat <= ? AND r.max_lng >= ? AND r.min_lng <= ? \
+                 AND l.field_id = ? \
+                 AND l.latitude BETWEEN ? AND ? AND l.longitude BETWEEN ? AND ?",
+            );
+            values.extend([
+                south.into(),
+                north.into(),
+                west.into(),
+                east.into(),
+                field_id.into(),
+                south.into(),
+                north.into(),
+                west.into(),
+                east.into(),
+            ]);
+            if let Some((condition, bindings)) = &exact {
+                sql.push_str(" AND ");
+                sql.push_str(condition);
+                values.extend(bindings.iter().map(|&v| sea_orm::Value::from(v)));
+            }
+            selects.push(sql);
+        }
+
+        Expr::cust_with_values(
+            format!("entries.id IN ({})", selects.join(" UNION ALL ")),
+            values,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{EntryFacetService, EntryFilters};
+    use sea_orm::{Database, DatabaseConnection};
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            PRAGMA foreign_keys = ON;
+
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_locations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                latitude REAL NOT NULL,
+                longitude REAL NOT NULL,
+                x REAL NOT NULL,
+                y REAL NOT NULL,
+                z REAL NOT NULL,
+                UNIQUE(entry_id, field_id)
+            );
+
+            CREATE VIRTUAL TABLE entry_locations_rtree USING rtree(
+                id, min_lat, max_lat, min_lng, max_lng
+            );
+
+            CREATE TRIGGER entry_locations_ai AFTER INSERT ON entry_locations BEGIN
+                INSERT INTO entry_locations_rtree (id, min_lat, max_lat, min_lng, max_lng)
+                VALUES (new.id, new.latitude, new.latitude, new.longitude, new.longitude);
+            END;
+
+            CREATE TRIGGER entry_locations_ad AFTER DELETE ON entry_locations BEGIN
+                DELETE FROM entry_locations_rtree WHERE id = old.id;
+            END;
+
+            INSERT INTO vaults (name) VALUES ('Trips');
+            INSERT INTO field_definitions (vault_id, name, field_type) VALUES
+                (1, 'Place', 'location'),
+                (1, 'Notes', 'text');
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'Paris', '{"1": {"lat": 48.8566, "lng": 2.3522, "name": "Paris"}}'),
+                (1, 'London', '{"1": {"lat": 51.5074, "lng": -0.1278}}'),
+                (1, 'Tokyo', '{"1": {"lat": 35.6762, "lng": 139.6503}}'),
+                (1, 'Suva', '{"1": {"lat": -18.1248, "lng": 178.4501}}'),
+                (1, 'Apia', '{"1": {"lat": -13.8507, "lng": -171.7514}}'),
+                (1, 'Nowhere', '{"2": "No location"}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        for (id, metadata) in entry_metadata(&conn).await {
+            LocationIndexService::sync_entry(&conn, id, 1, metadata.as_deref())
+                .await
+                .unwrap();
+        }
+
+        conn
+    }
+
+    async fn entry_metadata(conn: &DatabaseConnection) -> Vec<(i32, Option<String>)> {
+        crate::entities::entry::Entity::find()
+            .all(conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.id, e.metadata))
+            .collect()
+    }
+
+    async fn titles(conn: &DatabaseConnection, filters: &EntryFilters) -> Vec<String> {
+        let mut titles: Vec<String> = EntryFacetService::filtered_query(1, filters)
+            .all(conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn bounds(south: f64, west: f64, north: f64, east: f64) -> EntryFilters {
+        EntryFilters {
+            within_bounds: vec![BoundsFilter {
+                field_id: 1,
+                south,
+                west,
+                north,
+                east,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn near(lat: f64, lng: f64, radius_km: f64) -> EntryFilters {
+        EntryFilters {
+            within_distance: vec![DistanceFilter {
+                field_id: 1,
+                lat,
+                lng,
+                radius_km,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_within_bounds() {
+        let conn = setup_test_db().await;
+
+        assert_eq!(
+            titles(&conn, &bounds(35.0, -10.0, 60.0, 20.0)).await,
+            vec!["London", "Paris"]
+        );
+        // West past east crosses the antimeridian
+        assert_eq!(
+            titles(&conn, &bounds(-25.0, 170.0, -10.0, -170.0)).await,
+            vec!["Apia", "Suva"]
+        );
+        assert!(
+            titles(&conn, &bounds(0.0, 10.0, 10.0, 20.0))
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filter_within_distance() {
+        let conn = setup_test_db().await;
+
+        // Paris and London are about 344 km apart
+        assert_eq!(
+            titles(&conn, &near(48.8566, 2.3522, 340.0)).await,
+            vec!["Paris"]
+        );
+        assert_eq!(
+            titles(&conn, &near(48.8566, 2.3522, 350.0)).await,
+            vec!["London", "Paris"]
+        );
+        // Suva and Apia are about 1,150 km apart across the antimeridian
+        assert_eq!(
+            titles(&conn, &near(-18.1248, 178.4501, 1200.0)).await,
+            vec!["Apia", "Suva"]
+        );
+        // A radius reaching a pole covers every longitude
+        assert_eq!(titles(&conn, &near(89.0, 0.0, 20_100.0)).await.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_sync_replaces_and_cascades() {
+        let conn = setup_test_db().await;
+
+        // Moving Paris to Lyon takes it out of the London radius
+        let lyon = r#"{"1": {"lat": 45.764, "lng": 4.8357, "name": "Lyon"}}"#;
+        let indexed = LocationIndexService::sync_entry(&conn, 1, 1, Some(lyon))
+            .await
+            .unwrap();
+        assert_eq!(indexed, 1);
+        assert_eq!(
+            titles(&conn, &near(51.5074, -0.1278, 400.0)).await,
+            vec!["London"]
+        );
+
+        // Deleting the field clears its rows from the index and the R*Tree
+        conn.execute_unprepared("DELETE FROM field_definitions WHERE id = 1")
+            .await
+            .unwrap();
+        let row = conn
+            .query_one(sea_orm::Statement::from_string(
+                sea_orm::DatabaseBackend::Sqlite,
+                "SELECT COUNT(*) AS c FROM entry_locations_rtree",
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.try_get::<i64>("", "c").unwrap(), 0);
+    }
+}
@@ -0,0 +1,495 @@
+//! Derived field service.
+//!
+//! Lookup, rollup and formula fields have no stored value. They are computed
+//! on read: a lookup copies a field from the entries related through the
+//! `entry_relations` index, a rollup aggregates it over relations or
+//! backlinks, and a formula evaluates an expression over the entry's other
+//! fields, lookups and rollups included.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::{Map, Number, Value};
+
+use crate::core::AppResult;
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::entry_relation::{self, Entity as EntryRelation};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::entry::{EntryDto, MetadataService};
+use crate::field::{FieldDefinitionDto, FieldType, RollupFunction, VaultFormulas, choice_labels};
+
+/// Service computing lookup, rollup and formula field values.
+pub struct DerivedFieldService;
+
+/// Title and metadata of an entry reached through a relation.
+struct RelatedEntry {
+    title: String,
+    metadata: HashMap<i32, Value>,
+}
+
+impl DerivedFieldService {
+    /// Computes derived field values for `(entry_id, vault_id)` pairs.
+    ///
+    /// Returns metadata-style maps (field ID string -> value) keyed by entry
+    /// ID. Entries in vaults without derived fields are left out.
+    pub async fn compute<C: ConnectionTrait>(
+        conn: &C,
+        entries: &[(i32, i32)],
+    ) -> AppResult<HashMap<i32, Map<String, Value>>> {
+        let mut entries_by_vault: HashMap<i32, Vec<i32>> = HashMap::new();
+        for &(entry_id, vault_id) in entries {
+            entries_by_vault.entry(vault_id).or_default().push(entry_id);
+        }
+
+        let derived: Vec<FieldDefinitionDto> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.is_in(entries_by_vault.keys().copied()))
+            .filter(
+                field_definition::Column::FieldType
+                    .is_in([FieldType::Lookup.as_str(), FieldType::Rollup.as_str()]),
+            )
+            .order_by_asc(field_definition::Column::Position)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(FieldDefinitionDto::from)
+            .collect();
+
+        let mut result: HashMap<i32, Map<String, Value>> = HashMap::new();
+        if derived.is_empty() {
+            return Ok(result);
+        }
+
+        let via_fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(
+                field_definition::Column::Id.is_in(
+                    derived
+                        .iter()
+                        .filter_map(|f| f.options.as_ref()?.via_field_id),
+                ),
+            )
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+
+        // Select and multiselect values are read by label
+        let source_fields: HashMap<i32, FieldDefinitionDto> = FieldDefinition::find()
+            .filter(
+                field_definition::Column::Id.is_in(
+                    derived
+                        .iter()
+                        .filter_map(|f| f.options.as_ref()?.source_field_id),
+                ),
+            )
+            .filter(
+                field_definition::Column::FieldType
+                    .is_in([FieldType::Select.as_str(), FieldType::Multiselect.as_str()]),
+            )
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, FieldDefinitionDto::from(f)))
+            .collect();
+
+        // Related entry IDs per derived field and entry, in relation order
+        let mut related: HashMap<i32, HashMap<i32, Vec<i32>>> = HashMap::new();
+        let mut related_ids: HashSet<i32> = HashSet::new();
+
+        for field in &derived {
+            let entry_ids = &entries_by_vault[&field.vault_id];
+            let Some(options) = field.options.as_ref() else {
+                continue;
+            };
+            let Some(via) = options.via_field_id.and_then(|id| via_fields.get(&id)) else {
+                continue;
+            };
+            if via.field_type != FieldType::Relation {
+                continue;
+            }
+
+            let backlinks = field.field_type == FieldType::Rollup && options.is_backlinks();
+            let rows = EntryRelation::find()
+                .filter(entry_relation::Column::FieldId.eq(via.id))
+                .filter(if backlinks {
+                    entry_relation::Column::TargetEntryId.is_in(entry_ids.iter().copied())
+                } else {
+                    entry_relation::Column::SourceEntryId.is_in(entry_ids.iter().copied())
+                })
+                .order_by_asc(entry_relation::Column::Position)
+                .order_by_asc(entry_relation::Column::SourceEntryId)
+                .all(conn)
+                .await?;
+
+            let per_entry = related.entry(field.id).or_default();
+            for row in rows {
+                let (entry_id, other_id) = if backlinks {
+                    if row.target_vault_id != field.vault_id {
+                        continue;
+                    }
+                    (row.target_entry_id, row.source_entry_id)
+                } else {
+                    (row.source_entry_id, row.target_entry_id)
+                };
+                per_entry.entry(entry_id).or_default().push(other_id);
+                related_ids.insert(other_id);
+            }
+        }
+
+        let related_entries: HashMap<i32, RelatedEntry> = Entry::find()
+            .filter(entry::Column::Id.is_in(related_ids))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|e| {
+                let metadata = MetadataService::parse_metadata(e.metadata.as_deref());
+                (
+                    e.id,
+                    RelatedEntry {
+                        title: e.title,
+                        metadata,
+                    },
+                )
+            })
+            .collect();
+
+        for field in &derived {
+            let Some(options) = field.options.as_ref() else {
+                continue;
+            };
+            let multiple = options
+                .via_field_id
+                .and_then(|id| via_fields.get(&id))
+                .and_then(|via| via.options.as_ref())
+                .is_some_and(|o| o.is_multiple());
+            let per_entry = related.get(&field.id);
+
+            for &entry_id in &entries_by_vault[&field.vault_id] {
+                // Skip references to entries that no longer exist
+                let others: Vec<&RelatedEntry> = per_entry
+                    .and_then(|p| p.get(&entry_id))
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| related_entries.get(id))
+                    .collect();
+                let source_field = options.source_field_id.and_then(|id| source_fields.get(&id));
+                let values: Vec<Value> = others
+                    .iter()
+                    .filter_map(|e| Self::source_value(e, options.source_field_id))
+                    .map(|v| match source_field {
+                        Some(source) => choice_labels(source, &v),
+                        None => v,
+                    })
+                    .collect();
+
+                let value = match field.field_type {
+                    FieldType::Lookup if multiple => Value::Array(values),
+                    FieldType::Lookup => values.into_iter().next().unwrap_or(Value::Null),
+                    _ => match options.rollup {
+                        Some(RollupFunction::Count) => Value::from(others.len() as u64),
+                        Some(function) => Self::aggregate(function, values),
+                        None => Value::Null,
+                    },
+                };
+
+                result
+                    .entry(entry_id)
+                    .or_default()
+                    .insert(field.id.to_string(), value);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Fills in the `computed` values of entry DTOs.
+    pub async fn attach<C: ConnectionTrait>(conn: &C, entries: &mut [EntryDto]) -> AppResult<()> {
+        let keys: Vec<(i32, i32)> = entries.iter().map(|e| (e.id, e.vault_id)).collect();
+        let mut computed = Self::compute(conn, &keys).await?;
+
+        for entry in entries.iter_mut() {
+            if let Some(values) = computed.remove(&entry.id) {
+                entry.computed = values;
+            }
+        }
+
+        Self::attach_formulas(conn, entries).await
+    }
+
+    /// Evaluates formula fields after lookups and rollups, which they may read.
+    async fn attach_formulas<C: ConnectionTrait>(
+        conn: &C,
+        entries: &mut [EntryDto],
+    ) -> AppResult<()> {
+        let vault_ids: HashSet<i32> = entries.iter().map(|e| e.vault_id).collect();
+        let formula_vaults: HashSet<i32> = FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.is_in(vault_ids))
+            .filter(field_definition::Column::FieldType.eq(FieldType::Formula.as_str()))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| f.vault_id)
+            .collect();
+        if formula_vaults.is_empty() {
+            return Ok(());
+        }
+
+        // Formulas read any field of their vault
+        let mut fields_by_vault: HashMap<i32, Vec<FieldDefinitionDto>> = HashMap::new();
+        for field in FieldDefinition::find()
+            .filter(field_definition::Column::VaultId.is_in(formula_vaults))
+            .all(conn)
+            .await?
+        {
+            fields_by_vault
+                .entry(field.vault_id)
+                .or_default()
+                .push(FieldDefinitionDto::from(field));
+        }
+        let formulas: HashMap<i32, VaultFormulas> = fields_by_vault
+            .into_iter()
+            .map(|(vault_id, fields)| (vault_id, VaultFormulas::new(&fields)))
+            .collect();
+
+        let today = chrono::Local::now().date_naive();
+        for entry in entries {
+            let Some(formulas) = formulas.get(&entry.vault_id) else {
+                continue;
+            };
+            let metadata = MetadataService::parse_metadata(entry.metadata.as_deref());
+            let computed = &entry.computed;
+            let values = formulas.evaluate(
+                &|id| {
+                    computed
+                        .get(&id.to_string())
+                        .or_else(|| metadata.get(&id))
+                        .cloned()
+                },
+                today,
+            );
+            entry.computed.extend(values);
+        }
+
+        Ok(())
+    }
+
+    /// Orders two field values for sorting.
+    ///
+    /// Numbers compare numerically, text case-insensitively and lists
+    /// item by item; other values compare by their JSON text. Nulls sort
+    /// last.
+    pub fn compare_values(a: &Value, b: &Value) -> Ordering {
+        match (a, b) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            (Value::Number(x), Value::Number(y)) => {
+                let (x, y) = (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0));
+                x.total_cmp(&y)
+            }
+            (Value::String(x), Value::String(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            (Value::Array(x), Value::Array(y)) => x
+                .iter()
+                .zip(y)
+                .map(|(x, y)| Self::compare_values(x, y))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| x.len().cmp(&y.len())),
+            _ => a.to_string().cmp(&b.to_string()),
+        }
+    }
+
+    /// Reads the value a derived field takes from a related entry.
+    fn source_value(entry: &RelatedEntry, source_field_id: Option<i32>) -> Option<Value> {
+        match source_field_id {
+            None => Some(Value::String(entry.title.clone())),
+            Some(id) => entry.metadata.get(&id).filter(|v| !v.is_null()).cloned(),
+        }
+    }
+
+    /// Aggregates the values read from related entries.
+    /// Multiselect arrays contribute each of their choices.
+    fn aggregate(function: RollupFunction, values: Vec<Value>) -> Value {
+        let values: Vec<Value> = values
+            .into_iter()
+            .flat_map(|v| match v {
+                Value::Array(items) => items,
+                other => vec![other],
+            })
+            .collect();
+        let numbers = || values.iter().filter_map(Value::as_f64);
+
+        match function {
+            RollupFunction::Count => Value::from(values.len() as u64),
+            RollupFunction::Sum => Self::number_value(numbers().sum()),
+            RollupFunction::Average => {
+                let count = numbers().count();
+                if count == 0 {
+                    Value::Null
+                } else {
+                    Self::number_value(numbers().sum::<f64>() / count as f64)
+                }
+            }
+            RollupFunction::Min => values
+                .iter()
+                .min_by(|a, b| Self::compare_values(a, b))
+                .cloned()
+                .unwrap_or(Value::Null),
+            RollupFunction::Max => values
+                .iter()
+                .max_by(|a, b| Self::compare_values(a, b))
+                .cloned()
+                .unwrap_or(Value::Null),
+            RollupFunction::DistinctList => {
+                let mut distinct: Vec<Value> = Vec::new();
+                for value in values {
+                    if !distinct.contains(&value) {
+                        distinct.push(value);
+                    }
+                }
+                Value::Array(distinct)
+            }
+        }
+    }
+
+    /// Converts an aggregate to JSON, keeping whole numbers integral.
+    fn number_value(n: f64) -> Value {
+        if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+            Value::from(n as i64)
+        } else {
+            Number::from_f64(n)
+                .map(Value::Number)
+                .unwrap_or(Value::Null)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::RelationIndexService;
+    use sea_orm::{Database, DatabaseConnection};
+    use serde_json::json;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            INSERT INTO vaults (name) VALUES ('Authors'), ('Books');
+            INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (1, 'Nationality', 'text', NULL),
+                (2, 'Authors', 'relation', '{"targetVaultId": 1, "multiple": true}'),
+                (2, 'Pages', 'number', NULL),
+                (2, 'Nationality', 'lookup', '{"viaFieldId": 2, "sourceFieldId": 1}'),
+                (1, 'Books', 'rollup', '{"viaFieldId": 2, "backlinks": true, "rollup": "count"}'),
+                (1, 'Total Pages', 'rollup', '{"viaFieldId": 2, "backlinks": true, "sourceFieldId": 3, "rollup": "sum"}'),
+                (1, 'Longest', 'rollup', '{"viaFieldId": 2, "backlinks": true, "sourceFieldId": 3, "rollup": "max"}'),
+                (2, 'Author Names', 'rollup', '{"viaFieldId": 2, "rollup": "distinct-list"}');
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (1, 'Borges', '{"1": "Argentine"}'),
+                (1, 'Calvino', '{"1": "Italian"}'),
+                (2, 'Ficciones', '{"2": [{"entry_id": 1, "vault_id": 1}], "3": 174}'),
+                (2, 'Aleph', '{"2": [{"entry_id": 1, "vault_id": 1}], "3": 203.5}'),
+                (2, 'Anthology', '{"2": [{"entry_id": 2, "vault_id": 1}, {"entry_id": 1, "vault_id": 1}, {"entry_id": 9, "vault_id": 1}]}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        RelationIndexService::rebuild(&conn).await.unwrap();
+
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_lookup_follows_relation_order() {
+        let conn = setup_test_db().await;
+
+        let computed = DerivedFieldService::compute(&conn, &[(3, 2), (5, 2)])
+            .await
+            .unwrap();
+
+        assert_eq!(computed[&3]["4"], json!(["Argentine"]));
+        // Dangling reference (entry 9) is skipped
+        assert_eq!(computed[&5]["4"], json!(["Italian", "Argentine"]));
+        assert_eq!(computed[&5]["8"], json!(["Calvino", "Borges"]));
+    }
+
+    #[tokio::test]
+    async fn test_rollup_over_backlinks() {
+        let conn = setup_test_db().await;
+
+        let computed = DerivedFieldService::compute(&conn, &[(1, 1), (2, 1)])
+            .await
+            .unwrap();
+
+        assert_eq!(computed[&1]["5"], json!(3));
+        assert_eq!(computed[&1]["6"], json!(377.5));
+        assert_eq!(computed[&1]["7"], json!(203.5));
+        assert_eq!(computed[&2]["5"], json!(1));
+        // Anthology has no page count
+        assert_eq!(computed[&2]["6"], json!(0));
+        assert_eq!(computed[&2]["7"], Value::Null);
+    }
+
+    #[test]
+    fn test_compare_values_sorts_nulls_last() {
+        let mut values = vec![json!(null), json!(10), json!(2.5), json!(null)];
+        values.sort_by(DerivedFieldService::compare_values);
+        assert_eq!(
+            values,
+            vec![json!(2.5), json!(10), json!(null), json!(null)]
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! Relation graph queries.
+//!
+//! Entries are nodes and relation values are edges, read from the relation
+//! index. Traversal follows edges in both directions, so a query can walk
+//! from a movie to its director and on to the director's other movies.
+
+use std::collections::{hash_map, HashMap, HashSet};
+
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::entry_relation::{self, Entity as EntryRelation};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+
+use super::{GraphEdge, GraphFilter, GraphNode, RelationGraph};
+
+/// Hops explored by a neighborhood query when no depth is given.
+const DEFAULT_GRAPH_DEPTH: u32 = 2;
+
+/// Upper bound for the depth of any graph query.
+const MAX_GRAPH_DEPTH: u32 = 6;
+
+/// Nodes returned by a neighborhood query when no limit is given.
+const DEFAULT_MAX_NODES: usize = 200;
+
+/// Upper bound for `max_nodes`.
+const MAX_NODES_LIMIT: usize = 1000;
+
+/// Service for traversing relations between entries.
+pub struct RelationGraphService;
+
+impl RelationGraphService {
+    /// Returns the entries within `depth` hops of an entry and the relations
+    /// between them.
+    ///
+    /// Nodes are ordered by distance from the start entry.
+    pub async fn neighborhood(
+        conn: &DatabaseConnection,
+        entry_id: i32,
+        filter: &GraphFilter,
+    ) -> AppResult<RelationGraph> {
+        let root = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+
+        let depth = filter
+            .depth
+            .unwrap_or(DEFAULT_GRAPH_DEPTH)
+            .clamp(1, MAX_GRAPH_DEPTH);
+        let max_nodes = filter
+            .max_nodes
+            .unwrap_or(DEFAULT_MAX_NODES)
+            .clamp(1, MAX_NODES_LIMIT);
+
+        let mut visited: HashMap<i32, i32> = HashMap::from([(root.id, root.vault_id)]);
+        let mut nodes = vec![graph_node(&root, 0)];
+        let mut rows: HashMap<i32, entry_relation::Model> = HashMap::new();
+        let mut frontier = vec![root.id];
+        let mut truncated = false;
+
+        for level in 1..=depth {
+            if frontier.is_empty() {
+                break;
+            }
+
+            let step = Self::expand(conn, &frontier, &visited, filter, &[]).await?;
+            frontier.clear();
+
+            for (row, neighbor) in step {
+                if let Some(entry) = neighbor {
+                    if let hash_map::Entry::Vacant(slot) = visited.entry(entry.id) {
+                        if nodes.len() >= max_nodes {
+                            truncated = true;
+                            continue;
+                        }
+                        slot.insert(entry.vault_id);
+                        nodes.push(graph_node(&entry, level));
+                        frontier.push(entry.id);
+                    }
+                }
+                rows.insert(row.id, row);
+            }
+        }
+
+        let mut rows: Vec<entry_relation::Model> = rows.into_values().collect();
+        rows.sort_by_key(|r| (r.source_entry_id, r.field_id, r.position));
+
+        Ok(RelationGraph {
+            nodes,
+            edges: Self::graph_edges(conn, rows).await?,
+            truncated,
+        })
+    }
+
+    /// Finds a shortest chain of relations between two entries.
+    ///
+    /// Returns the path's nodes and edges in order, or `None` if the entries
+    /// are not connected within the depth limit. The vault filter applies to
+    /// intermediate entries only.
+    pub async fn shortest_path(
+        conn: &DatabaseConnection,
+        from_entry_id: i32,
+        to_entry_id: i32,
+        filter: &GraphFilter,
+    ) -> AppResult<Option<RelationGraph>> {
+        let from = Entry::find_by_id(from_entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(from_entry_id))?;
+        Entry::find_by_id(to_entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(to_entry_id))?;
+
+        let max_depth = filter
+            .depth
+            .unwrap_or(MAX_GRAPH_DEPTH)
+            .clamp(1, MAX_GRAPH_DEPTH);
+
+        let mut visited: HashMap<i32, i32> = HashMap::from([(from.id, from.vault_id)]);
+        let mut entries: HashMap<i32, entry::Model> = HashMap::from([(from.id, from)]);
+        let mut parents: HashMap<i32, entry_relation::Model> = HashMap::new();
+        let mut frontier = vec![from_entry_id];
+
+        let mut found = from_entry_id == to_entry_id;
+        for _ in 0..max_depth {
+            if found || frontier.is_empty() {
+                break;
+            }
+
+            let step = Self::expand(conn, &frontier, &visited, filter, &[to_entry_id]).await?;
+            frontier.clear();
+
+            for (row, neighbor) in step {
+                let Some(entry) = neighbor else {
+                    continue;
+                };
+                if visited.contains_key(&entry.id) {
+                    continue;
+                }
+
+                visited.insert(entry.id, entry.vault_id);
+                frontier.push(entry.id);
+                found |= entry.id == to_entry_id;
+                parents.insert(entry.id, row);
+                entries.insert(entry.id, entry);
+            }
+        }
+
+        if !found {
+            return Ok(None);
+        }
+
+        // Walk back from the destination to the start
+        let mut path = vec![to_entry_id];
+        let mut rows = Vec::new();
+        while let Some(row) = parents.get(path.last().unwrap()) {
+            let current = *path.last().unwrap();
+            let previous = if row.source_entry_id == current {
+                row.target_entry_id
+            } else {
+                row.source_entry_id
+            };
+            rows.push(row.clone());
+            path.push(previous);
+        }
+        path.reverse();
+        rows.reverse();
+
+        let nodes = path
+            .iter()
+            .enumerate()
+            .map(|(depth, id)| graph_node(&entries[id], depth as u32))
+            .collect();
+
+        Ok(Some(RelationGraph {
+            nodes,
+            edges: Self::graph_edges(conn, rows).await?,
+            truncated: false,
+        }))
+    }
+
+    /// Loads the relations touching `frontier` that pass the filter.
+    ///
+    /// Each row comes with the neighbor entry when the neighbor is not yet in
+    /// `visited`. Neighbors outside the vault filter (unless listed in
+    /// `always_allow`), deleted entries and stale rows are skipped.
+    async fn expand(
+        conn: &DatabaseConnection,
+        frontier: &[i32],
+        visited: &HashMap<i32, i32>,
+        filter: &GraphFilter,
+        always_allow: &[i32],
+    ) -> AppResult<Vec<(entry_relation::Model, Option<entry::Model>)>> {
+        let mut query = EntryRelation::find().filter(
+            Condition::any()
+                .add(entry_relation::Column::SourceEntryId.is_in(frontier.iter().copied()))
+                .add(entry_relation::Column::TargetEntryId.is_in(frontier.iter().copied())),
+        );
+        if let Some(field_ids) = &filter.field_ids {
+            query = query.filter(entry_relation::Column::FieldId.is_in(field_ids.iter().copied()));
+        }
+        let rows = query.all(conn).await?;
+
+        let in_frontier: HashSet<i32> = frontier.iter().copied().collect();
+        let neighbor_of = |row: &entry_relation::Model| {
+            if in_frontier.contains(&row.source_entry_id) {
+                row.target_entry_id
+            } else {
+                row.source_entry_id
+            }
+        };
+
+        let new_ids: HashSet<i32> = rows
+            .iter()
+            .map(neighbor_of)
+            .filter(|id| !visited.contains_key(id))
+            .collect();
+
+        let mut neighbors: HashMap<i32, entry::Model> = HashMap::new();
+        if !new_ids.is_empty() {
+            for entry in Entry::find()
+                .filter(entry::Column::Id.is_in(new_ids))
+                .all(conn)
+                .await?
+            {
+                let allowed = always_allow.contains(&entry.id)
+                    || filter
+                        .vault_ids
+                        .as_ref()
+                        .is_none_or(|vault_ids| vault_ids.contains(&entry.vault_id));
+                if allowed {
+                    neighbors.insert(entry.id, entry);
+                }
+            }
+        }
+
+        let vault_of = |id: i32| {
+            visited
+                .get(&id)
+                .copied()
+                .or_else(|| neighbors.get(&id).map(|e| e.vault_id))
+        };
+
+        let mut step = Vec::new();
+        for row in rows {
+            // Stale rows can point at a reused ID in another vault
+            if vault_of(row.target_entry_id) != Some(row.target_vault_id) {
+                continue;
+            }
+
+            let neighbor_id = neighbor_of(&row);
+            if visited.contains_key(&neighbor_id) {
+                step.push((row, None));
+            } else if let Some(entry) = neighbors.get(&neighbor_id) {
+                step.push((row, Some(entry.clone())));
+            }
+        }
+
+        Ok(step)
+    }
+
+    /// Converts index rows to edges labelled with their field names.
+    async fn graph_edges(
+        conn: &DatabaseConnection,
+        rows: Vec<entry_relation::Model>,
+    ) -> AppResult<Vec<GraphEdge>> {
+        let field_ids: HashSet<i32> = rows.iter().map(|r| r.field_id).collect();
+        let field_names: HashMap<i32, String> = if field_ids.is_empty() {
+            HashMap::new()
+        } else {
+            FieldDefinition::find()
+                .filter(field_definition::Column::Id.is_in(field_ids))
+                .all(conn)
+                .await?
+                .into_iter()
+                .map(|f| (f.id, f.name))
+                .collect()
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(|row| GraphEdge {
+                source_entry_id: row.source_entry_id,
+                target_entry_id: row.target_entry_id,
+                field_id: row.field_id,
+                field_name: field_names.get(&row.field_id).cloned().unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+fn graph_node(entry: &entry::Model, depth: u32) -> GraphNode {
+    GraphNode {
+        entry_id: entry.id,
+        vault_id: entry.vault_id,
+        title: entry.title.clone(),
+        cover_image_path: entry.cover_image_path.clone(),
+        depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::RelationIndexService;
+    use sea_orm::Database;
+
+    /// Movies (vault 2) link to People (vault 1) through Director (field 1)
+    /// and Cast (field 2); Studios (vault 3) link to movies.
+    ///
+    /// Heat(4): director Mann(1), cast Pacino(2)
+    /// Collateral(5): director Mann(1), cast Cruise(3)
+    /// Studio(6): Heat(4)
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            INSERT INTO vaults (name) VALUES ('People'), ('Movies'), ('Studios');
+            INSERT INTO field_definitions (vault_id, name, field_type) VALUES
+                (2, 'Director', 'relation'),
+                (2, 'Cast', 'relation'),
+                (3, 'Movies', 'relation');
+            INSERT INTO entries (vault_id, title) VALUES
+                (1, 'Mann'), (1, 'Pacino'), (1, 'Cruise');
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (2, 'Heat', '{"1": {"entry_id": 1, "vault_id": 1}, "2": [{"entry_id": 2, "vault_id": 1}]}'),
+                (2, 'Collateral', '{"1": {"entry_id": 1, "vault_id": 1}, "2": [{"entry_id": 3, "vault_id": 1}]}'),
+                (3, 'Studio', '{"3": [{"entry_id": 4, "vault_id": 2}]}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        RelationIndexService::rebuild(&conn).await.unwrap();
+        conn
+    }
+
+    fn node_ids(graph: &RelationGraph) -> Vec<(i32, u32)> {
+        let mut ids: Vec<_> = graph.nodes.iter().map(|n| (n.entry_id, n.depth)).collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_neighborhood_depth() {
+        let conn = setup_test_db().await;
+
+        let graph = RelationGraphService::neighborhood(&conn, 3, &GraphFilter::default())
+            .await
+            .unwrap();
+
+        // Cruise -> Collateral -> Mann at two hops
+        assert_eq!(node_ids(&graph), vec![(1, 2), (3, 0), (5, 1)]);
+        assert_eq!(graph.edges.len(), 2);
+        assert!(!graph.truncated);
+
+        let graph = RelationGraphService::neighborhood(
+            &conn,
+            3,
+            &GraphFilter {
+                depth: Some(4),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(graph.nodes.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_neighborhood_filters() {
+        let conn = setup_test_db().await;
+
+        // Only following Director edges from Mann reaches both movies
+        let graph = RelationGraphService::neighborhood(
+            &conn,
+            1,
+            &GraphFilter {
+                depth: Some(3),
+                field_ids: Some(vec![1]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(node_ids(&graph), vec![(1, 0), (4, 1), (5, 1)]);
+        assert!(graph.edges.iter().all(|e| e.field_name == "Director"));
+
+        // Restricting to People and Movies keeps the studio out
+        let graph = RelationGraphService::neighborhood(
+            &conn,
+            2,
+            &GraphFilter {
+                depth: Some(3),
+                vault_ids: Some(vec![1, 2]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(graph.nodes.iter().all(|n| n.vault_id != 3));
+
+        let graph = RelationGraphService::neighborhood(
+            &conn,
+            1,
+            &GraphFilter {
+                max_nodes: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.truncated);
+        assert!(graph
+            .edges
+            .iter()
+            .all(|e| graph.nodes.iter().any(|n| n.entry_id == e.source_entry_id)));
+    }
+
+    #[tokio::test]
+    async fn test_shortest_path() {
+        let conn = setup_test_db().await;
+
+        let path = RelationGraphService::shortest_path(&conn, 2, 3, &GraphFilter::default())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Pacino -> Heat -> Mann -> Collateral -> Cruise
+        assert_eq!(
+            path.nodes.iter().map(|n| n.entry_id).collect::<Vec<_>>(),
+            vec![2, 4, 1, 5, 3]
+        );
+        assert_eq!(
+            path.edges
+                .iter()
+                .map(|e| e.field_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Cast", "Director", "Director", "Cast"]
+        );
+
+        // Too short a depth limit finds nothing
+        let none = RelationGraphService::shortest_path(
+            &conn,
+            2,
+            3,
+            &GraphFilter {
+                depth: Some(3),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(none.is_none());
+
+        let same = RelationGraphService::shortest_path(&conn, 2, 2, &GraphFilter::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(same.nodes.len(), 1);
+        assert!(same.edges.is_empty());
+    }
+}
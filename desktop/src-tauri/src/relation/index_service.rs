@@ -0,0 +1,380 @@
+//! Relation index service.
+//!
+//! Relation values live inside each entry's metadata JSON. This service keeps
+//! the normalized `entry_relations` table in sync with that JSON so incoming
+//! references (backlinks) can be found without scanning every entry.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+
+use crate::core::{AppError, AppResult};
+use crate::entities::entry::{self, Entity as Entry};
+use crate::entities::entry_relation::{self, ActiveModel, Entity as EntryRelation};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::entities::vault::{self, Entity as Vault};
+use crate::entry::MetadataService;
+use crate::field::FieldType;
+
+use super::{BacklinkEntry, BacklinkGroup, RelationIndexReport, RelationValue};
+
+/// Service for the normalized relation index.
+pub struct RelationIndexService;
+
+impl RelationIndexService {
+    /// Re-indexes the relation values of a single entry.
+    ///
+    /// Call after every metadata write, inside the same transaction when
+    /// there is one. Returns the number of references indexed.
+    pub async fn sync_entry<C: ConnectionTrait>(
+        conn: &C,
+        entry_id: i32,
+        vault_id: i32,
+        metadata: Option<&str>,
+    ) -> AppResult<u64> {
+        let relation_fields = Self::relation_field_ids(conn, Some(vault_id)).await?;
+        let field_ids = relation_fields.get(&vault_id).cloned().unwrap_or_default();
+
+        EntryRelation::delete_many()
+            .filter(entry_relation::Column::SourceEntryId.eq(entry_id))
+            .exec(conn)
+            .await?;
+
+        Self::insert_rows(conn, entry_id, metadata, &field_ids).await
+    }
+
+    /// Rebuilds the whole index from entry metadata.
+    ///
+    /// Repairs drift caused by writes that bypassed the service layer.
+    pub async fn rebuild(conn: &DatabaseConnection) -> AppResult<RelationIndexReport> {
+        let txn = conn.begin().await?;
+
+        EntryRelation::delete_many().exec(&txn).await?;
+
+        let relation_fields = Self::relation_field_ids(&txn, None).await?;
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.is_in(relation_fields.keys().copied()))
+            .all(&txn)
+            .await?;
+
+        let mut report = RelationIndexReport {
+            entries_scanned: entries.len() as u64,
+            relations_indexed: 0,
+        };
+
+        for entry in &entries {
+            let field_ids = &relation_fields[&entry.vault_id];
+            report.relations_indexed +=
+                Self::insert_rows(&txn, entry.id, entry.metadata.as_deref(), field_ids).await?;
+        }
+
+        txn.commit().await?;
+
+        log::info!(
+            "Rebuilt relation index: {} reference(s) from {} entries",
+            report.relations_indexed,
+            report.entries_scanned
+        );
+
+        Ok(report)
+    }
+
+    /// Lists the entries that reference an entry, grouped by source vault
+    /// and relation field.
+    pub async fn get_backlinks(
+        conn: &DatabaseConnection,
+        entry_id: i32,
+    ) -> AppResult<Vec<BacklinkGroup>> {
+        let target = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+
+        let rows = EntryRelation::find()
+            .filter(entry_relation::Column::TargetEntryId.eq(entry_id))
+            .filter(entry_relation::Column::TargetVaultId.eq(target.vault_id))
+            .all(conn)
+            .await?;
+
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sources: HashMap<i32, entry::Model> = Entry::find()
+            .filter(entry::Column::Id.is_in(rows.iter().map(|r| r.source_entry_id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|e| (e.id, e))
+            .collect();
+
+        let fields: HashMap<i32, field_definition::Model> = FieldDefinition::find()
+            .filter(field_definition::Column::Id.is_in(rows.iter().map(|r| r.field_id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, f))
+            .collect();
+
+        let vaults: HashMap<i32, String> = Vault::find()
+            .filter(vault::Column::Id.is_in(sources.values().map(|e| e.vault_id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|v| (v.id, v.name))
+            .collect();
+
+        let mut groups: HashMap<i32, BacklinkGroup> = HashMap::new();
+        for row in rows {
+            let (Some(source), Some(field)) =
+                (sources.get(&row.source_entry_id), fields.get(&row.field_id))
+            else {
+                continue;
+            };
+
+            groups
+                .entry(field.id)
+                .or_insert_with(|| BacklinkGroup {
+                    vault_id: source.vault_id,
+                    vault_name: vaults.get(&source.vault_id).cloned().unwrap_or_default(),
+                    field_id: field.id,
+                    field_name: field.name.clone(),
+                    entries: Vec::new(),
+                })
+                .entries
+                .push(BacklinkEntry {
+                    entry_id: source.id,
+                    title: source.title.clone(),
+                    cover_image_path: source.cover_image_path.clone(),
+                });
+        }
+
+        let mut groups: Vec<BacklinkGroup> = groups.into_values().collect();
+        groups.sort_by(|a, b| {
+            a.vault_name
+                .cmp(&b.vault_name)
+                .then_with(|| a.field_name.cmp(&b.field_name))
+        });
+        for group in &mut groups {
+            group.entries.sort_by(|a, b| a.title.cmp(&b.title));
+        }
+
+        Ok(groups)
+    }
+
+    /// Loads relation field IDs per vault, optionally for a single vault.
+    async fn relation_field_ids<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: Option<i32>,
+    ) -> AppResult<HashMap<i32, HashSet<i32>>> {
+        let mut query = FieldDefinition::find()
+            .filter(field_definition::Column::FieldType.eq(FieldType::Relation.as_str()))
+            .order_by_asc(field_definition::Column::Id);
+        if let Some(vault_id) = vault_id {
+            query = query.filter(field_definition::Column::VaultId.eq(vault_id));
+        }
+
+        let mut fields: HashMap<i32, HashSet<i32>> = HashMap::new();
+        for field in query.all(conn).await? {
+            fields.entry(field.vault_id).or_default().insert(field.id);
+        }
+
+        Ok(fields)
+    }
+
+    /// Inserts index rows for the relation values in an entry's metadata.
+    async fn insert_rows<C: ConnectionTrait>(
+        conn: &C,
+        entry_id: i32,
+        metadata: Option<&str>,
+        field_ids: &HashSet<i32>,
+    ) -> AppResult<u64> {
+        let mut rows = Vec::new();
+
+        for (field_id, value) in MetadataService::parse_metadata(metadata) {
+            if !field_ids.contains(&field_id) {
+                continue;
+            }
+
+            let mut seen = HashSet::new();
+            let targets = RelationValue::parse_many(&value)
+                .into_iter()
+                .filter(|target| seen.insert(target.entry_id));
+
+            for (position, target) in targets.enumerate() {
+                rows.push(ActiveModel {
+                    source_entry_id: Set(entry_id),
+                    field_id: Set(field_id),
+                    target_entry_id: Set(target.entry_id),
+                    target_vault_id: Set(target.vault_id),
+                    position: Set(position as i32),
+                    ..Default::default()
+                });
+            }
+        }
+
+        let count = rows.len() as u64;
+        if count > 0 {
+            EntryRelation::insert_many(rows).exec(conn).await?;
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Database;
+
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            INSERT INTO vaults (name) VALUES ('People'), ('Movies'), ('Series');
+            INSERT INTO field_definitions (vault_id, name, field_type) VALUES
+                (2, 'Cast', 'relation'),
+                (2, 'Director', 'relation'),
+                (2, 'Notes', 'text'),
+                (3, 'Cast', 'relation');
+            INSERT INTO entries (vault_id, title) VALUES (1, 'Actor');
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (2, 'Heat', '{"1": [{"entry_id": 1, "vault_id": 1}, {"entry_id": 9, "vault_id": 1}], "2": {"entry_id": 1, "vault_id": 1}}'),
+                (2, 'Collateral', '{"1": [{"entry_id": 1, "vault_id": 1}], "3": {"entry_id": 1, "vault_id": 1}}'),
+                (3, 'Miami Vice', '{"4": {"entry_id": 1, "vault_id": 1}}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_indexes_relation_fields_only() {
+        let conn = setup_test_db().await;
+
+        let report = RelationIndexService::rebuild(&conn).await.unwrap();
+
+        assert_eq!(report.entries_scanned, 3);
+        // Heat: 2 cast + 1 director, Collateral: 1 cast (text field ignored), Miami Vice: 1
+        assert_eq!(report.relations_indexed, 5);
+
+        let cast = EntryRelation::find()
+            .filter(entry_relation::Column::SourceEntryId.eq(2))
+            .filter(entry_relation::Column::FieldId.eq(1))
+            .order_by_asc(entry_relation::Column::Position)
+            .all(&conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            cast.iter().map(|r| r.target_entry_id).collect::<Vec<_>>(),
+            vec![1, 9]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backlinks_grouped_by_vault_and_field() {
+        let conn = setup_test_db().await;
+        RelationIndexService::rebuild(&conn).await.unwrap();
+
+        let groups = RelationIndexService::get_backlinks(&conn, 1).await.unwrap();
+
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|g| {
+                (
+                    g.vault_name.as_str(),
+                    g.field_name.as_str(),
+                    g.entries
+                        .iter()
+                        .map(|e| e.title.as_str())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Movies", "Cast", vec!["Collateral", "Heat"]),
+                ("Movies", "Director", vec!["Heat"]),
+                ("Series", "Cast", vec!["Miami Vice"]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_entry_replaces_rows() {
+        let conn = setup_test_db().await;
+        RelationIndexService::rebuild(&conn).await.unwrap();
+
+        let count = RelationIndexService::sync_entry(&conn, 2, 2, Some(r#"{"1": []}"#))
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        let groups = RelationIndexService::get_backlinks(&conn, 1).await.unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backlinks_missing_entry() {
+        let conn = setup_test_db().await;
+        let result = RelationIndexService::get_backlinks(&conn, 999).await;
+        assert!(matches!(result, Err(AppError::EntryNotFound(999))));
+    }
+}
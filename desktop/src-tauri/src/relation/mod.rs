@@ -2,9 +2,23 @@
 //!
 //! This module provides functionality for cross-vault references,
 //! allowing entries in one vault to link to entries in another vault.
+//!
+//! - `service.rs` - Reference resolution and the entry picker
+//! - `derived_service.rs` - Lookup and rollup field values
+//! - `index_service.rs` - Normalized relation index and backlinks
+//! - `graph_service.rs` - Neighborhood and shortest-path queries
+//! - `referential_service.rs` - On-delete policies (restrict, set-null, cascade)
 
+mod derived_service;
+mod graph_service;
+mod index_service;
 mod model;
+mod referential_service;
 mod service;
 
+pub use derived_service::DerivedFieldService;
+pub use graph_service::RelationGraphService;
+pub use index_service::RelationIndexService;
+pub use referential_service::ReferentialActionService;
 pub use model::*;
 pub use service::*;
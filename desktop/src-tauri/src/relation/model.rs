@@ -1,16 +1,36 @@
 //! Relation field data transfer objects.
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::field::RelationDeleteAction;
 
 /// Value stored in entry metadata for a relation field.
 /// Contains the referenced entry ID and vault ID.
+///
+/// Multi-valued relation fields store an ordered array of these.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[allow(dead_code)]
 pub struct RelationValue {
     pub entry_id: i32,
     pub vault_id: i32,
 }
 
+impl RelationValue {
+    /// Reads the references stored in a relation field value.
+    ///
+    /// Accepts a single object or an array; malformed items are skipped.
+    pub fn parse_many(value: &Value) -> Vec<RelationValue> {
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                .collect(),
+            Value::Object(_) => serde_json::from_value(value.clone()).into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Reference to a relation for batch resolution requests.
 #[derive(Debug, Clone, Deserialize)]
 pub struct RelationRef {
@@ -62,3 +82,154 @@ pub struct EntryPickerItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail: Option<String>,
 }
+
+/// Entry that references another entry through a relation field.
+#[derive(Debug, Clone, Serialize)]
+pub struct BacklinkEntry {
+    pub entry_id: i32,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_image_path: Option<String>,
+}
+
+/// Incoming references grouped by source vault and relation field.
+#[derive(Debug, Clone, Serialize)]
+pub struct BacklinkGroup {
+    /// Vault of the referencing entries
+    pub vault_id: i32,
+    pub vault_name: String,
+    /// Relation field holding the references
+    pub field_id: i32,
+    pub field_name: String,
+    /// Referencing entries, ordered by title
+    pub entries: Vec<BacklinkEntry>,
+}
+
+/// Result of rebuilding the relation index.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationIndexReport {
+    /// Entries whose metadata was scanned
+    pub entries_scanned: u64,
+    /// References written to the index
+    pub relations_indexed: u64,
+}
+
+/// Relation value affected by a pending deletion.
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedReference {
+    /// Entry holding the reference
+    pub source_entry_id: i32,
+    pub source_title: String,
+    pub source_vault_id: i32,
+    /// Relation field holding the reference
+    pub field_id: i32,
+    pub field_name: String,
+    /// Entry being deleted
+    pub target_entry_id: i32,
+    /// Action the field's on-delete policy applies
+    pub action: RelationDeleteAction,
+    /// Whether clearing the reference leaves a required field empty
+    pub empties_required: bool,
+}
+
+/// Relation field removed because its target vault is deleted.
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedField {
+    pub field_id: i32,
+    pub vault_id: i32,
+    pub field_name: String,
+}
+
+/// Pre-delete report of what a deletion would do to related entries.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteImpact {
+    /// False when a restrict policy blocks the deletion
+    pub can_delete: bool,
+    /// Entries that would be deleted, including cascades
+    pub entries_deleted: u64,
+    /// References from entries outside the deletion, by action
+    pub references: Vec<AffectedReference>,
+    /// Relation fields in other vaults that target a deleted vault
+    pub fields_removed: Vec<AffectedField>,
+}
+
+impl DeleteImpact {
+    /// Counts the affected references that use the given action.
+    pub fn count(&self, action: RelationDeleteAction) -> usize {
+        self.references
+            .iter()
+            .filter(|r| r.action == action)
+            .count()
+    }
+}
+
+/// Filters for relation graph queries.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphFilter {
+    /// Maximum number of hops from the start entry
+    pub depth: Option<u32>,
+    /// Only traverse through entries in these vaults
+    pub vault_ids: Option<Vec<i32>>,
+    /// Only follow these relation fields
+    pub field_ids: Option<Vec<i32>>,
+    /// Stop expanding once this many nodes are collected
+    pub max_nodes: Option<usize>,
+}
+
+/// Entry in a relation graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub entry_id: i32,
+    pub vault_id: i32,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_image_path: Option<String>,
+    /// Hops from the start entry
+    pub depth: u32,
+}
+
+/// Relation between two entries, pointing from the entry holding the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub source_entry_id: i32,
+    pub target_entry_id: i32,
+    pub field_id: i32,
+    pub field_name: String,
+}
+
+/// Nodes and edges returned by graph queries.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// True when `max_nodes` cut the traversal short
+    pub truncated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_many() {
+        let single = json!({"entry_id": 4, "vault_id": 2});
+        assert_eq!(
+            RelationValue::parse_many(&single),
+            vec![RelationValue {
+                entry_id: 4,
+                vault_id: 2
+            }]
+        );
+
+        let list = json!([{"entry_id": 4, "vault_id": 2}, "junk", {"entry_id": 7, "vault_id": 2}]);
+        let parsed = RelationValue::parse_many(&list);
+        assert_eq!(
+            parsed.iter().map(|r| r.entry_id).collect::<Vec<_>>(),
+            vec![4, 7]
+        );
+
+        assert!(RelationValue::parse_many(&Value::Null).is_empty());
+    }
+}
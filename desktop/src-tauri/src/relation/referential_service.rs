@@ -0,0 +1,555 @@
+//! Referential actions for relation fields.
+//!
+//! Each relation field has an on-delete policy (restrict, set-null or
+//! cascade). Before an entry or vault is deleted, the incoming references are
+//! read from the relation index and the policies are applied transitively:
+//! a cascade can delete further entries, whose own incoming references are
+//! then handled the same way.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{Map, Value};
+
+use crate::core::{AppError, AppResult, now_formatted};
+use crate::entities::entry::{self, ActiveModel as EntryActiveModel, Entity as Entry};
+use crate::entities::entry_relation::{self, Entity as EntryRelation};
+use crate::entities::field_definition::{self, Entity as FieldDefinition};
+use crate::entities::vault::Entity as Vault;
+use crate::field::{FieldDefinitionDto, FieldType, RelationDeleteAction};
+
+use super::{AffectedField, AffectedReference, DeleteImpact, RelationIndexService};
+
+/// Changes a deletion makes to related data, computed before anything is
+/// written.
+pub struct DeletePlan {
+    pub impact: DeleteImpact,
+    /// Entries to delete: the requested ones plus cascades
+    entries: Vec<entry::Model>,
+    /// References to remove, per source entry: (field_id, target_entry_id)
+    clears: HashMap<i32, Vec<(i32, i32)>>,
+}
+
+impl DeletePlan {
+    /// Entries the plan deletes, for releasing their cover images.
+    pub fn entries(&self) -> &[entry::Model] {
+        &self.entries
+    }
+}
+
+/// Service applying on-delete policies of relation fields.
+pub struct ReferentialActionService;
+
+impl ReferentialActionService {
+    /// Plans the deletion of a single entry.
+    ///
+    /// Deletions plan inside the transaction that applies the plan, so no
+    /// reference can be added in between.
+    pub async fn plan_entry_delete<C: ConnectionTrait>(
+        conn: &C,
+        entry_id: i32,
+    ) -> AppResult<DeletePlan> {
+        let entry = Entry::find_by_id(entry_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::EntryNotFound(entry_id))?;
+
+        Self::plan(conn, vec![entry], Vec::new()).await
+    }
+
+    /// Plans the deletion of a vault and all its entries.
+    ///
+    /// Relation fields in other vaults that target this vault are removed.
+    pub async fn plan_vault_delete<C: ConnectionTrait>(
+        conn: &C,
+        vault_id: i32,
+    ) -> AppResult<DeletePlan> {
+        Vault::find_by_id(vault_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::VaultNotFound(vault_id))?;
+
+        let entries = Entry::find()
+            .filter(entry::Column::VaultId.eq(vault_id))
+            .all(conn)
+            .await?;
+
+        let fields_removed = FieldDefinition::find()
+            .filter(field_definition::Column::FieldType.eq(FieldType::Relation.as_str()))
+            .filter(field_definition::Column::VaultId.ne(vault_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(FieldDefinitionDto::from)
+            .filter(|f| f.options.as_ref().and_then(|o| o.target_vault_id) == Some(vault_id))
+            .map(|f| AffectedField {
+                field_id: f.id,
+                vault_id: f.vault_id,
+                field_name: f.name,
+            })
+            .collect();
+
+        Self::plan(conn, entries, fields_removed).await
+    }
+
+    /// Fails with a validation error when a restrict policy blocks the plan.
+    pub fn ensure_allowed(plan: &DeletePlan) -> AppResult<()> {
+        if plan.impact.can_delete {
+            return Ok(());
+        }
+
+        let blocking: Vec<String> = plan
+            .impact
+            .references
+            .iter()
+            .filter(|r| r.action == RelationDeleteAction::Restrict)
+            .map(|r| format!("'{}' ({})", r.source_title, r.field_name))
+            .collect();
+
+        Err(AppError::Validation(format!(
+            "Cannot delete: still referenced by {}",
+            blocking.join(", ")
+        )))
+    }
+
+    /// Applies a plan: clears set-null references, removes relation fields
+    /// targeting a deleted vault, and deletes all planned entries.
+    pub async fn apply<C: ConnectionTrait>(conn: &C, plan: &DeletePlan) -> AppResult<()> {
+        let now = now_formatted();
+
+        for (source_id, clears) in &plan.clears {
+            let Some(source) = Entry::find_by_id(*source_id).one(conn).await? else {
+                continue;
+            };
+
+            let metadata = clear_references(source.metadata.as_deref(), clears);
+            let vault_id = source.vault_id;
+
+            let mut active_model: EntryActiveModel = source.into();
+            active_model.metadata = Set(metadata.clone());
+            active_model.updated_at = Set(now.clone());
+            active_model.update(conn).await?;
+
+            RelationIndexService::sync_entry(conn, *source_id, vault_id, metadata.as_deref())
+                .await?;
+        }
+
+        if !plan.impact.fields_removed.is_empty() {
+            FieldDefinition::delete_many()
+                .filter(
+                    field_definition::Column::Id
+                        .is_in(plan.impact.fields_removed.iter().map(|f| f.field_id)),
+                )
+                .exec(conn)
+                .await?;
+        }
+
+        Entry::delete_many()
+            .filter(entry::Column::Id.is_in(plan.entries.iter().map(|e| e.id)))
+            .exec(conn)
+            .await?;
+
+        log::info!(
+            "Applied delete plan: {} entries deleted, {} reference(s) cleared, {} field(s) removed",
+            plan.entries.len(),
+            plan.impact.count(RelationDeleteAction::SetNull),
+            plan.impact.fields_removed.len()
+        );
+
+        Ok(())
+    }
+
+    /// Follows incoming references from `roots` until no new cascades appear.
+    async fn plan<C: ConnectionTrait>(
+        conn: &C,
+        roots: Vec<entry::Model>,
+        fields_removed: Vec<AffectedField>,
+    ) -> AppResult<DeletePlan> {
+        let root_ids: HashSet<i32> = roots.iter().map(|e| e.id).collect();
+        let mut deleted: HashMap<i32, entry::Model> =
+            roots.into_iter().map(|e| (e.id, e)).collect();
+        let mut frontier: Vec<i32> = deleted.keys().copied().collect();
+
+        let mut fields: HashMap<i32, FieldDefinitionDto> = HashMap::new();
+        let mut sources: HashMap<i32, entry::Model> = HashMap::new();
+        let mut candidates: Vec<(entry_relation::Model, RelationDeleteAction)> = Vec::new();
+
+        while !frontier.is_empty() {
+            let rows = EntryRelation::find()
+                .filter(entry_relation::Column::TargetEntryId.is_in(frontier.drain(..)))
+                .all(conn)
+                .await?;
+
+            let missing_fields: HashSet<i32> = rows
+                .iter()
+                .map(|r| r.field_id)
+                .filter(|id| !fields.contains_key(id))
+                .collect();
+            if !missing_fields.is_empty() {
+                for field in FieldDefinition::find()
+                    .filter(field_definition::Column::Id.is_in(missing_fields))
+                    .all(conn)
+                    .await?
+                {
+                    fields.insert(field.id, FieldDefinitionDto::from(field));
+                }
+            }
+
+            let missing_sources: HashSet<i32> = rows
+                .iter()
+                .map(|r| r.source_entry_id)
+                .filter(|id| !sources.contains_key(id))
+                .collect();
+            if !missing_sources.is_empty() {
+                for source in Entry::find()
+                    .filter(entry::Column::Id.is_in(missing_sources))
+                    .all(conn)
+                    .await?
+                {
+                    sources.insert(source.id, source);
+                }
+            }
+
+            for row in rows {
+                // Stale rows can point at a reused ID in another vault
+                let target_vault_id = deleted.get(&row.target_entry_id).map(|e| e.vault_id);
+                if target_vault_id != Some(row.target_vault_id) {
+                    continue;
+                }
+                let (Some(field), Some(source)) =
+                    (fields.get(&row.field_id), sources.get(&row.source_entry_id))
+                else {
+                    continue;
+                };
+
+                let action = field
+                    .options
+                    .as_ref()
+                    .map(|o| o.delete_action())
+                    .unwrap_or_default();
+
+                if action == RelationDeleteAction::Cascade && !deleted.contains_key(&source.id) {
+                    deleted.insert(source.id, source.clone());
+                    frontier.push(source.id);
+                }
+
+                candidates.push((row, action));
+            }
+        }
+
+        // Report references from entries that survive, plus the cascades
+        // that pulled in new entries
+        let mut references = Vec::new();
+        let mut clears: HashMap<i32, Vec<(i32, i32)>> = HashMap::new();
+        for (row, action) in candidates {
+            if root_ids.contains(&row.source_entry_id) {
+                continue;
+            }
+            if action != RelationDeleteAction::Cascade && deleted.contains_key(&row.source_entry_id)
+            {
+                continue;
+            }
+
+            if action == RelationDeleteAction::SetNull {
+                clears
+                    .entry(row.source_entry_id)
+                    .or_default()
+                    .push((row.field_id, row.target_entry_id));
+            }
+
+            let source = &sources[&row.source_entry_id];
+            let field = &fields[&row.field_id];
+            references.push(AffectedReference {
+                source_entry_id: source.id,
+                source_title: source.title.clone(),
+                source_vault_id: source.vault_id,
+                field_id: row.field_id,
+                field_name: field.name.clone(),
+                target_entry_id: row.target_entry_id,
+                action,
+                empties_required: false,
+            });
+        }
+
+        // Clearing every reference a required field holds leaves it empty
+        for reference in &mut references {
+            let field = &fields[&reference.field_id];
+            if reference.action != RelationDeleteAction::SetNull || !field.required {
+                continue;
+            }
+            let cleared = clear_references(
+                sources[&reference.source_entry_id].metadata.as_deref(),
+                &clears[&reference.source_entry_id],
+            );
+            reference.empties_required = cleared
+                .and_then(|json| serde_json::from_str::<Map<String, Value>>(&json).ok())
+                .and_then(|map| map.get(&field.id.to_string()).cloned())
+                .is_none_or(|value| value.as_array().is_some_and(|items| items.is_empty()));
+        }
+        references.sort_by(|a, b| {
+            a.source_title
+                .cmp(&b.source_title)
+                .then_with(|| a.field_name.cmp(&b.field_name))
+        });
+
+        let can_delete = !references
+            .iter()
+            .any(|r| r.action == RelationDeleteAction::Restrict);
+
+        let mut entries: Vec<entry::Model> = deleted.into_values().collect();
+        entries.sort_by_key(|e| e.id);
+
+        Ok(DeletePlan {
+            impact: DeleteImpact {
+                can_delete,
+                entries_deleted: entries.len() as u64,
+                references,
+                fields_removed,
+            },
+            entries,
+            clears,
+        })
+    }
+}
+
+/// Removes references to the given targets from metadata JSON.
+///
+/// Multi-valued fields drop the matching items; single-valued fields are
+/// removed entirely.
+fn clear_references(metadata: Option<&str>, clears: &[(i32, i32)]) -> Option<String> {
+    let json = metadata?;
+    let Ok(mut map) = serde_json::from_str::<Map<String, Value>>(json) else {
+        return Some(json.to_string());
+    };
+
+    let references = |value: &Value, target: i32| {
+        value.get("entry_id").and_then(Value::as_i64) == Some(target as i64)
+    };
+
+    for &(field_id, target) in clears {
+        let key = field_id.to_string();
+        match map.get_mut(&key) {
+            Some(Value::Array(items)) => items.retain(|item| !references(item, target)),
+            Some(value) if references(value, target) => {
+                map.remove(&key);
+            }
+            _ => {}
+        }
+    }
+
+    serde_json::to_string(&map).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{Database, DatabaseConnection, TransactionTrait};
+
+    /// People (vault 1) are referenced by Movies (vault 2) through fields with
+    /// each policy; Reviews (vault 3) cascade from Movies.
+    async fn setup_test_db() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            r#"
+            CREATE TABLE vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            INSERT INTO vaults (name) VALUES ('People'), ('Movies'), ('Reviews');
+            INSERT INTO field_definitions (vault_id, name, field_type, options) VALUES
+                (2, 'Cast', 'relation', '{"targetVaultId": 1, "multiple": true}'),
+                (2, 'Director', 'relation', '{"targetVaultId": 1, "onDelete": "restrict"}'),
+                (2, 'Producer', 'relation', '{"targetVaultId": 1, "onDelete": "cascade"}'),
+                (3, 'Movie', 'relation', '{"targetVaultId": 2, "onDelete": "cascade"}');
+            INSERT INTO entries (vault_id, title) VALUES
+                (1, 'Actor'), (1, 'Director'), (1, 'Producer');
+            INSERT INTO entries (vault_id, title, metadata) VALUES
+                (2, 'Heat', '{"1": [{"entry_id": 1, "vault_id": 1}, {"entry_id": 3, "vault_id": 1}], "2": {"entry_id": 2, "vault_id": 1}}'),
+                (2, 'Ronin', '{"1": [{"entry_id": 1, "vault_id": 1}], "3": {"entry_id": 3, "vault_id": 1}}'),
+                (3, 'Ronin review', '{"4": {"entry_id": 5, "vault_id": 2}}');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        RelationIndexService::rebuild(&conn).await.unwrap();
+        conn
+    }
+
+    async fn apply(conn: &DatabaseConnection, plan: &DeletePlan) {
+        let txn = conn.begin().await.unwrap();
+        ReferentialActionService::apply(&txn, plan).await.unwrap();
+        txn.commit().await.unwrap();
+    }
+
+    async fn metadata(conn: &DatabaseConnection, id: i32) -> Value {
+        let entry = Entry::find_by_id(id).one(conn).await.unwrap().unwrap();
+        serde_json::from_str(entry.metadata.as_deref().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_null_removes_reference() {
+        let conn = setup_test_db().await;
+
+        let plan = ReferentialActionService::plan_entry_delete(&conn, 1)
+            .await
+            .unwrap();
+        assert!(plan.impact.can_delete);
+        assert_eq!(plan.impact.entries_deleted, 1);
+        assert_eq!(plan.impact.count(RelationDeleteAction::SetNull), 2);
+
+        apply(&conn, &plan).await;
+
+        assert_eq!(
+            metadata(&conn, 4).await["1"],
+            serde_json::json!([{"entry_id": 3, "vault_id": 1}])
+        );
+        assert_eq!(metadata(&conn, 5).await["1"], serde_json::json!([]));
+        assert!(Entry::find_by_id(1).one(&conn).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_null_reports_emptied_required_fields() {
+        let conn = setup_test_db().await;
+        sea_orm::ConnectionTrait::execute_unprepared(
+            &conn,
+            "UPDATE field_definitions SET required = 1 WHERE id = 1",
+        )
+        .await
+        .unwrap();
+
+        // Heat keeps the producer in its cast, Ronin is left without one
+        let plan = ReferentialActionService::plan_entry_delete(&conn, 1)
+            .await
+            .unwrap();
+        let emptied: Vec<(&str, bool)> = plan
+            .impact
+            .references
+            .iter()
+            .map(|r| (r.source_title.as_str(), r.empties_required))
+            .collect();
+        assert_eq!(emptied, vec![("Heat", false), ("Ronin", true)]);
+        assert!(plan.impact.can_delete);
+    }
+
+    #[tokio::test]
+    async fn test_restrict_blocks_delete() {
+        let conn = setup_test_db().await;
+
+        let plan = ReferentialActionService::plan_entry_delete(&conn, 2)
+            .await
+            .unwrap();
+        assert!(!plan.impact.can_delete);
+        assert_eq!(plan.impact.references[0].source_title, "Heat");
+        assert!(matches!(
+            ReferentialActionService::ensure_allowed(&plan),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cascade_is_transitive() {
+        let conn = setup_test_db().await;
+
+        // Producer cascades to Ronin, which cascades to its review; Heat only
+        // loses the producer from its cast (set-null)
+        let plan = ReferentialActionService::plan_entry_delete(&conn, 3)
+            .await
+            .unwrap();
+        assert!(plan.impact.can_delete);
+        assert_eq!(
+            plan.entries().iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![3, 5, 6]
+        );
+        assert_eq!(plan.impact.count(RelationDeleteAction::Cascade), 2);
+        assert_eq!(plan.impact.count(RelationDeleteAction::SetNull), 1);
+
+        apply(&conn, &plan).await;
+
+        assert_eq!(Entry::find().all(&conn).await.unwrap().len(), 3);
+        assert_eq!(
+            metadata(&conn, 4).await["1"],
+            serde_json::json!([{"entry_id": 1, "vault_id": 1}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vault_delete_removes_targeting_fields() {
+        let conn = setup_test_db().await;
+
+        // Heat's director field restricts deleting People
+        let plan = ReferentialActionService::plan_vault_delete(&conn, 1)
+            .await
+            .unwrap();
+        assert!(!plan.impact.can_delete);
+        assert_eq!(plan.impact.fields_removed.len(), 3);
+
+        // Reviews can go: nothing references them
+        let plan = ReferentialActionService::plan_vault_delete(&conn, 3)
+            .await
+            .unwrap();
+        assert!(plan.impact.can_delete);
+        assert!(plan.impact.references.is_empty());
+        assert!(plan.impact.fields_removed.is_empty());
+    }
+
+    #[test]
+    fn test_clear_references() {
+        let metadata = r#"{"1": [{"entry_id": 3, "vault_id": 1}, {"entry_id": 4, "vault_id": 1}], "2": {"entry_id": 3, "vault_id": 1}, "5": "keep"}"#;
+        let cleared = clear_references(Some(metadata), &[(1, 3), (2, 3)]).unwrap();
+        let value: Value = serde_json::from_str(&cleared).unwrap();
+
+        assert_eq!(
+            value["1"],
+            serde_json::json!([{"entry_id": 4, "vault_id": 1}])
+        );
+        assert!(value.get("2").is_none());
+        assert_eq!(value["5"], "keep");
+    }
+}
@@ -1,6 +1,6 @@
 //! Relation service for resolving cross-vault references.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 
@@ -71,6 +71,13 @@ impl RelationService {
             return Ok(results);
         }
 
+        // Multi-valued fields can repeat the same reference across entries
+        let mut seen = HashSet::new();
+        let relations: Vec<RelationRef> = relations
+            .into_iter()
+            .filter(|r| seen.insert((r.entry_id, r.vault_id)))
+            .collect();
+
         // Collect unique entry IDs to fetch
         let entry_ids: Vec<i32> = relations.iter().map(|r| r.entry_id).collect();
 
@@ -125,11 +132,14 @@ impl RelationService {
     /// Searches entries in a vault for the relation picker.
     ///
     /// Returns a list of entry summaries matching the search query.
+    /// Entries in `exclude_entry_ids` (e.g. already selected in a
+    /// multi-valued field) are left out.
     pub async fn search_entries_for_picker(
         conn: &DatabaseConnection,
         vault_id: i32,
         query: &str,
         limit: i32,
+        exclude_entry_ids: &[i32],
     ) -> AppResult<Vec<EntryPickerItem>> {
         // Verify vault exists
         crate::core::find_vault_or_error(conn, vault_id).await?;
@@ -137,19 +147,22 @@ impl RelationService {
         let query_trimmed = query.trim();
         let limit = limit.max(1).min(100) as u64; // Clamp between 1 and 100
 
+        let mut select = Entry::find().filter(entry::Column::VaultId.eq(vault_id));
+        if !exclude_entry_ids.is_empty() {
+            select = select.filter(entry::Column::Id.is_not_in(exclude_entry_ids.to_vec()));
+        }
+
         // Search by title (case-insensitive contains)
         let entries: Vec<entry::Model> = if query_trimmed.is_empty() {
             // If no query, return most recent entries
-            Entry::find()
-                .filter(entry::Column::VaultId.eq(vault_id))
+            select
                 .order_by_desc(entry::Column::UpdatedAt)
                 .limit(limit)
                 .all(conn)
                 .await?
         } else {
             // Search by title using LIKE
-            Entry::find()
-                .filter(entry::Column::VaultId.eq(vault_id))
+            select
                 .filter(entry::Column::Title.contains(query_trimmed))
                 .order_by_desc(entry::Column::UpdatedAt)
                 .limit(limit)
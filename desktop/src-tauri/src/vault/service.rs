@@ -1,11 +1,15 @@
 //! Vault service for business logic.
 
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, EntityTrait, QueryOrder, Set,
+    ActiveModelTrait, DatabaseConnection, EntityTrait, QueryOrder, Set, TransactionTrait,
 };
 
+use crate::attachment::{AttachmentRegistry, AttachmentStorage};
 use crate::core::{AppError, AppResult, now_formatted};
 use crate::entities::vault::{self, ActiveModel, Entity as Vault};
+use crate::entry::EntryImageService;
+use crate::image::ImageStorage;
+use crate::relation::ReferentialActionService;
 
 use super::{CreateVaultDto, UpdateVaultDto, VaultDto};
 
@@ -101,7 +105,19 @@ impl VaultService {
     }
 
     /// Deletes a vault and all its entries (cascade).
-    pub async fn delete(conn: &DatabaseConnection, id: i32) -> AppResult<()> {
+    ///
+    /// References to the deleted entries from other vaults follow their
+    /// fields' on-delete policies, and relation fields targeting this vault
+    /// are removed.
+    ///
+    /// Releases the cover images of the deleted entries and removes the
+    /// legacy `images/<vault_id>/` directory when storage is provided.
+    pub async fn delete(
+        conn: &DatabaseConnection,
+        id: i32,
+        image_storage: Option<&ImageStorage>,
+        attachment_storage: Option<&AttachmentStorage>,
+    ) -> AppResult<()> {
         let vault = Vault::find_by_id(id)
             .one(conn)
             .await?
@@ -109,7 +125,46 @@ impl VaultService {
 
         log::info!("Deleting vault: {} (id={})", vault.name, vault.id);
 
-        Vault::delete_by_id(id).exec(conn).await?;
+        let txn = conn.begin().await?;
+        let plan = ReferentialActionService::plan_vault_delete(&txn, id).await?;
+        ReferentialActionService::ensure_allowed(&plan)?;
+
+        ReferentialActionService::apply(&txn, &plan).await?;
+        // Released while the vault's file fields still exist
+        let mut unreferenced = Vec::new();
+        for entry in plan.entries() {
+            unreferenced.extend(
+                AttachmentRegistry::sync_entry(
+                    &txn,
+                    entry.vault_id,
+                    entry.metadata.as_deref(),
+                    None,
+                )
+                .await?,
+            );
+        }
+        let unreferenced_covers = EntryImageService::release_covers(
+            &txn,
+            plan.entries()
+                .iter()
+                .flat_map(|e| [&e.cover_image_path, &e.cover_original_path])
+                .flatten(),
+        )
+        .await?;
+        Vault::delete_by_id(id).exec(&txn).await?;
+        txn.commit().await?;
+
+        if let Some(storage) = image_storage {
+            EntryImageService::delete_covers(storage, &unreferenced_covers);
+
+            if let Err(e) = storage.remove_vault_dir(id) {
+                log::warn!("Failed to remove image directory for vault {}: {}", id, e);
+            }
+        }
+
+        if let Some(storage) = attachment_storage {
+            AttachmentRegistry::delete_files(storage, &unreferenced);
+        }
 
         Ok(())
     }
@@ -118,7 +173,7 @@ impl VaultService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sea_orm::{Database, DatabaseConnection};
+    use sea_orm::{ConnectionTrait, Database, DatabaseConnection};
 
     async fn setup_test_db() -> DatabaseConnection {
         let conn = Database::connect("sqlite::memory:").await.unwrap();
@@ -136,6 +191,52 @@ mod tests {
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
+
+            CREATE TABLE entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT,
+                metadata TEXT,
+                cover_image_path TEXT,
+                cover_original_path TEXT,
+                cover_blurhash TEXT,
+                cover_palette TEXT,
+                cover_phash TEXT,
+                search_text TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE field_definitions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id INTEGER NOT NULL REFERENCES vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                field_type TEXT NOT NULL,
+                options TEXT,
+                position INTEGER NOT NULL DEFAULT 0,
+                required INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE entry_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+                field_id INTEGER NOT NULL REFERENCES field_definitions(id) ON DELETE CASCADE,
+                target_entry_id INTEGER NOT NULL,
+                target_vault_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(source_entry_id, field_id, target_entry_id)
+            );
+
+            CREATE TABLE image_blobs (
+                hash TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                ref_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
             "#,
         )
         .await
@@ -277,9 +378,42 @@ mod tests {
         .await
         .unwrap();
 
-        VaultService::delete(&conn, created.id).await.unwrap();
+        VaultService::delete(&conn, created.id, None, None).await.unwrap();
 
         let result = VaultService::get(&conn, created.id).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_delete_vault_keeps_shared_shard() {
+        let conn = setup_test_db().await;
+        let temp_dir = std::env::temp_dir().join("vaultrs_test_delete_vault_shard");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let storage = ImageStorage::new(&temp_dir);
+
+        // Vault 12 shares its legacy directory name with the `12` shard,
+        // where a cover of vault 1 is stored
+        let vault_dir = temp_dir.join("images").join("12");
+        let shard_dir = temp_dir.join("images").join("blobs").join("12");
+        std::fs::create_dir_all(&vault_dir).unwrap();
+        std::fs::create_dir_all(&shard_dir).unwrap();
+        std::fs::write(shard_dir.join("12ab.png"), b"shared").unwrap();
+        std::fs::write(vault_dir.join("legacy.jpg"), b"legacy").unwrap();
+        conn.execute_unprepared(
+            r#"
+            INSERT INTO vaults (id, name) VALUES (1, 'Books'), (12, 'Movies');
+            INSERT INTO entries (vault_id, title, cover_image_path) VALUES (1, 'Dune', 'blobs/12/12ab.png');
+            INSERT INTO image_blobs (hash, path, size, ref_count) VALUES ('12ab', 'blobs/12/12ab.png', 6, 1);
+            "#,
+        )
+        .await
+        .unwrap();
+
+        VaultService::delete(&conn, 12, Some(&storage), None).await.unwrap();
+
+        assert!(shard_dir.join("12ab.png").exists());
+        assert!(!vault_dir.exists());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 }
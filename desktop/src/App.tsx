@@ -1,6 +1,6 @@
 // Main App component - redesigned with three-panel layout
 
-import { useCallback } from 'react';
+import { useCallback, useState } from 'react';
 import { Archive } from 'lucide-react';
 import { EmptyState } from '@/components/EmptyState';
 import { MainLayout } from './components/layout';
@@ -20,7 +20,9 @@ import {
     AlertDialogHeader,
     AlertDialogTitle,
 } from '@/components/ui/alert-dialog';
-import { useVaultStore } from '@/modules/vault';
+import { useVaultStore, vaultApi } from '@/modules/vault';
+import { DeleteImpactNotice } from '@/modules/relation';
+import type { DeleteImpact } from '@/modules/relation';
 import { useUIStore } from '@/stores/uiStore';
 import { useGlobalShortcuts } from '@/hooks/useGlobalShortcuts';
 
@@ -45,6 +47,16 @@ function App() {
 
     const activeVault = vaults.find((v) => v.id === activeVaultId) ?? null;
 
+    // Impact of deleting the vault on relations from other vaults
+    const [deleteVaultBlocked, setDeleteVaultBlocked] = useState(false);
+    const loadVaultDeleteImpact = useCallback(
+        () => vaultApi.getDeleteImpact(activeVaultId!),
+        [activeVaultId]
+    );
+    const handleVaultDeleteImpact = useCallback((impact: DeleteImpact) => {
+        setDeleteVaultBlocked(!impact.can_delete);
+    }, []);
+
     const handleDeleteVault = useCallback(async () => {
         if (!activeVault) return;
         await deleteVault(activeVault.id);
@@ -112,10 +124,15 @@ function App() {
                                 This will also delete all entries in this vault. This action cannot be undone.
                             </AlertDialogDescription>
                         </AlertDialogHeader>
+                        <DeleteImpactNotice
+                            load={loadVaultDeleteImpact}
+                            onLoaded={handleVaultDeleteImpact}
+                        />
                         <AlertDialogFooter>
                             <AlertDialogCancel>Cancel</AlertDialogCancel>
                             <AlertDialogAction
                                 onClick={handleDeleteVault}
+                                disabled={deleteVaultBlocked}
                                 className="bg-destructive text-destructive-foreground hover:bg-destructive/90"
                             >
                                 Delete Vault
@@ -21,10 +21,12 @@ import {
     AlertDialogTitle,
 } from '@/components/ui/alert-dialog';
 import { useUIStore } from '@/stores/uiStore';
-import { useEntryStore, CoverImageDisplay, EditEntryForm } from '@/modules/entry';
+import { useEntryStore, CoverImageDisplay, EditEntryForm, entryApi } from '@/modules/entry';
 import type { Entry } from '@/modules/entry';
 import { useFieldStore, CustomFieldsSection } from '@/modules/field';
 import type { EntryMetadata } from '@/modules/field';
+import { BacklinksSection, DeleteImpactNotice } from '@/modules/relation';
+import type { DeleteImpact } from '@/modules/relation';
 
 type PanelMode = 'view' | 'edit';
 
@@ -39,11 +41,12 @@ export function DetailPanel() {
     } = useUIStore();
 
     const { entries, searchResults, searchQuery, deleteEntry } = useEntryStore();
-    const { fields } = useFieldStore();
+    const { fields, rules } = useFieldStore();
 
     const [mode, setMode] = useState<PanelMode>('view');
     const [isDeleting, setIsDeleting] = useState(false);
     const [showDeleteDialog, setShowDeleteDialog] = useState(false);
+    const [deleteBlocked, setDeleteBlocked] = useState(false);
 
     // Find the selected entry from current entries or search results
     const entry: Entry | null = useMemo(() => {
@@ -54,6 +57,7 @@ export function DetailPanel() {
 
     // Reset mode when entry changes, or switch to edit if autoEditMode is set
     useEffect(() => {
+        setDeleteBlocked(false);
         if (autoEditMode) {
             setMode('edit');
             setAutoEditMode(false);
@@ -93,6 +97,15 @@ export function DetailPanel() {
         }
     }, [entry, deleteEntry, handleClose]);
 
+    // Impact of deleting on entries that reference this one
+    const loadDeleteImpact = useCallback(
+        () => entryApi.getDeleteImpact(selectedEntryId!),
+        [selectedEntryId]
+    );
+    const handleDeleteImpact = useCallback((impact: DeleteImpact) => {
+        setDeleteBlocked(!impact.can_delete);
+    }, []);
+
     // After save, switch back to view mode
     const handleSaveComplete = useCallback(() => {
         setMode('view');
@@ -208,6 +221,7 @@ export function DetailPanel() {
                         <EditEntryForm
                             entry={entry}
                             fields={fields}
+                            rules={rules}
                             onSaveComplete={handleSaveComplete}
                             onCancel={handleCancelEdit}
                         />
@@ -242,8 +256,13 @@ export function DetailPanel() {
                         <CustomFieldsSection
                             fields={fields}
                             metadata={metadata}
+                            computed={entry.computed}
+                            entryId={entry.id}
                         />
 
+                        {/* Incoming relations */}
+                        <BacklinksSection entryId={entry.id} />
+
                         {/* Timestamps */}
                         <div className="space-y-2 pt-4 border-t border-border">
                             <div className="flex items-center gap-2 text-xs text-muted-foreground">
@@ -269,13 +288,19 @@ export function DetailPanel() {
                             cannot be undone.
                         </AlertDialogDescription>
                     </AlertDialogHeader>
+                    {showDeleteDialog && (
+                        <DeleteImpactNotice
+                            load={loadDeleteImpact}
+                            onLoaded={handleDeleteImpact}
+                        />
+                    )}
                     <AlertDialogFooter>
                         <AlertDialogCancel disabled={isDeleting}>
                             Cancel
                         </AlertDialogCancel>
                         <AlertDialogAction
                             onClick={handleDelete}
-                            disabled={isDeleting}
+                            disabled={isDeleting || deleteBlocked}
                             className="bg-destructive text-destructive-foreground hover:bg-destructive/90"
                         >
                             {isDeleting ? (
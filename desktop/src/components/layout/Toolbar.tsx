@@ -12,7 +12,7 @@ import {
 import { Button } from '@/components/ui/button';
 import { useUIStore } from '@/stores/uiStore';
 import { useVaultStore } from '@/modules/vault';
-import { useEntryStore, SearchInput } from '@/modules/entry';
+import { useEntryStore, SearchInput, EntrySortControl, EntryFilterControl } from '@/modules/entry';
 import { useShallow } from 'zustand/react/shallow';
 import { cn } from '@/lib/utils';
 
@@ -69,6 +69,12 @@ export function Toolbar() {
                 )}
             </div>
 
+            {/* Filters */}
+            {activeVaultId && <EntryFilterControl vaultId={activeVaultId} />}
+
+            {/* Sort order */}
+            {activeVaultId && <EntrySortControl vaultId={activeVaultId} />}
+
             {/* View mode toggle */}
             <div className="flex items-center rounded-md border border-border">
                 <Button
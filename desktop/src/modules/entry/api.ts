@@ -1,7 +1,8 @@
 // Entry API - Type-safe Tauri command wrappers
 
 import { invoke } from '@tauri-apps/api/core';
-import type { Entry, CreateEntryParams, UpdateEntryParams, PaginatedEntries, SearchResult } from './types';
+import type { DeleteImpact } from '@/modules/relation';
+import type { Entry, CreateEntryParams, UpdateEntryParams, PaginatedEntries, EntrySort, EntryFilters, ChoiceCount, NumericSummary, VaultExport, SearchResult, ImageGcReport, ImageTransform, DuplicateCoverCluster, ImageMetadata, ImageTagMapping, MetadataValidationResult } from './types';
 
 // Cache appDataDir() result - the path doesn't change during runtime
 let cachedAppDataDir: string | null = null;
@@ -36,15 +37,62 @@ export const entryApi = {
 
     /**
      * Lists entries for a vault with pagination.
+     * Without a sort order entries are listed newest first.
+     * Only entries matching every filter are listed.
      */
-    async list(vaultId: number, page: number = 0, limit: number = 100): Promise<PaginatedEntries> {
+    async list(
+        vaultId: number,
+        page: number = 0,
+        limit: number = 100,
+        sort?: EntrySort,
+        filters?: EntryFilters
+    ): Promise<PaginatedEntries> {
         return invoke<PaginatedEntries>('list_entries', {
             vaultId,
             page,
             limit,
+            sort: sort ?? null,
+            filters: filters ?? null,
         });
     },
 
+    /**
+     * Counts entries per choice of a select or multiselect field,
+     * over the entries matching the given filters.
+     */
+    async getChoiceCounts(fieldId: number, filters?: EntryFilters): Promise<ChoiceCount[]> {
+        return invoke<ChoiceCount[]>('get_choice_counts', {
+            fieldId,
+            filters: filters ?? null,
+        });
+    },
+
+    /**
+     * Summarizes a number or rating field (count, average, min, max)
+     * over the entries matching the given filters.
+     */
+    async getFieldSummary(fieldId: number, filters?: EntryFilters): Promise<NumericSummary> {
+        return invoke<NumericSummary>('get_field_summary', {
+            fieldId,
+            filters: filters ?? null,
+        });
+    },
+
+    /**
+     * Finds the entry holding an identifier, typed in any form the identifier
+     * field accepts. Resolves to null when no entry has it.
+     */
+    async findByIdentifier(fieldId: number, value: string): Promise<Entry | null> {
+        return invoke<Entry | null>('find_entry_by_identifier', { fieldId, value });
+    },
+
+    /**
+     * Exports all entries of a vault with stored and computed field values.
+     */
+    async exportVault(vaultId: number): Promise<VaultExport> {
+        return invoke<VaultExport>('export_vault_entries', { vaultId });
+    },
+
     /**
      * Counts entries in a vault.
      */
@@ -56,14 +104,39 @@ export const entryApi = {
      * Updates an existing entry.
      */
     async update(id: number, params: UpdateEntryParams): Promise<Entry> {
+        const appDataDirPath = await getAppDataDir();
         return invoke<Entry>('update_entry', {
             id,
             title: params.title ?? null,
             description: params.description ?? null,
             metadata: params.metadata ?? null,
+            appDataDir: appDataDirPath,
         });
     },
 
+    /**
+     * Validates metadata against the vault's fields, including unique values
+     * held by other entries. Pass the entry being edited to leave it out.
+     */
+    async validateMetadata(
+        vaultId: number,
+        metadata: string | null,
+        entryId?: number
+    ): Promise<MetadataValidationResult> {
+        return invoke<MetadataValidationResult>('validate_entry_metadata', {
+            vaultId,
+            entryId: entryId ?? null,
+            metadata,
+        });
+    },
+
+    /**
+     * Reports what deleting an entry would do to entries that reference it.
+     */
+    async getDeleteImpact(id: number): Promise<DeleteImpact> {
+        return invoke<DeleteImpact>('get_entry_delete_impact', { id });
+    },
+
     /**
      * Deletes an entry and its cover image.
      */
@@ -86,13 +159,44 @@ export const entryApi = {
 
     /**
      * Uploads a cover image for an entry from a local file.
+     * With keepOriginal, the unedited file is kept for later edits.
      */
-    async uploadCoverImage(entryId: number, filePath: string): Promise<Entry> {
+    async uploadCoverImage(
+        entryId: number,
+        filePath: string,
+        transform?: ImageTransform,
+        keepOriginal: boolean = false
+    ): Promise<Entry> {
         const appDataDirPath = await getAppDataDir();
         return invoke<Entry>('upload_entry_cover_image', {
             entryId,
             filePath,
             appDataDir: appDataDirPath,
+            transform,
+            keepOriginal,
+        });
+    },
+
+    /**
+     * Re-edits an entry's cover image, starting from the kept original if any.
+     */
+    async editCover(entryId: number, transform: ImageTransform): Promise<Entry> {
+        const appDataDirPath = await getAppDataDir();
+        return invoke<Entry>('edit_entry_cover', {
+            entryId,
+            transform,
+            appDataDir: appDataDirPath,
+        });
+    },
+
+    /**
+     * Restores an entry's cover image to its unedited original.
+     */
+    async revertCover(entryId: number): Promise<Entry> {
+        const appDataDirPath = await getAppDataDir();
+        return invoke<Entry>('revert_entry_cover', {
+            entryId,
+            appDataDir: appDataDirPath,
         });
     },
 
@@ -129,4 +233,48 @@ export const entryApi = {
             appDataDir: appDataDirPath,
         });
     },
+
+    /**
+     * Removes image files no entry references and reports missing covers.
+     * With dryRun, only reports what would be removed.
+     */
+    async collectImageGarbage(dryRun: boolean = false): Promise<ImageGcReport> {
+        const appDataDirPath = await getAppDataDir();
+        return invoke<ImageGcReport>('collect_image_garbage', {
+            appDataDir: appDataDirPath,
+            dryRun,
+        });
+    },
+
+    /**
+     * Lists clusters of entries with near-identical covers.
+     * Searches all vaults when vaultId is omitted.
+     */
+    async findDuplicateCovers(vaultId?: number, maxDistance?: number): Promise<DuplicateCoverCluster[]> {
+        return invoke<DuplicateCoverCluster[]>('find_duplicate_covers', {
+            vaultId,
+            maxDistance,
+        });
+    },
+
+    /**
+     * Reads EXIF/XMP metadata from a local image file.
+     */
+    async readImageMetadata(filePath: string): Promise<ImageMetadata> {
+        return invoke<ImageMetadata>('read_image_metadata', { filePath });
+    },
+
+    /**
+     * Lists a vault's mappings from image tags to fields.
+     */
+    async getImageMetadataMappings(vaultId: number): Promise<ImageTagMapping[]> {
+        return invoke<ImageTagMapping[]>('get_image_metadata_mappings', { vaultId });
+    },
+
+    /**
+     * Replaces a vault's mappings from image tags to fields.
+     */
+    async setImageMetadataMappings(vaultId: number, mappings: ImageTagMapping[]): Promise<ImageTagMapping[]> {
+        return invoke<ImageTagMapping[]>('set_image_metadata_mappings', { vaultId, mappings });
+    },
 };
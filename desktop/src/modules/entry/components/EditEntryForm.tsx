@@ -7,7 +7,9 @@ import { Label } from '@/components/ui/label';
 import { Textarea } from '@/components/ui/textarea';
 import { Loader2, Save } from 'lucide-react';
 import type { Entry } from '../types';
-import type { FieldDefinition, EntryMetadata, EntryMetadataValue } from '@/modules/field';
+import type { FieldDefinition, FieldRule, EntryMetadata, EntryMetadataValue } from '@/modules/field';
+import { evaluateFieldRules, isDerivedField } from '@/modules/field';
+import { entryApi } from '../api';
 import { useEntryStore } from '../store';
 import { CustomFieldInput } from '@/modules/field/components/CustomFieldInput';
 import { CoverImageDisplay, CoverImageUploader } from './';
@@ -15,6 +17,8 @@ import { CoverImageDisplay, CoverImageUploader } from './';
 interface EditEntryFormProps {
     entry: Entry;
     fields: FieldDefinition[];
+    /** Validation rules of the vault; hide fields and make them required */
+    rules?: FieldRule[];
     onSaveComplete: () => void;
     onCancel: () => void;
 }
@@ -22,6 +26,7 @@ interface EditEntryFormProps {
 export function EditEntryForm({
     entry,
     fields,
+    rules = [],
     onSaveComplete,
     onCancel,
 }: EditEntryFormProps) {
@@ -40,11 +45,20 @@ export function EditEntryForm({
         })()
         : {};
 
+    // Lookup, rollup and formula values are computed, not entered
+    const editableFields = fields.filter((field) => !isDerivedField(field));
+
     // Form state
     const [title, setTitle] = useState(entry.title);
     const [description, setDescription] = useState(entry.description || '');
     const [metadata, setMetadata] = useState<EntryMetadata>(existingMetadata);
 
+    // Fields hidden or required by the vault's rules, given the values entered
+    const ruleState = evaluateFieldRules(rules, metadata);
+    const visibleFields = editableFields.filter((field) => !ruleState.hidden.has(field.id));
+    const isRequired = (field: FieldDefinition) =>
+        field.required || ruleState.required.has(field.id);
+
     // Validation - uses field.id as key
     const [titleError, setTitleError] = useState<string | null>(null);
     const [fieldErrors, setFieldErrors] = useState<Record<string, string>>({});
@@ -62,11 +76,16 @@ export function EditEntryForm({
         }
 
         // Validate required custom fields - use field.id as key
-        for (const field of fields) {
+        for (const field of visibleFields) {
             const fieldKey = field.id.toString();
-            if (field.required) {
+            if (isRequired(field)) {
                 const value = metadata[fieldKey];
-                if (value === null || value === undefined || value === '') {
+                if (
+                    value === null ||
+                    value === undefined ||
+                    value === '' ||
+                    (Array.isArray(value) && value.length === 0)
+                ) {
                     newFieldErrors[fieldKey] = `${field.name} is required`;
                     isValid = false;
                 }
@@ -108,6 +127,14 @@ export function EditEntryForm({
                             newFieldErrors[fieldKey] = `Maximum ${field.options.maxLength} characters`;
                             isValid = false;
                         }
+                        if (
+                            field.options?.minLength &&
+                            typeof value === 'string' &&
+                            [...value].length < field.options.minLength
+                        ) {
+                            newFieldErrors[fieldKey] = `Minimum ${field.options.minLength} characters`;
+                            isValid = false;
+                        }
                         break;
                 }
             }
@@ -128,10 +155,22 @@ export function EditEntryForm({
         setError(null);
 
         try {
+            // Patterns and unique values are checked by the backend, which
+            // reports each error on its field
+            const metadataJson = JSON.stringify(metadata);
+            const validation = await entryApi.validateMetadata(entry.vault_id, metadataJson, entry.id);
+            const backendErrors = Object.entries(validation.field_errors);
+            if (backendErrors.length > 0) {
+                setFieldErrors(
+                    Object.fromEntries(backendErrors.map(([fieldKey, errors]) => [fieldKey, errors.join('; ')]))
+                );
+                return;
+            }
+
             await updateEntry(entry.id, {
                 title: title.trim(),
                 description: description.trim() || null,
-                metadata: JSON.stringify(metadata),
+                metadata: metadataJson,
             });
             onSaveComplete();
         } catch (err) {
@@ -211,13 +250,13 @@ export function EditEntryForm({
             </div>
 
             {/* Custom Fields */}
-            {fields.length > 0 && (
+            {visibleFields.length > 0 && (
                 <div className="space-y-4">
                     <h4 className="text-sm font-medium text-muted-foreground">
                         Custom Fields
                     </h4>
                     <div className="space-y-4">
-                        {fields.map((field) => {
+                        {visibleFields.map((field) => {
                             const fieldKey = field.id.toString();
                             return (
                                 <CustomFieldInput
@@ -228,6 +267,8 @@ export function EditEntryForm({
                                         handleFieldChange(field.id, value)
                                     }
                                     error={fieldErrors[fieldKey]}
+                                    required={isRequired(field)}
+                                    entryId={entry.id}
                                 />
                             );
                         })}
@@ -43,7 +43,7 @@ export function EntryDetailDialog({
     const [isEditing, setIsEditing] = useState(false);
     const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
     const { deleteEntry } = useEntryStore();
-    const { fields, fetchFields } = useFieldStore();
+    const { fields, rules, fetchFields } = useFieldStore();
 
     // Fetch field definitions when dialog opens
     useEffect(() => {
@@ -160,6 +160,7 @@ export function EntryDetailDialog({
                         <EditEntryForm
                             entry={entry}
                             fields={fields}
+                            rules={rules}
                             onSaveComplete={handleSaveComplete}
                             onCancel={() => setIsEditing(false)}
                         />
@@ -193,6 +194,8 @@ export function EntryDetailDialog({
                                 <CustomFieldsSection
                                     fields={fields}
                                     metadata={metadata}
+                                    computed={entry.computed}
+                                    entryId={entry.id}
                                 />
                             )}
 
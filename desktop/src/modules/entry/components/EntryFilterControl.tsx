@@ -0,0 +1,295 @@
+// Entry Filter Control - Narrow entries by choices, minimum ratings and distance
+
+import { useEffect, useState } from 'react';
+import { Filter, MapPin, X } from 'lucide-react';
+import { Badge } from '@/components/ui/badge';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Popover, PopoverContent, PopoverTrigger } from '@/components/ui/popover';
+import { DEFAULT_RATING_MAX, useFieldStore } from '@/modules/field';
+import { entryApi } from '../api';
+import { useEntryStore } from '../store';
+import type {
+    ChoiceCount,
+    ChoiceFilter,
+    ChoiceMatch,
+    DistanceFilter,
+    NumericSummary,
+    RangeFilter,
+} from '../types';
+
+interface DistanceDraft {
+    lat: string;
+    lng: string;
+    radiusKm: string;
+}
+
+const EMPTY_DRAFT: DistanceDraft = { lat: '', lng: '', radiusKm: '10' };
+
+interface EntryFilterControlProps {
+    vaultId: number;
+}
+
+export function EntryFilterControl({ vaultId }: EntryFilterControlProps) {
+    const fields = useFieldStore((s) => s.fields);
+    const filters = useEntryStore((s) => s.filtersByVault[vaultId]);
+    const setFilters = useEntryStore((s) => s.setFilters);
+    const [isOpen, setIsOpen] = useState(false);
+    const [counts, setCounts] = useState<Record<number, ChoiceCount[]>>({});
+    const [summaries, setSummaries] = useState<Record<number, NumericSummary>>({});
+    const [drafts, setDrafts] = useState<Record<number, DistanceDraft>>({});
+
+    const vaultFields = fields.filter((f) => f.vault_id === vaultId);
+    const choiceFields = vaultFields.filter(
+        (f) => f.field_type === 'select' || f.field_type === 'multiselect'
+    );
+    const ratingFields = vaultFields.filter((f) => f.field_type === 'rating');
+    const locationFields = vaultFields.filter((f) => f.field_type === 'location');
+    const choices = filters?.choices ?? [];
+    const ranges = filters?.ranges ?? [];
+    const distances = filters?.withinDistance ?? [];
+    const activeCount =
+        choices.reduce((n, f) => n + f.choices.length, 0) +
+        ranges.length +
+        distances.length +
+        (filters?.withinBounds?.length ?? 0);
+
+    // Counts follow the current filters so they show what a click would leave
+    useEffect(() => {
+        if (!isOpen) return;
+        let cancelled = false;
+
+        Promise.all([
+            Promise.all(choiceFields.map((f) => entryApi.getChoiceCounts(f.id, filters))),
+            Promise.all(ratingFields.map((f) => entryApi.getFieldSummary(f.id, filters))),
+        ])
+            .then(([countLists, summaryList]) => {
+                if (!cancelled) {
+                    setCounts(Object.fromEntries(choiceFields.map((f, i) => [f.id, countLists[i]])));
+                    setSummaries(Object.fromEntries(ratingFields.map((f, i) => [f.id, summaryList[i]])));
+                }
+            })
+            .catch((err) => console.error('Failed to load filter counts:', err));
+
+        return () => {
+            cancelled = true;
+        };
+    }, [isOpen, filters, fields, vaultId]);
+
+    if (choiceFields.length === 0 && ratingFields.length === 0 && locationFields.length === 0) {
+        return null;
+    }
+
+    const choiceFilterFor = (fieldId: number): ChoiceFilter =>
+        choices.find((f) => f.fieldId === fieldId) ?? { fieldId, choices: [] };
+
+    const updateChoiceFilter = (next: ChoiceFilter) => {
+        setFilters(vaultId, {
+            ...filters,
+            choices: [...choices.filter((f) => f.fieldId !== next.fieldId), next],
+        });
+    };
+
+    const toggleChoice = (fieldId: number, choice: string) => {
+        const current = choiceFilterFor(fieldId);
+        const next = current.choices.includes(choice)
+            ? current.choices.filter((c) => c !== choice)
+            : [...current.choices, choice];
+        updateChoiceFilter({ ...current, choices: next });
+    };
+
+    const toggleMinimum = (fieldId: number, min: number) => {
+        const current = ranges.find((r) => r.fieldId === fieldId);
+        const others = ranges.filter((r) => r.fieldId !== fieldId);
+        const next: RangeFilter[] = current?.min === min ? others : [...others, { fieldId, min }];
+        setFilters(vaultId, { ...filters, ranges: next });
+    };
+
+    const draftFor = (fieldId: number): DistanceDraft => {
+        if (drafts[fieldId]) return drafts[fieldId];
+        const active = distances.find((d) => d.fieldId === fieldId);
+        return active
+            ? { lat: String(active.lat), lng: String(active.lng), radiusKm: String(active.radiusKm) }
+            : EMPTY_DRAFT;
+    };
+
+    const updateDraft = (fieldId: number, patch: Partial<DistanceDraft>) => {
+        setDrafts((prev) => ({ ...prev, [fieldId]: { ...draftFor(fieldId), ...patch } }));
+    };
+
+    // Null until the draft holds a point in range and a positive radius
+    const parseDraft = (fieldId: number): DistanceFilter | null => {
+        const draft = draftFor(fieldId);
+        const lat = Number(draft.lat);
+        const lng = Number(draft.lng);
+        const radiusKm = Number(draft.radiusKm);
+        if (!draft.lat.trim() || !draft.lng.trim()) return null;
+        if (!Number.isFinite(lat) || Math.abs(lat) > 90) return null;
+        if (!Number.isFinite(lng) || Math.abs(lng) > 180) return null;
+        if (!Number.isFinite(radiusKm) || radiusKm <= 0) return null;
+        return { fieldId, lat, lng, radiusKm };
+    };
+
+    const setDistance = (fieldId: number, next: DistanceFilter | null) => {
+        const others = distances.filter((d) => d.fieldId !== fieldId);
+        setFilters(vaultId, { ...filters, withinDistance: next ? [...others, next] : others });
+    };
+
+    return (
+        <Popover open={isOpen} onOpenChange={setIsOpen}>
+            <PopoverTrigger asChild>
+                <Button variant={activeCount > 0 ? 'secondary' : 'ghost'} size="sm" className="h-8 gap-1.5">
+                    <Filter className="h-4 w-4" />
+                    Filter
+                    {activeCount > 0 && (
+                        <Badge variant="default" className="h-5 px-1.5 text-xs">
+                            {activeCount}
+                        </Badge>
+                    )}
+                </Button>
+            </PopoverTrigger>
+            <PopoverContent className="w-80 space-y-4" align="end">
+                {choiceFields.map((field) => {
+                    const current = choiceFilterFor(field.id);
+                    const mode: ChoiceMatch = current.mode ?? 'any';
+                    return (
+                        <div key={field.id} className="space-y-2">
+                            <div className="flex items-center justify-between">
+                                <span className="text-sm font-medium">{field.name}</span>
+                                {field.field_type === 'multiselect' && (
+                                    <Button
+                                        variant="ghost"
+                                        size="sm"
+                                        className="h-6 px-2 text-xs"
+                                        onClick={() =>
+                                            updateChoiceFilter({ ...current, mode: mode === 'any' ? 'all' : 'any' })
+                                        }
+                                    >
+                                        {mode === 'any' ? 'Match any' : 'Match all'}
+                                    </Button>
+                                )}
+                            </div>
+                            <div className="flex flex-wrap gap-1.5">
+                                {(counts[field.id] ?? []).map(({ choice, label, color, count }) => (
+                                    <Badge
+                                        key={choice}
+                                        variant={current.choices.includes(choice) ? 'default' : 'outline'}
+                                        className="cursor-pointer gap-1 select-none"
+                                        onClick={() => toggleChoice(field.id, choice)}
+                                    >
+                                        {color && (
+                                            <span
+                                                className="h-2 w-2 rounded-full flex-shrink-0"
+                                                style={{ backgroundColor: color }}
+                                            />
+                                        )}
+                                        {label}
+                                        <span className="text-xs opacity-70">{count}</span>
+                                    </Badge>
+                                ))}
+                            </div>
+                        </div>
+                    );
+                })}
+                {ratingFields.map((field) => {
+                    const max = field.options?.max ?? DEFAULT_RATING_MAX;
+                    const min = ranges.find((r) => r.fieldId === field.id)?.min;
+                    const summary = summaries[field.id];
+                    return (
+                        <div key={field.id} className="space-y-2">
+                            <div className="flex items-center justify-between">
+                                <span className="text-sm font-medium">{field.name}</span>
+                                {summary?.average != null && (
+                                    <span className="text-xs text-muted-foreground">
+                                        Average {summary.average.toFixed(1)} of {summary.count}
+                                    </span>
+                                )}
+                            </div>
+                            <div className="flex flex-wrap gap-1.5">
+                                {Array.from({ length: max }, (_, i) => i + 1).map((value) => (
+                                    <Badge
+                                        key={value}
+                                        variant={min === value ? 'default' : 'outline'}
+                                        className="cursor-pointer select-none"
+                                        onClick={() => toggleMinimum(field.id, value)}
+                                    >
+                                        {value === max ? value : `${value}+`}
+                                    </Badge>
+                                ))}
+                            </div>
+                        </div>
+                    );
+                })}
+                {locationFields.map((field) => {
+                    const draft = draftFor(field.id);
+                    const parsed = parseDraft(field.id);
+                    const active = distances.some((d) => d.fieldId === field.id);
+                    return (
+                        <div key={field.id} className="space-y-2">
+                            <div className="flex items-center justify-between">
+                                <span className="text-sm font-medium">{field.name}</span>
+                                {active && (
+                                    <Button
+                                        variant="ghost"
+                                        size="sm"
+                                        className="h-6 px-2 text-xs"
+                                        onClick={() => setDistance(field.id, null)}
+                                    >
+                                        <X className="h-3 w-3" />
+                                        Remove
+                                    </Button>
+                                )}
+                            </div>
+                            <div className="grid grid-cols-3 gap-1.5">
+                                <Input
+                                    inputMode="decimal"
+                                    value={draft.lat}
+                                    onChange={(e) => updateDraft(field.id, { lat: e.target.value })}
+                                    placeholder="Latitude"
+                                    className="h-8 text-xs"
+                                />
+                                <Input
+                                    inputMode="decimal"
+                                    value={draft.lng}
+                                    onChange={(e) => updateDraft(field.id, { lng: e.target.value })}
+                                    placeholder="Longitude"
+                                    className="h-8 text-xs"
+                                />
+                                <Input
+                                    inputMode="decimal"
+                                    value={draft.radiusKm}
+                                    onChange={(e) => updateDraft(field.id, { radiusKm: e.target.value })}
+                                    placeholder="km"
+                                    className="h-8 text-xs"
+                                />
+                            </div>
+                            <Button
+                                variant={active ? 'secondary' : 'outline'}
+                                size="sm"
+                                className="h-7 w-full gap-1.5 text-xs"
+                                disabled={!parsed}
+                                onClick={() => setDistance(field.id, parsed)}
+                            >
+                                <MapPin className="h-3 w-3" />
+                                Within {draft.radiusKm || '?'} km
+                            </Button>
+                        </div>
+                    );
+                })}
+                {activeCount > 0 && (
+                    <Button
+                        variant="outline"
+                        size="sm"
+                        className="w-full"
+                        onClick={() => {
+                            setDrafts({});
+                            setFilters(vaultId, {});
+                        }}
+                    >
+                        Clear filters
+                    </Button>
+                )}
+            </PopoverContent>
+        </Popover>
+    );
+}
@@ -0,0 +1,78 @@
+// Entry Sort Control - Pick the field and direction entries are listed by
+
+import { ArrowDownNarrowWide, ArrowUpNarrowWide } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import { useFieldStore } from '@/modules/field';
+import { useEntryStore } from '../store';
+
+interface EntrySortControlProps {
+    vaultId: number;
+}
+
+const NEWEST = 'newest';
+const TITLE = 'title';
+
+export function EntrySortControl({ vaultId }: EntrySortControlProps) {
+    const fields = useFieldStore((s) => s.fields);
+    const sort = useEntryStore((s) => s.sortByVault[vaultId]);
+    const setSort = useEntryStore((s) => s.setSort);
+
+    // Relation values have no meaningful order of their own
+    const sortableFields = fields.filter(
+        (f) => f.vault_id === vaultId && f.field_type !== 'relation'
+    );
+
+    const value = !sort ? NEWEST : sort.fieldId === undefined ? TITLE : String(sort.fieldId);
+
+    const handleChange = (next: string) => {
+        if (next === NEWEST) {
+            setSort(vaultId, null);
+        } else {
+            setSort(vaultId, {
+                fieldId: next === TITLE ? undefined : parseInt(next, 10),
+                descending: sort?.descending,
+            });
+        }
+    };
+
+    return (
+        <div className="flex items-center gap-1">
+            <Select value={value} onValueChange={handleChange}>
+                <SelectTrigger className="h-8 w-[160px] text-sm">
+                    <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                    <SelectItem value={NEWEST}>Newest first</SelectItem>
+                    <SelectItem value={TITLE}>Title</SelectItem>
+                    {sortableFields.map((field) => (
+                        <SelectItem key={field.id} value={String(field.id)}>
+                            {field.name}
+                        </SelectItem>
+                    ))}
+                </SelectContent>
+            </Select>
+            {sort && (
+                <Button
+                    variant="ghost"
+                    size="icon"
+                    className="h-8 w-8"
+                    onClick={() => setSort(vaultId, { ...sort, descending: !sort.descending })}
+                    title={sort.descending ? 'Descending' : 'Ascending'}
+                >
+                    {sort.descending ? (
+                        <ArrowDownNarrowWide className="h-4 w-4" />
+                    ) : (
+                        <ArrowUpNarrowWide className="h-4 w-4" />
+                    )}
+                </Button>
+            )}
+        </div>
+    );
+}
@@ -8,6 +8,8 @@ export * from './CreateEntryDialog';
 export * from './CoverImageDisplay';
 export * from './CoverImageUploader';
 export * from './SearchInput';
+export * from './EntrySortControl';
+export * from './EntryFilterControl';
 export * from './HighlightText';
 export * from './EntryGridCard';
 export * from './EntryGridView';
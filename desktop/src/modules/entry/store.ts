@@ -1,7 +1,7 @@
 // Entry store using Zustand
 
 import { create } from 'zustand';
-import type { Entry, CreateEntryParams, UpdateEntryParams } from './types';
+import type { Entry, CreateEntryParams, UpdateEntryParams, EntrySort, EntryFilters } from './types';
 import { entryApi } from './api';
 
 interface EntryState {
@@ -15,6 +15,11 @@ interface EntryState {
     isLoadingMore: boolean;
     error: string | null;
 
+    // Sort order per vault; vaults without one list newest first
+    sortByVault: Record<number, EntrySort>;
+    // Filters per vault; every filter must match
+    filtersByVault: Record<number, EntryFilters>;
+
     // Search state
     searchQuery: string;
     searchResults: Entry[];
@@ -27,6 +32,8 @@ interface EntryState {
     createEntry: (params: CreateEntryParams) => Promise<Entry>;
     updateEntry: (id: number, params: UpdateEntryParams) => Promise<Entry>;
     deleteEntry: (id: number) => Promise<void>;
+    setSort: (vaultId: number, sort: EntrySort | null) => Promise<void>;
+    setFilters: (vaultId: number, filters: EntryFilters) => Promise<void>;
     resetEntries: () => void;
     clearError: () => void;
 
@@ -47,6 +54,8 @@ export const useEntryStore = create<EntryState>((set, get) => ({
     isLoading: false,
     isLoadingMore: false,
     error: null,
+    sortByVault: {},
+    filtersByVault: {},
 
     // Search initial state
     searchQuery: '',
@@ -71,7 +80,13 @@ export const useEntryStore = create<EntryState>((set, get) => ({
         }
 
         try {
-            const result = await entryApi.list(vaultId, 0, DEFAULT_LIMIT);
+            const result = await entryApi.list(
+                vaultId,
+                0,
+                DEFAULT_LIMIT,
+                get().sortByVault[vaultId],
+                get().filtersByVault[vaultId]
+            );
             set({
                 entries: result.entries,
                 total: result.total,
@@ -96,7 +111,13 @@ export const useEntryStore = create<EntryState>((set, get) => ({
 
         try {
             const nextPage = page + 1;
-            const result = await entryApi.list(vaultId, nextPage, DEFAULT_LIMIT);
+            const result = await entryApi.list(
+                vaultId,
+                nextPage,
+                DEFAULT_LIMIT,
+                get().sortByVault[vaultId],
+                get().filtersByVault[vaultId]
+            );
             set((state) => ({
                 entries: [...state.entries, ...result.entries],
                 total: result.total,
@@ -110,6 +131,38 @@ export const useEntryStore = create<EntryState>((set, get) => ({
         }
     },
 
+    setSort: async (vaultId, sort) => {
+        set((state) => {
+            const sortByVault = { ...state.sortByVault };
+            if (sort) {
+                sortByVault[vaultId] = sort;
+            } else {
+                delete sortByVault[vaultId];
+            }
+            return { sortByVault };
+        });
+        await get().fetchEntries(vaultId);
+    },
+
+    setFilters: async (vaultId, filters) => {
+        set((state) => {
+            const filtersByVault = { ...state.filtersByVault };
+            // Choice filters without choices match everything but keep their mode
+            const count =
+                (filters.choices?.length ?? 0) +
+                (filters.ranges?.length ?? 0) +
+                (filters.withinBounds?.length ?? 0) +
+                (filters.withinDistance?.length ?? 0);
+            if (count > 0) {
+                filtersByVault[vaultId] = filters;
+            } else {
+                delete filtersByVault[vaultId];
+            }
+            return { filtersByVault };
+        });
+        await get().fetchEntries(vaultId);
+    },
+
     createEntry: async (params) => {
         set({ error: null });
         try {
@@ -1,5 +1,7 @@
 // Entry types matching Rust models
 
+import type { ComputedValues, FieldDefinition } from '@/modules/field';
+
 export interface Entry {
     id: number;
     vault_id: number;
@@ -7,6 +9,11 @@ export interface Entry {
     description: string | null;
     metadata: string | null;
     cover_image_path: string | null;
+    cover_original_path: string | null;
+    cover_blurhash: string | null;
+    cover_palette: string[];
+    // Lookup, rollup and formula values keyed by field ID; omitted when there are none
+    computed?: ComputedValues;
     created_at: string;
     updated_at: string;
 }
@@ -24,6 +31,75 @@ export interface UpdateEntryParams {
     metadata?: string | null;
 }
 
+// Sort order for entry listings; without a field entries sort by title
+export interface EntrySort {
+    fieldId?: number;
+    descending?: boolean;
+}
+
+// How the choices of a filter must match: at least one, or every one
+export type ChoiceMatch = 'any' | 'all';
+
+// Narrows entries by the choices of a select or multiselect field, by ID
+export interface ChoiceFilter {
+    fieldId: number;
+    choices: string[];
+    mode?: ChoiceMatch;
+}
+
+// Keeps entries whose value lies within inclusive bounds. Numeric fields
+// take numbers; date fields take date strings, where a shorter upper bound
+// like '1999' includes the whole year.
+export interface RangeFilter {
+    fieldId: number;
+    min?: number | string;
+    max?: number | string;
+}
+
+// Keeps entries whose location lies inside a box, in degrees. A west edge
+// greater than the east edge describes a box crossing the antimeridian.
+export interface BoundsFilter {
+    fieldId: number;
+    south: number;
+    west: number;
+    north: number;
+    east: number;
+}
+
+// Keeps entries whose location lies within radiusKm of a point
+export interface DistanceFilter {
+    fieldId: number;
+    lat: number;
+    lng: number;
+    radiusKm: number;
+}
+
+// Filters applied to an entry listing; every filter must match
+export interface EntryFilters {
+    choices?: ChoiceFilter[];
+    ranges?: RangeFilter[];
+    withinBounds?: BoundsFilter[];
+    withinDistance?: DistanceFilter[];
+}
+
+// Number of entries having a choice of a select or multiselect field
+export interface ChoiceCount {
+    // Choice ID, as filters take it
+    choice: string;
+    // Stored value for values that are no longer a choice
+    label: string;
+    color: string | null;
+    count: number;
+}
+
+// Aggregates of a number or rating field over the matching entries
+export interface NumericSummary {
+    count: number;
+    average: number | null;
+    min: number | null;
+    max: number | null;
+}
+
 export interface PaginatedEntries {
     entries: Entry[];
     total: number;
@@ -41,10 +117,30 @@ export interface SearchResult {
     has_more: boolean;
 }
 
+// Entry in a vault export, with field values keyed by field name
+export interface ExportedEntry {
+    id: number;
+    title: string;
+    description: string | null;
+    values: Record<string, unknown>;
+    created_at: string;
+    updated_at: string;
+}
+
+// All entries of a vault, including lookup and rollup values
+export interface VaultExport {
+    vault_id: number;
+    vault_name: string;
+    fields: FieldDefinition[];
+    entries: ExportedEntry[];
+}
+
 // Metadata validation result from backend
 export interface MetadataValidationResult {
     is_valid: boolean;
     errors: string[];
+    // Errors by the ID of the field they concern
+    field_errors: Record<string, string[]>;
     warnings: string[];
 }
 
@@ -77,3 +173,80 @@ export function buildMetadataJson(values: ParsedMetadata): string {
     }
     return JSON.stringify(filtered);
 }
+
+// Entry whose cover image file is missing from storage
+export interface MissingCover {
+    entry_id: number;
+    vault_id: number;
+    cover_image_path: string;
+}
+
+// Report from an image garbage collection run
+export interface ImageGcReport {
+    dry_run: boolean;
+    scanned_files: number;
+    unreferenced_files: string[];
+    reclaimed_bytes: number;
+    missing_covers: MissingCover[];
+    repaired_ref_counts: number;
+}
+
+// Aspect-ratio presets for cover cropping
+export type AspectPreset = 'poster' | 'album' | 'widescreen';
+
+// Crop region applied after rotation and flipping
+export type CropRegion =
+    | { type: 'rect'; x: number; y: number; width: number; height: number }
+    | { type: 'aspect'; preset: AspectPreset };
+
+// Edits applied to a cover image on import or later editing
+export interface ImageTransform {
+    autoOrient?: boolean;
+    rotate?: 0 | 90 | 180 | 270;
+    flipHorizontal?: boolean;
+    flipVertical?: boolean;
+    crop?: CropRegion;
+    maxDimension?: number;
+}
+
+// Entry in a cluster of near-identical cover images
+export interface DuplicateCoverEntry {
+    entry_id: number;
+    vault_id: number;
+    title: string;
+    cover_image_path: string | null;
+    cover_phash: string;
+}
+
+// Group of entries whose covers are within the distance threshold
+export interface DuplicateCoverCluster {
+    entries: DuplicateCoverEntry[];
+    max_distance: number;
+}
+
+// Image metadata tags that can be mapped to fields (EXIF tag names)
+export type ImageTag =
+    | 'Make'
+    | 'Model'
+    | 'LensModel'
+    | 'DateTimeOriginal'
+    | 'ImageWidth'
+    | 'ImageHeight'
+    | 'FNumber'
+    | 'ExposureTime'
+    | 'Iso'
+    | 'FocalLength'
+    | 'GpsLatitude'
+    | 'GpsLongitude'
+    | 'GpsAltitude';
+
+// Metadata read from an image file
+export interface ImageMetadata {
+    tags: Partial<Record<ImageTag, string | number>>;
+}
+
+// Maps an image tag to a vault field definition
+export interface ImageTagMapping {
+    tag: ImageTag;
+    field_id: number;
+}
@@ -5,7 +5,28 @@ import type {
     FieldDefinition,
     CreateFieldParams,
     UpdateFieldParams,
+    ConvertFieldParams,
+    ConversionPreview,
+    FieldRule,
+    RenderedMarkdown,
+    Attachment,
 } from './types';
+import { toAttachments } from './types';
+
+async function getAppDataDir(): Promise<string> {
+    const { appDataDir } = await import('@tauri-apps/api/path');
+    return appDataDir();
+}
+
+// Reads a file field's attachments from an entry returned by the backend
+function attachmentsOf(entry: { metadata: string | null }, fieldId: number): Attachment[] {
+    try {
+        const metadata = entry.metadata ? JSON.parse(entry.metadata) : {};
+        return toAttachments(metadata[fieldId.toString()] ?? null);
+    } catch {
+        return [];
+    }
+}
 
 export const fieldApi = {
     /**
@@ -18,6 +39,7 @@ export const fieldApi = {
             fieldType: params.field_type,
             options: params.options ?? null,
             required: params.required ?? false,
+            backfill: params.backfill ?? false,
         });
     },
 
@@ -44,6 +66,32 @@ export const fieldApi = {
             name: params.name ?? null,
             options: params.options ?? null,
             required: params.required ?? null,
+            backfill: params.backfill ?? false,
+            mergeChoices: params.merge_choices ?? null,
+        });
+    },
+
+    /**
+     * Counts how many of a field's values convert to another type,
+     * without changing anything.
+     */
+    async previewConversion(id: number, params: ConvertFieldParams): Promise<ConversionPreview> {
+        return invoke<ConversionPreview>('preview_field_conversion', {
+            id,
+            fieldType: params.field_type,
+            options: params.options ?? null,
+        });
+    },
+
+    /**
+     * Changes a field's type, converting the value of every entry.
+     */
+    async convert(id: number, params: ConvertFieldParams): Promise<FieldDefinition> {
+        return invoke<FieldDefinition>('convert_field_definition', {
+            id,
+            fieldType: params.field_type,
+            options: params.options ?? null,
+            discardFailures: params.discard_failures ?? false,
         });
     },
 
@@ -51,7 +99,7 @@ export const fieldApi = {
      * Deletes a field definition.
      */
     async delete(id: number): Promise<void> {
-        return invoke<void>('delete_field_definition', { id });
+        return invoke<void>('delete_field_definition', { id, appDataDir: await getAppDataDir() });
     },
 
     /**
@@ -60,4 +108,67 @@ export const fieldApi = {
     async reorder(vaultId: number, ids: number[]): Promise<void> {
         return invoke<void>('reorder_field_definitions', { vaultId, ids });
     },
+
+    /**
+     * Lists a vault's validation rules between fields.
+     */
+    async getRules(vaultId: number): Promise<FieldRule[]> {
+        return invoke<FieldRule[]>('get_field_rules', { vaultId });
+    },
+
+    /**
+     * Replaces a vault's validation rules between fields. Returns them as
+     * stored, with choice conditions given by ID.
+     */
+    async setRules(vaultId: number, rules: FieldRule[]): Promise<FieldRule[]> {
+        return invoke<FieldRule[]>('set_field_rules', { vaultId, rules });
+    },
+
+    /**
+     * Renders a markdown value to sanitized HTML, resolving
+     * [[Title]] wiki-links to entries of the vault.
+     */
+    async renderMarkdown(vaultId: number, source: string): Promise<RenderedMarkdown> {
+        return invoke<RenderedMarkdown>('render_markdown', { vaultId, source });
+    },
+
+    /**
+     * Copies a local file into managed storage and attaches it to an
+     * entry's file field. Returns the field's attachments.
+     */
+    async addAttachment(entryId: number, fieldId: number, sourcePath: string): Promise<Attachment[]> {
+        const entry = await invoke<{ metadata: string | null }>('add_entry_attachment', {
+            entryId,
+            fieldId,
+            sourcePath,
+            appDataDir: await getAppDataDir(),
+        });
+        return attachmentsOf(entry, fieldId);
+    },
+
+    /**
+     * Gets the absolute path of an attachment for opening it.
+     */
+    async getAttachmentPath(entryId: number, fieldId: number, sha256: string): Promise<string> {
+        return invoke<string>('get_attachment_path', {
+            entryId,
+            fieldId,
+            sha256,
+            appDataDir: await getAppDataDir(),
+        });
+    },
+
+    /**
+     * Removes an attachment from an entry's file field.
+     * Returns the field's remaining attachments.
+     */
+    async removeAttachment(entryId: number, fieldId: number, sha256: string): Promise<Attachment[]> {
+        const entry = await invoke<{ metadata: string | null }>('remove_entry_attachment', {
+            entryId,
+            fieldId,
+            sha256,
+            appDataDir: await getAppDataDir(),
+        });
+        return attachmentsOf(entry, fieldId);
+    },
 };
@@ -0,0 +1,82 @@
+// Attachment List - Files of a file field, opened with the system viewer
+
+import { useState } from 'react';
+import { File, Loader2, X } from 'lucide-react';
+import { openPath } from '@tauri-apps/plugin-opener';
+import { Button } from '@/components/ui/button';
+import { fieldApi } from '../api';
+import type { Attachment } from '../types';
+import { formatFileSize } from '../types';
+
+interface AttachmentListProps {
+    entryId?: number;
+    fieldId: number;
+    attachments: Attachment[];
+    /** Shows a remove button per attachment when set */
+    onRemove?: (attachment: Attachment) => void;
+    /** Attachment currently being removed */
+    removing?: string | null;
+}
+
+export function AttachmentList({
+    entryId,
+    fieldId,
+    attachments,
+    onRemove,
+    removing,
+}: AttachmentListProps) {
+    const [error, setError] = useState<string | null>(null);
+
+    // Attachments are looked up through their entry, so unsaved entries cannot open them
+    const handleOpen = async (attachment: Attachment) => {
+        if (entryId === undefined) return;
+        setError(null);
+        try {
+            const path = await fieldApi.getAttachmentPath(entryId, fieldId, attachment.sha256);
+            await openPath(path);
+        } catch (err) {
+            console.error('Failed to open attachment:', err);
+            setError(String(err));
+        }
+    };
+
+    return (
+        <div className="space-y-1">
+            {attachments.map((attachment) => (
+                <div key={attachment.sha256} className="flex items-center gap-2">
+                    <button
+                        type="button"
+                        onClick={() => handleOpen(attachment)}
+                        disabled={entryId === undefined}
+                        className="flex min-w-0 flex-1 items-center gap-1.5 text-left text-sm text-primary hover:underline disabled:text-foreground disabled:no-underline"
+                        title={attachment.filename}
+                    >
+                        <File className="h-3.5 w-3.5 shrink-0 text-muted-foreground" />
+                        <span className="truncate">{attachment.filename}</span>
+                        <span className="shrink-0 text-xs text-muted-foreground">
+                            {formatFileSize(attachment.size)}
+                        </span>
+                    </button>
+                    {onRemove && (
+                        <Button
+                            type="button"
+                            variant="ghost"
+                            size="icon"
+                            className="h-7 w-7 shrink-0"
+                            onClick={() => onRemove(attachment)}
+                            disabled={removing !== null && removing !== undefined}
+                            title="Remove attachment"
+                        >
+                            {removing === attachment.sha256 ? (
+                                <Loader2 className="h-4 w-4 animate-spin" />
+                            ) : (
+                                <X className="h-4 w-4" />
+                            )}
+                        </Button>
+                    )}
+                </div>
+            ))}
+            {error && <p className="text-xs text-destructive">{error}</p>}
+        </div>
+    );
+}
@@ -0,0 +1,44 @@
+// Choice Badge - Label of a select or multiselect choice with its color
+
+import type { ReactNode } from 'react';
+import { Badge } from '@/components/ui/badge';
+import { cn } from '@/lib/utils';
+import type { Choice } from '../types';
+
+interface ChoiceBadgeProps {
+    // Undefined for stored IDs that are no longer a choice
+    choice: Choice | undefined;
+    // Shown when the choice is gone
+    fallback?: string;
+    variant?: 'default' | 'secondary' | 'outline';
+    className?: string;
+    onClick?: () => void;
+    children?: ReactNode;
+}
+
+export function ChoiceBadge({
+    choice,
+    fallback,
+    variant = 'outline',
+    className,
+    onClick,
+    children,
+}: ChoiceBadgeProps) {
+    return (
+        <Badge
+            variant={variant}
+            className={cn('gap-1', className)}
+            onClick={onClick}
+            title={choice ? undefined : 'No longer an option'}
+        >
+            {children}
+            {choice?.color && (
+                <span
+                    className="h-2 w-2 rounded-full flex-shrink-0"
+                    style={{ backgroundColor: choice.color }}
+                />
+            )}
+            {choice?.label ?? fallback}
+        </Badge>
+    );
+}
@@ -0,0 +1,181 @@
+// Choice List Editor - Labels, colors and order of select and multiselect choices
+
+import { ChevronDown, ChevronUp, Plus, X } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import type { Choice } from '../types';
+
+// Merge target meaning the entries of a removed choice lose it
+const CLEAR = '__clear__';
+
+interface ChoiceListEditorProps {
+    label: string;
+    choices: Choice[];
+    onChange: (choices: Choice[]) => void;
+    // Saved choices of an existing field; choices removed from them can
+    // have their entries moved to a remaining choice
+    saved?: Choice[];
+    // Number of entries having each saved choice, by ID
+    counts?: Record<string, number>;
+    merges?: Record<string, string>;
+    onMergesChange?: (merges: Record<string, string>) => void;
+}
+
+export function ChoiceListEditor({
+    label,
+    choices,
+    onChange,
+    saved = [],
+    counts = {},
+    merges = {},
+    onMergesChange,
+}: ChoiceListEditorProps) {
+    const update = (index: number, changes: Partial<Choice>) =>
+        onChange(choices.map((c, i) => (i === index ? { ...c, ...changes } : c)));
+
+    const move = (index: number, offset: number) => {
+        const next = [...choices];
+        [next[index], next[index + offset]] = [next[index + offset], next[index]];
+        onChange(next);
+    };
+
+    const remove = (index: number) => {
+        const removedId = choices[index].id;
+        onChange(choices.filter((_, i) => i !== index));
+        // Entries cannot move into a choice that is going away
+        if (onMergesChange && Object.values(merges).includes(removedId)) {
+            onMergesChange(
+                Object.fromEntries(Object.entries(merges).filter(([, to]) => to !== removedId))
+            );
+        }
+    };
+
+    const removed = saved.filter((s) => !choices.some((c) => c.id === s.id));
+    const targets = choices.filter((c) => c.id && c.label.trim());
+
+    const setMerge = (from: string, to: string) => {
+        const next = { ...merges };
+        if (to === CLEAR) {
+            delete next[from];
+        } else {
+            next[from] = to;
+        }
+        onMergesChange?.(next);
+    };
+
+    return (
+        <div className="space-y-2">
+            <Label>{label}</Label>
+            <div className="space-y-2">
+                {choices.map((choice, index) => (
+                    <div key={choice.id || `new-${index}`} className="flex gap-1">
+                        <input
+                            type="color"
+                            value={choice.color ?? '#9ca3af'}
+                            onChange={(e) => update(index, { color: e.target.value })}
+                            className="h-9 w-9 shrink-0 cursor-pointer rounded-md border bg-transparent p-1"
+                            title="Color"
+                        />
+                        <Input
+                            value={choice.label}
+                            onChange={(e) => update(index, { label: e.target.value })}
+                            placeholder={`Option ${index + 1}`}
+                        />
+                        {choice.color && (
+                            <Button
+                                type="button"
+                                variant="ghost"
+                                size="sm"
+                                onClick={() => update(index, { color: null })}
+                                title="Remove color"
+                            >
+                                No color
+                            </Button>
+                        )}
+                        <Button
+                            type="button"
+                            variant="ghost"
+                            size="icon"
+                            onClick={() => move(index, -1)}
+                            disabled={index === 0}
+                            title="Move up"
+                        >
+                            <ChevronUp className="h-4 w-4" />
+                        </Button>
+                        <Button
+                            type="button"
+                            variant="ghost"
+                            size="icon"
+                            onClick={() => move(index, 1)}
+                            disabled={index === choices.length - 1}
+                            title="Move down"
+                        >
+                            <ChevronDown className="h-4 w-4" />
+                        </Button>
+                        <Button
+                            type="button"
+                            variant="ghost"
+                            size="icon"
+                            onClick={() => remove(index)}
+                            disabled={choices.length <= 1}
+                        >
+                            <X className="h-4 w-4" />
+                        </Button>
+                    </div>
+                ))}
+                <Button
+                    type="button"
+                    variant="outline"
+                    size="sm"
+                    onClick={() => onChange([...choices, { id: '', label: '' }])}
+                    className="w-full"
+                >
+                    <Plus className="h-4 w-4 mr-2" />
+                    Add Option
+                </Button>
+            </div>
+
+            {removed.length > 0 && onMergesChange && (
+                <div className="space-y-2 rounded-md border p-3">
+                    <p className="text-xs text-muted-foreground">
+                        Removed options are cleared from their entries unless moved to another option.
+                    </p>
+                    {removed.map((choice) => (
+                        <div key={choice.id} className="flex items-center gap-2 text-sm">
+                            <span className="flex-1 truncate">
+                                {choice.label}
+                                <span className="text-muted-foreground">
+                                    {' '}({counts[choice.id] ?? 0} entries)
+                                </span>
+                            </span>
+                            <Select
+                                value={merges[choice.id] ?? CLEAR}
+                                onValueChange={(to) => setMerge(choice.id, to)}
+                            >
+                                <SelectTrigger className="w-44">
+                                    <SelectValue />
+                                </SelectTrigger>
+                                <SelectContent>
+                                    <SelectItem value={CLEAR}>Clear from entries</SelectItem>
+                                    {targets.map((target) => (
+                                        <SelectItem key={target.id} value={target.id}>
+                                            Move to {target.label}
+                                        </SelectItem>
+                                    ))}
+                                </SelectContent>
+                            </Select>
+                        </div>
+                    ))}
+                </div>
+            )}
+        </div>
+    );
+}
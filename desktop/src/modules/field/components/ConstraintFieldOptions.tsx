@@ -0,0 +1,106 @@
+// Constraint Field Options - Uniqueness, pattern and minimum length of field values
+
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import type { FieldOptions, FieldType } from '../types';
+import { canBeUnique, takesPattern } from '../types';
+
+interface ConstraintFieldOptionsProps {
+    fieldType: FieldType;
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+export function ConstraintFieldOptions({
+    fieldType,
+    options,
+    onChange,
+}: ConstraintFieldOptionsProps) {
+    // Identifier fields set their pattern and uniqueness with their scheme
+    if (fieldType === 'identifier') return null;
+
+    const hasMinLength = fieldType === 'text' || fieldType === 'markdown';
+    const hasPattern = takesPattern(fieldType);
+    const hasUnique = canBeUnique(fieldType);
+    if (!hasMinLength && !hasPattern && !hasUnique) return null;
+
+    return (
+        <div className="space-y-3">
+            {hasMinLength && (
+                <div className="space-y-2">
+                    <Label htmlFor="minLength">Minimum Length (optional)</Label>
+                    <Input
+                        id="minLength"
+                        type="number"
+                        value={options.minLength ?? ''}
+                        onChange={(e) =>
+                            onChange({
+                                ...options,
+                                minLength: e.target.value ? parseInt(e.target.value, 10) : undefined,
+                            })
+                        }
+                        placeholder="No minimum"
+                        min={0}
+                    />
+                </div>
+            )}
+
+            {hasPattern && (
+                <>
+                    <div className="space-y-2">
+                        <Label htmlFor="pattern">Pattern (optional)</Label>
+                        <Input
+                            id="pattern"
+                            value={options.pattern ?? ''}
+                            onChange={(e) =>
+                                onChange({
+                                    ...options,
+                                    pattern: e.target.value || undefined,
+                                    patternMessage: e.target.value ? options.patternMessage : undefined,
+                                })
+                            }
+                            placeholder="e.g. [A-Z]{2}\d{4}"
+                            className="font-mono text-sm"
+                        />
+                        <p className="text-xs text-muted-foreground">
+                            Regular expression the whole value must match
+                        </p>
+                    </div>
+                    {options.pattern && (
+                        <div className="space-y-2">
+                            <Label htmlFor="patternMessage">Message when it does not match (optional)</Label>
+                            <Input
+                                id="patternMessage"
+                                value={options.patternMessage ?? ''}
+                                onChange={(e) =>
+                                    onChange({ ...options, patternMessage: e.target.value || undefined })
+                                }
+                                placeholder="e.g. Use two capitals and four digits"
+                                maxLength={200}
+                            />
+                        </div>
+                    )}
+                </>
+            )}
+
+            {hasUnique && (
+                <div className="flex items-center justify-between">
+                    <div className="space-y-0.5">
+                        <Label htmlFor="unique">Unique</Label>
+                        <p className="text-xs text-muted-foreground">
+                            No two entries in this vault may share a value
+                        </p>
+                    </div>
+                    <Switch
+                        id="unique"
+                        checked={options.unique ?? false}
+                        onCheckedChange={(checked) =>
+                            onChange({ ...options, unique: checked || undefined })
+                        }
+                    />
+                </div>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,251 @@
+// Convert Field Dialog - Change a field's type, converting existing values
+
+import { useState, useEffect } from 'react';
+import {
+    Dialog,
+    DialogContent,
+    DialogHeader,
+    DialogTitle,
+    DialogDescription,
+} from '@/components/ui/dialog';
+import { Button } from '@/components/ui/button';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import { Loader2 } from 'lucide-react';
+import { useFieldStore } from '../store';
+import { fieldApi } from '../api';
+import type { ConversionPreview, FieldDefinition, FieldType } from '../types';
+
+interface ConvertFieldDialogProps {
+    field: FieldDefinition | null;
+    isOpen: boolean;
+    onClose: () => void;
+}
+
+// Types a field can be converted to; files, relations and computed
+// fields hold values that have no counterpart in other types
+const TARGET_TYPES: { value: FieldType; label: string }[] = [
+    { value: 'text', label: 'Text' },
+    { value: 'markdown', label: 'Rich text' },
+    { value: 'number', label: 'Number' },
+    { value: 'date', label: 'Date' },
+    { value: 'partialdate', label: 'Partial date' },
+    { value: 'datetime', label: 'Date & time' },
+    { value: 'duration', label: 'Duration' },
+    { value: 'url', label: 'URL' },
+    { value: 'identifier', label: 'Identifier' },
+    { value: 'email', label: 'Email' },
+    { value: 'phone', label: 'Phone' },
+    { value: 'color', label: 'Color' },
+    { value: 'currency', label: 'Currency' },
+    { value: 'json', label: 'JSON' },
+    { value: 'boolean', label: 'Yes/No' },
+    { value: 'select', label: 'Dropdown' },
+    { value: 'multiselect', label: 'Multi-select' },
+    { value: 'rating', label: 'Rating' },
+    { value: 'location', label: 'Location' },
+];
+
+function formatValue(value: unknown): string {
+    return typeof value === 'string' ? value : JSON.stringify(value);
+}
+
+export function ConvertFieldDialog({
+    field,
+    isOpen,
+    onClose,
+}: ConvertFieldDialogProps) {
+    const { convertField } = useFieldStore();
+    const [fieldType, setFieldType] = useState<FieldType | null>(null);
+    const [preview, setPreview] = useState<ConversionPreview | null>(null);
+    const [discardFailures, setDiscardFailures] = useState(false);
+    const [isLoading, setIsLoading] = useState(false);
+    const [isSaving, setIsSaving] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    // Reset the form when another field is converted
+    useEffect(() => {
+        setFieldType(null);
+        setPreview(null);
+        setDiscardFailures(false);
+        setError(null);
+    }, [field]);
+
+    // Preview the conversion whenever the target type changes
+    useEffect(() => {
+        if (!field || !fieldType) return;
+
+        let cancelled = false;
+        setIsLoading(true);
+        setPreview(null);
+        setError(null);
+        fieldApi
+            .previewConversion(field.id, { field_type: fieldType })
+            .then((result) => {
+                if (!cancelled) setPreview(result);
+            })
+            .catch((err) => {
+                if (!cancelled) setError(String(err));
+            })
+            .finally(() => {
+                if (!cancelled) setIsLoading(false);
+            });
+
+        return () => {
+            cancelled = true;
+        };
+    }, [field, fieldType]);
+
+    if (!field) return null;
+
+    const blocked = !preview || (preview.failed > 0 && !discardFailures);
+
+    const handleConvert = async () => {
+        if (!fieldType || blocked) return;
+
+        setIsSaving(true);
+        setError(null);
+
+        try {
+            await convertField(field.id, {
+                field_type: fieldType,
+                discard_failures: discardFailures,
+            });
+            onClose();
+        } catch (err) {
+            setError(String(err));
+        } finally {
+            setIsSaving(false);
+        }
+    };
+
+    return (
+        <Dialog open={isOpen} onOpenChange={(open) => !open && onClose()}>
+            <DialogContent className="max-w-md">
+                <DialogHeader>
+                    <DialogTitle>Change Field Type</DialogTitle>
+                    <DialogDescription>
+                        Convert <strong>{field.name}</strong> to another type. The value of every entry is converted at once.
+                    </DialogDescription>
+                </DialogHeader>
+
+                <div className="space-y-4 py-2">
+                    {error && (
+                        <div className="p-3 rounded-md bg-destructive/10 text-destructive text-sm">
+                            {error}
+                        </div>
+                    )}
+
+                    <div className="space-y-2">
+                        <Label>New Type</Label>
+                        <Select
+                            value={fieldType ?? undefined}
+                            onValueChange={(v) => setFieldType(v as FieldType)}
+                        >
+                            <SelectTrigger>
+                                <SelectValue placeholder="Select a type..." />
+                            </SelectTrigger>
+                            <SelectContent>
+                                {TARGET_TYPES.filter((type) => type.value !== field.field_type).map((type) => (
+                                    <SelectItem key={type.value} value={type.value}>
+                                        {type.label}
+                                    </SelectItem>
+                                ))}
+                            </SelectContent>
+                        </Select>
+                    </div>
+
+                    {isLoading && (
+                        <div className="flex items-center gap-2 text-sm text-muted-foreground">
+                            <Loader2 className="h-4 w-4 animate-spin" />
+                            Checking existing values...
+                        </div>
+                    )}
+
+                    {preview && (
+                        <div className="space-y-3">
+                            <div className="grid grid-cols-3 gap-2 text-center text-sm">
+                                <div className="rounded-md bg-muted p-2">
+                                    <div className="text-lg font-medium">{preview.converted}</div>
+                                    <div className="text-xs text-muted-foreground">Converted</div>
+                                </div>
+                                <div className="rounded-md bg-muted p-2">
+                                    <div className="text-lg font-medium">{preview.emptied}</div>
+                                    <div className="text-xs text-muted-foreground">Emptied</div>
+                                </div>
+                                <div className="rounded-md bg-muted p-2">
+                                    <div className="text-lg font-medium">{preview.failed}</div>
+                                    <div className="text-xs text-muted-foreground">Failed</div>
+                                </div>
+                            </div>
+
+                            {preview.choices && (
+                                <p className="text-xs text-muted-foreground">
+                                    Options: {preview.choices.length > 0 ? preview.choices.map((c) => c.label).join(', ') : 'none'}
+                                </p>
+                            )}
+
+                            {preview.failures.length > 0 && (
+                                <div className="max-h-40 overflow-y-auto rounded-md border border-border text-xs">
+                                    {preview.failures.map((failure) => (
+                                        <div key={failure.entry_id} className="border-b border-border p-2 last:border-b-0">
+                                            <div className="font-medium truncate">{failure.title}</div>
+                                            <div className="text-muted-foreground truncate">
+                                                {formatValue(failure.value)} — {failure.error}
+                                            </div>
+                                        </div>
+                                    ))}
+                                </div>
+                            )}
+
+                            {preview.failed > 0 && (
+                                <div className="flex items-center justify-between">
+                                    <div className="space-y-0.5">
+                                        <Label htmlFor="discardFailures">Clear failed values</Label>
+                                        <p className="text-xs text-muted-foreground">
+                                            Values that cannot be converted are removed
+                                        </p>
+                                    </div>
+                                    <Switch
+                                        id="discardFailures"
+                                        checked={discardFailures}
+                                        onCheckedChange={setDiscardFailures}
+                                    />
+                                </div>
+                            )}
+                        </div>
+                    )}
+
+                    {/* Actions */}
+                    <div className="flex justify-end gap-2 pt-4">
+                        <Button
+                            type="button"
+                            variant="outline"
+                            onClick={onClose}
+                            disabled={isSaving}
+                        >
+                            Cancel
+                        </Button>
+                        <Button onClick={handleConvert} disabled={isSaving || isLoading || blocked}>
+                            {isSaving ? (
+                                <>
+                                    <Loader2 className="h-4 w-4 animate-spin mr-2" />
+                                    Converting...
+                                </>
+                            ) : (
+                                'Convert'
+                            )}
+                        </Button>
+                    </div>
+                </div>
+            </DialogContent>
+        </Dialog>
+    );
+}
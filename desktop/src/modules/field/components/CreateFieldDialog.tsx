@@ -19,10 +19,23 @@ import {
     SelectTrigger,
     SelectValue,
 } from '@/components/ui/select';
-import { Loader2, Plus, X } from 'lucide-react';
+import { Loader2 } from 'lucide-react';
 import { useFieldStore } from '../store';
 import { vaultApi, type Vault } from '@/modules/vault';
-import type { FieldType, FieldOptions } from '../types';
+import type { Choice, FieldType, FieldOptions } from '../types';
+import { canBeUnique, defaultValueKinds, takesPattern } from '../types';
+import type { RelationDeleteAction } from '@/modules/relation';
+import { ChoiceListEditor } from './ChoiceListEditor';
+import { ConstraintFieldOptions } from './ConstraintFieldOptions';
+import { CurrencyFieldOptions } from './CurrencyFieldOptions';
+import { DefaultValueOptions } from './DefaultValueOptions';
+import { DerivedFieldOptions } from './DerivedFieldOptions';
+import { FileFieldOptions } from './FileFieldOptions';
+import { IdentifierFieldOptions } from './IdentifierFieldOptions';
+import { FormulaFieldOptions } from './FormulaFieldOptions';
+import { JsonFieldOptions } from './JsonFieldOptions';
+import { PhoneFieldOptions } from './PhoneFieldOptions';
+import { RatingFieldOptions } from './RatingFieldOptions';
 
 interface CreateFieldDialogProps {
     vaultId: number;
@@ -32,12 +45,35 @@ interface CreateFieldDialogProps {
 
 const FIELD_TYPES: { value: FieldType; label: string; description?: string }[] = [
     { value: 'text', label: 'Text', description: 'Single or multi-line text' },
+    { value: 'markdown', label: 'Rich text', description: 'Formatted notes with [[links]]' },
     { value: 'number', label: 'Number', description: 'Integer or decimal' },
     { value: 'date', label: 'Date', description: 'Date picker' },
+    { value: 'partialdate', label: 'Partial date', description: 'Year, month or full date' },
+    { value: 'datetime', label: 'Date & time', description: 'Timestamp with timezone' },
+    { value: 'duration', label: 'Duration', description: 'Length of time, like 2h 28m' },
     { value: 'url', label: 'URL', description: 'Web link' },
+    { value: 'identifier', label: 'Identifier', description: 'ISBN, barcode, IMDb ID or custom code' },
+    { value: 'email', label: 'Email', description: 'Email address' },
+    { value: 'phone', label: 'Phone', description: 'Phone number in international format' },
+    { value: 'color', label: 'Color', description: 'Hex or RGB color' },
+    { value: 'currency', label: 'Currency', description: 'Amount of money in a currency' },
+    { value: 'json', label: 'JSON', description: 'Structured data, optionally checked by a schema' },
     { value: 'boolean', label: 'Yes/No', description: 'Toggle switch' },
     { value: 'select', label: 'Dropdown', description: 'Pick from options' },
+    { value: 'multiselect', label: 'Multi-select', description: 'Pick several options, like tags' },
+    { value: 'rating', label: 'Rating', description: 'Score on a scale of stars' },
+    { value: 'file', label: 'File', description: 'Attached documents and other files' },
+    { value: 'location', label: 'Location', description: 'Map point with an optional place name' },
     { value: 'relation', label: 'Relation', description: 'Link to another vault' },
+    { value: 'lookup', label: 'Lookup', description: 'Show a field of a linked entry' },
+    { value: 'rollup', label: 'Rollup', description: 'Aggregate over linked entries' },
+    { value: 'formula', label: 'Formula', description: 'Computed from other fields' },
+];
+
+const DELETE_ACTIONS: { value: RelationDeleteAction; label: string }[] = [
+    { value: 'set-null', label: 'Remove the link' },
+    { value: 'cascade', label: 'Delete this entry too' },
+    { value: 'restrict', label: 'Prevent the deletion' },
 ];
 
 export function CreateFieldDialog({
@@ -45,7 +81,7 @@ export function CreateFieldDialog({
     isOpen,
     onClose,
 }: CreateFieldDialogProps) {
-    const { createField } = useFieldStore();
+    const { fields, createField } = useFieldStore();
     const [isSaving, setIsSaving] = useState(false);
     const [error, setError] = useState<string | null>(null);
 
@@ -56,13 +92,48 @@ export function CreateFieldDialog({
     const [maxLength, setMaxLength] = useState<string>('');
     const [min, setMin] = useState<string>('');
     const [max, setMax] = useState<string>('');
-    const [choices, setChoices] = useState<string[]>(['']);
+    const [choices, setChoices] = useState<Choice[]>([{ id: '', label: '' }]);
     
     // Relation-specific state
     const [targetVaultId, setTargetVaultId] = useState<string>('');
+    const [multiple, setMultiple] = useState(false);
+    const [minItems, setMinItems] = useState<string>('');
+    const [maxItems, setMaxItems] = useState<string>('');
+    const [onDelete, setOnDelete] = useState<RelationDeleteAction>('set-null');
     const [availableVaults, setAvailableVaults] = useState<Vault[]>([]);
     const [isLoadingVaults, setIsLoadingVaults] = useState(false);
 
+    // Rating state
+    const [ratingOptions, setRatingOptions] = useState<FieldOptions>({});
+
+    // File state
+    const [fileOptions, setFileOptions] = useState<FieldOptions>({});
+
+    // Identifier state
+    const [identifierOptions, setIdentifierOptions] = useState<FieldOptions>({ scheme: 'isbn' });
+
+    // Phone, currency and JSON state
+    const [phoneOptions, setPhoneOptions] = useState<FieldOptions>({});
+    const [currencyOptions, setCurrencyOptions] = useState<FieldOptions>({});
+    const [jsonOptions, setJsonOptions] = useState<FieldOptions>({});
+
+    // Uniqueness, pattern and minimum length state
+    const [constraintOptions, setConstraintOptions] = useState<FieldOptions>({});
+
+    // Default value state
+    const [defaultOptions, setDefaultOptions] = useState<FieldOptions>({});
+    const [backfill, setBackfill] = useState(false);
+
+    // Lookup and rollup state
+    const [derivedOptions, setDerivedOptions] = useState<FieldOptions>({});
+    const isDerived = fieldType === 'lookup' || fieldType === 'rollup';
+
+    // Formula state
+    const [formulaOptions, setFormulaOptions] = useState<FieldOptions>({});
+    const isComputed = isDerived || fieldType === 'formula';
+
+    const hasChoices = fieldType === 'select' || fieldType === 'multiselect';
+
     // Load available vaults when relation type is selected
     useEffect(() => {
         if (fieldType === 'relation' && isOpen) {
@@ -87,8 +158,23 @@ export function CreateFieldDialog({
         setMaxLength('');
         setMin('');
         setMax('');
-        setChoices(['']);
+        setChoices([{ id: '', label: '' }]);
         setTargetVaultId('');
+        setMultiple(false);
+        setMinItems('');
+        setMaxItems('');
+        setOnDelete('set-null');
+        setRatingOptions({});
+        setFileOptions({});
+        setIdentifierOptions({ scheme: 'isbn' });
+        setPhoneOptions({});
+        setCurrencyOptions({});
+        setJsonOptions({});
+        setDerivedOptions({});
+        setFormulaOptions({});
+        setConstraintOptions({});
+        setDefaultOptions({});
+        setBackfill(false);
         setError(null);
     };
 
@@ -112,7 +198,7 @@ export function CreateFieldDialog({
             // Build options based on field type
             const options: FieldOptions = {};
 
-            if (fieldType === 'text' && maxLength) {
+            if ((fieldType === 'text' || fieldType === 'markdown') && maxLength) {
                 options.maxLength = parseInt(maxLength, 10);
             }
 
@@ -121,10 +207,39 @@ export function CreateFieldDialog({
                 if (max) options.max = parseFloat(max);
             }
 
-            if (fieldType === 'select') {
-                const validChoices = choices.filter((c) => c.trim());
+            if (fieldType === 'rating') {
+                Object.assign(options, ratingOptions);
+            }
+
+            if (fieldType === 'file') {
+                Object.assign(options, fileOptions);
+            }
+
+            if (fieldType === 'identifier') {
+                if (identifierOptions.scheme === 'custom' && !identifierOptions.pattern?.trim()) {
+                    setError('Please enter a pattern for custom identifiers');
+                    setIsSaving(false);
+                    return;
+                }
+                Object.assign(options, identifierOptions);
+            }
+
+            if (fieldType === 'phone') {
+                Object.assign(options, phoneOptions);
+            }
+
+            if (fieldType === 'currency') {
+                Object.assign(options, currencyOptions);
+            }
+
+            if (fieldType === 'json') {
+                Object.assign(options, jsonOptions);
+            }
+
+            if (hasChoices) {
+                const validChoices = choices.filter((c) => c.label.trim());
                 if (validChoices.length === 0) {
-                    setError('At least one choice is required for dropdown fields');
+                    setError(`At least one choice is required for ${fieldType === 'select' ? 'dropdown' : 'multi-select'} fields`);
                     setIsSaving(false);
                     return;
                 }
@@ -138,6 +253,60 @@ export function CreateFieldDialog({
                     return;
                 }
                 options.targetVaultId = parseInt(targetVaultId, 10);
+                if (multiple) {
+                    options.multiple = true;
+                    if (minItems) options.minItems = parseInt(minItems, 10);
+                    if (maxItems) options.maxItems = parseInt(maxItems, 10);
+                }
+                if (onDelete !== 'set-null') {
+                    options.onDelete = onDelete;
+                }
+            }
+
+            if (isDerived) {
+                if (derivedOptions.viaFieldId === undefined) {
+                    setError('Please select the relation this field follows');
+                    setIsSaving(false);
+                    return;
+                }
+                if (fieldType === 'rollup' && !derivedOptions.rollup) {
+                    setError('Please select an aggregate for the rollup field');
+                    setIsSaving(false);
+                    return;
+                }
+                Object.assign(options, derivedOptions);
+                if (fieldType === 'lookup') {
+                    delete options.rollup;
+                    delete options.backlinks;
+                }
+            }
+
+            if (fieldType === 'formula') {
+                if (!formulaOptions.formula?.trim()) {
+                    setError('Please enter a formula');
+                    setIsSaving(false);
+                    return;
+                }
+                options.formula = formulaOptions.formula.trim();
+            }
+
+            // Identifier fields set their own pattern and uniqueness
+            if (fieldType !== 'identifier') {
+                const { minLength, pattern, patternMessage, unique } = constraintOptions;
+                if ((fieldType === 'text' || fieldType === 'markdown') && minLength !== undefined) {
+                    options.minLength = minLength;
+                }
+                if (takesPattern(fieldType) && pattern) {
+                    options.pattern = pattern;
+                    if (patternMessage?.trim()) options.patternMessage = patternMessage.trim();
+                }
+                if (canBeUnique(fieldType) && unique) {
+                    options.unique = true;
+                }
+            }
+
+            if (defaultOptions.defaultValue && defaultValueKinds(fieldType).includes(defaultOptions.defaultValue.kind)) {
+                options.defaultValue = defaultOptions.defaultValue;
             }
 
             await createField({
@@ -145,7 +314,8 @@ export function CreateFieldDialog({
                 name: name.trim(),
                 field_type: fieldType,
                 options: Object.keys(options).length > 0 ? options : undefined,
-                required,
+                required: required && !isComputed,
+                backfill: backfill && options.defaultValue !== undefined,
             });
 
             handleClose();
@@ -156,22 +326,6 @@ export function CreateFieldDialog({
         }
     };
 
-    const addChoice = () => {
-        setChoices([...choices, '']);
-    };
-
-    const removeChoice = (index: number) => {
-        if (choices.length > 1) {
-            setChoices(choices.filter((_, i) => i !== index));
-        }
-    };
-
-    const updateChoice = (index: number, value: string) => {
-        const newChoices = [...choices];
-        newChoices[index] = value;
-        setChoices(newChoices);
-    };
-
     return (
         <Dialog open={isOpen} onOpenChange={(open) => !open && handleClose()}>
             <DialogContent className="max-w-md">
@@ -217,23 +371,25 @@ export function CreateFieldDialog({
                         </Select>
                     </div>
 
-                    {/* Required toggle */}
-                    <div className="flex items-center justify-between">
-                        <div className="space-y-0.5">
-                            <Label htmlFor="required">Required</Label>
-                            <p className="text-xs text-muted-foreground">
-                                Entry cannot be saved without this field
-                            </p>
+                    {/* Required toggle (not available for computed fields) */}
+                    {!isComputed && (
+                        <div className="flex items-center justify-between">
+                            <div className="space-y-0.5">
+                                <Label htmlFor="required">Required</Label>
+                                <p className="text-xs text-muted-foreground">
+                                    Entry cannot be saved without this field
+                                </p>
+                            </div>
+                            <Switch
+                                id="required"
+                                checked={required}
+                                onCheckedChange={setRequired}
+                            />
                         </div>
-                        <Switch
-                            id="required"
-                            checked={required}
-                            onCheckedChange={setRequired}
-                        />
-                    </div>
+                    )}
 
                     {/* Type-specific options */}
-                    {fieldType === 'text' && (
+                    {(fieldType === 'text' || fieldType === 'markdown') && (
                         <div className="space-y-2">
                             <Label htmlFor="maxLength">Maximum Length (optional)</Label>
                             <Input
@@ -272,40 +428,39 @@ export function CreateFieldDialog({
                         </div>
                     )}
 
-                    {fieldType === 'select' && (
-                        <div className="space-y-2">
-                            <Label>Dropdown Options *</Label>
-                            <div className="space-y-2">
-                                {choices.map((choice, index) => (
-                                    <div key={index} className="flex gap-2">
-                                        <Input
-                                            value={choice}
-                                            onChange={(e) => updateChoice(index, e.target.value)}
-                                            placeholder={`Option ${index + 1}`}
-                                        />
-                                        <Button
-                                            type="button"
-                                            variant="ghost"
-                                            size="icon"
-                                            onClick={() => removeChoice(index)}
-                                            disabled={choices.length <= 1}
-                                        >
-                                            <X className="h-4 w-4" />
-                                        </Button>
-                                    </div>
-                                ))}
-                                <Button
-                                    type="button"
-                                    variant="outline"
-                                    size="sm"
-                                    onClick={addChoice}
-                                    className="w-full"
-                                >
-                                    <Plus className="h-4 w-4 mr-2" />
-                                    Add Option
-                                </Button>
-                            </div>
-                        </div>
+                    {fieldType === 'rating' && (
+                        <RatingFieldOptions options={ratingOptions} onChange={setRatingOptions} />
+                    )}
+
+                    {fieldType === 'identifier' && (
+                        <IdentifierFieldOptions
+                            options={identifierOptions}
+                            onChange={setIdentifierOptions}
+                        />
+                    )}
+
+                    {fieldType === 'file' && (
+                        <FileFieldOptions options={fileOptions} onChange={setFileOptions} />
+                    )}
+
+                    {fieldType === 'phone' && (
+                        <PhoneFieldOptions options={phoneOptions} onChange={setPhoneOptions} />
+                    )}
+
+                    {fieldType === 'currency' && (
+                        <CurrencyFieldOptions options={currencyOptions} onChange={setCurrencyOptions} />
+                    )}
+
+                    {fieldType === 'json' && (
+                        <JsonFieldOptions options={jsonOptions} onChange={setJsonOptions} />
+                    )}
+
+                    {hasChoices && (
+                        <ChoiceListEditor
+                            label={fieldType === 'select' ? 'Dropdown Options *' : 'Tag Options *'}
+                            choices={choices}
+                            onChange={setChoices}
+                        />
                     )}
 
                     {fieldType === 'relation' && (
@@ -343,9 +498,95 @@ export function CreateFieldDialog({
                                     </SelectContent>
                                 </Select>
                             )}
+                            <div className="space-y-2 pt-2">
+                                <Label>When the linked entry is deleted</Label>
+                                <Select
+                                    value={onDelete}
+                                    onValueChange={(value) => setOnDelete(value as RelationDeleteAction)}
+                                >
+                                    <SelectTrigger>
+                                        <SelectValue />
+                                    </SelectTrigger>
+                                    <SelectContent>
+                                        {DELETE_ACTIONS.map((action) => (
+                                            <SelectItem key={action.value} value={action.value}>
+                                                {action.label}
+                                            </SelectItem>
+                                        ))}
+                                    </SelectContent>
+                                </Select>
+                            </div>
+                            <div className="flex items-center justify-between pt-2">
+                                <Label htmlFor="relation-multiple">Allow multiple entries</Label>
+                                <Switch
+                                    id="relation-multiple"
+                                    checked={multiple}
+                                    onCheckedChange={setMultiple}
+                                />
+                            </div>
+                            {multiple && (
+                                <div className="grid grid-cols-2 gap-4">
+                                    <div className="space-y-2">
+                                        <Label htmlFor="relation-min-items">Minimum</Label>
+                                        <Input
+                                            id="relation-min-items"
+                                            type="number"
+                                            min={0}
+                                            value={minItems}
+                                            onChange={(e) => setMinItems(e.target.value)}
+                                            placeholder="No minimum"
+                                        />
+                                    </div>
+                                    <div className="space-y-2">
+                                        <Label htmlFor="relation-max-items">Maximum</Label>
+                                        <Input
+                                            id="relation-max-items"
+                                            type="number"
+                                            min={1}
+                                            value={maxItems}
+                                            onChange={(e) => setMaxItems(e.target.value)}
+                                            placeholder="No limit"
+                                        />
+                                    </div>
+                                </div>
+                            )}
                         </div>
                     )}
 
+                    {isDerived && (
+                        <DerivedFieldOptions
+                            vaultId={vaultId}
+                            fieldType={fieldType}
+                            options={derivedOptions}
+                            onChange={setDerivedOptions}
+                        />
+                    )}
+
+                    {fieldType === 'formula' && (
+                        <FormulaFieldOptions
+                            vaultId={vaultId}
+                            options={formulaOptions}
+                            onChange={setFormulaOptions}
+                        />
+                    )}
+
+                    <ConstraintFieldOptions
+                        fieldType={fieldType}
+                        options={constraintOptions}
+                        onChange={setConstraintOptions}
+                    />
+
+                    <DefaultValueOptions
+                        key={fieldType}
+                        fieldType={fieldType}
+                        fields={fields.filter((f) => f.vault_id === vaultId)}
+                        choices={choices}
+                        options={defaultOptions}
+                        onChange={setDefaultOptions}
+                        backfill={backfill}
+                        onBackfillChange={setBackfill}
+                    />
+
                     {/* Actions */}
                     <div className="flex justify-end gap-2 pt-4">
                         <Button
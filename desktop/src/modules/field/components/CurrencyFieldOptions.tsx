@@ -0,0 +1,34 @@
+// Currency Field Options - Fixed currency for currency fields
+
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import type { FieldOptions } from '../types';
+
+interface CurrencyFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+export function CurrencyFieldOptions({ options, onChange }: CurrencyFieldOptionsProps) {
+    return (
+        <div className="space-y-2">
+            <Label htmlFor="currencyCode">Currency</Label>
+            <Input
+                id="currencyCode"
+                maxLength={3}
+                value={options.currency ?? ''}
+                onChange={(e) =>
+                    onChange({
+                        ...options,
+                        currency: e.target.value.replace(/[^a-z]/gi, '').toUpperCase() || undefined,
+                    })
+                }
+                placeholder="Any"
+                className="w-24 font-mono"
+            />
+            <p className="text-xs text-muted-foreground">
+                ISO 4217 code such as EUR or USD; leave empty to enter a code with each amount
+            </p>
+        </div>
+    );
+}
@@ -1,8 +1,14 @@
 // Custom Field Input - Edit components for custom field values
 
+import { useState } from 'react';
+import { Check, Loader2, Paperclip, X } from 'lucide-react';
+import { open } from '@tauri-apps/plugin-dialog';
+import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { Switch } from '@/components/ui/switch';
+import { Textarea } from '@/components/ui/textarea';
+import { cn } from '@/lib/utils';
 import {
     Select,
     SelectContent,
@@ -10,15 +16,57 @@ import {
     SelectTrigger,
     SelectValue,
 } from '@/components/ui/select';
-import type { FieldDefinition, RelationValue, EntryMetadataValue } from '../types';
-import { isRelationValue } from '../types';
+import { fieldApi } from '../api';
+import type {
+    Attachment,
+    FieldDefinition,
+    IdentifierScheme,
+    RelationValue,
+    EntryMetadataValue,
+} from '../types';
+import {
+    formatDuration,
+    isLocation,
+    isMoney,
+    isRelationValue,
+    toAttachments,
+    toChoiceValues,
+    toRelationValues,
+} from '../types';
+import { AttachmentList } from './AttachmentList';
+import { ChoiceBadge } from './ChoiceBadge';
+import { JsonInput } from './JsonInput';
+import { LocationInput } from './LocationInput';
+import { RatingScale } from './RatingScale';
+import { RelationFieldDisplay } from './RelationFieldDisplay';
 import { RelationFieldEditor } from './RelationFieldEditor';
 
+// Example values shown in empty identifier inputs
+const IDENTIFIER_PLACEHOLDERS: Record<IdentifierScheme, string> = {
+    isbn: 'e.g. 978-0-441-17271-9',
+    ean: 'e.g. 5901234123457',
+    imdb: 'e.g. tt0111161 or an IMDb link',
+    custom: '',
+};
+
+// Converts a stored UTC timestamp to the local value of a datetime-local input
+function toLocalDateTimeInput(value: EntryMetadataValue): string {
+    if (typeof value !== 'string') return '';
+    const date = new Date(value);
+    if (isNaN(date.getTime())) return '';
+    const local = new Date(date.getTime() - date.getTimezoneOffset() * 60000);
+    return local.toISOString().slice(0, 16);
+}
+
 interface CustomFieldInputProps {
     field: FieldDefinition;
     value: EntryMetadataValue;
     onChange: (value: EntryMetadataValue) => void;
     error?: string;
+    /** Overrides the field's own setting, for fields required by a rule */
+    required?: boolean;
+    /** Entry being edited; files can only be attached to saved entries */
+    entryId?: number;
 }
 
 export function CustomFieldInput({
@@ -26,7 +74,49 @@ export function CustomFieldInput({
     value,
     onChange,
     error,
+    required = field.required,
+    entryId,
 }: CustomFieldInputProps) {
+    // Attachments are written right away, then mirrored into the form value
+    const [isAttaching, setIsAttaching] = useState(false);
+    const [removing, setRemoving] = useState<string | null>(null);
+    const [attachmentError, setAttachmentError] = useState<string | null>(null);
+
+    const handleAttach = async () => {
+        if (entryId === undefined) return;
+        const extensions = field.options?.allowedExtensions;
+        const selected = await open({
+            multiple: false,
+            filters: extensions ? [{ name: field.name, extensions }] : undefined,
+        });
+        if (!selected) return;
+
+        setIsAttaching(true);
+        setAttachmentError(null);
+        try {
+            const attachments = await fieldApi.addAttachment(entryId, field.id, selected as string);
+            onChange(attachments.length > 0 ? attachments : null);
+        } catch (err) {
+            setAttachmentError(String(err));
+        } finally {
+            setIsAttaching(false);
+        }
+    };
+
+    const handleRemoveAttachment = async (attachment: Attachment) => {
+        if (entryId === undefined) return;
+        setRemoving(attachment.sha256);
+        setAttachmentError(null);
+        try {
+            const attachments = await fieldApi.removeAttachment(entryId, field.id, attachment.sha256);
+            onChange(attachments.length > 0 ? attachments : null);
+        } catch (err) {
+            setAttachmentError(String(err));
+        } finally {
+            setRemoving(null);
+        }
+    };
+
     const renderInput = () => {
         switch (field.field_type) {
             case 'text':
@@ -40,6 +130,18 @@ export function CustomFieldInput({
                     />
                 );
 
+            case 'markdown':
+                return (
+                    <Textarea
+                        value={value !== null ? String(value) : ''}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder="Markdown, with [[Entry Title]] to link entries"
+                        maxLength={field.options?.maxLength}
+                        rows={6}
+                        className={cn('font-mono text-sm', error && 'border-destructive')}
+                    />
+                );
+
             case 'number':
                 return (
                     <Input
@@ -70,6 +172,41 @@ export function CustomFieldInput({
                     />
                 );
 
+            case 'partialdate':
+                return (
+                    <Input
+                        value={value !== null ? String(value) : ''}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder="YYYY, YYYY-MM or YYYY-MM-DD"
+                        className={error ? 'border-destructive' : ''}
+                    />
+                );
+
+            case 'datetime':
+                return (
+                    <Input
+                        type="datetime-local"
+                        value={toLocalDateTimeInput(value)}
+                        onChange={(e) => {
+                            // The input holds local time; store the instant in UTC
+                            const date = new Date(e.target.value);
+                            onChange(isNaN(date.getTime()) ? null : date.toISOString());
+                        }}
+                        className={error ? 'border-destructive' : ''}
+                    />
+                );
+
+            case 'duration':
+                // Typed text is sent as is and normalized to seconds on save
+                return (
+                    <Input
+                        value={typeof value === 'number' ? formatDuration(value) : String(value ?? '')}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder="e.g. 2h 28m or 1:30:00"
+                        className={error ? 'border-destructive' : ''}
+                    />
+                );
+
             case 'url':
                 return (
                     <Input
@@ -81,6 +218,90 @@ export function CustomFieldInput({
                     />
                 );
 
+            case 'identifier':
+                return (
+                    <Input
+                        value={value !== null ? String(value) : ''}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder={IDENTIFIER_PLACEHOLDERS[field.options?.scheme ?? 'custom']}
+                        className={cn('font-mono', error && 'border-destructive')}
+                    />
+                );
+
+            case 'email':
+                return (
+                    <Input
+                        type="email"
+                        value={value !== null ? String(value) : ''}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder="name@example.com"
+                        className={error ? 'border-destructive' : ''}
+                    />
+                );
+
+            case 'phone':
+                // Typed text is sent as is and normalized to E.164 on save
+                return (
+                    <Input
+                        type="tel"
+                        value={value !== null ? String(value) : ''}
+                        onChange={(e) => onChange(e.target.value || null)}
+                        placeholder={
+                            field.options?.callingCode
+                                ? `e.g. +${field.options.callingCode} …, or a national number`
+                                : 'e.g. +44 20 7183 8750'
+                        }
+                        className={error ? 'border-destructive' : ''}
+                    />
+                );
+
+            case 'color': {
+                const color = typeof value === 'string' ? value : '';
+                return (
+                    <div className="flex items-center gap-2">
+                        <input
+                            type="color"
+                            value={/^#[0-9a-f]{6}/i.test(color) ? color.slice(0, 7) : '#000000'}
+                            onChange={(e) => onChange(e.target.value)}
+                            className="h-9 w-12 shrink-0 cursor-pointer rounded-md border bg-transparent p-1"
+                        />
+                        <Input
+                            value={color}
+                            onChange={(e) => onChange(e.target.value || null)}
+                            placeholder="#3b82f6 or rgb(59, 130, 246)"
+                            className={cn('font-mono', error && 'border-destructive')}
+                        />
+                    </div>
+                );
+            }
+
+            case 'currency': {
+                // Typed text is sent as is and stored as an exact amount on save
+                const fixed = field.options?.currency;
+                const text = isMoney(value)
+                    ? fixed
+                        ? value.amount
+                        : `${value.amount} ${value.currency}`
+                    : String(value ?? '');
+                return (
+                    <div className="flex items-center gap-2">
+                        <Input
+                            inputMode="decimal"
+                            value={text}
+                            onChange={(e) => onChange(e.target.value || null)}
+                            placeholder={fixed ? '0.00' : 'e.g. 12.50 EUR'}
+                            className={error ? 'border-destructive' : ''}
+                        />
+                        {fixed && (
+                            <span className="text-sm font-mono text-muted-foreground">{fixed}</span>
+                        )}
+                    </div>
+                );
+            }
+
+            case 'json':
+                return <JsonInput value={value ?? null} onChange={onChange} invalid={!!error} />;
+
             case 'boolean':
                 return (
                     <div className="flex items-center gap-2">
@@ -110,14 +331,109 @@ export function CustomFieldInput({
                         </SelectTrigger>
                         <SelectContent>
                             {choices.map((choice) => (
-                                <SelectItem key={choice} value={choice}>
-                                    {choice}
+                                <SelectItem key={choice.id} value={choice.id}>
+                                    <span className="flex items-center gap-2">
+                                        {choice.color && (
+                                            <span
+                                                className="h-2 w-2 rounded-full flex-shrink-0"
+                                                style={{ backgroundColor: choice.color }}
+                                            />
+                                        )}
+                                        {choice.label}
+                                    </span>
                                 </SelectItem>
                             ))}
                         </SelectContent>
                     </Select>
                 );
 
+            case 'rating':
+                return (
+                    <RatingScale
+                        value={typeof value === 'number' ? value : null}
+                        options={field.options}
+                        onChange={onChange}
+                    />
+                );
+
+            case 'multiselect': {
+                const options = field.options?.choices || [];
+                const selected = toChoiceValues(value);
+                const toggle = (choice: string) => {
+                    const next = selected.includes(choice)
+                        ? selected.filter((c) => c !== choice)
+                        : [...selected, choice];
+                    onChange(next.length > 0 ? next : null);
+                };
+                return (
+                    <div
+                        className={`flex flex-wrap gap-1.5 ${error ? 'rounded-md border border-destructive p-1.5' : ''}`}
+                    >
+                        {options.map((choice) => {
+                            const isSelected = selected.includes(choice.id);
+                            return (
+                                <ChoiceBadge
+                                    key={choice.id}
+                                    choice={choice}
+                                    variant={isSelected ? 'default' : 'outline'}
+                                    className="cursor-pointer select-none"
+                                    onClick={() => toggle(choice.id)}
+                                >
+                                    {isSelected && <Check className="h-3 w-3" />}
+                                </ChoiceBadge>
+                            );
+                        })}
+                    </div>
+                );
+            }
+
+            case 'file':
+                if (entryId === undefined) {
+                    return (
+                        <p className="text-sm text-muted-foreground">
+                            Save the entry first to attach files
+                        </p>
+                    );
+                }
+                return (
+                    <div className="space-y-2">
+                        <AttachmentList
+                            entryId={entryId}
+                            fieldId={field.id}
+                            attachments={toAttachments(value)}
+                            onRemove={handleRemoveAttachment}
+                            removing={removing}
+                        />
+                        <Button
+                            type="button"
+                            variant="outline"
+                            size="sm"
+                            onClick={handleAttach}
+                            disabled={isAttaching || removing !== null}
+                            className={error ? 'border-destructive' : ''}
+                        >
+                            {isAttaching ? (
+                                <Loader2 className="h-4 w-4 animate-spin" />
+                            ) : (
+                                <Paperclip className="h-4 w-4" />
+                            )}
+                            Add file
+                        </Button>
+                        {attachmentError && (
+                            <p className="text-xs text-destructive">{attachmentError}</p>
+                        )}
+                    </div>
+                );
+
+            case 'location':
+                return (
+                    <LocationInput
+                        value={isLocation(value) ? value : null}
+                        onChange={onChange}
+                        invalid={!!error}
+                    />
+                );
+
             case 'relation':
                 const targetVaultId = field.options?.targetVaultId;
                 if (!targetVaultId) {
@@ -127,6 +443,49 @@ export function CustomFieldInput({
                         </div>
                     );
                 }
+                if (field.options?.multiple) {
+                    const relations = toRelationValues(value);
+                    const maxItems = field.options.maxItems;
+                    const canAdd = maxItems === undefined || relations.length < maxItems;
+                    return (
+                        <div className="space-y-2">
+                            {relations.map((relation, index) => (
+                                <div
+                                    key={`${relation.vault_id}:${relation.entry_id}`}
+                                    className="flex items-center gap-2"
+                                >
+                                    <div className="flex-1 min-w-0">
+                                        <RelationFieldDisplay value={relation} compact={true} />
+                                    </div>
+                                    <Button
+                                        type="button"
+                                        variant="ghost"
+                                        size="icon"
+                                        className="shrink-0"
+                                        onClick={() => {
+                                            const next = relations.filter((_, i) => i !== index);
+                                            onChange(next.length > 0 ? next : null);
+                                        }}
+                                    >
+                                        <X className="h-4 w-4" />
+                                    </Button>
+                                </div>
+                            ))}
+                            {canAdd && (
+                                <RelationFieldEditor
+                                    targetVaultId={targetVaultId}
+                                    value={null}
+                                    onChange={(added: RelationValue | null) => {
+                                        if (added) onChange([...relations, added]);
+                                    }}
+                                    excludeIds={relations.map((relation) => relation.entry_id)}
+                                    placeholder="Add entry..."
+                                    error={error}
+                                />
+                            )}
+                        </div>
+                    );
+                }
                 return (
                     <RelationFieldEditor
                         targetVaultId={targetVaultId}
@@ -151,7 +510,7 @@ export function CustomFieldInput({
         <div className="space-y-2">
             <Label htmlFor={`field-${field.id}`} className="flex items-center gap-1">
                 {field.name}
-                {field.required && (
+                {required && (
                     <span className="text-destructive">*</span>
                 )}
             </Label>
@@ -1,23 +1,48 @@
 // Custom Field Renderer - Read-only display of custom field values
 
-import { ExternalLink, Calendar, Check, X } from 'lucide-react';
+import { ExternalLink, Calendar, CalendarClock, Check, Mail, MapPin, Phone, Timer, X } from 'lucide-react';
 import { Badge } from '@/components/ui/badge';
-import type { FieldDefinition, EntryMetadataValue } from '../types';
-import { isRelationValue } from '../types';
+import type { FieldDefinition, ComputedValue } from '../types';
+import {
+    findChoice,
+    formatCoordinates,
+    formatDuration,
+    formatMoney,
+    formatPartialDate,
+    identifierUrl,
+    isLocation,
+    isMoney,
+    isRelationValue,
+    toAttachments,
+    toChoiceValues,
+    toRelationValues,
+} from '../types';
+import { AttachmentList } from './AttachmentList';
+import { ChoiceBadge } from './ChoiceBadge';
+import { MarkdownView } from './MarkdownView';
+import { RatingScale } from './RatingScale';
 import { RelationFieldDisplay } from './RelationFieldDisplay';
 
 interface CustomFieldRendererProps {
     field: FieldDefinition;
-    value: EntryMetadataValue;
+    value: ComputedValue;
+    /** Entry the value belongs to, needed to open attachments */
+    entryId?: number;
     onNavigateToEntry?: (entryId: number, vaultId: number) => void;
 }
 
 export function CustomFieldRenderer({ 
     field, 
     value,
+    entryId,
     onNavigateToEntry,
 }: CustomFieldRendererProps) {
-    if (value === null || value === undefined || value === '') {
+    if (
+        value === null ||
+        value === undefined ||
+        value === '' ||
+        (Array.isArray(value) && value.length === 0)
+    ) {
         return null;
     }
 
@@ -28,6 +53,15 @@ export function CustomFieldRenderer({
                     <span className="text-sm">{String(value)}</span>
                 );
 
+            case 'markdown':
+                return (
+                    <MarkdownView
+                        vaultId={field.vault_id}
+                        source={String(value)}
+                        onNavigateToEntry={onNavigateToEntry}
+                    />
+                );
+
             case 'number':
                 return (
                     <span className="text-sm font-mono">
@@ -54,6 +88,33 @@ export function CustomFieldRenderer({
                     return <span className="text-sm">{String(value)}</span>;
                 }
 
+            case 'partialdate':
+                return (
+                    <span className="text-sm flex items-center gap-1.5">
+                        <Calendar className="h-3.5 w-3.5 text-muted-foreground" />
+                        {formatPartialDate(String(value))}
+                    </span>
+                );
+
+            case 'datetime':
+                return (
+                    <span className="text-sm flex items-center gap-1.5">
+                        <CalendarClock className="h-3.5 w-3.5 text-muted-foreground" />
+                        {new Date(String(value)).toLocaleString(undefined, {
+                            dateStyle: 'medium',
+                            timeStyle: 'short',
+                        })}
+                    </span>
+                );
+
+            case 'duration':
+                return (
+                    <span className="text-sm flex items-center gap-1.5">
+                        <Timer className="h-3.5 w-3.5 text-muted-foreground" />
+                        {typeof value === 'number' ? formatDuration(value) : String(value)}
+                    </span>
+                );
+
             case 'url':
                 return (
                     <a
@@ -67,6 +128,94 @@ export function CustomFieldRenderer({
                     </a>
                 );
 
+            case 'identifier': {
+                const url = identifierUrl(field.options?.scheme, String(value));
+                if (!url) {
+                    return <span className="text-sm font-mono">{String(value)}</span>;
+                }
+                return (
+                    <a
+                        href={url}
+                        target="_blank"
+                        rel="noopener noreferrer"
+                        className="text-sm font-mono text-primary hover:underline flex items-center gap-1.5 max-w-full"
+                    >
+                        <span className="truncate">{String(value)}</span>
+                        <ExternalLink className="h-3.5 w-3.5 flex-shrink-0" />
+                    </a>
+                );
+            }
+
+            case 'email':
+                return (
+                    <a
+                        href={`mailto:${String(value)}`}
+                        className="text-sm text-primary hover:underline flex items-center gap-1.5 max-w-full"
+                    >
+                        <Mail className="h-3.5 w-3.5 flex-shrink-0" />
+                        <span className="truncate">{String(value)}</span>
+                    </a>
+                );
+
+            case 'phone':
+                return (
+                    <a
+                        href={`tel:${String(value)}`}
+                        className="text-sm font-mono text-primary hover:underline flex items-center gap-1.5"
+                    >
+                        <Phone className="h-3.5 w-3.5 flex-shrink-0" />
+                        {String(value)}
+                    </a>
+                );
+
+            case 'color':
+                return (
+                    <span className="text-sm font-mono flex items-center gap-1.5">
+                        <span
+                            className="h-4 w-4 rounded border flex-shrink-0"
+                            style={{ backgroundColor: String(value) }}
+                        />
+                        {String(value)}
+                    </span>
+                );
+
+            case 'currency':
+                if (!isMoney(value)) return null;
+                return (
+                    <span className="text-sm font-mono" title={`${value.amount} ${value.currency}`}>
+                        {formatMoney(value)}
+                    </span>
+                );
+
+            case 'json':
+                return (
+                    <pre className="text-xs font-mono bg-muted rounded-md p-2 overflow-x-auto max-h-64">
+                        {JSON.stringify(value, null, 2)}
+                    </pre>
+                );
+
+            case 'location': {
+                if (!isLocation(value)) return null;
+                const mapUrl = `https://www.openstreetmap.org/?mlat=${value.lat}&mlon=${value.lng}#map=14/${value.lat}/${value.lng}`;
+                return (
+                    <a
+                        href={mapUrl}
+                        target="_blank"
+                        rel="noopener noreferrer"
+                        className="text-sm text-primary hover:underline flex items-center gap-1.5 max-w-full"
+                        title={formatCoordinates(value)}
+                    >
+                        <MapPin className="h-3.5 w-3.5 flex-shrink-0" />
+                        <span className="truncate">{value.name || formatCoordinates(value)}</span>
+                        {value.name && (
+                            <span className="text-xs text-muted-foreground flex-shrink-0">
+                                {formatCoordinates(value)}
+                            </span>
+                        )}
+                    </a>
+                );
+            }
+
             case 'boolean':
                 return value ? (
                     <Badge variant="default" className="gap-1">
@@ -82,10 +231,54 @@ export function CustomFieldRenderer({
 
             case 'select':
                 return (
-                    <Badge variant="outline">{String(value)}</Badge>
+                    <ChoiceBadge
+                        choice={findChoice(field.options, String(value))}
+                        fallback={String(value)}
+                    />
+                );
+
+            case 'rating':
+                return (
+                    <RatingScale
+                        value={typeof value === 'number' ? value : null}
+                        options={field.options}
+                        size="sm"
+                    />
+                );
+
+            case 'multiselect':
+                return (
+                    <div className="flex flex-wrap gap-1">
+                        {toChoiceValues(value).map((id) => (
+                            <ChoiceBadge key={id} choice={findChoice(field.options, id)} fallback={id} />
+                        ))}
+                    </div>
+                );
+
+            case 'file':
+                return (
+                    <AttachmentList
+                        entryId={entryId}
+                        fieldId={field.id}
+                        attachments={toAttachments(value)}
+                    />
                 );
 
             case 'relation':
+                if (Array.isArray(value)) {
+                    return (
+                        <div className="flex flex-col gap-1">
+                            {toRelationValues(value).map((relation) => (
+                                <RelationFieldDisplay
+                                    key={`${relation.vault_id}:${relation.entry_id}`}
+                                    value={relation}
+                                    onNavigate={onNavigateToEntry}
+                                    compact={true}
+                                />
+                            ))}
+                        </div>
+                    );
+                }
                 if (!isRelationValue(value)) {
                     return <span className="text-sm text-muted-foreground">Invalid relation</span>;
                 }
@@ -97,6 +290,40 @@ export function CustomFieldRenderer({
                     />
                 );
 
+            case 'lookup':
+            case 'rollup': {
+                const items = Array.isArray(value) ? value : [value];
+                return (
+                    <div className="flex flex-wrap gap-1">
+                        {items.map((item, index) =>
+                            isRelationValue(item) ? (
+                                <RelationFieldDisplay
+                                    key={index}
+                                    value={item}
+                                    onNavigate={onNavigateToEntry}
+                                    compact={true}
+                                />
+                            ) : items.length > 1 ? (
+                                <Badge key={index} variant="outline">
+                                    {formatComputedItem(item)}
+                                </Badge>
+                            ) : (
+                                <span key={index} className="text-sm">
+                                    {formatComputedItem(item)}
+                                </span>
+                            )
+                        )}
+                    </div>
+                );
+            }
+
+            case 'formula':
+                return (
+                    <span className={typeof value === 'number' ? 'text-sm font-mono' : 'text-sm'}>
+                        {formatComputedItem(value)}
+                    </span>
+                );
+
             default:
                 return <span className="text-sm">{String(value)}</span>;
         }
@@ -114,3 +341,15 @@ export function CustomFieldRenderer({
         </div>
     );
 }
+
+/**
+ * Formats one value read from a related entry or computed by a formula.
+ */
+function formatComputedItem(item: ComputedValue): string {
+    if (typeof item === 'number') return item.toLocaleString();
+    if (typeof item === 'boolean') return item ? 'Yes' : 'No';
+    if (Array.isArray(item)) return item.map(formatComputedItem).join(', ');
+    if (isMoney(item)) return formatMoney(item);
+    if (item !== null && typeof item === 'object') return JSON.stringify(item);
+    return String(item ?? '');
+}
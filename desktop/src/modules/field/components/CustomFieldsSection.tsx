@@ -1,26 +1,43 @@
 // Custom Fields Section - Display custom fields in read-only mode
 
-import type { FieldDefinition, EntryMetadata } from '../types';
+import type { FieldDefinition, EntryMetadata, ComputedValues } from '../types';
+import { isDerivedField } from '../types';
 import { CustomFieldRenderer } from './CustomFieldRenderer';
 
 interface CustomFieldsSectionProps {
     fields: FieldDefinition[];
     metadata: EntryMetadata | null;
+    /** Lookup, rollup and formula values of the entry */
+    computed?: ComputedValues | null;
+    /** Entry the values belong to, needed to open attachments */
+    entryId?: number;
 }
 
 export function CustomFieldsSection({
     fields,
     metadata,
+    computed,
+    entryId,
 }: CustomFieldsSectionProps) {
     if (fields.length === 0) {
         return null;
     }
 
-    // Filter to only show fields that have values - use field.id as key
-    const fieldsWithValues = fields.filter((field) => {
+    // Derived fields read from the computed values - use field.id as key
+    const valueOf = (field: FieldDefinition) => {
         const fieldKey = field.id.toString();
-        const value = metadata?.[fieldKey];
-        return value !== null && value !== undefined && value !== '';
+        return isDerivedField(field) ? computed?.[fieldKey] : metadata?.[fieldKey];
+    };
+
+    // Filter to only show fields that have values
+    const fieldsWithValues = fields.filter((field) => {
+        const value = valueOf(field);
+        return (
+            value !== null &&
+            value !== undefined &&
+            value !== '' &&
+            !(Array.isArray(value) && value.length === 0)
+        );
     });
 
     if (fieldsWithValues.length === 0) {
@@ -42,16 +59,14 @@ export function CustomFieldsSection({
                 Custom Fields
             </h4>
             <div className="grid gap-3">
-                {fieldsWithValues.map((field) => {
-                    const fieldKey = field.id.toString();
-                    return (
-                        <CustomFieldRenderer
-                            key={field.id}
-                            field={field}
-                            value={metadata?.[fieldKey] ?? null}
-                        />
-                    );
-                })}
+                {fieldsWithValues.map((field) => (
+                    <CustomFieldRenderer
+                        key={field.id}
+                        field={field}
+                        value={valueOf(field) ?? null}
+                        entryId={entryId}
+                    />
+                ))}
             </div>
         </div>
     );
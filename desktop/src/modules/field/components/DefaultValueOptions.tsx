@@ -0,0 +1,244 @@
+// Default Value Options - Value filled in on new entries that leave a field empty
+
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import type {
+    Choice,
+    DefaultValue,
+    DefaultValueKind,
+    FieldDefinition,
+    FieldOptions,
+    FieldType,
+    JsonValue,
+} from '../types';
+import { defaultValueKinds, isDerivedField } from '../types';
+
+interface DefaultValueOptionsProps {
+    fieldType: FieldType;
+    // Field being edited, which cannot copy itself
+    fieldId?: number;
+    // Fields of the vault a default can copy
+    fields: FieldDefinition[];
+    // Choices of select and multiselect fields, new ones without an ID yet
+    choices?: Choice[];
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+    backfill: boolean;
+    onBackfillChange: (backfill: boolean) => void;
+}
+
+const KIND_LABELS: Record<DefaultValueKind, string> = {
+    value: 'Fixed value',
+    today: 'Today',
+    now: 'Current time',
+    counter: 'Counter',
+    'copy-field': 'Copy another field',
+};
+
+const NONE = 'none';
+
+// Reads a fixed default typed as text into a value of the field's type
+function parseValue(fieldType: FieldType, text: string): JsonValue {
+    switch (fieldType) {
+        case 'number':
+        case 'rating': {
+            const number = parseFloat(text);
+            return Number.isNaN(number) ? text : number;
+        }
+        case 'multiselect':
+            return text.split(',').map((item) => item.trim()).filter(Boolean);
+        case 'json':
+            try {
+                return JSON.parse(text) as JsonValue;
+            } catch {
+                return text;
+            }
+        default:
+            return text;
+    }
+}
+
+// Choices are referred to by ID once saved, and by label until then
+const choiceKey = (choice: Choice) => choice.id || choice.label;
+
+// Shows a fixed default as text, with saved choice IDs as their labels
+function formatValue(value: JsonValue, choices: Choice[]): string {
+    const label = (item: string) => choices.find((c) => c.id === item)?.label ?? item;
+    if (typeof value === 'string') return label(value);
+    if (Array.isArray(value) && value.every((item) => typeof item === 'string')) {
+        return value.map(label).join(', ');
+    }
+    return JSON.stringify(value);
+}
+
+export function DefaultValueOptions({
+    fieldType,
+    fieldId,
+    fields,
+    choices,
+    options,
+    onChange,
+    backfill,
+    onBackfillChange,
+}: DefaultValueOptionsProps) {
+    const kinds = defaultValueKinds(fieldType);
+    if (kinds.length === 0) return null;
+
+    const defaultValue = options.defaultValue;
+    const sources = fields.filter(
+        (f) =>
+            f.id !== fieldId &&
+            !isDerivedField(f) &&
+            f.field_type !== 'file' &&
+            f.field_type !== 'relation'
+    );
+
+    const setDefault = (value: DefaultValue | undefined) => {
+        onChange({ ...options, defaultValue: value });
+    };
+
+    const handleKindChange = (kind: string) => {
+        switch (kind) {
+            case 'value':
+                setDefault({ kind: 'value', value: fieldType === 'boolean' ? false : '' });
+                break;
+            case 'today':
+            case 'now':
+                setDefault({ kind });
+                break;
+            case 'counter':
+                setDefault({ kind: 'counter' });
+                break;
+            case 'copy-field':
+                setDefault(sources.length > 0 ? { kind: 'copy-field', fieldId: sources[0].id } : undefined);
+                break;
+            default:
+                setDefault(undefined);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="space-y-2">
+                <Label>Default value</Label>
+                <Select value={defaultValue?.kind ?? NONE} onValueChange={handleKindChange}>
+                    <SelectTrigger>
+                        <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                        <SelectItem value={NONE}>None</SelectItem>
+                        {kinds
+                            .filter((kind) => kind !== 'copy-field' || sources.length > 0)
+                            .map((kind) => (
+                                <SelectItem key={kind} value={kind}>
+                                    {KIND_LABELS[kind]}
+                                </SelectItem>
+                            ))}
+                    </SelectContent>
+                </Select>
+            </div>
+
+            {defaultValue?.kind === 'value' && fieldType === 'boolean' && (
+                <Select
+                    value={defaultValue.value === true ? 'yes' : 'no'}
+                    onValueChange={(v) => setDefault({ kind: 'value', value: v === 'yes' })}
+                >
+                    <SelectTrigger>
+                        <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                        <SelectItem value="yes">Yes</SelectItem>
+                        <SelectItem value="no">No</SelectItem>
+                    </SelectContent>
+                </Select>
+            )}
+
+            {defaultValue?.kind === 'value' && fieldType === 'select' && (
+                <Select
+                    value={typeof defaultValue.value === 'string' && defaultValue.value ? defaultValue.value : undefined}
+                    onValueChange={(v) => setDefault({ kind: 'value', value: v })}
+                >
+                    <SelectTrigger>
+                        <SelectValue placeholder="Select an option..." />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {(choices ?? []).filter((c) => c.label.trim()).map((choice) => (
+                            <SelectItem key={choiceKey(choice)} value={choiceKey(choice)}>
+                                {choice.label}
+                            </SelectItem>
+                        ))}
+                    </SelectContent>
+                </Select>
+            )}
+
+            {defaultValue?.kind === 'value' && fieldType !== 'boolean' && fieldType !== 'select' && (
+                <Input
+                    type={fieldType === 'number' || fieldType === 'rating' ? 'number' : fieldType === 'date' ? 'date' : 'text'}
+                    value={formatValue(defaultValue.value, choices ?? [])}
+                    onChange={(e) => setDefault({ kind: 'value', value: parseValue(fieldType, e.target.value) })}
+                    placeholder={fieldType === 'multiselect' ? 'Options, separated by commas' : 'Default value'}
+                />
+            )}
+
+            {defaultValue?.kind === 'counter' && (
+                <div className="space-y-2">
+                    <Label htmlFor="counterStart">Start at</Label>
+                    <Input
+                        id="counterStart"
+                        type="number"
+                        step={1}
+                        value={defaultValue.start ?? ''}
+                        onChange={(e) =>
+                            setDefault({
+                                kind: 'counter',
+                                start: e.target.value ? parseInt(e.target.value, 10) : undefined,
+                            })
+                        }
+                        placeholder="1"
+                    />
+                    <p className="text-xs text-muted-foreground">
+                        Each new entry gets one more than the largest number in the vault
+                    </p>
+                </div>
+            )}
+
+            {defaultValue?.kind === 'copy-field' && (
+                <Select
+                    value={String(defaultValue.fieldId)}
+                    onValueChange={(v) => setDefault({ kind: 'copy-field', fieldId: parseInt(v, 10) })}
+                >
+                    <SelectTrigger>
+                        <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {sources.map((source) => (
+                            <SelectItem key={source.id} value={String(source.id)}>
+                                {source.name}
+                            </SelectItem>
+                        ))}
+                    </SelectContent>
+                </Select>
+            )}
+
+            {defaultValue && (
+                <div className="flex items-center justify-between">
+                    <div className="space-y-0.5">
+                        <Label htmlFor="backfill">Fill in existing entries</Label>
+                        <p className="text-xs text-muted-foreground">
+                            Entries without a value get the default too
+                        </p>
+                    </div>
+                    <Switch id="backfill" checked={backfill} onCheckedChange={onBackfillChange} />
+                </div>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,216 @@
+// Derived Field Options - Relation, source field and aggregate for lookup and rollup fields
+
+import { useEffect, useMemo, useState } from 'react';
+import { Loader2 } from 'lucide-react';
+import { Label } from '@/components/ui/label';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import { vaultApi, type Vault } from '@/modules/vault';
+import { fieldApi } from '../api';
+import type { FieldDefinition, FieldOptions, RollupFunction } from '../types';
+import { isDerivedField, isNumericField } from '../types';
+
+interface DerivedFieldOptionsProps {
+    vaultId: number;
+    fieldType: 'lookup' | 'rollup';
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+const ROLLUP_FUNCTIONS: { value: RollupFunction; label: string; numeric?: boolean }[] = [
+    { value: 'count', label: 'Count' },
+    { value: 'sum', label: 'Sum', numeric: true },
+    { value: 'average', label: 'Average', numeric: true },
+    { value: 'min', label: 'Minimum' },
+    { value: 'max', label: 'Maximum' },
+    { value: 'distinct-list', label: 'Distinct values' },
+];
+
+interface RelationPath {
+    key: string;
+    field: FieldDefinition;
+    backlinks: boolean;
+    relatedVaultId: number;
+    label: string;
+}
+
+export function DerivedFieldOptions({
+    vaultId,
+    fieldType,
+    options,
+    onChange,
+}: DerivedFieldOptionsProps) {
+    const [vaults, setVaults] = useState<Vault[]>([]);
+    const [fieldsByVault, setFieldsByVault] = useState<Map<number, FieldDefinition[]>>(new Map());
+    const [isLoading, setIsLoading] = useState(false);
+
+    // Relation fields can live in any vault when following backlinks
+    useEffect(() => {
+        let cancelled = false;
+        setIsLoading(true);
+
+        vaultApi.list()
+            .then(async (loaded) => {
+                const lists = await Promise.all(loaded.map((v) => fieldApi.list(v.id)));
+                if (!cancelled) {
+                    setVaults(loaded);
+                    setFieldsByVault(new Map(loaded.map((v, i) => [v.id, lists[i]])));
+                }
+            })
+            .catch((err) => console.error('Failed to load relation fields:', err))
+            .finally(() => !cancelled && setIsLoading(false));
+
+        return () => {
+            cancelled = true;
+        };
+    }, [vaultId]);
+
+    const paths = useMemo(() => {
+        const vaultName = (id: number) => vaults.find((v) => v.id === id)?.name ?? `Vault ${id}`;
+        const result: RelationPath[] = [];
+
+        for (const [id, fields] of fieldsByVault) {
+            for (const field of fields) {
+                const target = field.options?.targetVaultId;
+                if (field.field_type !== 'relation' || target === undefined) continue;
+
+                if (id === vaultId) {
+                    result.push({
+                        key: `out:${field.id}`,
+                        field,
+                        backlinks: false,
+                        relatedVaultId: target,
+                        label: `${field.name} → ${vaultName(target)}`,
+                    });
+                }
+                if (fieldType === 'rollup' && target === vaultId) {
+                    result.push({
+                        key: `in:${field.id}`,
+                        field,
+                        backlinks: true,
+                        relatedVaultId: id,
+                        label: `${vaultName(id)} linking here via ${field.name}`,
+                    });
+                }
+            }
+        }
+
+        return result;
+    }, [fieldsByVault, vaults, vaultId, fieldType]);
+
+    const pathKey = options.viaFieldId !== undefined
+        ? `${options.backlinks ? 'in' : 'out'}:${options.viaFieldId}`
+        : '';
+    const path = paths.find((p) => p.key === pathKey);
+    const sourceFields = (path ? fieldsByVault.get(path.relatedVaultId) ?? [] : []).filter(
+        (f) => !isDerivedField(f)
+    );
+    const sourceField = sourceFields.find((f) => f.id === options.sourceFieldId);
+
+    const selectPath = (key: string) => {
+        const next = paths.find((p) => p.key === key);
+        if (!next) return;
+        onChange({
+            ...options,
+            viaFieldId: next.field.id,
+            backlinks: next.backlinks || undefined,
+            sourceFieldId: undefined,
+        });
+    };
+
+    if (isLoading) {
+        return (
+            <div className="flex items-center gap-2 text-sm text-muted-foreground">
+                <Loader2 className="h-4 w-4 animate-spin" />
+                Loading relation fields...
+            </div>
+        );
+    }
+
+    if (paths.length === 0) {
+        return (
+            <div className="text-sm text-muted-foreground">
+                No relation fields available. Add a relation field first.
+            </div>
+        );
+    }
+
+    return (
+        <div className="space-y-3">
+            <div className="space-y-2">
+                <Label>Relation *</Label>
+                <Select value={pathKey} onValueChange={selectPath}>
+                    <SelectTrigger>
+                        <SelectValue placeholder="Select a relation..." />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {paths.map((p) => (
+                            <SelectItem key={p.key} value={p.key}>
+                                {p.label}
+                            </SelectItem>
+                        ))}
+                    </SelectContent>
+                </Select>
+            </div>
+
+            {path && !(fieldType === 'rollup' && options.rollup === 'count') && (
+                <div className="space-y-2">
+                    <Label>Field from related entries</Label>
+                    <Select
+                        value={options.sourceFieldId !== undefined ? String(options.sourceFieldId) : 'title'}
+                        onValueChange={(value) =>
+                            onChange({
+                                ...options,
+                                sourceFieldId: value === 'title' ? undefined : parseInt(value, 10),
+                            })
+                        }
+                    >
+                        <SelectTrigger>
+                            <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                            <SelectItem value="title">Title</SelectItem>
+                            {sourceFields.map((f) => (
+                                <SelectItem key={f.id} value={String(f.id)}>
+                                    {f.name}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+            )}
+
+            {fieldType === 'rollup' && (
+                <div className="space-y-2">
+                    <Label>Aggregate *</Label>
+                    <Select
+                        value={options.rollup ?? ''}
+                        onValueChange={(value) =>
+                            onChange({ ...options, rollup: value as RollupFunction })
+                        }
+                    >
+                        <SelectTrigger>
+                            <SelectValue placeholder="Select an aggregate..." />
+                        </SelectTrigger>
+                        <SelectContent>
+                            {ROLLUP_FUNCTIONS.map((fn) => (
+                                <SelectItem
+                                    key={fn.value}
+                                    value={fn.value}
+                                    disabled={fn.numeric && !(sourceField && isNumericField(sourceField))}
+                                >
+                                    {fn.label}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+            )}
+        </div>
+    );
+}
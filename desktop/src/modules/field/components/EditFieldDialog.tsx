@@ -12,9 +12,21 @@ import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { Switch } from '@/components/ui/switch';
-import { Loader2, Plus, X } from 'lucide-react';
+import { Loader2 } from 'lucide-react';
+import { entryApi } from '@/modules/entry/api';
 import { useFieldStore } from '../store';
-import type { FieldDefinition, FieldOptions } from '../types';
+import type { Choice, FieldDefinition, FieldOptions } from '../types';
+import { isDerivedField } from '../types';
+import { ChoiceListEditor } from './ChoiceListEditor';
+import { ConstraintFieldOptions } from './ConstraintFieldOptions';
+import { CurrencyFieldOptions } from './CurrencyFieldOptions';
+import { DefaultValueOptions } from './DefaultValueOptions';
+import { FileFieldOptions } from './FileFieldOptions';
+import { IdentifierFieldOptions } from './IdentifierFieldOptions';
+import { FormulaFieldOptions } from './FormulaFieldOptions';
+import { JsonFieldOptions } from './JsonFieldOptions';
+import { PhoneFieldOptions } from './PhoneFieldOptions';
+import { RatingFieldOptions } from './RatingFieldOptions';
 
 interface EditFieldDialogProps {
     field: FieldDefinition | null;
@@ -27,7 +39,7 @@ export function EditFieldDialog({
     isOpen,
     onClose,
 }: EditFieldDialogProps) {
-    const { updateField } = useFieldStore();
+    const { fields, updateField } = useFieldStore();
     const [isSaving, setIsSaving] = useState(false);
     const [error, setError] = useState<string | null>(null);
 
@@ -37,7 +49,19 @@ export function EditFieldDialog({
     const [maxLength, setMaxLength] = useState<string>('');
     const [min, setMin] = useState<string>('');
     const [max, setMax] = useState<string>('');
-    const [choices, setChoices] = useState<string[]>(['']);
+    const [choices, setChoices] = useState<Choice[]>([{ id: '', label: '' }]);
+    const [choiceCounts, setChoiceCounts] = useState<Record<string, number>>({});
+    const [mergeChoices, setMergeChoices] = useState<Record<string, string>>({});
+    const [ratingOptions, setRatingOptions] = useState<FieldOptions>({});
+    const [fileOptions, setFileOptions] = useState<FieldOptions>({});
+    const [identifierOptions, setIdentifierOptions] = useState<FieldOptions>({});
+    const [phoneOptions, setPhoneOptions] = useState<FieldOptions>({});
+    const [currencyOptions, setCurrencyOptions] = useState<FieldOptions>({});
+    const [jsonOptions, setJsonOptions] = useState<FieldOptions>({});
+    const [formulaOptions, setFormulaOptions] = useState<FieldOptions>({});
+    const [constraintOptions, setConstraintOptions] = useState<FieldOptions>({});
+    const [defaultOptions, setDefaultOptions] = useState<FieldOptions>({});
+    const [backfill, setBackfill] = useState(false);
 
     // Initialize form when field changes
     useEffect(() => {
@@ -47,13 +71,56 @@ export function EditFieldDialog({
             setMaxLength(field.options?.maxLength?.toString() || '');
             setMin(field.options?.min?.toString() || '');
             setMax(field.options?.max?.toString() || '');
-            setChoices(field.options?.choices || ['']);
+            setChoices(field.options?.choices || [{ id: '', label: '' }]);
+            setMergeChoices({});
+            setRatingOptions({
+                max: field.options?.max,
+                step: field.options?.step,
+                icon: field.options?.icon,
+            });
+            setFileOptions({
+                maxFileSize: field.options?.maxFileSize,
+                allowedExtensions: field.options?.allowedExtensions,
+            });
+            setIdentifierOptions({
+                scheme: field.options?.scheme,
+                pattern: field.options?.pattern,
+                patternMessage: field.options?.patternMessage,
+                unique: field.options?.unique,
+            });
+            setPhoneOptions({ callingCode: field.options?.callingCode });
+            setCurrencyOptions({ currency: field.options?.currency });
+            setJsonOptions({ jsonSchema: field.options?.jsonSchema });
+            setFormulaOptions({ formula: field.options?.formula });
+            setConstraintOptions({
+                minLength: field.options?.minLength,
+                pattern: field.options?.pattern,
+                patternMessage: field.options?.patternMessage,
+                unique: field.options?.unique,
+            });
+            setDefaultOptions({ defaultValue: field.options?.defaultValue });
+            setBackfill(false);
             setError(null);
         }
     }, [field]);
 
+    // Entry counts shown for choices being removed
+    useEffect(() => {
+        setChoiceCounts({});
+        if (!field || !isOpen) return;
+        if (field.field_type !== 'select' && field.field_type !== 'multiselect') return;
+        entryApi
+            .getChoiceCounts(field.id)
+            .then((counts) =>
+                setChoiceCounts(Object.fromEntries(counts.map((c) => [c.choice, c.count])))
+            )
+            .catch(() => setChoiceCounts({}));
+    }, [field, isOpen]);
+
     if (!field) return null;
 
+    const hasChoices = field.field_type === 'select' || field.field_type === 'multiselect';
+
     const handleSubmit = async (e: React.FormEvent) => {
         e.preventDefault();
 
@@ -69,7 +136,7 @@ export function EditFieldDialog({
             // Build options based on field type
             const options: FieldOptions = {};
 
-            if (field.field_type === 'text' && maxLength) {
+            if ((field.field_type === 'text' || field.field_type === 'markdown') && maxLength) {
                 options.maxLength = parseInt(maxLength, 10);
             }
 
@@ -78,20 +145,82 @@ export function EditFieldDialog({
                 if (max) options.max = parseFloat(max);
             }
 
-            if (field.field_type === 'select') {
-                const validChoices = choices.filter((c) => c.trim());
+            if (field.field_type === 'rating') {
+                Object.assign(options, ratingOptions);
+            }
+
+            if (field.field_type === 'file') {
+                Object.assign(options, fileOptions);
+            }
+
+            if (field.field_type === 'identifier') {
+                if (identifierOptions.scheme === 'custom' && !identifierOptions.pattern?.trim()) {
+                    setError('Please enter a pattern for custom identifiers');
+                    setIsSaving(false);
+                    return;
+                }
+                Object.assign(options, identifierOptions);
+            }
+
+            if (field.field_type === 'phone') {
+                Object.assign(options, phoneOptions);
+            }
+
+            if (field.field_type === 'currency') {
+                Object.assign(options, currencyOptions);
+            }
+
+            if (field.field_type === 'json') {
+                Object.assign(options, jsonOptions);
+            }
+
+            if (field.field_type === 'formula') {
+                if (!formulaOptions.formula?.trim()) {
+                    setError('Please enter a formula');
+                    setIsSaving(false);
+                    return;
+                }
+                options.formula = formulaOptions.formula.trim();
+            }
+
+            if (hasChoices) {
+                const validChoices = choices.filter((c) => c.label.trim());
                 if (validChoices.length === 0) {
-                    setError('At least one choice is required for dropdown fields');
+                    setError(`At least one choice is required for ${field.field_type === 'select' ? 'dropdown' : 'multi-select'} fields`);
                     setIsSaving(false);
                     return;
                 }
                 options.choices = validChoices;
             }
 
+            // Identifier fields set their own pattern and uniqueness
+            if (field.field_type !== 'identifier') {
+                const { minLength, pattern, patternMessage, unique } = constraintOptions;
+                if (minLength !== undefined) options.minLength = minLength;
+                if (pattern) {
+                    options.pattern = pattern;
+                    if (patternMessage?.trim()) options.patternMessage = patternMessage.trim();
+                }
+                if (unique) options.unique = true;
+            }
+
+            if (defaultOptions.defaultValue) {
+                options.defaultValue = defaultOptions.defaultValue;
+            }
+
+            // Empty options still clear a default or constraint that was removed
+            const hadOptions =
+                field.options?.defaultValue !== undefined ||
+                field.options?.minLength !== undefined ||
+                field.options?.pattern !== undefined ||
+                field.options?.unique !== undefined;
+
             await updateField(field.id, {
                 name: name.trim(),
-                options: Object.keys(options).length > 0 ? options : undefined,
-                required,
+                options: Object.keys(options).length > 0 || hadOptions ? options : undefined,
+                required: required && !isDerivedField(field),
+                backfill: backfill && options.defaultValue !== undefined,
+                merge_choices: hasChoices ? mergeChoices : undefined,
             });
 
             onClose();
@@ -102,29 +231,13 @@ export function EditFieldDialog({
         }
     };
 
-    const addChoice = () => {
-        setChoices([...choices, '']);
-    };
-
-    const removeChoice = (index: number) => {
-        if (choices.length > 1) {
-            setChoices(choices.filter((_, i) => i !== index));
-        }
-    };
-
-    const updateChoice = (index: number, value: string) => {
-        const newChoices = [...choices];
-        newChoices[index] = value;
-        setChoices(newChoices);
-    };
-
     return (
         <Dialog open={isOpen} onOpenChange={(open) => !open && onClose()}>
             <DialogContent className="max-w-md">
                 <DialogHeader>
                     <DialogTitle>Edit Field</DialogTitle>
                     <DialogDescription>
-                        Update the field settings. Field type cannot be changed.
+                        Update the field settings. Use Change type in the field list to convert it to another type.
                     </DialogDescription>
                 </DialogHeader>
 
@@ -155,23 +268,25 @@ export function EditFieldDialog({
                         />
                     </div>
 
-                    {/* Required toggle */}
-                    <div className="flex items-center justify-between">
-                        <div className="space-y-0.5">
-                            <Label htmlFor="editRequired">Required</Label>
-                            <p className="text-xs text-muted-foreground">
-                                Entry cannot be saved without this field
-                            </p>
+                    {/* Required toggle (not available for computed fields) */}
+                    {!isDerivedField(field) && (
+                        <div className="flex items-center justify-between">
+                            <div className="space-y-0.5">
+                                <Label htmlFor="editRequired">Required</Label>
+                                <p className="text-xs text-muted-foreground">
+                                    Entry cannot be saved without this field
+                                </p>
+                            </div>
+                            <Switch
+                                id="editRequired"
+                                checked={required}
+                                onCheckedChange={setRequired}
+                            />
                         </div>
-                        <Switch
-                            id="editRequired"
-                            checked={required}
-                            onCheckedChange={setRequired}
-                        />
-                    </div>
+                    )}
 
                     {/* Type-specific options */}
-                    {field.field_type === 'text' && (
+                    {(field.field_type === 'text' || field.field_type === 'markdown') && (
                         <div className="space-y-2">
                             <Label htmlFor="editMaxLength">Maximum Length (optional)</Label>
                             <Input
@@ -185,6 +300,51 @@ export function EditFieldDialog({
                         </div>
                     )}
 
+                    {field.field_type === 'rating' && (
+                        <RatingFieldOptions options={ratingOptions} onChange={setRatingOptions} />
+                    )}
+
+                    {field.field_type === 'identifier' && (
+                        <IdentifierFieldOptions
+                            options={identifierOptions}
+                            onChange={setIdentifierOptions}
+                            lockScheme={true}
+                        />
+                    )}
+
+                    {field.field_type === 'file' && (
+                        <FileFieldOptions
+                            key={field.id}
+                            options={fileOptions}
+                            onChange={setFileOptions}
+                        />
+                    )}
+
+                    {field.field_type === 'phone' && (
+                        <PhoneFieldOptions options={phoneOptions} onChange={setPhoneOptions} />
+                    )}
+
+                    {field.field_type === 'currency' && (
+                        <CurrencyFieldOptions options={currencyOptions} onChange={setCurrencyOptions} />
+                    )}
+
+                    {field.field_type === 'json' && (
+                        <JsonFieldOptions
+                            key={field.id}
+                            options={jsonOptions}
+                            onChange={setJsonOptions}
+                        />
+                    )}
+
+                    {field.field_type === 'formula' && (
+                        <FormulaFieldOptions
+                            vaultId={field.vault_id}
+                            fieldId={field.id}
+                            options={formulaOptions}
+                            onChange={setFormulaOptions}
+                        />
+                    )}
+
                     {field.field_type === 'number' && (
                         <div className="grid grid-cols-2 gap-4">
                             <div className="space-y-2">
@@ -210,42 +370,35 @@ export function EditFieldDialog({
                         </div>
                     )}
 
-                    {field.field_type === 'select' && (
-                        <div className="space-y-2">
-                            <Label>Dropdown Options *</Label>
-                            <div className="space-y-2">
-                                {choices.map((choice, index) => (
-                                    <div key={index} className="flex gap-2">
-                                        <Input
-                                            value={choice}
-                                            onChange={(e) => updateChoice(index, e.target.value)}
-                                            placeholder={`Option ${index + 1}`}
-                                        />
-                                        <Button
-                                            type="button"
-                                            variant="ghost"
-                                            size="icon"
-                                            onClick={() => removeChoice(index)}
-                                            disabled={choices.length <= 1}
-                                        >
-                                            <X className="h-4 w-4" />
-                                        </Button>
-                                    </div>
-                                ))}
-                                <Button
-                                    type="button"
-                                    variant="outline"
-                                    size="sm"
-                                    onClick={addChoice}
-                                    className="w-full"
-                                >
-                                    <Plus className="h-4 w-4 mr-2" />
-                                    Add Option
-                                </Button>
-                            </div>
-                        </div>
+                    {hasChoices && (
+                        <ChoiceListEditor
+                            label={field.field_type === 'select' ? 'Dropdown Options *' : 'Tag Options *'}
+                            choices={choices}
+                            onChange={setChoices}
+                            saved={field.options?.choices}
+                            counts={choiceCounts}
+                            merges={mergeChoices}
+                            onMergesChange={setMergeChoices}
+                        />
                     )}
 
+                    <ConstraintFieldOptions
+                        fieldType={field.field_type}
+                        options={constraintOptions}
+                        onChange={setConstraintOptions}
+                    />
+
+                    <DefaultValueOptions
+                        fieldType={field.field_type}
+                        fieldId={field.id}
+                        fields={fields.filter((f) => f.vault_id === field.vault_id)}
+                        choices={choices}
+                        options={defaultOptions}
+                        onChange={setDefaultOptions}
+                        backfill={backfill}
+                        onBackfillChange={setBackfill}
+                    />
+
                     {/* Actions */}
                     <div className="flex justify-end gap-2 pt-4">
                         <Button
@@ -19,12 +19,20 @@ import {
     AlertDialogTitle,
 } from '@/components/ui/alert-dialog';
 import { Button } from '@/components/ui/button';
-import { Plus, Settings2 } from 'lucide-react';
+import { ListChecks, Plus, Settings2 } from 'lucide-react';
 import { useFieldStore } from '../store';
 import { FieldDefinitionRow } from './FieldDefinitionRow';
 import { CreateFieldDialog } from './CreateFieldDialog';
 import { EditFieldDialog } from './EditFieldDialog';
+import { ConvertFieldDialog } from './ConvertFieldDialog';
+import { FieldRulesDialog } from './FieldRulesDialog';
 import type { FieldDefinition } from '../types';
+import { isDerivedField } from '../types';
+
+// Files, relations and computed fields cannot change type
+function isConvertible(field: FieldDefinition): boolean {
+    return !isDerivedField(field) && field.field_type !== 'file' && field.field_type !== 'relation';
+}
 
 interface FieldDefinitionManagerProps {
     vaultId: number;
@@ -42,6 +50,8 @@ export function FieldDefinitionManager({
     const { fields, isLoading, fetchFields, deleteField, reorderFields } = useFieldStore();
     const [showCreateDialog, setShowCreateDialog] = useState(false);
     const [editingField, setEditingField] = useState<FieldDefinition | null>(null);
+    const [convertingField, setConvertingField] = useState<FieldDefinition | null>(null);
+    const [showRulesDialog, setShowRulesDialog] = useState(false);
     const [showDeleteConfirm, setShowDeleteConfirm] = useState(false);
     const [deletingFieldId, setDeletingFieldId] = useState<number | null>(null);
 
@@ -116,6 +126,9 @@ export function FieldDefinitionManager({
                                         key={field.id}
                                         field={field}
                                         onEdit={() => setEditingField(field)}
+                                        onConvert={
+                                            isConvertible(field) ? () => setConvertingField(field) : undefined
+                                        }
                                         onDelete={() => handleDeleteClick(field.id)}
                                         onMoveUp={index > 0 ? () => handleMoveUp(index) : undefined}
                                         onMoveDown={index < fields.length - 1 ? () => handleMoveDown(index) : undefined}
@@ -126,15 +139,23 @@ export function FieldDefinitionManager({
                     </div>
 
                     {fields.length > 0 && (
-                        <div className="border-t pt-4">
+                        <div className="border-t pt-4 flex gap-2">
                             <Button
                                 variant="outline"
                                 onClick={() => setShowCreateDialog(true)}
-                                className="w-full"
+                                className="flex-1"
                             >
                                 <Plus className="h-4 w-4 mr-2" />
                                 Add Field
                             </Button>
+                            <Button
+                                variant="outline"
+                                onClick={() => setShowRulesDialog(true)}
+                                disabled={fields.length < 2}
+                            >
+                                <ListChecks className="h-4 w-4 mr-2" />
+                                Rules
+                            </Button>
                         </div>
                     )}
                 </DialogContent>
@@ -154,6 +175,20 @@ export function FieldDefinitionManager({
                 onClose={() => setEditingField(null)}
             />
 
+            {/* Convert Field Dialog */}
+            <ConvertFieldDialog
+                field={convertingField}
+                isOpen={!!convertingField}
+                onClose={() => setConvertingField(null)}
+            />
+
+            {/* Field Rules Dialog */}
+            <FieldRulesDialog
+                vaultId={vaultId}
+                isOpen={showRulesDialog}
+                onClose={() => setShowRulesDialog(false)}
+            />
+
             {/* Delete Confirmation Dialog */}
             <AlertDialog open={showDeleteConfirm} onOpenChange={setShowDeleteConfirm}>
                 <AlertDialogContent>
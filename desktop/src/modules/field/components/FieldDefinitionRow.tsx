@@ -4,22 +4,42 @@ import { Button } from '@/components/ui/button';
 import { Badge } from '@/components/ui/badge';
 import {
     Pencil,
+    Repeat,
     Trash2,
     ChevronUp,
     ChevronDown,
     Type,
+    FileText,
     Hash,
     Calendar,
+    CalendarDays,
+    CalendarClock,
+    Timer,
     Link,
+    Barcode,
+    Mail,
+    Phone,
+    Palette,
+    Banknote,
+    Braces,
     ToggleLeft,
     List,
+    ListChecks,
     Link2,
+    ArrowRight,
+    Sigma,
+    Star,
+    Paperclip,
+    MapPin,
+    Calculator,
 } from 'lucide-react';
-import type { FieldDefinition, FieldType } from '../types';
+import type { DefaultValueKind, FieldDefinition, FieldType } from '../types';
+import { DEFAULT_RATING_MAX, IDENTIFIER_SCHEMES } from '../types';
 
 interface FieldDefinitionRowProps {
     field: FieldDefinition;
     onEdit: () => void;
+    onConvert?: () => void;
     onDelete: () => void;
     onMoveUp?: () => void;
     onMoveDown?: () => void;
@@ -27,27 +47,70 @@ interface FieldDefinitionRowProps {
 
 const FIELD_TYPE_ICONS: Record<FieldType, React.ReactNode> = {
     text: <Type className="h-4 w-4" />,
+    markdown: <FileText className="h-4 w-4" />,
     number: <Hash className="h-4 w-4" />,
     date: <Calendar className="h-4 w-4" />,
+    partialdate: <CalendarDays className="h-4 w-4" />,
+    datetime: <CalendarClock className="h-4 w-4" />,
+    duration: <Timer className="h-4 w-4" />,
     url: <Link className="h-4 w-4" />,
+    identifier: <Barcode className="h-4 w-4" />,
+    email: <Mail className="h-4 w-4" />,
+    phone: <Phone className="h-4 w-4" />,
+    color: <Palette className="h-4 w-4" />,
+    currency: <Banknote className="h-4 w-4" />,
+    json: <Braces className="h-4 w-4" />,
     boolean: <ToggleLeft className="h-4 w-4" />,
     select: <List className="h-4 w-4" />,
+    multiselect: <ListChecks className="h-4 w-4" />,
+    rating: <Star className="h-4 w-4" />,
+    file: <Paperclip className="h-4 w-4" />,
+    location: <MapPin className="h-4 w-4" />,
     relation: <Link2 className="h-4 w-4" />,
+    lookup: <ArrowRight className="h-4 w-4" />,
+    rollup: <Sigma className="h-4 w-4" />,
+    formula: <Calculator className="h-4 w-4" />,
 };
 
 const FIELD_TYPE_LABELS: Record<FieldType, string> = {
     text: 'Text',
+    markdown: 'Rich text',
     number: 'Number',
     date: 'Date',
+    partialdate: 'Partial date',
+    datetime: 'Date & time',
+    duration: 'Duration',
     url: 'URL',
+    identifier: 'Identifier',
+    email: 'Email',
+    phone: 'Phone',
+    color: 'Color',
+    currency: 'Currency',
+    json: 'JSON',
     boolean: 'Boolean',
     select: 'Select',
+    multiselect: 'Multi-select',
+    rating: 'Rating',
+    file: 'File',
+    location: 'Location',
     relation: 'Relation',
+    lookup: 'Lookup',
+    rollup: 'Rollup',
+    formula: 'Formula',
+};
+
+const DEFAULT_VALUE_LABELS: Record<DefaultValueKind, string> = {
+    value: 'value',
+    today: 'today',
+    now: 'now',
+    counter: 'counter',
+    'copy-field': 'copied',
 };
 
 export function FieldDefinitionRow({
     field,
     onEdit,
+    onConvert,
     onDelete,
     onMoveUp,
     onMoveDown,
@@ -93,7 +156,7 @@ export function FieldDefinitionRow({
                 </div>
                 <div className="mt-0.5 flex items-center gap-2 text-xs text-muted-foreground">
                     <span>{FIELD_TYPE_LABELS[field.field_type]}</span>
-                    {field.field_type === 'select' && field.options?.choices && (
+                    {(field.field_type === 'select' || field.field_type === 'multiselect') && field.options?.choices && (
                         <span>• {field.options.choices.length} options</span>
                     )}
                     {field.field_type === 'text' && field.options?.maxLength && (
@@ -106,6 +169,45 @@ export function FieldDefinitionRow({
                             {field.options.max !== undefined && `Max ${field.options.max}`}
                         </span>
                     )}
+                    {field.field_type === 'rating' && (
+                        <span>
+                            • 1 to {field.options?.max ?? DEFAULT_RATING_MAX}
+                            {field.options?.step === 0.5 && ', half steps'}
+                        </span>
+                    )}
+                    {field.field_type === 'identifier' && field.options?.scheme && (
+                        <span>
+                            • {IDENTIFIER_SCHEMES.find((s) => s.value === field.options?.scheme)?.label}
+                            {field.options.unique && ', unique'}
+                        </span>
+                    )}
+                    {field.field_type !== 'identifier' && field.options?.minLength !== undefined && (
+                        <span>• Min {field.options.minLength} chars</span>
+                    )}
+                    {field.field_type !== 'identifier' && field.options?.pattern && (
+                        <span className="truncate font-mono">• {field.options.pattern}</span>
+                    )}
+                    {field.field_type !== 'identifier' && field.options?.unique && (
+                        <span>• Unique</span>
+                    )}
+                    {field.field_type === 'phone' && field.options?.callingCode && (
+                        <span>• Default +{field.options.callingCode}</span>
+                    )}
+                    {field.field_type === 'currency' && field.options?.currency && (
+                        <span>• {field.options.currency}</span>
+                    )}
+                    {field.field_type === 'json' && field.options?.jsonSchema !== undefined && (
+                        <span>• With schema</span>
+                    )}
+                    {field.options?.defaultValue && (
+                        <span>• Default {DEFAULT_VALUE_LABELS[field.options.defaultValue.kind]}</span>
+                    )}
+                    {field.field_type === 'rollup' && field.options?.rollup && (
+                        <span>• {field.options.rollup}{field.options.backlinks && ' of backlinks'}</span>
+                    )}
+                    {field.field_type === 'formula' && field.options?.formula && (
+                        <span className="truncate font-mono">• {field.options.formula}</span>
+                    )}
                 </div>
             </div>
 
@@ -120,6 +222,17 @@ export function FieldDefinitionRow({
                 >
                     <Pencil className="h-4 w-4" />
                 </Button>
+                {onConvert && (
+                    <Button
+                        variant="ghost"
+                        size="icon"
+                        className="h-8 w-8"
+                        onClick={onConvert}
+                        title="Change type"
+                    >
+                        <Repeat className="h-4 w-4" />
+                    </Button>
+                )}
                 <Button
                     variant="ghost"
                     size="icon"
@@ -0,0 +1,320 @@
+// Field Rules Dialog - Conditional fields and cross-field validation rules of a vault
+
+import { useState, useEffect } from 'react';
+import {
+    Dialog,
+    DialogContent,
+    DialogHeader,
+    DialogTitle,
+    DialogDescription,
+} from '@/components/ui/dialog';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import { Loader2, Plus, X } from 'lucide-react';
+import { useFieldStore } from '../store';
+import type { EntryMetadataValue, FieldDefinition, FieldRule, FieldRuleKind, RuleOperator } from '../types';
+import { RULE_KINDS, RULE_OPERATORS, isDerivedField, isPresenceOperator } from '../types';
+
+interface FieldRulesDialogProps {
+    vaultId: number;
+    isOpen: boolean;
+    onClose: () => void;
+}
+
+// Comparisons order two values, so they cannot test presence
+function operatorsFor(kind: FieldRuleKind) {
+    return kind === 'compare'
+        ? RULE_OPERATORS.filter((o) => !isPresenceOperator(o.value))
+        : RULE_OPERATORS;
+}
+
+// Reads a condition value typed into the form as a value of the tested field
+function parseConditionValue(field: FieldDefinition | undefined, text: string): EntryMetadataValue | null {
+    if (text === '') return null;
+    switch (field?.field_type) {
+        case 'number':
+        case 'rating':
+        case 'duration':
+            return isNaN(Number(text)) ? text : Number(text);
+        case 'boolean':
+            return text === 'true';
+        default:
+            return text;
+    }
+}
+
+export function FieldRulesDialog({ vaultId, isOpen, onClose }: FieldRulesDialogProps) {
+    const { fields, rules, setRules } = useFieldStore();
+    const [draft, setDraft] = useState<FieldRule[]>([]);
+    const [isSaving, setIsSaving] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    // Start from the saved rules whenever the dialog opens
+    useEffect(() => {
+        if (isOpen) {
+            setDraft(rules);
+            setError(null);
+        }
+    }, [isOpen, rules]);
+
+    // Computed fields can be shown or hidden, but not tested or compared
+    const inputFields = fields.filter((f) => !isDerivedField(f));
+    const fieldById = (id: number) => fields.find((f) => f.id === id);
+
+    const update = (index: number, changes: Partial<FieldRule>) =>
+        setDraft((prev) => prev.map((r, i) => (i === index ? { ...r, ...changes } : r)));
+
+    const addRule = () => {
+        if (inputFields.length < 2) return;
+        setDraft((prev) => [
+            ...prev,
+            {
+                field_id: inputFields[0].id,
+                kind: 'visible-if',
+                other_field_id: inputFields[1].id,
+                operator: 'is-set',
+            },
+        ]);
+    };
+
+    const handleSave = async () => {
+        setIsSaving(true);
+        setError(null);
+
+        try {
+            await setRules(
+                vaultId,
+                draft.map((rule) => ({
+                    ...rule,
+                    value: rule.kind === 'compare' || isPresenceOperator(rule.operator) ? null : rule.value,
+                    message: rule.message?.trim() || null,
+                }))
+            );
+            onClose();
+        } catch (err) {
+            setError(String(err));
+        } finally {
+            setIsSaving(false);
+        }
+    };
+
+    const renderValueInput = (rule: FieldRule, index: number) => {
+        if (rule.kind === 'compare' || isPresenceOperator(rule.operator)) {
+            return null;
+        }
+        const other = fieldById(rule.other_field_id);
+        const value = rule.value === null || rule.value === undefined ? '' : String(rule.value);
+
+        if (other?.field_type === 'select' || other?.field_type === 'multiselect') {
+            return (
+                <Select value={value || undefined} onValueChange={(v) => update(index, { value: v })}>
+                    <SelectTrigger className="w-40">
+                        <SelectValue placeholder="Option..." />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {(other.options?.choices ?? []).map((choice) => (
+                            <SelectItem key={choice.id} value={choice.id}>
+                                {choice.label}
+                            </SelectItem>
+                        ))}
+                    </SelectContent>
+                </Select>
+            );
+        }
+        if (other?.field_type === 'boolean') {
+            return (
+                <Select value={value || undefined} onValueChange={(v) => update(index, { value: v === 'true' })}>
+                    <SelectTrigger className="w-40">
+                        <SelectValue placeholder="Value..." />
+                    </SelectTrigger>
+                    <SelectContent>
+                        <SelectItem value="true">Yes</SelectItem>
+                        <SelectItem value="false">No</SelectItem>
+                    </SelectContent>
+                </Select>
+            );
+        }
+        return (
+            <Input
+                value={value}
+                onChange={(e) => update(index, { value: parseConditionValue(other, e.target.value) })}
+                placeholder="Value"
+                className="w-40"
+            />
+        );
+    };
+
+    const fieldSelect = (value: number, options: FieldDefinition[], onChange: (id: number) => void) => (
+        <Select value={value.toString()} onValueChange={(v) => onChange(Number(v))}>
+            <SelectTrigger className="w-40">
+                <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+                {options.map((f) => (
+                    <SelectItem key={f.id} value={f.id.toString()}>
+                        {f.name}
+                    </SelectItem>
+                ))}
+            </SelectContent>
+        </Select>
+    );
+
+    return (
+        <Dialog open={isOpen} onOpenChange={(open) => !open && onClose()}>
+            <DialogContent className="max-w-3xl max-h-[85vh] overflow-hidden flex flex-col">
+                <DialogHeader>
+                    <DialogTitle>Field Rules</DialogTitle>
+                    <DialogDescription>
+                        Show or require fields depending on other fields, and check values against each other. Rules are checked whenever an entry is saved.
+                    </DialogDescription>
+                </DialogHeader>
+
+                <div className="flex-1 overflow-y-auto space-y-3 py-2">
+                    {error && (
+                        <div className="p-3 rounded-md bg-destructive/10 text-destructive text-sm">
+                            {error}
+                        </div>
+                    )}
+
+                    {draft.length === 0 && (
+                        <p className="text-sm text-muted-foreground text-center py-4">
+                            No rules defined yet.
+                        </p>
+                    )}
+
+                    {draft.map((rule, index) => (
+                        <div key={index} className="space-y-2 rounded-md border p-3">
+                            <div className="flex flex-wrap items-center gap-2">
+                                {fieldSelect(
+                                    rule.field_id,
+                                    rule.kind === 'visible-if' ? fields : inputFields,
+                                    (id) => update(index, { field_id: id })
+                                )}
+                                <Select
+                                    value={rule.kind}
+                                    onValueChange={(v) => {
+                                        const kind = v as FieldRuleKind;
+                                        update(index, {
+                                            kind,
+                                            operator: operatorsFor(kind).some((o) => o.value === rule.operator)
+                                                ? rule.operator
+                                                : 'equals',
+                                        });
+                                    }}
+                                >
+                                    <SelectTrigger className="w-32">
+                                        <SelectValue />
+                                    </SelectTrigger>
+                                    <SelectContent>
+                                        {RULE_KINDS.map((kind) => (
+                                            <SelectItem key={kind.value} value={kind.value}>
+                                                {kind.label}
+                                            </SelectItem>
+                                        ))}
+                                    </SelectContent>
+                                </Select>
+                                {rule.kind === 'compare' ? (
+                                    <>
+                                        <Select
+                                            value={rule.operator}
+                                            onValueChange={(v) => update(index, { operator: v as RuleOperator })}
+                                        >
+                                            <SelectTrigger className="w-40">
+                                                <SelectValue />
+                                            </SelectTrigger>
+                                            <SelectContent>
+                                                {operatorsFor(rule.kind).map((o) => (
+                                                    <SelectItem key={o.value} value={o.value}>
+                                                        {o.label}
+                                                    </SelectItem>
+                                                ))}
+                                            </SelectContent>
+                                        </Select>
+                                        {fieldSelect(rule.other_field_id, inputFields, (id) =>
+                                            update(index, { other_field_id: id })
+                                        )}
+                                    </>
+                                ) : (
+                                    <>
+                                        {fieldSelect(rule.other_field_id, inputFields, (id) =>
+                                            update(index, { other_field_id: id, value: null })
+                                        )}
+                                        <Select
+                                            value={rule.operator}
+                                            onValueChange={(v) => update(index, { operator: v as RuleOperator })}
+                                        >
+                                            <SelectTrigger className="w-40">
+                                                <SelectValue />
+                                            </SelectTrigger>
+                                            <SelectContent>
+                                                {operatorsFor(rule.kind).map((o) => (
+                                                    <SelectItem key={o.value} value={o.value}>
+                                                        {o.label}
+                                                    </SelectItem>
+                                                ))}
+                                            </SelectContent>
+                                        </Select>
+                                        {renderValueInput(rule, index)}
+                                    </>
+                                )}
+                                <Button
+                                    type="button"
+                                    variant="ghost"
+                                    size="icon"
+                                    onClick={() => setDraft((prev) => prev.filter((_, i) => i !== index))}
+                                    title="Remove rule"
+                                >
+                                    <X className="h-4 w-4" />
+                                </Button>
+                            </div>
+                            {rule.kind !== 'visible-if' && (
+                                <Input
+                                    value={rule.message ?? ''}
+                                    onChange={(e) => update(index, { message: e.target.value })}
+                                    placeholder="Custom error message (optional)"
+                                    maxLength={200}
+                                />
+                            )}
+                        </div>
+                    ))}
+
+                    <Button
+                        type="button"
+                        variant="outline"
+                        size="sm"
+                        onClick={addRule}
+                        disabled={inputFields.length < 2}
+                        className="w-full"
+                    >
+                        <Plus className="h-4 w-4 mr-2" />
+                        Add Rule
+                    </Button>
+                </div>
+
+                {/* Actions */}
+                <div className="flex justify-end gap-2 border-t pt-4">
+                    <Button type="button" variant="outline" onClick={onClose} disabled={isSaving}>
+                        Cancel
+                    </Button>
+                    <Button onClick={handleSave} disabled={isSaving}>
+                        {isSaving ? (
+                            <>
+                                <Loader2 className="h-4 w-4 animate-spin mr-2" />
+                                Saving...
+                            </>
+                        ) : (
+                            'Save Rules'
+                        )}
+                    </Button>
+                </div>
+            </DialogContent>
+        </Dialog>
+    );
+}
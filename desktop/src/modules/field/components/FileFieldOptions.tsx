@@ -0,0 +1,65 @@
+// File Field Options - Size limit and allowed extensions for file fields
+
+import { useState } from 'react';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import type { FieldOptions } from '../types';
+import { MAX_ATTACHMENT_SIZE } from '../types';
+
+interface FileFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+const MB = 1024 * 1024;
+
+// Splits "pdf, .EPUB srt" into ["pdf", "epub", "srt"]
+function parseExtensions(text: string): string[] | undefined {
+    const extensions = text
+        .split(/[\s,]+/)
+        .map((ext) => ext.replace(/^\./, '').toLowerCase())
+        .filter((ext) => ext.length > 0);
+    return extensions.length > 0 ? [...new Set(extensions)] : undefined;
+}
+
+export function FileFieldOptions({ options, onChange }: FileFieldOptionsProps) {
+    // Kept as typed so separators can be entered freely
+    const [extensionsText, setExtensionsText] = useState(
+        options.allowedExtensions?.join(', ') ?? ''
+    );
+
+    return (
+        <div className="grid grid-cols-2 gap-4">
+            <div className="space-y-2">
+                <Label htmlFor="maxFileSize">Max file size (MB)</Label>
+                <Input
+                    id="maxFileSize"
+                    type="number"
+                    min={1}
+                    max={MAX_ATTACHMENT_SIZE / MB}
+                    value={options.maxFileSize ? options.maxFileSize / MB : ''}
+                    onChange={(e) => {
+                        const megabytes = parseFloat(e.target.value);
+                        onChange({
+                            ...options,
+                            maxFileSize: megabytes > 0 ? Math.round(megabytes * MB) : undefined,
+                        });
+                    }}
+                    placeholder={String(MAX_ATTACHMENT_SIZE / MB)}
+                />
+            </div>
+            <div className="space-y-2">
+                <Label htmlFor="allowedExtensions">Allowed extensions</Label>
+                <Input
+                    id="allowedExtensions"
+                    value={extensionsText}
+                    onChange={(e) => {
+                        setExtensionsText(e.target.value);
+                        onChange({ ...options, allowedExtensions: parseExtensions(e.target.value) });
+                    }}
+                    placeholder="Any, or e.g. pdf, epub"
+                />
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,85 @@
+// Formula Field Options - Expression computing a formula field from other fields
+
+import { useEffect, useState } from 'react';
+import { Badge } from '@/components/ui/badge';
+import { Label } from '@/components/ui/label';
+import { Textarea } from '@/components/ui/textarea';
+import { fieldApi } from '../api';
+import type { FieldDefinition, FieldOptions } from '../types';
+
+interface FormulaFieldOptionsProps {
+    vaultId: number;
+    /** Field being edited, left out of the reference list */
+    fieldId?: number;
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+// Field types the backend cannot read in formulas
+const UNSUPPORTED_TYPES = ['multiselect', 'file', 'location', 'currency', 'json', 'relation', 'lookup'];
+
+export function FormulaFieldOptions({
+    vaultId,
+    fieldId,
+    options,
+    onChange,
+}: FormulaFieldOptionsProps) {
+    const [fields, setFields] = useState<FieldDefinition[]>([]);
+
+    useEffect(() => {
+        let cancelled = false;
+        fieldApi.list(vaultId)
+            .then((loaded) => !cancelled && setFields(loaded))
+            .catch((err) => console.error('Failed to load fields:', err));
+        return () => {
+            cancelled = true;
+        };
+    }, [vaultId]);
+
+    const formula = options.formula ?? '';
+    const references = fields.filter(
+        (f) => f.id !== fieldId && !UNSUPPORTED_TYPES.includes(f.field_type)
+    );
+
+    // Appends a reference, so fields can be picked instead of typing IDs
+    const insertReference = (field: FieldDefinition) => {
+        const separator = formula && !formula.endsWith(' ') ? ' ' : '';
+        onChange({ ...options, formula: `${formula}${separator}{${field.id}}` });
+    };
+
+    return (
+        <div className="space-y-2">
+            <Label htmlFor="formula">Formula *</Label>
+            <Textarea
+                id="formula"
+                value={formula}
+                onChange={(e) => onChange({ ...options, formula: e.target.value })}
+                placeholder={'e.g. round({3} / {4} * 100) & "%"'}
+                rows={3}
+                className="font-mono text-sm"
+            />
+            <p className="text-xs text-muted-foreground">
+                Use + - * / for numbers and dates, &amp; to join text, and functions like
+                if(), coalesce(), today(), years_between() or round().
+            </p>
+            {references.length > 0 && (
+                <div className="flex flex-wrap gap-1.5">
+                    {references.map((field) => (
+                        <Badge
+                            key={field.id}
+                            variant="outline"
+                            className="cursor-pointer select-none font-normal"
+                            onClick={() => insertReference(field)}
+                            title={`Insert {${field.id}}`}
+                        >
+                            {field.name}
+                            <span className="ml-1 font-mono text-muted-foreground">
+                                {`{${field.id}}`}
+                            </span>
+                        </Badge>
+                    ))}
+                </div>
+            )}
+        </div>
+    );
+}
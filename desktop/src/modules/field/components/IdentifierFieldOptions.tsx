@@ -0,0 +1,97 @@
+// Identifier Field Options - Scheme, custom pattern and uniqueness for identifier fields
+
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import type { FieldOptions, IdentifierScheme } from '../types';
+import { IDENTIFIER_SCHEMES } from '../types';
+
+interface IdentifierFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+    /** Stored values are normalized for the scheme, so it is fixed once created */
+    lockScheme?: boolean;
+}
+
+export function IdentifierFieldOptions({
+    options,
+    onChange,
+    lockScheme = false,
+}: IdentifierFieldOptionsProps) {
+    const scheme = options.scheme ?? 'isbn';
+
+    // Only custom identifiers take a pattern
+    const setScheme = (next: IdentifierScheme) => {
+        onChange({
+            ...options,
+            scheme: next,
+            pattern: next === 'custom' ? options.pattern : undefined,
+        });
+    };
+
+    return (
+        <div className="space-y-3">
+            <div className="space-y-2">
+                <Label>Scheme</Label>
+                <Select
+                    value={scheme}
+                    onValueChange={(value) => setScheme(value as IdentifierScheme)}
+                    disabled={lockScheme}
+                >
+                    <SelectTrigger>
+                        <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                        {IDENTIFIER_SCHEMES.map((s) => (
+                            <SelectItem key={s.value} value={s.value}>
+                                {s.label}
+                            </SelectItem>
+                        ))}
+                    </SelectContent>
+                </Select>
+                <p className="text-xs text-muted-foreground">
+                    {IDENTIFIER_SCHEMES.find((s) => s.value === scheme)?.description}
+                </p>
+            </div>
+
+            {scheme === 'custom' && (
+                <div className="space-y-2">
+                    <Label htmlFor="identifierPattern">Pattern *</Label>
+                    <Input
+                        id="identifierPattern"
+                        value={options.pattern ?? ''}
+                        onChange={(e) => onChange({ ...options, pattern: e.target.value || undefined })}
+                        placeholder="e.g. [A-Z]{3}-\d{4}"
+                        className="font-mono text-sm"
+                    />
+                    <p className="text-xs text-muted-foreground">
+                        Regular expression the whole value must match
+                    </p>
+                </div>
+            )}
+
+            <div className="flex items-center justify-between">
+                <div className="space-y-0.5">
+                    <Label htmlFor="identifierUnique">Unique</Label>
+                    <p className="text-xs text-muted-foreground">
+                        No two entries in this vault may share a value
+                    </p>
+                </div>
+                <Switch
+                    id="identifierUnique"
+                    checked={options.unique ?? false}
+                    onCheckedChange={(checked) =>
+                        onChange({ ...options, unique: checked || undefined })
+                    }
+                />
+            </div>
+        </div>
+    );
+}
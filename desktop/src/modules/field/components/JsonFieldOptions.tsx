@@ -0,0 +1,56 @@
+// JSON Field Options - Optional JSON Schema for JSON fields
+
+import { useState } from 'react';
+import { Label } from '@/components/ui/label';
+import { Textarea } from '@/components/ui/textarea';
+import type { FieldOptions } from '../types';
+
+interface JsonFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+export function JsonFieldOptions({ options, onChange }: JsonFieldOptionsProps) {
+    // Kept as typed so the schema can be edited while it is not yet valid JSON
+    const [schemaText, setSchemaText] = useState(
+        options.jsonSchema !== undefined ? JSON.stringify(options.jsonSchema, null, 2) : ''
+    );
+    const [parseError, setParseError] = useState<string | null>(null);
+
+    const handleChange = (text: string) => {
+        setSchemaText(text);
+        if (!text.trim()) {
+            setParseError(null);
+            onChange({ ...options, jsonSchema: undefined });
+            return;
+        }
+        try {
+            onChange({ ...options, jsonSchema: JSON.parse(text) });
+            setParseError(null);
+        } catch {
+            setParseError('Not valid JSON');
+        }
+    };
+
+    return (
+        <div className="space-y-2">
+            <Label htmlFor="jsonSchema">JSON Schema</Label>
+            <Textarea
+                id="jsonSchema"
+                value={schemaText}
+                onChange={(e) => handleChange(e.target.value)}
+                placeholder={'{\n  "type": "object",\n  "required": ["pages"]\n}'}
+                rows={6}
+                className="font-mono text-sm"
+            />
+            {parseError ? (
+                <p className="text-xs text-destructive">{parseError}</p>
+            ) : (
+                <p className="text-xs text-muted-foreground">
+                    Optional; values must satisfy it. Supports type, enum, const, properties,
+                    required, items, length and range bounds, pattern and allOf/anyOf/oneOf/not
+                </p>
+            )}
+        </div>
+    );
+}
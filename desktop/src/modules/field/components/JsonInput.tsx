@@ -0,0 +1,46 @@
+// JSON Input - Text editor for the value of a JSON field
+
+import { useState } from 'react';
+import { Textarea } from '@/components/ui/textarea';
+import { cn } from '@/lib/utils';
+import type { JsonValue } from '../types';
+
+interface JsonInputProps {
+    value: JsonValue;
+    onChange: (value: JsonValue) => void;
+    invalid?: boolean;
+}
+
+export function JsonInput({ value, onChange, invalid }: JsonInputProps) {
+    // Edited as text, so the value is only replaced once the text parses
+    const [text, setText] = useState(value === null ? '' : JSON.stringify(value, null, 2));
+    const [parseError, setParseError] = useState<string | null>(null);
+
+    const handleChange = (next: string) => {
+        setText(next);
+        if (!next.trim()) {
+            setParseError(null);
+            onChange(null);
+            return;
+        }
+        try {
+            onChange(JSON.parse(next));
+            setParseError(null);
+        } catch (err) {
+            setParseError(err instanceof SyntaxError ? err.message : 'Not valid JSON');
+        }
+    };
+
+    return (
+        <div className="space-y-1">
+            <Textarea
+                value={text}
+                onChange={(e) => handleChange(e.target.value)}
+                placeholder='e.g. {"pages": 412}'
+                rows={6}
+                className={cn('font-mono text-sm', (invalid || parseError) && 'border-destructive')}
+            />
+            {parseError && <p className="text-xs text-destructive">{parseError}</p>}
+        </div>
+    );
+}
@@ -0,0 +1,84 @@
+// Location Input - Place name and coordinate inputs for a location field
+
+import { useEffect, useState } from 'react';
+import { Input } from '@/components/ui/input';
+import type { Location } from '../types';
+
+interface LocationInputProps {
+    value: Location | null;
+    onChange: (value: Location | null) => void;
+    invalid?: boolean;
+}
+
+// Parses a coordinate, accepting a comma as the decimal separator
+function parseCoordinate(text: string): number | null {
+    const trimmed = text.trim().replace(',', '.');
+    if (!trimmed) return null;
+    const parsed = Number(trimmed);
+    return Number.isFinite(parsed) ? parsed : null;
+}
+
+export function LocationInput({ value, onChange, invalid }: LocationInputProps) {
+    // Coordinates are edited as text, so a half-typed pair isn't lost
+    const [lat, setLat] = useState(value ? String(value.lat) : '');
+    const [lng, setLng] = useState(value ? String(value.lng) : '');
+    const [name, setName] = useState(value?.name ?? '');
+
+    useEffect(() => {
+        if (!value) return;
+        setLat(String(value.lat));
+        setLng(String(value.lng));
+        setName(value.name ?? '');
+    }, [value?.lat, value?.lng, value?.name]);
+
+    const emit = (nextLat: string, nextLng: string, nextName: string) => {
+        const parsedLat = parseCoordinate(nextLat);
+        const parsedLng = parseCoordinate(nextLng);
+        if (parsedLat === null || parsedLng === null) {
+            // Only clear the value once both coordinates are gone
+            if (!nextLat.trim() && !nextLng.trim()) onChange(null);
+            return;
+        }
+        const location: Location = { lat: parsedLat, lng: parsedLng };
+        if (nextName.trim()) location.name = nextName;
+        onChange(location);
+    };
+
+    const border = invalid ? 'border-destructive' : '';
+
+    return (
+        <div className="space-y-2">
+            <Input
+                value={name}
+                onChange={(e) => {
+                    setName(e.target.value);
+                    emit(lat, lng, e.target.value);
+                }}
+                placeholder="Place name (optional)"
+                className={border}
+            />
+            <div className="grid grid-cols-2 gap-2">
+                <Input
+                    inputMode="decimal"
+                    value={lat}
+                    onChange={(e) => {
+                        setLat(e.target.value);
+                        emit(e.target.value, lng, name);
+                    }}
+                    placeholder="Latitude, e.g. 48.8566"
+                    className={border}
+                />
+                <Input
+                    inputMode="decimal"
+                    value={lng}
+                    onChange={(e) => {
+                        setLng(e.target.value);
+                        emit(lat, e.target.value, name);
+                    }}
+                    placeholder="Longitude, e.g. 2.3522"
+                    className={border}
+                />
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,75 @@
+// Markdown View - Rendered markdown with wiki-links to entries
+
+import { useEffect, useState } from 'react';
+import { Skeleton } from '@/components/ui/skeleton';
+import { cn } from '@/lib/utils';
+import { fieldApi } from '../api';
+
+interface MarkdownViewProps {
+    vaultId: number;
+    source: string;
+    onNavigateToEntry?: (entryId: number, vaultId: number) => void;
+    className?: string;
+}
+
+// The rendered HTML has no classes of its own, so elements are styled here
+const MARKDOWN_STYLES = cn(
+    'text-sm space-y-2 break-words',
+    '[&_h1]:text-lg [&_h1]:font-semibold [&_h2]:text-base [&_h2]:font-semibold',
+    '[&_h3]:font-semibold [&_h4]:font-medium [&_h5]:font-medium [&_h6]:font-medium',
+    '[&_ul]:list-disc [&_ol]:list-decimal [&_ul]:pl-5 [&_ol]:pl-5',
+    '[&_blockquote]:border-l-2 [&_blockquote]:pl-3 [&_blockquote]:text-muted-foreground',
+    '[&_code]:rounded [&_code]:bg-muted [&_code]:px-1 [&_code]:font-mono [&_code]:text-xs',
+    '[&_pre]:overflow-x-auto [&_pre]:rounded [&_pre]:bg-muted [&_pre]:p-2',
+    '[&_table]:text-xs [&_td]:border [&_th]:border [&_td]:px-2 [&_th]:px-2',
+    '[&_a]:text-primary [&_a:hover]:underline [&_a.wiki-link]:cursor-pointer',
+    '[&_.wiki-link-missing]:text-muted-foreground [&_.wiki-link-missing]:border-b [&_.wiki-link-missing]:border-dashed'
+);
+
+export function MarkdownView({ vaultId, source, onNavigateToEntry, className }: MarkdownViewProps) {
+    const [html, setHtml] = useState<string | null>(null);
+    const [failed, setFailed] = useState(false);
+
+    useEffect(() => {
+        let cancelled = false;
+        setFailed(false);
+        fieldApi
+            .renderMarkdown(vaultId, source)
+            .then((rendered) => {
+                if (!cancelled) setHtml(rendered.html);
+            })
+            .catch((err) => {
+                console.error('Failed to render markdown:', err);
+                if (!cancelled) setFailed(true);
+            });
+        return () => {
+            cancelled = true;
+        };
+    }, [vaultId, source]);
+
+    // Wiki-links carry the target entry instead of an href
+    const handleClick = (e: React.MouseEvent<HTMLDivElement>) => {
+        const link = (e.target as HTMLElement).closest<HTMLAnchorElement>('a[data-entry-id]');
+        if (!link) return;
+        e.preventDefault();
+        onNavigateToEntry?.(Number(link.dataset.entryId), vaultId);
+    };
+
+    // Fall back to the source text rather than showing nothing
+    if (failed) {
+        return <p className={cn('text-sm whitespace-pre-wrap', className)}>{source}</p>;
+    }
+
+    if (html === null) {
+        return <Skeleton className="h-4 w-full" />;
+    }
+
+    return (
+        <div
+            className={cn(MARKDOWN_STYLES, className)}
+            onClick={handleClick}
+            // Sanitized against an allow-list by the backend
+            dangerouslySetInnerHTML={{ __html: html }}
+        />
+    );
+}
@@ -0,0 +1,38 @@
+// Phone Field Options - Default calling code for phone fields
+
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import type { FieldOptions } from '../types';
+
+interface PhoneFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+export function PhoneFieldOptions({ options, onChange }: PhoneFieldOptionsProps) {
+    return (
+        <div className="space-y-2">
+            <Label htmlFor="callingCode">Default calling code</Label>
+            <div className="flex items-center gap-2">
+                <span className="text-sm text-muted-foreground">+</span>
+                <Input
+                    id="callingCode"
+                    inputMode="numeric"
+                    maxLength={3}
+                    value={options.callingCode ?? ''}
+                    onChange={(e) =>
+                        onChange({
+                            ...options,
+                            callingCode: e.target.value.replace(/\D/g, '') || undefined,
+                        })
+                    }
+                    placeholder="e.g. 44"
+                    className="w-24"
+                />
+            </div>
+            <p className="text-xs text-muted-foreground">
+                Used for numbers entered without one; all numbers are stored as +countrycode…
+            </p>
+        </div>
+    );
+}
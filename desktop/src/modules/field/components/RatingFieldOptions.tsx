@@ -0,0 +1,92 @@
+// Rating Field Options - Scale, step and icon for rating fields
+
+import { Label } from '@/components/ui/label';
+import { Switch } from '@/components/ui/switch';
+import {
+    Select,
+    SelectContent,
+    SelectItem,
+    SelectTrigger,
+    SelectValue,
+} from '@/components/ui/select';
+import type { FieldOptions, RatingIcon } from '../types';
+import { DEFAULT_RATING_MAX } from '../types';
+import { RatingScale } from './RatingScale';
+
+interface RatingFieldOptionsProps {
+    options: FieldOptions;
+    onChange: (options: FieldOptions) => void;
+}
+
+const SCALES = [3, 4, 5, 6, 7, 10];
+
+const ICONS: { value: RatingIcon; label: string }[] = [
+    { value: 'star', label: 'Stars' },
+    { value: 'heart', label: 'Hearts' },
+    { value: 'circle', label: 'Circles' },
+];
+
+export function RatingFieldOptions({ options, onChange }: RatingFieldOptionsProps) {
+    const max = options.max ?? DEFAULT_RATING_MAX;
+
+    return (
+        <div className="space-y-3">
+            <div className="grid grid-cols-2 gap-4">
+                <div className="space-y-2">
+                    <Label>Scale</Label>
+                    <Select
+                        value={String(max)}
+                        onValueChange={(value) => onChange({ ...options, max: parseInt(value, 10) })}
+                    >
+                        <SelectTrigger>
+                            <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                            {SCALES.map((scale) => (
+                                <SelectItem key={scale} value={String(scale)}>
+                                    1 to {scale}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+                <div className="space-y-2">
+                    <Label>Icon</Label>
+                    <Select
+                        value={options.icon ?? 'star'}
+                        onValueChange={(value) => onChange({ ...options, icon: value as RatingIcon })}
+                    >
+                        <SelectTrigger>
+                            <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                            {ICONS.map((icon) => (
+                                <SelectItem key={icon.value} value={icon.value}>
+                                    {icon.label}
+                                </SelectItem>
+                            ))}
+                        </SelectContent>
+                    </Select>
+                </div>
+            </div>
+
+            <div className="flex items-center justify-between">
+                <div className="space-y-0.5">
+                    <Label htmlFor="halfSteps">Half steps</Label>
+                    <p className="text-xs text-muted-foreground">
+                        Allow ratings like 3.5
+                    </p>
+                </div>
+                <Switch
+                    id="halfSteps"
+                    checked={options.step === 0.5}
+                    onCheckedChange={(checked) =>
+                        onChange({ ...options, step: checked ? 0.5 : undefined })
+                    }
+                />
+            </div>
+
+            <RatingScale value={max} options={{ ...options, max }} />
+        </div>
+    );
+}
@@ -0,0 +1,65 @@
+// Rating Scale - Row of icons showing, and optionally editing, a rating
+
+import { Circle, Heart, Star } from 'lucide-react';
+import { cn } from '@/lib/utils';
+import type { FieldOptions, RatingIcon } from '../types';
+import { DEFAULT_RATING_MAX } from '../types';
+
+interface RatingScaleProps {
+    value: number | null;
+    options: FieldOptions | null;
+    // Omit for a read-only scale
+    onChange?: (value: number | null) => void;
+    size?: 'sm' | 'md';
+}
+
+const ICONS: Record<RatingIcon, typeof Star> = {
+    star: Star,
+    heart: Heart,
+    circle: Circle,
+};
+
+export function RatingScale({ value, options, onChange, size = 'md' }: RatingScaleProps) {
+    const max = options?.max ?? DEFAULT_RATING_MAX;
+    const step = options?.step ?? 1;
+    const Icon = ICONS[options?.icon ?? 'star'];
+    const rating = value ?? 0;
+    const iconClass = size === 'sm' ? 'h-3.5 w-3.5' : 'h-5 w-5';
+
+    // Round the pointer position up to the next step, so half-steps land on
+    // the icon's left half
+    const handleClick = (index: number, e: React.MouseEvent<HTMLButtonElement>) => {
+        if (!onChange) return;
+        const rect = e.currentTarget.getBoundingClientRect();
+        const fraction = rect.width > 0 ? (e.clientX - rect.left) / rect.width : 1;
+        const next = Math.min(max, Math.max(step, Math.ceil((index + fraction) / step) * step));
+        onChange(next === value ? null : next);
+    };
+
+    return (
+        <div className="flex items-center gap-0.5" title={`${rating}/${max}`}>
+            {Array.from({ length: max }, (_, index) => {
+                const fill = Math.max(0, Math.min(1, rating - index));
+                return (
+                    <button
+                        key={index}
+                        type="button"
+                        disabled={!onChange}
+                        onClick={(e) => handleClick(index, e)}
+                        className={cn('relative', onChange ? 'cursor-pointer' : 'cursor-default')}
+                    >
+                        <Icon className={cn(iconClass, 'text-muted-foreground/40')} />
+                        {fill > 0 && (
+                            <span
+                                className="absolute inset-0 overflow-hidden"
+                                style={{ width: `${fill * 100}%` }}
+                            >
+                                <Icon className={cn(iconClass, 'fill-amber-400 text-amber-400')} />
+                            </span>
+                        )}
+                    </button>
+                );
+            })}
+        </div>
+    );
+}
@@ -17,6 +17,9 @@ interface RelationFieldEditorProps {
     onChange: (value: RelationValue | null) => void;
     disabled?: boolean;
     error?: string;
+    // Entry IDs to hide from the picker (e.g. already selected)
+    excludeIds?: number[];
+    placeholder?: string;
 }
 
 export function RelationFieldEditor({
@@ -25,6 +28,8 @@ export function RelationFieldEditor({
     onChange,
     disabled = false,
     error,
+    excludeIds,
+    placeholder = 'Select entry...',
 }: RelationFieldEditorProps) {
     const [isOpen, setIsOpen] = useState(false);
     const [query, setQuery] = useState('');
@@ -64,7 +69,8 @@ export function RelationFieldEditor({
                     const items = await relationApi.searchEntriesForPicker(
                         targetVaultId,
                         searchQuery,
-                        20
+                        20,
+                        excludeIds
                     );
                     setResults(items);
                 } catch (err) {
@@ -74,7 +80,7 @@ export function RelationFieldEditor({
                     setIsSearching(false);
                 }
             },
-            [targetVaultId]
+            [targetVaultId, excludeIds]
         ),
         300
     );
@@ -138,7 +144,7 @@ export function RelationFieldEditor({
         return (
             <span className="text-muted-foreground flex items-center gap-2">
                 <Link2 className="h-4 w-4" />
-                Select entry...
+                {placeholder}
             </span>
         );
     };
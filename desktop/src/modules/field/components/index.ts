@@ -1,10 +1,29 @@
 // Field components exports
+export * from './AttachmentList';
+export * from './ChoiceBadge';
+export * from './ChoiceListEditor';
+export * from './ConstraintFieldOptions';
+export * from './ConvertFieldDialog';
 export * from './CreateFieldDialog';
+export * from './CurrencyFieldOptions';
 export * from './CustomFieldInput';
 export * from './CustomFieldRenderer';
 export * from './CustomFieldsSection';
+export * from './DefaultValueOptions';
+export * from './DerivedFieldOptions';
 export * from './EditFieldDialog';
 export * from './FieldDefinitionManager';
 export * from './FieldDefinitionRow';
+export * from './FieldRulesDialog';
+export * from './FileFieldOptions';
+export * from './FormulaFieldOptions';
+export * from './IdentifierFieldOptions';
+export * from './JsonFieldOptions';
+export * from './JsonInput';
+export * from './LocationInput';
+export * from './MarkdownView';
+export * from './PhoneFieldOptions';
+export * from './RatingFieldOptions';
+export * from './RatingScale';
 export * from './RelationFieldEditor';
 export * from './RelationFieldDisplay';
@@ -1,12 +1,20 @@
 // Field definition store using Zustand
 
 import { create } from 'zustand';
-import type { FieldDefinition, CreateFieldParams, UpdateFieldParams } from './types';
+import type {
+    FieldDefinition,
+    CreateFieldParams,
+    UpdateFieldParams,
+    ConvertFieldParams,
+    FieldRule,
+} from './types';
 import { fieldApi } from './api';
 
 interface FieldState {
     // State
     fields: FieldDefinition[];
+    // Validation rules between the fields
+    rules: FieldRule[];
     isLoading: boolean;
     error: string | null;
 
@@ -14,8 +22,10 @@ interface FieldState {
     fetchFields: (vaultId: number) => Promise<void>;
     createField: (params: CreateFieldParams) => Promise<FieldDefinition>;
     updateField: (id: number, params: UpdateFieldParams) => Promise<FieldDefinition>;
+    convertField: (id: number, params: ConvertFieldParams) => Promise<FieldDefinition>;
     deleteField: (id: number) => Promise<void>;
     reorderFields: (vaultId: number, ids: number[]) => Promise<void>;
+    setRules: (vaultId: number, rules: FieldRule[]) => Promise<FieldRule[]>;
     resetFields: () => void;
     clearError: () => void;
 }
@@ -23,6 +33,7 @@ interface FieldState {
 export const useFieldStore = create<FieldState>((set) => ({
     // Initial state
     fields: [],
+    rules: [],
     isLoading: false,
     error: null,
 
@@ -31,8 +42,11 @@ export const useFieldStore = create<FieldState>((set) => ({
         set({ isLoading: true, error: null });
 
         try {
-            const fields = await fieldApi.list(vaultId);
-            set({ fields, isLoading: false });
+            const [fields, rules] = await Promise.all([
+                fieldApi.list(vaultId),
+                fieldApi.getRules(vaultId),
+            ]);
+            set({ fields, rules, isLoading: false });
         } catch (err) {
             set({ error: String(err), isLoading: false });
             throw err;
@@ -67,12 +81,28 @@ export const useFieldStore = create<FieldState>((set) => ({
         }
     },
 
+    convertField: async (id, params) => {
+        set({ error: null });
+        try {
+            const converted = await fieldApi.convert(id, params);
+            set((state) => ({
+                fields: state.fields.map((f) => (f.id === id ? converted : f)),
+            }));
+            return converted;
+        } catch (err) {
+            set({ error: String(err) });
+            throw err;
+        }
+    },
+
     deleteField: async (id) => {
         set({ error: null });
         try {
             await fieldApi.delete(id);
+            // Rules involving the field are deleted with it
             set((state) => ({
                 fields: state.fields.filter((f) => f.id !== id),
+                rules: state.rules.filter((r) => r.field_id !== id && r.other_field_id !== id),
             }));
         } catch (err) {
             set({ error: String(err) });
@@ -97,9 +127,22 @@ export const useFieldStore = create<FieldState>((set) => ({
         }
     },
 
+    setRules: async (vaultId, rules) => {
+        set({ error: null });
+        try {
+            const saved = await fieldApi.setRules(vaultId, rules);
+            set({ rules: saved });
+            return saved;
+        } catch (err) {
+            set({ error: String(err) });
+            throw err;
+        }
+    },
+
     resetFields: () => {
         set({
             fields: [],
+            rules: [],
             isLoading: false,
             error: null,
         });
@@ -1,17 +1,212 @@
 // Field definition types matching Rust models
 
-export type FieldType = 'text' | 'number' | 'date' | 'url' | 'boolean' | 'select' | 'relation';
+import type { RelationDeleteAction } from '@/modules/relation';
+
+export type FieldType =
+    | 'text'
+    | 'markdown'
+    | 'number'
+    | 'date'
+    | 'partialdate'
+    | 'datetime'
+    | 'duration'
+    | 'url'
+    | 'identifier'
+    | 'email'
+    | 'phone'
+    | 'color'
+    | 'currency'
+    | 'json'
+    | 'boolean'
+    | 'select'
+    | 'multiselect'
+    | 'rating'
+    | 'file'
+    | 'location'
+    | 'relation'
+    | 'lookup'
+    | 'rollup'
+    | 'formula';
+
+// Icon used to draw a rating scale
+export type RatingIcon = 'star' | 'heart' | 'circle';
+
+// Scale used by rating fields without options
+export const DEFAULT_RATING_MAX = 5;
+
+// Kind of identifier held by identifier fields
+export type IdentifierScheme = 'isbn' | 'ean' | 'imdb' | 'custom';
+
+export const IDENTIFIER_SCHEMES: { value: IdentifierScheme; label: string; description: string }[] = [
+    { value: 'isbn', label: 'ISBN', description: 'ISBN-10 or ISBN-13, checked and stored as ISBN-13' },
+    { value: 'ean', label: 'EAN / UPC', description: 'EAN-8, EAN-13 or UPC-A barcode with check digit' },
+    { value: 'imdb', label: 'IMDb ID', description: 'Title ID such as tt0111161, or a link to the title' },
+    { value: 'custom', label: 'Custom', description: 'Any code matching a regular expression' },
+];
+
+// Aggregate applied by rollup fields
+export type RollupFunction = 'count' | 'sum' | 'average' | 'min' | 'max' | 'distinct-list';
+
+// Choice of a select or multiselect field. Entries store the ID, so the
+// label and color can change; the ID is empty until the field is saved.
+export interface Choice {
+    id: string;
+    label: string;
+    color?: string | null;
+}
 
 export interface FieldOptions {
+    minLength?: number;
     maxLength?: number;
     min?: number;
     max?: number;
-    choices?: string[];
+    // In display order
+    choices?: Choice[];
+    // Rating field options (max is the top of the scale)
+    step?: number;
+    icon?: RatingIcon;
+    // File field options (size in bytes, extensions without the dot)
+    maxFileSize?: number;
+    allowedExtensions?: string[];
     // Relation field options
     targetVaultId?: number;
     displayFields?: string[];
+    multiple?: boolean;
+    minItems?: number;
+    maxItems?: number;
+    onDelete?: RelationDeleteAction;
+    // Lookup and rollup field options
+    viaFieldId?: number;
+    backlinks?: boolean;
+    sourceFieldId?: number;
+    rollup?: RollupFunction;
+    // Formula field expression, reading other fields as {id}
+    formula?: string;
+    // Identifier field scheme
+    scheme?: IdentifierScheme;
+    // Regular expression the whole value must match: the format of custom
+    // identifiers, or a constraint on text, URL, email and phone values
+    pattern?: string;
+    // Message shown instead of the pattern when a value does not match
+    patternMessage?: string;
+    // No two entries of the vault may share a value
+    unique?: boolean;
+    // Phone field default calling code, without '+' (e.g. "44")
+    callingCode?: string;
+    // Currency field fixed ISO 4217 code
+    currency?: string;
+    // JSON field schema values must satisfy
+    jsonSchema?: JsonValue;
+    // Value filled in on new entries that leave the field empty
+    defaultValue?: DefaultValue;
+}
+
+// Default value of a field: a fixed value, the current date or time, the
+// next number of a counter, or the value of another field of the entry
+export type DefaultValue =
+    | { kind: 'value'; value: JsonValue }
+    | { kind: 'today' }
+    | { kind: 'now' }
+    | { kind: 'counter'; start?: number }
+    | { kind: 'copy-field'; fieldId: number };
+
+export type DefaultValueKind = DefaultValue['kind'];
+
+// Default value kinds a field type supports; computed, file and relation
+// fields have no default
+export function defaultValueKinds(fieldType: FieldType): DefaultValueKind[] {
+    switch (fieldType) {
+        case 'lookup':
+        case 'rollup':
+        case 'formula':
+        case 'file':
+        case 'relation':
+            return [];
+        case 'location':
+            return ['copy-field'];
+        case 'date':
+        case 'partialdate':
+            return ['value', 'today', 'copy-field'];
+        case 'datetime':
+            return ['value', 'today', 'now', 'copy-field'];
+        case 'number':
+            return ['value', 'counter', 'copy-field'];
+        default:
+            return ['value', 'copy-field'];
+    }
+}
+
+// Whether a field type can be unique: single values that compare by
+// equality, unlike lists, free-form documents and yes/no answers
+export function canBeUnique(fieldType: FieldType): boolean {
+    switch (fieldType) {
+        case 'lookup':
+        case 'rollup':
+        case 'formula':
+        case 'markdown':
+        case 'boolean':
+        case 'multiselect':
+        case 'json':
+        case 'file':
+        case 'location':
+        case 'relation':
+            return false;
+        default:
+            return true;
+    }
+}
+
+// Whether values of a field type can be checked against a pattern
+export function takesPattern(fieldType: FieldType): boolean {
+    return ['text', 'url', 'email', 'phone'].includes(fieldType);
+}
+
+// Entry named by a [[Title]] wiki-link in markdown
+export interface WikiLink {
+    title: string;
+    entry_id: number | null;
+}
+
+// Markdown rendered to sanitized HTML by the backend
+export interface RenderedMarkdown {
+    html: string;
+    links: WikiLink[];
+}
+
+// Value stored in metadata for each file attached to a file field
+export interface Attachment {
+    sha256: string;
+    path: string;
+    filename: string;
+    mime_type: string;
+    size: number;
 }
 
+// Value stored in metadata for location fields (degrees)
+export interface Location {
+    lat: number;
+    lng: number;
+    name?: string;
+}
+
+// Value stored in metadata for currency fields (exact decimal amount)
+export interface Money {
+    amount: string;
+    currency: string;
+}
+
+// Any value a JSON field can hold
+export type JsonValue =
+    | string
+    | number
+    | boolean
+    | null
+    | JsonValue[]
+    | { [key: string]: JsonValue };
+
+// Largest file a file field accepts, whatever its options say
+export const MAX_ATTACHMENT_SIZE = 100 * 1024 * 1024;
+
 // Value stored in metadata for relation fields
 export interface RelationValue {
     entry_id: number;
@@ -36,24 +231,195 @@ export interface CreateFieldParams {
     field_type: FieldType;
     options?: FieldOptions | null;
     required?: boolean;
+    // Whether existing entries get the field's default value
+    backfill?: boolean;
 }
 
 export interface UpdateFieldParams {
     name?: string | null;
     options?: FieldOptions | null;
     required?: boolean | null;
+    // Whether existing entries without a value get the field's default value
+    backfill?: boolean;
+    // Removed choice ID to the remaining choice ID its entries move to;
+    // entries of other removed choices lose them
+    merge_choices?: Record<string, string>;
+}
+
+export interface ConvertFieldParams {
+    field_type: FieldType;
+    options?: FieldOptions | null;
+    discard_failures?: boolean;
+}
+
+// Entry value that cannot be converted to a field's new type
+export interface ConversionFailure {
+    entry_id: number;
+    title: string;
+    value: unknown;
+    error: string;
+}
+
+// Outcome of converting a field's values to another type
+export interface ConversionPreview {
+    field_id: number;
+    from: FieldType;
+    to: FieldType;
+    converted: number;
+    emptied: number;
+    failed: number;
+    choices: Choice[] | null;
+    failures: ConversionFailure[];
 }
 
+// Vault validation rule between two fields. Required-if and visible-if
+// rules test other_field_id against value; compare rules order the field's
+// value against other_field_id's.
+export type FieldRuleKind = 'required-if' | 'visible-if' | 'compare';
+
+export type RuleOperator =
+    | 'equals'
+    | 'not-equals'
+    | 'is-set'
+    | 'is-not-set'
+    | 'less-than'
+    | 'less-or-equal'
+    | 'greater-than'
+    | 'greater-or-equal';
+
+export interface FieldRule {
+    field_id: number;
+    kind: FieldRuleKind;
+    other_field_id: number;
+    operator: RuleOperator;
+    // Choice ID for select and multiselect fields; none for is-set tests
+    // and comparisons
+    value?: EntryMetadataValue | null;
+    // Shown instead of the generated error message
+    message?: string | null;
+}
+
+export const RULE_KINDS: { value: FieldRuleKind; label: string }[] = [
+    { value: 'required-if', label: 'Required if' },
+    { value: 'visible-if', label: 'Visible if' },
+    { value: 'compare', label: 'Must be' },
+];
+
+export const RULE_OPERATORS: { value: RuleOperator; label: string }[] = [
+    { value: 'equals', label: 'equals' },
+    { value: 'not-equals', label: 'does not equal' },
+    { value: 'is-set', label: 'is set' },
+    { value: 'is-not-set', label: 'is empty' },
+    { value: 'less-than', label: 'less than' },
+    { value: 'less-or-equal', label: 'at most' },
+    { value: 'greater-than', label: 'greater than' },
+    { value: 'greater-or-equal', label: 'at least' },
+];
+
 // Entry metadata type - dynamic based on field definitions
 // Key is field ID (string), value depends on field type
-// For relation fields, value is a RelationValue object
-export type EntryMetadataValue = string | number | boolean | RelationValue | null;
+// For relation fields, value is a RelationValue object,
+// or an ordered RelationValue array when the field allows multiple.
+// File fields hold an ordered Attachment array, location fields a Location,
+// currency fields a Money and JSON fields any JSON value.
+export type EntryMetadataValue =
+    | string
+    | number
+    | boolean
+    | string[]
+    | RelationValue
+    | RelationValue[]
+    | Attachment[]
+    | Location
+    | Money
+    | JsonValue;
 export type EntryMetadata = Record<string, EntryMetadataValue>;
 
+// Lookup, rollup and formula values computed by the backend, keyed like metadata.
+// Lists hold one value per related entry.
+export type ComputedValue = EntryMetadataValue | EntryMetadataValue[];
+export type ComputedValues = Record<string, ComputedValue>;
+
+/**
+ * Whether a field's value is computed, through relations or from a formula,
+ * instead of stored.
+ */
+export function isDerivedField(field: FieldDefinition): boolean {
+    return (
+        field.field_type === 'lookup' ||
+        field.field_type === 'rollup' ||
+        field.field_type === 'formula'
+    );
+}
+
+/**
+ * Whether a field holds numbers that can be summed, averaged and
+ * filtered by range. Durations are stored as seconds.
+ */
+export function isNumericField(field: FieldDefinition): boolean {
+    return (
+        field.field_type === 'number' ||
+        field.field_type === 'rating' ||
+        field.field_type === 'duration'
+    );
+}
+
+/**
+ * Formats a duration in seconds as units, e.g. "2h 28m".
+ */
+export function formatDuration(seconds: number): string {
+    const units: [string, number][] = [
+        ['d', 86400],
+        ['h', 3600],
+        ['m', 60],
+        ['s', 1],
+    ];
+    let rest = Math.round(seconds);
+    const parts: string[] = [];
+    for (const [unit, size] of units) {
+        if (rest >= size) {
+            parts.push(`${Math.floor(rest / size)}${unit}`);
+            rest %= size;
+        }
+    }
+    return parts.length > 0 ? parts.join(' ') : '0s';
+}
+
+/**
+ * Formats a file size in bytes, e.g. "1.5 MB".
+ */
+export function formatFileSize(bytes: number): string {
+    const units = ['bytes', 'KB', 'MB', 'GB'];
+    let size = bytes;
+    let unit = 0;
+    while (size >= 1024 && unit < units.length - 1) {
+        size /= 1024;
+        unit++;
+    }
+    return unit === 0 ? `${size} ${units[0]}` : `${size.toFixed(size < 10 ? 1 : 0)} ${units[unit]}`;
+}
+
+/**
+ * Formats a normalized partial date (YYYY, YYYY-MM or YYYY-MM-DD)
+ * for display, showing only the parts that are known.
+ */
+export function formatPartialDate(value: string): string {
+    const [year, month, day] = value.split('-').map(Number);
+    if (!year) return value;
+    // Noon UTC keeps the day stable in every timezone
+    const date = new Date(Date.UTC(year, (month ?? 1) - 1, day ?? 1, 12));
+    return date.toLocaleDateString(undefined, {
+        year: 'numeric',
+        month: month ? 'short' : undefined,
+        day: day ? 'numeric' : undefined,
+        timeZone: 'UTC',
+    });
+}
+
 /**
  * Type guard to check if a value is a RelationValue
  */
-export function isRelationValue(value: EntryMetadataValue): value is RelationValue {
+export function isRelationValue(value: ComputedValue): value is RelationValue {
     return (
         value !== null &&
         typeof value === 'object' &&
@@ -62,6 +428,209 @@ export function isRelationValue(value: EntryMetadataValue): value is RelationVal
     );
 }
 
+/**
+ * Normalizes a relation field value to a list of references.
+ * Single values become a one-item list; anything else becomes empty.
+ */
+export function toRelationValues(value: ComputedValue): RelationValue[] {
+    if (Array.isArray(value)) {
+        return value.filter(isRelationValue);
+    }
+    return isRelationValue(value) ? [value] : [];
+}
+
+/**
+ * Type guard to check if a value is an Attachment
+ */
+export function isAttachment(value: unknown): value is Attachment {
+    return (
+        value !== null &&
+        typeof value === 'object' &&
+        'sha256' in value &&
+        'path' in value &&
+        'filename' in value
+    );
+}
+
+/**
+ * Normalizes a file field value to its list of attachments.
+ * Anything that is not an attachment list becomes empty.
+ */
+export function toAttachments(value: ComputedValue): Attachment[] {
+    return Array.isArray(value) ? value.filter(isAttachment) : [];
+}
+
+/**
+ * Type guard to check if a value is a Location
+ */
+export function isLocation(value: unknown): value is Location {
+    return (
+        value !== null &&
+        typeof value === 'object' &&
+        typeof (value as Location).lat === 'number' &&
+        typeof (value as Location).lng === 'number'
+    );
+}
+
+/**
+ * Type guard to check if a value is a Money amount
+ */
+export function isMoney(value: unknown): value is Money {
+    return (
+        value !== null &&
+        typeof value === 'object' &&
+        typeof (value as Money).amount === 'string' &&
+        typeof (value as Money).currency === 'string'
+    );
+}
+
+/**
+ * Formats an amount in its currency, e.g. "€12.50", without rounding
+ * through floating point for display of the stored decimals.
+ */
+export function formatMoney(money: Money): string {
+    const decimals = money.amount.split('.')[1]?.length ?? 0;
+    try {
+        return new Intl.NumberFormat(undefined, {
+            style: 'currency',
+            currency: money.currency,
+            minimumFractionDigits: decimals,
+            maximumFractionDigits: decimals,
+        }).format(Number(money.amount));
+    } catch {
+        return `${money.amount} ${money.currency}`;
+    }
+}
+
+/**
+ * Link to a public page for an identifier, where its scheme has one.
+ */
+export function identifierUrl(scheme: IdentifierScheme | undefined, value: string): string | null {
+    switch (scheme) {
+        case 'isbn':
+            return `https://openlibrary.org/isbn/${encodeURIComponent(value)}`;
+        case 'imdb':
+            return `https://www.imdb.com/title/${encodeURIComponent(value)}/`;
+        default:
+            return null;
+    }
+}
+
+/**
+ * Formats coordinates as e.g. "48.8566° N, 2.3522° E".
+ */
+export function formatCoordinates(location: Location): string {
+    const lat = `${Math.abs(location.lat).toFixed(4)}° ${location.lat >= 0 ? 'N' : 'S'}`;
+    const lng = `${Math.abs(location.lng).toFixed(4)}° ${location.lng >= 0 ? 'E' : 'W'}`;
+    return `${lat}, ${lng}`;
+}
+
+/**
+ * Normalizes a select or multiselect value to a list of choices.
+ * A single choice becomes a one-item list; anything else becomes empty.
+ */
+export function toChoiceValues(value: ComputedValue): string[] {
+    if (Array.isArray(value)) {
+        return value.filter((v): v is string => typeof v === 'string');
+    }
+    return typeof value === 'string' ? [value] : [];
+}
+
+/**
+ * Finds the choice a stored select or multiselect value refers to.
+ */
+export function findChoice(options: FieldOptions | null | undefined, id: string): Choice | undefined {
+    return options?.choices?.find((c) => c.id === id);
+}
+
+/**
+ * Label of a stored choice ID; IDs that are no longer a choice show as is.
+ */
+export function choiceLabel(options: FieldOptions | null | undefined, id: string): string {
+    return findChoice(options, id)?.label ?? id;
+}
+
+/**
+ * Whether a rule operator tests presence rather than a value.
+ */
+export function isPresenceOperator(operator: RuleOperator): boolean {
+    return operator === 'is-set' || operator === 'is-not-set';
+}
+
+function isEmptyValue(value: EntryMetadataValue | null | undefined): boolean {
+    return (
+        value === null ||
+        value === undefined ||
+        value === '' ||
+        (Array.isArray(value) && value.length === 0)
+    );
+}
+
+// Orders two values of a field the way the backend does: numbers by
+// value, dates as text at the precision of the coarser one
+function compareRuleValues(a: EntryMetadataValue, b: EntryMetadataValue): number | null {
+    if (typeof a === 'string' && typeof b === 'string' && isNaN(Number(a))) {
+        const length = Math.min(a.length, b.length);
+        return a.slice(0, length).localeCompare(b.slice(0, length));
+    }
+    const [x, y] = [Number(a), Number(b)];
+    return isNaN(x) || isNaN(y) ? null : x - y;
+}
+
+function conditionHolds(rule: FieldRule, value: EntryMetadataValue | null | undefined): boolean {
+    const empty = isEmptyValue(value);
+    if (rule.operator === 'is-set') return !empty;
+    if (rule.operator === 'is-not-set') return empty;
+    if (empty || rule.value === null || rule.value === undefined) {
+        return rule.operator === 'not-equals';
+    }
+    const expected = rule.value;
+    const equals = Array.isArray(value)
+        ? value.some((v) => v === expected)
+        : value === expected || compareRuleValues(value!, expected) === 0;
+    switch (rule.operator) {
+        case 'equals':
+            return equals;
+        case 'not-equals':
+            return !equals;
+        default: {
+            const order = compareRuleValues(value!, expected);
+            if (order === null) return false;
+            if (rule.operator === 'less-than') return order < 0;
+            if (rule.operator === 'less-or-equal') return order <= 0;
+            if (rule.operator === 'greater-than') return order > 0;
+            return order >= 0;
+        }
+    }
+}
+
+/**
+ * Evaluates the required-if and visible-if rules of a vault against an
+ * entry's values: the fields to hide, and those required by a rule.
+ * Comparisons are left to the backend, which reports them per field.
+ */
+export function evaluateFieldRules(
+    rules: FieldRule[],
+    metadata: EntryMetadata
+): { hidden: Set<number>; required: Set<number> } {
+    const hidden = new Set<number>();
+    const required = new Set<number>();
+    const holds = (rule: FieldRule) =>
+        conditionHolds(rule, metadata[rule.other_field_id.toString()]);
+
+    for (const rule of rules) {
+        if (rule.kind === 'visible-if' && !holds(rule)) {
+            hidden.add(rule.field_id);
+        }
+    }
+    for (const rule of rules) {
+        if (rule.kind === 'required-if' && !hidden.has(rule.field_id) && holds(rule)) {
+            required.add(rule.field_id);
+        }
+    }
+    return { hidden, required };
+}
+
 /**
  * Creates a Map of field ID to field definition for O(1) lookup.
  * Use this when rendering entries to efficiently map metadata keys to field names.
@@ -95,10 +664,13 @@ export function getFieldDisplayValue(
 
     // For relation fields, return a placeholder - use RelationFieldDisplay component
     if (field.field_type === 'relation') {
-        if (isRelationValue(value)) {
+        const relations = toRelationValues(value);
+        if (relations.length > 0) {
             return {
                 name: field.name,
-                value: `[Relation: Entry ${value.entry_id}]`,
+                value: relations
+                    .map((relation) => `[Relation: Entry ${relation.entry_id}]`)
+                    .join(', '),
                 isValid: true,
             };
         }
@@ -130,15 +702,50 @@ function formatFieldValue(
 ): string {
     if (value === null || value === undefined) return '';
 
+    if (field.field_type === 'select' || field.field_type === 'multiselect') {
+        return toChoiceValues(value)
+            .map((id) => choiceLabel(field.options, id))
+            .join(', ');
+    }
+
+    if (field.field_type === 'file') {
+        return toAttachments(value)
+            .map((attachment) => attachment.filename)
+            .join(', ');
+    }
+
+    if (field.field_type === 'location') {
+        if (!isLocation(value)) return '';
+        return value.name
+            ? `${value.name} (${formatCoordinates(value)})`
+            : formatCoordinates(value);
+    }
+
+    if (field.field_type === 'currency') {
+        return isMoney(value) ? formatMoney(value) : '';
+    }
+
+    if (field.field_type === 'json') {
+        return JSON.stringify(value);
+    }
+
     // Relations are handled by RelationFieldDisplay
-    if (isRelationValue(value)) return '';
+    if (isRelationValue(value) || Array.isArray(value)) return '';
 
     switch (field.field_type) {
         case 'boolean':
             return value ? 'Yes' : 'No';
+        case 'rating':
+            return `${value}/${field.options?.max ?? DEFAULT_RATING_MAX}`;
         case 'date':
             // Format date for display
             return String(value);
+        case 'partialdate':
+            return formatPartialDate(String(value));
+        case 'datetime':
+            return new Date(String(value)).toLocaleString();
+        case 'duration':
+            return typeof value === 'number' ? formatDuration(value) : String(value);
         case 'url':
             return String(value);
         default:
@@ -154,18 +761,78 @@ function validateFieldValue(
     value: EntryMetadataValue,
     field: FieldDefinition
 ): { isValid: boolean; warning?: string } {
-    if (value === null || value === undefined) {
+    if (value === null || value === undefined || (Array.isArray(value) && value.length === 0)) {
         if (field.required) {
             return { isValid: false, warning: 'Required field is empty' };
         }
         return { isValid: true };
     }
 
+    // Attachments are checked against the limits they were added under
+    if (field.field_type === 'file') {
+        const attachments = toAttachments(value);
+        if (!Array.isArray(value) || attachments.length !== value.length) {
+            return { isValid: false, warning: 'Invalid attachment value' };
+        }
+        const maxFileSize = Math.min(field.options?.maxFileSize ?? MAX_ATTACHMENT_SIZE, MAX_ATTACHMENT_SIZE);
+        const tooLarge = attachments.find((attachment) => attachment.size > maxFileSize);
+        if (tooLarge) {
+            return {
+                isValid: false,
+                warning: `'${tooLarge.filename}' exceeds ${formatFileSize(maxFileSize)}`,
+            };
+        }
+        return { isValid: true };
+    }
+
+    if (field.field_type === 'location') {
+        if (!isLocation(value)) {
+            return { isValid: false, warning: 'Invalid location value' };
+        }
+        if (Math.abs(value.lat) > 90 || Math.abs(value.lng) > 180) {
+            return { isValid: false, warning: 'Coordinates out of range' };
+        }
+        return { isValid: true };
+    }
+
+    if (field.field_type === 'currency') {
+        if (!isMoney(value)) {
+            return { isValid: false, warning: 'Invalid amount' };
+        }
+        if (field.options?.currency && value.currency !== field.options.currency) {
+            return { isValid: false, warning: `Not in ${field.options.currency}` };
+        }
+        return { isValid: true };
+    }
+
+    // JSON values are checked against their schema by the backend
+    if (field.field_type === 'json') {
+        return { isValid: true };
+    }
+
     // Relations are validated differently
     if (field.field_type === 'relation') {
-        if (!isRelationValue(value)) {
+        if (!field.options?.multiple) {
+            if (!isRelationValue(value)) {
+                return { isValid: false, warning: 'Invalid relation value' };
+            }
+            return { isValid: true };
+        }
+
+        if (!Array.isArray(value) && !isRelationValue(value)) {
             return { isValid: false, warning: 'Invalid relation value' };
         }
+        const relations = toRelationValues(value);
+        if (Array.isArray(value) && relations.length !== value.length) {
+            return { isValid: false, warning: 'Invalid relation value' };
+        }
+        const { minItems, maxItems } = field.options;
+        if (minItems !== undefined && relations.length < minItems) {
+            return { isValid: false, warning: `At least ${minItems} required` };
+        }
+        if (maxItems !== undefined && relations.length > maxItems) {
+            return { isValid: false, warning: `At most ${maxItems} allowed` };
+        }
         return { isValid: true };
     }
 
@@ -173,6 +840,7 @@ function validateFieldValue(
 
     switch (field.field_type) {
         case 'text':
+        case 'markdown':
             if (options?.maxLength && String(value).length > options.maxLength) {
                 return {
                     isValid: false,
@@ -195,13 +863,75 @@ function validateFieldValue(
             break;
 
         case 'select':
-            if (options?.choices && !options.choices.includes(String(value))) {
+            if (options?.choices && !findChoice(options, String(value))) {
                 return {
                     isValid: false,
                     warning: `'${value}' is not a valid choice`,
                 };
             }
             break;
+
+        case 'rating': {
+            const rating = Number(value);
+            const ratingMax = options?.max ?? DEFAULT_RATING_MAX;
+            const step = options?.step ?? 1;
+            if (typeof value !== 'number' || rating < 0 || rating > ratingMax) {
+                return { isValid: false, warning: `Not a rating from 0 to ${ratingMax}` };
+            }
+            if (Math.abs(rating / step - Math.round(rating / step)) > 1e-9) {
+                return { isValid: false, warning: `Not a multiple of ${step}` };
+            }
+            break;
+        }
+
+        case 'partialdate':
+            if (!/^\d{4}(-\d{2}(-\d{2})?)?$/.test(String(value))) {
+                return { isValid: false, warning: 'Not a valid partial date' };
+            }
+            break;
+
+        case 'datetime':
+            if (isNaN(new Date(String(value)).getTime())) {
+                return { isValid: false, warning: 'Not a valid date and time' };
+            }
+            break;
+
+        case 'duration':
+            if (typeof value !== 'number' || value < 0) {
+                return { isValid: false, warning: 'Not a valid duration' };
+            }
+            break;
+
+        case 'email':
+            if (!/^[^\s@]+@[^\s@]+$/.test(String(value))) {
+                return { isValid: false, warning: 'Not a valid email address' };
+            }
+            break;
+
+        case 'phone':
+            if (!/^\+[1-9]\d{6,14}$/.test(String(value))) {
+                return { isValid: false, warning: 'Not an international phone number' };
+            }
+            break;
+
+        case 'color':
+            if (!/^#([0-9a-f]{6}|[0-9a-f]{8})$/.test(String(value))) {
+                return { isValid: false, warning: 'Not a valid color' };
+            }
+            break;
+
+        case 'multiselect': {
+            if (!Array.isArray(value) || value.some((v) => typeof v !== 'string')) {
+                return { isValid: false, warning: 'Invalid choice list' };
+            }
+            const invalid = toChoiceValues(value).find(
+                (choice) => options?.choices && !findChoice(options, choice)
+            );
+            if (invalid !== undefined) {
+                return { isValid: false, warning: `'${invalid}' is not a valid choice` };
+            }
+            break;
+        }
     }
 
     return { isValid: true };
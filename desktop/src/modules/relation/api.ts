@@ -2,7 +2,11 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import type {
+    BacklinkGroup,
     EntryPickerItem,
+    GraphFilter,
+    RelationGraph,
+    RelationIndexReport,
     RelationRef,
     ResolvedRelation,
 } from './types';
@@ -13,16 +17,19 @@ import type {
 export const relationApi = {
     /**
      * Searches entries in a vault for the relation picker.
+     * Entries in excludeIds (e.g. already selected) are left out.
      */
     async searchEntriesForPicker(
         vaultId: number,
         query: string,
-        limit?: number
+        limit?: number,
+        excludeIds?: number[]
     ): Promise<EntryPickerItem[]> {
         return invoke<EntryPickerItem[]>('search_entries_for_relation', {
             vaultId,
             query,
             limit: limit ?? 20,
+            excludeIds: excludeIds ?? null,
         });
     },
 
@@ -38,6 +45,46 @@ export const relationApi = {
         });
     },
 
+    /**
+     * Lists entries that reference an entry, grouped by source vault and field.
+     */
+    async getBacklinks(entryId: number): Promise<BacklinkGroup[]> {
+        return invoke<BacklinkGroup[]>('get_backlinks', { entryId });
+    },
+
+    /**
+     * Rebuilds the relation index from entry metadata.
+     */
+    async rebuildIndex(): Promise<RelationIndexReport> {
+        return invoke<RelationIndexReport>('rebuild_relation_index');
+    },
+
+    /**
+     * Returns the entries within a number of hops of an entry.
+     */
+    async getNeighborhood(entryId: number, filter?: GraphFilter): Promise<RelationGraph> {
+        return invoke<RelationGraph>('get_relation_neighborhood', {
+            entryId,
+            filter: filter ?? null,
+        });
+    },
+
+    /**
+     * Finds a shortest chain of relations between two entries.
+     * Returns null when they are not connected within the depth limit.
+     */
+    async findPath(
+        fromEntryId: number,
+        toEntryId: number,
+        filter?: GraphFilter
+    ): Promise<RelationGraph | null> {
+        return invoke<RelationGraph | null>('find_relation_path', {
+            fromEntryId,
+            toEntryId,
+            filter: filter ?? null,
+        });
+    },
+
     /**
      * Creates a key for looking up resolved relations.
      */
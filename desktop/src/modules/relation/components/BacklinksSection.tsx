@@ -0,0 +1,84 @@
+// Backlinks Section - Entries that reference the current entry
+
+import { useEffect, useState } from 'react';
+import { Skeleton } from '@/components/ui/skeleton';
+import { FileText } from 'lucide-react';
+import { relationApi } from '../api';
+import type { BacklinkGroup } from '../types';
+
+interface BacklinksSectionProps {
+    entryId: number;
+    onNavigate?: (entryId: number, vaultId: number) => void;
+}
+
+export function BacklinksSection({ entryId, onNavigate }: BacklinksSectionProps) {
+    const [groups, setGroups] = useState<BacklinkGroup[]>([]);
+    const [isLoading, setIsLoading] = useState(true);
+
+    useEffect(() => {
+        let cancelled = false;
+        setIsLoading(true);
+        relationApi
+            .getBacklinks(entryId)
+            .then((result) => {
+                if (!cancelled) setGroups(result);
+            })
+            .catch((err) => {
+                console.error('Failed to load backlinks:', err);
+                if (!cancelled) setGroups([]);
+            })
+            .finally(() => {
+                if (!cancelled) setIsLoading(false);
+            });
+        return () => {
+            cancelled = true;
+        };
+    }, [entryId]);
+
+    if (isLoading) {
+        return <Skeleton className="h-5 w-40" />;
+    }
+
+    if (groups.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="space-y-4">
+            <h4 className="text-sm font-medium text-muted-foreground">
+                Referenced By
+            </h4>
+            <div className="grid gap-3">
+                {groups.map((group) => (
+                    <div key={group.field_id} className="space-y-1">
+                        <span className="text-xs text-muted-foreground">
+                            {group.vault_name} · {group.field_name}
+                        </span>
+                        <div className="flex flex-col gap-1">
+                            {group.entries.map((entry) => (
+                                <button
+                                    key={entry.entry_id}
+                                    type="button"
+                                    onClick={() => onNavigate?.(entry.entry_id, group.vault_id)}
+                                    disabled={!onNavigate}
+                                    className="flex items-center gap-2 text-sm text-left truncate enabled:hover:underline"
+                                >
+                                    {entry.cover_image_path ? (
+                                        <img
+                                            src={entry.cover_image_path}
+                                            alt=""
+                                            className="h-5 w-5 rounded object-cover"
+                                        />
+                                    ) : (
+                                        <FileText className="h-4 w-4 text-muted-foreground" />
+                                    )}
+                                    <span className="truncate">{entry.title}</span>
+                                </button>
+                            ))}
+                        </div>
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+}
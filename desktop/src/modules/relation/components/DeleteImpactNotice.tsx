@@ -0,0 +1,89 @@
+// Delete Impact Notice - Summarizes how a deletion affects related entries
+
+import { useEffect, useState } from 'react';
+import { AlertTriangle, Ban } from 'lucide-react';
+import type { DeleteImpact } from '../types';
+
+interface DeleteImpactNoticeProps {
+    // Loads the impact report; keep the reference stable between renders
+    load: () => Promise<DeleteImpact>;
+    onLoaded?: (impact: DeleteImpact) => void;
+}
+
+export function DeleteImpactNotice({ load, onLoaded }: DeleteImpactNoticeProps) {
+    const [impact, setImpact] = useState<DeleteImpact | null>(null);
+
+    useEffect(() => {
+        let cancelled = false;
+        load()
+            .then((result) => {
+                if (cancelled) return;
+                setImpact(result);
+                onLoaded?.(result);
+            })
+            .catch((err) => console.error('Failed to load delete impact:', err));
+        return () => {
+            cancelled = true;
+        };
+    }, [load, onLoaded]);
+
+    if (!impact) {
+        return null;
+    }
+
+    const blocking = impact.references.filter((r) => r.action === 'restrict');
+    const cleared = impact.references.filter((r) => r.action === 'set-null').length;
+    const cascaded = impact.references.filter((r) => r.action === 'cascade').length;
+    const emptied = impact.references.filter((r) => r.empties_required);
+
+    if (blocking.length > 0) {
+        return (
+            <div className="flex gap-2 rounded-md bg-destructive/10 p-3 text-sm text-destructive">
+                <Ban className="h-4 w-4 shrink-0 mt-0.5" />
+                <div className="space-y-1">
+                    <p>Cannot delete while these entries still reference it:</p>
+                    <ul className="list-disc pl-4">
+                        {blocking.map((r) => (
+                            <li key={`${r.source_entry_id}:${r.field_id}:${r.target_entry_id}`}>
+                                {r.source_title} ({r.field_name})
+                            </li>
+                        ))}
+                    </ul>
+                </div>
+            </div>
+        );
+    }
+
+    if (cleared === 0 && cascaded === 0 && impact.fields_removed.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="flex gap-2 rounded-md bg-muted p-3 text-sm text-muted-foreground">
+            <AlertTriangle className="h-4 w-4 shrink-0 mt-0.5" />
+            <ul className="space-y-1">
+                {cleared > 0 && (
+                    <li>{cleared} reference(s) in other entries will be cleared.</li>
+                )}
+                {emptied.length > 0 && (
+                    <li>
+                        These required fields will be left empty:{' '}
+                        {emptied.map((r) => `${r.source_title} (${r.field_name})`).join(', ')}.
+                    </li>
+                )}
+                {cascaded > 0 && (
+                    <li>
+                        {cascaded} referencing entr{cascaded === 1 ? 'y' : 'ies'} will also be
+                        deleted ({impact.entries_deleted} entries in total).
+                    </li>
+                )}
+                {impact.fields_removed.length > 0 && (
+                    <li>
+                        Relation fields linking here will be removed:{' '}
+                        {impact.fields_removed.map((f) => f.field_name).join(', ')}.
+                    </li>
+                )}
+            </ul>
+        </div>
+    );
+}
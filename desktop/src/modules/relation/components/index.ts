@@ -0,0 +1,4 @@
+// Relation components exports
+
+export * from './BacklinksSection';
+export * from './DeleteImpactNotice';
@@ -3,6 +3,8 @@
 // This module contains all relation-related functionality:
 // - API calls for cross-vault references
 // - Type definitions
+// - Backlinks display
 
 export * from './api';
 export * from './types';
+export * from './components';
@@ -24,3 +24,88 @@ export interface RelationRef {
     entry_id: number;
     vault_id: number;
 }
+
+// Entry referencing another entry through a relation field
+export interface BacklinkEntry {
+    entry_id: number;
+    title: string;
+    cover_image_path?: string;
+}
+
+// Incoming references grouped by source vault and field
+export interface BacklinkGroup {
+    vault_id: number;
+    vault_name: string;
+    field_id: number;
+    field_name: string;
+    entries: BacklinkEntry[];
+}
+
+// Result of rebuilding the relation index
+export interface RelationIndexReport {
+    entries_scanned: number;
+    relations_indexed: number;
+}
+
+// On-delete policy of a relation field
+export type RelationDeleteAction = 'restrict' | 'set-null' | 'cascade';
+
+// Relation value affected by a pending deletion
+export interface AffectedReference {
+    source_entry_id: number;
+    source_title: string;
+    source_vault_id: number;
+    field_id: number;
+    field_name: string;
+    target_entry_id: number;
+    action: RelationDeleteAction;
+    // Clearing the reference leaves a required field empty
+    empties_required: boolean;
+}
+
+// Relation field removed because its target vault is deleted
+export interface AffectedField {
+    field_id: number;
+    vault_id: number;
+    field_name: string;
+}
+
+// Pre-delete report of what a deletion would do to related entries
+export interface DeleteImpact {
+    can_delete: boolean;
+    entries_deleted: number;
+    references: AffectedReference[];
+    fields_removed: AffectedField[];
+}
+
+// Filters for relation graph queries
+export interface GraphFilter {
+    depth?: number;
+    vaultIds?: number[];
+    fieldIds?: number[];
+    maxNodes?: number;
+}
+
+// Entry in a relation graph
+export interface GraphNode {
+    entry_id: number;
+    vault_id: number;
+    title: string;
+    cover_image_path?: string;
+    depth: number;
+}
+
+// Relation between two entries, from the entry holding the value
+export interface GraphEdge {
+    source_entry_id: number;
+    target_entry_id: number;
+    field_id: number;
+    field_name: string;
+}
+
+// Nodes and edges returned by graph queries
+export interface RelationGraph {
+    nodes: GraphNode[];
+    edges: GraphEdge[];
+    truncated: boolean;
+}
@@ -1,6 +1,7 @@
 // Vault API - Type-safe Tauri command wrappers
 
 import { invoke } from '@tauri-apps/api/core';
+import type { DeleteImpact } from '@/modules/relation';
 import type { Vault, CreateVaultParams, UpdateVaultParams } from './types';
 
 export const vaultApi = {
@@ -44,9 +45,17 @@ export const vaultApi = {
     },
 
     /**
-     * Deletes a vault.
+     * Reports what deleting a vault would do to entries and fields in other vaults.
+     */
+    async getDeleteImpact(id: number): Promise<DeleteImpact> {
+        return invoke<DeleteImpact>('get_vault_delete_impact', { id });
+    },
+
+    /**
+     * Deletes a vault, its entries and their cover images.
      */
     async delete(id: number): Promise<void> {
-        return invoke<void>('delete_vault', { id });
+        const { appDataDir } = await import('@tauri-apps/api/path');
+        return invoke<void>('delete_vault', { id, appDataDir: await appDataDir() });
     },
 };